@@ -0,0 +1,135 @@
+/// Type of large-scale storage tables whose entries can be iterated.
+///
+/// Keys are iterated in ascending order of their BCS serialization. This is the numeric order
+/// for `u8` keys, but not for larger integers, which are serialized in little endian.
+module extensions::iterable_table {
+    use std::errors;
+    use std::option::{Self, Option};
+    use std::vector;
+
+    // native code raises this with Errors::invalid_arguments()
+    const EALREADY_EXISTS: u64 = 100;
+    // native code raises this with Errors::invalid_arguments()
+    const ENOT_FOUND: u64 = 101;
+    const ENOT_EMPTY: u64 = 102;
+
+    /// Type of iterable tables. The number of entries is maintained by the native implementation.
+    struct IterableTable<phantom K: copy + drop, phantom V> has store {
+        handle: address,
+    }
+
+    /// Create a new IterableTable.
+    public fun new<K: copy + drop, V: store>(): IterableTable<K, V> {
+        IterableTable{
+            handle: new_table_handle<K, V>(),
+        }
+    }
+
+    /// Destroy a table. The table must be empty to succeed.
+    public fun destroy_empty<K: copy + drop, V>(table: IterableTable<K, V>) {
+        assert!(length(&table) == 0, errors::invalid_state(ENOT_EMPTY));
+        destroy_empty_box<K, V, Box<V>>(&table);
+        drop_unchecked_box<K, V, Box<V>>(table)
+    }
+
+    /// Add a new entry to the table. Aborts if an entry for this
+    /// key already exists.
+    public fun add<K: copy + drop, V>(table: &mut IterableTable<K, V>, key: K, val: V) {
+        add_box<K, V, Box<V>>(table, key, Box{val})
+    }
+
+    /// Acquire an immutable reference to the value which `key` maps to.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow<K: copy + drop, V>(table: &IterableTable<K, V>, key: K): &V {
+        &borrow_box<K, V, Box<V>>(table, key).val
+    }
+
+    /// Acquire a mutable reference to the value which `key` maps to.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow_mut<K: copy + drop, V>(table: &mut IterableTable<K, V>, key: K): &mut V {
+        &mut borrow_box_mut<K, V, Box<V>>(table, key).val
+    }
+
+    /// Returns the length of the table, i.e. the number of entries.
+    public fun length<K: copy + drop, V>(table: &IterableTable<K, V>): u64 {
+        length_box<K, V, Box<V>>(table)
+    }
+
+    /// Returns true if this table is empty.
+    public fun empty<K: copy + drop, V>(table: &IterableTable<K, V>): bool {
+        length(table) == 0
+    }
+
+    /// Acquire a mutable reference to the value which `key` maps to.
+    /// Insert the pair (`key`, `default`) first if there is no entry for `key`.
+    public fun borrow_mut_with_default<K: copy + drop, V: drop>(
+        table: &mut IterableTable<K, V>,
+        key: K,
+        default: V
+    ): &mut V {
+        if (!contains(table, copy key)) {
+            add(table, copy key, default)
+        };
+        borrow_mut(table, key)
+    }
+
+    /// Remove from `table` and return the value which `key` maps to.
+    /// Aborts if there is no entry for `key`.
+    public fun remove<K: copy + drop, V>(table: &mut IterableTable<K, V>, key: K): V {
+        let Box{val} = remove_box<K, V, Box<V>>(table, key);
+        val
+    }
+
+    /// Returns true iff `table` contains an entry for `key`.
+    public fun contains<K: copy + drop, V>(table: &IterableTable<K, V>, key: K): bool {
+        contains_box<K, V, Box<V>>(table, key)
+    }
+
+    /// Returns the first key of `table`, or none if `table` is empty.
+    public fun first_key<K: copy + drop, V>(table: &IterableTable<K, V>): Option<K> {
+        to_option(first_key_box<K, V, Box<V>>(table))
+    }
+
+    /// Returns the key following `key` in `table`, or none if there is none. `key` is a cursor
+    /// which does not need to have an entry, so entries can be removed while iterating.
+    public fun next_key<K: copy + drop, V>(table: &IterableTable<K, V>, key: K): Option<K> {
+        to_option(next_key_box<K, V, Box<V>>(table, key))
+    }
+
+    #[test_only]
+    /// Testing only: allows to drop a table even if it is not empty.
+    public fun drop_unchecked<K: copy + drop, V>(table: IterableTable<K, V>) {
+        drop_unchecked_box<K, V, Box<V>>(table)
+    }
+
+    // ======================================================================================================
+    // Internal API
+
+    /// Wrapper for values. Required for making values appear as resources in the implementation.
+    struct Box<V> has key, drop, store {
+        val: V
+    }
+
+    /// Converts the result of `first_key_box` and `next_key_box`, which is empty or has one key.
+    fun to_option<K: drop>(keys: vector<K>): Option<K> {
+        if (vector::is_empty(&keys)) {
+            option::none()
+        } else {
+            option::some(vector::pop_back(&mut keys))
+        }
+    }
+
+    // Primitives which take as an additional type parameter `Box<V>`, so the implementation
+    // can use this to determine serialization layout.
+    native fun new_table_handle<K, V>(): address;
+    native fun add_box<K: copy + drop, V, B>(table: &mut IterableTable<K, V>, key: K, val: Box<V>);
+    native fun borrow_box<K: copy + drop, V, B>(table: &IterableTable<K, V>, key: K): &Box<V>;
+    native fun borrow_box_mut<K: copy + drop, V, B>(table: &mut IterableTable<K, V>, key: K): &mut Box<V>;
+    native fun contains_box<K: copy + drop, V, B>(table: &IterableTable<K, V>, key: K): bool;
+    native fun remove_box<K: copy + drop, V, B>(table: &mut IterableTable<K, V>, key: K): Box<V>;
+    native fun destroy_empty_box<K: copy + drop, V, B>(table: &IterableTable<K, V>);
+    native fun drop_unchecked_box<K: copy + drop, V, B>(table: IterableTable<K, V>);
+    native fun length_box<K: copy + drop, V, B>(table: &IterableTable<K, V>): u64;
+    native fun first_key_box<K: copy + drop, V, B>(table: &IterableTable<K, V>): vector<K>;
+    native fun next_key_box<K: copy + drop, V, B>(table: &IterableTable<K, V>, key: K): vector<K>;
+}
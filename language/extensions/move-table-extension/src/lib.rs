@@ -4,7 +4,8 @@
 
 //! A crate which extends Move by tables.
 //!
-//! See [`Table.move`](../sources/Table.move) for language use.
+//! See [`Table.move`](../sources/Table.move) and [`IterableTable.move`](../sources/IterableTable.move)
+//! for language use.
 //! See [`README.md`](../README.md) for integration into an adapter.
 
 use better_any::{Tid, TidAble};
@@ -25,14 +26,16 @@ use move_vm_types::{
     loaded_data::runtime_types::Type,
     natives::function::NativeResult,
     pop_arg,
-    values::{GlobalValue, Reference, StructRef, Value},
+    values::{GlobalValue, Reference, StructRef, Value, Vector},
 };
+use serde::{Deserialize, Serialize};
 use sha3::{Digest, Sha3_256};
 use smallvec::smallvec;
 use std::{
     cell::RefCell,
     collections::{btree_map::Entry, BTreeMap, BTreeSet, VecDeque},
     fmt::Display,
+    ops::Bound,
     sync::Arc,
 };
 
@@ -72,7 +75,8 @@ impl Display for TableInfo {
     }
 }
 
-/// A table change set.
+/// A table change set. Iterable tables share the representation of tables, and their changes are
+/// reported the same way.
 #[derive(Default)]
 pub struct TableChangeSet {
     pub new_tables: BTreeMap<TableHandle, TableInfo>,
@@ -85,6 +89,18 @@ pub struct TableChange {
     pub entries: BTreeMap<Vec<u8>, Op<Vec<u8>>>,
 }
 
+/// The table data read from remote storage.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TableReadSet {
+    /// The entries read, with the bytes read, `None` for entries which did not exist.
+    pub items: BTreeMap<TableHandle, BTreeMap<Vec<u8>, Option<Vec<u8>>>>,
+    /// The lengths of the tables read.
+    pub lengths: BTreeMap<TableHandle, u64>,
+    /// The keys read by iterating a table, each mapped from the key it follows (`None` for the
+    /// first key) to the key found, `None` if there was no further key.
+    pub next_keys: BTreeMap<TableHandle, BTreeMap<Option<Vec<u8>>, Option<Vec<u8>>>>,
+}
+
 /// A table resolver which needs to be provided by the environment. This allows to lookup
 /// data in remote storage, as well as retrieve cost of table operations.
 pub trait TableResolver {
@@ -93,6 +109,27 @@ pub trait TableResolver {
         handle: &TableHandle,
         key: &[u8],
     ) -> Result<Option<Vec<u8>>, anyhow::Error>;
+
+    /// Returns the number of entries of a table. This is only needed for iterable tables.
+    fn resolve_table_length(&self, _handle: &TableHandle) -> Result<u64, anyhow::Error> {
+        Err(anyhow::anyhow!(
+            "table lengths are not supported by this resolver"
+        ))
+    }
+
+    /// Returns at most `limit` keys of a table in ascending order of their serialized bytes,
+    /// starting after `after`, or with the first key if `after` is `None`. This is only needed
+    /// for iterable tables.
+    fn resolve_table_keys(
+        &self,
+        _handle: &TableHandle,
+        _after: Option<&[u8]>,
+        _limit: usize,
+    ) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+        Err(anyhow::anyhow!(
+            "table range scans are not supported by this resolver"
+        ))
+    }
 }
 
 /// The native table context extension. This needs to be attached to the NativeContextExtensions
@@ -103,6 +140,7 @@ pub struct NativeTableContext<'a> {
     resolver: &'a dyn TableResolver,
     txn_hash: [u8; 32],
     table_data: RefCell<TableData>,
+    reads: Option<RefCell<TableReadSet>>,
 }
 
 // See stdlib/Error.move
@@ -132,6 +170,12 @@ struct Table {
     key_layout: MoveTypeLayout,
     value_layout: MoveTypeLayout,
     content: BTreeMap<Vec<u8>, GlobalValue>,
+    /// Whether the table was created in this session, so it has no entries in remote storage.
+    is_new: bool,
+    /// The number of entries in remote storage, once resolved.
+    remote_length: Option<u64>,
+    /// The number of entries added minus the number of entries removed in this session.
+    length_delta: i64,
 }
 
 /// The field index of the `handle` field in the `Table` Move struct.
@@ -148,12 +192,27 @@ impl<'a> NativeTableContext<'a> {
             resolver,
             txn_hash,
             table_data: Default::default(),
+            reads: None,
         }
     }
 
+    /// Start capturing the table entries read from remote storage, to be returned by
+    /// `into_change_set_and_read_set`.
+    pub fn capture_read_set(&mut self) {
+        self.reads = Some(Default::default());
+    }
+
     /// Computes the change set from a NativeTableContext.
     pub fn into_change_set(self) -> PartialVMResult<TableChangeSet> {
-        let NativeTableContext { table_data, .. } = self;
+        Ok(self.into_change_set_and_read_set()?.0)
+    }
+
+    /// Computes the change set from a NativeTableContext, together with the table entries read
+    /// since `capture_read_set` was called, empty if it was not.
+    pub fn into_change_set_and_read_set(self) -> PartialVMResult<(TableChangeSet, TableReadSet)> {
+        let NativeTableContext {
+            table_data, reads, ..
+        } = self;
         let TableData {
             new_tables,
             removed_tables,
@@ -191,11 +250,15 @@ impl<'a> NativeTableContext<'a> {
                 changes.insert(handle, TableChange { entries });
             }
         }
-        Ok(TableChangeSet {
+        let change_set = TableChangeSet {
             new_tables,
             removed_tables,
             changes,
-        })
+        };
+        Ok((
+            change_set,
+            reads.map(RefCell::into_inner).unwrap_or_default(),
+        ))
     }
 }
 
@@ -218,6 +281,9 @@ impl TableData {
                     key_layout,
                     value_layout,
                     content: Default::default(),
+                    is_new: self.new_tables.contains_key(&handle),
+                    remote_length: None,
+                    length_delta: 0,
                 };
                 e.insert(table)
             }
@@ -234,12 +300,21 @@ impl Table {
     ) -> PartialVMResult<(&mut GlobalValue, Option<Option<NumBytes>>)> {
         Ok(match self.content.entry(key) {
             Entry::Vacant(entry) => {
-                let (gv, loaded) = match context
+                let val_bytes = context
                     .resolver
                     .resolve_table_entry(&self.handle, entry.key())
                     .map_err(|err| {
                         partial_extension_error(format!("remote table resolver failure: {}", err))
-                    })? {
+                    })?;
+                if let Some(reads) = &context.reads {
+                    reads
+                        .borrow_mut()
+                        .items
+                        .entry(self.handle)
+                        .or_default()
+                        .insert(entry.key().clone(), val_bytes.clone());
+                }
+                let (gv, loaded) = match val_bytes {
                     Some(val_bytes) => {
                         let val = deserialize(&self.value_layout, &val_bytes)?;
                         (
@@ -254,60 +329,189 @@ impl Table {
             Entry::Occupied(entry) => (entry.into_mut(), None),
         })
     }
+
+    /// Returns the number of entries of the table, and whether the number of entries in remote
+    /// storage had to be resolved for this.
+    fn length(&mut self, context: &NativeTableContext) -> PartialVMResult<(u64, bool)> {
+        let (remote_length, resolved) = match self.remote_length {
+            Some(length) => (length, false),
+            None if self.is_new => (0, false),
+            None => {
+                let length = context
+                    .resolver
+                    .resolve_table_length(&self.handle)
+                    .map_err(|err| {
+                        partial_extension_error(format!("remote table resolver failure: {}", err))
+                    })?;
+                if let Some(reads) = &context.reads {
+                    reads.borrow_mut().lengths.insert(self.handle, length);
+                }
+                self.remote_length = Some(length);
+                (length, true)
+            }
+        };
+        let length = remote_length as i64 + self.length_delta;
+        if length < 0 {
+            return Err(partial_extension_error(
+                "inconsistent table length in remote storage",
+            ));
+        }
+        Ok((length as u64, resolved))
+    }
+
+    /// Returns the smallest key with an entry in the table which is greater than `after`, or the
+    /// smallest key if `after` is `None`. Also returns the sizes of the keys resolved from remote
+    /// storage for this, `None` for a resolution which found no key.
+    fn next_key(
+        &self,
+        context: &NativeTableContext,
+        after: Option<Vec<u8>>,
+    ) -> PartialVMResult<(Option<Vec<u8>>, Vec<Option<NumBytes>>)> {
+        let lower = match &after {
+            Some(key) => Bound::Excluded(key.clone()),
+            None => Bound::Unbounded,
+        };
+        let mut local = None;
+        for (key, gv) in self.content.range::<Vec<u8>, _>((lower, Bound::Unbounded)) {
+            if gv.exists()? {
+                local = Some(key.clone());
+                break;
+            }
+        }
+        if self.is_new {
+            return Ok((local, vec![]));
+        }
+
+        // Entries in `content` supersede remote storage, so skip remote keys removed in this
+        // session. There is no need to look further than the next key found locally.
+        let mut resolved = vec![];
+        let mut cursor = after;
+        let remote = loop {
+            if let (Some(cursor), Some(local)) = (&cursor, &local) {
+                if cursor >= local {
+                    break None;
+                }
+            }
+            let key = self.resolve_next_key(context, cursor.as_deref())?;
+            resolved.push(key.as_ref().map(|key| NumBytes::new(key.len() as u64)));
+            match key {
+                Some(key) => match self.content.get(&key) {
+                    Some(gv) if !gv.exists()? => cursor = Some(key),
+                    _ => break Some(key),
+                },
+                None => break None,
+            }
+        };
+        let next = match (local, remote) {
+            (Some(local), Some(remote)) => Some(local.min(remote)),
+            (local, remote) => local.or(remote),
+        };
+        Ok((next, resolved))
+    }
+
+    fn resolve_next_key(
+        &self,
+        context: &NativeTableContext,
+        after: Option<&[u8]>,
+    ) -> PartialVMResult<Option<Vec<u8>>> {
+        let key = context
+            .resolver
+            .resolve_table_keys(&self.handle, after, 1)
+            .map_err(|err| {
+                partial_extension_error(format!("remote table resolver failure: {}", err))
+            })?
+            .into_iter()
+            .next();
+        if let Some(reads) = &context.reads {
+            reads
+                .borrow_mut()
+                .next_keys
+                .entry(self.handle)
+                .or_default()
+                .insert(after.map(<[u8]>::to_vec), key.clone());
+        }
+        Ok(key)
+    }
 }
 
 // =========================================================================================
 // Native Function Implementations
 
-/// Returns all natives for tables.
+/// Returns all natives for tables and iterable tables.
 pub fn table_natives(table_addr: AccountAddress, gas_params: GasParameters) -> NativeFunctionTable {
-    let natives: [(&str, &str, NativeFunction); 8] = [
+    let mut natives = box_natives("table", gas_params.clone());
+    natives.extend(box_natives("iterable_table", gas_params.clone()));
+    natives.extend([
+        (
+            "iterable_table",
+            "length_box",
+            make_native_length_box(gas_params.common.clone(), gas_params.length_box),
+        ),
+        (
+            "iterable_table",
+            "first_key_box",
+            make_native_next_key_box(gas_params.common.clone(), gas_params.next_key_box.clone()),
+        ),
+        (
+            "iterable_table",
+            "next_key_box",
+            make_native_next_key_box(gas_params.common, gas_params.next_key_box),
+        ),
+    ]);
+
+    native_functions::make_table_from_iter(table_addr, natives)
+}
+
+/// Returns the natives which both `table` and `iterable_table` declare, for `module`.
+fn box_natives(
+    module: &'static str,
+    gas_params: GasParameters,
+) -> Vec<(&'static str, &'static str, NativeFunction)> {
+    vec![
         (
-            "table",
+            module,
             "new_table_handle",
             make_native_new_table_handle(gas_params.new_table_handle),
         ),
         (
-            "table",
+            module,
             "add_box",
             make_native_add_box(gas_params.common.clone(), gas_params.add_box),
         ),
         (
-            "table",
+            module,
             "borrow_box",
             make_native_borrow_box(gas_params.common.clone(), gas_params.borrow_box.clone()),
         ),
         (
-            "table",
+            module,
             "borrow_box_mut",
             make_native_borrow_box(gas_params.common.clone(), gas_params.borrow_box),
         ),
         (
-            "table",
+            module,
             "remove_box",
             make_native_remove_box(gas_params.common.clone(), gas_params.remove_box),
         ),
         (
-            "table",
+            module,
             "contains_box",
             make_native_contains_box(gas_params.common, gas_params.contains_box),
         ),
         (
-            "table",
+            module,
             "destroy_empty_box",
             make_native_destroy_empty_box(gas_params.destroy_empty_box),
         ),
         (
-            "table",
+            module,
             "drop_unchecked_box",
             make_native_drop_unchecked_box(gas_params.drop_unchecked_box),
         ),
-    ];
-
-    native_functions::make_table_from_iter(table_addr, natives)
+    ]
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommonGasParameters {
     pub load_base: InternalGas,
     pub load_per_byte: InternalGasPerByte,
@@ -325,7 +529,7 @@ impl CommonGasParameters {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewTableHandleGasParameters {
     pub base: InternalGas,
 }
@@ -373,7 +577,7 @@ pub fn make_native_new_table_handle(gas_params: NewTableHandleGasParameters) ->
     )
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddBoxGasParameters {
     pub base: InternalGas,
     pub per_byte_serialized: InternalGasPerByte,
@@ -407,7 +611,10 @@ fn native_add_box(
     cost += common_gas_params.calculate_load_cost(loaded);
 
     match gv.move_to(val) {
-        Ok(_) => Ok(NativeResult::ok(cost, smallvec![])),
+        Ok(_) => {
+            table.length_delta += 1;
+            Ok(NativeResult::ok(cost, smallvec![]))
+        }
         Err(_) => Ok(NativeResult::err(cost, ALREADY_EXISTS)),
     }
 }
@@ -423,7 +630,7 @@ pub fn make_native_add_box(
     )
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BorrowBoxGasParameters {
     pub base: InternalGas,
     pub per_byte_serialized: InternalGasPerByte,
@@ -472,7 +679,7 @@ pub fn make_native_borrow_box(
     )
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContainsBoxGasParameters {
     pub base: InternalGas,
     pub per_byte_serialized: InternalGasPerByte,
@@ -520,7 +727,7 @@ pub fn make_native_contains_box(
     )
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemoveGasParameters {
     pub base: InternalGas,
     pub per_byte_serialized: InternalGasPerByte,
@@ -553,7 +760,10 @@ fn native_remove_box(
     cost += common_gas_params.calculate_load_cost(loaded);
 
     match gv.move_from() {
-        Ok(val) => Ok(NativeResult::ok(cost, smallvec![val])),
+        Ok(val) => {
+            table.length_delta -= 1;
+            Ok(NativeResult::ok(cost, smallvec![val]))
+        }
         Err(_) => Ok(NativeResult::err(cost, NOT_FOUND)),
     }
 }
@@ -569,7 +779,7 @@ pub fn make_native_remove_box(
     )
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DestroyEmptyBoxGasParameters {
     pub base: InternalGas,
 }
@@ -603,7 +813,7 @@ pub fn make_native_destroy_empty_box(gas_params: DestroyEmptyBoxGasParameters) -
     )
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DropUncheckedBoxGasParameters {
     pub base: InternalGas,
 }
@@ -628,7 +838,122 @@ pub fn make_native_drop_unchecked_box(gas_params: DropUncheckedBoxGasParameters)
     )
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LengthBoxGasParameters {
+    pub base: InternalGas,
+}
+
+fn native_length_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &LengthBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 3);
+    assert_eq!(args.len(), 1);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.borrow_mut();
+
+    let handle = get_table_handle(&pop_arg!(args, StructRef))?;
+
+    let table = table_data.get_or_create_table(context, handle, &ty_args[0], &ty_args[2])?;
+
+    let mut cost = gas_params.base;
+
+    let (length, resolved) = table.length(table_context)?;
+    if resolved {
+        cost += common_gas_params.load_base;
+    }
+
+    Ok(NativeResult::ok(cost, smallvec![Value::u64(length)]))
+}
+
+pub fn make_native_length_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: LengthBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_length_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NextKeyBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Implements both `first_key_box`, which only takes the table, and `next_key_box`, which also
+/// takes the key to continue after. The key found is returned in a vector, which is empty if
+/// there is none.
+fn native_next_key_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &NextKeyBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 3);
+    assert!(args.len() == 1 || args.len() == 2);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.borrow_mut();
+
+    let after = if args.len() == 2 {
+        args.pop_back()
+    } else {
+        None
+    };
+    let handle = get_table_handle(&pop_arg!(args, StructRef))?;
+
+    let table = table_data.get_or_create_table(context, handle, &ty_args[0], &ty_args[2])?;
+
+    let mut cost = gas_params.base;
+
+    let after = match after {
+        Some(key) => {
+            let key_bytes = serialize(&table.key_layout, &key)?;
+            cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+            Some(key_bytes)
+        }
+        None => None,
+    };
+
+    let (next, resolved) = table.next_key(table_context, after)?;
+    for loaded in resolved {
+        cost += common_gas_params.calculate_load_cost(Some(loaded));
+    }
+
+    let keys = match next {
+        Some(key_bytes) => {
+            cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+            vec![deserialize(&table.key_layout, &key_bytes)?]
+        }
+        None => vec![],
+    };
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Vector::pack(&ty_args[0], keys)?],
+    ))
+}
+
+pub fn make_native_next_key_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: NextKeyBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_next_key_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GasParameters {
     pub common: CommonGasParameters,
     pub new_table_handle: NewTableHandleGasParameters,
@@ -638,6 +963,8 @@ pub struct GasParameters {
     pub remove_box: RemoveGasParameters,
     pub destroy_empty_box: DestroyEmptyBoxGasParameters,
     pub drop_unchecked_box: DropUncheckedBoxGasParameters,
+    pub length_box: LengthBoxGasParameters,
+    pub next_key_box: NextKeyBoxGasParameters,
 }
 
 impl GasParameters {
@@ -667,6 +994,11 @@ impl GasParameters {
             },
             destroy_empty_box: DestroyEmptyBoxGasParameters { base: 0.into() },
             drop_unchecked_box: DropUncheckedBoxGasParameters { base: 0.into() },
+            length_box: LengthBoxGasParameters { base: 0.into() },
+            next_key_box: NextKeyBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
         }
     }
 }
@@ -702,3 +1034,258 @@ fn get_type_layout(context: &NativeContext, ty: &Type) -> PartialVMResult<MoveTy
         .type_to_type_layout(ty)?
         .ok_or_else(|| partial_extension_error("cannot determine type layout"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tables in remote storage, pre-populated by the tests. This stands in for `InMemoryStorage`
+    /// of move-vm-test-utils, which depends on this crate and so implements the `TableResolver`
+    /// of another instance of it in unit tests.
+    #[derive(Default)]
+    struct InMemoryStorage {
+        tables: BTreeMap<TableHandle, BTreeMap<Vec<u8>, Vec<u8>>>,
+    }
+
+    impl TableResolver for InMemoryStorage {
+        fn resolve_table_entry(
+            &self,
+            handle: &TableHandle,
+            key: &[u8],
+        ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+            Ok(self.tables.get(handle).and_then(|t| t.get(key).cloned()))
+        }
+
+        fn resolve_table_length(&self, handle: &TableHandle) -> Result<u64, anyhow::Error> {
+            Ok(self.tables.get(handle).map_or(0, |t| t.len() as u64))
+        }
+
+        fn resolve_table_keys(
+            &self,
+            handle: &TableHandle,
+            after: Option<&[u8]>,
+            limit: usize,
+        ) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+            let lower = match after {
+                Some(key) => Bound::Excluded(key),
+                None => Bound::Unbounded,
+            };
+            Ok(self.tables.get(handle).map_or_else(Vec::new, |t| {
+                t.range::<[u8], _>((lower, Bound::Unbounded))
+                    .take(limit)
+                    .map(|(key, _)| key.clone())
+                    .collect()
+            }))
+        }
+    }
+
+    const HANDLE: TableHandle = TableHandle(AccountAddress::ONE);
+
+    /// A storage with a `u8` to `u8` table of `keys`, each mapped to itself
+    fn storage(keys: &[u8]) -> InMemoryStorage {
+        let table = keys.iter().map(|key| (vec![*key], vec![*key])).collect();
+        InMemoryStorage {
+            tables: BTreeMap::from([(HANDLE, table)]),
+        }
+    }
+
+    fn table(is_new: bool) -> Table {
+        Table {
+            handle: HANDLE,
+            key_layout: MoveTypeLayout::U8,
+            value_layout: MoveTypeLayout::U8,
+            content: BTreeMap::new(),
+            is_new,
+            remote_length: None,
+            length_delta: 0,
+        }
+    }
+
+    /// Adds `key` to `table` the way `add_box` does
+    fn add(table: &mut Table, context: &NativeTableContext, key: u8) {
+        let (gv, _) = table
+            .get_or_create_global_value(context, vec![key])
+            .unwrap();
+        assert!(gv.move_to(Value::u8(key)).is_ok());
+        table.length_delta += 1;
+    }
+
+    /// Removes `key` from `table` the way `remove_box` does
+    fn remove(table: &mut Table, context: &NativeTableContext, key: u8) {
+        let (gv, _) = table
+            .get_or_create_global_value(context, vec![key])
+            .unwrap();
+        gv.move_from().unwrap();
+        table.length_delta -= 1;
+    }
+
+    /// The keys of `table`, iterated with `next_key`
+    fn keys(table: &Table, context: &NativeTableContext) -> Vec<u8> {
+        let mut keys = vec![];
+        let mut after = None;
+        while let (Some(key), _) = table.next_key(context, after).unwrap() {
+            keys.push(key[0]);
+            after = Some(key);
+        }
+        keys
+    }
+
+    #[test]
+    fn length_resolves_remote_length_once() {
+        let storage = storage(&[1, 3, 5]);
+        let mut context = NativeTableContext::new([0; 32], &storage);
+        context.capture_read_set();
+        let mut table = table(false);
+
+        assert_eq!(table.length(&context).unwrap(), (3, true));
+        assert_eq!(table.length(&context).unwrap(), (3, false));
+        let (_, reads) = context.into_change_set_and_read_set().unwrap();
+        assert_eq!(reads.lengths, BTreeMap::from([(HANDLE, 3)]));
+    }
+
+    #[test]
+    fn length_adds_local_delta() {
+        let storage = storage(&[1, 3, 5]);
+        let context = NativeTableContext::new([0; 32], &storage);
+        let mut table = table(false);
+
+        add(&mut table, &context, 2);
+        add(&mut table, &context, 4);
+        remove(&mut table, &context, 3);
+        assert_eq!(table.length_delta, 1);
+        assert_eq!(table.length(&context).unwrap(), (4, true));
+
+        remove(&mut table, &context, 1);
+        remove(&mut table, &context, 5);
+        assert_eq!(table.length(&context).unwrap(), (2, false));
+    }
+
+    #[test]
+    fn length_of_new_table_is_local() {
+        // The table is not in storage, as it was created in this session
+        let storage = InMemoryStorage::default();
+        let context = NativeTableContext::new([0; 32], &storage);
+        let mut table = table(true);
+        assert_eq!(table.length(&context).unwrap(), (0, false));
+
+        add(&mut table, &context, 7);
+        assert_eq!(table.length(&context).unwrap(), (1, false));
+    }
+
+    #[test]
+    fn length_inconsistent_with_remote_storage() {
+        let storage = storage(&[]);
+        let context = NativeTableContext::new([0; 32], &storage);
+        let mut table = table(false);
+        table.length_delta = -1;
+        assert!(table.length(&context).is_err());
+    }
+
+    #[test]
+    fn next_key_of_remote_keys() {
+        let storage = storage(&[1, 3, 5]);
+        let context = NativeTableContext::new([0; 32], &storage);
+        let table = table(false);
+        assert_eq!(keys(&table, &context), vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn next_key_interleaves_local_and_remote_keys() {
+        let storage = storage(&[1, 3, 5]);
+        let context = NativeTableContext::new([0; 32], &storage);
+        let mut table = table(false);
+        add(&mut table, &context, 0);
+        add(&mut table, &context, 4);
+        add(&mut table, &context, 6);
+        assert_eq!(keys(&table, &context), vec![0, 1, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn next_key_skips_keys_removed_locally() {
+        let storage = storage(&[1, 3, 5]);
+        let context = NativeTableContext::new([0; 32], &storage);
+        let mut table = table(false);
+        add(&mut table, &context, 2);
+        add(&mut table, &context, 6);
+        remove(&mut table, &context, 3);
+        assert_eq!(keys(&table, &context), vec![1, 2, 5, 6]);
+
+        remove(&mut table, &context, 1);
+        remove(&mut table, &context, 5);
+        assert_eq!(keys(&table, &context), vec![2, 6]);
+    }
+
+    #[test]
+    fn next_key_of_table_with_all_keys_removed() {
+        let storage = storage(&[1, 3]);
+        let context = NativeTableContext::new([0; 32], &storage);
+        let mut table = table(false);
+        remove(&mut table, &context, 1);
+        remove(&mut table, &context, 3);
+        assert_eq!(
+            table.next_key(&context, None).unwrap(),
+            (
+                None,
+                vec![Some(NumBytes::new(1)), Some(NumBytes::new(1)), None]
+            )
+        );
+    }
+
+    #[test]
+    fn next_key_stops_resolving_at_local_key() {
+        let storage = storage(&[1, 3, 5]);
+        let context = NativeTableContext::new([0; 32], &storage);
+        let mut table = table(false);
+        add(&mut table, &context, 2);
+        remove(&mut table, &context, 3);
+
+        // The remote key after 1 is removed, and the one after it is past the local key 2
+        let (next, resolved) = table.next_key(&context, Some(vec![1])).unwrap();
+        assert_eq!(next, Some(vec![2]));
+        assert_eq!(resolved, vec![Some(NumBytes::new(1))]);
+    }
+
+    #[test]
+    fn next_key_of_new_table_is_local() {
+        // The table is not in storage, as it was created in this session
+        let storage = InMemoryStorage::default();
+        let context = NativeTableContext::new([0; 32], &storage);
+        let mut table = table(true);
+        add(&mut table, &context, 4);
+        add(&mut table, &context, 2);
+        assert_eq!(keys(&table, &context), vec![2, 4]);
+    }
+
+    #[test]
+    fn resolve_next_key_records_reads() {
+        let storage = storage(&[1, 3]);
+        let mut context = NativeTableContext::new([0; 32], &storage);
+        context.capture_read_set();
+        let table = table(false);
+
+        assert_eq!(
+            table.resolve_next_key(&context, None).unwrap(),
+            Some(vec![1])
+        );
+        assert_eq!(
+            table.resolve_next_key(&context, Some(&[1][..])).unwrap(),
+            Some(vec![3])
+        );
+        assert_eq!(
+            table.resolve_next_key(&context, Some(&[3][..])).unwrap(),
+            None
+        );
+        let (_, reads) = context.into_change_set_and_read_set().unwrap();
+        assert_eq!(
+            reads.next_keys,
+            BTreeMap::from([(
+                HANDLE,
+                BTreeMap::from([
+                    (None, Some(vec![1])),
+                    (Some(vec![1]), Some(vec![3])),
+                    (Some(vec![3]), None),
+                ])
+            )])
+        );
+    }
+}
@@ -0,0 +1,72 @@
+#[test_only]
+module extensions::iterable_table_tests {
+    use std::option;
+    use extensions::iterable_table as T;
+
+    #[test]
+    fun length_after_add_and_remove() {
+        let t = T::new<u64, u64>();
+        assert!(T::empty(&t), 0);
+        T::add(&mut t, 1, 2);
+        T::add(&mut t, 10, 33);
+        assert!(T::length(&t) == 2, 1);
+        T::remove(&mut t, 1);
+        assert!(T::length(&t) == 1, 2);
+        *T::borrow_mut_with_default(&mut t, 3, 0) = 4;
+        assert!(T::length(&t) == 2, 3);
+        assert!(*T::borrow(&t, 3) == 4, 4);
+        T::remove(&mut t, 3);
+        T::remove(&mut t, 10);
+        T::destroy_empty(t)
+    }
+
+    #[test]
+    fun iterate_in_key_order() {
+        let t = T::new<u8, u64>();
+        T::add(&mut t, 30, 3);
+        T::add(&mut t, 10, 1);
+        T::add(&mut t, 20, 2);
+        let key = T::first_key(&t);
+        let sum = 0;
+        let expected = 10;
+        while (option::is_some(&key)) {
+            let k = option::extract(&mut key);
+            assert!(k == expected, 0);
+            sum = sum + *T::borrow(&t, k);
+            expected = expected + 10;
+            key = T::next_key(&t, k);
+        };
+        assert!(sum == 6, 1);
+        assert!(expected == 40, 2);
+        T::drop_unchecked(t)
+    }
+
+    #[test]
+    fun next_key_after_removed_key() {
+        let t = T::new<u8, u64>();
+        T::add(&mut t, 1, 1);
+        T::add(&mut t, 2, 2);
+        T::add(&mut t, 3, 3);
+        T::remove(&mut t, 2);
+        assert!(T::next_key(&t, 1) == option::some(3), 0);
+        assert!(T::next_key(&t, 2) == option::some(3), 1);
+        assert!(T::next_key(&t, 3) == option::none(), 2);
+        T::drop_unchecked(t)
+    }
+
+    #[test]
+    fun empty_table_has_no_keys() {
+        let t = T::new<u64, u64>();
+        assert!(option::is_none(&T::first_key(&t)), 0);
+        assert!(option::is_none(&T::next_key(&t, 0)), 1);
+        T::destroy_empty(t)
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 26113, location = extensions::iterable_table)]
+    fun destroy_non_empty() {
+        let t = T::new<u64, u64>();
+        T::add(&mut t, 1, 1);
+        T::destroy_empty(t)
+    }
+}
@@ -5,12 +5,12 @@
 use move_cli::base::test::{run_move_unit_tests, UnitTestResult};
 use move_core_types::account_address::AccountAddress;
 use move_table_extension::{table_natives, GasParameters};
-use move_unit_test::UnitTestingConfig;
+use move_unit_test::{gas_schedule::GasSchedule, UnitTestingConfig};
+use move_vm_runtime::native_functions::NativeFunctionTable;
 use std::path::PathBuf;
 use tempfile::tempdir;
 
-fn run_tests_for_pkg(path_to_pkg: impl Into<String>) {
-    let pkg_path = path_in_crate(path_to_pkg);
+fn natives() -> NativeFunctionTable {
     let mut natives = move_stdlib::natives::all_natives(
         AccountAddress::from_hex_literal("0x1").unwrap(),
         move_stdlib::natives::GasParameters::zeros(),
@@ -19,15 +19,24 @@ fn run_tests_for_pkg(path_to_pkg: impl Into<String>) {
         AccountAddress::from_hex_literal("0x2").unwrap(),
         GasParameters::zeros(),
     ));
+    natives
+}
+
+fn build_config() -> move_package::BuildConfig {
+    move_package::BuildConfig {
+        test_mode: true,
+        install_dir: Some(tempdir().unwrap().path().to_path_buf()),
+        ..Default::default()
+    }
+}
+
+fn run_tests_for_pkg(path_to_pkg: impl Into<String>) {
+    let pkg_path = path_in_crate(path_to_pkg);
     let res = run_move_unit_tests(
         &pkg_path,
-        move_package::BuildConfig {
-            test_mode: true,
-            install_dir: Some(tempdir().unwrap().path().to_path_buf()),
-            ..Default::default()
-        },
+        build_config(),
         UnitTestingConfig::default_with_bound(Some(100_000)),
-        natives,
+        natives(),
         None,
         /* compute_coverage */ false,
         &mut std::io::stdout(),
@@ -38,11 +47,49 @@ fn run_tests_for_pkg(path_to_pkg: impl Into<String>) {
     }
 }
 
+/// Runs the test `name` of the package with `schedule`, and returns the gas it used
+fn gas_used(schedule: GasSchedule, name: &str) -> u64 {
+    let mut config = UnitTestingConfig::default_with_bound(Some(1_000_000));
+    config.filter = Some(name.to_string());
+    config.report_statistics = true;
+    config.gas_schedule = Some(schedule);
+    let mut output = vec![];
+    let res = run_move_unit_tests(
+        &path_in_crate("."),
+        build_config(),
+        config,
+        natives(),
+        None,
+        /* compute_coverage */ false,
+        &mut output,
+    )
+    .unwrap();
+    assert_eq!(res, UnitTestResult::Success);
+    // The row of the test in the statistics is `│ <name> │ <time> │ <gas used> │`
+    let output = String::from_utf8(output).unwrap();
+    let row = output
+        .lines()
+        .find(|line| line.starts_with('│') && line.contains(name))
+        .unwrap();
+    row.split('│').nth(3).unwrap().trim().parse().unwrap()
+}
+
 #[test]
 fn move_unit_tests() {
     run_tests_for_pkg(".");
 }
 
+#[test]
+fn table_costs_from_gas_schedule() {
+    let schedule = GasSchedule::initial();
+    let mut expensive = schedule.clone();
+    // A unit of gas is 1000 units of internal gas
+    expensive.natives.table.add_box.base = 1_000_000.into();
+    let gas = gas_used(schedule, "simple_read_write");
+    // The test adds two entries
+    assert_eq!(gas_used(expensive, "simple_read_write"), gas + 2000);
+}
+
 pub fn path_in_crate<S>(relative: S) -> PathBuf
 where
     S: Into<String>,
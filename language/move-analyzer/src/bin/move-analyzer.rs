@@ -7,9 +7,12 @@ use clap::Parser;
 use crossbeam::channel::{bounded, select};
 use lsp_server::{Connection, Message, Notification, Request, Response};
 use lsp_types::{
-    notification::Notification as _, request::Request as _, CompletionOptions, Diagnostic,
-    HoverProviderCapability, OneOf, SaveOptions, TextDocumentSyncCapability, TextDocumentSyncKind,
-    TextDocumentSyncOptions, TypeDefinitionProviderCapability, WorkDoneProgressOptions,
+    notification::Notification as _, request::Request as _, CodeActionKind, CodeActionOptions,
+    CodeActionProviderCapability, CompletionOptions, Diagnostic, HoverProviderCapability, OneOf,
+    SaveOptions, SemanticTokensFullOptions, SemanticTokensOptions,
+    SemanticTokensServerCapabilities, SignatureHelpOptions, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextDocumentSyncOptions, TypeDefinitionProviderCapability,
+    WorkDoneProgressOptions,
 };
 use std::{
     collections::BTreeMap,
@@ -19,6 +22,7 @@ use std::{
 };
 
 use move_analyzer::{
+    code_action::on_code_action_request,
     completion::on_completion_request,
     context::Context,
     symbols,
@@ -61,7 +65,7 @@ fn main() {
         .initialize_start()
         .expect("could not start connection initialization");
 
-    let capabilities = serde_json::to_value(lsp_types::ServerCapabilities {
+    let mut capabilities = serde_json::to_value(lsp_types::ServerCapabilities {
         // The server receives notifications from the client as users open, close,
         // and modify documents.
         text_document_sync: Some(TextDocumentSyncCapability::Options(
@@ -107,9 +111,38 @@ fn main() {
         )),
         references_provider: Some(OneOf::Left(symbols::DEFS_AND_REFS_SUPPORT)),
         document_symbol_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Left(symbols::DEFS_AND_REFS_SUPPORT)),
+        // Signature help is shown when the argument list of a call is opened, and updated as the
+        // arguments are separated
+        signature_help_provider: Some(SignatureHelpOptions {
+            trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+            retrigger_characters: None,
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        }),
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+            resolve_provider: None,
+        })),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                work_done_progress_options: WorkDoneProgressOptions {
+                    work_done_progress: None,
+                },
+                legend: symbols::semantic_tokens_legend(),
+                range: None,
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+            },
+        )),
         ..Default::default()
     })
     .expect("could not serialize server capabilities");
+    // The version of lsp-types in use has no field for the inlay hint capability
+    capabilities["inlayHintProvider"] = serde_json::json!(true);
 
     let (diag_sender, diag_receiver) = bounded::<Result<BTreeMap<Symbol, Vec<Diagnostic>>>>(0);
     let mut symbolicator_runner = symbols::SymbolicatorRunner::idle();
@@ -241,6 +274,21 @@ fn on_request(context: &Context, request: &Request) {
         lsp_types::request::DocumentSymbolRequest::METHOD => {
             symbols::on_document_symbol_request(context, request, &context.symbols.lock().unwrap());
         }
+        lsp_types::request::Rename::METHOD => {
+            symbols::on_rename_request(context, request, &context.symbols.lock().unwrap());
+        }
+        lsp_types::request::SignatureHelpRequest::METHOD => {
+            symbols::on_signature_help_request(context, request, &context.symbols.lock().unwrap());
+        }
+        lsp_types::request::CodeActionRequest::METHOD => {
+            on_code_action_request(context, request, &context.symbols.lock().unwrap());
+        }
+        lsp_types::request::SemanticTokensFullRequest::METHOD => {
+            symbols::on_semantic_tokens_request(context, request, &context.symbols.lock().unwrap());
+        }
+        symbols::InlayHintRequest::METHOD => {
+            symbols::on_inlay_hint_request(context, request, &context.symbols.lock().unwrap());
+        }
         _ => eprintln!("handle request '{}' from client", request.method),
     }
 }
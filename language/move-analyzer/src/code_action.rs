@@ -0,0 +1,266 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Quick fixes for common compiler diagnostics, offered as code actions:
+//!
+//! - an unbound module alias, type or function is imported with a `use` of a module (or module
+//!   member) of the same name;
+//! - an ability required by a constraint is added to the declaration of the struct;
+//! - a resource acquired by a call is added to the `acquires` list of the calling function.
+//!
+//! Diagnostics are recognized by their messages, as the language server reports them to the client
+//! without their codes. Candidate modules for imports come from the last successful
+//! symbolication, while edits are computed on the current contents of the files.
+
+use crate::{
+    context::Context,
+    symbols::{addr_to_ide_string, Symbols},
+    utils::{get_offset, get_position},
+};
+use lsp_server::Request;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic, Position, Range,
+    TextEdit, WorkspaceEdit,
+};
+use move_compiler::expansion::ast::ModuleIdent_;
+use move_symbol_pool::Symbol;
+use std::{collections::HashMap, path::PathBuf};
+use url::Url;
+
+/// Handles code action request of the language server
+pub fn on_code_action_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<CodeActionParams>(request.params.clone())
+        .expect("could not deserialize code action request");
+
+    let uri = parameters.text_document.uri;
+    let mut actions = vec![];
+    for diag in &parameters.context.diagnostics {
+        for (title, edit_uri, edit) in quick_fixes(context, symbols, &uri, diag) {
+            let changes = HashMap::from([(edit_uri, vec![edit])]);
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title,
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diag.clone()]),
+                edit: Some(WorkspaceEdit::new(changes)),
+                ..Default::default()
+            }));
+        }
+    }
+
+    let response = lsp_server::Response::new_ok(request.id.clone(), actions);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send code action response: {:?}", err);
+    }
+}
+
+/// Returns the title of each fix for `diag`, together with the edit implementing it
+fn quick_fixes(
+    context: &Context,
+    symbols: &Symbols,
+    uri: &Url,
+    diag: &Diagnostic,
+) -> Vec<(String, Url, TextEdit)> {
+    if let Some(fixes) = missing_use(context, symbols, uri, diag) {
+        return fixes;
+    }
+    let related = diag.related_information.iter().flatten();
+    for info in related {
+        let fix = if let Some(ability) = quoted_after(&info.message, "the '", "ability would") {
+            let buffer = match file_buffer(context, &info.location.uri) {
+                Some(buffer) => buffer,
+                None => continue,
+            };
+            add_ability_edit(&buffer, &info.location.range.start, ability).map(|(name, edit)| {
+                let title = format!("Add '{}' ability to '{}'", ability, name);
+                (title, info.location.uri.clone(), edit)
+            })
+        } else if let Some(resource) = quoted_after(&info.message, "The call acquires '", "") {
+            let buffer = match file_buffer(context, uri) {
+                Some(buffer) => buffer,
+                None => continue,
+            };
+            let name = resource.rsplit("::").next().unwrap();
+            add_acquires_edit(&buffer, &diag.range.start, name).map(|(fun, edit)| {
+                let title = format!("Add '{}' to the acquires list of '{}'", name, fun);
+                (title, uri.clone(), edit)
+            })
+        } else {
+            None
+        };
+        if let Some(fix) = fix {
+            return vec![fix];
+        }
+    }
+    vec![]
+}
+
+/// Fixes for an unbound module alias, type or function, importing each module (member) with the
+/// same name
+fn missing_use(
+    context: &Context,
+    symbols: &Symbols,
+    uri: &Url,
+    diag: &Diagnostic,
+) -> Option<Vec<(String, Url, TextEdit)>> {
+    let msg = &diag.message;
+    let mut imports = vec![];
+    if let Some(alias) = quoted_after(msg, "Unbound module alias '", "") {
+        for mod_defs in symbols.file_mods().values().flatten() {
+            if mod_defs.name().module.value().as_str() == alias {
+                imports.push(format!("{}::{}", addr(mod_defs.name()), alias))
+            }
+        }
+    } else if let Some(name) = quoted_after(msg, "Unbound type '", "in current scope")
+        .or_else(|| quoted_after(msg, "Unbound function '", "in current scope"))
+    {
+        let name_sym = Symbol::from(name);
+        for mod_defs in symbols.file_mods().values().flatten() {
+            if mod_defs.contains_struct(&name_sym) || mod_defs.functions().contains_key(&name_sym) {
+                let mident = mod_defs.name();
+                imports.push(format!("{}::{}::{}", addr(mident), mident.module, name))
+            }
+        }
+    } else {
+        return None;
+    }
+    imports.sort();
+    imports.dedup();
+
+    let buffer = file_buffer(context, uri)?;
+    let pos = use_insertion_point(&buffer, &diag.range.start)?;
+    Some(
+        imports
+            .into_iter()
+            .map(|import| {
+                let title = format!("Add 'use {}'", import);
+                let edit = TextEdit::new(Range::new(pos, pos), format!("    use {};\n", import));
+                (title, uri.clone(), edit)
+            })
+            .collect(),
+    )
+}
+
+fn addr(mident: &ModuleIdent_) -> String {
+    addr_to_ide_string(&mident.address)
+}
+
+/// The start of the line after the `module` (or `script`) declaration enclosing `pos`
+fn use_insertion_point(buffer: &str, pos: &Position) -> Option<Position> {
+    let lines = buffer
+        .lines()
+        .take(pos.line as usize + 1)
+        .collect::<Vec<_>>();
+    let decl_line = lines.iter().rposition(|l| {
+        let l = l.trim_start();
+        l.starts_with("module ") || l.starts_with("script")
+    })?;
+    Some(Position {
+        line: decl_line as u32 + 1,
+        character: 0,
+    })
+}
+
+/// Adds `ability` to the declaration of the struct whose name starts at `name_pos`. Returns the
+/// name of the struct and the edit.
+fn add_ability_edit(
+    buffer: &str,
+    name_pos: &Position,
+    ability: &str,
+) -> Option<(String, TextEdit)> {
+    let name_offset = get_offset(buffer, name_pos)?;
+    let header_len = buffer[name_offset..].find(&['{', ';'][..])?;
+    let header = &buffer[name_offset..name_offset + header_len];
+    let name = header
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .next()
+        .unwrap();
+    let text = if header.split_whitespace().any(|tok| tok == "has") {
+        format!(", {}", ability)
+    } else {
+        format!(" has {}", ability)
+    };
+    let pos = get_position(buffer, name_offset + header.trim_end().len());
+    Some((name.to_string(), TextEdit::new(Range::new(pos, pos), text)))
+}
+
+/// Adds `resource` to the `acquires` list of the function enclosing `pos`. Returns the name of the
+/// function and the edit.
+fn add_acquires_edit(buffer: &str, pos: &Position, resource: &str) -> Option<(String, TextEdit)> {
+    let offset = get_offset(buffer, pos)?;
+    let fun_offset = buffer[..offset]
+        .match_indices("fun ")
+        .map(|(idx, _)| idx)
+        .filter(|idx| *idx == 0 || !buffer.as_bytes()[idx - 1].is_ascii_alphanumeric())
+        .last()?;
+    let header_len = buffer[fun_offset..].find('{')?;
+    let header = &buffer[fun_offset..fun_offset + header_len];
+    let name = header["fun ".len()..]
+        .trim_start()
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .next()
+        .unwrap();
+    let text = if header.split_whitespace().any(|tok| tok == "acquires") {
+        format!(", {}", resource)
+    } else {
+        format!(" acquires {}", resource)
+    };
+    let pos = get_position(buffer, fun_offset + header.trim_end().len());
+    Some((name.to_string(), TextEdit::new(Range::new(pos, pos), text)))
+}
+
+/// The text between `prefix` and the next `'`, if `msg` contains `prefix`, and if the closing `'`
+/// is followed by `suffix`
+fn quoted_after<'a>(msg: &'a str, prefix: &str, suffix: &str) -> Option<&'a str> {
+    let start = msg.find(prefix)? + prefix.len();
+    let len = msg[start..].find('\'')?;
+    if !msg[start + len + 1..].trim_start().starts_with(suffix) {
+        return None;
+    }
+    Some(&msg[start..start + len])
+}
+
+/// The contents of the file at `uri`, from the editor if it is open
+fn file_buffer(context: &Context, uri: &Url) -> Option<String> {
+    let path: PathBuf = uri.to_file_path().ok()?;
+    match context.files.get(&path) {
+        Some(buffer) => Some(buffer.to_string()),
+        None => std::fs::read_to_string(&path).ok(),
+    }
+}
+
+#[test]
+fn add_ability_test() {
+    let buffer = "module 0x1::m {\n    struct S<T> {}\n    struct R has key { f: u64 }\n}\n";
+    let (name, edit) = add_ability_edit(buffer, &Position::new(1, 11), "drop").unwrap();
+    assert!(name == "S");
+    assert!(edit.range.start == Position::new(1, 15));
+    assert!(edit.new_text == " has drop");
+
+    let (name, edit) = add_ability_edit(buffer, &Position::new(2, 11), "store").unwrap();
+    assert!(name == "R");
+    assert!(edit.range.start == Position::new(2, 20));
+    assert!(edit.new_text == ", store");
+}
+
+#[test]
+fn add_acquires_test() {
+    let buffer =
+        "module 0x1::m {\n    fun f(a: address): u64 {\n        borrow_global<R>(a).f\n    }\n}\n";
+    let (name, edit) = add_acquires_edit(buffer, &Position::new(2, 8), "R").unwrap();
+    assert!(name == "f");
+    assert!(edit.range.start == Position::new(1, 26));
+    assert!(edit.new_text == " acquires R");
+}
+
+#[test]
+fn quoted_after_test() {
+    let msg = "To satisfy the constraint, the 'drop' ability would need to be added here";
+    assert!(quoted_after(msg, "the '", "ability would") == Some("drop"));
+    let msg = "Unbound type 'Coin' in current scope";
+    assert!(quoted_after(msg, "Unbound type '", "in current scope") == Some("Coin"));
+    assert!(quoted_after(msg, "Unbound function '", "in current scope").is_none());
+}
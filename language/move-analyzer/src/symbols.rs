@@ -49,7 +49,7 @@
 use crate::{
     context::Context,
     diagnostics::{lsp_diagnostics, lsp_empty_diagnostics},
-    utils::get_loc,
+    utils::{get_loc, get_offset, get_position},
 };
 use anyhow::{anyhow, Result};
 use codespan_reporting::files::SimpleFiles;
@@ -59,9 +59,14 @@ use im::ordmap::OrdMap;
 use lsp_server::{Request, RequestId};
 use lsp_types::{
     request::GotoTypeDefinitionParams, Diagnostic, DocumentSymbol, DocumentSymbolParams,
-    GotoDefinitionParams, Hover, HoverContents, HoverParams, LanguageString, Location,
-    MarkedString, Position, Range, ReferenceParams, SymbolKind,
+    Documentation, GotoDefinitionParams, Hover, HoverContents, HoverParams, LanguageString,
+    Location, MarkedString, ParameterInformation, ParameterLabel, Position, Range, ReferenceParams,
+    RenameParams, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensLegend, SemanticTokensParams, SemanticTokensResult, SignatureHelp,
+    SignatureHelpParams, SignatureInformation, SymbolKind, TextDocumentIdentifier, TextEdit,
+    WorkspaceEdit,
 };
+use serde::{Deserialize, Serialize};
 
 use std::{
     cmp,
@@ -74,17 +79,19 @@ use std::{
 use tempfile::tempdir;
 use url::Url;
 
-use move_command_line_common::files::FileHash;
+use move_command_line_common::files::{find_move_filenames, FileHash};
 use move_compiler::{
+    construct_pre_compiled_lib,
+    diagnostics::FilesSourceText,
     expansion::ast::{Address, Fields, ModuleIdent, ModuleIdent_},
     naming::ast::{StructDefinition, StructFields, TParam, Type, TypeName_, Type_},
-    parser::ast::StructName,
-    shared::Identifier,
+    parser::{ast::StructName, keywords::KEYWORDS},
+    shared::{unique_map::UniqueMap, Identifier, PackagePaths},
     typing::ast::{
         BuiltinFunction_, Exp, ExpListItem, Function, FunctionBody_, LValue, LValueList, LValue_,
         ModuleCall, ModuleDefinition, SequenceItem, SequenceItem_, UnannotatedExp_,
     },
-    PASS_TYPING,
+    Compiler, Flags, FullyCompiledProgram, PASS_TYPING,
 };
 use move_ir_types::location::*;
 use move_package::compilation::build_plan::BuildPlan;
@@ -134,6 +141,18 @@ pub enum IdentType {
     ),
 }
 
+/// Kind of the definition an identifier refers to
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum IdentKind {
+    Function,
+    Struct,
+    Field,
+    Const,
+    TypeParam,
+    Parameter,
+    Local,
+}
+
 /// Information about both the use identifier (source file is specified wherever an instance of this
 /// struct is used) and the definition identifier
 #[derive(Debug, Clone, Eq)]
@@ -151,6 +170,11 @@ pub struct UseDef {
     type_def_loc: Option<DefLoc>,
     /// Doc string for the relevant identifier/function
     doc_string: String,
+    /// Kind of the definition
+    kind: IdentKind,
+    /// Whether the type of the identifier should be shown as an inlay hint (only set for the
+    /// definitions of locals bound by `let` without a type annotation)
+    type_hint: bool,
 }
 
 /// Definition of a struct field
@@ -221,6 +245,7 @@ struct UseDefMap(BTreeMap<u32, BTreeSet<UseDef>>);
 pub struct FunctionIdentTypeMap(BTreeMap<String, IdentType>);
 
 /// Result of the symbolication process
+#[derive(Clone)]
 pub struct Symbols {
     /// A map from def locations to all the references (uses)
     references: BTreeMap<DefLoc, BTreeSet<UseLoc>>,
@@ -232,9 +257,38 @@ pub struct Symbols {
     file_mods: BTreeMap<PathBuf, BTreeSet<ModuleDefs>>,
 }
 
+/// Source files of a program, indexed to compute locations and doc strings
+struct SourceFiles {
+    /// A mapping from file names to file content (used to obtain source file locations)
+    files: SimpleFiles<Symbol, String>,
+    /// A mapping from file hashes to file IDs (used to obtain source file locations)
+    file_id_mapping: HashMap<FileHash, usize>,
+    // A mapping from file IDs to a split vector of the lines in each file (used to build docstrings)
+    file_id_to_lines: HashMap<usize, Vec<String>>,
+    /// A mapping from file hashes to file names
+    file_name_mapping: BTreeMap<FileHash, Symbol>,
+}
+
+/// Dependencies of a package, compiled and symbolicated once to be reused as long as their files do
+/// not change
+struct CachedDeps {
+    /// Hashes of the contents of the dependencies' files, by file name
+    file_hashes: BTreeMap<Symbol, FileHash>,
+    /// The dependencies compiled as a library the package is compiled against
+    program: FullyCompiledProgram,
+    /// Outer definitions of the dependencies' modules
+    mod_outer_defs: BTreeMap<ModuleIdent_, ModuleDefs>,
+    /// Symbols of the dependencies' modules
+    symbols: Symbols,
+}
+
+/// Cached dependencies of packages, by package root directory
+#[derive(Default)]
+pub struct DepsCache(BTreeMap<PathBuf, CachedDeps>);
+
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 enum RunnerState {
-    Run(PathBuf),
+    Run(PathBuf, BTreeMap<PathBuf, String>),
     Wait,
     Quit,
 }
@@ -245,9 +299,17 @@ pub struct SymbolicatorRunner {
 }
 
 impl ModuleDefs {
+    pub fn name(&self) -> &ModuleIdent_ {
+        &self.name
+    }
+
     pub fn functions(&self) -> &BTreeMap<Symbol, FunctionDef> {
         &self.functions
     }
+
+    pub fn contains_struct(&self, name: &Symbol) -> bool {
+        self.structs.contains_key(name)
+    }
 }
 
 impl fmt::Display for IdentType {
@@ -342,13 +404,18 @@ fn type_to_ide_string(sp!(_, t): &Type) -> String {
                 )
             }
         },
+        Type_::Fun(args, ret) => format!(
+            "|{}|{}",
+            type_list_to_ide_string(args),
+            type_to_ide_string(ret)
+        ),
         Type_::Anything => "_".to_string(),
         Type_::Var(_) => "invalid type (var)".to_string(),
         Type_::UnresolvedError => "invalid type (unresolved)".to_string(),
     }
 }
 
-fn addr_to_ide_string(addr: &Address) -> String {
+pub fn addr_to_ide_string(addr: &Address) -> String {
     match addr {
         Address::Numerical(None, sp!(_, bytes)) => format!("{}", bytes),
         Address::Numerical(Some(name), _) => format!("{}", name),
@@ -364,6 +431,79 @@ fn type_list_to_ide_string(types: &[Type]) -> String {
         .join(", ")
 }
 
+/// Locals introduced by the compiler have names that cannot be written in the source code
+fn is_compiler_local(name: &Symbol) -> bool {
+    name.contains('#')
+}
+
+impl SourceFiles {
+    fn new(sources: &FilesSourceText) -> Self {
+        let mut files = SimpleFiles::new();
+        let mut file_id_mapping = HashMap::new();
+        let mut file_id_to_lines = HashMap::new();
+        let mut file_name_mapping = BTreeMap::new();
+        for (fhash, (fname, source)) in sources {
+            let id = files.add(*fname, source.clone());
+            file_id_mapping.insert(*fhash, id);
+            file_name_mapping.insert(*fhash, *fname);
+            let lines: Vec<String> = source.lines().map(String::from).collect();
+            file_id_to_lines.insert(id, lines);
+        }
+        Self {
+            files,
+            file_id_mapping,
+            file_id_to_lines,
+            file_name_mapping,
+        }
+    }
+}
+
+impl DepsCache {
+    /// Returns the dependencies of the package at `pkg_path`, compiling and symbolicating them if
+    /// they are not cached or if any of their files changed, and whether they were (re)computed.
+    /// Returns no dependencies if they fail to compile (the errors are then reported when compiling
+    /// them together with the package).
+    fn get(
+        &mut self,
+        pkg_path: &Path,
+        deps: &[PackagePaths],
+        flags: Flags,
+    ) -> Result<(Option<&CachedDeps>, bool)> {
+        let dep_paths = deps
+            .iter()
+            .flat_map(|d| d.paths.iter().map(|p| p.as_str()))
+            .collect::<Vec<_>>();
+        let mut file_hashes = BTreeMap::new();
+        for fname in find_move_filenames(&dep_paths, true)? {
+            let source = std::fs::read_to_string(&fname)?;
+            file_hashes.insert(Symbol::from(fname), FileHash::new(&source));
+        }
+        if matches!(self.0.get(pkg_path), Some(cached) if cached.file_hashes == file_hashes) {
+            return Ok((self.0.get(pkg_path), false));
+        }
+
+        self.0.remove(pkg_path);
+        eprintln!("compiling dependencies");
+        let program = match construct_pre_compiled_lib(deps.to_vec(), None, flags)? {
+            Ok(program) => program,
+            Err(_) => return Ok((None, false)),
+        };
+        let source_files = SourceFiles::new(&program.files);
+        let (mod_outer_defs, symbols) =
+            Symbolicator::symbolicate(&program.typing.modules, source_files, BTreeMap::new());
+        let cached = CachedDeps {
+            file_hashes,
+            program,
+            mod_outer_defs,
+            symbols,
+        };
+        Ok((
+            Some(&*self.0.entry(pkg_path.to_path_buf()).or_insert(cached)),
+            true,
+        ))
+    }
+}
+
 impl SymbolicatorRunner {
     /// Create a new idle runner (one that does not actually symbolicate)
     pub fn idle() -> Self {
@@ -386,6 +526,8 @@ impl SymbolicatorRunner {
                 let (mtx, cvar) = &*thread_mtx_cvar;
                 // Locations opened in the IDE (files or directories) for which manifest file is missing
                 let mut missing_manifests = BTreeSet::new();
+                // Dependencies of the packages symbolicated so far
+                let mut deps_cache = DepsCache::default();
                 // infinite loop to wait for symbolication requests
                 eprintln!("starting symbolicator runner loop");
                 loop {
@@ -393,27 +535,23 @@ impl SymbolicatorRunner {
                         // hold the lock only as long as it takes to get the data, rather than through
                         // the whole symbolication process (hence a separate scope here)
                         let mut symbolicate = mtx.lock().unwrap();
-                        match symbolicate.clone() {
+                        match std::mem::replace(&mut *symbolicate, RunnerState::Wait) {
                             RunnerState::Quit => break,
-                            RunnerState::Run(root_dir) => {
-                                *symbolicate = RunnerState::Wait;
-                                Some(root_dir)
-                            }
+                            RunnerState::Run(root_dir, buffers) => Some((root_dir, buffers)),
                             RunnerState::Wait => {
                                 // wait for next request
                                 symbolicate = cvar.wait(symbolicate).unwrap();
-                                match symbolicate.clone() {
+                                match std::mem::replace(&mut *symbolicate, RunnerState::Wait) {
                                     RunnerState::Quit => break,
-                                    RunnerState::Run(root_dir) => {
-                                        *symbolicate = RunnerState::Wait;
-                                        Some(root_dir)
+                                    RunnerState::Run(root_dir, buffers) => {
+                                        Some((root_dir, buffers))
                                     }
                                     RunnerState::Wait => None,
                                 }
                             }
                         }
                     };
-                    if let Some(starting_path) = starting_path_opt {
+                    if let Some((starting_path, buffers)) = starting_path_opt {
                         let root_dir = Self::root_dir(&starting_path);
                         if root_dir.is_none() && !missing_manifests.contains(&starting_path) {
                             eprintln!("reporting missing manifest");
@@ -432,7 +570,11 @@ impl SymbolicatorRunner {
                             continue;
                         }
                         eprintln!("symbolication started");
-                        match Symbolicator::get_symbols(root_dir.unwrap().as_path()) {
+                        match Symbolicator::get_symbols_incremental(
+                            root_dir.unwrap().as_path(),
+                            &mut deps_cache,
+                            &buffers,
+                        ) {
                             Ok((symbols_opt, lsp_diagnostics)) => {
                                 eprintln!("symbolication finished");
                                 if let Some(new_symbols) = symbols_opt {
@@ -467,11 +609,14 @@ impl SymbolicatorRunner {
         runner
     }
 
-    pub fn run(&self, starting_path: PathBuf) {
+    /// Schedules symbolication of the package containing `starting_path`, whose files that have a
+    /// buffer (indexed by canonical path) are symbolicated with its contents. A scheduled run that
+    /// has not started yet is superseded.
+    pub fn run(&self, starting_path: PathBuf, buffers: BTreeMap<PathBuf, String>) {
         eprintln!("scheduling run for {:?}", starting_path);
         let (mtx, cvar) = &*self.mtx_cvar;
         let mut symbolicate = mtx.lock().unwrap();
-        *symbolicate = RunnerState::Run(starting_path);
+        *symbolicate = RunnerState::Run(starting_path, buffers);
         cvar.notify_one();
         eprintln!("scheduled run");
     }
@@ -509,6 +654,7 @@ impl UseDef {
         use_type: IdentType,
         type_def_loc: Option<DefLoc>,
         doc_string: String,
+        kind: IdentKind,
     ) -> Self {
         let def_loc = DefLoc {
             fhash: def_fhash,
@@ -532,6 +678,8 @@ impl UseDef {
             def_loc,
             type_def_loc,
             doc_string,
+            kind,
+            type_hint: false,
         }
     }
 }
@@ -574,6 +722,26 @@ impl UseDefMap {
     fn extend(&mut self, use_defs: BTreeMap<u32, BTreeSet<UseDef>>) {
         self.0.extend(use_defs);
     }
+
+    /// Kind of the identifier starting at `start`, if any
+    fn kind(&self, start: &Position) -> Option<IdentKind> {
+        self.0
+            .get(&start.line)?
+            .iter()
+            .find(|u| u.col_start == start.character)
+            .map(|u| u.kind)
+    }
+
+    /// Requests an inlay hint with the type of the identifier starting at `start`
+    fn set_type_hint(&mut self, start: &Position) {
+        if let Some(uses) = self.0.get_mut(&start.line) {
+            if let Some(u) = uses.iter().find(|u| u.col_start == start.character) {
+                let mut u = u.clone();
+                u.type_hint = true;
+                uses.replace(u);
+            }
+        }
+    }
 }
 
 impl FunctionIdentTypeMap {
@@ -592,6 +760,28 @@ impl FunctionIdentTypeMap {
 
 impl Symbols {
     pub fn merge(&mut self, other: Self) {
+        // uses in the previous versions of the files symbolicated again are replaced by the uses in
+        // their new versions
+        let stale_files = self
+            .file_name_mapping
+            .iter()
+            .filter_map(|(fhash, fname)| {
+                let fpath = dunce::canonicalize(fname.as_str())
+                    .unwrap_or_else(|_| PathBuf::from(fname.as_str()));
+                if other.file_use_defs.contains_key(&fpath) {
+                    Some(*fhash)
+                } else {
+                    None
+                }
+            })
+            .collect::<BTreeSet<_>>();
+        for uses in self.references.values_mut() {
+            uses.retain(|u| !stale_files.contains(&u.fhash));
+        }
+        self.references.retain(|_, uses| !uses.is_empty());
+        self.file_name_mapping
+            .retain(|fhash, _| !stale_files.contains(fhash));
+
         for (k, v) in other.references {
             self.references
                 .entry(k)
@@ -615,6 +805,21 @@ impl Symbolicator {
     /// be retained even if it's getting out-of-date.
     pub fn get_symbols(
         pkg_path: &Path,
+    ) -> Result<(Option<Symbols>, BTreeMap<Symbol, Vec<Diagnostic>>)> {
+        Self::get_symbols_incremental(pkg_path, &mut DepsCache::default(), &BTreeMap::new())
+    }
+
+    /// Gets symbols for a package, only re-analyzing its own modules if its dependencies are in
+    /// the cache and their files have not changed. Files of the package that have a buffer
+    /// (indexed by canonical path) are analyzed with the contents of the buffer rather than the
+    /// contents saved on disk.
+    ///
+    /// The symbols of the dependencies are only part of the result when they are (re)computed, and
+    /// are expected to be retained from a previous result otherwise.
+    pub fn get_symbols_incremental(
+        pkg_path: &Path,
+        deps_cache: &mut DepsCache,
+        buffers: &BTreeMap<PathBuf, String>,
     ) -> Result<(Option<Symbols>, BTreeMap<Symbol, Vec<Diagnostic>>)> {
         let build_config = move_package::BuildConfig {
             test_mode: true,
@@ -628,67 +833,100 @@ impl Symbolicator {
         // vector as the writer
         let resolution_graph =
             build_config.resolution_graph_for_package(pkg_path, &mut Vec::new())?;
+        let build_plan = BuildPlan::create(resolution_graph)?;
+        let (root_paths, deps_paths) = build_plan.package_paths()?;
+        let flags = Flags::testing();
+
+        // dependencies are compiled separately to be cached, unless some of them are only available
+        // as bytecode (a pre-compiled library cannot have dependencies of its own)
+        let (src_deps, bytecode_deps): (Vec<_>, Vec<_>) =
+            deps_paths.into_iter().partition(|(_, src)| *src);
+        let src_deps = src_deps.into_iter().map(|(p, _)| p).collect::<Vec<_>>();
+        let bytecode_deps = bytecode_deps
+            .into_iter()
+            .map(|(p, _)| p)
+            .collect::<Vec<_>>();
+        let (cached_deps, new_deps) = if bytecode_deps.is_empty() {
+            deps_cache.get(pkg_path, &src_deps, flags.clone())?
+        } else {
+            (None, false)
+        };
 
-        // get source files to be able to correlate positions (in terms of byte offsets) with actual
-        // file locations (in terms of line/column numbers)
-        let source_files = &resolution_graph.file_sources();
-        let mut files = SimpleFiles::new();
-        let mut file_id_mapping = HashMap::new();
-        let mut file_id_to_lines = HashMap::new();
-        let mut file_name_mapping = BTreeMap::new();
-        for (fhash, (fname, source)) in source_files {
-            let id = files.add(*fname, source.clone());
-            file_id_mapping.insert(*fhash, id);
-            file_name_mapping.insert(*fhash, *fname);
-            let lines: Vec<String> = source.lines().map(String::from).collect();
-            file_id_to_lines.insert(id, lines);
+        let mut source_overrides = BTreeMap::new();
+        for fname in find_move_filenames(
+            &root_paths
+                .paths
+                .iter()
+                .map(|p| p.as_str())
+                .collect::<Vec<_>>(),
+            true,
+        )? {
+            let fpath = dunce::canonicalize(&fname).unwrap_or_else(|_| PathBuf::from(&fname));
+            if let Some(buffer) = buffers.get(&fpath) {
+                source_overrides.insert(Symbol::from(fname), buffer.clone());
+            }
         }
 
-        let build_plan = BuildPlan::create(resolution_graph)?;
+        let mut targets = vec![root_paths];
+        if cached_deps.is_none() {
+            targets.extend(src_deps);
+        }
+        let compiler = Compiler::from_package_paths(targets, bytecode_deps)
+            .set_flags(flags)
+            .set_pre_compiled_lib_opt(cached_deps.map(|deps| &deps.program))
+            .set_source_overrides(source_overrides);
+
         let mut typed_ast = None;
         let mut diagnostics = None;
-        build_plan.compile_with_driver(&mut std::io::sink(), None, |compiler| {
-            let (files, compilation_result) = compiler.run::<PASS_TYPING>()?;
-            let (_, compiler) = match compilation_result {
-                Ok(v) => v,
-                Err(diags) => {
-                    let failure = true;
-                    diagnostics = Some((diags, failure));
-                    eprintln!("typed AST compilation failed");
-                    return Ok((files, vec![]));
-                }
-            };
-            eprintln!("compiled to typed AST");
-            let (compiler, typed_program) = compiler.into_ast();
-            typed_ast = Some(typed_program.clone());
-            eprintln!("compiling to bytecode");
-            let compilation_result = compiler.at_typing(typed_program).build();
-            let (units, diags) = match compilation_result {
-                Ok(v) => v,
-                Err(diags) => {
-                    let failure = false;
-                    diagnostics = Some((diags, failure));
-                    eprintln!("bytecode compilation failed");
-                    return Ok((files, vec![]));
+        let (mut files, compilation_result) = compiler.run::<PASS_TYPING>()?;
+        match compilation_result {
+            Ok((_, compiler)) => {
+                eprintln!("compiled to typed AST");
+                let (compiler, typed_program) = compiler.into_ast();
+                typed_ast = Some(typed_program.clone());
+                eprintln!("compiling to bytecode");
+                match compiler.at_typing(typed_program).build() {
+                    // warning diagnostics (if any) since compilation succeeded
+                    Ok((_, diags)) => {
+                        // assign only if non-empty, otherwise return None to reset previous
+                        // diagnostics
+                        if !diags.is_empty() {
+                            let failure = false;
+                            diagnostics = Some((diags, failure));
+                        }
+                        eprintln!("compiled to bytecode");
+                    }
+                    Err(diags) => {
+                        let failure = false;
+                        diagnostics = Some((diags, failure));
+                        eprintln!("bytecode compilation failed");
+                    }
                 }
-            };
-            // warning diagnostics (if any) since compilation succeeded
-            if !diags.is_empty() {
-                // assign only if non-empty, otherwise return None to reset previous diagnostics
-                let failure = false;
+            }
+            Err(diags) => {
+                let failure = true;
                 diagnostics = Some((diags, failure));
+                eprintln!("typed AST compilation failed");
+            }
+        };
+
+        // locations in the dependencies are resolved with the files of the cached library
+        let dep_outer_defs = match cached_deps {
+            Some(deps) => {
+                files.extend(deps.program.files.clone());
+                deps.mod_outer_defs.clone()
             }
-            eprintln!("compiled to bytecode");
-            Ok((files, units))
-        })?;
+            None => BTreeMap::new(),
+        };
+        let source_files = SourceFiles::new(&files);
 
-        let mut ide_diagnostics = lsp_empty_diagnostics(&file_name_mapping);
+        let mut ide_diagnostics = lsp_empty_diagnostics(&source_files.file_name_mapping);
         if let Some((compiler_diagnostics, failure)) = diagnostics {
             let lsp_diagnostics = lsp_diagnostics(
                 &compiler_diagnostics.into_codespan_format(),
-                &files,
-                &file_id_mapping,
-                &file_name_mapping,
+                &source_files.files,
+                &source_files.file_id_mapping,
+                &source_files.file_name_mapping,
             );
             // start with empty diagnostics for all files and replace them with actual diagnostics
             // only for files that have failures/warnings so that diagnostics for all other files
@@ -703,8 +941,33 @@ impl Symbolicator {
         }
 
         let modules = &typed_ast.unwrap().modules;
+        let (_, mut symbols) = Self::symbolicate(modules, source_files, dep_outer_defs);
+        if new_deps {
+            symbols.merge(cached_deps.unwrap().symbols.clone());
+        }
+
+        eprintln!("get_symbols load complete");
 
-        let mut mod_outer_defs = BTreeMap::new();
+        Ok((Some(symbols), ide_diagnostics))
+    }
+
+    /// Computes symbols for the given modules, whose files are among `source_files`. The outer
+    /// definitions of the modules they depend on (if not among the given modules) are in
+    /// `dep_outer_defs`. Returns the outer definitions of the given modules along with their
+    /// symbols.
+    fn symbolicate(
+        modules: &UniqueMap<ModuleIdent, ModuleDefinition>,
+        source_files: SourceFiles,
+        dep_outer_defs: BTreeMap<ModuleIdent_, ModuleDefs>,
+    ) -> (BTreeMap<ModuleIdent_, ModuleDefs>, Symbols) {
+        let SourceFiles {
+            files,
+            file_id_mapping,
+            file_id_to_lines,
+            file_name_mapping,
+        } = source_files;
+
+        let mut mod_outer_defs = dep_outer_defs;
         let mut mod_use_defs = BTreeMap::new();
         let mut file_mods = BTreeMap::new();
 
@@ -756,8 +1019,8 @@ impl Symbolicator {
                 &mut function_ident_type,
             );
 
-            let fpath = match source_files.get(&pos.file_hash()) {
-                Some((p, _)) => p,
+            let fpath = match file_name_mapping.get(&pos.file_hash()) {
+                Some(p) => p,
                 None => continue,
             };
 
@@ -770,16 +1033,20 @@ impl Symbolicator {
                 .extend(use_defs.elements());
         }
 
+        let outer_defs = modules
+            .key_cloned_iter()
+            .filter_map(|(mident, _)| {
+                let defs = symbolicator.mod_outer_defs.remove(&mident.value)?;
+                Some((mident.value, defs))
+            })
+            .collect();
         let symbols = Symbols {
             references,
             file_use_defs,
             file_name_mapping,
             file_mods,
         };
-
-        eprintln!("get_symbols load complete");
-
-        Ok((Some(symbols), ide_diagnostics))
+        (outer_defs, symbols)
     }
 
     /// Get empty symbols
@@ -969,6 +1236,7 @@ impl Symbolicator {
                 use_type.clone(),
                 fun_type_def,
                 doc_string,
+                IdentKind::Function,
             );
 
             use_defs.insert(name_start.line, use_def);
@@ -994,6 +1262,7 @@ impl Symbolicator {
                     ident_type,
                     ident_type_def,
                     doc_string,
+                    IdentKind::Const,
                 ),
             );
         }
@@ -1021,6 +1290,7 @@ impl Symbolicator {
                     ident_type,
                     ident_type_def,
                     doc_string,
+                    IdentKind::Struct,
                 ),
             );
 
@@ -1061,6 +1331,7 @@ impl Symbolicator {
                         ident_type,
                         ident_type_def,
                         doc_string,
+                        IdentKind::Field,
                     ),
                 );
             }
@@ -1096,6 +1367,7 @@ impl Symbolicator {
                 references,
                 use_defs,
                 ptype.clone(),
+                IdentKind::Parameter,
             );
         }
 
@@ -1228,7 +1500,32 @@ impl Symbolicator {
                     }
                 }
                 self.lvalue_list_symbols(true, lvalues, scope, references, use_defs);
+                // the types of the bound locals are only written down if the RHS is annotated
+                if !matches!(e.exp.value, UnannotatedExp_::Annotate(_, _)) {
+                    for lval in &lvalues.value {
+                        self.add_type_hints(lval, use_defs);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Request inlay hints with the types of the locals defined by an lvalue
+    fn add_type_hints(&self, lval: &LValue, use_defs: &mut UseDefMap) {
+        match &lval.value {
+            LValue_::Var(var, _) => {
+                if let Some(start) =
+                    Self::get_start_loc(&var.loc(), &self.files, &self.file_id_mapping)
+                {
+                    use_defs.set_type_hint(&start);
+                }
+            }
+            LValue_::Unpack(_, _, _, fields) | LValue_::BorrowUnpack(_, _, _, _, fields) => {
+                for (_, _, (_, (_, lvalue))) in fields {
+                    self.add_type_hints(lvalue, use_defs);
+                }
             }
+            LValue_::Ignore => (),
         }
     }
 
@@ -1265,6 +1562,7 @@ impl Symbolicator {
                         references,
                         use_defs,
                         *t.clone(),
+                        IdentKind::Local,
                     );
                 } else {
                     self.add_local_use_def(
@@ -1491,6 +1789,22 @@ impl Symbolicator {
                 self.exp_symbols(exp, scope, references, use_defs);
                 self.add_type_id_use_def(t, references, use_defs);
             }
+            E::VarCall(v, args) => {
+                let arg_types = match &args.ty {
+                    sp!(_, Type_::Unit) => vec![],
+                    sp!(_, Type_::Apply(_, sp!(_, TypeName_::Multiple(_)), ss)) => ss.clone(),
+                    t => vec![t.clone()],
+                };
+                let fun_type = sp(v.loc(), Type_::Fun(arg_types, Box::new(exp.ty.clone())));
+                self.add_local_use_def(&v.value(), &v.loc(), references, scope, use_defs, fun_type);
+                self.exp_symbols(args, scope, references, use_defs);
+            }
+            E::Lambda(lvalues, body) => {
+                // lambda parameters are only visible in the lambda's body
+                let mut new_scope = scope.clone();
+                self.lvalue_list_symbols(true, lvalues, &mut new_scope, references, use_defs);
+                self.exp_symbols(body, &mut new_scope, references, use_defs);
+            }
 
             _ => (),
         }
@@ -1638,6 +1952,7 @@ impl Symbolicator {
                         ident_type,
                         ident_type_def,
                         doc_string,
+                        IdentKind::TypeParam,
                     ),
                 );
                 let exists = tp_scope.insert(tname, DefLoc { fhash, start });
@@ -1713,6 +2028,7 @@ impl Symbolicator {
                             ident_type,
                             ident_type_def,
                             doc_string,
+                            IdentKind::Const,
                         ),
                     );
                 }
@@ -1751,6 +2067,7 @@ impl Symbolicator {
                             use_type.clone(),
                             self.ident_type_def_loc(&use_type),
                             doc_string,
+                            IdentKind::Function,
                         ),
                     );
                 }
@@ -1792,6 +2109,7 @@ impl Symbolicator {
                             ident_type,
                             ident_type_def,
                             doc_string,
+                            IdentKind::Struct,
                         ),
                     );
                 }
@@ -1836,6 +2154,7 @@ impl Symbolicator {
                                     ident_type,
                                     ident_type_def,
                                     doc_string,
+                                    IdentKind::Field,
                                 ),
                             );
                         }
@@ -1877,6 +2196,7 @@ impl Symbolicator {
                                     ident_type,
                                     ident_type_def,
                                     doc_string,
+                                    IdentKind::TypeParam,
                                 ),
                             );
                         }
@@ -1900,6 +2220,12 @@ impl Symbolicator {
                     self.add_type_id_use_def(t, references, use_defs);
                 }
             }
+            Type_::Fun(args, ret) => {
+                for t in args {
+                    self.add_type_id_use_def(t, references, use_defs);
+                }
+                self.add_type_id_use_def(ret, references, use_defs);
+            }
             _ => (), // nothing to be done for the other types
         }
     }
@@ -1913,6 +2239,7 @@ impl Symbolicator {
         references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
         use_defs: &mut UseDefMap,
         use_type: Type,
+        kind: IdentKind,
     ) {
         match Self::get_start_loc(pos, &self.files, &self.file_id_mapping) {
             Some(name_start) => {
@@ -1925,6 +2252,11 @@ impl Symbolicator {
                 // in rust) a variable can be re-defined in the same scope replacing the previous
                 // definition
 
+                if is_compiler_local(name) {
+                    // not in the source code (e.g., introduced by desugaring for loops)
+                    return;
+                }
+
                 let doc_string = self.extract_doc_string(&name_start, &pos.file_hash());
 
                 // enter self-definition for def name
@@ -1942,6 +2274,7 @@ impl Symbolicator {
                         ident_type,
                         ident_type_def,
                         doc_string,
+                        kind,
                     ),
                 );
             }
@@ -1962,6 +2295,9 @@ impl Symbolicator {
         use_defs: &mut UseDefMap,
         use_type: Type,
     ) {
+        if is_compiler_local(use_name) {
+            return;
+        }
         let name_start = match Self::get_start_loc(use_pos, &self.files, &self.file_id_mapping) {
             Some(v) => v,
             None => {
@@ -1972,6 +2308,8 @@ impl Symbolicator {
 
         if let Some(def_loc) = scope.get(use_name) {
             let doc_string = self.extract_doc_string(&def_loc.start, &def_loc.fhash);
+            // locals are defined in the same module as their uses
+            let kind = use_defs.kind(&def_loc.start).unwrap_or(IdentKind::Local);
             let ident_type = IdentType::RegularType(use_type);
             let ident_type_def = self.ident_type_def_loc(&ident_type);
             use_defs.insert(
@@ -1986,6 +2324,7 @@ impl Symbolicator {
                     ident_type,
                     ident_type_def,
                     doc_string,
+                    kind,
                 ),
             );
         } else {
@@ -2209,6 +2548,405 @@ pub fn on_hover_request(context: &Context, request: &Request, symbols: &Symbols)
     );
 }
 
+/// Handles rename request of the language server
+pub fn on_rename_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<RenameParams>(request.params.clone())
+        .expect("could not deserialize rename request");
+
+    let fpath = parameters
+        .text_document_position
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let loc = parameters.text_document_position.position;
+    let new_name = parameters.new_name;
+
+    if !is_valid_identifier(&new_name) {
+        let response = lsp_server::Response::new_err(
+            request.id.clone(),
+            lsp_server::ErrorCode::InvalidParams as i32,
+            format!("'{}' is not a valid identifier", new_name),
+        );
+        if let Err(err) = context
+            .connection
+            .sender
+            .send(lsp_server::Message::Response(response))
+        {
+            eprintln!("could not send rename response: {:?}", err);
+        }
+        return;
+    }
+
+    on_use_request(
+        context,
+        symbols,
+        &fpath,
+        loc.line,
+        loc.character,
+        request.id.clone(),
+        |u| Some(serde_json::to_value(rename_edit(symbols, u, &new_name)).unwrap()),
+    );
+}
+
+/// Computes the edit renaming the definition of `u`, and all of its uses, to `new_name`
+fn rename_edit(symbols: &Symbols, u: &UseDef, new_name: &str) -> Option<WorkspaceEdit> {
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for ref_loc in symbols.references.get(&u.def_loc)? {
+        let range = Range {
+            start: ref_loc.start,
+            end: Position {
+                line: ref_loc.start.line,
+                character: ref_loc.col_end,
+            },
+        };
+        let path = symbols.file_name_mapping.get(&ref_loc.fhash).unwrap();
+        changes
+            .entry(Url::from_file_path(path.as_str()).unwrap())
+            .or_insert_with(Vec::new)
+            .push(TextEdit::new(range, new_name.to_string()));
+    }
+    Some(WorkspaceEdit::new(changes))
+}
+
+fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && !KEYWORDS.contains(&name)
+}
+
+/// Handles signature help request of the language server
+pub fn on_signature_help_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<SignatureHelpParams>(request.params.clone())
+        .expect("could not deserialize signature help request");
+
+    let fpath = parameters
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let loc = parameters.text_document_position_params.position;
+
+    // the buffer may have been edited since the last symbolication, but the name of the function
+    // being called usually stays in place while its arguments are typed
+    let buffer = match context.files.get(&fpath) {
+        Some(buffer) => buffer.to_string(),
+        None => std::fs::read_to_string(&fpath).unwrap_or_default(),
+    };
+    let call = get_offset(&buffer, &loc).and_then(|offset| enclosing_call(&buffer, offset));
+    let (name_offset, active_parameter) = match call {
+        Some(call) => call,
+        None => {
+            let response =
+                lsp_server::Response::new_ok(request.id.clone(), Option::<SignatureHelp>::None);
+            if let Err(err) = context
+                .connection
+                .sender
+                .send(lsp_server::Message::Response(response))
+            {
+                eprintln!("could not send signature help response: {:?}", err);
+            }
+            return;
+        }
+    };
+    let name_pos = get_position(&buffer, name_offset);
+
+    on_use_request(
+        context,
+        symbols,
+        &fpath,
+        name_pos.line,
+        name_pos.character,
+        request.id.clone(),
+        |u| Some(serde_json::to_value(signature_help(u, active_parameter)).unwrap()),
+    );
+}
+
+/// Builds the signature help for a call of the function `u` refers to, if any
+fn signature_help(u: &UseDef, active_parameter: u32) -> Option<SignatureHelp> {
+    let (arg_names, arg_types) = match &u.use_type {
+        IdentType::FunctionType(_, _, _, arg_names, arg_types, _, _) => (arg_names, arg_types),
+        IdentType::RegularType(_) => return None,
+    };
+    let parameters = arg_names
+        .iter()
+        .zip(arg_types)
+        .map(|(n, t)| ParameterInformation {
+            label: ParameterLabel::Simple(format!("{}: {}", n, type_to_ide_string(t))),
+            documentation: None,
+        })
+        .collect();
+    let documentation = if u.doc_string.is_empty() {
+        None
+    } else {
+        Some(Documentation::String(u.doc_string.clone()))
+    };
+    let signature = SignatureInformation {
+        label: format!("{}", u.use_type),
+        documentation,
+        parameters: Some(parameters),
+        active_parameter: None,
+    };
+    Some(SignatureHelp {
+        signatures: vec![signature],
+        active_signature: Some(0),
+        active_parameter: Some(active_parameter),
+    })
+}
+
+/// Finds the innermost call whose argument list contains `offset`. Returns the offset of the name
+/// of the called function and the index of the argument `offset` is in.
+fn enclosing_call(buffer: &str, offset: usize) -> Option<(usize, u32)> {
+    let bytes = buffer.as_bytes();
+    let mut depth = 0;
+    let mut active_parameter = 0;
+    let mut idx = offset;
+    // find the opening parenthesis of the argument list
+    let open_paren = loop {
+        if idx == 0 {
+            return None;
+        }
+        idx -= 1;
+        match bytes[idx] {
+            b')' | b']' | b'}' => depth += 1,
+            b'(' | b'[' if depth > 0 => depth -= 1,
+            b'{' if depth > 0 => depth -= 1,
+            b'(' => break idx,
+            b'[' | b'{' | b';' => return None,
+            b',' if depth == 0 => active_parameter += 1,
+            _ => (),
+        }
+    };
+    // skip whitespace and type arguments between the name and the argument list
+    let mut name_end = buffer[..open_paren].trim_end().len();
+    if name_end > 0 && bytes[name_end - 1] == b'>' {
+        let mut depth = 0;
+        while name_end > 0 {
+            name_end -= 1;
+            match bytes[name_end] {
+                b'>' => depth += 1,
+                b'<' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => (),
+            }
+        }
+        name_end = buffer[..name_end].trim_end().len();
+    }
+    let name_start = buffer[..name_end]
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    if name_start == name_end {
+        return None;
+    }
+    Some((name_start, active_parameter))
+}
+
+/// Legend of the semantic tokens, sent to the client when the server is initialized (a token
+/// refers to its type and modifiers by their indexes in the legend)
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: vec![
+            SemanticTokenType::FUNCTION,
+            SemanticTokenType::STRUCT,
+            SemanticTokenType::PROPERTY,
+            SemanticTokenType::VARIABLE,
+            SemanticTokenType::TYPE_PARAMETER,
+            SemanticTokenType::PARAMETER,
+        ],
+        token_modifiers: vec![
+            SemanticTokenModifier::DECLARATION,
+            SemanticTokenModifier::READONLY,
+        ],
+    }
+}
+
+impl IdentKind {
+    /// Semantic token type and modifiers (other than the declaration modifier) of an identifier
+    fn semantic_token(&self) -> (SemanticTokenType, Vec<SemanticTokenModifier>) {
+        match self {
+            IdentKind::Function => (SemanticTokenType::FUNCTION, vec![]),
+            IdentKind::Struct => (SemanticTokenType::STRUCT, vec![]),
+            IdentKind::Field => (SemanticTokenType::PROPERTY, vec![]),
+            IdentKind::Const => (
+                SemanticTokenType::VARIABLE,
+                vec![SemanticTokenModifier::READONLY],
+            ),
+            IdentKind::TypeParam => (SemanticTokenType::TYPE_PARAMETER, vec![]),
+            IdentKind::Parameter => (SemanticTokenType::PARAMETER, vec![]),
+            IdentKind::Local => (SemanticTokenType::VARIABLE, vec![]),
+        }
+    }
+}
+
+/// Handles semantic tokens request of the language server
+pub fn on_semantic_tokens_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<SemanticTokensParams>(request.params.clone())
+        .expect("could not deserialize semantic tokens request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let result = SemanticTokensResult::Tokens(SemanticTokens {
+        result_id: None,
+        data: semantic_tokens(symbols, &fpath),
+    });
+
+    let response = lsp_server::Response::new_ok(request.id.clone(), result);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send semantic tokens response: {:?}", err);
+    }
+}
+
+/// Semantic tokens for all identifiers in a file, each token positioned relatively to the previous
+/// one
+fn semantic_tokens(symbols: &Symbols, fpath: &PathBuf) -> Vec<SemanticToken> {
+    let mut tokens = vec![];
+    let mod_symbols = match symbols.file_use_defs.get(fpath) {
+        Some(v) => v,
+        None => return tokens,
+    };
+    // an identifier is a declaration if it is its own definition
+    let fhash = symbols.file_name_mapping.iter().find_map(|(fhash, fname)| {
+        let path =
+            dunce::canonicalize(fname.as_str()).unwrap_or_else(|_| PathBuf::from(fname.as_str()));
+        if path == *fpath {
+            Some(*fhash)
+        } else {
+            None
+        }
+    });
+
+    let legend = semantic_tokens_legend();
+    let index_of = |modifier: &SemanticTokenModifier| {
+        legend
+            .token_modifiers
+            .iter()
+            .position(|m| m == modifier)
+            .unwrap() as u32
+    };
+    let (mut prev_line, mut prev_start, mut prev_end) = (0, 0, 0);
+    for (line, uses) in &mod_symbols.0 {
+        for u in uses {
+            // tokens cannot overlap
+            if *line == prev_line && u.col_start < prev_end {
+                continue;
+            }
+            let (token_type, mut modifiers) = u.kind.semantic_token();
+            let start = Position::new(*line, u.col_start);
+            if Some(u.def_loc.fhash) == fhash && u.def_loc.start == start {
+                modifiers.push(SemanticTokenModifier::DECLARATION);
+            }
+            let delta_line = line - prev_line;
+            tokens.push(SemanticToken {
+                delta_line,
+                delta_start: if delta_line == 0 {
+                    u.col_start - prev_start
+                } else {
+                    u.col_start
+                },
+                length: u.col_end - u.col_start,
+                token_type: legend
+                    .token_types
+                    .iter()
+                    .position(|t| *t == token_type)
+                    .unwrap() as u32,
+                token_modifiers_bitset: modifiers.iter().fold(0, |bits, m| bits | 1 << index_of(m)),
+            });
+            prev_line = *line;
+            prev_start = u.col_start;
+            prev_end = u.col_end;
+        }
+    }
+    tokens
+}
+
+/// The inlay hint request (not yet available in the version of `lsp_types` in use)
+pub enum InlayHintRequest {}
+
+impl lsp_types::request::Request for InlayHintRequest {
+    type Params = InlayHintParams;
+    type Result = Option<Vec<InlayHint>>;
+    const METHOD: &'static str = "textDocument/inlayHint";
+}
+
+/// Parameters of the inlay hint request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintParams {
+    pub text_document: TextDocumentIdentifier,
+    /// The visible part of the document for which hints are requested
+    pub range: Range,
+}
+
+/// A hint shown inline with the source code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHint {
+    pub position: Position,
+    pub label: String,
+    /// 1 for type hints, 2 for parameter hints
+    pub kind: u32,
+}
+
+/// Kind of the inlay hints showing the type of an identifier
+const INLAY_HINT_KIND_TYPE: u32 = 1;
+
+/// Handles inlay hint request of the language server
+pub fn on_inlay_hint_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<InlayHintParams>(request.params.clone())
+        .expect("could not deserialize inlay hint request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let hints = inlay_hints(symbols, &fpath, &parameters.range);
+
+    let response = lsp_server::Response::new_ok(request.id.clone(), hints);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send inlay hint response: {:?}", err);
+    }
+}
+
+/// Hints with the inferred types of the locals defined in `range` of a file
+fn inlay_hints(symbols: &Symbols, fpath: &PathBuf, range: &Range) -> Vec<InlayHint> {
+    let mut hints = vec![];
+    let mod_symbols = match symbols.file_use_defs.get(fpath) {
+        Some(v) => v,
+        None => return hints,
+    };
+    for (line, uses) in &mod_symbols.0 {
+        for u in uses {
+            let t = match &u.use_type {
+                IdentType::RegularType(t) if u.type_hint => t,
+                _ => continue,
+            };
+            let position = Position::new(*line, u.col_end);
+            if position < range.start || position > range.end {
+                continue;
+            }
+            hints.push(InlayHint {
+                position,
+                label: format!(": {}", type_to_ide_string(t)),
+                kind: INLAY_HINT_KIND_TYPE,
+            });
+        }
+    }
+    hints
+}
+
 /// Helper function to handle language server queries related to identifier uses
 pub fn on_use_request(
     context: &Context,
@@ -3677,3 +4415,271 @@ fn symbols_test() {
         None,
     );
 }
+
+#[test]
+/// Tests if renaming an identifier edits its definition and all of its uses.
+fn rename_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let uri = Url::from_file_path(&cpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // local variable in the pack function, which does not affect the one in the cp function
+    let uses = mod_symbols.get(20).unwrap();
+    let u = uses.iter().find(|u| u.col_start == 12).unwrap();
+    let edit = rename_edit(&symbols, u, "res").unwrap();
+    let changes = edit.changes.unwrap();
+    let mut ranges = changes[&uri].iter().map(|e| e.range).collect::<Vec<_>>();
+    ranges.sort_by_key(|r| (r.start.line, r.start.character));
+    assert!(changes.len() == 1);
+    assert!(
+        ranges
+            == vec![
+                Range::new(Position::new(20, 12), Position::new(20, 15)),
+                Range::new(Position::new(21, 8), Position::new(21, 11))
+            ]
+    );
+    assert!(changes[&uri].iter().all(|e| e.new_text == "res"));
+
+    // constant, at its definition and in two functions
+    let uses = mod_symbols.get(6).unwrap();
+    let u = uses.iter().find(|u| u.col_start == 10).unwrap();
+    let edit = rename_edit(&symbols, u, "OTHER_CONST").unwrap();
+    let changes = edit.changes.unwrap();
+    let mut lines = changes[&uri]
+        .iter()
+        .map(|e| e.range.start.line)
+        .collect::<Vec<_>>();
+    lines.sort();
+    assert!(lines.starts_with(&[6, 20, 25]));
+
+    assert!(is_valid_identifier("new_name"));
+    assert!(is_valid_identifier("_x1"));
+    assert!(!is_valid_identifier("1x"));
+    assert!(!is_valid_identifier("a-b"));
+    assert!(!is_valid_identifier("fun"));
+}
+
+#[test]
+/// Tests if the function called at a position, and the argument at this position, are found.
+fn signature_help_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let buffer = std::fs::read_to_string(&cpath).unwrap();
+
+    // second argument of the call in the multi_arg_call function
+    let offset = get_offset(&buffer, &Position::new(40, 36)).unwrap();
+    let (name_offset, active_parameter) = enclosing_call(&buffer, offset).unwrap();
+    let name_pos = get_position(&buffer, name_offset);
+    assert!(name_pos == Position::new(40, 12));
+    assert!(active_parameter == 1);
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+    let uses = mod_symbols.get(name_pos.line).unwrap();
+    let u = uses
+        .iter()
+        .find(|u| u.col_start == name_pos.character)
+        .unwrap();
+    let help = signature_help(u, active_parameter).unwrap();
+    let signature = &help.signatures[0];
+    assert!(signature.label == "fun Symbols::M2::multi_arg(p1: u64, p2: u64): u64");
+    let params = signature
+        .parameters
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|p| match &p.label {
+            ParameterLabel::Simple(l) => l.as_str(),
+            ParameterLabel::LabelOffsets(_) => panic!("unexpected parameter label"),
+        })
+        .collect::<Vec<_>>();
+    assert!(params == vec!["p1: u64", "p2: u64"]);
+    assert!(help.active_parameter == Some(1));
+
+    let buffer = "fun f() { g<u64, bool>(x, h(y, z), |) }";
+    let offset = buffer.find('|').unwrap();
+    let (name_offset, active_parameter) = enclosing_call(buffer, offset).unwrap();
+    assert!(&buffer[name_offset..name_offset + 1] == "g");
+    assert!(active_parameter == 2);
+    assert!(enclosing_call(buffer, buffer.find('{').unwrap() + 1).is_none());
+}
+
+#[test]
+/// Tests if semantic tokens have the right types and modifiers, and are encoded relatively to each
+/// other.
+fn semantic_tokens_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    // decode tokens into (line, start, length, type, modifiers)
+    let mut decoded = vec![];
+    let (mut line, mut start) = (0, 0);
+    for t in semantic_tokens(&symbols, &cpath) {
+        if t.delta_line != 0 {
+            start = 0;
+        }
+        line += t.delta_line;
+        start += t.delta_start;
+        decoded.push((
+            line,
+            start,
+            t.length,
+            t.token_type,
+            t.token_modifiers_bitset,
+        ));
+    }
+    let legend = semantic_tokens_legend();
+    let token_type =
+        |t: SemanticTokenType| legend.token_types.iter().position(|lt| *lt == t).unwrap() as u32;
+    let declaration = 1;
+    let readonly = 2;
+
+    // struct definition
+    assert!(decoded.contains(&(
+        2,
+        11,
+        10,
+        token_type(SemanticTokenType::STRUCT),
+        declaration
+    )));
+    // field definition
+    assert!(decoded.contains(&(
+        3,
+        8,
+        10,
+        token_type(SemanticTokenType::PROPERTY),
+        declaration
+    )));
+    // const definition
+    assert!(decoded.contains(&(
+        6,
+        10,
+        10,
+        token_type(SemanticTokenType::VARIABLE),
+        declaration | readonly
+    )));
+    // function definition and its parameter
+    assert!(decoded.contains(&(
+        9,
+        8,
+        6,
+        token_type(SemanticTokenType::FUNCTION),
+        declaration
+    )));
+    assert!(decoded.contains(&(
+        9,
+        15,
+        1,
+        token_type(SemanticTokenType::PARAMETER),
+        declaration
+    )));
+    // use of a local
+    assert!(decoded.contains(&(11, 8, 5, token_type(SemanticTokenType::VARIABLE), 0)));
+    // use of a parameter
+    assert!(decoded.contains(&(15, 18, 5, token_type(SemanticTokenType::PARAMETER), 0)));
+    // use of a const
+    assert!(decoded.contains(&(
+        20,
+        43,
+        10,
+        token_type(SemanticTokenType::VARIABLE),
+        readonly
+    )));
+}
+
+#[test]
+/// Tests if inlay hints are only shown for locals bound by `let` without a type annotation.
+fn inlay_hints_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let range = Range::new(Position::new(10, 0), Position::new(21, 0));
+    let hints = inlay_hints(&symbols, &cpath, &range)
+        .into_iter()
+        .map(|h| (h.position.line, h.position.character, h.label))
+        .collect::<Vec<_>>();
+    assert!(
+        hints
+            == vec![
+                (10, 42, ": u64".to_string()),
+                (15, 15, ": u64".to_string()),
+                (20, 15, ": Symbols::M1::SomeStruct".to_string()),
+            ]
+    );
+
+    // a local declared with its type
+    let range = Range::new(Position::new(49, 0), Position::new(53, 0));
+    assert!(inlay_hints(&symbols, &cpath, &range).is_empty());
+}
+
+#[test]
+/// Tests if dependencies are only symbolicated once and if buffers are analyzed instead of files.
+fn incremental_symbols_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mut deps_cache = DepsCache::default();
+    let is_dep_file = |p: &PathBuf| p.to_string_lossy().contains("move-stdlib");
+
+    let (symbols_opt, _) =
+        Symbolicator::get_symbols_incremental(path.as_path(), &mut deps_cache, &BTreeMap::new())
+            .unwrap();
+    let symbols = symbols_opt.unwrap();
+    assert!(symbols.file_use_defs.contains_key(&cpath));
+    assert!(symbols.file_use_defs.keys().any(is_dep_file));
+
+    // dependencies are cached
+    let (symbols_opt, _) =
+        Symbolicator::get_symbols_incremental(path.as_path(), &mut deps_cache, &BTreeMap::new())
+            .unwrap();
+    let symbols = symbols_opt.unwrap();
+    assert!(symbols.file_use_defs.contains_key(&cpath));
+    assert!(!symbols.file_use_defs.keys().any(is_dep_file));
+
+    // an unsaved buffer with a type error
+    let mut buffer = std::fs::read_to_string(&cpath).unwrap();
+    buffer.push_str("\nmodule Symbols::M9 { fun f(): u64 { true } }\n");
+    let buffers = BTreeMap::from([(cpath, buffer)]);
+    let (symbols_opt, diags) =
+        Symbolicator::get_symbols_incremental(path.as_path(), &mut deps_cache, &buffers).unwrap();
+    assert!(symbols_opt.is_none());
+    assert!(diags.values().any(|d| !d.is_empty()));
+}
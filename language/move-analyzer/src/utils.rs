@@ -29,3 +29,30 @@ pub fn get_loc(
         Err(_) => None,
     }
 }
+
+/// Converts a line/character (Position) location in `buffer` to a byte offset. Returns `None` if
+/// the position is past the end of its line.
+pub fn get_offset(buffer: &str, pos: &Position) -> Option<usize> {
+    let mut line_start = 0;
+    for _ in 0..pos.line {
+        line_start += buffer[line_start..].find('\n')? + 1;
+    }
+    let line = buffer[line_start..].split('\n').next().unwrap();
+    if pos.character as usize == line.chars().count() {
+        return Some(line_start + line.len());
+    }
+    line.char_indices()
+        .nth(pos.character as usize)
+        .map(|(idx, _)| line_start + idx)
+}
+
+/// Converts a byte offset in `buffer` to the line/character (Position) format, where
+/// line/character are 0-based.
+pub fn get_position(buffer: &str, offset: usize) -> Position {
+    let before = &buffer[..offset];
+    let line_start = before.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..].chars().count() as u32,
+    }
+}
@@ -9,6 +9,9 @@
 //! To manage these buffers, this module provides a "virtual file system" -- in reality, it is
 //! basically just a mapping from file identifier (this could be the file's path were it to be
 //! saved) to its textual contents.
+//!
+//! Symbolication is scheduled whenever a buffer is opened, changed or saved, and runs on the
+//! contents of the buffers rather than on the files they were loaded from.
 
 use crate::symbols;
 use lsp_server::Notification;
@@ -16,7 +19,7 @@ use lsp_types::{
     notification::Notification as _, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
     DidOpenTextDocumentParams, DidSaveTextDocumentParams,
 };
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
 /// A mapping from identifiers (file names, potentially, but not necessarily) to their contents.
 #[derive(Debug, Default)]
@@ -45,6 +48,17 @@ impl VirtualFileSystem {
     pub fn remove(&mut self, identifier: &PathBuf) {
         self.files.remove(identifier);
     }
+
+    /// Returns a copy of all buffers, indexed by the canonical paths of their identifiers.
+    pub fn snapshot(&self) -> BTreeMap<PathBuf, String> {
+        self.files
+            .iter()
+            .map(|(identifier, content)| {
+                let path = dunce::canonicalize(identifier).unwrap_or_else(|_| identifier.clone());
+                (path, content.clone())
+            })
+            .collect()
+    }
 }
 
 /// Updates the given virtual file system based on the text document sync notification that was sent.
@@ -63,7 +77,10 @@ pub fn on_text_document_sync_notification(
                 parameters.text_document.uri.to_file_path().unwrap(),
                 &parameters.text_document.text,
             );
-            symbolicator_runner.run(parameters.text_document.uri.to_file_path().unwrap());
+            symbolicator_runner.run(
+                parameters.text_document.uri.to_file_path().unwrap(),
+                files.snapshot(),
+            );
         }
         lsp_types::notification::DidChangeTextDocument::METHOD => {
             let parameters =
@@ -73,6 +90,10 @@ pub fn on_text_document_sync_notification(
                 parameters.text_document.uri.to_file_path().unwrap(),
                 &parameters.content_changes.last().unwrap().text,
             );
+            symbolicator_runner.run(
+                parameters.text_document.uri.to_file_path().unwrap(),
+                files.snapshot(),
+            );
         }
         lsp_types::notification::DidSaveTextDocument::METHOD => {
             let parameters =
@@ -82,7 +103,10 @@ pub fn on_text_document_sync_notification(
                 parameters.text_document.uri.to_file_path().unwrap(),
                 &parameters.text.unwrap(),
             );
-            symbolicator_runner.run(parameters.text_document.uri.to_file_path().unwrap());
+            symbolicator_runner.run(
+                parameters.text_document.uri.to_file_path().unwrap(),
+                files.snapshot(),
+            );
         }
         lsp_types::notification::DidCloseTextDocument::METHOD => {
             let parameters =
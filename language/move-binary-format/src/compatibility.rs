@@ -2,7 +2,7 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, fmt};
 
 use crate::{
     errors::{PartialVMError, PartialVMResult},
@@ -10,7 +10,11 @@ use crate::{
     file_format_common::VERSION_5,
     normalized::Module,
 };
-use move_core_types::vm_status::StatusCode;
+use move_core_types::{
+    identifier::{IdentStr, Identifier},
+    language_storage::ModuleId,
+    vm_status::StatusCode,
+};
 
 /// The result of a linking and layout compatibility check. Here is what the different combinations. NOTE that if `check_struct_layout` is false, type safety over a series of upgrades cannot be guaranteed.
 /// mean:
@@ -72,74 +76,212 @@ impl Compatibility {
 
     /// Check compatibility for `new_module` relative to old module `old_module`.
     pub fn check(&self, old_module: &Module, new_module: &Module) -> PartialVMResult<()> {
-        let mut struct_and_pub_function_linking = true;
-        let mut struct_layout = true;
-        let mut friend_linking = true;
+        let is_checked = |guarantee: &CompatibilityGuarantee| match guarantee {
+            CompatibilityGuarantee::StructAndPubFunctionLinking => {
+                self.check_struct_and_pub_function_linking
+            }
+            CompatibilityGuarantee::StructLayout => self.check_struct_layout,
+            CompatibilityGuarantee::FriendLinking => self.check_friend_linking,
+        };
+        let is_incompatible = module_changes(old_module, new_module)
+            .iter()
+            .any(|change| change.breaks.iter().any(is_checked));
+        if is_incompatible {
+            return Err(PartialVMError::new(
+                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A guarantee about the dependents and the published data of a module, which an upgrade of the
+/// module may break
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum CompatibilityGuarantee {
+    /// Dependent modules referencing public functions or structs of the module still link
+    StructAndPubFunctionLinking,
+    /// Structs published with the old module can still be read
+    StructLayout,
+    /// Friend modules referencing friend functions of the module still link
+    FriendLinking,
+}
+
+/// A change to a declaration of the old module in the new one
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ModuleChangeKind {
+    ModuleIdChanged,
+    StructRemoved(Identifier),
+    StructAbilitiesChanged {
+        name: Identifier,
+        old: AbilitySet,
+        new: AbilitySet,
+    },
+    StructTypeParametersChanged(Identifier),
+    StructFieldsChanged(Identifier),
+    FunctionRemoved(Identifier),
+    FunctionVisibilityChanged {
+        name: Identifier,
+        old: Visibility,
+        new: Visibility,
+    },
+    FunctionEntryChanged {
+        name: Identifier,
+        old: bool,
+        new: bool,
+    },
+    FunctionSignatureChanged(Identifier),
+    FriendRemoved(ModuleId),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ModuleChange {
+    pub kind: ModuleChangeKind,
+    /// The guarantees broken by the change, empty if the change is compatible
+    pub breaks: Vec<CompatibilityGuarantee>,
+}
 
-        // module's name and address are unchanged
-        if old_module.address != new_module.address || old_module.name != new_module.name {
-            struct_and_pub_function_linking = false;
+impl ModuleChange {
+    fn new(kind: ModuleChangeKind, breaks: Vec<CompatibilityGuarantee>) -> Self {
+        Self { kind, breaks }
+    }
+
+    pub fn is_compatible(&self) -> bool {
+        self.breaks.is_empty()
+    }
+
+    /// The struct or function changed, if any
+    pub fn member_name(&self) -> Option<&IdentStr> {
+        use ModuleChangeKind::*;
+        match &self.kind {
+            StructRemoved(name)
+            | StructAbilitiesChanged { name, .. }
+            | StructTypeParametersChanged(name)
+            | StructFieldsChanged(name)
+            | FunctionRemoved(name)
+            | FunctionVisibilityChanged { name, .. }
+            | FunctionEntryChanged { name, .. }
+            | FunctionSignatureChanged(name) => Some(name),
+            ModuleIdChanged | FriendRemoved(_) => None,
         }
+    }
+}
 
-        // old module's structs are a subset of the new module's structs
-        for (name, old_struct) in &old_module.structs {
-            let new_struct = match new_module.structs.get(name) {
-                Some(new_struct) => new_struct,
-                None => {
-                    // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
-                    // Also, struct layout cannot be guaranteed transitively, because after
-                    // removing the struct, it could be re-added later with a different layout.
-                    struct_and_pub_function_linking = false;
-                    struct_layout = false;
-                    break;
-                }
-            };
+/// Lists the changes to the declarations of `old_module` in `new_module`, which may or may not be
+/// compatible. Declarations added in `new_module` are not reported.
+pub fn module_changes(old_module: &Module, new_module: &Module) -> Vec<ModuleChange> {
+    use CompatibilityGuarantee::*;
+    use ModuleChangeKind::*;
 
-            if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities)
-                || !struct_type_parameters_compatibile(
-                    &old_struct.type_parameters,
-                    &new_struct.type_parameters,
-                )
+    let mut changes = vec![];
+
+    // module's name and address are unchanged
+    if old_module.address != new_module.address || old_module.name != new_module.name {
+        changes.push(ModuleChange::new(
+            ModuleIdChanged,
+            vec![StructAndPubFunctionLinking],
+        ));
+    }
+
+    // old module's structs are a subset of the new module's structs
+    for (name, old_struct) in &old_module.structs {
+        let new_struct = match new_module.structs.get(name) {
+            Some(new_struct) => new_struct,
+            None => {
+                // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
+                // Also, struct layout cannot be guaranteed transitively, because after
+                // removing the struct, it could be re-added later with a different layout.
+                changes.push(ModuleChange::new(
+                    StructRemoved(name.clone()),
+                    vec![StructAndPubFunctionLinking, StructLayout],
+                ));
+                continue;
+            }
+        };
+
+        if old_struct.abilities != new_struct.abilities {
+            let breaks = if struct_abilities_compatibile(old_struct.abilities, new_struct.abilities)
             {
-                struct_and_pub_function_linking = false;
+                vec![]
+            } else {
+                vec![StructAndPubFunctionLinking]
+            };
+            changes.push(ModuleChange::new(
+                StructAbilitiesChanged {
+                    name: name.clone(),
+                    old: old_struct.abilities,
+                    new: new_struct.abilities,
+                },
+                breaks,
+            ));
+        }
+        if old_struct.type_parameters != new_struct.type_parameters {
+            let breaks = if struct_type_parameters_compatibile(
+                &old_struct.type_parameters,
+                &new_struct.type_parameters,
+            ) {
+                vec![]
+            } else {
+                vec![StructAndPubFunctionLinking]
+            };
+            changes.push(ModuleChange::new(
+                StructTypeParametersChanged(name.clone()),
+                breaks,
+            ));
+        }
+        if new_struct.fields != old_struct.fields {
+            // Fields changed. Code in this module will fail at runtime if it tries to
+            // read a previously published struct value
+            // TODO: this is a stricter definition than required. We could in principle
+            // choose that changing the name (but not position or type) of a field is
+            // compatible. The VM does not care about the name of a field
+            // (it's purely informational), but clients presumably do.
+            changes.push(ModuleChange::new(
+                StructFieldsChanged(name.clone()),
+                vec![StructLayout],
+            ));
+        }
+    }
+
+    // The modules are considered as compatible function-wise when all the conditions are met:
+    //
+    // - old module's public functions are a subset of the new module's public functions
+    //   (i.e. we cannot remove or change public functions)
+    // - old module's script functions are a subset of the new module's script functions
+    //   (i.e. we cannot remove or change script functions)
+    // - for any friend function that is removed or changed in the old module
+    //   - if the function visibility is upgraded to public, it is OK
+    //   - otherwise, it is considered as incompatible.
+    //
+    // NOTE: it is possible to relax the compatibility checking for a friend function, i.e.,
+    // we can remove/change a friend function if the function is not used by any module in the
+    // friend list. But for simplicity, we decided to go to the more restrictive form now and
+    // we may revisit this in the future.
+    for (name, old_func) in &old_module.exposed_functions {
+        let broken_linking = if matches!(old_func.visibility, Visibility::Friend) {
+            FriendLinking
+        } else {
+            StructAndPubFunctionLinking
+        };
+        let breaks_linking_if = |incompatible: bool| {
+            if incompatible {
+                vec![broken_linking]
+            } else {
+                vec![]
             }
-            if new_struct.fields != old_struct.fields {
-                // Fields changed. Code in this module will fail at runtime if it tries to
-                // read a previously published struct value
-                // TODO: this is a stricter definition than required. We could in principle
-                // choose that changing the name (but not position or type) of a field is
-                // compatible. The VM does not care about the name of a field
-                // (it's purely informational), but clients presumably do.
-                struct_layout = false
+        };
+        let new_func = match new_module.exposed_functions.get(name) {
+            Some(new_func) => new_func,
+            None => {
+                changes.push(ModuleChange::new(
+                    FunctionRemoved(name.clone()),
+                    vec![broken_linking],
+                ));
+                continue;
             }
-        }
+        };
 
-        // The modules are considered as compatible function-wise when all the conditions are met:
-        //
-        // - old module's public functions are a subset of the new module's public functions
-        //   (i.e. we cannot remove or change public functions)
-        // - old module's script functions are a subset of the new module's script functions
-        //   (i.e. we cannot remove or change script functions)
-        // - for any friend function that is removed or changed in the old module
-        //   - if the function visibility is upgraded to public, it is OK
-        //   - otherwise, it is considered as incompatible.
-        //
-        // NOTE: it is possible to relax the compatibility checking for a friend function, i.e.,
-        // we can remove/change a friend function if the function is not used by any module in the
-        // friend list. But for simplicity, we decided to go to the more restrictive form now and
-        // we may revisit this in the future.
-        for (name, old_func) in &old_module.exposed_functions {
-            let new_func = match new_module.exposed_functions.get(name) {
-                Some(new_func) => new_func,
-                None => {
-                    if matches!(old_func.visibility, Visibility::Friend) {
-                        friend_linking = false;
-                    } else {
-                        struct_and_pub_function_linking = false;
-                    }
-                    continue;
-                }
-            };
+        if old_func.visibility != new_func.visibility {
             let is_vis_compatible = match (old_func.visibility, new_func.visibility) {
                 // public must remain public
                 (Visibility::Public, Visibility::Public) => true,
@@ -151,62 +293,104 @@ impl Compatibility {
                 // private can become public or friend, or stay private
                 (Visibility::Private, _) => true,
             };
+            changes.push(ModuleChange::new(
+                FunctionVisibilityChanged {
+                    name: name.clone(),
+                    old: old_func.visibility,
+                    new: new_func.visibility,
+                },
+                breaks_linking_if(!is_vis_compatible),
+            ));
+        }
+        if old_func.is_entry != new_func.is_entry {
             let is_entry_compatible = if old_module.file_format_version < VERSION_5
                 && new_module.file_format_version < VERSION_5
             {
                 // if it was public(script), it must remain pubic(script)
                 // if it was not public(script), it _cannot_ become public(script)
-                old_func.is_entry == new_func.is_entry
+                false
             } else {
                 // If it was an entry function, it must remain one.
                 // If it was not an entry function, it is allowed to become one.
-                !old_func.is_entry || new_func.is_entry
+                !old_func.is_entry
             };
-            if !is_vis_compatible
-                || !is_entry_compatible
-                || old_func.parameters != new_func.parameters
-                || old_func.return_ != new_func.return_
-                || !fun_type_parameters_compatibile(
-                    &old_func.type_parameters,
-                    &new_func.type_parameters,
-                )
-            {
-                if matches!(old_func.visibility, Visibility::Friend) {
-                    friend_linking = false;
-                } else {
-                    struct_and_pub_function_linking = false;
-                }
-            }
-        }
-
-        // check friend declarations compatibility
-        //
-        // - additions to the list are allowed
-        // - removals are not allowed
-        //
-        let old_friend_module_ids: BTreeSet<_> = old_module.friends.iter().cloned().collect();
-        let new_friend_module_ids: BTreeSet<_> = new_module.friends.iter().cloned().collect();
-        if !old_friend_module_ids.is_subset(&new_friend_module_ids) {
-            friend_linking = false;
-        }
-
-        if self.check_struct_and_pub_function_linking && !struct_and_pub_function_linking {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
+            changes.push(ModuleChange::new(
+                FunctionEntryChanged {
+                    name: name.clone(),
+                    old: old_func.is_entry,
+                    new: new_func.is_entry,
+                },
+                breaks_linking_if(!is_entry_compatible),
             ));
         }
-        if self.check_struct_layout && !struct_layout {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
+        if old_func.parameters != new_func.parameters
+            || old_func.return_ != new_func.return_
+            || old_func.type_parameters != new_func.type_parameters
+        {
+            let is_signature_compatible = old_func.parameters == new_func.parameters
+                && old_func.return_ == new_func.return_
+                && fun_type_parameters_compatibile(
+                    &old_func.type_parameters,
+                    &new_func.type_parameters,
+                );
+            changes.push(ModuleChange::new(
+                FunctionSignatureChanged(name.clone()),
+                breaks_linking_if(!is_signature_compatible),
             ));
         }
-        if self.check_friend_linking && !friend_linking {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
+    }
+
+    // check friend declarations compatibility
+    //
+    // - additions to the list are allowed
+    // - removals are not allowed
+    //
+    let new_friend_module_ids: BTreeSet<_> = new_module.friends.iter().collect();
+    for friend in &old_module.friends {
+        if !new_friend_module_ids.contains(friend) {
+            changes.push(ModuleChange::new(
+                FriendRemoved(friend.clone()),
+                vec![FriendLinking],
             ));
         }
+    }
 
-        Ok(())
+    changes
+}
+
+impl fmt::Display for ModuleChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ModuleChangeKind::*;
+        match self {
+            ModuleIdChanged => write!(f, "the address or name of the module changed"),
+            StructRemoved(name) => write!(f, "struct `{}` was removed", name),
+            StructAbilitiesChanged { name, old, new } => write!(
+                f,
+                "the abilities of struct `{}` changed from {:?} to {:?}",
+                name, old, new
+            ),
+            StructTypeParametersChanged(name) => {
+                write!(f, "the type parameters of struct `{}` changed", name)
+            }
+            StructFieldsChanged(name) => write!(f, "the fields of struct `{}` changed", name),
+            FunctionRemoved(name) => write!(f, "function `{}` was removed", name),
+            FunctionVisibilityChanged { name, old, new } => write!(
+                f,
+                "the visibility of function `{}` changed from {:?} to {:?}",
+                name, old, new
+            ),
+            FunctionEntryChanged { name, new, .. } => {
+                if *new {
+                    write!(f, "function `{}` became an entry function", name)
+                } else {
+                    write!(f, "function `{}` is no longer an entry function", name)
+                }
+            }
+            FunctionSignatureChanged(name) => {
+                write!(f, "the signature of function `{}` changed", name)
+            }
+            FriendRemoved(module_id) => write!(f, "friend `{}` was removed", module_id),
+        }
     }
 }
 
@@ -3,7 +3,13 @@
 
 use std::convert::TryFrom;
 
-use crate::{compatibility::Compatibility, file_format::*, normalized};
+use crate::{
+    compatibility::{
+        module_changes, Compatibility, CompatibilityGuarantee, ModuleChange, ModuleChangeKind,
+    },
+    file_format::*,
+    normalized,
+};
 use move_core_types::{account_address::AccountAddress, identifier::Identifier};
 
 fn mk_module(vis: u8) -> normalized::Module {
@@ -115,3 +121,26 @@ fn deprecated_add_script_visibility() {
         .check(&friend_module, &script_module)
         .is_err());
 }
+
+#[test]
+fn report_visibility_changes() {
+    let public_module = mk_module(Visibility::Public as u8);
+    let friend_module = mk_module(Visibility::Friend as u8);
+    // public -> friend, breaks linking
+    assert_eq!(
+        module_changes(&public_module, &friend_module),
+        vec![ModuleChange {
+            kind: ModuleChangeKind::FunctionVisibilityChanged {
+                name: Identifier::new("fn").unwrap(),
+                old: Visibility::Public,
+                new: Visibility::Friend,
+            },
+            breaks: vec![CompatibilityGuarantee::StructAndPubFunctionLinking],
+        }]
+    );
+    // friend -> public, compatible
+    let changes = module_changes(&friend_module, &public_module);
+    assert_eq!(changes.len(), 1);
+    assert!(changes[0].is_compatible());
+    assert!(module_changes(&public_module, &public_module).is_empty());
+}
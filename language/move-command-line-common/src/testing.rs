@@ -63,3 +63,28 @@ pub fn format_diff(expected: impl AsRef<str>, actual: impl AsRef<str>) -> String
     }
     ret
 }
+
+/// Like `format_diff`, but marks the added and removed lines with `+` and `-` instead of colors,
+/// for output which may not end up in a terminal
+pub fn format_diff_no_color(expected: impl AsRef<str>, actual: impl AsRef<str>) -> String {
+    use difference::*;
+
+    let changeset = Changeset::new(expected.as_ref(), actual.as_ref(), "\n");
+
+    let mut ret = String::new();
+
+    for seq in changeset.diffs {
+        let (marker, lines) = match &seq {
+            Difference::Same(x) => (' ', x),
+            Difference::Add(x) => ('+', x),
+            Difference::Rem(x) => ('-', x),
+        };
+        for line in lines.split('\n') {
+            ret.push(marker);
+            ret.push(' ');
+            ret.push_str(line);
+            ret.push('\n');
+        }
+    }
+    ret
+}
@@ -104,6 +104,7 @@ pub fn new_native_fun(
         loc,
         visibility,
         entry,
+        inline: false,
         signature,
         acquires: vec![],
         name,
@@ -126,6 +127,7 @@ pub fn new_fun(
         loc,
         visibility,
         entry,
+        inline: false,
         signature,
         acquires: vec![],
         name,
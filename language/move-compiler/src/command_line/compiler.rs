@@ -8,7 +8,9 @@ use crate::{
     compiled_unit,
     compiled_unit::AnnotatedCompiledUnit,
     diagnostics::{codes::Severity, *},
-    expansion, hlir, interface_generator, naming, parser,
+    expansion, hlir, interface_generator,
+    linters::{self, Linter},
+    naming, parser,
     parser::{comments::*, *},
     shared::{
         CompilationEnv, Flags, IndexedPackagePath, NamedAddressMap, NamedAddressMaps,
@@ -42,6 +44,8 @@ pub struct Compiler<'a> {
     pre_compiled_lib: Option<&'a FullyCompiledProgram>,
     compiled_module_named_address_mapping: BTreeMap<CompiledModuleId, String>,
     flags: Flags,
+    linters: Vec<Linter>,
+    source_overrides: BTreeMap<Symbol, String>,
 }
 
 pub struct SteppedCompiler<'a, const P: Pass> {
@@ -130,6 +134,8 @@ impl<'a> Compiler<'a> {
             pre_compiled_lib: None,
             compiled_module_named_address_mapping: BTreeMap::new(),
             flags: Flags::empty(),
+            linters: vec![],
+            source_overrides: BTreeMap::new(),
         }
     }
 
@@ -193,6 +199,20 @@ impl<'a> Compiler<'a> {
         self
     }
 
+    /// Registers lints to run in addition to the built-in ones, see `linters`
+    pub fn add_linters(mut self, linters: impl IntoIterator<Item = Linter>) -> Self {
+        self.linters.extend(linters);
+        self
+    }
+
+    /// Uses the given contents for the files they are mapped to, instead of reading these files,
+    /// e.g. to compile the buffers of an editor that are not saved yet
+    pub fn set_source_overrides(mut self, source_overrides: BTreeMap<Symbol, String>) -> Self {
+        assert!(self.source_overrides.is_empty());
+        self.source_overrides = source_overrides;
+        self
+    }
+
     pub fn run<const TARGET: Pass>(
         self,
     ) -> anyhow::Result<(
@@ -207,6 +227,8 @@ impl<'a> Compiler<'a> {
             pre_compiled_lib,
             compiled_module_named_address_mapping,
             flags,
+            linters,
+            source_overrides,
         } = self;
         generate_interface_files_for_deps(
             &mut deps,
@@ -214,8 +236,9 @@ impl<'a> Compiler<'a> {
             &compiled_module_named_address_mapping,
         )?;
         let mut compilation_env = CompilationEnv::new(flags);
+        compilation_env.add_linters(linters);
         let (source_text, pprog_and_comments_res) =
-            parse_program(&mut compilation_env, maps, targets, deps)?;
+            parse_program(&mut compilation_env, maps, targets, deps, &source_overrides)?;
         let res: Result<_, Diagnostics> = pprog_and_comments_res.and_then(|(pprog, comments)| {
             SteppedCompiler::new_at_parser(compilation_env, pre_compiled_lib, pprog)
                 .run::<TARGET>()
@@ -789,6 +812,9 @@ fn run(
             )
         }
         PassResult::Typing(tprog) => {
+            linters::typing(compilation_env, &tprog);
+            let tprog = typing::inlining::program(compilation_env, pre_compiled_lib, tprog);
+            compilation_env.check_diags_at_or_above_severity(Severity::BlockingError)?;
             let hprog = hlir::translate::program(compilation_env, pre_compiled_lib, tprog);
             compilation_env.check_diags_at_or_above_severity(Severity::Bug)?;
             run(
@@ -802,6 +828,7 @@ fn run(
         PassResult::HLIR(hprog) => {
             let cprog = cfgir::translate::program(compilation_env, pre_compiled_lib, hprog);
             compilation_env.check_diags_at_or_above_severity(Severity::NonblockingError)?;
+            linters::cfgir(compilation_env, &cprog);
             run(
                 compilation_env,
                 pre_compiled_lib,
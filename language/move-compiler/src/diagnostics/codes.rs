@@ -182,6 +182,8 @@ codes!(
                 (NOTE: this may become an error in the future)",
             severity: Warning
         },
+        InvalidFunctionType: { msg: "invalid usage of function type", severity: BlockingError },
+        InvalidLambda: { msg: "invalid usage of lambda", severity: BlockingError },
     ],
     // errors for ability rules. mostly typing/translate
     AbilitySafety: [
@@ -235,10 +237,26 @@ codes!(
     Bug: [
         BytecodeGeneration: { msg: "BYTECODE GENERATION FAILED", severity: Bug },
         BytecodeVerification: { msg: "BYTECODE VERIFICATION FAILED", severity: Bug },
+        Formatting: { msg: "FORMATTING FAILED", severity: Bug },
     ],
     Derivation: [
         DeriveFailed: { msg: "attribute derivation failed", severity: BlockingError }
-    ]
+    ],
+    // errors for expanding 'inline' functions. mostly typing/inlining
+    Inlining: [
+        Recursion: { msg: "cyclic inlining", severity: BlockingError },
+        Visibility: { msg: "restricted visibility after inlining", severity: BlockingError },
+        InvalidReturn: { msg: "invalid return in inlined code", severity: BlockingError },
+    ],
+    // warnings for code that is valid but likely a mistake or needlessly complex. see linters
+    Lint: [
+        NeedlessCopy: { msg: "needless copy", severity: Warning },
+        SelfAssignment: { msg: "self assignment", severity: Warning },
+        UnnecessaryMutRef: { msg: "unnecessary mutable reference", severity: Warning },
+        BoolComparison: { msg: "comparison to a boolean literal", severity: Warning },
+        EmptyAcquires: { msg: "empty acquires", severity: Warning },
+        ShadowedLocal: { msg: "shadowed local", severity: Warning },
+    ],
 );
 
 //**************************************************************************************************
@@ -271,6 +289,10 @@ impl DiagnosticInfo {
     pub fn severity(&self) -> Severity {
         self.severity
     }
+
+    pub fn category(&self) -> Category {
+        self.category
+    }
 }
 
 impl Severity {
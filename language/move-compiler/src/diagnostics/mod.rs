@@ -6,7 +6,7 @@ pub mod codes;
 
 use crate::{
     command_line::COLOR_MODE_ENV_VAR,
-    diagnostics::codes::{DiagnosticCode, DiagnosticInfo, Severity},
+    diagnostics::codes::{Category, DiagnosticCode, DiagnosticInfo, Severity},
 };
 use codespan_reporting::{
     self as csr,
@@ -232,6 +232,13 @@ impl Diagnostics {
         self.diagnostics
     }
 
+    pub fn count_category(&self, category: Category) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diag| diag.info.category() == category)
+            .count()
+    }
+
     pub fn into_codespan_format(
         self,
     ) -> Vec<(
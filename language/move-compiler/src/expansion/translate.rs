@@ -507,7 +507,17 @@ fn script_(context: &mut Context, package_name: Option<Symbol>, pscript: P::Scri
 
     // TODO remove after Self rework
     check_valid_module_member_name(context, ModuleMemberKind::Function, pfunction.name.0);
-    let (function_name, function) = function_(context, pfunction);
+    let (function_name, mut function) = function_(context, pfunction);
+    if function.inline {
+        context.env.add_diag(diag!(
+            Declarations::InvalidScript,
+            (
+                function_name.loc(),
+                "Invalid 'inline' function. 'script' functions cannot be 'inline'"
+            )
+        ));
+        function.inline = false;
+    }
     match &function.visibility {
         E::Visibility::Public(loc) | E::Visibility::Friend(loc) => {
             let msg = format!(
@@ -1201,6 +1211,7 @@ fn function_(context: &mut Context, pfunction: P::Function) -> (FunctionName, E:
         name,
         visibility: pvisibility,
         entry,
+        inline,
         signature: psignature,
         body: pbody,
         acquires,
@@ -1220,6 +1231,7 @@ fn function_(context: &mut Context, pfunction: P::Function) -> (FunctionName, E:
         loc,
         visibility,
         entry,
+        inline,
         signature,
         acquires,
         body,
@@ -1587,18 +1599,12 @@ fn type_(context: &mut Context, sp!(loc, pt_): P::Type) -> E::Type {
             }
         }
         PT::Ref(mut_, inner) => ET::Ref(mut_, Box::new(type_(context, *inner))),
+        // Outside of specifications, function types are only valid for the parameters of
+        // 'inline' functions. This is checked during typing
         PT::Fun(args, result) => {
-            if context.in_spec_context {
-                let args = types(context, args);
-                let result = type_(context, *result);
-                ET::Fun(args, Box::new(result))
-            } else {
-                context.env.add_diag(diag!(
-                    Syntax::SpecContextRestricted,
-                    (loc, "`|_|_` function type only allowed in specifications")
-                ));
-                ET::UnresolvedError
-            }
+            let args = types(context, args);
+            let result = type_(context, *result);
+            ET::Fun(args, Box::new(result))
         }
     };
     sp(loc, t_)
@@ -1880,23 +1886,25 @@ fn exp_(context: &mut Context, sp!(loc, pe_): P::Exp) -> E::Exp {
         }
         PE::While(pb, ploop) => EE::While(exp(context, *pb), exp(context, *ploop)),
         PE::Loop(ploop) => EE::Loop(exp(context, *ploop)),
+        PE::For(_, sp!(_, P::ForIter_::Vector(_, _)), _, _)
+            if !check_for_vector_module(context, loc) =>
+        {
+            EE::UnresolvedError
+        }
+        PE::For(pv, piter, ploop, spec_opt) => {
+            exp_(context, for_loop(loc, pv, piter, *ploop, spec_opt)).value
+        }
         PE::Block(seq) => EE::Block(sequence(context, loc, seq)),
+        // Outside of specifications, lambdas are only valid as arguments to 'inline' functions.
+        // This is checked during typing
         PE::Lambda(pbs, pe) => {
-            if !context.in_spec_context {
-                context.env.add_diag(diag!(
-                    Syntax::SpecContextRestricted,
-                    (loc, "lambda expression only allowed in specifications"),
-                ));
-                EE::UnresolvedError
-            } else {
-                let bs_opt = bind_list(context, pbs);
-                let e = exp_(context, *pe);
-                match bs_opt {
-                    Some(bs) => EE::Lambda(bs, Box::new(e)),
-                    None => {
-                        assert!(context.env.has_errors());
-                        EE::UnresolvedError
-                    }
+            let bs_opt = bind_list(context, pbs);
+            let e = exp_(context, *pe);
+            match bs_opt {
+                Some(bs) => EE::Lambda(bs, Box::new(e)),
+                None => {
+                    assert!(context.env.has_errors());
+                    EE::UnresolvedError
                 }
             }
         }
@@ -2005,6 +2013,176 @@ fn exp_(context: &mut Context, sp!(loc, pe_): P::Exp) -> E::Exp {
     sp(loc, e_)
 }
 
+//**************************************************************************************************
+// For loops
+//**************************************************************************************************
+
+const FOR_STARTED: &str = "_for#started";
+const FOR_BOUND: &str = "_for#bound";
+const FOR_INDEX: &str = "_for#index";
+const FOR_VECTOR: &str = "_for#vector";
+
+// Desugar a 'for' loop over a range
+//
+//     for (i in lo..hi) body spec { .. }
+//
+// into
+//
+//     {
+//         let (i, _for#bound) = (lo, hi);
+//         let _for#started = false;
+//         while ({
+//             if (_for#started) i = i + 1 else _for#started = true;
+//             spec { .. };
+//             i < _for#bound
+//         }) body
+//     }
+//
+// and a 'for' loop over a reference to a vector
+//
+//     for (x in v) body spec { .. }
+//
+// into
+//
+//     {
+//         let _for#vector = v;
+//         let _for#index = 0;
+//         let _for#bound = std::vector::length(_for#vector);
+//         while ({ spec { .. }; _for#index < _for#bound }) {
+//             let x = std::vector::borrow(_for#vector, _for#index);
+//             _for#index = _for#index + 1;
+//             body
+//         }
+//     }
+//
+// where 'std::vector::borrow_mut' is used if the elements are borrowed mutably. In both cases,
+// the counter is advanced before the body runs, so that 'continue' moves on to the next element,
+// and the loop invariant is checked before every evaluation of the bound. The names of the
+// introduced locals cannot be written in source code, so they never capture user variables.
+fn for_loop(
+    loc: Loc,
+    v: Var,
+    sp!(iter_loc, iter_): P::ForIter,
+    body: P::Exp,
+    spec_opt: Option<P::SpecBlock>,
+) -> P::Exp {
+    use P::Exp_ as PE;
+    let e = |e_| sp(loc, e_);
+    let var = |s: &str| Var(sp(loc, s.into()));
+    let name = |v: Var| e(PE::Name(sp(loc, P::NameAccessChain_::One(v.0)), None));
+    let num = |s: &str| e(PE::Value(sp(loc, P::Value_::Num(s.into()))));
+    let bind = |vs: Vec<Var>, rhs: P::Exp| {
+        let bs = vs
+            .into_iter()
+            .map(|v| sp(v.loc(), P::Bind_::Var(v)))
+            .collect();
+        sp(
+            loc,
+            P::SequenceItem_::Bind(sp(loc, bs), None, Box::new(rhs)),
+        )
+    };
+    let seq = |item: P::Exp| sp(item.loc, P::SequenceItem_::Seq(Box::new(item)));
+    let block = |items: Vec<P::SequenceItem>, final_e: P::Exp| {
+        e(PE::Block((vec![], items, None, Box::new(Some(final_e)))))
+    };
+    let assign = |v: Var, rhs: P::Exp| e(PE::Assign(Box::new(name(v)), Box::new(rhs)));
+    let binop = |lhs: P::Exp, op: P::BinOp_, rhs: P::Exp| {
+        e(PE::BinopExp(Box::new(lhs), sp(loc, op), Box::new(rhs)))
+    };
+    let vector_call = |f: &str, args: Vec<P::Exp>| {
+        let std = sp(loc, P::LeadingNameAccess_::Name(sp(loc, "std".into())));
+        let m = sp(loc, (std, sp(loc, "vector".into())));
+        let chain = sp(loc, P::NameAccessChain_::Three(m, sp(loc, f.into())));
+        e(PE::Call(chain, false, None, sp(loc, args)))
+    };
+
+    let bound = var(FOR_BOUND);
+    let (setup, index, cond_prefix, body) = match iter_ {
+        P::ForIter_::Range(lo, hi) => {
+            let index = if v.is_underscore() { var(FOR_INDEX) } else { v };
+            let started = var(FOR_STARTED);
+            let bounds = sp(iter_loc, PE::ExpList(vec![*lo, *hi]));
+            let setup = vec![
+                bind(vec![index, bound], bounds),
+                bind(vec![started], e(PE::Value(sp(loc, P::Value_::Bool(false))))),
+            ];
+            let advance = e(PE::IfElse(
+                Box::new(name(started)),
+                Box::new(assign(index, binop(name(index), P::BinOp_::Add, num("1")))),
+                Some(Box::new(assign(
+                    started,
+                    e(PE::Value(sp(loc, P::Value_::Bool(true)))),
+                ))),
+            ));
+            (setup, index, vec![seq(advance)], body)
+        }
+        P::ForIter_::Vector(mut_, pv) => {
+            let vector = var(FOR_VECTOR);
+            let index = var(FOR_INDEX);
+            // Type errors for what is iterated over are reported at the call to 'length'
+            let vector_ref = sp(iter_loc, name(vector).value);
+            let setup = vec![
+                bind(vec![vector], *pv),
+                bind(vec![index], num("0")),
+                bind(vec![bound], vector_call("length", vec![vector_ref])),
+            ];
+            let borrow = if mut_ { "borrow_mut" } else { "borrow" };
+            let body = block(
+                vec![
+                    bind(
+                        vec![v],
+                        vector_call(borrow, vec![name(vector), name(index)]),
+                    ),
+                    seq(assign(index, binop(name(index), P::BinOp_::Add, num("1")))),
+                ],
+                body,
+            );
+            (setup, index, vec![], body)
+        }
+    };
+
+    let mut cond_items = cond_prefix;
+    if let Some(spec) = spec_opt {
+        cond_items.push(seq(sp(spec.loc, PE::Spec(spec))));
+    }
+    let cond = block(cond_items, binop(name(index), P::BinOp_::Lt, name(bound)));
+    let while_ = e(PE::While(Box::new(cond), Box::new(body)));
+    block(setup, while_)
+}
+
+// Loops over vectors are desugared into calls to 'std::vector', so report a missing module at
+// the loop, rather than at calls that do not appear in the source
+fn check_for_vector_module(context: &mut Context, loc: Loc) -> bool {
+    let std_addr = context
+        .named_address_mapping
+        .as_ref()
+        .and_then(|m| m.get(&Symbol::from("std")).copied());
+    let msg = match std_addr {
+        None => {
+            "'for' loops over vectors call functions of 'std::vector', but address 'std' is not \
+             assigned a value"
+        }
+        Some(addr) => {
+            let mident = sp(
+                loc,
+                ModuleIdent_::new(
+                    Address::Numerical(None, sp(loc, addr)),
+                    ModuleName(sp(loc, "vector".into())),
+                ),
+            );
+            if context.module_members.contains_key(&mident) {
+                return true;
+            }
+            "'for' loops over vectors call functions of 'std::vector', but the module is not \
+             available. Try adding a dependency on the Move standard library"
+        }
+    };
+    context
+        .env
+        .add_diag(diag!(NameResolution::UnboundModule, (loc, msg)));
+    false
+}
+
 fn exp_dotted(context: &mut Context, sp!(loc, pdotted_): P::Exp) -> Option<E::ExpDotted> {
     use E::ExpDotted_ as EE;
     use P::Exp_ as PE;
@@ -0,0 +1,281 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A small document algebra in the style of Wadler's "A prettier printer". The printer describes
+//! the layout of the source as a `Doc`, and `render` picks, for every group, whether it is printed
+//! on a single line or broken over several lines.
+
+/// Number of spaces added by each level of nesting
+pub const INDENT: usize = 4;
+
+#[derive(Debug, Clone)]
+pub enum Doc {
+    Nil,
+    Text(String),
+    // A space if the enclosing group is flat, a newline otherwise
+    Line,
+    // Nothing if the enclosing group is flat, a newline otherwise
+    SoftLine,
+    // Always a newline. The enclosing groups are broken.
+    HardLine,
+    // Requests an empty line at this point, unless at the start of a block or of the file
+    BlankLine,
+    // A comment from the source. See `Comment`.
+    Comment(Comment),
+    Nest(Box<Doc>),
+    Group(Box<Doc>),
+    // Only printed if the enclosing group is broken
+    IfBreak(Box<Doc>),
+    Concat(Vec<Doc>),
+}
+
+/// How a comment sits in the source relative to the surrounding tokens
+#[derive(Debug, Clone)]
+pub struct Comment {
+    pub content: String,
+    // The comment is the first thing on its line. Otherwise it trails the previous token, and it
+    // stays on the line that token is printed on.
+    pub own_line: bool,
+    // The comment is the last thing on its line, which is always the case for '//' comments
+    pub newline_after: bool,
+}
+
+impl Doc {
+    pub fn text(s: impl Into<String>) -> Doc {
+        Doc::Text(s.into())
+    }
+
+    pub fn concat(docs: Vec<Doc>) -> Doc {
+        Doc::Concat(docs)
+    }
+
+    pub fn nest(doc: Doc) -> Doc {
+        Doc::Nest(Box::new(doc))
+    }
+
+    pub fn group(doc: Doc) -> Doc {
+        Doc::Group(Box::new(doc))
+    }
+
+    pub fn if_break(doc: Doc) -> Doc {
+        Doc::IfBreak(Box::new(doc))
+    }
+
+    /// Joins `docs`, placing `sep` between consecutive elements
+    pub fn join(docs: Vec<Doc>, sep: Doc) -> Doc {
+        let mut result = vec![];
+        for (idx, doc) in docs.into_iter().enumerate() {
+            if idx > 0 {
+                result.push(sep.clone());
+            }
+            result.push(doc);
+        }
+        Doc::Concat(result)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+struct Renderer {
+    width: usize,
+    out: String,
+    // A '//' comment was printed, so anything that follows must start on a new line
+    pending_newline: bool,
+    // A block comment was printed inline, so it must be separated from what follows
+    pending_space: bool,
+}
+
+/// Renders `doc`, breaking groups that do not fit in `width` columns
+pub fn render(doc: &Doc, width: usize) -> String {
+    let mut renderer = Renderer {
+        width,
+        out: String::new(),
+        pending_newline: false,
+        pending_space: false,
+    };
+    renderer.render(doc);
+    let mut out = renderer.out;
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+impl Renderer {
+    fn render(&mut self, doc: &Doc) {
+        let mut stack: Vec<(usize, Mode, &Doc)> = vec![(0, Mode::Break, doc)];
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Nil => (),
+                Doc::Text(s) => self.write(indent, s),
+                Doc::Line => match mode {
+                    Mode::Flat => self.write(indent, " "),
+                    Mode::Break => self.newline(indent),
+                },
+                Doc::SoftLine => {
+                    if mode == Mode::Break {
+                        self.newline(indent)
+                    }
+                }
+                Doc::HardLine => self.newline(indent),
+                Doc::BlankLine => self.blank_line(indent),
+                Doc::Comment(comment) => self.comment(indent, comment),
+                Doc::Nest(doc) => stack.push((indent + INDENT, mode, doc)),
+                Doc::Group(doc) => {
+                    let mode = if mode == Mode::Flat || self.fits(doc, &stack) {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+                    stack.push((indent, mode, doc))
+                }
+                Doc::IfBreak(doc) => {
+                    if mode == Mode::Break {
+                        stack.push((indent, mode, doc))
+                    }
+                }
+                Doc::Concat(docs) => {
+                    stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+                }
+            }
+        }
+    }
+
+    // Checks whether `doc` can be printed flat on the rest of the current line, together with
+    // whatever follows it up to the next possible line break
+    fn fits(&self, doc: &Doc, rest: &[(usize, Mode, &Doc)]) -> bool {
+        let mut remaining = self.width as isize - self.current_line().len() as isize;
+        let mut rest = rest.iter().rev();
+        let mut work: Vec<(Mode, &Doc)> = vec![(Mode::Flat, doc)];
+        loop {
+            if remaining < 0 {
+                return false;
+            }
+            let (mode, doc) = match work.pop() {
+                Some(item) => item,
+                None => match rest.next() {
+                    Some((_, mode, doc)) => (*mode, *doc),
+                    None => return true,
+                },
+            };
+            match doc {
+                Doc::Nil => (),
+                Doc::Text(s) => remaining -= s.len() as isize,
+                Doc::Line | Doc::SoftLine if mode == Mode::Flat => {
+                    if matches!(doc, Doc::Line) {
+                        remaining -= 1
+                    }
+                }
+                Doc::Line | Doc::SoftLine | Doc::HardLine | Doc::BlankLine => {
+                    return mode == Mode::Break
+                }
+                Doc::Comment(comment) => {
+                    let breaks_line =
+                        comment.newline_after || comment.own_line || comment.content.contains('\n');
+                    if mode == Mode::Flat && breaks_line {
+                        return false;
+                    }
+                    remaining -= comment.content.len() as isize + 1
+                }
+                Doc::Nest(doc) | Doc::Group(doc) => work.push((mode, doc)),
+                Doc::IfBreak(doc) => {
+                    if mode == Mode::Break {
+                        work.push((mode, doc))
+                    }
+                }
+                Doc::Concat(docs) => work.extend(docs.iter().rev().map(|doc| (mode, doc))),
+            }
+        }
+    }
+
+    fn current_line(&self) -> &str {
+        match self.out.rfind('\n') {
+            Some(idx) => &self.out[idx + 1..],
+            None => &self.out,
+        }
+    }
+
+    fn line_is_blank(&self) -> bool {
+        self.current_line().chars().all(|c| c == ' ')
+    }
+
+    fn trim_end_spaces(&mut self) {
+        let len = self.out.trim_end_matches(' ').len();
+        self.out.truncate(len);
+    }
+
+    fn write(&mut self, indent: usize, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        if self.pending_newline {
+            self.newline(indent);
+        }
+        if self.pending_space {
+            self.pending_space = false;
+            if !s.starts_with([' ', ',', ';', ')', ']', '>']) {
+                self.out.push(' ');
+            }
+        }
+        self.out.push_str(s)
+    }
+
+    fn newline(&mut self, indent: usize) {
+        self.pending_newline = false;
+        self.pending_space = false;
+        self.trim_end_spaces();
+        self.out.push('\n');
+        self.out.extend(std::iter::repeat(' ').take(indent));
+    }
+
+    fn blank_line(&mut self, indent: usize) {
+        if self.pending_newline || !self.line_is_blank() {
+            self.newline(indent)
+        }
+        let before = self.out.trim_end();
+        if before.is_empty() || before.ends_with(['{', '(', '[']) {
+            return;
+        }
+        let idx = self.out.rfind('\n').unwrap();
+        if !self.out[..idx].ends_with('\n') {
+            self.out.insert(idx, '\n');
+        }
+    }
+
+    fn comment(&mut self, indent: usize, comment: &Comment) {
+        let Comment {
+            content,
+            own_line,
+            newline_after,
+        } = comment;
+        let has_previous_line = self.out.contains('\n');
+        if !own_line && self.line_is_blank() && has_previous_line {
+            // The comment trails a token that has already been followed by a newline. Move it
+            // back to the end of that line.
+            let current_indent = self.current_line().len();
+            self.trim_end_spaces();
+            self.out.pop();
+            self.trim_end_spaces();
+            self.out.push(' ');
+            self.out.push_str(content);
+            self.newline(current_indent);
+            return;
+        }
+        if *own_line && !self.line_is_blank() {
+            self.newline(indent);
+        }
+        self.pending_space = false;
+        if !self.line_is_blank() && !self.out.ends_with([' ', '(', '[', '<']) {
+            self.out.push(' ');
+        }
+        self.out.push_str(content);
+        if *newline_after {
+            self.pending_newline = true;
+        } else {
+            self.pending_space = true;
+        }
+    }
+}
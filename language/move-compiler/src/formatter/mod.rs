@@ -0,0 +1,82 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Source formatter for Move, used by `move fmt`. A file is parsed, printed back from its AST with
+//! a fixed layout, and the comments of the original file are re-inserted between the same tokens.
+
+pub mod doc;
+mod printer;
+
+use crate::{
+    diag,
+    diagnostics::{
+        codes::{Bug, Severity},
+        Diagnostics,
+    },
+    parser::{
+        ast::Definition,
+        comments::{extract_comments, verify_string, Comment},
+        syntax::parse_file_string,
+    },
+    shared::{ast_debug, CompilationEnv, Flags},
+};
+use move_command_line_common::files::FileHash;
+use move_ir_types::location::*;
+
+/// Maximum width of a line. Longer lines are only produced if an item cannot be broken.
+pub const MAX_WIDTH: usize = 100;
+
+/// Formats the Move source `input`. Fails with the parser diagnostics if `input` does not parse.
+/// As a safeguard, the formatted source is parsed again and checked to have the same AST and the
+/// same comments as the input.
+pub fn format_string(file_hash: FileHash, input: &str) -> Result<String, Diagnostics> {
+    let (defs, comments) = parse(file_hash, input)?;
+    let doc = printer::Printer::new(input, comments.clone()).definitions(&defs);
+    let output = doc::render(&doc, MAX_WIDTH);
+
+    let mismatch = match parse(file_hash, &output) {
+        Err(_) => Some("the formatted source does not parse"),
+        Ok((output_defs, _)) if display_defs(&output_defs) != display_defs(&defs) => {
+            Some("the formatted source has a different meaning")
+        }
+        Ok((_, output_comments)) if !same_comments(&comments, &output_comments) => {
+            Some("the formatted source lost or reordered comments")
+        }
+        Ok(_) => None,
+    };
+    match mismatch {
+        None => Ok(output),
+        Some(msg) => Err(Diagnostics::from(vec![diag!(
+            Bug::Formatting,
+            (Loc::new(file_hash, 0, 0), msg)
+        )])),
+    }
+}
+
+fn parse(file_hash: FileHash, input: &str) -> Result<(Vec<Definition>, Vec<Comment>), Diagnostics> {
+    verify_string(file_hash, input)?;
+    let mut env = CompilationEnv::new(Flags::empty());
+    let (defs, _) = parse_file_string(&mut env, file_hash, input)?;
+    env.check_diags_at_or_above_severity(Severity::NonblockingError)?;
+    let comments = extract_comments(file_hash, input)?;
+    Ok((defs, comments))
+}
+
+fn display_defs(defs: &[Definition]) -> Vec<String> {
+    defs.iter().map(ast_debug::display).collect()
+}
+
+// Comments are compared without trailing whitespace, which the formatter removes
+fn same_comments(expected: &[Comment], actual: &[Comment]) -> bool {
+    let normalize = |c: &Comment| -> Vec<String> {
+        c.content
+            .lines()
+            .map(|l| l.trim_end().to_string())
+            .collect()
+    };
+    expected.len() == actual.len()
+        && expected
+            .iter()
+            .zip(actual)
+            .all(|(e, a)| normalize(e) == normalize(a))
+}
@@ -0,0 +1,1505 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Translates the parser AST into a `Doc`. The AST does not record comments, so the comments of
+//! the file are kept in source order and each one is emitted before the first AST node that
+//! follows it. Since every node is printed in source order, a comment always ends up between the
+//! same two tokens as in the original file.
+
+use super::doc::{self, Doc};
+use crate::{
+    parser::{ast::*, comments::Comment},
+    shared::Identifier,
+};
+use move_ir_types::location::*;
+
+// Precedence levels of expressions, on the same scale as the binary operators in the parser.
+// Lambdas, quantifiers, assignments and control expressions extend as far to the right as
+// possible, so they always need parentheses when used as an operand.
+const PREC_OPEN: u32 = 0;
+const PREC_UNARY: u32 = 13;
+const PREC_TERM: u32 = 14;
+
+// Spec variables with these names must be declared with 'local', otherwise they are parsed as
+// the spec block member of the same name
+const SPEC_MEMBER_KEYWORDS: &[&str] = &[
+    "assert",
+    "assume",
+    "decreases",
+    "aborts_if",
+    "aborts_with",
+    "succeeds_if",
+    "modifies",
+    "emits",
+    "ensures",
+    "requires",
+    "axiom",
+    "include",
+    "apply",
+    "pragma",
+    "global",
+    "local",
+    "update",
+];
+
+pub struct Printer<'a> {
+    source: &'a str,
+    comments: Vec<Comment>,
+    next_comment: usize,
+}
+
+impl<'a> Printer<'a> {
+    pub fn new(source: &'a str, comments: Vec<Comment>) -> Self {
+        Self {
+            source,
+            comments,
+            next_comment: 0,
+        }
+    }
+
+    //**********************************************************************************************
+    // Comments and blank lines
+    //**********************************************************************************************
+
+    // Emits all comments that start before `pos` and have not been emitted yet
+    fn comments_before(&mut self, pos: u32) -> Doc {
+        let mut docs = vec![];
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.loc.start() >= pos {
+                break;
+            }
+            let start = comment.loc.start() as usize;
+            let end = comment.loc.end() as usize;
+            let own_line = self.newline_before(start);
+            if own_line && self.blank_line_before(start) {
+                docs.push(Doc::BlankLine);
+            }
+            docs.push(Doc::Comment(doc::Comment {
+                content: comment.content.trim_end().to_string(),
+                own_line,
+                newline_after: self.newline_after(end),
+            }));
+            self.next_comment += 1;
+        }
+        if docs.is_empty() {
+            Doc::Nil
+        } else {
+            Doc::concat(docs)
+        }
+    }
+
+    // Emits the comments before an item of a block, and keeps the item separated from the
+    // previous one by an empty line if it was in the source
+    fn item_leading(&mut self, pos: u32) -> Doc {
+        let comments = self.comments_before(pos);
+        if self.blank_line_before(pos as usize) {
+            Doc::concat(vec![comments, Doc::BlankLine])
+        } else {
+            comments
+        }
+    }
+
+    fn newline_before(&self, pos: usize) -> bool {
+        let before = self.source[..pos].trim_end_matches([' ', '\t', '\r']);
+        before.is_empty() || before.ends_with('\n')
+    }
+
+    fn newline_after(&self, pos: usize) -> bool {
+        let after = self.source[pos..].trim_start_matches([' ', '\t', '\r']);
+        after.is_empty() || after.starts_with('\n')
+    }
+
+    fn blank_line_before(&self, pos: usize) -> bool {
+        let before = &self.source[..pos];
+        let whitespace = &before[before.trim_end().len()..];
+        whitespace.matches('\n').count() > 1
+    }
+
+    // The position of the first token at or after `pos`, skipping commas. Used to find the
+    // closing delimiter of a list for which the AST has no location.
+    fn list_end(&self, pos: u32) -> u32 {
+        let bytes = self.source.as_bytes();
+        let mut pos = pos as usize;
+        let mut comments = self.comments[self.next_comment..].iter();
+        loop {
+            while pos < bytes.len() && (bytes[pos].is_ascii_whitespace() || bytes[pos] == b',') {
+                pos += 1;
+            }
+            match comments.find(|c| c.loc.start() as usize >= pos) {
+                Some(c) if c.loc.start() as usize == pos => pos = c.loc.end() as usize,
+                _ => return pos as u32,
+            }
+        }
+    }
+
+    // The start of `keyword` if it immediately precedes `pos`
+    fn keyword_start(&self, pos: u32, keyword: &str) -> u32 {
+        let before = self.source[..pos as usize].trim_end();
+        match before.strip_suffix(keyword) {
+            Some(rest) => rest.len() as u32,
+            None => pos,
+        }
+    }
+
+    fn source_text(&self, loc: Loc) -> Doc {
+        Doc::text(&self.source[loc.usize_range()])
+    }
+
+    //**********************************************************************************************
+    // Lists
+    //**********************************************************************************************
+
+    // A list that is either printed on one line, or with one item per line and a trailing comma
+    fn comma_list(open: &str, items: Vec<Doc>, close: &str, dangling: Doc) -> Doc {
+        if items.is_empty() && matches!(dangling, Doc::Nil) {
+            return Doc::text(format!("{}{}", open, close));
+        }
+        let trailing_comma = if items.is_empty() {
+            Doc::Nil
+        } else {
+            Doc::if_break(Doc::text(","))
+        };
+        Doc::group(Doc::concat(vec![
+            Doc::text(open),
+            Doc::nest(Doc::concat(vec![
+                Doc::SoftLine,
+                Doc::join(items, Doc::concat(vec![Doc::text(","), Doc::Line])),
+                trailing_comma,
+                dangling,
+            ])),
+            Doc::SoftLine,
+            Doc::text(close),
+        ]))
+    }
+
+    // Same as `comma_list`, but with spaces inside the braces when printed on one line
+    fn brace_list(items: Vec<Doc>, dangling: Doc) -> Doc {
+        if items.is_empty() && matches!(dangling, Doc::Nil) {
+            return Doc::text("{}");
+        }
+        Doc::group(Doc::concat(vec![
+            Doc::text("{"),
+            Doc::nest(Doc::concat(vec![
+                Doc::Line,
+                Doc::join(items, Doc::concat(vec![Doc::text(","), Doc::Line])),
+                Doc::if_break(Doc::text(",")),
+                dangling,
+            ])),
+            Doc::Line,
+            Doc::text("}"),
+        ]))
+    }
+
+    // A block whose items are always printed on separate lines. Each item of `body` should start
+    // with a `HardLine`.
+    fn braces(&mut self, mut body: Vec<Doc>, end: u32) -> Doc {
+        let dangling = self.comments_before(end);
+        if !matches!(dangling, Doc::Nil) {
+            body.push(dangling)
+        }
+        if body.is_empty() {
+            return Doc::text("{}");
+        }
+        Doc::concat(vec![
+            Doc::text("{"),
+            Doc::nest(Doc::concat(body)),
+            Doc::HardLine,
+            Doc::text("}"),
+        ])
+    }
+
+    //**********************************************************************************************
+    // Definitions
+    //**********************************************************************************************
+
+    pub fn definitions(&mut self, defs: &[Definition]) -> Doc {
+        let mut docs = vec![];
+        for (idx, def) in defs.iter().enumerate() {
+            if idx > 0 {
+                docs.push(Doc::HardLine);
+            }
+            let start = match def {
+                Definition::Module(m) => attributes_start(&m.attributes, m.loc.start()),
+                Definition::Address(a) => {
+                    let start = self.keyword_start(a.loc.start(), "address");
+                    attributes_start(&a.attributes, start)
+                }
+                Definition::Script(s) => attributes_start(&s.attributes, s.loc.start()),
+            };
+            docs.push(self.item_leading(start));
+            docs.push(match def {
+                Definition::Module(m) => self.module(m),
+                Definition::Address(a) => self.address(a),
+                Definition::Script(s) => self.script(s),
+            });
+        }
+        docs.push(self.comments_before(u32::MAX));
+        Doc::concat(docs)
+    }
+
+    fn address(&mut self, a: &AddressDefinition) -> Doc {
+        let AddressDefinition {
+            attributes,
+            loc: _,
+            addr,
+            modules,
+        } = a;
+        let mut docs = vec![
+            self.attributes(attributes),
+            Doc::text("address "),
+            Doc::text(self.leading_name_access(addr)),
+            Doc::text(" {"),
+        ];
+        // Modules are not indented in an address block
+        for m in modules {
+            docs.push(Doc::HardLine);
+            docs.push(self.item_leading(attributes_start(&m.attributes, m.loc.start())));
+            docs.push(self.module(m));
+        }
+        let end = match modules.last() {
+            Some(m) => self.list_end(m.loc.end()),
+            None => self.list_end(addr.loc.end() + 1),
+        };
+        docs.push(self.comments_before(end));
+        docs.push(Doc::HardLine);
+        docs.push(Doc::text("}"));
+        Doc::concat(docs)
+    }
+
+    fn module(&mut self, m: &ModuleDefinition) -> Doc {
+        let ModuleDefinition {
+            attributes,
+            loc,
+            address,
+            name,
+            is_spec_module,
+            members,
+        } = m;
+        let mut docs = vec![
+            self.attributes(attributes),
+            Doc::text(if *is_spec_module { "spec " } else { "module " }),
+        ];
+        if let Some(address) = address {
+            docs.push(Doc::text(self.leading_name_access(address)));
+            docs.push(Doc::text("::"));
+        }
+        docs.push(Doc::text(format!("{} ", name)));
+        let mut body = vec![];
+        for member in members {
+            body.push(Doc::HardLine);
+            let start = self.module_member_start(member);
+            body.push(self.item_leading(start));
+            body.push(self.module_member(member));
+        }
+        docs.push(self.braces(body, loc.end()));
+        Doc::concat(docs)
+    }
+
+    fn module_member_start(&self, member: &ModuleMember) -> u32 {
+        match member {
+            ModuleMember::Function(f) => attributes_start(&f.attributes, f.loc.start()),
+            ModuleMember::Struct(s) => attributes_start(&s.attributes, s.loc.start()),
+            ModuleMember::Use(u) => self.use_start(u),
+            ModuleMember::Friend(f) => attributes_start(&f.attributes, f.loc.start()),
+            ModuleMember::Constant(c) => attributes_start(&c.attributes, c.loc.start()),
+            ModuleMember::Spec(s) => attributes_start(&s.value.attributes, s.loc.start()),
+        }
+    }
+
+    fn module_member(&mut self, member: &ModuleMember) -> Doc {
+        match member {
+            ModuleMember::Function(f) => self.function(f),
+            ModuleMember::Struct(s) => self.struct_def(s),
+            ModuleMember::Use(u) => self.use_decl(u),
+            ModuleMember::Friend(f) => self.friend_decl(f),
+            ModuleMember::Constant(c) => self.constant(c),
+            ModuleMember::Spec(s) => self.module_spec_block(s),
+        }
+    }
+
+    fn script(&mut self, s: &Script) -> Doc {
+        let Script {
+            attributes,
+            loc,
+            uses,
+            constants,
+            function,
+            specs,
+        } = s;
+        let mut docs = vec![self.attributes(attributes), Doc::text("script {")];
+        // The contents of a script are not indented
+        for u in uses {
+            docs.push(Doc::HardLine);
+            docs.push(self.item_leading(self.use_start(u)));
+            docs.push(self.use_decl(u));
+        }
+        for c in constants {
+            docs.push(Doc::HardLine);
+            docs.push(self.item_leading(attributes_start(&c.attributes, c.loc.start())));
+            docs.push(self.constant(c));
+        }
+        docs.push(Doc::HardLine);
+        let start = attributes_start(&function.attributes, function.loc.start());
+        docs.push(self.item_leading(start));
+        docs.push(self.function(function));
+        for spec in specs {
+            docs.push(Doc::HardLine);
+            let start = attributes_start(&spec.value.attributes, spec.loc.start());
+            docs.push(self.item_leading(start));
+            docs.push(self.spec_block(spec));
+        }
+        docs.push(self.comments_before(loc.end()));
+        docs.push(Doc::HardLine);
+        docs.push(Doc::text("}"));
+        Doc::concat(docs)
+    }
+
+    //**********************************************************************************************
+    // Attributes
+    //**********************************************************************************************
+
+    fn attributes(&mut self, attributes: &[Attributes]) -> Doc {
+        let mut docs = vec![];
+        for attrs in attributes {
+            docs.push(self.comments_before(attrs.loc.start()));
+            let items = attrs.value.iter().map(|a| self.attribute(a)).collect();
+            docs.push(Self::comma_list("#[", items, "]", Doc::Nil));
+            docs.push(Doc::HardLine);
+        }
+        Doc::concat(docs)
+    }
+
+    fn attribute(&self, attribute: &Attribute) -> Doc {
+        match &attribute.value {
+            Attribute_::Name(n) => Doc::text(n.value.as_str()),
+            Attribute_::Assigned(n, v) => {
+                let value = match &v.value {
+                    AttributeValue_::Value(v) => self.source_text(v.loc),
+                    AttributeValue_::ModuleAccess(chain) => Doc::text(self.name_chain(chain)),
+                };
+                Doc::concat(vec![Doc::text(format!("{} = ", n)), value])
+            }
+            Attribute_::Parameterized(n, attrs) => {
+                let items = attrs.value.iter().map(|a| self.attribute(a)).collect();
+                Doc::concat(vec![
+                    Doc::text(n.value.as_str()),
+                    Self::comma_list("(", items, ")", Doc::Nil),
+                ])
+            }
+        }
+    }
+
+    //**********************************************************************************************
+    // Module members
+    //**********************************************************************************************
+
+    fn use_start(&self, u: &UseDecl) -> u32 {
+        let ident_loc = match &u.use_ {
+            Use::Module(ident, _) | Use::Members(ident, _) => ident.loc,
+        };
+        let start = self.keyword_start(ident_loc.start(), "use");
+        attributes_start(&u.attributes, start)
+    }
+
+    fn use_decl(&mut self, u: &UseDecl) -> Doc {
+        let UseDecl { attributes, use_ } = u;
+        let mut docs = vec![self.attributes(attributes), Doc::text("use ")];
+        match use_ {
+            Use::Module(ident, alias) => {
+                docs.push(Doc::text(self.module_ident(ident)));
+                if let Some(alias) = alias {
+                    docs.push(Doc::text(format!(" as {}", alias)));
+                }
+            }
+            Use::Members(ident, members) => {
+                docs.push(Doc::text(format!("{}::", self.module_ident(ident))));
+                let members: Vec<_> = members
+                    .iter()
+                    .map(|(member, alias)| match alias {
+                        Some(alias) => Doc::text(format!("{} as {}", member, alias)),
+                        None => Doc::text(member.value.as_str()),
+                    })
+                    .collect();
+                if members.len() == 1 {
+                    docs.extend(members)
+                } else {
+                    docs.push(Self::comma_list("{", members, "}", Doc::Nil))
+                }
+            }
+        }
+        docs.push(Doc::text(";"));
+        Doc::concat(docs)
+    }
+
+    fn friend_decl(&mut self, f: &FriendDecl) -> Doc {
+        let FriendDecl {
+            attributes,
+            loc: _,
+            friend,
+        } = f;
+        Doc::concat(vec![
+            self.attributes(attributes),
+            Doc::text(format!("friend {};", self.name_chain(friend))),
+        ])
+    }
+
+    fn constant(&mut self, c: &Constant) -> Doc {
+        let Constant {
+            attributes,
+            loc: _,
+            signature,
+            name,
+            value,
+        } = c;
+        Doc::concat(vec![
+            self.attributes(attributes),
+            Doc::text(format!("const {}: ", name)),
+            self.type_(signature),
+            Doc::text(" = "),
+            self.exp(value),
+            Doc::text(";"),
+        ])
+    }
+
+    fn struct_def(&mut self, s: &StructDefinition) -> Doc {
+        let StructDefinition {
+            attributes,
+            loc,
+            abilities,
+            name,
+            type_parameters,
+            fields,
+        } = s;
+        let mut docs = vec![self.attributes(attributes)];
+        if let StructFields::Native(_) = fields {
+            docs.push(Doc::text("native "));
+        }
+        docs.push(Doc::text(format!("struct {}", name)));
+        docs.push(self.struct_type_parameters(type_parameters));
+        docs.push(Self::ability_decls(abilities));
+        match fields {
+            StructFields::Native(_) => docs.push(Doc::text(";")),
+            StructFields::Defined(fields) => {
+                let mut body = vec![];
+                for (field, ty) in fields {
+                    body.push(Doc::HardLine);
+                    body.push(self.comments_before(field.loc().start()));
+                    body.push(Doc::text(format!("{}: ", field)));
+                    body.push(self.type_(ty));
+                    body.push(Doc::text(","));
+                }
+                docs.push(Doc::text(" "));
+                docs.push(self.braces(body, loc.end()));
+            }
+        }
+        Doc::concat(docs)
+    }
+
+    fn ability_decls(abilities: &[Ability]) -> Doc {
+        if abilities.is_empty() {
+            return Doc::Nil;
+        }
+        let abilities: Vec<_> = abilities.iter().map(|a| a.value.to_string()).collect();
+        Doc::text(format!(" has {}", abilities.join(", ")))
+    }
+
+    fn function(&mut self, f: &Function) -> Doc {
+        let Function {
+            attributes,
+            loc: _,
+            visibility,
+            entry,
+            inline,
+            signature,
+            acquires,
+            name,
+            body,
+        } = f;
+        let mut docs = vec![self.attributes(attributes)];
+        let mut header = vec![];
+        if *visibility != Visibility::Internal {
+            header.push(Doc::text(format!("{} ", visibility)));
+        }
+        // 'public(script)' implies 'entry'
+        match (visibility, entry) {
+            (Visibility::Script(vis_loc), Some(entry_loc)) if vis_loc == entry_loc => (),
+            (_, Some(_)) => header.push(Doc::text(format!("{} ", ENTRY_MODIFIER))),
+            (_, None) => (),
+        }
+        if let FunctionBody_::Native = body.value {
+            header.push(Doc::text(format!("{} ", NATIVE_MODIFIER)));
+        }
+        if *inline {
+            header.push(Doc::text(format!("{} ", INLINE_MODIFIER)));
+        }
+        header.push(Doc::text(format!("fun {}", name)));
+        header.push(self.signature(signature, /* omit unit */ true));
+        if !acquires.is_empty() {
+            let acquires: Vec<_> = acquires.iter().map(|a| self.name_chain(a)).collect();
+            header.push(Doc::text(format!(" acquires {}", acquires.join(", "))));
+        }
+        docs.push(Doc::group(Doc::concat(header)));
+        docs.push(self.function_body(body));
+        Doc::concat(docs)
+    }
+
+    fn signature(&mut self, signature: &FunctionSignature, omit_unit: bool) -> Doc {
+        let FunctionSignature {
+            type_parameters,
+            parameters,
+            return_type,
+        } = signature;
+        let mut items = vec![];
+        for (var, ty) in parameters {
+            items.push(Doc::concat(vec![
+                self.comments_before(var.loc().start()),
+                Doc::text(format!("{}: ", var)),
+                self.type_(ty),
+            ]))
+        }
+        let dangling = match parameters.last() {
+            Some((_, ty)) => {
+                let end = self.list_end(ty.loc.end());
+                self.comments_before(end)
+            }
+            None => Doc::Nil,
+        };
+        let mut docs = vec![
+            Self::type_parameters(type_parameters),
+            Self::comma_list("(", items, ")", dangling),
+        ];
+        if !(omit_unit && return_type.value == Type_::Unit) {
+            docs.push(Doc::text(": "));
+            docs.push(self.type_(return_type));
+        }
+        Doc::concat(docs)
+    }
+
+    fn function_body(&mut self, body: &FunctionBody) -> Doc {
+        match &body.value {
+            FunctionBody_::Native => Doc::text(";"),
+            FunctionBody_::Defined(seq) => {
+                let leading = self.comments_before(body.loc.start());
+                Doc::concat(vec![
+                    Doc::text(" "),
+                    leading,
+                    self.sequence(seq, body.loc.end(), /* inline */ false),
+                ])
+            }
+        }
+    }
+
+    //**********************************************************************************************
+    // Specification blocks
+    //**********************************************************************************************
+
+    fn module_spec_block(&mut self, sb: &SpecBlock) -> Doc {
+        let SpecBlock_ {
+            attributes,
+            target,
+            uses: _,
+            members,
+        } = &sb.value;
+        // A module invariant or spec function declared on its own is represented as a module
+        // spec block with an empty target location
+        let is_singleton = target.value == SpecBlockTarget_::Module
+            && target.loc.start() == target.loc.end()
+            && members.len() == 1;
+        if !is_singleton {
+            return self.spec_block(sb);
+        }
+        let member = &members[0];
+        let prefix = match &member.value {
+            SpecBlockMember_::Function { .. } => Doc::text("spec "),
+            _ => Doc::Nil,
+        };
+        Doc::concat(vec![
+            self.attributes(attributes),
+            prefix,
+            self.spec_member(member),
+        ])
+    }
+
+    fn spec_block(&mut self, sb: &SpecBlock) -> Doc {
+        let SpecBlock_ {
+            attributes,
+            target,
+            uses,
+            members,
+        } = &sb.value;
+        let mut docs = vec![self.attributes(attributes), Doc::text("spec")];
+        match &target.value {
+            SpecBlockTarget_::Code => (),
+            SpecBlockTarget_::Module => docs.push(Doc::text(" module")),
+            SpecBlockTarget_::Member(name, signature) => {
+                docs.push(Doc::text(format!(" {}", name)));
+                if let Some(signature) = signature {
+                    docs.push(Doc::group(self.signature(signature, true)));
+                }
+            }
+            SpecBlockTarget_::Schema(name, type_parameters) => {
+                docs.push(Doc::text(format!(" schema {}", name)));
+                docs.push(Self::type_parameters(type_parameters));
+            }
+        }
+        docs.push(Doc::text(" "));
+        let mut body = vec![];
+        for u in uses {
+            body.push(Doc::HardLine);
+            body.push(self.item_leading(self.use_start(u)));
+            body.push(self.use_decl(u));
+        }
+        for member in members {
+            body.push(Doc::HardLine);
+            body.push(self.item_leading(member.loc.start()));
+            body.push(self.spec_member(member));
+        }
+        docs.push(self.braces(body, sb.loc.end()));
+        Doc::concat(docs)
+    }
+
+    fn spec_member(&mut self, member: &SpecBlockMember) -> Doc {
+        match &member.value {
+            SpecBlockMember_::Condition {
+                kind,
+                properties,
+                exp,
+                additional_exps,
+            } => {
+                let mut docs = vec![Self::condition_kind(kind)];
+                if !properties.is_empty() {
+                    docs.push(Doc::text(" "));
+                    docs.push(self.properties(properties));
+                }
+                match &kind.value {
+                    // These only have the additional expressions, 'exp' is a placeholder
+                    SpecConditionKind_::AbortsWith | SpecConditionKind_::Modifies => {
+                        let exps = additional_exps.iter().map(|e| self.exp(e)).collect();
+                        docs.push(Doc::text(" "));
+                        docs.push(Doc::join(exps, Doc::text(", ")));
+                    }
+                    SpecConditionKind_::AbortsIf => {
+                        docs.push(Doc::text(" "));
+                        docs.push(self.exp(exp));
+                        if let Some(code) = additional_exps.first() {
+                            docs.push(Doc::text(" with "));
+                            docs.push(self.exp(code));
+                        }
+                    }
+                    SpecConditionKind_::Emits => {
+                        docs.push(Doc::text(" "));
+                        docs.push(self.exp(exp));
+                        if let Some(handle) = additional_exps.first() {
+                            docs.push(Doc::text(" to "));
+                            docs.push(self.exp(handle));
+                        }
+                        if let Some(cond) = additional_exps.get(1) {
+                            docs.push(Doc::text(" if "));
+                            docs.push(self.exp(cond));
+                        }
+                    }
+                    _ => {
+                        docs.push(Doc::text(" "));
+                        docs.push(self.exp(exp));
+                    }
+                }
+                docs.push(Doc::text(";"));
+                Doc::concat(docs)
+            }
+            SpecBlockMember_::Function {
+                uninterpreted,
+                name,
+                signature,
+                body,
+            } => {
+                let mut docs = vec![];
+                if !uninterpreted && body.value == FunctionBody_::Native {
+                    docs.push(Doc::text(format!("{} ", NATIVE_MODIFIER)));
+                }
+                docs.push(Doc::text(format!("fun {}", name)));
+                docs.push(self.signature(signature, /* omit unit */ false));
+                Doc::concat(vec![
+                    Doc::group(Doc::concat(docs)),
+                    self.function_body(body),
+                ])
+            }
+            SpecBlockMember_::Variable {
+                is_global,
+                name,
+                type_parameters,
+                type_,
+                init,
+            } => {
+                let mut docs = vec![];
+                if *is_global {
+                    docs.push(Doc::text("global "));
+                } else if SPEC_MEMBER_KEYWORDS.contains(&name.value.as_str()) {
+                    docs.push(Doc::text("local "));
+                }
+                docs.push(Doc::text(name.value.as_str()));
+                docs.push(Self::type_parameters(type_parameters));
+                docs.push(Doc::text(": "));
+                docs.push(self.type_(type_));
+                if let Some(init) = init {
+                    docs.push(Doc::text(" = "));
+                    docs.push(self.exp(init));
+                }
+                docs.push(Doc::text(";"));
+                Doc::concat(docs)
+            }
+            SpecBlockMember_::Let {
+                name,
+                post_state,
+                def,
+            } => Doc::concat(vec![
+                Doc::text(if *post_state { "let post " } else { "let " }),
+                Doc::text(format!("{} = ", name)),
+                self.exp(def),
+                Doc::text(";"),
+            ]),
+            SpecBlockMember_::Update { lhs, rhs } => Doc::concat(vec![
+                Doc::text("update "),
+                self.operand(lhs, PREC_UNARY),
+                Doc::text(" = "),
+                self.exp(rhs),
+                Doc::text(";"),
+            ]),
+            SpecBlockMember_::Include { properties, exp } => {
+                let mut docs = vec![Doc::text("include ")];
+                if !properties.is_empty() {
+                    docs.push(self.properties(properties));
+                    docs.push(Doc::text(" "));
+                }
+                docs.push(self.exp(exp));
+                docs.push(Doc::text(";"));
+                Doc::concat(docs)
+            }
+            SpecBlockMember_::Apply {
+                exp,
+                patterns,
+                exclusion_patterns,
+            } => {
+                let mut docs = vec![Doc::text("apply "), self.exp(exp), Doc::text(" to ")];
+                let patterns: Vec<_> = patterns.iter().map(Self::apply_pattern).collect();
+                docs.push(Doc::text(patterns.join(", ")));
+                if !exclusion_patterns.is_empty() {
+                    let patterns: Vec<_> =
+                        exclusion_patterns.iter().map(Self::apply_pattern).collect();
+                    docs.push(Doc::text(format!(" except {}", patterns.join(", "))));
+                }
+                docs.push(Doc::text(";"));
+                Doc::concat(docs)
+            }
+            SpecBlockMember_::Pragma { properties } => {
+                if properties.is_empty() {
+                    return Doc::text("pragma;");
+                }
+                let properties: Vec<_> = properties.iter().map(|p| self.property(p)).collect();
+                Doc::concat(vec![
+                    Doc::text("pragma "),
+                    Doc::join(properties, Doc::text(", ")),
+                    Doc::text(";"),
+                ])
+            }
+        }
+    }
+
+    fn condition_kind(kind: &SpecConditionKind) -> Doc {
+        let (keyword, type_parameters) = match &kind.value {
+            SpecConditionKind_::Assert => ("assert", None),
+            SpecConditionKind_::Assume => ("assume", None),
+            SpecConditionKind_::Decreases => ("decreases", None),
+            SpecConditionKind_::AbortsIf => ("aborts_if", None),
+            SpecConditionKind_::AbortsWith => ("aborts_with", None),
+            SpecConditionKind_::SucceedsIf => ("succeeds_if", None),
+            SpecConditionKind_::Modifies => ("modifies", None),
+            SpecConditionKind_::Emits => ("emits", None),
+            SpecConditionKind_::Ensures => ("ensures", None),
+            SpecConditionKind_::Requires => ("requires", None),
+            SpecConditionKind_::Invariant(tps) => ("invariant", Some(tps)),
+            SpecConditionKind_::InvariantUpdate(tps) => {
+                return Doc::concat(vec![
+                    Doc::text("invariant"),
+                    Self::type_parameters(tps),
+                    Doc::text(" update"),
+                ])
+            }
+            SpecConditionKind_::Axiom(tps) => ("axiom", Some(tps)),
+        };
+        match type_parameters {
+            Some(tps) => Doc::concat(vec![Doc::text(keyword), Self::type_parameters(tps)]),
+            None => Doc::text(keyword),
+        }
+    }
+
+    fn properties(&self, properties: &[PragmaProperty]) -> Doc {
+        let properties = properties.iter().map(|p| self.property(p)).collect();
+        Self::comma_list("[", properties, "]", Doc::Nil)
+    }
+
+    fn property(&self, property: &PragmaProperty) -> Doc {
+        let PragmaProperty_ { name, value } = &property.value;
+        match value {
+            None => Doc::text(name.value.as_str()),
+            Some(PragmaValue::Literal(v)) => Doc::concat(vec![
+                Doc::text(format!("{} = ", name)),
+                self.source_text(v.loc),
+            ]),
+            Some(PragmaValue::Ident(chain)) => {
+                Doc::text(format!("{} = {}", name, self.name_chain(chain)))
+            }
+        }
+    }
+
+    fn apply_pattern(pattern: &SpecApplyPattern) -> String {
+        let SpecApplyPattern_ {
+            visibility,
+            name_pattern,
+            type_parameters,
+        } = &pattern.value;
+        let mut s = match visibility {
+            Some(Visibility::Internal) => "internal ".to_string(),
+            Some(vis) => format!("{} ", vis),
+            None => String::new(),
+        };
+        for fragment in name_pattern {
+            match &fragment.value {
+                SpecApplyFragment_::Wildcard => s.push('*'),
+                SpecApplyFragment_::NamePart(n) => s.push_str(n.value.as_str()),
+            }
+        }
+        s.push_str(&type_parameters_string(type_parameters));
+        s
+    }
+
+    //**********************************************************************************************
+    // Names and types
+    //**********************************************************************************************
+
+    fn leading_name_access(&self, ln: &LeadingNameAccess) -> String {
+        match &ln.value {
+            LeadingNameAccess_::Name(n) => n.value.to_string(),
+            // Keep the address as written
+            LeadingNameAccess_::AnonymousAddress(_) => {
+                self.source[ln.loc.usize_range()].to_string()
+            }
+        }
+    }
+
+    fn module_ident(&self, ident: &ModuleIdent) -> String {
+        let ModuleIdent_ { address, module } = &ident.value;
+        format!("{}::{}", self.leading_name_access(address), module)
+    }
+
+    fn name_chain(&self, chain: &NameAccessChain) -> String {
+        match &chain.value {
+            NameAccessChain_::One(n) => n.value.to_string(),
+            NameAccessChain_::Two(ln, n) => format!("{}::{}", self.leading_name_access(ln), n),
+            NameAccessChain_::Three(sp!(_, (ln, n2)), n3) => {
+                format!("{}::{}::{}", self.leading_name_access(ln), n2, n3)
+            }
+        }
+    }
+
+    fn type_parameters(type_parameters: &[(Name, Vec<Ability>)]) -> Doc {
+        Doc::text(type_parameters_string(type_parameters))
+    }
+
+    fn struct_type_parameters(&self, type_parameters: &[StructTypeParameter]) -> Doc {
+        if type_parameters.is_empty() {
+            return Doc::Nil;
+        }
+        let tps: Vec<_> = type_parameters
+            .iter()
+            .map(|tp| {
+                let phantom = if tp.is_phantom { "phantom " } else { "" };
+                format!(
+                    "{}{}",
+                    phantom,
+                    type_parameter_string(&tp.name, &tp.constraints)
+                )
+            })
+            .collect();
+        Doc::text(format!("<{}>", tps.join(", ")))
+    }
+
+    fn type_(&self, ty: &Type) -> Doc {
+        Doc::text(self.type_string(ty))
+    }
+
+    fn type_string(&self, ty: &Type) -> String {
+        match &ty.value {
+            Type_::Apply(n, tys) => {
+                if tys.is_empty() {
+                    self.name_chain(n)
+                } else {
+                    format!("{}<{}>", self.name_chain(n), self.types_string(tys))
+                }
+            }
+            Type_::Ref(mut_, ty) => {
+                let prefix = if *mut_ { "&mut " } else { "&" };
+                format!("{}{}", prefix, self.type_string(ty))
+            }
+            Type_::Fun(args, result) => {
+                format!("|{}| {}", self.types_string(args), self.type_string(result))
+            }
+            Type_::Unit => "()".to_string(),
+            Type_::Multiple(tys) => format!("({})", self.types_string(tys)),
+        }
+    }
+
+    fn types_string(&self, tys: &[Type]) -> String {
+        let tys: Vec<_> = tys.iter().map(|ty| self.type_string(ty)).collect();
+        tys.join(", ")
+    }
+
+    // Type arguments must directly follow the name, otherwise the '<' is parsed as an operator
+    fn type_args(&self, tys: &Option<Vec<Type>>) -> Doc {
+        match tys {
+            Some(tys) => Doc::text(format!("<{}>", self.types_string(tys))),
+            None => Doc::Nil,
+        }
+    }
+
+    //**********************************************************************************************
+    // Sequences
+    //**********************************************************************************************
+
+    // Prints a block. If `inline` is set, a block with only a final expression may be printed on
+    // a single line.
+    fn sequence(&mut self, seq: &Sequence, end: u32, inline: bool) -> Doc {
+        let (uses, items, _, last) = seq;
+        let last: &Option<Exp> = last;
+        let has_comments = self
+            .comments
+            .get(self.next_comment)
+            .map_or(false, |c| c.loc.start() < end);
+        if inline && uses.is_empty() && items.is_empty() && !has_comments {
+            if let Some(e) = last {
+                return Doc::group(Doc::concat(vec![
+                    Doc::text("{"),
+                    Doc::nest(Doc::concat(vec![Doc::Line, self.exp(e)])),
+                    Doc::Line,
+                    Doc::text("}"),
+                ]));
+            }
+        }
+        let mut body = vec![];
+        for u in uses {
+            body.push(Doc::HardLine);
+            body.push(self.item_leading(self.use_start(u)));
+            body.push(self.use_decl(u));
+        }
+        for item in items {
+            body.push(Doc::HardLine);
+            body.push(self.item_leading(item.loc.start()));
+            body.push(self.sequence_item(item));
+            body.push(Doc::text(";"));
+        }
+        if let Some(e) = last {
+            body.push(Doc::HardLine);
+            body.push(self.item_leading(e.loc.start()));
+            body.push(self.exp(e));
+        }
+        self.braces(body, end)
+    }
+
+    fn sequence_item(&mut self, item: &SequenceItem) -> Doc {
+        match &item.value {
+            SequenceItem_::Seq(e) => self.exp(e),
+            SequenceItem_::Declare(binds, ty) => {
+                let mut docs = vec![Doc::text("let "), self.bind_list(binds)];
+                if let Some(ty) = ty {
+                    docs.push(Doc::text(": "));
+                    docs.push(self.type_(ty));
+                }
+                Doc::concat(docs)
+            }
+            SequenceItem_::Bind(binds, ty, e) => {
+                let mut docs = vec![Doc::text("let "), self.bind_list(binds)];
+                if let Some(ty) = ty {
+                    docs.push(Doc::text(": "));
+                    docs.push(self.type_(ty));
+                }
+                docs.push(Doc::text(" = "));
+                docs.push(self.exp(e));
+                Doc::concat(docs)
+            }
+        }
+    }
+
+    fn bind_list(&mut self, binds: &BindList) -> Doc {
+        if binds.value.len() == 1 {
+            return self.bind(&binds.value[0]);
+        }
+        let items = binds.value.iter().map(|b| self.bind(b)).collect();
+        let dangling = self.comments_before(binds.loc.end());
+        Self::comma_list("(", items, ")", dangling)
+    }
+
+    fn bind(&mut self, bind: &Bind) -> Doc {
+        let leading = self.comments_before(bind.loc.start());
+        let doc = match &bind.value {
+            Bind_::Var(v) => Doc::text(v.to_string()),
+            Bind_::Unpack(n, tys, fields) => {
+                let mut items = vec![];
+                for (field, b) in fields {
+                    let leading = self.comments_before(field.loc().start());
+                    let item = match &b.value {
+                        Bind_::Var(v) if v.value() == field.value() => Doc::text(field.to_string()),
+                        _ => Doc::concat(vec![Doc::text(format!("{}: ", field)), self.bind(b)]),
+                    };
+                    items.push(Doc::concat(vec![leading, item]));
+                }
+                let dangling = self.comments_before(bind.loc.end());
+                Doc::concat(vec![
+                    Doc::text(self.name_chain(n)),
+                    self.type_args(tys),
+                    Doc::text(" "),
+                    Self::brace_list(items, dangling),
+                ])
+            }
+        };
+        Doc::concat(vec![leading, doc])
+    }
+
+    //**********************************************************************************************
+    // Expressions
+    //**********************************************************************************************
+
+    fn exp(&mut self, e: &Exp) -> Doc {
+        let leading = self.comments_before(e.loc.start());
+        let doc = self.exp_(e);
+        Doc::concat(vec![leading, doc])
+    }
+
+    // Prints `e`, in parentheses if it binds less tightly than `min_prec`
+    fn operand(&mut self, e: &Exp, min_prec: u32) -> Doc {
+        if exp_precedence(&e.value) < min_prec {
+            Doc::concat(vec![Doc::text("("), self.exp(e), Doc::text(")")])
+        } else {
+            self.exp(e)
+        }
+    }
+
+    // Prints the body of a control expression. If it is not a block but starts with one, the
+    // parser would stop after that block, so it needs parentheses.
+    fn branch(&mut self, e: &Exp) -> Doc {
+        if !matches!(e.value, Exp_::Block(_)) && starts_with_block(&e.value) {
+            Doc::concat(vec![Doc::text("("), self.exp(e), Doc::text(")")])
+        } else {
+            self.exp(e)
+        }
+    }
+
+    fn loop_body(&mut self, body: &Exp) -> Doc {
+        if let Exp_::Block(_) = body.value {
+            Doc::concat(vec![Doc::text(" "), self.exp(body)])
+        } else {
+            Doc::group(Doc::nest(Doc::concat(vec![Doc::Line, self.branch(body)])))
+        }
+    }
+
+    fn exp_(&mut self, e: &Exp) -> Doc {
+        match &e.value {
+            Exp_::Value(v) => self.source_text(v.loc),
+            Exp_::Move(v) => Doc::text(format!("move {}", v)),
+            Exp_::Copy(v) => Doc::text(format!("copy {}", v)),
+            Exp_::Name(n, tys) => {
+                Doc::concat(vec![Doc::text(self.name_chain(n)), self.type_args(tys)])
+            }
+            Exp_::Call(n, is_macro, tys, args) => {
+                let items = args.value.iter().map(|arg| self.exp(arg)).collect();
+                let dangling = self.comments_before(args.loc.end());
+                Doc::concat(vec![
+                    Doc::text(self.name_chain(n)),
+                    Doc::text(if *is_macro { "!" } else { "" }),
+                    self.type_args(tys),
+                    Self::comma_list("(", items, ")", dangling),
+                ])
+            }
+            Exp_::Pack(n, tys, fields) => {
+                let mut items = vec![];
+                for (field, arg) in fields {
+                    let leading = self.comments_before(field.loc().start());
+                    let item = match &arg.value {
+                        Exp_::Name(sp!(_, NameAccessChain_::One(n)), None)
+                            if n.value == field.value() =>
+                        {
+                            Doc::text(field.to_string())
+                        }
+                        _ => Doc::concat(vec![Doc::text(format!("{}: ", field)), self.exp(arg)]),
+                    };
+                    items.push(Doc::concat(vec![leading, item]));
+                }
+                let dangling = self.comments_before(e.loc.end());
+                Doc::concat(vec![
+                    Doc::text(self.name_chain(n)),
+                    self.type_args(tys),
+                    Doc::text(" "),
+                    Self::brace_list(items, dangling),
+                ])
+            }
+            Exp_::Vector(_, tys, args) => {
+                let items = args.value.iter().map(|arg| self.exp(arg)).collect();
+                let dangling = self.comments_before(args.loc.end());
+                Doc::concat(vec![
+                    Doc::text("vector"),
+                    self.type_args(tys),
+                    Self::comma_list("[", items, "]", dangling),
+                ])
+            }
+            Exp_::IfElse(cond, then, else_) => self.if_else(cond, then, else_.as_deref()),
+            Exp_::While(cond, body) => self.while_loop(cond, body),
+            Exp_::Loop(body) => Doc::concat(vec![Doc::text("loop"), self.loop_body(body)]),
+            Exp_::For(var, iter, body, spec) => {
+                let mut docs = vec![
+                    Doc::text(format!("for ({} in ", var)),
+                    self.for_iter(iter),
+                    Doc::text(")"),
+                    self.loop_body(body),
+                ];
+                if let Some(spec) = spec {
+                    docs.push(Doc::text(" "));
+                    docs.push(self.spec_block(spec));
+                }
+                Doc::concat(docs)
+            }
+            Exp_::Block(seq) => self.sequence(seq, e.loc.end(), /* inline */ true),
+            Exp_::Lambda(binds, body) => {
+                let binds = binds.value.iter().map(|b| self.bind(b)).collect();
+                Doc::concat(vec![
+                    Doc::text("|"),
+                    Doc::join(binds, Doc::text(", ")),
+                    Doc::text("| "),
+                    self.exp(body),
+                ])
+            }
+            Exp_::Quant(kind, binds, triggers, cond, body) => {
+                self.quant(kind, binds, triggers, cond.as_deref(), body)
+            }
+            Exp_::ExpList(es) => {
+                let items = es.iter().map(|e| self.exp(e)).collect();
+                let dangling = self.comments_before(e.loc.end());
+                Self::comma_list("(", items, ")", dangling)
+            }
+            Exp_::Unit => Doc::text("()"),
+            Exp_::Assign(lhs, rhs) => Doc::concat(vec![
+                self.operand(lhs, PREC_UNARY),
+                Doc::text(" = "),
+                self.exp(rhs),
+            ]),
+            Exp_::Return(None) => Doc::text("return"),
+            Exp_::Return(Some(e)) => Doc::concat(vec![Doc::text("return "), self.branch(e)]),
+            Exp_::Abort(e) => Doc::concat(vec![Doc::text("abort "), self.branch(e)]),
+            Exp_::Break => Doc::text("break"),
+            Exp_::Continue => Doc::text("continue"),
+            Exp_::Dereference(e) => Doc::concat(vec![Doc::text("*"), self.operand(e, PREC_UNARY)]),
+            Exp_::UnaryExp(op, e) => Doc::concat(vec![
+                Doc::text(op.value.to_string()),
+                self.operand(e, PREC_UNARY),
+            ]),
+            Exp_::BinopExp(..) => self.binop(e),
+            Exp_::Borrow(mut_, inner) => {
+                let prefix = match (mut_, &inner.value) {
+                    (true, _) => "&mut ",
+                    // '&&' is a single token
+                    (false, Exp_::Borrow(..)) => "& ",
+                    (false, _) => "&",
+                };
+                Doc::concat(vec![Doc::text(prefix), self.operand(inner, PREC_UNARY)])
+            }
+            Exp_::Dot(e, n) => Doc::concat(vec![
+                self.operand(e, PREC_TERM),
+                Doc::text(format!(".{}", n)),
+            ]),
+            Exp_::Index(e, i) => Doc::concat(vec![
+                self.operand(e, PREC_TERM),
+                Doc::text("["),
+                self.exp(i),
+                Doc::text("]"),
+            ]),
+            Exp_::Cast(e, ty) => Doc::concat(vec![
+                Doc::text("("),
+                self.exp(e),
+                Doc::text(" as "),
+                self.type_(ty),
+                Doc::text(")"),
+            ]),
+            Exp_::Annotate(e, ty) => Doc::concat(vec![
+                Doc::text("("),
+                self.exp(e),
+                Doc::text(": "),
+                self.type_(ty),
+                Doc::text(")"),
+            ]),
+            Exp_::Spec(sb) => self.spec_block(sb),
+            Exp_::UnresolvedError => panic!("ICE unexpected error node in the parser AST"),
+        }
+    }
+
+    fn if_else(&mut self, cond: &Exp, then: &Exp, else_: Option<&Exp>) -> Doc {
+        let mut docs = vec![Doc::text("if ("), self.exp(cond), Doc::text(")")];
+        if let Exp_::Block(_) = then.value {
+            docs.push(Doc::text(" "));
+            docs.push(self.exp(then));
+            if let Some(else_) = else_ {
+                docs.push(Doc::text(" else "));
+                docs.push(self.branch(else_));
+            }
+            return Doc::concat(docs);
+        }
+        // Without parentheses, the 'else' would be attached to an 'if' at the end of the branch
+        let then = if else_.is_some() && ends_with_open_if(&then.value) {
+            Doc::concat(vec![Doc::text("("), self.exp(then), Doc::text(")")])
+        } else {
+            self.branch(then)
+        };
+        docs.push(Doc::nest(Doc::concat(vec![Doc::Line, then])));
+        if let Some(else_) = else_ {
+            docs.push(Doc::Line);
+            docs.push(Doc::text("else"));
+            match else_.value {
+                Exp_::IfElse(..) | Exp_::Block(_) => {
+                    docs.push(Doc::text(" "));
+                    docs.push(self.exp(else_));
+                }
+                _ => docs.push(Doc::nest(Doc::concat(vec![Doc::Line, self.branch(else_)]))),
+            }
+        }
+        Doc::group(Doc::concat(docs))
+    }
+
+    fn while_loop(&mut self, cond: &Exp, body: &Exp) -> Doc {
+        // The parser turns `while (cond) body spec { .. }` into `while ({ spec { .. }; cond }) body`,
+        // where the block has the location of the condition
+        if let Exp_::Block((uses, items, None, last)) = &cond.value {
+            let last: &Option<Exp> = last;
+            if let (true, [item], Some(last)) = (uses.is_empty(), &items[..], last) {
+                if let SequenceItem_::Seq(spec_exp) = &item.value {
+                    if let (Exp_::Spec(spec), true) = (&spec_exp.value, last.loc == cond.loc) {
+                        return Doc::concat(vec![
+                            Doc::text("while ("),
+                            self.exp(last),
+                            Doc::text(")"),
+                            self.loop_body(body),
+                            Doc::text(" "),
+                            self.spec_block(spec),
+                        ]);
+                    }
+                }
+            }
+        }
+        Doc::concat(vec![
+            Doc::text("while ("),
+            self.exp(cond),
+            Doc::text(")"),
+            self.loop_body(body),
+        ])
+    }
+
+    fn for_iter(&mut self, iter: &ForIter) -> Doc {
+        match &iter.value {
+            ForIter_::Range(lo, hi) => {
+                let prec = binop_precedence(BinOp_::Range);
+                Doc::concat(vec![
+                    self.operand(lo, prec),
+                    Doc::text(BinOp_::RANGE),
+                    self.operand(hi, prec + 1),
+                ])
+            }
+            ForIter_::Vector(_, e) if matches!(e.value, Exp_::Borrow(..)) => self.exp(e),
+            ForIter_::Vector(true, e) => {
+                Doc::concat(vec![Doc::text("&mut *"), self.operand(e, PREC_UNARY)])
+            }
+            ForIter_::Vector(false, e) => self.exp(e),
+        }
+    }
+
+    fn binop(&mut self, e: &Exp) -> Doc {
+        let prec = match &e.value {
+            Exp_::BinopExp(_, op, _) => binop_precedence(op.value),
+            _ => unreachable!(),
+        };
+        // Operators are left associative. Print a chain of operators with the same precedence as
+        // a single group, so that it is broken after every operator or not at all.
+        let mut rhs = vec![];
+        let mut first = e;
+        while let Exp_::BinopExp(lhs, op, r) = &first.value {
+            if binop_precedence(op.value) != prec {
+                break;
+            }
+            rhs.push((op, r));
+            first = &**lhs;
+        }
+        let mut docs = vec![self.operand(first, prec)];
+        let mut rest = vec![];
+        for (op, r) in rhs.into_iter().rev() {
+            if op.value == BinOp_::Range {
+                rest.push(Doc::text(BinOp_::RANGE));
+            } else {
+                rest.push(Doc::text(format!(" {}", op.value)));
+                rest.push(Doc::Line);
+            }
+            rest.push(self.operand(r, prec + 1));
+        }
+        docs.push(Doc::nest(Doc::concat(rest)));
+        Doc::group(Doc::concat(docs))
+    }
+
+    fn quant(
+        &mut self,
+        kind: &QuantKind,
+        binds: &BindWithRangeList,
+        triggers: &[Vec<Exp>],
+        cond: Option<&Exp>,
+        body: &Exp,
+    ) -> Doc {
+        let keyword = match kind.value {
+            QuantKind_::Forall => "forall",
+            QuantKind_::Exists => "exists",
+            QuantKind_::Choose => "choose",
+            QuantKind_::ChooseMin => "choose min",
+        };
+        let mut docs = vec![Doc::text(format!("{} ", keyword))];
+        let binds = binds.value.iter().map(|b| self.quant_bind(b)).collect();
+        docs.push(Doc::join(binds, Doc::text(", ")));
+        if let QuantKind_::Choose | QuantKind_::ChooseMin = kind.value {
+            docs.push(Doc::text(" where "));
+            docs.push(self.exp(body));
+            return Doc::concat(docs);
+        }
+        for trigger in triggers {
+            let exps = trigger.iter().map(|e| self.exp(e)).collect();
+            docs.push(Doc::text(" {"));
+            docs.push(Doc::join(exps, Doc::text(", ")));
+            docs.push(Doc::text("}"));
+        }
+        if let Some(cond) = cond {
+            docs.push(Doc::text(" where "));
+            docs.push(self.exp(cond));
+        }
+        docs.push(Doc::text(": "));
+        docs.push(self.exp(body));
+        Doc::concat(docs)
+    }
+
+    fn quant_bind(&mut self, bind: &BindWithRange) -> Doc {
+        let (b, range) = &bind.value;
+        let var = self.bind(b);
+        // A quantifier over a type is represented as a call to the builtin `$spec_domain<T>()`
+        match &range.value {
+            Exp_::Call(sp!(_, NameAccessChain_::One(n)), false, Some(tys), args)
+                if n.value.as_str() == "$spec_domain"
+                    && tys.len() == 1
+                    && args.value.is_empty() =>
+            {
+                Doc::concat(vec![var, Doc::text(": "), self.type_(&tys[0])])
+            }
+            _ => Doc::concat(vec![var, Doc::text(" in "), self.exp(range)]),
+        }
+    }
+}
+
+//**************************************************************************************************
+// Precedence
+//**************************************************************************************************
+
+// Must agree with `get_precedence` in the parser
+fn binop_precedence(op: BinOp_) -> u32 {
+    match op {
+        BinOp_::Implies | BinOp_::Iff => 2,
+        BinOp_::Or => 3,
+        BinOp_::And => 4,
+        BinOp_::Eq | BinOp_::Neq | BinOp_::Lt | BinOp_::Gt | BinOp_::Le | BinOp_::Ge => 5,
+        BinOp_::Range => 6,
+        BinOp_::BitOr => 7,
+        BinOp_::Xor => 8,
+        BinOp_::BitAnd => 9,
+        BinOp_::Shl | BinOp_::Shr => 10,
+        BinOp_::Add | BinOp_::Sub => 11,
+        BinOp_::Mul | BinOp_::Div | BinOp_::Mod => 12,
+    }
+}
+
+fn exp_precedence(e: &Exp_) -> u32 {
+    match e {
+        Exp_::Lambda(..)
+        | Exp_::Quant(..)
+        | Exp_::Assign(..)
+        | Exp_::IfElse(..)
+        | Exp_::While(..)
+        | Exp_::Loop(..)
+        | Exp_::For(..)
+        | Exp_::Return(..)
+        | Exp_::Abort(..) => PREC_OPEN,
+        Exp_::BinopExp(_, op, _) => binop_precedence(op.value),
+        Exp_::UnaryExp(..)
+        | Exp_::Dereference(..)
+        | Exp_::Borrow(..)
+        | Exp_::Move(..)
+        | Exp_::Copy(..) => PREC_UNARY,
+        _ => PREC_TERM,
+    }
+}
+
+// Whether the printed expression starts with a '{'
+fn starts_with_block(e: &Exp_) -> bool {
+    match e {
+        Exp_::Block(_) => true,
+        Exp_::BinopExp(lhs, op, _) => {
+            exp_precedence(&lhs.value) >= binop_precedence(op.value)
+                && starts_with_block(&lhs.value)
+        }
+        Exp_::Dot(e, _) | Exp_::Index(e, _) => {
+            exp_precedence(&e.value) >= PREC_TERM && starts_with_block(&e.value)
+        }
+        Exp_::Assign(lhs, _) => {
+            exp_precedence(&lhs.value) >= PREC_UNARY && starts_with_block(&lhs.value)
+        }
+        _ => false,
+    }
+}
+
+// Whether the printed expression ends with an 'if' without an 'else'
+fn ends_with_open_if(e: &Exp_) -> bool {
+    match e {
+        Exp_::IfElse(_, _, None) => true,
+        Exp_::IfElse(_, _, Some(e))
+        | Exp_::Lambda(_, e)
+        | Exp_::Quant(_, _, _, _, e)
+        | Exp_::Assign(_, e)
+        | Exp_::Return(Some(e))
+        | Exp_::Abort(e)
+        | Exp_::While(_, e)
+        | Exp_::Loop(e)
+        | Exp_::For(_, _, e, None) => ends_with_open_if(&e.value),
+        _ => false,
+    }
+}
+
+fn attributes_start(attributes: &[Attributes], start: u32) -> u32 {
+    match attributes.first() {
+        Some(attrs) => std::cmp::min(attrs.loc.start(), start),
+        None => start,
+    }
+}
+
+fn type_parameter_string(name: &Name, constraints: &[Ability]) -> String {
+    if constraints.is_empty() {
+        return name.value.to_string();
+    }
+    let constraints: Vec<_> = constraints.iter().map(|a| a.value.to_string()).collect();
+    format!("{}: {}", name, constraints.join(" + "))
+}
+
+fn type_parameters_string(type_parameters: &[(Name, Vec<Ability>)]) -> String {
+    if type_parameters.is_empty() {
+        return String::new();
+    }
+    let tps: Vec<_> = type_parameters
+        .iter()
+        .map(|(name, constraints)| type_parameter_string(name, constraints))
+        .collect();
+    format!("<{}>", tps.join(", "))
+}
@@ -225,7 +225,14 @@ fn module(
     let structs = tstructs.map(|name, s| struct_def(context, name, s));
 
     let constants = tconstants.map(|name, c| constant(context, name, c));
-    let functions = tfunctions.map(|name, f| function(context, name, f));
+    // 'inline' functions have been expanded at their call sites during typing
+    let functions = tfunctions.filter_map(|name, f| {
+        if f.inline {
+            None
+        } else {
+            Some(function(context, name, f))
+        }
+    });
     (
         module_ident,
         H::ModuleDefinition {
@@ -283,6 +290,7 @@ fn function(context: &mut Context, _name: FunctionName, f: T::Function) -> H::Fu
         attributes,
         visibility,
         entry,
+        inline: _,
         signature,
         acquires,
         body,
@@ -481,7 +489,7 @@ fn base_type(context: &Context, sp!(loc, nb_): N::Type) -> H::BaseType {
         NT::Param(tp) => HB::Param(tp),
         NT::UnresolvedError => HB::UnresolvedError,
         NT::Anything => HB::Unreachable,
-        NT::Ref(_, _) | NT::Unit => {
+        NT::Ref(_, _) | NT::Unit | NT::Fun(_, _) => {
             panic!(
                 "ICE type constraints failed {}:{}-{}",
                 loc.file_hash(),
@@ -1371,6 +1379,7 @@ fn exp_impl(
         }
 
         TE::IfElse(..) | TE::BinopExp(..) => unreachable!(),
+        TE::VarCall(..) | TE::Lambda(..) => panic!("ICE should have been removed when inlining"),
     };
     H::exp(ty, sp(eloc, res))
 }
@@ -1740,7 +1749,9 @@ fn bind_for_short_circuit(e: &T::Exp) -> bool {
         | TE::Vector(_, _, _, _)
         | TE::BorrowLocal(_, _)
         | TE::ExpList(_)
-        | TE::Cast(_, _) => panic!("ICE unexpected exp in short circuit check: {:?}", e),
+        | TE::Cast(_, _)
+        | TE::VarCall(_, _)
+        | TE::Lambda(_, _) => panic!("ICE unexpected exp in short circuit check: {:?}", e),
     }
 }
 
@@ -0,0 +1,60 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports comparisons to a boolean literal, e.g. `b == true` or `b != false`, which can be
+//! written `b` (or `!b`).
+
+use super::{visit_sequence, LintContext, TypingLinter};
+use crate::{
+    diag,
+    expansion::ast::Value_,
+    parser::ast::{BinOp_, FunctionName},
+    typing::ast as T,
+};
+
+pub const BOOL_COMPARISON: &str = "bool_comparison";
+
+pub struct BoolComparison;
+
+impl TypingLinter for BoolComparison {
+    fn name(&self) -> &'static str {
+        BOOL_COMPARISON
+    }
+
+    fn function(&self, context: &mut LintContext, _name: FunctionName, fdef: &T::Function) {
+        use T::UnannotatedExp_ as E;
+        let seq = match &fdef.body.value {
+            T::FunctionBody_::Native => return,
+            T::FunctionBody_::Defined(seq) => seq,
+        };
+        visit_sequence(seq, &mut |e| {
+            let (lhs, op, rhs) = match &e.exp.value {
+                E::BinopExp(lhs, sp!(_, op @ (BinOp_::Eq | BinOp_::Neq)), _, rhs) => (lhs, op, rhs),
+                _ => return,
+            };
+            let literal = match (bool_literal(lhs), bool_literal(rhs)) {
+                (Some(b), _) | (None, Some(b)) => b,
+                (None, None) => return,
+            };
+            let msg = if (*op == BinOp_::Eq) == literal {
+                format!(
+                    "Unnecessary comparison to '{}'. Use the other operand directly",
+                    literal
+                )
+            } else {
+                format!(
+                    "Unnecessary comparison to '{}'. Negate the other operand with '!' instead",
+                    literal
+                )
+            };
+            context.add_diag(diag!(Lint::BoolComparison, (e.exp.loc, msg)))
+        })
+    }
+}
+
+fn bool_literal(e: &T::Exp) -> Option<bool> {
+    match &e.exp.value {
+        T::UnannotatedExp_::Value(sp!(_, Value_::Bool(b))) => Some(*b),
+        _ => None,
+    }
+}
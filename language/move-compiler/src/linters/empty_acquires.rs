@@ -0,0 +1,86 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports `acquires` annotations on functions where no reachable code acquires a resource. The
+//! type checker already rejects annotations that are never used, so this only happens when all
+//! of the acquiring code is unreachable, e.g. behind an `if (false)`, and is removed by the
+//! optimizations of the CFGIR.
+
+use super::{CfgirLinter, LintContext};
+use crate::{
+    cfgir::ast as G,
+    diag,
+    hlir::ast::{BuiltinFunction_, Command_, Exp, ExpListItem, UnannotatedExp_},
+    parser::ast::FunctionName,
+};
+
+pub const EMPTY_ACQUIRES: &str = "empty_acquires";
+
+pub struct EmptyAcquires;
+
+impl CfgirLinter for EmptyAcquires {
+    fn name(&self) -> &'static str {
+        EMPTY_ACQUIRES
+    }
+
+    fn function(&self, context: &mut LintContext, name: FunctionName, fdef: &G::Function) {
+        let blocks = match &fdef.body.value {
+            G::FunctionBody_::Native => return,
+            G::FunctionBody_::Defined { blocks, .. } => blocks,
+        };
+        let first_acquire = match fdef.acquires.values().min_by_key(|loc| loc.start()) {
+            None => return,
+            Some(loc) => *loc,
+        };
+        let has_acquire = blocks.values().flatten().any(|sp!(_, cmd_)| match cmd_ {
+            Command_::Mutate(el, er) => acquires(el) || acquires(er),
+            Command_::Assign(_, e) => acquires(e),
+            Command_::Return { exp: e, .. }
+            | Command_::Abort(e)
+            | Command_::IgnoreAndPop { exp: e, .. }
+            | Command_::JumpIf { cond: e, .. } => acquires(e),
+            Command_::Jump { .. } | Command_::Break | Command_::Continue => false,
+        });
+        if !has_acquire {
+            let msg = format!(
+                "Function '{}' declares 'acquires', but none of its reachable code acquires a \
+                 resource. Consider removing the annotation",
+                name
+            );
+            context.add_diag(diag!(Lint::EmptyAcquires, (first_acquire, msg)))
+        }
+    }
+}
+
+// Whether `e` contains a `move_from`, a `borrow_global`, or a call that acquires resources
+fn acquires(e: &Exp) -> bool {
+    use UnannotatedExp_ as E;
+    match &e.exp.value {
+        E::Unit { .. }
+        | E::Value(_)
+        | E::Move { .. }
+        | E::Copy { .. }
+        | E::Constant(_)
+        | E::BorrowLocal(_, _)
+        | E::Spec(_, _)
+        | E::Unreachable
+        | E::UnresolvedError => false,
+
+        E::ModuleCall(mcall) => !mcall.acquires.is_empty() || acquires(&mcall.arguments),
+        E::Builtin(b, e) => match &b.value {
+            BuiltinFunction_::MoveFrom(_) | BuiltinFunction_::BorrowGlobal(_, _) => true,
+            BuiltinFunction_::MoveTo(_) | BuiltinFunction_::Exists(_) => acquires(e),
+        },
+        E::Vector(_, _, _, e)
+        | E::Freeze(e)
+        | E::Dereference(e)
+        | E::UnaryExp(_, e)
+        | E::Borrow(_, e, _)
+        | E::Cast(e, _) => acquires(e),
+        E::BinopExp(e1, _, e2) => acquires(e1) || acquires(e2),
+        E::Pack(_, _, fields) => fields.iter().any(|(_, _, e)| acquires(e)),
+        E::ExpList(es) => es.iter().any(|item| match item {
+            ExpListItem::Single(e, _) | ExpListItem::Splat(_, e, _) => acquires(e),
+        }),
+    }
+}
@@ -0,0 +1,311 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lints report code that compiles, but that is likely a mistake or that can be written more
+//! simply. A lint checks one function at a time, either on the typed AST (`TypingLinter`) or on
+//! the control flow graph of the CFGIR (`CfgirLinter`). Only the functions of source modules are
+//! checked, and all findings are reported as warnings.
+//!
+//! The built-in lints are registered when `Flags::lint` is set. Other lints can be registered with
+//! `Compiler::add_linters`. The lints named in a `#[lint_allow(..)]` attribute are not run on the
+//! module or function the attribute is attached to.
+
+mod bool_comparison;
+mod empty_acquires;
+mod needless_copy;
+mod self_assignment;
+mod shadowed_local;
+mod unnecessary_mut_ref;
+
+use crate::{
+    cfgir::ast as G,
+    diag,
+    diagnostics::{Diagnostic, Diagnostics},
+    expansion::ast::{self as E, ModuleIdent},
+    parser::ast::FunctionName,
+    shared::{
+        known_attributes::{KnownAttribute, LintAttribute},
+        CompilationEnv,
+    },
+    typing::ast as T,
+};
+use move_symbol_pool::Symbol;
+use std::{collections::BTreeSet, fmt};
+
+//**************************************************************************************************
+// Linters
+//**************************************************************************************************
+
+/// A lint over the typed AST. Inline functions are checked before they are inlined.
+pub trait TypingLinter {
+    /// The name of the lint, as used in `#[lint_allow(..)]`
+    fn name(&self) -> &'static str;
+
+    fn function(&self, context: &mut LintContext, name: FunctionName, fdef: &T::Function);
+}
+
+/// A lint over the CFGIR, after locals have been refined and the code has been optimized
+pub trait CfgirLinter {
+    /// The name of the lint, as used in `#[lint_allow(..)]`
+    fn name(&self) -> &'static str;
+
+    fn function(&self, context: &mut LintContext, name: FunctionName, fdef: &G::Function);
+}
+
+pub enum Linter {
+    Typing(Box<dyn TypingLinter>),
+    Cfgir(Box<dyn CfgirLinter>),
+}
+
+impl Linter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Linter::Typing(l) => l.name(),
+            Linter::Cfgir(l) => l.name(),
+        }
+    }
+}
+
+impl fmt::Debug for Linter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Linter({})", self.name())
+    }
+}
+
+/// The lints run when `Flags::lint` is set
+pub fn builtin() -> Vec<Linter> {
+    vec![
+        Linter::Cfgir(Box::new(needless_copy::NeedlessCopy)),
+        Linter::Typing(Box::new(self_assignment::SelfAssignment)),
+        Linter::Typing(Box::new(unnecessary_mut_ref::UnnecessaryMutRef)),
+        Linter::Typing(Box::new(bool_comparison::BoolComparison)),
+        Linter::Cfgir(Box::new(empty_acquires::EmptyAcquires)),
+        Linter::Typing(Box::new(shadowed_local::ShadowedLocal)),
+    ]
+}
+
+//**************************************************************************************************
+// Context
+//**************************************************************************************************
+
+pub struct LintContext<'a> {
+    diags: &'a mut Diagnostics,
+    current_module: ModuleIdent,
+}
+
+impl<'a> LintContext<'a> {
+    pub fn current_module(&self) -> &ModuleIdent {
+        &self.current_module
+    }
+
+    pub fn add_diag(&mut self, diag: Diagnostic) {
+        self.diags.add(diag)
+    }
+}
+
+//**************************************************************************************************
+// Entry
+//**************************************************************************************************
+
+/// Runs the typing lints on the source modules of `prog`. Also reports invalid `#[lint_allow(..)]`
+/// attributes, if any lint is registered.
+pub fn typing(env: &mut CompilationEnv, prog: &T::Program) {
+    if env.linters().is_empty() {
+        return;
+    }
+    let known = env
+        .linters()
+        .iter()
+        .map(|l| l.name())
+        .collect::<BTreeSet<_>>();
+    let linters = env
+        .linters()
+        .iter()
+        .filter_map(|l| match l {
+            Linter::Typing(l) => Some(l),
+            Linter::Cfgir(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let mut diags = Diagnostics::new();
+    for (mident, mdef) in prog.modules.key_cloned_iter() {
+        if !mdef.is_source_module {
+            continue;
+        }
+        let module_allowed = allowed_lints(Some((&mut diags, &known)), &mdef.attributes);
+        for (name, fdef) in mdef.functions.key_cloned_iter() {
+            let allowed = allowed_lints(Some((&mut diags, &known)), &fdef.attributes);
+            for linter in &linters {
+                let lint = Symbol::from(linter.name());
+                if module_allowed.contains(&lint) || allowed.contains(&lint) {
+                    continue;
+                }
+                let context = &mut LintContext {
+                    diags: &mut diags,
+                    current_module: mident,
+                };
+                linter.function(context, name, fdef)
+            }
+        }
+    }
+    env.add_diags(diags)
+}
+
+/// Runs the CFGIR lints on the source modules of `prog`
+pub fn cfgir(env: &mut CompilationEnv, prog: &G::Program) {
+    let linters = env
+        .linters()
+        .iter()
+        .filter_map(|l| match l {
+            Linter::Cfgir(l) => Some(l),
+            Linter::Typing(_) => None,
+        })
+        .collect::<Vec<_>>();
+    if linters.is_empty() {
+        return;
+    }
+    let mut diags = Diagnostics::new();
+    for (mident, mdef) in prog.modules.key_cloned_iter() {
+        if !mdef.is_source_module {
+            continue;
+        }
+        let module_allowed = allowed_lints(None, &mdef.attributes);
+        for (name, fdef) in mdef.functions.key_cloned_iter() {
+            let allowed = allowed_lints(None, &fdef.attributes);
+            for linter in &linters {
+                let lint = Symbol::from(linter.name());
+                if module_allowed.contains(&lint) || allowed.contains(&lint) {
+                    continue;
+                }
+                let context = &mut LintContext {
+                    diags: &mut diags,
+                    current_module: mident,
+                };
+                linter.function(context, name, fdef)
+            }
+        }
+    }
+    env.add_diags(diags)
+}
+
+// The lints named in the `#[lint_allow(..)]` attribute, if any. If `report` is set, malformed
+// attributes and names that are not among the known lints are reported as warnings.
+fn allowed_lints(
+    mut report: Option<(&mut Diagnostics, &BTreeSet<&'static str>)>,
+    attributes: &E::Attributes,
+) -> BTreeSet<Symbol> {
+    let mut allowed = BTreeSet::new();
+    let allow = E::AttributeName_::Known(KnownAttribute::Lint(LintAttribute::Allow));
+    let attr = match attributes.get_(&allow) {
+        None => return allowed,
+        Some(attr) => attr,
+    };
+    let names = match &attr.value {
+        E::Attribute_::Parameterized(_, names) => names,
+        E::Attribute_::Name(_) | E::Attribute_::Assigned(_, _) => {
+            if let Some((diags, _)) = &mut report {
+                let msg = format!(
+                    "Expected a list of lints, e.g. '#[{}({})]'",
+                    LintAttribute::ALLOW,
+                    self_assignment::SELF_ASSIGNMENT
+                );
+                diags.add(diag!(Attributes::ValueWarning, (attr.loc, msg)))
+            }
+            return allowed;
+        }
+    };
+    for (loc, name_, inner) in names {
+        let name = match (name_, &inner.value) {
+            (E::AttributeName_::Unknown(name), E::Attribute_::Name(_)) => *name,
+            _ => {
+                if let Some((diags, _)) = &mut report {
+                    let msg = "Expected the name of a lint";
+                    diags.add(diag!(Attributes::ValueWarning, (loc, msg)))
+                }
+                continue;
+            }
+        };
+        if let Some((diags, known)) = &mut report {
+            if !known.contains(name.as_str()) {
+                let msg = format!("Unknown lint '{}'", name);
+                diags.add(diag!(Attributes::ValueWarning, (loc, msg)))
+            }
+        }
+        allowed.insert(name);
+    }
+    allowed
+}
+
+//**************************************************************************************************
+// Traversal
+//**************************************************************************************************
+
+/// Calls `f` on `e` and on all of its subexpressions, parents before children
+pub fn visit_exp<'a>(e: &'a T::Exp, f: &mut impl FnMut(&'a T::Exp)) {
+    f(e);
+    for sub in sub_exps(e) {
+        visit_exp(sub, f)
+    }
+}
+
+/// Calls `f` on all expressions of the function body `seq`, parents before children
+pub fn visit_sequence<'a>(seq: &'a T::Sequence, f: &mut impl FnMut(&'a T::Exp)) {
+    for e in sequence_exps(seq) {
+        visit_exp(e, f)
+    }
+}
+
+/// The direct subexpressions of `e`, in evaluation order. For a block, these are the expressions
+/// of its items.
+pub fn sub_exps(e: &T::Exp) -> Vec<&T::Exp> {
+    use T::UnannotatedExp_ as E;
+    match &e.exp.value {
+        E::Unit { .. }
+        | E::Value(_)
+        | E::Move { .. }
+        | E::Copy { .. }
+        | E::Use(_)
+        | E::Constant(_, _)
+        | E::Break
+        | E::Continue
+        | E::BorrowLocal(_, _)
+        | E::Spec(_, _)
+        | E::UnresolvedError => vec![],
+
+        E::ModuleCall(call) => vec![&call.arguments],
+        E::Builtin(_, e)
+        | E::Vector(_, _, _, e)
+        | E::VarCall(_, e)
+        | E::Loop { body: e, .. }
+        | E::Assign(_, _, e)
+        | E::Return(e)
+        | E::Abort(e)
+        | E::Dereference(e)
+        | E::UnaryExp(_, e)
+        | E::Borrow(_, e, _)
+        | E::TempBorrow(_, e)
+        | E::Cast(e, _)
+        | E::Annotate(e, _)
+        | E::Lambda(_, e) => vec![e],
+
+        E::IfElse(e1, e2, e3) => vec![e1, e2, e3],
+        E::While(e1, e2) | E::Mutate(e1, e2) | E::BinopExp(e1, _, _, e2) => vec![e1, e2],
+        E::Block(seq) => sequence_exps(seq),
+        E::Pack(_, _, _, fields) => fields.iter().map(|(_, _, (_, (_, e)))| e).collect(),
+        E::ExpList(items) => items
+            .iter()
+            .map(|item| match item {
+                T::ExpListItem::Single(e, _) | T::ExpListItem::Splat(_, e, _) => e,
+            })
+            .collect(),
+    }
+}
+
+fn sequence_exps(seq: &T::Sequence) -> Vec<&T::Exp> {
+    use T::SequenceItem_ as S;
+    seq.iter()
+        .filter_map(|item| match &item.value {
+            S::Seq(e) | S::Bind(_, _, e) => Some(&**e),
+            S::Declare(_) => None,
+        })
+        .collect()
+}
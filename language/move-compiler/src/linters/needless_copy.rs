@@ -0,0 +1,178 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports an explicit `copy x` when `x` is not used afterwards. Without the annotation, the
+//! value of `x` would be moved instead. Locals that are borrowed somewhere in the function are
+//! ignored, as moving them might not be possible while a borrow is alive.
+
+use super::{CfgirLinter, LintContext};
+use crate::{
+    cfgir::ast as G,
+    diag,
+    hlir::{
+        ast::{BasicBlock, Command, Command_, Exp, ExpListItem, LValue, LValue_, Label},
+        translate::{display_var, DisplayVar},
+    },
+    parser::ast::{FunctionName, Var},
+    shared::Identifier,
+};
+use move_ir_types::location::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const NEEDLESS_COPY: &str = "needless_copy";
+
+pub struct NeedlessCopy;
+
+impl CfgirLinter for NeedlessCopy {
+    fn name(&self) -> &'static str {
+        NEEDLESS_COPY
+    }
+
+    fn function(&self, context: &mut LintContext, _name: FunctionName, fdef: &G::Function) {
+        let blocks = match &fdef.body.value {
+            G::FunctionBody_::Native => return,
+            G::FunctionBody_::Defined { blocks, .. } => blocks,
+        };
+
+        // The locals live at the start of each block, computed backwards until a fixpoint
+        let mut live_in: BTreeMap<Label, BTreeSet<Var>> = BTreeMap::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (lbl, block) in blocks.iter().rev() {
+                let state = Liveness::block(&live_in, block);
+                if live_in.get(lbl) != Some(&state.live) {
+                    live_in.insert(*lbl, state.live);
+                    changed = true;
+                }
+            }
+        }
+
+        let mut needless = vec![];
+        let mut borrowed = BTreeSet::new();
+        for block in blocks.values() {
+            let state = Liveness::block(&live_in, block);
+            needless.extend(state.needless_copies);
+            borrowed.extend(state.borrowed);
+        }
+        for (loc, var) in needless {
+            if borrowed.contains(&var) {
+                continue;
+            }
+            let v_str = match display_var(var.value()) {
+                DisplayVar::Tmp => continue,
+                DisplayVar::Orig(v_str) => v_str,
+            };
+            let msg = format!(
+                "Needless 'copy' of local '{}', which is not used afterwards. Consider removing \
+                 'copy', so that the value is moved",
+                v_str
+            );
+            context.add_diag(diag!(Lint::NeedlessCopy, (loc, msg)))
+        }
+    }
+}
+
+// Traverses a block backwards, in reverse evaluation order, tracking the live locals
+struct Liveness {
+    live: BTreeSet<Var>,
+    // Explicit copies of locals that are dead after the copy
+    needless_copies: Vec<(Loc, Var)>,
+    borrowed: BTreeSet<Var>,
+}
+
+impl Liveness {
+    fn block(live_in: &BTreeMap<Label, BTreeSet<Var>>, block: &BasicBlock) -> Self {
+        let successors = match block.back().map(|cmd| &cmd.value) {
+            Some(Command_::Jump { target, .. }) => vec![*target],
+            Some(Command_::JumpIf {
+                if_true, if_false, ..
+            }) => vec![*if_true, *if_false],
+            _ => vec![],
+        };
+        let mut state = Liveness {
+            live: successors
+                .iter()
+                .filter_map(|lbl| live_in.get(lbl))
+                .flatten()
+                .cloned()
+                .collect(),
+            needless_copies: vec![],
+            borrowed: BTreeSet::new(),
+        };
+        for cmd in block.iter().rev() {
+            state.command(cmd)
+        }
+        state
+    }
+
+    fn command(&mut self, sp!(_, cmd_): &Command) {
+        use Command_ as C;
+        match cmd_ {
+            C::Assign(ls, e) => {
+                ls.iter().for_each(|l| self.lvalue(l));
+                self.exp(e)
+            }
+            C::Mutate(el, er) => {
+                self.exp(el);
+                self.exp(er)
+            }
+            C::Return { exp: e, .. }
+            | C::Abort(e)
+            | C::IgnoreAndPop { exp: e, .. }
+            | C::JumpIf { cond: e, .. } => self.exp(e),
+            C::Jump { .. } | C::Break | C::Continue => (),
+        }
+    }
+
+    fn lvalue(&mut self, sp!(_, l_): &LValue) {
+        match l_ {
+            LValue_::Ignore => (),
+            LValue_::Var(v, _) => {
+                self.live.remove(v);
+            }
+            LValue_::Unpack(_, _, fields) => fields.iter().for_each(|(_, l)| self.lvalue(l)),
+        }
+    }
+
+    fn exp(&mut self, e: &Exp) {
+        use crate::hlir::ast::UnannotatedExp_ as E;
+        match &e.exp.value {
+            E::Unit { .. } | E::Value(_) | E::Constant(_) => (),
+            E::Unreachable | E::UnresolvedError => (),
+
+            E::Copy { var, from_user } => {
+                if *from_user && !self.live.contains(var) {
+                    self.needless_copies.push((e.exp.loc, *var))
+                }
+                self.live.insert(*var);
+            }
+            E::Move { var, .. } => {
+                self.live.insert(*var);
+            }
+            E::BorrowLocal(_, var) => {
+                self.borrowed.insert(*var);
+                self.live.insert(*var);
+            }
+            E::Spec(_, used_locals) => self.live.extend(used_locals.keys().cloned()),
+
+            E::ModuleCall(mcall) => self.exp(&mcall.arguments),
+            E::Builtin(_, e)
+            | E::Vector(_, _, _, e)
+            | E::Freeze(e)
+            | E::Dereference(e)
+            | E::UnaryExp(_, e)
+            | E::Borrow(_, e, _)
+            | E::Cast(e, _) => self.exp(e),
+
+            E::BinopExp(e1, _, e2) => {
+                self.exp(e2);
+                self.exp(e1)
+            }
+            E::Pack(_, _, fields) => fields.iter().rev().for_each(|(_, _, e)| self.exp(e)),
+            E::ExpList(es) => es.iter().rev().for_each(|item| match item {
+                ExpListItem::Single(e, _) | ExpListItem::Splat(_, e, _) => self.exp(e),
+            }),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports assignments of a local, or of a location behind a reference, to itself, e.g. `x = x`
+//! or `s.f = s.f`. Such an assignment has no effect.
+
+use super::{visit_sequence, LintContext, TypingLinter};
+use crate::{
+    diag,
+    parser::ast::{FunctionName, Var},
+    typing::ast as T,
+};
+
+pub const SELF_ASSIGNMENT: &str = "self_assignment";
+
+pub struct SelfAssignment;
+
+impl TypingLinter for SelfAssignment {
+    fn name(&self) -> &'static str {
+        SELF_ASSIGNMENT
+    }
+
+    fn function(&self, context: &mut LintContext, _name: FunctionName, fdef: &T::Function) {
+        use T::UnannotatedExp_ as E;
+        let seq = match &fdef.body.value {
+            T::FunctionBody_::Native => return,
+            T::FunctionBody_::Defined(seq) => seq,
+        };
+        visit_sequence(seq, &mut |e| match &e.exp.value {
+            E::Assign(sp!(_, lvalues), _, rhs) => {
+                let lhs = match lvalues.as_slice() {
+                    [sp!(_, T::LValue_::Var(lhs, _))] => lhs,
+                    _ => return,
+                };
+                if local(rhs) == Some(lhs) {
+                    let msg = format!("Local '{}' is assigned to itself", lhs);
+                    context.add_diag(diag!(Lint::SelfAssignment, (e.exp.loc, msg)))
+                }
+            }
+            E::Mutate(lhs, rhs) => match &rhs.exp.value {
+                E::Dereference(rhs) if same_location(lhs, rhs) => {
+                    let msg = "This location is assigned the value it already holds";
+                    context.add_diag(diag!(Lint::SelfAssignment, (e.exp.loc, msg)))
+                }
+                _ => (),
+            },
+            _ => (),
+        })
+    }
+}
+
+fn local(e: &T::Exp) -> Option<&Var> {
+    use T::UnannotatedExp_ as E;
+    match &e.exp.value {
+        E::Use(var) | E::Copy { var, .. } | E::Move { var, .. } => Some(var),
+        _ => None,
+    }
+}
+
+// Whether the references `e1` and `e2` point to the same location. Mutability is ignored.
+fn same_location(e1: &T::Exp, e2: &T::Exp) -> bool {
+    use T::UnannotatedExp_ as E;
+    match (&e1.exp.value, &e2.exp.value) {
+        (E::BorrowLocal(_, v1), E::BorrowLocal(_, v2)) => v1 == v2,
+        (E::Borrow(_, e1, f1), E::Borrow(_, e2, f2)) => f1 == f2 && same_location(e1, e2),
+        _ => matches!((local(e1), local(e2)), (Some(v1), Some(v2)) if v1 == v2),
+    }
+}
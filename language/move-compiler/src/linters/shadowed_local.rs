@@ -0,0 +1,138 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports `let` bindings in a nested block that shadow a local of an enclosing block, e.g. the
+//! inner `x` in `let x = 0; if (c) { let x = 1; }`. Rebinding a name in the same block, as in
+//! `let x = x + 1`, is a common idiom and is not reported. Locals whose name starts with '_' are
+//! ignored.
+
+use super::{sub_exps, LintContext, TypingLinter};
+use crate::{
+    diag,
+    parser::ast::{FunctionName, Var},
+    shared::Identifier,
+    typing::ast as T,
+};
+use move_ir_types::location::*;
+use move_symbol_pool::Symbol;
+use std::collections::BTreeMap;
+
+pub const SHADOWED_LOCAL: &str = "shadowed_local";
+
+pub struct ShadowedLocal;
+
+struct Context<'a, 'b> {
+    lint: &'a mut LintContext<'b>,
+    // The locals declared in each enclosing block, innermost last
+    scopes: Vec<BTreeMap<Symbol, Loc>>,
+}
+
+impl TypingLinter for ShadowedLocal {
+    fn name(&self) -> &'static str {
+        SHADOWED_LOCAL
+    }
+
+    fn function(&self, context: &mut LintContext, _name: FunctionName, fdef: &T::Function) {
+        let seq = match &fdef.body.value {
+            T::FunctionBody_::Native => return,
+            T::FunctionBody_::Defined(seq) => seq,
+        };
+        let params = fdef
+            .signature
+            .parameters
+            .iter()
+            .map(|(v, _)| (v.value(), v.loc()))
+            .collect();
+        let context = &mut Context {
+            lint: context,
+            scopes: vec![params],
+        };
+        sequence(context, seq)
+    }
+}
+
+impl<'a, 'b> Context<'a, 'b> {
+    fn declare(&mut self, v: &Var) {
+        if v.starts_with_underscore() {
+            return;
+        }
+        let (current, enclosing) = self.scopes.split_last_mut().unwrap();
+        if current.contains_key(&v.value()) {
+            return;
+        }
+        if let Some(prev_loc) = enclosing.iter().rev().find_map(|s| s.get(&v.value())) {
+            let msg = format!(
+                "Local '{}' shadows a local of an enclosing block. Consider renaming it",
+                v
+            );
+            self.lint.add_diag(diag!(
+                Lint::ShadowedLocal,
+                (v.loc(), msg),
+                (*prev_loc, "Shadowed local declared here"),
+            ))
+        }
+        current.insert(v.value(), v.loc());
+    }
+}
+
+fn sequence(context: &mut Context, seq: &T::Sequence) {
+    use T::SequenceItem_ as S;
+    for item in seq {
+        match &item.value {
+            S::Seq(e) => exp(context, e),
+            S::Declare(sp!(_, lvalues)) => lvalues.iter().for_each(|l| lvalue(context, l)),
+            S::Bind(sp!(_, lvalues), _, e) => {
+                exp(context, e);
+                lvalues.iter().for_each(|l| lvalue(context, l))
+            }
+        }
+    }
+}
+
+fn lvalue(context: &mut Context, sp!(_, l_): &T::LValue) {
+    use T::LValue_ as L;
+    match l_ {
+        L::Ignore => (),
+        L::Var(v, _) => context.declare(v),
+        L::Unpack(_, _, _, fields) | L::BorrowUnpack(_, _, _, _, fields) => fields
+            .iter()
+            .for_each(|(_, _, (_, (_, l)))| lvalue(context, l)),
+    }
+}
+
+fn exp(context: &mut Context, e: &T::Exp) {
+    use T::UnannotatedExp_ as E;
+    match &e.exp.value {
+        E::Block(seq) => {
+            context.scopes.push(BTreeMap::new());
+            sequence(context, seq);
+            context.scopes.pop();
+        }
+        // Lambda parameters are not bindings of a `let`, and are not reported
+        E::Lambda(sp!(_, params), body) => {
+            let mut scope = BTreeMap::new();
+            lambda_params(&mut scope, params);
+            context.scopes.push(scope);
+            exp(context, body);
+            context.scopes.pop();
+        }
+        _ => sub_exps(e).into_iter().for_each(|e| exp(context, e)),
+    }
+}
+
+fn lambda_params(scope: &mut BTreeMap<Symbol, Loc>, lvalues: &[T::LValue]) {
+    use T::LValue_ as L;
+    for sp!(_, l_) in lvalues {
+        match l_ {
+            L::Ignore => (),
+            L::Var(v, _) => {
+                scope.insert(v.value(), v.loc());
+            }
+            L::Unpack(_, _, _, fields) | L::BorrowUnpack(_, _, _, _, fields) => {
+                for (_, _, (_, (_, l))) in fields {
+                    lambda_params(scope, std::slice::from_ref(l))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports mutable borrows, e.g. `&mut x`, passed as arguments to parameters that only take an
+//! immutable reference. An immutable borrow, `&x`, is enough.
+
+use super::{visit_sequence, LintContext, TypingLinter};
+use crate::{diag, naming::ast::Type_, parser::ast::FunctionName, typing::ast as T};
+
+pub const UNNECESSARY_MUT_REF: &str = "unnecessary_mut_ref";
+
+pub struct UnnecessaryMutRef;
+
+impl TypingLinter for UnnecessaryMutRef {
+    fn name(&self) -> &'static str {
+        UNNECESSARY_MUT_REF
+    }
+
+    fn function(&self, context: &mut LintContext, _name: FunctionName, fdef: &T::Function) {
+        use T::UnannotatedExp_ as E;
+        let seq = match &fdef.body.value {
+            T::FunctionBody_::Native => return,
+            T::FunctionBody_::Defined(seq) => seq,
+        };
+        visit_sequence(seq, &mut |e| {
+            let call = match &e.exp.value {
+                E::ModuleCall(call) => call,
+                _ => return,
+            };
+            let arguments = match &call.arguments.exp.value {
+                E::ExpList(items) => items
+                    .iter()
+                    .map(|item| match item {
+                        T::ExpListItem::Single(e, _) => Some(e),
+                        T::ExpListItem::Splat(_, _, _) => None,
+                    })
+                    .collect::<Option<Vec<_>>>(),
+                _ => Some(vec![&*call.arguments]),
+            };
+            let arguments = match arguments {
+                Some(arguments) if arguments.len() == call.parameter_types.len() => arguments,
+                _ => return,
+            };
+            for (arg, param_ty) in arguments.into_iter().zip(&call.parameter_types) {
+                let is_mut_borrow = matches!(
+                    &arg.exp.value,
+                    E::BorrowLocal(true, _) | E::TempBorrow(true, _) | E::Borrow(true, _, _)
+                );
+                if is_mut_borrow && matches!(&param_ty.value, Type_::Ref(false, _)) {
+                    let msg = format!(
+                        "Unnecessary mutable borrow. The parameter of '{}::{}' is an immutable \
+                         reference, consider using '&' instead",
+                        call.module, call.name
+                    );
+                    context.add_diag(diag!(Lint::UnnecessaryMutRef, (arg.exp.loc, msg)))
+                }
+            }
+        })
+    }
+}
@@ -9,6 +9,7 @@ use crate::{
     },
     parser::ast::{
         BinOp, ConstantName, Field, FunctionName, StructName, UnaryOp, Var, ENTRY_MODIFIER,
+        INLINE_MODIFIER,
     },
     shared::{ast_debug::*, unique_map::UniqueMap, *},
 };
@@ -111,6 +112,7 @@ pub struct Function {
     pub attributes: Attributes,
     pub visibility: Visibility,
     pub entry: Option<Loc>,
+    pub inline: bool,
     pub signature: FunctionSignature,
     pub acquires: BTreeMap<StructName, Loc>,
     pub body: FunctionBody,
@@ -187,6 +189,8 @@ pub enum Type_ {
     Ref(bool, Box<Type>),
     Param(TParam),
     Apply(Option<AbilitySet>, TypeName, Vec<Type>),
+    // Only valid for parameters of 'inline' functions
+    Fun(Vec<Type>, Box<Type>),
     Var(TVar),
     Anything,
     UnresolvedError,
@@ -244,6 +248,8 @@ pub enum Exp_ {
     ),
     Builtin(BuiltinFunction, Spanned<Vec<Exp>>),
     Vector(Loc, Option<Type>, Spanned<Vec<Exp>>),
+    // Call of a local with a function type, i.e. a lambda parameter of an 'inline' function
+    VarCall(Var, Spanned<Vec<Exp>>),
 
     IfElse(Box<Exp>, Box<Exp>, Box<Exp>),
     While(Box<Exp>, Box<Exp>),
@@ -275,6 +281,9 @@ pub enum Exp_ {
     Cast(Box<Exp>, Type),
     Annotate(Box<Exp>, Type),
 
+    // Only valid as an argument to an 'inline' function
+    Lambda(LValueList, Box<Exp>),
+
     Spec(SpecId, BTreeSet<Var>),
 
     UnresolvedError,
@@ -734,6 +743,7 @@ impl AstDebug for (FunctionName, &Function) {
                 attributes,
                 visibility,
                 entry,
+                inline,
                 signature,
                 acquires,
                 body,
@@ -744,6 +754,9 @@ impl AstDebug for (FunctionName, &Function) {
         if entry.is_some() {
             w.write(&format!("{} ", ENTRY_MODIFIER));
         }
+        if *inline {
+            w.write(&format!("{} ", INLINE_MODIFIER));
+        }
         if let FunctionBody_::Native = &body.value {
             w.write("native ");
         }
@@ -904,6 +917,12 @@ impl AstDebug for Type_ {
                     }),
                 }
             }
+            Type_::Fun(args, result) => {
+                w.write("|");
+                w.comma(args, |w, ty| ty.ast_debug(w));
+                w.write("|");
+                result.ast_debug(w);
+            }
             Type_::Var(tv) => w.write(&format!("#{}", tv.0)),
             Type_::Anything => w.write("_"),
             Type_::UnresolvedError => w.write("_|_"),
@@ -976,6 +995,12 @@ impl AstDebug for Exp_ {
                 w.comma(rhs, |w, e| e.ast_debug(w));
                 w.write(")");
             }
+            E::VarCall(v, sp!(_, rhs)) => {
+                w.write(&format!("{}", v));
+                w.write("(");
+                w.comma(rhs, |w, e| e.ast_debug(w));
+                w.write(")");
+            }
             E::Vector(_loc, ty_opt, sp!(_, elems)) => {
                 w.write("vector");
                 if let Some(ty) = ty_opt {
@@ -1095,6 +1120,12 @@ impl AstDebug for Exp_ {
                 ty.ast_debug(w);
                 w.write(")");
             }
+            E::Lambda(sp!(_, bs), e) => {
+                w.write("|");
+                bs.ast_debug(w);
+                w.write("| ");
+                e.ast_debug(w);
+            }
             E::Spec(u, used_locals) => {
                 w.write(&format!("spec #{}", u));
                 if !used_locals.is_empty() {
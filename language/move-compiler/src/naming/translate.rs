@@ -490,6 +490,7 @@ fn function(
         loc: _,
         visibility,
         entry,
+        inline,
         signature,
         acquires,
         body,
@@ -502,6 +503,7 @@ fn function(
         attributes,
         visibility,
         entry,
+        inline,
         signature,
         acquires,
         body,
@@ -794,7 +796,11 @@ fn type_(context: &mut Context, sp!(loc, ety_): E::Type) -> N::Type {
                 }
             }
         }
-        ET::Fun(_, _) => panic!("ICE only allowed in spec context"),
+        ET::Fun(args, result) => {
+            let args = types(context, args);
+            let result = type_(context, *result);
+            NT::Fun(args, Box::new(result))
+        }
     };
     sp(loc, ty_)
 }
@@ -1024,6 +1030,8 @@ fn exp_(context: &mut Context, e: E::Exp) -> N::Exp {
                     }
                 }
 
+                // Might be a call to a lambda parameter. Checked during typing
+                EA::Name(n) if ty_args.is_none() => NE::VarCall(Var(n), nes),
                 EA::Name(n) => {
                     context.env.add_diag(diag!(
                         NameResolution::UnboundUnscopedName,
@@ -1058,6 +1066,18 @@ fn exp_(context: &mut Context, e: E::Exp) -> N::Exp {
             NE::Vector(vec_loc, ty_opt, nes)
         }
 
+        EE::Lambda(elvs, e) => {
+            let nlvs_opt = bind_list(context, elvs);
+            let ne = exp(context, *e);
+            match nlvs_opt {
+                None => {
+                    assert!(context.env.has_errors());
+                    NE::UnresolvedError
+                }
+                Some(nlvs) => NE::Lambda(nlvs, ne),
+            }
+        }
+
         EE::Spec(u, unbound_names) => {
             // Vars currently aren't shadowable by types/functions
             let used_locals = unbound_names.into_iter().map(Var).collect();
@@ -1068,7 +1088,7 @@ fn exp_(context: &mut Context, e: E::Exp) -> N::Exp {
             NE::UnresolvedError
         }
         // `Name` matches name variants only allowed in specs (we handle the allowed ones above)
-        EE::Index(..) | EE::Lambda(..) | EE::Quant(..) | EE::Name(_, Some(_)) => {
+        EE::Index(..) | EE::Quant(..) | EE::Name(_, Some(_)) => {
             panic!("ICE unexpected specification construct")
         }
     };
@@ -232,6 +232,7 @@ new_name!(FunctionName);
 
 pub const NATIVE_MODIFIER: &str = "native";
 pub const ENTRY_MODIFIER: &str = "entry";
+pub const INLINE_MODIFIER: &str = "inline";
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct FunctionSignature {
@@ -260,11 +261,15 @@ pub type FunctionBody = Spanned<FunctionBody_>;
 //    body
 //  }
 // (public?) native foo<T1(: copyable?), ..., TN(: copyable?)>(x1: t1, ..., xn: tn): t1 * ... * tn;
+// (public?) inline foo<T1(: copyable?), ..., TN(: copyable?)>(x1: t1, ..., xn: |t1|t2): t1 {
+//    body
+//  }
 pub struct Function {
     pub attributes: Vec<Attributes>,
     pub loc: Loc,
     pub visibility: Visibility,
     pub entry: Option<Loc>,
+    pub inline: bool,
     pub signature: FunctionSignature,
     pub acquires: Vec<NameAccessChain>,
     pub name: FunctionName,
@@ -474,6 +479,15 @@ pub type BindList = Spanned<Vec<Bind>>;
 pub type BindWithRange = Spanned<(Bind, Exp)>;
 pub type BindWithRangeList = Spanned<Vec<BindWithRange>>;
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForIter_ {
+    // e1..e2
+    Range(Box<Exp>, Box<Exp>),
+    // e, a reference to a vector. The flag is set if its elements are borrowed mutably
+    Vector(bool, Box<Exp>),
+}
+pub type ForIter = Spanned<ForIter_>;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Value_ {
     // @<num>
@@ -589,6 +603,9 @@ pub enum Exp_ {
     While(Box<Exp>, Box<Exp>),
     // loop eloop
     Loop(Box<Exp>),
+    // for (x in iter) eloop
+    // for (x in iter) eloop spec { ... }
+    For(Var, ForIter, Box<Exp>, Option<SpecBlock>),
 
     // { seq }
     Block(Sequence),
@@ -1462,6 +1479,7 @@ impl AstDebug for Function {
             loc: _loc,
             visibility,
             entry,
+            inline,
             signature,
             acquires,
             name,
@@ -1472,6 +1490,9 @@ impl AstDebug for Function {
         if entry.is_some() {
             w.write(&format!("{} ", ENTRY_MODIFIER));
         }
+        if *inline {
+            w.write(&format!("{} ", INLINE_MODIFIER));
+        }
         if let FunctionBody_::Native = &body.value {
             w.write("native ");
         }
@@ -1761,6 +1782,16 @@ impl AstDebug for Exp_ {
                 w.write("loop ");
                 e.ast_debug(w);
             }
+            E::For(v, iter, e, spec_opt) => {
+                w.write(&format!("for ({} in ", v));
+                iter.ast_debug(w);
+                w.write(")");
+                e.ast_debug(w);
+                if let Some(spec) = spec_opt {
+                    w.write(" ");
+                    spec.ast_debug(w);
+                }
+            }
             E::Block(seq) => w.block(|w| seq.ast_debug(w)),
             E::Lambda(sp!(_, bs), e) => {
                 w.write("fun ");
@@ -1940,6 +1971,19 @@ impl AstDebug for Vec<Vec<Exp>> {
     }
 }
 
+impl AstDebug for ForIter_ {
+    fn ast_debug(&self, w: &mut AstWriter) {
+        match self {
+            ForIter_::Range(lo, hi) => {
+                lo.ast_debug(w);
+                w.write("..");
+                hi.ast_debug(w);
+            }
+            ForIter_::Vector(_, e) => e.ast_debug(w),
+        }
+    }
+}
+
 impl AstDebug for Bind_ {
     fn ast_debug(&self, w: &mut AstWriter) {
         use Bind_ as B;
@@ -2,7 +2,11 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{diag, diagnostics::Diagnostics};
+use crate::{
+    diag,
+    diagnostics::Diagnostics,
+    parser::lexer::{Lexer, Tok},
+};
 use move_command_line_common::{character_sets::is_permitted_chars, files::FileHash};
 use move_ir_types::location::*;
 use std::collections::BTreeMap;
@@ -12,6 +16,14 @@ pub type CommentMap = BTreeMap<FileHash, MatchedFileCommentMap>;
 pub type MatchedFileCommentMap = BTreeMap<u32, String>;
 pub type FileCommentMap = BTreeMap<(u32, u32), String>;
 
+/// A comment as written in the source, documentation or not. Both the location and the content
+/// include the comment delimiters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub loc: Loc,
+    pub content: String,
+}
+
 // We restrict strings to only ascii visual characters (0x20 <= c <= 0x7E) or a permitted newline
 // character--\r--,--\n--or a tab--\t.
 pub fn verify_string(file_hash: FileHash, string: &str) -> Result<(), Diagnostics> {
@@ -35,3 +47,67 @@ pub fn verify_string(file_hash: FileHash, string: &str) -> Result<(), Diagnostic
         }
     }
 }
+
+/// Collects every comment of the file, in order of appearance. The lexer only keeps documentation
+/// comments, so this walks the tokens and scans the gaps between them, which can only contain
+/// whitespace and comments.
+pub fn extract_comments(file_hash: FileHash, input: &str) -> Result<Vec<Comment>, Diagnostics> {
+    let mut tokens = Lexer::new(input, file_hash);
+    let mut comments = vec![];
+    loop {
+        tokens
+            .advance()
+            .map_err(|diag| Diagnostics::from(vec![*diag]))?;
+        let gap_start = tokens.previous_end_loc();
+        let gap_end = tokens.start_loc();
+        extract_gap_comments(file_hash, input, gap_start, gap_end, &mut comments);
+        if tokens.peek() == Tok::EOF {
+            break;
+        }
+    }
+    Ok(comments)
+}
+
+fn extract_gap_comments(
+    file_hash: FileHash,
+    input: &str,
+    gap_start: usize,
+    gap_end: usize,
+    comments: &mut Vec<Comment>,
+) {
+    let gap = &input.as_bytes()[..gap_end];
+    let mut start = gap_start;
+    while start < gap_end {
+        let end = if gap[start..].starts_with(b"//") {
+            let len = gap[start..].iter().position(|c| *c == b'\n');
+            start + len.unwrap_or(gap_end - start)
+        } else if gap[start..].starts_with(b"/*") {
+            // Block comments can be nested, as in '/* /* ... */ */'
+            let mut depth = 0;
+            let mut end = start;
+            while end < gap_end {
+                if gap[end..].starts_with(b"/*") {
+                    depth += 1;
+                    end += 2;
+                } else if gap[end..].starts_with(b"*/") {
+                    depth -= 1;
+                    end += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    end += 1;
+                }
+            }
+            end
+        } else {
+            start += 1;
+            continue;
+        };
+        comments.push(Comment {
+            loc: Loc::new(file_hash, start as u32, end as u32),
+            content: input[start..end].to_string(),
+        });
+        start = end;
+    }
+}
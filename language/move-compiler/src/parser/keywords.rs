@@ -44,8 +44,10 @@ pub const CONTEXTUAL_KEYWORDS: &[&str] = &[
     "emits",
     "ensures",
     "except",
+    "for",
     "forall",
     "global",
+    "in",
     "include",
     "internal",
     "local",
@@ -341,6 +341,21 @@ impl<'input> Lexer<'input> {
         Ok((first, second))
     }
 
+    // Look ahead to the next `n` tokens after the current one and return them, together with
+    // their content, without advancing the state of the lexer.
+    pub fn lookahead_n(&mut self, n: usize) -> Result<Vec<(Tok, &'input str)>, Box<Diagnostic>> {
+        let mut toks = Vec::with_capacity(n);
+        let mut offset = self.cur_end;
+        for _ in 0..n {
+            let text = self.trim_whitespace_and_comments(offset)?;
+            let start = self.text.len() - text.len();
+            let (tok, length) = find_token(self.file_hash, text, start)?;
+            toks.push((tok, &self.text[start..start + length]));
+            offset = start + length;
+        }
+        Ok(toks)
+    }
+
     // Matches the doc comments after the last token (or the beginning of the file) to the position
     // of the current token. This moves the comments out of `doc_comments` and
     // into `matched_doc_comments`. At the end of parsing, if `doc_comments` is not empty, errors
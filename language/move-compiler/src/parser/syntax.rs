@@ -18,6 +18,8 @@ use crate::{
     MatchedFileCommentMap,
 };
 
+const FOR_IDENT: &str = "for";
+
 struct Context<'env, 'lexer, 'input> {
     env: &'env mut CompilationEnv,
     tokens: &'lexer mut Lexer<'input>,
@@ -428,6 +430,7 @@ struct Modifiers {
     visibility: Option<Visibility>,
     entry: Option<Loc>,
     native: Option<Loc>,
+    inline: Option<Loc>,
 }
 
 impl Modifiers {
@@ -436,14 +439,15 @@ impl Modifiers {
             visibility: None,
             entry: None,
             native: None,
+            inline: None,
         }
     }
 }
 
-// Parse module member modifiers: visiblility and native.
+// Parse module member modifiers: visiblility, native, entry, and inline.
 // The modifiers are also used for script-functions
 //      ModuleMemberModifiers = <ModuleMemberModifier>*
-//      ModuleMemberModifier = <Visibility> | "native"
+//      ModuleMemberModifier = <Visibility> | "native" | "entry" | "inline"
 // ModuleMemberModifiers checks for uniqueness, meaning each individual ModuleMemberModifier can
 // appear only once
 fn parse_module_member_modifiers(context: &mut Context) -> Result<Modifiers, Box<Diagnostic>> {
@@ -491,6 +495,20 @@ fn parse_module_member_modifiers(context: &mut Context) -> Result<Modifiers, Box
                 }
                 mods.entry = Some(loc)
             }
+            Tok::Identifier if context.tokens.content() == INLINE_MODIFIER => {
+                let loc = current_token_loc(context.tokens);
+                context.tokens.advance()?;
+                if let Some(prev_loc) = mods.inline {
+                    let msg = format!("Duplicate '{}' modifier", INLINE_MODIFIER);
+                    let prev_msg = format!("'{}' modifier previously given here", INLINE_MODIFIER);
+                    context.env.add_diag(diag!(
+                        Declarations::DuplicateItem,
+                        (loc, msg),
+                        (prev_loc, prev_msg)
+                    ))
+                }
+                mods.inline = Some(loc)
+            }
             _ => break,
         }
     }
@@ -703,15 +721,21 @@ fn parse_bind_list(context: &mut Context) -> Result<BindList, Box<Diagnostic>> {
 // Parse a list of bindings for lambda.
 //      LambdaBindList =
 //          "|" Comma<Bind> "|"
+//          | "||"
 fn parse_lambda_bind_list(context: &mut Context) -> Result<BindList, Box<Diagnostic>> {
     let start_loc = context.tokens.start_loc();
-    let b = parse_comma_list(
-        context,
-        Tok::Pipe,
-        Tok::Pipe,
-        parse_bind,
-        "a variable or structure binding",
-    )?;
+    let b = if context.tokens.peek() == Tok::PipePipe {
+        context.tokens.advance()?;
+        vec![]
+    } else {
+        parse_comma_list(
+            context,
+            Tok::Pipe,
+            Tok::Pipe,
+            parse_bind,
+            "a variable or structure binding",
+        )?
+    };
     let end_loc = context.tokens.previous_end_loc();
     Ok(spanned(context.tokens.file_hash(), start_loc, end_loc, b))
 }
@@ -890,6 +914,8 @@ fn parse_sequence(context: &mut Context) -> Result<Sequence, Box<Diagnostic>> {
 //          | "if" "(" <Exp> ")" <Exp> ("else" <Exp>)?
 //          | "while" "(" <Exp> ")" "{" <Exp> "}"
 //          | "while" "(" <Exp> ")" <Exp> (SpecBlock)?
+//          | "for" "(" <Var> "in" <ForIter> ")" "{" <Exp> "}"
+//          | "for" "(" <Var> "in" <ForIter> ")" <Exp> (SpecBlock)?
 //          | "loop" <Exp>
 //          | "loop" "{" <Exp> "}"
 //          | "return" "{" <Exp> "}"
@@ -901,7 +927,7 @@ fn parse_term(context: &mut Context) -> Result<Exp, Box<Diagnostic>> {
 
     let start_loc = context.tokens.start_loc();
     let term = match context.tokens.peek() {
-        tok if is_control_exp(tok) => {
+        tok if is_control_exp(tok) || is_for_loop(context) => {
             let (control_exp, ends_in_block) = parse_control_exp(context)?;
             if !ends_in_block || at_end_of_exp(context) {
                 return Ok(control_exp);
@@ -1043,6 +1069,25 @@ fn is_control_exp(tok: Tok) -> bool {
     )
 }
 
+// Note that "for" is not a token. It starts a loop only if it is followed by "(" <Identifier> "in",
+// otherwise it is the name of a variable or function.
+fn is_for_loop(context: &mut Context) -> bool {
+    if context.tokens.peek() != Tok::Identifier || context.tokens.content() != FOR_IDENT {
+        return false;
+    }
+    match context.tokens.lookahead_n(3) {
+        Ok(toks) => matches!(
+            toks.as_slice(),
+            [
+                (Tok::LParen, _),
+                (Tok::Identifier, _),
+                (Tok::Identifier, "in")
+            ]
+        ),
+        Err(_) => false,
+    }
+}
+
 // if there is a block, only parse the block, not any subsequent tokens
 // e.g.           if (cond) e1 else { e2 } + 1
 // should be,    (if (cond) e1 else { e2 }) + 1
@@ -1091,25 +1136,9 @@ fn parse_control_exp(context: &mut Context) -> Result<(Exp, bool), Box<Diagnosti
             consume_token(context.tokens, Tok::RParen)?;
             let (eloop, ends_in_block) = parse_exp_or_sequence(context)?;
             let (econd, ends_in_block) = if context.tokens.peek() == Tok::Spec {
-                // Parse a loop invariant. Also validate that only `invariant`
-                // properties are contained in the spec block. This is
-                // transformed into `while ({spec { .. }; cond) body`.
-                let spec = parse_spec_block(vec![], context)?;
-                for member in &spec.value.members {
-                    match member.value {
-                        // Ok
-                        SpecBlockMember_::Condition {
-                            kind: sp!(_, SpecConditionKind_::Invariant(..)),
-                            ..
-                        } => (),
-                        _ => {
-                            return Err(Box::new(diag!(
-                                Syntax::InvalidSpecBlockMember,
-                                (member.loc, "only 'invariant' allowed here")
-                            )))
-                        }
-                    }
-                }
+                // Parse a loop invariant. This is transformed into
+                // `while ({spec { .. }; cond) body`.
+                let spec = parse_loop_invariant(context)?;
                 let spec_seq = sp(
                     spec.loc,
                     SequenceItem_::Seq(Box::new(sp(spec.loc, Exp_::Spec(spec)))),
@@ -1127,6 +1156,26 @@ fn parse_control_exp(context: &mut Context) -> Result<(Exp, bool), Box<Diagnosti
             let (eloop, ends_in_block) = parse_exp_or_sequence(context)?;
             (Exp_::Loop(Box::new(eloop)), ends_in_block)
         }
+        Tok::Identifier => {
+            consume_identifier(context.tokens, FOR_IDENT)?;
+            consume_token(context.tokens, Tok::LParen)?;
+            let var = parse_var(context)?;
+            consume_identifier(context.tokens, "in")?;
+            let iter = parse_for_iter(context)?;
+            consume_token(context.tokens, Tok::RParen)?;
+            let (eloop, ends_in_block) = parse_exp_or_sequence(context)?;
+            let (spec_opt, ends_in_block) = if context.tokens.peek() == Tok::Spec {
+                // Parse a loop invariant, which is attached to the condition of the
+                // loop when it is desugared during expansion.
+                (Some(parse_loop_invariant(context)?), true)
+            } else {
+                (None, ends_in_block)
+            };
+            (
+                Exp_::For(var, iter, Box::new(eloop), spec_opt),
+                ends_in_block,
+            )
+        }
         Tok::Return => {
             context.tokens.advance()?;
             let (e, ends_in_block) = if !at_start_of_exp(context) {
@@ -1149,6 +1198,54 @@ fn parse_control_exp(context: &mut Context) -> Result<(Exp, bool), Box<Diagnosti
     Ok((exp, ends_in_block))
 }
 
+// Parse a loop invariant:
+//      LoopInvariant = <SpecBlock>
+//
+// Also validate that only `invariant` properties are contained in the spec block.
+fn parse_loop_invariant(context: &mut Context) -> Result<SpecBlock, Box<Diagnostic>> {
+    let spec = parse_spec_block(vec![], context)?;
+    for member in &spec.value.members {
+        match member.value {
+            // Ok
+            SpecBlockMember_::Condition {
+                kind: sp!(_, SpecConditionKind_::Invariant(..)),
+                ..
+            } => (),
+            _ => {
+                return Err(Box::new(diag!(
+                    Syntax::InvalidSpecBlockMember,
+                    (member.loc, "only 'invariant' allowed here")
+                )))
+            }
+        }
+    }
+    Ok(spec)
+}
+
+// Parse what is iterated over by a "for" loop:
+//      ForIter = <Exp> ".." <Exp> | <Exp>
+//
+// Anything other than a range is a reference to a vector, and the elements are borrowed mutably
+// if it is of the form "&mut" <Exp>. For "&" "*" <Exp> and "&mut" "*" <Exp>, the loop is over the
+// vector that the inner expression refers to, and not over a copy of it.
+fn parse_for_iter(context: &mut Context) -> Result<ForIter, Box<Diagnostic>> {
+    let sp!(loc, e_) = parse_exp(context)?;
+    let iter_ = match e_ {
+        Exp_::BinopExp(lo, sp!(_, BinOp_::Range), hi) => ForIter_::Range(lo, hi),
+        Exp_::Borrow(mut_, inner) if matches!(inner.value, Exp_::Dereference(_)) => {
+            match inner.value {
+                Exp_::Dereference(e) => ForIter_::Vector(mut_, e),
+                _ => unreachable!(),
+            }
+        }
+        e_ => {
+            let mut_ = matches!(e_, Exp_::Borrow(true, _));
+            ForIter_::Vector(mut_, Box::new(sp(loc, e_)))
+        }
+    };
+    Ok(sp(loc, iter_))
+}
+
 // Parse a pack, call, or other reference to a name:
 //      NameExp =
 //          <NameAccessChain> <OptionalTypeArgs> "{" Comma<ExpField> "}"
@@ -1266,14 +1363,14 @@ fn at_start_of_exp(context: &mut Context) -> bool {
 
 // Parse an expression:
 //      Exp =
-//            <LambdaBindList> <Exp>        spec only
+//            <LambdaBindList> <Exp>        spec only, or argument to an 'inline' function
 //          | <Quantifier>                  spec only
 //          | <BinOpExp>
 //          | <UnaryExp> "=" <Exp>
 fn parse_exp(context: &mut Context) -> Result<Exp, Box<Diagnostic>> {
     let start_loc = context.tokens.start_loc();
     let exp = match context.tokens.peek() {
-        Tok::Pipe => {
+        Tok::Pipe | Tok::PipePipe => {
             let bindings = parse_lambda_bind_list(context)?;
             let body = Box::new(parse_exp(context)?);
             Exp_::Lambda(bindings, body)
@@ -1671,7 +1768,8 @@ fn make_builtin_call(loc: Loc, name: Symbol, type_args: Option<Vec<Type>>, args:
 //          <NameAccessChain> ('<' Comma<Type> ">")?
 //          | "&" <Type>
 //          | "&mut" <Type>
-//          | "|" Comma<Type> "|" Type   (spec only)
+//          | "|" Comma<Type> "|" Type?  (spec only, or parameter of an 'inline' function)
+//          | "||" Type?
 //          | "(" Comma<Type> ")"
 fn parse_type(context: &mut Context) -> Result<Type, Box<Diagnostic>> {
     let start_loc = context.tokens.start_loc();
@@ -1694,9 +1792,21 @@ fn parse_type(context: &mut Context) -> Result<Type, Box<Diagnostic>> {
             let t = parse_type(context)?;
             Type_::Ref(true, Box::new(t))
         }
-        Tok::Pipe => {
-            let args = parse_comma_list(context, Tok::Pipe, Tok::Pipe, parse_type, "a type")?;
-            let result = parse_type(context)?;
+        Tok::Pipe | Tok::PipePipe => {
+            let args = if context.tokens.peek() == Tok::PipePipe {
+                context.tokens.advance()?;
+                vec![]
+            } else {
+                parse_comma_list(context, Tok::Pipe, Tok::Pipe, parse_type, "a type")?
+            };
+            let result = match context.tokens.peek() {
+                // An omitted result type is '()'
+                Tok::Comma | Tok::RParen | Tok::Greater => {
+                    let end_loc = context.tokens.previous_end_loc();
+                    spanned(context.tokens.file_hash(), end_loc, end_loc, Type_::Unit)
+                }
+                _ => parse_type(context)?,
+            };
             return Ok(spanned(
                 context.tokens.file_hash(),
                 start_loc,
@@ -1880,6 +1990,7 @@ fn parse_function_decl(
         visibility,
         mut entry,
         native,
+        inline,
     } = modifiers;
 
     if let Some(Visibility::Script(vloc)) = visibility {
@@ -1898,6 +2009,31 @@ fn parse_function_decl(
         }
     }
 
+    if let Some(inline_loc) = inline {
+        if let Some(native_loc) = native {
+            let msg = format!(
+                "Invalid function declaration. '{}' functions cannot be '{}'",
+                NATIVE_MODIFIER, INLINE_MODIFIER
+            );
+            context.env.add_diag(diag!(
+                Syntax::InvalidModifier,
+                (inline_loc, msg),
+                (native_loc, "'native' modifier given here"),
+            ));
+        }
+        if let Some(entry_loc) = entry {
+            let msg = format!(
+                "Invalid function declaration. '{}' functions cannot be '{}'",
+                ENTRY_MODIFIER, INLINE_MODIFIER
+            );
+            context.env.add_diag(diag!(
+                Syntax::InvalidModifier,
+                (inline_loc, msg),
+                (entry_loc, "'entry' modifier given here"),
+            ));
+        }
+    }
+
     // "fun" <FunctionDefName>
     consume_token(context.tokens, Tok::Fun)?;
     let name = FunctionName(parse_identifier(context)?);
@@ -1970,6 +2106,7 @@ fn parse_function_decl(
         loc,
         visibility: visibility.unwrap_or(Visibility::Internal),
         entry,
+        inline: inline.is_some(),
         signature,
         acquires,
         name,
@@ -2006,6 +2143,7 @@ fn parse_struct_decl(
         visibility,
         entry,
         native,
+        inline,
     } = modifiers;
     if let Some(vis) = visibility {
         let msg = format!(
@@ -2026,6 +2164,15 @@ fn parse_struct_decl(
             .env
             .add_diag(diag!(Syntax::InvalidModifier, (loc, msg)));
     }
+    if let Some(loc) = inline {
+        let msg = format!(
+            "Invalid struct declaration. '{}' is used only on functions",
+            INLINE_MODIFIER
+        );
+        context
+            .env
+            .add_diag(diag!(Syntax::InvalidModifier, (loc, msg)));
+    }
 
     consume_token(context.tokens, Tok::Struct)?;
 
@@ -2118,6 +2265,7 @@ fn parse_constant_decl(
         visibility,
         entry,
         native,
+        inline,
     } = modifiers;
     if let Some(vis) = visibility {
         let msg = "Invalid constant declaration. Constants cannot have visibility modifiers as \
@@ -2135,6 +2283,15 @@ fn parse_constant_decl(
             .env
             .add_diag(diag!(Syntax::InvalidModifier, (loc, msg)));
     }
+    if let Some(loc) = inline {
+        let msg = format!(
+            "Invalid constant declaration. '{}' is used only on functions",
+            INLINE_MODIFIER
+        );
+        context
+            .env
+            .add_diag(diag!(Syntax::InvalidModifier, (loc, msg)));
+    }
     if let Some(loc) = native {
         let msg = "Invalid constant declaration. 'native' constants are not supported";
         context
@@ -5,6 +5,7 @@
 use crate::{
     command_line as cli,
     diagnostics::{codes::Severity, Diagnostic, Diagnostics},
+    linters::{self, Linter},
     naming::ast::ModuleDefinition,
 };
 use clap::*;
@@ -170,19 +171,26 @@ pub struct IndexedPackagePath {
 
 pub type AttributeDeriver = dyn Fn(&mut CompilationEnv, &mut ModuleDefinition);
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Debug)]
 pub struct CompilationEnv {
     flags: Flags,
     diags: Diagnostics,
+    linters: Vec<Linter>,
     // TODO(tzakian): Remove the global counter and use this counter instead
     // pub counter: u64,
 }
 
 impl CompilationEnv {
     pub fn new(flags: Flags) -> Self {
+        let linters = if flags.lint() {
+            linters::builtin()
+        } else {
+            vec![]
+        };
         Self {
             flags,
             diags: Diagnostics::new(),
+            linters,
         }
     }
 
@@ -238,6 +246,14 @@ impl CompilationEnv {
     pub fn flags(&self) -> &Flags {
         &self.flags
     }
+
+    pub fn add_linters(&mut self, linters: impl IntoIterator<Item = Linter>) {
+        self.linters.extend(linters)
+    }
+
+    pub fn linters(&self) -> &[Linter] {
+        &self.linters
+    }
 }
 
 //**************************************************************************************************
@@ -312,6 +328,12 @@ pub struct Flags {
     )]
     shadow: bool,
 
+    /// Run the built-in lints, reporting their findings as warnings
+    #[clap(
+        long = cli::LINT,
+    )]
+    lint: bool,
+
     /// Internal flag used by the model builder to maintain functions which would be otherwise
     /// included only in tests, without creating the unit test code regular tests do.
     #[clap(skip)]
@@ -326,6 +348,7 @@ impl Flags {
             shadow: false,
             flavor: "".to_string(),
             bytecode_version: None,
+            lint: false,
             keep_testing_functions: false,
         }
     }
@@ -337,6 +360,7 @@ impl Flags {
             shadow: false,
             flavor: "".to_string(),
             bytecode_version: None,
+            lint: false,
             keep_testing_functions: false,
         }
     }
@@ -348,6 +372,7 @@ impl Flags {
             shadow: true, // allows overlapping between sources and deps
             flavor: "".to_string(),
             bytecode_version: None,
+            lint: false,
             keep_testing_functions: false,
         }
     }
@@ -373,6 +398,10 @@ impl Flags {
         }
     }
 
+    pub fn set_lint(self, lint: bool) -> Self {
+        Self { lint, ..self }
+    }
+
     pub fn is_empty(&self) -> bool {
         self == &Self::empty()
     }
@@ -393,6 +422,10 @@ impl Flags {
         self.shadow
     }
 
+    pub fn lint(&self) -> bool {
+        self.lint
+    }
+
     pub fn has_flavor(&self, flavor: &str) -> bool {
         self.flavor == flavor
     }
@@ -428,6 +461,7 @@ pub mod known_attributes {
         Testing(TestingAttribute),
         Verification(VerificationAttribute),
         Native(NativeAttribute),
+        Lint(LintAttribute),
     }
 
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -436,6 +470,8 @@ pub mod known_attributes {
         TestOnly,
         // Is a test that will be run
         Test,
+        // Is a test that will be run with arguments generated for its parameters
+        RandomTest,
         // This test is expected to fail
         ExpectedFailure,
     }
@@ -452,6 +488,12 @@ pub mod known_attributes {
         BytecodeInstruction,
     }
 
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum LintAttribute {
+        // Silences the lints listed as parameters, e.g. `#[lint_allow(self_assignment)]`
+        Allow,
+    }
+
     impl fmt::Display for AttributePosition {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             match self {
@@ -472,6 +514,7 @@ pub mod known_attributes {
         pub fn resolve(attribute_str: impl AsRef<str>) -> Option<Self> {
             Some(match attribute_str.as_ref() {
                 TestingAttribute::TEST => Self::Testing(TestingAttribute::Test),
+                TestingAttribute::RANDOM_TEST => Self::Testing(TestingAttribute::RandomTest),
                 TestingAttribute::TEST_ONLY => Self::Testing(TestingAttribute::TestOnly),
                 TestingAttribute::EXPECTED_FAILURE => {
                     Self::Testing(TestingAttribute::ExpectedFailure)
@@ -482,6 +525,7 @@ pub mod known_attributes {
                 NativeAttribute::BYTECODE_INSTRUCTION => {
                     Self::Native(NativeAttribute::BytecodeInstruction)
                 }
+                LintAttribute::ALLOW => Self::Lint(LintAttribute::Allow),
                 _ => return None,
             })
         }
@@ -491,6 +535,7 @@ pub mod known_attributes {
                 Self::Testing(a) => a.name(),
                 Self::Verification(a) => a.name(),
                 Self::Native(a) => a.name(),
+                Self::Lint(a) => a.name(),
             }
         }
 
@@ -499,12 +544,14 @@ pub mod known_attributes {
                 Self::Testing(a) => a.expected_positions(),
                 Self::Verification(a) => a.expected_positions(),
                 Self::Native(a) => a.expected_positions(),
+                Self::Lint(a) => a.expected_positions(),
             }
         }
     }
 
     impl TestingAttribute {
         pub const TEST: &'static str = "test";
+        pub const RANDOM_TEST: &'static str = "random_test";
         pub const EXPECTED_FAILURE: &'static str = "expected_failure";
         pub const TEST_ONLY: &'static str = "test_only";
         pub const ABORT_CODE_NAME: &'static str = "abort_code";
@@ -518,6 +565,7 @@ pub mod known_attributes {
         pub const fn name(&self) -> &str {
             match self {
                 Self::Test => Self::TEST,
+                Self::RandomTest => Self::RANDOM_TEST,
                 Self::TestOnly => Self::TEST_ONLY,
                 Self::ExpectedFailure => Self::EXPECTED_FAILURE,
             }
@@ -542,7 +590,7 @@ pub mod known_attributes {
                 Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
             match self {
                 TestingAttribute::TestOnly => &TEST_ONLY_POSITIONS,
-                TestingAttribute::Test => &TEST_POSITIONS,
+                TestingAttribute::Test | TestingAttribute::RandomTest => &TEST_POSITIONS,
                 TestingAttribute::ExpectedFailure => &EXPECTED_FAILURE_POSITIONS,
             }
         }
@@ -603,4 +651,24 @@ pub mod known_attributes {
             }
         }
     }
+
+    impl LintAttribute {
+        pub const ALLOW: &'static str = "lint_allow";
+
+        pub const fn name(&self) -> &str {
+            match self {
+                LintAttribute::Allow => Self::ALLOW,
+            }
+        }
+
+        pub fn expected_positions(&self) -> &'static BTreeSet<AttributePosition> {
+            static ALLOW_POSITIONS: Lazy<BTreeSet<AttributePosition>> = Lazy::new(|| {
+                IntoIterator::into_iter([AttributePosition::Module, AttributePosition::Function])
+                    .collect()
+            });
+            match self {
+                LintAttribute::Allow => &ALLOW_POSITIONS,
+            }
+        }
+    }
 }
@@ -7,6 +7,7 @@ use crate::{
     naming::ast::{FunctionSignature, StructDefinition, Type, TypeName_, Type_},
     parser::ast::{
         BinOp, ConstantName, Field, FunctionName, StructName, UnaryOp, Var, ENTRY_MODIFIER,
+        INLINE_MODIFIER,
     },
     shared::{ast_debug::*, unique_map::UniqueMap},
 };
@@ -76,6 +77,7 @@ pub struct Function {
     pub attributes: Attributes,
     pub visibility: Visibility,
     pub entry: Option<Loc>,
+    pub inline: bool,
     pub signature: FunctionSignature,
     pub acquires: BTreeMap<StructName, Loc>,
     pub body: FunctionBody,
@@ -149,6 +151,8 @@ pub enum UnannotatedExp_ {
     ModuleCall(Box<ModuleCall>),
     Builtin(Box<BuiltinFunction>, Box<Exp>),
     Vector(Loc, usize, Box<Type>, Box<Exp>),
+    // Call of a lambda parameter. Removed when inlining
+    VarCall(Var, Box<Exp>),
 
     IfElse(Box<Exp>, Box<Exp>, Box<Exp>),
     While(Box<Exp>, Box<Exp>),
@@ -175,6 +179,9 @@ pub enum UnannotatedExp_ {
     Cast(Box<Exp>, Box<Type>),
     Annotate(Box<Exp>, Box<Type>),
 
+    // Argument to an 'inline' function. Removed when inlining
+    Lambda(LValueList, Box<Exp>),
+
     Spec(SpecId, BTreeMap<Var, Type>),
 
     UnresolvedError,
@@ -341,6 +348,7 @@ impl AstDebug for (FunctionName, &Function) {
                 attributes,
                 visibility,
                 entry,
+                inline,
                 signature,
                 acquires,
                 body,
@@ -351,6 +359,9 @@ impl AstDebug for (FunctionName, &Function) {
         if entry.is_some() {
             w.write(&format!("{} ", ENTRY_MODIFIER));
         }
+        if *inline {
+            w.write(&format!("{} ", INLINE_MODIFIER));
+        }
         if let FunctionBody_::Native = &body.value {
             w.write("native ");
         }
@@ -453,6 +464,12 @@ impl AstDebug for UnannotatedExp_ {
                 rhs.ast_debug(w);
                 w.write(")");
             }
+            E::VarCall(v, rhs) => {
+                w.write(&format!("{}", v));
+                w.write("(");
+                rhs.ast_debug(w);
+                w.write(")");
+            }
             E::Vector(_loc, usize, ty, elems) => {
                 w.write(format!("vector#{}", usize));
                 w.write("<");
@@ -585,6 +602,12 @@ impl AstDebug for UnannotatedExp_ {
                 ty.ast_debug(w);
                 w.write(")");
             }
+            E::Lambda(sp!(_, bs), e) => {
+                w.write("|");
+                bs.ast_debug(w);
+                w.write("| ");
+                e.ast_debug(w);
+            }
             E::Spec(u, used_locals) => {
                 w.write(&format!("spec #{}", u));
                 if !used_locals.is_empty() {
@@ -40,6 +40,7 @@ pub type TParamSubst = HashMap<TParamID, Type>;
 pub struct FunctionInfo {
     pub defined_loc: Loc,
     pub visibility: Visibility,
+    pub inline: bool,
     pub signature: FunctionSignature,
     pub acquires: BTreeMap<StructName, Loc>,
 }
@@ -78,6 +79,7 @@ pub struct Context<'env> {
     pub constraints: Constraints,
 
     loop_info: LoopInfo,
+    in_lambda: bool,
 }
 
 impl<'env> Context<'env> {
@@ -101,6 +103,7 @@ impl<'env> Context<'env> {
             let functions = mdef.functions.ref_map(|fname, fdef| FunctionInfo {
                 defined_loc: fname.loc(),
                 visibility: fdef.visibility.clone(),
+                inline: fdef.inline,
                 signature: fdef.signature.clone(),
                 acquires: fdef.acquires.clone(),
             });
@@ -126,6 +129,7 @@ impl<'env> Context<'env> {
             constraints: vec![],
             locals: UniqueMap::new(),
             loop_info: LoopInfo(LoopInfo_::NotInLoop),
+            in_lambda: false,
             modules,
             env,
         }
@@ -136,6 +140,10 @@ impl<'env> Context<'env> {
             matches!(&self.loop_info, LoopInfo(LoopInfo_::NotInLoop)),
             "ICE loop_info should be reset after the loop"
         );
+        assert!(
+            !self.in_lambda,
+            "ICE in_lambda should be reset after the lambda"
+        );
         self.return_type = None;
         self.locals = UniqueMap::new();
         self.subst = Subst::empty();
@@ -322,6 +330,17 @@ impl<'env> Context<'env> {
             .expect("ICE should have failed in naming")
     }
 
+    pub fn is_inline_function(&self, m: &ModuleIdent, n: &FunctionName) -> bool {
+        self.function_info(m, n).inline
+    }
+
+    pub fn current_function_is_inline(&self) -> bool {
+        match (&self.current_module, &self.current_function) {
+            (Some(m), Some(f)) => self.is_inline_function(m, f),
+            _ => false,
+        }
+    }
+
     fn constant_info(&mut self, m_opt: &Option<ModuleIdent>, n: &ConstantName) -> &ConstantInfo {
         let constants = match m_opt {
             None => self.current_script_constants.as_ref().unwrap(),
@@ -365,6 +384,26 @@ impl<'env> Context<'env> {
             LoopInfo_::BreakType(t) => Some(*t),
         }
     }
+
+    pub fn in_lambda(&self) -> bool {
+        self.in_lambda
+    }
+
+    // Loops outside of the lambda cannot be exited from inside of its body
+    pub fn enter_lambda(&mut self) -> (LoopInfo, bool) {
+        let old_loop_info = std::mem::replace(&mut self.loop_info, LoopInfo(LoopInfo_::NotInLoop));
+        let old_in_lambda = std::mem::replace(&mut self.in_lambda, true);
+        (old_loop_info, old_in_lambda)
+    }
+
+    pub fn exit_lambda(&mut self, (old_loop_info, old_in_lambda): (LoopInfo, bool)) {
+        assert!(
+            matches!(&self.loop_info, LoopInfo(LoopInfo_::NotInLoop)),
+            "ICE loop_info should be reset after the loop"
+        );
+        self.loop_info = old_loop_info;
+        self.in_lambda = old_in_lambda;
+    }
 }
 
 //**************************************************************************************************
@@ -493,6 +532,11 @@ fn error_format_impl_(b_: &Type_, subst: &Subst, nested: bool) -> String {
             if *mut_ { "mut " } else { "" },
             error_format_nested(ty, subst)
         ),
+        Fun(args, result) => format!(
+            "|{}|{}",
+            format_comma(args.iter().map(|t| error_format_nested(t, subst))),
+            error_format_nested(result, subst)
+        ),
     };
     if nested {
         res
@@ -511,6 +555,7 @@ pub fn infer_abilities(context: &Context, subst: &Subst, ty: Type) -> AbilitySet
     match unfold_type(subst, ty).value {
         T::Unit => AbilitySet::collection(loc),
         T::Ref(_, _) => AbilitySet::references(loc),
+        T::Fun(_, _) => AbilitySet::empty(),
         T::Var(_) => unreachable!("ICE unfold_type failed, which is impossible"),
         T::UnresolvedError | T::Anything => AbilitySet::all(loc),
         T::Param(TParam { abilities, .. }) | T::Apply(Some(abilities), _, _) => abilities,
@@ -553,6 +598,7 @@ fn debug_abilities_info(context: &Context, ty: &Type) -> (Option<Loc>, AbilitySe
     let loc = ty.loc;
     match &ty.value {
         T::Unit | T::Ref(_, _) => (None, AbilitySet::references(loc), vec![]),
+        T::Fun(_, _) => (None, AbilitySet::empty(), vec![]),
         T::Var(_) => panic!("ICE call unfold_type before debug_abilities_info"),
         T::UnresolvedError | T::Anything => (None, AbilitySet::all(loc), vec![]),
         T::Param(TParam {
@@ -747,19 +793,22 @@ pub fn make_constant_type(
 // Functions
 //**************************************************************************************************
 
+// The defining location, type arguments, parameters, acquires, and return type of a function
+pub type FunctionType = (
+    Loc,
+    Vec<Type>,
+    Vec<(Var, Type)>,
+    BTreeMap<StructName, Loc>,
+    Type,
+);
+
 pub fn make_function_type(
     context: &mut Context,
     loc: Loc,
     m: &ModuleIdent,
     f: &FunctionName,
     ty_args_opt: Option<Vec<Type>>,
-) -> (
-    Loc,
-    Vec<Type>,
-    Vec<(Var, Type)>,
-    BTreeMap<StructName, Loc>,
-    Type,
-) {
+) -> FunctionType {
     let in_current_module = match &context.current_module {
         Some(current) => m == current,
         None => false,
@@ -1040,7 +1089,7 @@ fn solve_base_type_constraint(context: &mut Context, loc: Loc, msg: String, ty:
     let sp!(tyloc, unfolded_) = unfold_type(&context.subst, ty.clone());
     match unfolded_ {
         Var(_) => unreachable!(),
-        Unit | Ref(_, _) | Apply(_, sp!(_, Multiple(_)), _) => {
+        Unit | Ref(_, _) | Fun(_, _) | Apply(_, sp!(_, Multiple(_)), _) => {
             let tystr = error_format(ty, &context.subst);
             let tmsg = format!("Expected a single non-reference type, but found: {}", tystr);
             context.env.add_diag(diag!(
@@ -1070,7 +1119,7 @@ fn solve_single_type_constraint(context: &mut Context, loc: Loc, msg: String, ty
                 (tyloc, tmsg)
             ))
         }
-        UnresolvedError | Anything | Ref(_, _) | Param(_) | Apply(_, _, _) => (),
+        UnresolvedError | Anything | Ref(_, _) | Fun(_, _) | Param(_) | Apply(_, _, _) => (),
     }
 }
 
@@ -1133,6 +1182,11 @@ pub fn subst_tparams(subst: &TParamSubst, sp!(loc, t_): Type) -> Type {
         x @ Unit | x @ UnresolvedError | x @ Anything => sp(loc, x),
         Var(_) => panic!("ICE tvar in subst_tparams"),
         Ref(mut_, t) => sp(loc, Ref(mut_, Box::new(subst_tparams(subst, *t)))),
+        Fun(args, result) => {
+            let args = args.into_iter().map(|t| subst_tparams(subst, t)).collect();
+            let result = subst_tparams(subst, *result);
+            sp(loc, Fun(args, Box::new(result)))
+        }
         Param(tp) => subst
             .get(&tp.id)
             .expect("ICE unmapped tparam in subst_tparams_base")
@@ -1152,6 +1206,11 @@ pub fn ready_tvars(subst: &Subst, sp!(loc, t_): Type) -> Type {
     match t_ {
         x @ UnresolvedError | x @ Unit | x @ Anything | x @ Param(_) => sp(loc, x),
         Ref(mut_, t) => sp(loc, Ref(mut_, Box::new(ready_tvars(subst, *t)))),
+        Fun(args, result) => {
+            let args = args.into_iter().map(|t| ready_tvars(subst, t)).collect();
+            let result = ready_tvars(subst, *result);
+            sp(loc, Fun(args, Box::new(result)))
+        }
         Apply(k, n, tys) => {
             let tys = tys.into_iter().map(|t| ready_tvars(subst, t)).collect();
             sp(loc, Apply(k, n, tys))
@@ -1182,6 +1241,11 @@ pub fn instantiate(context: &mut Context, sp!(loc, t_): Type) -> Type {
             context.add_base_type_constraint(loc, "Invalid reference type", inner.clone());
             Ref(mut_, Box::new(instantiate(context, inner)))
         }
+        Fun(args, result) => {
+            let args = args.into_iter().map(|t| instantiate(context, t)).collect();
+            let result = instantiate(context, *result);
+            Fun(args, Box::new(result))
+        }
         Apply(abilities_opt, n, ty_args) => {
             instantiate_apply(context, loc, abilities_opt, n, ty_args)
         }
@@ -1386,6 +1450,17 @@ fn join_impl(
             let (subst, t) = join_impl(subst, case, t1, t2)?;
             Ok((subst, sp(loc, Ref(mut_, Box::new(t)))))
         }
+        (sp!(_, Fun(args1, result1)), sp!(loc, Fun(args2, result2)))
+            if args1.len() == args2.len() =>
+        {
+            // Arguments are contravariant
+            let (subst, args) = match case {
+                Join => join_impl_types(subst, case, args1, args2)?,
+                Subtype => join_impl_types(subst, case, args2, args1)?,
+            };
+            let (subst, result) = join_impl(subst, case, result1, result2)?;
+            Ok((subst, sp(*loc, Fun(args, Box::new(result)))))
+        }
         (sp!(_, Param(TParam { id: id1, .. })), sp!(_, Param(TParam { id: id2, .. })))
             if id1 == id2 =>
         {
@@ -1555,6 +1630,10 @@ fn join_bind_tvar(subst: &mut Subst, loc: Loc, tvar: TVar, ty: Type) -> Result<b
                 used.insert(*v, *loc);
             }
             T::Ref(_, inner) => used_tvars(used, inner),
+            T::Fun(args, result) => {
+                args.iter().rev().for_each(|arg| used_tvars(used, arg));
+                used_tvars(used, result)
+            }
             T::Apply(_, _, inners) => inners
                 .iter()
                 .rev()
@@ -52,6 +52,10 @@ pub fn type_(context: &mut Context, ty: &mut Type) {
     match &mut ty.value {
         Anything | UnresolvedError | Param(_) | Unit => (),
         Ref(_, b) => type_(context, b),
+        Fun(args, result) => {
+            types(context, args);
+            type_(context, result);
+        }
         Var(tvar) => {
             let ty_tvar = sp(ty.loc, Var(*tvar));
             let replacement = core::unfold_type(&context.subst, ty_tvar);
@@ -234,6 +238,7 @@ pub fn exp(context: &mut Context, e: &mut T::Exp) {
             builtin_function(context, b);
             exp(context, args);
         }
+        E::VarCall(_, args) => exp(context, args),
         E::Vector(_vec_loc, _n, ty_arg, args) => {
             type_(context, ty_arg);
             exp(context, args);
@@ -284,6 +289,10 @@ pub fn exp(context: &mut Context, e: &mut T::Exp) {
             exp(context, el);
             type_(context, rhs_ty);
         }
+        E::Lambda(binds, body) => {
+            lvalues(context, binds);
+            exp(context, body);
+        }
     }
 }
 
@@ -116,6 +116,7 @@ fn exp(
             exp(context, annotated_acquires, seen, args);
         }
         E::Vector(_vec_loc, _n, _targ, args) => exp(context, annotated_acquires, seen, args),
+        E::VarCall(_, args) => exp(context, annotated_acquires, seen, args),
 
         E::IfElse(eb, et, ef) => {
             exp(context, annotated_acquires, seen, eb);
@@ -151,6 +152,7 @@ fn exp(
         E::ExpList(el) => exp_list(context, annotated_acquires, seen, el),
 
         E::Cast(e, _) | E::Annotate(e, _) => exp(context, annotated_acquires, seen, e),
+        E::Lambda(_, body) => exp(context, annotated_acquires, seen, body),
     }
 }
 
@@ -268,7 +270,7 @@ where
         T::Anything | T::UnresolvedError => {
             return None;
         }
-        T::Ref(_, _) | T::Unit => {
+        T::Ref(_, _) | T::Fun(_, _) | T::Unit => {
             // Key ability is checked by constraints, and these types do not have Key
             assert!(context.env.has_errors());
             return None;
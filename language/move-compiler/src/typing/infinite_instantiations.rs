@@ -94,6 +94,15 @@ impl<'a> Context<'a> {
                 tys.iter()
                     .for_each(|t| Self::add_tparam_edges(acc, tparam, info.clone(), t))
             }
+            Fun(args, result) => {
+                let info = EdgeInfo {
+                    edge: Edge::Nested,
+                    ..info
+                };
+                args.iter()
+                    .chain(std::iter::once(&**result))
+                    .for_each(|t| Self::add_tparam_edges(acc, tparam, info.clone(), t))
+            }
             Param(tp) => {
                 let tp_neighbors = acc.entry(tp.clone()).or_insert_with(BTreeMap::new);
                 match tp_neighbors.get(tparam) {
@@ -244,6 +253,7 @@ fn exp(context: &mut Context, e: &T::Exp) {
 
         E::Builtin(_, er)
         | E::Vector(_, _, _, er)
+        | E::VarCall(_, er)
         | E::Return(er)
         | E::Abort(er)
         | E::Dereference(er)
@@ -262,7 +272,7 @@ fn exp(context: &mut Context, e: &T::Exp) {
         }
         E::ExpList(el) => exp_list(context, el),
 
-        E::Cast(e, _) | E::Annotate(e, _) => exp(context, e),
+        E::Cast(e, _) | E::Annotate(e, _) | E::Lambda(_, e) => exp(context, e),
     }
 }
 
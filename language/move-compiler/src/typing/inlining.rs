@@ -0,0 +1,752 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Expands calls to 'inline' functions at their call sites.
+//! The body of the 'inline' function is instantiated with the call's type arguments, its locals
+//! are renamed to avoid clashes with the caller's locals, and calls of its function typed
+//! parameters are replaced with the bodies of the lambdas given at the call site.
+//! After this pass, the program no longer contains calls to 'inline' functions, lambdas or
+//! function types (outside of the 'inline' functions themselves, which are not compiled).
+
+use super::core::{self, TParamSubst};
+use crate::{
+    diag,
+    expansion::ast::{ModuleIdent, Visibility},
+    naming::ast::{Type, TypeName_, Type_},
+    parser::ast::{ConstantName, FunctionName, StructName, Var},
+    shared::{unique_map::UniqueMap, *},
+    typing::ast as T,
+    FullyCompiledProgram,
+};
+use move_ir_types::location::*;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+const INLINE_NAME_DELIM: &str = "#inline";
+
+//**************************************************************************************************
+// Context
+//**************************************************************************************************
+
+struct Context<'env> {
+    env: &'env mut CompilationEnv,
+    // The module currently being processed. `None` for scripts
+    current_module: Option<ModuleIdent>,
+    // All 'inline' functions, including those from the pre compiled library
+    inline_functions: BTreeMap<(ModuleIdent, FunctionName), T::Function>,
+    function_visibilities: BTreeMap<(ModuleIdent, FunctionName), Visibility>,
+    friends: BTreeMap<ModuleIdent, BTreeSet<ModuleIdent>>,
+    constants: BTreeMap<(ModuleIdent, ConstantName), T::Exp>,
+    // The 'inline' functions currently being expanded, used to detect cycles
+    inline_stack: Vec<(ModuleIdent, FunctionName)>,
+    // The 'inline' functions whose visibility has been checked for a given calling module
+    checked_visibility: BTreeSet<(ModuleIdent, FunctionName, Option<ModuleIdent>)>,
+    counter: usize,
+}
+
+impl<'env> Context<'env> {
+    fn new(
+        env: &'env mut CompilationEnv,
+        pre_compiled_lib: Option<&FullyCompiledProgram>,
+        prog: &T::Program,
+    ) -> Self {
+        let all_modules = prog
+            .modules
+            .key_cloned_iter()
+            .chain(pre_compiled_lib.iter().flat_map(|pre_compiled| {
+                pre_compiled
+                    .typing
+                    .modules
+                    .key_cloned_iter()
+                    .filter(|(mident, _m)| !prog.modules.contains_key(mident))
+            }));
+        let mut inline_functions = BTreeMap::new();
+        let mut function_visibilities = BTreeMap::new();
+        let mut friends = BTreeMap::new();
+        let mut constants = BTreeMap::new();
+        for (mident, mdef) in all_modules {
+            friends.insert(
+                mident,
+                mdef.friends.key_cloned_iter().map(|(f, _)| f).collect(),
+            );
+            for (fname, fdef) in mdef.functions.key_cloned_iter() {
+                function_visibilities.insert((mident, fname), fdef.visibility.clone());
+                if fdef.inline {
+                    inline_functions.insert((mident, fname), fdef.clone());
+                }
+            }
+            for (cname, cdef) in mdef.constants.key_cloned_iter() {
+                constants.insert((mident, cname), cdef.value.clone());
+            }
+        }
+        Self {
+            env,
+            current_module: None,
+            inline_functions,
+            function_visibilities,
+            friends,
+            constants,
+            inline_stack: vec![],
+            checked_visibility: BTreeSet::new(),
+            counter: 0,
+        }
+    }
+
+    fn is_inline_function(&self, m: &ModuleIdent, f: &FunctionName) -> bool {
+        self.inline_functions.contains_key(&(*m, *f))
+    }
+
+    fn counter_next(&mut self) -> usize {
+        self.counter += 1;
+        self.counter
+    }
+}
+
+//**************************************************************************************************
+// Entry
+//**************************************************************************************************
+
+pub fn program(
+    compilation_env: &mut CompilationEnv,
+    pre_compiled_lib: Option<&FullyCompiledProgram>,
+    prog: T::Program,
+) -> T::Program {
+    let mut context = Context::new(compilation_env, pre_compiled_lib, &prog);
+    let T::Program { modules, scripts } = prog;
+    let modules = modules.map(|mident, mdef| module(&mut context, mident, mdef));
+    let scripts = scripts
+        .into_iter()
+        .map(|(n, s)| (n, script(&mut context, s)))
+        .collect();
+    T::Program { modules, scripts }
+}
+
+fn module(
+    context: &mut Context,
+    mident: ModuleIdent,
+    mut mdef: T::ModuleDefinition,
+) -> T::ModuleDefinition {
+    context.current_module = Some(mident);
+    for (_, _, fdef) in mdef.functions.iter_mut() {
+        if fdef.inline {
+            // 'inline' functions are not compiled, but expand a copy of their body so that errors,
+            // such as cyclic inlining, are reported even if the function is never called
+            let mut body = fdef.body.clone();
+            function_body(context, &mut body);
+        } else {
+            function_body(context, &mut fdef.body);
+        }
+    }
+    context.current_module = None;
+    mdef
+}
+
+fn script(context: &mut Context, mut sdef: T::Script) -> T::Script {
+    context.current_module = None;
+    function_body(context, &mut sdef.function.body);
+    sdef
+}
+
+fn function_body(context: &mut Context, sp!(_, b_): &mut T::FunctionBody) {
+    match b_ {
+        T::FunctionBody_::Native => (),
+        T::FunctionBody_::Defined(seq) => visit_seq(&mut Expand { context }, seq),
+    }
+}
+
+//**************************************************************************************************
+// Expansion
+//**************************************************************************************************
+
+struct Expand<'a, 'env> {
+    context: &'a mut Context<'env>,
+}
+
+impl Visitor for Expand<'_, '_> {
+    fn exp(&mut self, e: &mut T::Exp) -> bool {
+        use T::UnannotatedExp_ as E;
+        match &e.exp.value {
+            E::ModuleCall(call) if self.context.is_inline_function(&call.module, &call.name) => (),
+            _ => return true,
+        }
+        let loc = e.exp.loc;
+        let ty = e.ty.clone();
+        let call = match std::mem::replace(&mut e.exp.value, E::UnresolvedError) {
+            E::ModuleCall(call) => call,
+            _ => unreachable!(),
+        };
+        *e = inline_call(self.context, loc, ty, *call);
+        false
+    }
+}
+
+fn inline_call(context: &mut Context, loc: Loc, ty: Type, call: T::ModuleCall) -> T::Exp {
+    let T::ModuleCall {
+        module,
+        name,
+        type_arguments,
+        mut arguments,
+        parameter_types: _,
+        acquires: _,
+    } = call;
+    // The arguments, including the bodies of lambdas, belong to the caller
+    visit_exp(&mut Expand { context }, &mut arguments);
+
+    let key = (module, name);
+    if context.inline_stack.contains(&key) {
+        let (def_name, _) = context.inline_functions.get_key_value(&key).unwrap();
+        let msg = format!(
+            "Invalid call of '{}::{}'. 'inline' functions cannot be called recursively",
+            module, name
+        );
+        let def_msg = format!("'{}' is declared 'inline' here", name);
+        context.env.add_diag(diag!(
+            Inlining::Recursion,
+            (loc, msg),
+            (def_name.1.loc(), def_msg)
+        ));
+        return error_exp(loc, ty);
+    }
+    let T::Function {
+        signature, body, ..
+    } = context.inline_functions.get(&key).unwrap().clone();
+    let mut seq = match body.value {
+        T::FunctionBody_::Defined(seq) => seq,
+        T::FunctionBody_::Native => panic!("ICE 'inline' functions cannot be native"),
+    };
+    check_visibility(context, loc, &key, &mut seq);
+
+    // Instantiate the body for this call site
+    let suffix = context.counter_next();
+    let subst = core::make_tparam_subst(&signature.type_parameters, type_arguments);
+    let mut instantiate = Instantiate {
+        subst: &subst,
+        suffix,
+        current_module: context.current_module,
+        constants: &context.constants,
+    };
+    visit_seq(&mut instantiate, &mut seq);
+    let parameters = signature
+        .parameters
+        .into_iter()
+        .map(|(mut v, t)| {
+            instantiate.var(&mut v);
+            (v, core::subst_tparams(&subst, t))
+        })
+        .collect::<Vec<_>>();
+
+    let args = match call_arguments(parameters.len(), *arguments) {
+        Some(args) => args,
+        None => {
+            assert!(context.env.has_errors());
+            return error_exp(loc, ty);
+        }
+    };
+    let mut fun_args = BTreeMap::new();
+    let mut binds = vec![];
+    let mut bind_tys = vec![];
+    let mut bind_args = vec![];
+    for ((param, param_ty), arg) in parameters.into_iter().zip(args) {
+        match &param_ty.value {
+            Type_::Fun(_, _) => {
+                fun_args.insert(param, arg);
+            }
+            _ => {
+                let param_loc = param.loc();
+                let lvalue = T::LValue_::Var(param, Box::new(param_ty.clone()));
+                binds.push(sp(param_loc, lvalue));
+                bind_tys.push(param_ty);
+                bind_args.push(arg);
+            }
+        }
+    }
+
+    // Expand any 'inline' calls in the body, then substitute the lambda arguments
+    context.inline_stack.push(key);
+    visit_seq(&mut Expand { context }, &mut seq);
+    context.inline_stack.pop();
+    visit_seq(
+        &mut SubstituteLambdas {
+            fun_args: &fun_args,
+        },
+        &mut seq,
+    );
+
+    annotate_last(&mut seq, &ty);
+    if !binds.is_empty() {
+        let rhs = match bind_args.len() {
+            1 => bind_args.pop().unwrap(),
+            _ => {
+                let items = bind_args
+                    .into_iter()
+                    .zip(bind_tys.iter())
+                    .map(|(e, t)| T::ExpListItem::Single(e, Box::new(t.clone())))
+                    .collect();
+                let rhs_ty = Type_::multiple(loc, bind_tys.clone());
+                T::exp(rhs_ty, sp(loc, T::UnannotatedExp_::ExpList(items)))
+            }
+        };
+        let bind_tys = bind_tys.into_iter().map(Some).collect();
+        let bind = T::SequenceItem_::Bind(sp(loc, binds), bind_tys, Box::new(rhs));
+        seq.push_front(sp(loc, bind));
+    }
+    T::exp(ty, sp(loc, T::UnannotatedExp_::Block(seq)))
+}
+
+// Splits the arguments of a call into one expression per parameter
+fn call_arguments(arity: usize, arguments: T::Exp) -> Option<Vec<T::Exp>> {
+    use T::UnannotatedExp_ as E;
+    match (arity, arguments.exp.value) {
+        (0, _) => Some(vec![]),
+        (1, E::ExpList(_)) | (1, E::UnresolvedError) => None,
+        (1, e_) => Some(vec![T::exp(arguments.ty, sp(arguments.exp.loc, e_))]),
+        (n, E::ExpList(items)) if items.len() == n => items
+            .into_iter()
+            .map(|item| match item {
+                T::ExpListItem::Single(e, _) => Some(e),
+                T::ExpListItem::Splat(_, _, _) => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+// Annotates the result of the sequence with the type of the inlined call
+fn annotate_last(seq: &mut T::Sequence, ty: &Type) {
+    if let Some(sp!(_, T::SequenceItem_::Seq(e))) = seq.back_mut() {
+        let e_ = std::mem::replace(e.as_mut(), error_exp(e.exp.loc, ty.clone()));
+        **e = annotate(e_, ty);
+    }
+}
+
+fn annotate(e: T::Exp, ty: &Type) -> T::Exp {
+    let loc = e.exp.loc;
+    let e_ = T::UnannotatedExp_::Annotate(Box::new(e), Box::new(ty.clone()));
+    T::exp(ty.clone(), sp(loc, e_))
+}
+
+fn error_exp(loc: Loc, ty: Type) -> T::Exp {
+    T::exp(ty, sp(loc, T::UnannotatedExp_::UnresolvedError))
+}
+
+//**************************************************************************************************
+// Instantiation
+//**************************************************************************************************
+
+struct Instantiate<'a> {
+    subst: &'a TParamSubst,
+    suffix: usize,
+    current_module: Option<ModuleIdent>,
+    constants: &'a BTreeMap<(ModuleIdent, ConstantName), T::Exp>,
+}
+
+impl Visitor for Instantiate<'_> {
+    fn exp(&mut self, e: &mut T::Exp) -> bool {
+        use T::UnannotatedExp_ as E;
+        match &e.exp.value {
+            // Specifications are not checked at the call site
+            E::Spec(_, _) => {
+                e.exp.value = E::Unit { trailing: false };
+                false
+            }
+            // Constants cannot be accessed outside of their module, so use their value instead
+            E::Constant(Some(m), c) if Some(*m) != self.current_module => {
+                *e = self.constants.get(&(*m, *c)).unwrap().clone();
+                false
+            }
+            _ => true,
+        }
+    }
+
+    fn type_(&mut self, ty: &mut Type) {
+        *ty = core::subst_tparams(self.subst, ty.clone())
+    }
+
+    fn var(&mut self, v: &mut Var) {
+        let name = format!("{}{}{}", v.value(), INLINE_NAME_DELIM, self.suffix);
+        *v = Var(sp(v.loc(), name.into()))
+    }
+}
+
+//**************************************************************************************************
+// Lambdas
+//**************************************************************************************************
+
+struct SubstituteLambdas<'a> {
+    fun_args: &'a BTreeMap<Var, T::Exp>,
+}
+
+impl Visitor for SubstituteLambdas<'_> {
+    fn exp(&mut self, e: &mut T::Exp) -> bool {
+        use T::UnannotatedExp_ as E;
+        let fun_arg = match &e.exp.value {
+            E::VarCall(var, _) | E::Use(var) | E::Move { var, .. } | E::Copy { var, .. } => {
+                match self.fun_args.get(var) {
+                    Some(fun_arg) => fun_arg.clone(),
+                    None => return true,
+                }
+            }
+            _ => return true,
+        };
+        let loc = e.exp.loc;
+        let ty = e.ty.clone();
+        *e = match std::mem::replace(&mut e.exp.value, E::UnresolvedError) {
+            E::VarCall(_, mut args) => {
+                visit_exp(self, &mut args);
+                apply(loc, ty, fun_arg, args)
+            }
+            // A function typed parameter passed on to another 'inline' function, which failed to
+            // expand
+            _ => fun_arg,
+        };
+        false
+    }
+}
+
+// The result of calling the function argument `fun_arg` with `args`
+fn apply(loc: Loc, ty: Type, fun_arg: T::Exp, args: Box<T::Exp>) -> T::Exp {
+    use T::UnannotatedExp_ as E;
+    let arg_tys = match fun_arg.ty.value {
+        Type_::Fun(arg_tys, _) => arg_tys,
+        _ => return error_exp(loc, ty),
+    };
+    match fun_arg.exp.value {
+        E::Lambda(binds, body) => {
+            let mut seq = VecDeque::new();
+            if !binds.value.is_empty() {
+                let bind_tys = arg_tys.into_iter().map(Some).collect();
+                seq.push_back(sp(loc, T::SequenceItem_::Bind(binds, bind_tys, args)));
+            }
+            let body = annotate(*body, &ty);
+            seq.push_back(sp(loc, T::SequenceItem_::Seq(Box::new(body))));
+            T::exp(ty, sp(loc, E::Block(seq)))
+        }
+        // The function typed parameter of an enclosing 'inline' function
+        E::Use(var) | E::Move { var, .. } | E::Copy { var, .. } => {
+            T::exp(ty, sp(loc, E::VarCall(var, args)))
+        }
+        _ => error_exp(loc, ty),
+    }
+}
+
+//**************************************************************************************************
+// Visibility
+//**************************************************************************************************
+
+// Checks that the body of the 'inline' function `key` is valid once inlined into the current
+// module (or script)
+fn check_visibility(
+    context: &mut Context,
+    call_loc: Loc,
+    key: &(ModuleIdent, FunctionName),
+    seq: &mut T::Sequence,
+) {
+    let (m, f) = key;
+    if context.current_module == Some(*m) {
+        return;
+    }
+    if !context
+        .checked_visibility
+        .insert((*m, *f, context.current_module))
+    {
+        return;
+    }
+    let mut check = CheckVisibility {
+        context,
+        call_loc,
+        inlined: *key,
+    };
+    visit_seq(&mut check, seq)
+}
+
+struct CheckVisibility<'a, 'env> {
+    context: &'a mut Context<'env>,
+    call_loc: Loc,
+    inlined: (ModuleIdent, FunctionName),
+}
+
+impl CheckVisibility<'_, '_> {
+    fn caller_desc(&self) -> String {
+        match &self.context.current_module {
+            Some(m) => format!("module '{}'", m),
+            None => "a script".to_owned(),
+        }
+    }
+
+    fn report(&mut self, loc: Loc, msg: String) {
+        let (m, f) = &self.inlined;
+        let call_msg = format!("'{}::{}' is inlined here", m, f);
+        self.context.env.add_diag(diag!(
+            Inlining::Visibility,
+            (loc, msg),
+            (self.call_loc, call_msg)
+        ));
+    }
+
+    fn function(&mut self, loc: Loc, m: &ModuleIdent, f: &FunctionName) {
+        if self.context.current_module == Some(*m) || self.context.is_inline_function(m, f) {
+            return;
+        }
+        let visible = match self.context.function_visibilities.get(&(*m, *f)) {
+            None | Some(Visibility::Public(_)) => true,
+            Some(Visibility::Friend(_)) => match &self.context.current_module {
+                Some(current) => self
+                    .context
+                    .friends
+                    .get(m)
+                    .map(|friends| friends.contains(current))
+                    .unwrap_or(false),
+                None => false,
+            },
+            Some(Visibility::Internal) => false,
+        };
+        if !visible {
+            let msg = format!(
+                "Invalid call to '{}::{}'. The call is inlined into {}, where the function is \
+                 not visible",
+                m,
+                f,
+                self.caller_desc()
+            );
+            self.report(loc, msg)
+        }
+    }
+
+    fn struct_(&mut self, loc: Loc, m: &ModuleIdent, s: &StructName, case: &str) {
+        if self.context.current_module == Some(*m) {
+            return;
+        }
+        let msg = format!(
+            "Invalid usage of '{}::{}'. The code is inlined into {}, but the struct can only be \
+             {} inside the module in which it is declared",
+            m,
+            s,
+            self.caller_desc(),
+            case
+        );
+        self.report(loc, msg)
+    }
+
+    fn struct_type(&mut self, loc: Loc, ty: &Type, case: &str) {
+        match &ty.value {
+            Type_::Ref(_, inner) => self.struct_type(loc, inner, case),
+            Type_::Apply(_, sp!(_, TypeName_::ModuleType(m, s)), _) => {
+                self.struct_(loc, m, s, case)
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Visitor for CheckVisibility<'_, '_> {
+    fn exp(&mut self, e: &mut T::Exp) -> bool {
+        use T::{BuiltinFunction_ as B, UnannotatedExp_ as E};
+        let loc = e.exp.loc;
+        match &e.exp.value {
+            E::ModuleCall(call) => self.function(loc, &call.module, &call.name),
+            E::Pack(m, s, _, _) => self.struct_(loc, m, s, "constructed"),
+            E::Borrow(_, eb, _) => self.struct_type(loc, &eb.ty, "accessed"),
+            E::Builtin(b, _) => match &b.value {
+                B::MoveTo(ty) | B::MoveFrom(ty) | B::BorrowGlobal(_, ty) | B::Exists(ty) => {
+                    self.struct_type(loc, ty, "used in global storage operations")
+                }
+                B::Freeze(_) | B::Assert(_) => (),
+            },
+            _ => (),
+        }
+        true
+    }
+
+    fn lvalue(&mut self, l: &mut T::LValue) {
+        match &l.value {
+            T::LValue_::Unpack(m, s, _, _) | T::LValue_::BorrowUnpack(_, m, s, _, _) => {
+                self.struct_(l.loc, m, s, "destructured")
+            }
+            T::LValue_::Ignore | T::LValue_::Var(_, _) => (),
+        }
+    }
+}
+
+//**************************************************************************************************
+// Traversal
+//**************************************************************************************************
+
+trait Visitor {
+    // Called before visiting the sub-expressions of `e`. Returns false if they should be skipped
+    fn exp(&mut self, _e: &mut T::Exp) -> bool {
+        true
+    }
+
+    fn lvalue(&mut self, _l: &mut T::LValue) {}
+
+    fn type_(&mut self, _ty: &mut Type) {}
+
+    fn var(&mut self, _v: &mut Var) {}
+}
+
+fn visit_seq<V: Visitor>(v: &mut V, seq: &mut T::Sequence) {
+    for item in seq {
+        visit_seq_item(v, item)
+    }
+}
+
+fn visit_seq_item<V: Visitor>(v: &mut V, sp!(_, item_): &mut T::SequenceItem) {
+    use T::SequenceItem_ as S;
+    match item_ {
+        S::Seq(e) => visit_exp(v, e),
+        S::Declare(binds) => visit_lvalues(v, binds),
+        S::Bind(binds, tys, e) => {
+            visit_lvalues(v, binds);
+            visit_opt_types(v, tys);
+            visit_exp(v, e)
+        }
+    }
+}
+
+fn visit_lvalues<V: Visitor>(v: &mut V, binds: &mut T::LValueList) {
+    for l in &mut binds.value {
+        visit_lvalue(v, l)
+    }
+}
+
+fn visit_lvalue<V: Visitor>(v: &mut V, l: &mut T::LValue) {
+    use T::LValue_ as L;
+    v.lvalue(l);
+    match &mut l.value {
+        L::Ignore => (),
+        L::Var(var, ty) => {
+            v.var(var);
+            v.type_(ty)
+        }
+        L::Unpack(_, _, tys, fields) | L::BorrowUnpack(_, _, _, tys, fields) => {
+            visit_types(v, tys);
+            for (_, _, (_, (ty, fl))) in fields.iter_mut() {
+                v.type_(ty);
+                visit_lvalue(v, fl)
+            }
+        }
+    }
+}
+
+fn visit_types<V: Visitor>(v: &mut V, tys: &mut [Type]) {
+    for ty in tys {
+        v.type_(ty)
+    }
+}
+
+fn visit_opt_types<V: Visitor>(v: &mut V, tys: &mut [Option<Type>]) {
+    for ty in tys.iter_mut().flatten() {
+        v.type_(ty)
+    }
+}
+
+fn visit_exp<V: Visitor>(v: &mut V, e: &mut T::Exp) {
+    use T::{BuiltinFunction_ as B, UnannotatedExp_ as E};
+    if !v.exp(e) {
+        return;
+    }
+    v.type_(&mut e.ty);
+    match &mut e.exp.value {
+        E::Unit { .. }
+        | E::Value(_)
+        | E::Constant(_, _)
+        | E::Break
+        | E::Continue
+        | E::UnresolvedError => (),
+        E::Move { var, .. } | E::Copy { var, .. } | E::Use(var) | E::BorrowLocal(_, var) => {
+            v.var(var)
+        }
+
+        E::ModuleCall(call) => {
+            visit_types(v, &mut call.type_arguments);
+            visit_types(v, &mut call.parameter_types);
+            visit_exp(v, &mut call.arguments)
+        }
+        E::Builtin(b, args) => {
+            match &mut b.value {
+                B::MoveTo(ty)
+                | B::MoveFrom(ty)
+                | B::BorrowGlobal(_, ty)
+                | B::Exists(ty)
+                | B::Freeze(ty) => v.type_(ty),
+                B::Assert(_) => (),
+            }
+            visit_exp(v, args)
+        }
+        E::Vector(_, _, ty, args) => {
+            v.type_(ty);
+            visit_exp(v, args)
+        }
+        E::VarCall(var, args) => {
+            v.var(var);
+            visit_exp(v, args)
+        }
+
+        E::IfElse(eb, et, ef) => {
+            visit_exp(v, eb);
+            visit_exp(v, et);
+            visit_exp(v, ef)
+        }
+        E::While(eb, body) => {
+            visit_exp(v, eb);
+            visit_exp(v, body)
+        }
+        E::Loop { body, .. } => visit_exp(v, body),
+        E::Block(seq) => visit_seq(v, seq),
+        E::Assign(binds, tys, er) => {
+            visit_lvalues(v, binds);
+            visit_opt_types(v, tys);
+            visit_exp(v, er)
+        }
+        E::Mutate(el, er) => {
+            visit_exp(v, el);
+            visit_exp(v, er)
+        }
+        E::Return(er)
+        | E::Abort(er)
+        | E::Dereference(er)
+        | E::UnaryExp(_, er)
+        | E::Borrow(_, er, _)
+        | E::TempBorrow(_, er) => visit_exp(v, er),
+        E::BinopExp(el, _, ty, er) => {
+            visit_exp(v, el);
+            v.type_(ty);
+            visit_exp(v, er)
+        }
+
+        E::Pack(_, _, tys, fields) => {
+            visit_types(v, tys);
+            for (_, _, (_, (ty, fe))) in fields.iter_mut() {
+                v.type_(ty);
+                visit_exp(v, fe)
+            }
+        }
+        E::ExpList(items) => {
+            for item in items {
+                match item {
+                    T::ExpListItem::Single(e, ty) => {
+                        visit_exp(v, e);
+                        v.type_(ty)
+                    }
+                    T::ExpListItem::Splat(_, e, tys) => {
+                        visit_exp(v, e);
+                        visit_types(v, tys)
+                    }
+                }
+            }
+        }
+
+        E::Cast(e, ty) | E::Annotate(e, ty) => {
+            visit_exp(v, e);
+            v.type_(ty)
+        }
+        E::Lambda(binds, body) => {
+            visit_lvalues(v, binds);
+            visit_exp(v, body)
+        }
+        E::Spec(_, used_locals) => {
+            for ty in used_locals.values_mut() {
+                v.type_(ty)
+            }
+        }
+    }
+}
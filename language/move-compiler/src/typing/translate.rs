@@ -135,6 +135,7 @@ fn function(
         attributes,
         visibility,
         entry,
+        inline,
         mut signature,
         body: n_body,
         acquires,
@@ -142,6 +143,7 @@ fn function(
     assert!(context.constraints.is_empty());
     context.reset_for_module_item();
     context.current_function = Some(name);
+    check_function_types(context, inline, &signature);
     function_signature(context, &signature);
     if is_script {
         let mk_msg = || {
@@ -168,12 +170,73 @@ fn function(
         attributes,
         visibility,
         entry,
+        inline,
         signature,
         acquires,
         body,
     }
 }
 
+// Function types are only permitted as the parameter types of 'inline' functions, and they
+// cannot themselves take or return functions
+fn check_function_types(context: &mut Context, inline: bool, sig: &N::FunctionSignature) {
+    for (param, param_ty) in &sig.parameters {
+        let invalid_loc = match &param_ty.value {
+            Type_::Fun(args, result) if inline => args
+                .iter()
+                .chain(std::iter::once(&**result))
+                .find_map(function_type_loc),
+            _ => function_type_loc(param_ty),
+        };
+        if let Some(invalid_loc) = invalid_loc {
+            let msg = if inline {
+                format!(
+                    "Invalid type for parameter '{}'. Function types cannot take or return \
+                     other functions",
+                    param
+                )
+            } else {
+                format!(
+                    "Invalid type for parameter '{}'. Function types are only supported for \
+                     parameters of 'inline' functions",
+                    param
+                )
+            };
+            context
+                .env
+                .add_diag(diag!(TypeSafety::InvalidFunctionType, (invalid_loc, msg)))
+        }
+    }
+    if let Some(invalid_loc) = function_type_loc(&sig.return_type) {
+        let msg = "Invalid return type. Functions cannot return function types";
+        context
+            .env
+            .add_diag(diag!(TypeSafety::InvalidFunctionType, (invalid_loc, msg)))
+    }
+}
+
+fn check_no_function_type(context: &mut Context, case: &str, ty: &Type) {
+    if let Some(invalid_loc) = function_type_loc(ty) {
+        let msg = format!(
+            "Invalid {}. Function types are only supported for parameters of 'inline' functions",
+            case
+        );
+        context
+            .env
+            .add_diag(diag!(TypeSafety::InvalidFunctionType, (invalid_loc, msg)))
+    }
+}
+
+fn function_type_loc(sp!(loc, ty_): &Type) -> Option<Loc> {
+    match ty_ {
+        Type_::Fun(_, _) => Some(*loc),
+        Type_::Ref(_, inner) => function_type_loc(inner),
+        Type_::Apply(_, _, ty_args) => ty_args.iter().find_map(function_type_loc),
+        Type_::Unit | Type_::Param(_) | Type_::Var(_) | Type_::Anything => None,
+        Type_::UnresolvedError => None,
+    }
+}
+
 fn function_signature(context: &mut Context, sig: &N::FunctionSignature) {
     assert!(context.constraints.is_empty());
 
@@ -360,6 +423,8 @@ mod check_valid_constant {
             // Error cases handled elsewhere
             //*****************************************
             E::Use(_) | E::Continue | E::Break | E::UnresolvedError => return,
+            // Lambdas and their calls are rejected when typing
+            E::Lambda(_, _) | E::VarCall(_, _) => return,
 
             //*****************************************
             // Valid cases
@@ -642,6 +707,13 @@ fn visit_type_params(
         Type_::Ref(_, ty) => {
             visit_type_params(context, ty, ParamPos::NonPhantom(NonPhantomPos::TypeArg), f)
         }
+        // Function types cannot appear in structs, but we still report them as a non-phantom
+        // position for full information.
+        Type_::Fun(args, result) => {
+            for ty in args.iter().chain(std::iter::once(&**result)) {
+                visit_type_params(context, ty, ParamPos::NonPhantom(NonPhantomPos::TypeArg), f)
+            }
+        }
         Type_::Apply(_, n, ty_args) => match &n.value {
             // Tuples cannot appear in structs, but we still report them as a non-phantom position
             // for full information.
@@ -732,6 +804,9 @@ fn has_unresolved_error_type(ty: &Type) -> bool {
     match &ty.value {
         Type_::UnresolvedError => true,
         Type_::Ref(_, ty) => has_unresolved_error_type(ty),
+        Type_::Fun(args, result) => {
+            args.iter().any(has_unresolved_error_type) || has_unresolved_error_type(result)
+        }
         Type_::Apply(_, _, ty_args) => ty_args.iter().any(has_unresolved_error_type),
         Type_::Param(_) | Type_::Var(_) | Type_::Anything | Type_::Unit => false,
     }
@@ -973,6 +1048,9 @@ fn sequence(context: &mut Context, seq: N::Sequence) -> T::Sequence {
                 work_queue.push_front(SeqCase::Seq(loc, Box::new(e)));
             }
             NS::Declare(nbind, ty_opt) => {
+                if let Some(ty) = &ty_opt {
+                    check_no_function_type(context, "type annotation", ty);
+                }
                 let old_locals = context.save_locals_scope();
                 let instantiated_ty_op = ty_opt.map(|t| core::instantiate(context, t));
                 let (declared, b) = bind_list(context, nbind, instantiated_ty_op);
@@ -1211,11 +1289,13 @@ fn exp_inner(context: &mut Context, sp!(eloc, ne_): N::Exp) -> T::Exp {
 
         NE::Move(var) => {
             let ty = context.get_local(eloc, "move", &var);
+            check_function_local_usage(context, eloc, &var, &ty);
             let from_user = true;
             (ty, TE::Move { var, from_user })
         }
         NE::Copy(var) => {
             let ty = context.get_local(eloc, "copy", &var);
+            check_function_local_usage(context, eloc, &var, &ty);
             context.add_ability_constraint(
                 eloc,
                 Some(format!(
@@ -1230,13 +1310,20 @@ fn exp_inner(context: &mut Context, sp!(eloc, ne_): N::Exp) -> T::Exp {
         }
         NE::Use(var) => {
             let ty = context.get_local(eloc, "variable usage", &var);
+            check_function_local_usage(context, eloc, &var, &ty);
             (ty, TE::Use(var))
         }
 
+        NE::ModuleCall(m, f, ty_args_opt, sp!(argloc, nargs_))
+            if context.is_inline_function(&m, &f) =>
+        {
+            inline_module_call(context, eloc, m, f, ty_args_opt, argloc, nargs_)
+        }
         NE::ModuleCall(m, f, ty_args_opt, sp!(argloc, nargs_)) => {
             let args = exp_vec(context, nargs_);
             module_call(context, eloc, m, f, ty_args_opt, argloc, args)
         }
+        NE::VarCall(var, sp!(argloc, nargs_)) => var_call(context, eloc, var, argloc, nargs_),
         NE::Builtin(b, sp!(argloc, nargs_)) => {
             let args = exp_vec(context, nargs_);
             builtin_call(context, eloc, b, argloc, args)
@@ -1314,6 +1401,18 @@ fn exp_inner(context: &mut Context, sp!(eloc, ne_): N::Exp) -> T::Exp {
         }
 
         NE::Return(nret) => {
+            if context.in_lambda() {
+                let msg = "Invalid usage of 'return'. 'return' cannot be used inside a lambda";
+                context
+                    .env
+                    .add_diag(diag!(TypeSafety::InvalidLambda, (eloc, msg)))
+            } else if context.current_function_is_inline() {
+                let msg = "Invalid usage of 'return'. 'return' cannot be used inside an 'inline' \
+                           function";
+                context
+                    .env
+                    .add_diag(diag!(Inlining::InvalidReturn, (eloc, msg)))
+            }
             let eret = exp(context, nret);
             let ret_ty = context.return_type.clone().unwrap();
             subtype(context, eloc, || "Invalid return", eret.ty.clone(), ret_ty);
@@ -1478,6 +1577,7 @@ fn exp_inner(context: &mut Context, sp!(eloc, ne_): N::Exp) -> T::Exp {
 
         NE::Annotate(nl, ty_annot) => {
             let el = exp(context, nl);
+            check_no_function_type(context, "type annotation", &ty_annot);
             let annot_loc = ty_annot.loc;
             let rhs = core::instantiate(context, ty_annot);
             subtype(
@@ -1489,6 +1589,14 @@ fn exp_inner(context: &mut Context, sp!(eloc, ne_): N::Exp) -> T::Exp {
             );
             (rhs.clone(), TE::Annotate(el, Box::new(rhs)))
         }
+        NE::Lambda(_, _) => {
+            let msg = "Invalid usage of lambda. Lambdas can only be used as arguments to 'inline' \
+                       functions";
+            context
+                .env
+                .add_diag(diag!(TypeSafety::InvalidLambda, (eloc, msg)));
+            (context.error_type(eloc), TE::UnresolvedError)
+        }
         NE::Spec(u, used_locals) => {
             let used_local_types = used_locals
                 .into_iter()
@@ -1540,6 +1648,33 @@ fn loop_body(
 // Locals and LValues
 //**************************************************************************************************
 
+fn is_function_local(context: &mut Context, var: &Var) -> bool {
+    match context.get_local_(var) {
+        None => false,
+        Some(ty) => matches!(
+            core::unfold_type(&context.subst, ty).value,
+            Type_::Fun(_, _)
+        ),
+    }
+}
+
+// Parameters with a function type can only be called or passed along to other 'inline' functions
+fn check_function_local_usage(context: &mut Context, loc: Loc, var: &Var, ty: &Type) {
+    if matches!(
+        core::unfold_type(&context.subst, ty.clone()).value,
+        Type_::Fun(_, _)
+    ) {
+        let msg = format!(
+            "Invalid usage of '{}'. Parameters with a function type can only be called or passed \
+             as arguments to 'inline' functions",
+            var
+        );
+        context
+            .env
+            .add_diag(diag!(TypeSafety::InvalidFunctionType, (loc, msg)))
+    }
+}
+
 fn lvalues_expected_types(
     context: &mut Context,
     sp!(_loc, bs_): &T::LValueList,
@@ -2056,8 +2191,180 @@ fn module_call(
     argloc: Loc,
     args: Vec<T::Exp>,
 ) -> (Type, T::UnannotatedExp_) {
-    let (_, ty_args, parameters, acquires, ret_ty) =
-        core::make_function_type(context, loc, &m, &f, ty_args_opt);
+    let fty = core::make_function_type(context, loc, &m, &f, ty_args_opt);
+    module_call_impl(context, loc, m, f, fty, argloc, args)
+}
+
+// Calls to 'inline' functions are checked like any other call, but their arguments can be
+// lambdas or function typed parameters. The call is expanded after typing in `inlining`
+fn inline_module_call(
+    context: &mut Context,
+    loc: Loc,
+    m: ModuleIdent,
+    f: FunctionName,
+    ty_args_opt: Option<Vec<Type>>,
+    argloc: Loc,
+    nargs: Vec<N::Exp>,
+) -> (Type, T::UnannotatedExp_) {
+    use N::Exp_ as NE;
+    let fty = core::make_function_type(context, loc, &m, &f, ty_args_opt);
+    let parameters = fty.2.clone();
+    // The arguments that are not lambdas are checked first, so that the types of the lambda
+    // parameters can be inferred from them
+    let mut args = vec![];
+    let mut lambdas = vec![];
+    for (idx, narg) in nargs.into_iter().enumerate() {
+        match narg {
+            sp!(eloc, NE::Lambda(nbinds, nbody)) => {
+                lambdas.push((idx, eloc, nbinds, nbody));
+                args.push(None)
+            }
+            narg => {
+                let arg = inline_call_arg(context, narg);
+                if let Some((param, param_ty)) = parameters.get(idx) {
+                    let msg = || {
+                        format!(
+                            "Invalid call of '{}::{}'. Invalid argument for parameter '{}'",
+                            &m, &f, param
+                        )
+                    };
+                    subtype(context, loc, msg, arg.ty.clone(), param_ty.clone());
+                }
+                args.push(Some(arg))
+            }
+        }
+    }
+    for (idx, eloc, nbinds, nbody) in lambdas {
+        let param_ty_opt = parameters.get(idx).map(|(_, ty)| ty.clone());
+        args[idx] = Some(lambda(context, eloc, param_ty_opt, nbinds, nbody));
+    }
+    let args = args.into_iter().map(|arg| arg.unwrap()).collect();
+    module_call_impl(context, loc, m, f, fty, argloc, args)
+}
+
+fn inline_call_arg(context: &mut Context, narg: N::Exp) -> T::Exp {
+    use N::Exp_ as NE;
+    use T::UnannotatedExp_ as TE;
+    match narg {
+        sp!(eloc, NE::Use(var)) if is_function_local(context, &var) => {
+            let ty = context.get_local(eloc, "variable usage", &var);
+            T::exp(ty, sp(eloc, TE::Use(var)))
+        }
+        narg => exp_(context, narg),
+    }
+}
+
+fn lambda(
+    context: &mut Context,
+    loc: Loc,
+    param_ty_opt: Option<Type>,
+    nbinds: N::LValueList,
+    nbody: Box<N::Exp>,
+) -> T::Exp {
+    let arity = nbinds.value.len();
+    let expected_arg_tys =
+        param_ty_opt.and_then(|ty| match core::unfold_type(&context.subst, ty).value {
+            Type_::Fun(args, _) if args.len() == arity => Some(args),
+            _ => None,
+        });
+    // Mismatches with the expected function type are reported when checking the argument
+    let arg_tys = match expected_arg_tys {
+        Some(args) => args,
+        None => nbinds
+            .value
+            .iter()
+            .map(|sp!(bloc, _)| core::make_tvar(context, *bloc))
+            .collect::<Vec<_>>(),
+    };
+    let bloc = nbinds.loc;
+    let old_locals = context.save_locals_scope();
+    let (declared, binds) = bind_list(
+        context,
+        nbinds,
+        Some(Type_::multiple(bloc, arg_tys.clone())),
+    );
+    let old_lambda_info = context.enter_lambda();
+    let body = exp(context, nbody);
+    context.exit_lambda(old_lambda_info);
+    context.close_locals_scope(old_locals, declared);
+    let ty = sp(loc, Type_::Fun(arg_tys, Box::new(body.ty.clone())));
+    T::exp(ty, sp(loc, T::UnannotatedExp_::Lambda(binds, body)))
+}
+
+fn var_call(
+    context: &mut Context,
+    loc: Loc,
+    var: Var,
+    argloc: Loc,
+    nargs: Vec<N::Exp>,
+) -> (Type, T::UnannotatedExp_) {
+    let fun_ty = match context.get_local_(&var) {
+        None => {
+            context.env.add_diag(diag!(
+                NameResolution::UnboundUnscopedName,
+                (
+                    var.loc(),
+                    format!("Unbound function '{}' in current scope", var)
+                ),
+            ));
+            return (context.error_type(loc), T::UnannotatedExp_::UnresolvedError);
+        }
+        Some(ty) => core::unfold_type(&context.subst, ty),
+    };
+    let args = exp_vec(context, nargs);
+    let (param_tys, ret_ty) = match fun_ty.value {
+        Type_::Fun(param_tys, ret_ty) => (param_tys, *ret_ty),
+        Type_::UnresolvedError => {
+            assert!(context.env.has_errors());
+            return (context.error_type(loc), T::UnannotatedExp_::UnresolvedError);
+        }
+        ty_ => {
+            let msg = format!(
+                "Invalid call of '{}'. Only parameters with a function type can be called",
+                var
+            );
+            let tmsg = format!(
+                "Expected a function type, but found: {}",
+                core::error_format_(&ty_, &context.subst)
+            );
+            context.env.add_diag(diag!(
+                TypeSafety::InvalidFunctionType,
+                (loc, msg),
+                (fun_ty.loc, tmsg),
+            ));
+            return (context.error_type(loc), T::UnannotatedExp_::UnresolvedError);
+        }
+    };
+    let (arguments, arg_tys) = call_args(
+        context,
+        loc,
+        || format!("Invalid call of '{}'", var),
+        param_tys.len(),
+        argloc,
+        args,
+    );
+    assert!(arg_tys.len() == param_tys.len());
+    for (idx, (arg_ty, param_ty)) in arg_tys.into_iter().zip(param_tys).enumerate() {
+        let msg = || {
+            format!(
+                "Invalid call of '{}'. Invalid argument at position {}",
+                var, idx
+            )
+        };
+        subtype(context, loc, msg, arg_ty, param_ty);
+    }
+    (ret_ty, T::UnannotatedExp_::VarCall(var, arguments))
+}
+
+fn module_call_impl(
+    context: &mut Context,
+    loc: Loc,
+    m: ModuleIdent,
+    f: FunctionName,
+    (_, ty_args, parameters, acquires, ret_ty): core::FunctionType,
+    argloc: Loc,
+    args: Vec<T::Exp>,
+) -> (Type, T::UnannotatedExp_) {
     let (arguments, arg_tys) = call_args(
         context,
         loc,
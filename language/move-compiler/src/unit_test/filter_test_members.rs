@@ -199,6 +199,7 @@ fn create_test_poison(mloc: Loc) -> P::ModuleMember {
         loc: mloc,
         visibility: P::Visibility::Internal,
         entry: None,
+        inline: false,
         acquires: vec![],
         signature,
         name: P::FunctionName(sp(mloc, "unit_test_poison".into())),
@@ -218,19 +219,26 @@ fn create_test_poison(mloc: Loc) -> P::ModuleMember {
 }
 
 // A module member should be removed if:
-// * It is annotated as a test function (test_only, test, abort) and test mode is not set; or
-// * If it is a library and is annotated as #[test]
+// * It is annotated as a test function (test_only, test, random_test, abort) and test mode is not
+//   set; or
+// * If it is a library and is annotated as #[test] or #[random_test]
 fn should_remove_node(env: &CompilationEnv, attrs: &[P::Attributes], is_source_def: bool) -> bool {
     use known_attributes::TestingAttribute;
     let flattened_attrs: Vec<_> = attrs.iter().flat_map(test_attributes).collect();
-    let is_test_only = flattened_attrs
-        .iter()
-        .any(|attr| matches!(attr.1, TestingAttribute::Test | TestingAttribute::TestOnly));
+    let is_test_only = flattened_attrs.iter().any(|attr| {
+        matches!(
+            attr.1,
+            TestingAttribute::Test | TestingAttribute::RandomTest | TestingAttribute::TestOnly
+        )
+    });
     is_test_only && !env.flags().keep_testing_functions()
         || (!is_source_def
-            && flattened_attrs
-                .iter()
-                .any(|attr| attr.1 == TestingAttribute::Test))
+            && flattened_attrs.iter().any(|attr| {
+                matches!(
+                    attr.1,
+                    TestingAttribute::Test | TestingAttribute::RandomTest
+                )
+            }))
 }
 
 fn test_attributes(attrs: &P::Attributes) -> Vec<(Loc, known_attributes::TestingAttribute)> {
@@ -241,7 +249,9 @@ fn test_attributes(attrs: &P::Attributes) -> Vec<(Loc, known_attributes::Testing
         .filter_map(
             |attr| match KnownAttribute::resolve(attr.value.attribute_name().value)? {
                 KnownAttribute::Testing(test_attr) => Some((attr.loc, test_attr)),
-                KnownAttribute::Verification(_) | KnownAttribute::Native(_) => None,
+                KnownAttribute::Verification(_)
+                | KnownAttribute::Native(_)
+                | KnownAttribute::Lint(_) => None,
             },
         )
         .collect()
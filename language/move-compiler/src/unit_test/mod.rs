@@ -35,9 +35,27 @@ pub struct ModuleTestPlan {
 pub struct TestCase {
     pub test_name: TestName,
     pub arguments: Vec<MoveValue>,
+    // the parameters of a #[random_test], for which the test runner generates the arguments
+    pub random_parameters: Vec<(String, RandomTestType)>,
     pub expected_failure: Option<ExpectedFailure>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RandomTestType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    // 0x1::string::String
+    String,
+    Vector(Box<RandomTestType>),
+}
+
 #[derive(Debug, Clone)]
 pub enum ExpectedFailure {
     // expected failure, but codes are not checked
@@ -106,6 +124,12 @@ impl TestPlan {
     }
 }
 
+impl TestCase {
+    pub fn is_random_test(&self) -> bool {
+        !self.random_parameters.is_empty()
+    }
+}
+
 impl ExpectedMoveError {
     pub fn verbiage(&self, is_past_tense: bool) -> ExpectedMoveErrorDisplay {
         ExpectedMoveErrorDisplay {
@@ -8,13 +8,15 @@ use crate::{
     expansion::ast::{
         self as E, Address, Attribute, AttributeValue, ModuleAccess_, ModuleIdent, ModuleIdent_,
     },
+    hlir::ast::{self as H, BaseType_, SingleType_},
+    naming::ast::BuiltinTypeName_,
     parser::ast::ConstantName,
     shared::{
         known_attributes::{KnownAttribute, TestingAttribute},
         unique_map::UniqueMap,
         CompilationEnv, Identifier, NumericalAddress,
     },
-    unit_test::{ExpectedFailure, ExpectedMoveError, ModuleTestPlan, TestCase},
+    unit_test::{ExpectedFailure, ExpectedMoveError, ModuleTestPlan, RandomTestType, TestCase},
 };
 use move_core_types::{
     account_address::AccountAddress as MoveAddress, language_storage::ModuleId, u256::U256,
@@ -124,11 +126,12 @@ fn build_test_info<'func>(
     const IN_THIS_TEST_MSG: &str = "Error found in this test";
 
     let test_attribute_opt = get_attrs(TestingAttribute::Test);
+    let random_test_attribute_opt = get_attrs(TestingAttribute::RandomTest);
     let abort_attribute_opt = get_attrs(TestingAttribute::ExpectedFailure);
     let test_only_attribute_opt = get_attrs(TestingAttribute::TestOnly);
 
-    let test_attribute = match test_attribute_opt {
-        None => {
+    let test_attribute = match (test_attribute_opt, random_test_attribute_opt) {
+        (None, None) => {
             // expected failures cannot be annotated on non-#[test] functions
             if let Some(abort_attribute) = abort_attribute_opt {
                 let fn_msg = "Only functions defined as a test with #[test] can also have an \
@@ -142,7 +145,19 @@ fn build_test_info<'func>(
             }
             return None;
         }
-        Some(test_attribute) => test_attribute,
+        // A function is either a #[test] or a #[random_test], never both
+        (Some(test_attribute), Some(random_test_attribute)) => {
+            let msg = "Function annotated as both #[test(...)] and #[random_test]. You need to \
+                       declare it as either one or the other";
+            context.env.add_diag(diag!(
+                Attributes::InvalidUsage,
+                (random_test_attribute.loc, msg),
+                (test_attribute.loc, PREVIOUSLY_ANNOTATED_MSG),
+                (fn_loc, IN_THIS_TEST_MSG),
+            ));
+            return None;
+        }
+        (Some(test_attribute), None) | (None, Some(test_attribute)) => test_attribute,
     };
 
     // A #[test] function cannot also be annotated #[test_only]
@@ -157,20 +172,25 @@ fn build_test_info<'func>(
         ))
     }
 
-    let test_annotation_params = parse_test_attribute(context, test_attribute, 0);
     let mut arguments = Vec::new();
-    for (var, _) in &function.signature.parameters {
-        match test_annotation_params.get(&var.value()) {
-            Some(value) => arguments.push(value.clone()),
-            None => {
-                let missing_param_msg = "Missing test parameter assignment in test. Expected a \
-                                         parameter to be assigned in this attribute";
-                context.env.add_diag(diag!(
-                    Attributes::InvalidTest,
-                    (test_attribute.loc, missing_param_msg),
-                    (var.loc(), "Corresponding to this parameter"),
-                    (fn_loc, IN_THIS_TEST_MSG),
-                ))
+    let mut random_parameters = Vec::new();
+    if random_test_attribute_opt.is_some() {
+        random_parameters = parse_random_test_parameters(context, test_attribute, function)?;
+    } else {
+        let test_annotation_params = parse_test_attribute(context, test_attribute, 0);
+        for (var, _) in &function.signature.parameters {
+            match test_annotation_params.get(&var.value()) {
+                Some(value) => arguments.push(value.clone()),
+                None => {
+                    let missing_param_msg = "Missing test parameter assignment in test. Expected \
+                                             a parameter to be assigned in this attribute";
+                    context.env.add_diag(diag!(
+                        Attributes::InvalidTest,
+                        (test_attribute.loc, missing_param_msg),
+                        (var.loc(), "Corresponding to this parameter"),
+                        (fn_loc, IN_THIS_TEST_MSG),
+                    ))
+                }
             }
         }
     }
@@ -183,10 +203,95 @@ fn build_test_info<'func>(
     Some(TestCase {
         test_name: fn_name.to_string(),
         arguments,
+        random_parameters,
         expected_failure,
     })
 }
 
+// Determines the types of the values the unit test runner has to generate for a #[random_test].
+// Values are only generated for the parameters, so the attribute itself cannot assign any.
+fn parse_random_test_parameters(
+    context: &mut Context,
+    sp!(aloc, random_test_attribute): &E::Attribute,
+    function: &G::Function,
+) -> Option<Vec<(String, RandomTestType)>> {
+    if !matches!(random_test_attribute, E::Attribute_::Name(_)) {
+        let msg = "Unexpected arguments in #[random_test]. Values for the parameters of a random \
+                   test are generated by the test runner";
+        context
+            .env
+            .add_diag(diag!(Attributes::InvalidTest, (*aloc, msg)));
+        return None;
+    }
+    let mut parameters = Vec::new();
+    let mut has_errors = false;
+    for (var, ty) in &function.signature.parameters {
+        match random_test_type(ty) {
+            Some(ty) => parameters.push((var.value().to_string(), ty)),
+            None => {
+                let msg = "Unsupported parameter type for #[random_test]. Expected one of: bool, \
+                           u8, u16, u32, u64, u128, u256, address, signer, \
+                           0x1::string::String, or a vector of these";
+                context
+                    .env
+                    .add_diag(diag!(Attributes::InvalidTest, (var.loc(), msg)));
+                has_errors = true;
+            }
+        }
+    }
+    if has_errors {
+        None
+    } else {
+        Some(parameters)
+    }
+}
+
+fn random_test_type(sp!(_, ty): &H::SingleType) -> Option<RandomTestType> {
+    match ty {
+        SingleType_::Base(bt) => random_test_base_type(bt),
+        SingleType_::Ref(_, _) => None,
+    }
+}
+
+fn random_test_base_type(sp!(_, bt): &H::BaseType) -> Option<RandomTestType> {
+    use H::TypeName_ as TN;
+    let (tn, ty_args) = match bt {
+        BaseType_::Apply(_, sp!(_, tn), ty_args) => (tn, ty_args),
+        BaseType_::Param(_) | BaseType_::Unreachable | BaseType_::UnresolvedError => return None,
+    };
+    Some(match tn {
+        TN::Builtin(sp!(_, b)) => match b {
+            BuiltinTypeName_::Bool => RandomTestType::Bool,
+            BuiltinTypeName_::U8 => RandomTestType::U8,
+            BuiltinTypeName_::U16 => RandomTestType::U16,
+            BuiltinTypeName_::U32 => RandomTestType::U32,
+            BuiltinTypeName_::U64 => RandomTestType::U64,
+            BuiltinTypeName_::U128 => RandomTestType::U128,
+            BuiltinTypeName_::U256 => RandomTestType::U256,
+            BuiltinTypeName_::Address => RandomTestType::Address,
+            BuiltinTypeName_::Signer => RandomTestType::Signer,
+            BuiltinTypeName_::Vector => match ty_args.as_slice() {
+                [elem_ty] => RandomTestType::Vector(Box::new(random_test_base_type(elem_ty)?)),
+                _ => return None,
+            },
+        },
+        TN::ModuleType(sp!(_, ModuleIdent_ { address, module }), struct_name) => {
+            let is_std = matches!(
+                address,
+                Address::Numerical(_, sp!(_, a)) if a.into_inner() == MoveAddress::ONE
+            );
+            if is_std
+                && module.value().as_str() == "string"
+                && struct_name.value().as_str() == "String"
+            {
+                RandomTestType::String
+            } else {
+                return None;
+            }
+        }
+    })
+}
+
 //***************************************************************************
 // Attribute parsers
 //***************************************************************************
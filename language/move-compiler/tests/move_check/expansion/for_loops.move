@@ -0,0 +1,33 @@
+module 0x42::M {
+    struct S has drop { f: u64 }
+
+    fun sum(v: &vector<u64>): u64 {
+        let sum = 0;
+        for (x in v) sum = sum + *x;
+        sum
+    }
+
+    fun reset(v: &mut vector<S>) {
+        for (s in &mut *v) s.f = 0
+    }
+
+    fun count(n: u64): u64 {
+        let c = 0;
+        for (i in 0..n) {
+            c = c + 1
+        } spec {
+            invariant c == i;
+        };
+        c
+    }
+
+    fun nested(v: &vector<vector<u64>>): u64 {
+        let max = 0;
+        for (row in v) {
+            for (x in row) {
+                if (*x > max) max = *x
+            }
+        };
+        max
+    }
+}
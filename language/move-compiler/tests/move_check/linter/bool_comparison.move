@@ -0,0 +1,17 @@
+module 0x42::m {
+    public fun eq_true(b: bool): bool {
+        b == true
+    }
+
+    public fun neq_false(b: bool): bool {
+        false != b
+    }
+
+    public fun eq_false(b: bool): bool {
+        b == false
+    }
+
+    public fun not_reported(a: bool, b: bool): bool {
+        a == b
+    }
+}
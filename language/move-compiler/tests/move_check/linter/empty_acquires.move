@@ -0,0 +1,15 @@
+module 0x42::m {
+    struct R has key { f: u64 }
+
+    public fun unreachable(a: address): u64 acquires R {
+        if (false) {
+            borrow_global<R>(a).f
+        } else {
+            0
+        }
+    }
+
+    public fun not_reported(a: address): u64 acquires R {
+        borrow_global<R>(a).f
+    }
+}
@@ -0,0 +1,23 @@
+module 0x42::m {
+    #[lint_allow(bool_comparison)]
+    public fun allowed(b: bool): bool {
+        b == true
+    }
+
+    #[lint_allow(self_assignment, unknown_lint)]
+    public fun unknown(b: bool): bool {
+        b == true
+    }
+
+    #[lint_allow]
+    public fun malformed(): u64 {
+        0
+    }
+}
+
+#[lint_allow(needless_copy)]
+module 0x42::n {
+    public fun allowed(x: u64): u64 {
+        copy x
+    }
+}
@@ -0,0 +1,14 @@
+module 0x42::m {
+    public fun last_use(x: u64): u64 {
+        copy x
+    }
+
+    public fun in_branch(c: bool, v: vector<u64>): vector<u64> {
+        if (c) copy v else v
+    }
+
+    public fun not_reported(x: u64): u64 {
+        let y = copy x;
+        x + y
+    }
+}
@@ -0,0 +1,17 @@
+module 0x42::m {
+    struct S has drop { f: u64 }
+
+    public fun assign_local(x: u64): u64 {
+        x = x;
+        x
+    }
+
+    public fun assign_field(s: &mut S) {
+        s.f = s.f;
+    }
+
+    public fun not_reported(s: &mut S, x: u64): u64 {
+        s.f = x;
+        s.f
+    }
+}
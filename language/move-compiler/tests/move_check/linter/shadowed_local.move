@@ -0,0 +1,27 @@
+module 0x42::m {
+    public fun nested(c: bool): u64 {
+        let x = 0;
+        if (c) {
+            let x = 1;
+            x
+        } else {
+            x
+        }
+    }
+
+    public fun param(x: u64): u64 {
+        {
+            let x = x + 1;
+            x
+        }
+    }
+
+    public fun not_reported(x: u64): u64 {
+        let x = x + 1;
+        let _y = 0;
+        {
+            let _y = 1;
+        };
+        x
+    }
+}
@@ -0,0 +1,28 @@
+module 0x42::m {
+    struct S has drop { f: u64 }
+
+    fun read(s: &S): u64 {
+        s.f
+    }
+
+    fun read_both(s: &S, x: &u64): u64 {
+        s.f + *x
+    }
+
+    fun write(s: &mut S) {
+        s.f = 0;
+    }
+
+    public fun borrow_local(s: S): u64 {
+        read(&mut s)
+    }
+
+    public fun borrow_field(s: &mut S, other: S): u64 {
+        read_both(&other, &mut s.f)
+    }
+
+    public fun not_reported(s: S): u64 {
+        write(&mut s);
+        read(&s)
+    }
+}
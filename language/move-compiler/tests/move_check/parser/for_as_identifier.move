@@ -0,0 +1,10 @@
+module 0x42::M {
+    struct S has drop { for: u64 }
+
+    fun for(for: u64): u64 { for }
+
+    fun t(s: S): u64 {
+        let for = for(s.for);
+        for(for) + (for)
+    }
+}
@@ -0,0 +1,8 @@
+module 0x42::M {
+    fun f(n: u64) {
+        for (i in 0..n) {
+        } spec {
+            ensures i == n;
+        };
+    }
+}
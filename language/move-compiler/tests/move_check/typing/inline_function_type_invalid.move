@@ -0,0 +1,14 @@
+module 0x42::M {
+    inline fun apply(f: |u64|u64): u64 {
+        let g = f;
+        g(1)
+    }
+
+    fun not_inline(f: |u64|u64): u64 {
+        0
+    }
+
+    fun t(x: u64): u64 {
+        x(1)
+    }
+}
@@ -0,0 +1,49 @@
+module 0x42::M {
+    const BASE: u64 = 10;
+
+    public inline fun repeat(n: u64, f: |u64|) {
+        let i = 0;
+        while (i < n) {
+            f(i);
+            i = i + 1;
+        }
+    }
+
+    public inline fun fold_range<T>(n: u64, init: T, f: |T, u64|T): T {
+        let acc = init;
+        repeat(n, |i| acc = f(acc, i));
+        acc
+    }
+
+    public inline fun scale(x: u64): u64 {
+        x * BASE
+    }
+
+    public inline fun apply(f: ||u64): u64 {
+        f()
+    }
+
+    fun sum(n: u64): u64 {
+        fold_range(n, 0, |acc, i| acc + i)
+    }
+}
+
+module 0x42::N {
+    use 0x42::M;
+
+    fun count(n: u64): u64 {
+        let c = 0;
+        M::repeat(n, |_| c = c + 1);
+        c
+    }
+
+    fun scaled(): u64 {
+        let x = 2;
+        M::apply(|| M::scale(x))
+    }
+
+    fun shadowing(): u64 {
+        let i = 7;
+        M::fold_range(3, i, |acc, i| acc + i)
+    }
+}
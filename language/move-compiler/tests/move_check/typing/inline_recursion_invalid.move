@@ -0,0 +1,13 @@
+module 0x42::M {
+    inline fun f(x: u64): u64 {
+        if (x == 0) 0 else g(x - 1)
+    }
+
+    inline fun g(x: u64): u64 {
+        f(x)
+    }
+
+    inline fun h(x: u64): u64 {
+        h(x)
+    }
+}
@@ -0,0 +1,14 @@
+module 0x42::M {
+    inline fun apply(f: |u64|u64): u64 {
+        f(1)
+    }
+
+    inline fun early(x: u64): u64 {
+        if (x > 0) return x;
+        0
+    }
+
+    fun t(): u64 {
+        apply(|x| return x)
+    }
+}
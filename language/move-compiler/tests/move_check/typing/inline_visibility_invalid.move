@@ -0,0 +1,25 @@
+module 0x42::M {
+    struct S has drop { f: u64 }
+
+    fun internal(): u64 { 0 }
+
+    public inline fun call_internal(): u64 {
+        internal()
+    }
+
+    public inline fun make(): S {
+        S { f: 0 }
+    }
+}
+
+module 0x42::N {
+    use 0x42::M;
+
+    fun t(): u64 {
+        M::call_internal()
+    }
+
+    fun u() {
+        M::make();
+    }
+}
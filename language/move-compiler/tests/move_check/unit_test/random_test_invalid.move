@@ -0,0 +1,17 @@
+// #[random_test] functions cannot assign their parameters, and only take generatable types
+module 0x1::M {
+    struct S has drop {}
+
+    #[random_test(_a=@0x1)]
+    fun assigned(_a: address) { }
+
+    #[test]
+    #[random_test]
+    fun both() { }
+
+    #[random_test]
+    fun struct_param(_s: S) { }
+
+    #[random_test]
+    fun reference_param(_x: &u64) { }
+}
@@ -0,0 +1,22 @@
+// #[random_test] functions can take any parameter type the test runner knows how to generate
+module 0x1::string {
+    struct String has copy, drop { bytes: vector<u8> }
+}
+
+module 0x1::M {
+    #[test_only]
+    use 0x1::string::String;
+
+    #[random_test]
+    fun primitives(_a: bool, _b: u8, _c: u16, _d: u32, _e: u64, _f: u128, _g: u256) { }
+
+    #[random_test]
+    fun accounts(_a: address, _s: signer) { }
+
+    #[random_test]
+    fun nested(_v: vector<vector<u64>>, _s: String, _ss: vector<String>) { }
+
+    #[random_test]
+    #[expected_failure]
+    fun fails(x: u64) { assert!(x == 0 && x == 1, 0) }
+}
@@ -0,0 +1,20 @@
+// A module header comment
+address 0x42 {
+module M {
+    use std::vector;
+    use std::option::{Self,Option};
+    struct S has copy,drop { f: u64, g: vector<u8> }
+    const MAX :u64=100;
+
+    public fun add(a:u64,b:u64):u64{ a+b*2 }
+
+
+
+    fun long_signature(first_argument: u64, second_argument: vector<u8>, third_argument: Option<u64>): u64 {
+        let x = if (first_argument > 0) first_argument else 1; // trailing
+        let S { f, g: _ } = S { f: x, g: second_argument };
+        while (f < MAX) { f = f + 1 };
+        f
+    }
+}
+}
@@ -0,0 +1,21 @@
+module 0x42::comments {
+    /// Doc comment on a struct
+    struct Empty has drop { x: u64 }
+
+    /* block comment before a function */
+    fun empty() {
+        // only a comment
+    }
+
+    fun args(/* first */ a: u64, b: u64 /* after b */): u64 {
+        a /* inline */ + b
+    }
+
+    fun nested() {
+        /* nested /* comment */ here */
+        let _x = 1;
+
+
+        // after two blank lines
+    }
+}
@@ -0,0 +1,11 @@
+module 0x42::exps {
+    fun prec(a: u64, b: u64, c: bool): u64 {
+        let x=(a+b)*(a-b);
+        let y=a-(b-1);
+        if (c&&!(a>b||b>a)) return x;
+        let v=vector[1,2,3];
+        for (i in 0..10) {y=y+i};
+        for (e in &v) y = y + *e;
+        y
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::HashMap, fs, path::Path};
+
+use move_command_line_common::{
+    files::FileHash,
+    testing::{add_update_baseline_fix, format_diff, read_env_update_baseline, EXP_EXT},
+};
+use move_compiler::{diagnostics::report_diagnostics_to_buffer, formatter::format_string};
+use move_symbol_pool::Symbol;
+
+// Formats the file at `path` and compares the result against the `.exp` file next to it. The
+// output of the formatter must also be left unchanged by formatting it again.
+fn move_fmt_testsuite(path: &Path) -> datatest_stable::Result<()> {
+    let exp_path = path.with_extension(EXP_EXT);
+    let source = fs::read_to_string(path)?;
+    let file_hash = FileHash::new(&source);
+    let output = match format_string(file_hash, &source) {
+        Ok(formatted) => {
+            let reformatted = format_string(FileHash::new(&formatted), &formatted);
+            match reformatted {
+                Ok(reformatted) if reformatted == formatted => (),
+                Ok(reformatted) => {
+                    let msg = format!(
+                        "Formatting is not idempotent:\n{}",
+                        format_diff(&formatted, &reformatted)
+                    );
+                    anyhow::bail!(msg)
+                }
+                Err(_) => anyhow::bail!("Formatted source fails to format:\n{}", formatted),
+            }
+            formatted
+        }
+        Err(diags) => {
+            let files = HashMap::from([(
+                file_hash,
+                (Symbol::from(path.to_string_lossy().as_ref()), source),
+            )]);
+            String::from_utf8(report_diagnostics_to_buffer(&files, diags))?
+        }
+    };
+
+    if read_env_update_baseline() {
+        fs::write(exp_path, output)?;
+        return Ok(());
+    }
+
+    let expected = fs::read_to_string(&exp_path)?;
+    if output != expected {
+        let msg = format!(
+            "Expected output differs from actual output:\n{}",
+            format_diff(expected, output),
+        );
+        anyhow::bail!(add_update_baseline_fix(msg))
+    }
+    Ok(())
+}
+
+datatest_stable::harness!(move_fmt_testsuite, "tests/move_fmt", r".*\.move$");
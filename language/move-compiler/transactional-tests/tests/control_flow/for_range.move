@@ -0,0 +1,31 @@
+//# run
+script {
+fun main() {
+    let sum = 0;
+    for (i in 0..10) {
+        if (i % 2 == 0) continue;
+        if (i > 7) break;
+        sum = sum + i
+    };
+    assert!(sum == 16, 42);
+
+    let count = 0;
+    for (_ in 5..5) count = count + 1;
+    assert!(count == 0, 43);
+
+    // the bound is only evaluated once
+    let n = 3;
+    for (i in 0..n) {
+        n = n + 1;
+        count = count + i
+    };
+    assert!(count == 3, 44);
+    assert!(n == 6, 45);
+
+    let pairs = 0;
+    for (i in 0..4) {
+        for (j in i..4) pairs = pairs + j - i
+    };
+    assert!(pairs == 10, 46);
+}
+}
@@ -0,0 +1,24 @@
+//# run
+script {
+use std::vector;
+
+fun main() {
+    let v = vector[1, 2, 3, 4, 5];
+    let sum = 0;
+    for (x in &v) {
+        if (*x == 3) continue;
+        sum = sum + *x
+    };
+    assert!(sum == 12, 42);
+
+    for (x in &mut v) *x = *x * 10;
+    assert!(*vector::borrow(&v, 4) == 50, 43);
+
+    let r = &mut v;
+    for (x in &mut *r) *x = *x + 1;
+    assert!(*vector::borrow(&v, 0) == 11, 44);
+
+    let empty = vector::empty<u64>();
+    for (_ in &empty) abort 45;
+}
+}
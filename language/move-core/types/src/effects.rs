@@ -296,3 +296,106 @@ impl ChangeSet {
 }
 
 pub type Event = (Vec<u8>, u64, TypeTag, Vec<u8>);
+
+/// The modules and resources of an account read from storage, with the bytes observed when they
+/// were first read, `None` for those which did not exist
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct AccountReadSet {
+    modules: BTreeMap<Identifier, Option<Vec<u8>>>,
+    resources: BTreeMap<StructTag, Option<Vec<u8>>>,
+}
+
+impl AccountReadSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a read of a module. Only the first read of a module is kept.
+    pub fn add_module_read(&mut self, name: Identifier, blob: Option<Vec<u8>>) {
+        self.modules.entry(name).or_insert(blob);
+    }
+
+    /// Records a read of a resource. Only the first read of a resource is kept.
+    pub fn add_resource_read(&mut self, struct_tag: StructTag, blob: Option<Vec<u8>>) {
+        self.resources.entry(struct_tag).or_insert(blob);
+    }
+
+    pub fn modules(&self) -> &BTreeMap<Identifier, Option<Vec<u8>>> {
+        &self.modules
+    }
+
+    pub fn resources(&self) -> &BTreeMap<StructTag, Option<Vec<u8>>> {
+        &self.resources
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty() && self.resources.is_empty()
+    }
+}
+
+/// A collection of reads from a Move state, the counterpart of a `ChangeSet`
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ReadSet {
+    accounts: BTreeMap<AccountAddress, AccountReadSet>,
+}
+
+impl ReadSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn accounts(&self) -> &BTreeMap<AccountAddress, AccountReadSet> {
+        &self.accounts
+    }
+
+    pub fn into_inner(self) -> BTreeMap<AccountAddress, AccountReadSet> {
+        self.accounts
+    }
+
+    pub fn add_module_read(&mut self, module_id: ModuleId, blob: Option<Vec<u8>>) {
+        self.accounts
+            .entry(*module_id.address())
+            .or_default()
+            .add_module_read(module_id.name().to_owned(), blob)
+    }
+
+    pub fn add_resource_read(
+        &mut self,
+        addr: AccountAddress,
+        struct_tag: StructTag,
+        blob: Option<Vec<u8>>,
+    ) {
+        self.accounts
+            .entry(addr)
+            .or_default()
+            .add_resource_read(struct_tag, blob)
+    }
+
+    pub fn contains_module(&self, module_id: &ModuleId) -> bool {
+        self.accounts
+            .get(module_id.address())
+            .map_or(false, |account| {
+                account.modules.contains_key(module_id.name())
+            })
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = (AccountAddress, &Identifier, Option<&[u8]>)> {
+        self.accounts.iter().flat_map(|(addr, account)| {
+            let addr = *addr;
+            account
+                .modules
+                .iter()
+                .map(move |(module_name, blob)| (addr, module_name, blob.as_deref()))
+        })
+    }
+
+    pub fn resources(&self) -> impl Iterator<Item = (AccountAddress, &StructTag, Option<&[u8]>)> {
+        self.accounts.iter().flat_map(|(addr, account)| {
+            let addr = *addr;
+            account
+                .resources
+                .iter()
+                .map(move |(struct_tag, blob)| (addr, struct_tag, blob.as_deref()))
+        })
+    }
+}
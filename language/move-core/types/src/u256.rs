@@ -419,6 +419,52 @@ impl U256 {
         Self(self.0.overflowing_mul(rhs.0).0)
     }
 
+    /// Checked exponentiation. Computes self ^ exp, returning None if overflow occurred.
+    pub fn checked_pow(self, mut exp: u32) -> Option<Self> {
+        let mut base = self;
+        let mut result = Self::one();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.checked_mul(base)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.checked_mul(base)?;
+            }
+        }
+        Some(result)
+    }
+
+    /// Integer square root, rounded down
+    pub fn integer_sqrt(self) -> Self {
+        Self(self.0.integer_sqrt())
+    }
+
+    /// Computes self * mul / div with a 512 bit intermediate product, returning None if div == 0
+    /// or if the quotient does not fit in 256 bits.
+    pub fn checked_mul_div(self, mul: Self, div: Self) -> Option<Self> {
+        if div == Self::zero() {
+            return None;
+        }
+        let (high, low) = self.wmul(mul);
+        if high >= div {
+            return None;
+        }
+        // Long division of the 512 bit product, one bit of the low half at a time. The remainder
+        // is below div, so it only overflows 256 bits by the bit shifted out of it.
+        let mut rem = high;
+        let mut quot = Self::zero();
+        for i in (0..U256_NUM_BITS as u32).rev() {
+            let carry = rem.leading_zeros() == 0;
+            rem = (rem << 1u32) | ((low >> (i as u8)) & Self::one());
+            if carry || rem >= div {
+                rem = rem.wrapping_sub(div);
+                quot = quot | (Self::one() << i);
+            }
+        }
+        Some(quot)
+    }
+
     /// Implementation of widenining multiply
     /// https://github.com/rust-random/rand/blob/master/src/distributions/utils.rs
     #[inline(always)]
@@ -718,3 +764,19 @@ fn wrapping_add() {
 
     assert!(a.wrapping_add(b) == U256::from(99u8));
 }
+
+#[test]
+fn checked_mul_div() {
+    let max = U256::max_value();
+    assert_eq!(max.checked_mul_div(max, max), Some(max));
+    assert_eq!(
+        max.checked_mul_div(U256::from(3u8), U256::from(4u8)),
+        Some(max / U256::from(4u8) * U256::from(3u8) + U256::from(2u8))
+    );
+    assert_eq!(max.checked_mul_div(U256::from(2u8), U256::from(1u8)), None);
+    assert_eq!(max.checked_mul_div(U256::one(), U256::zero()), None);
+    assert_eq!(
+        U256::from(7u8).checked_mul_div(U256::from(3u8), U256::from(2u8)),
+        Some(U256::from(10u8))
+    );
+}
@@ -346,7 +346,7 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
         for (name, struct_def) in module_def.structs.key_cloned_iter() {
             self.decl_ana_struct(&name, struct_def);
         }
-        for (name, fun_def) in module_def.functions.key_cloned_iter() {
+        for (name, fun_def) in non_inline_functions(module_def) {
             self.decl_ana_fun(&name, fun_def);
         }
         for (name, const_def) in module_def.constants.key_cloned_iter() {
@@ -668,14 +668,14 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
         }
 
         // Analyze all functions.
-        for (idx, (name, fun_def)) in module_def.functions.key_cloned_iter().enumerate() {
+        for (idx, (name, fun_def)) in non_inline_functions(module_def).enumerate() {
             self.def_ana_fun(&name, &fun_def.body, idx);
         }
 
         // Propagate the impurity of functions: a Move function which calls an
         // impure Move function is also considered impure.
         let mut visited = BTreeMap::new();
-        for (idx, (name, _)) in module_def.functions.key_cloned_iter().enumerate() {
+        for (idx, (name, _)) in non_inline_functions(module_def).enumerate() {
             let is_pure = self.propagate_function_impurity(&mut visited, SpecFunId::new(idx));
             let full_name = self.qualified_by_module_from_name(&name.0);
             if is_pure {
@@ -742,7 +742,7 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
         }
 
         // Analyze in-function spec blocks.
-        for (name, fun_def) in module_def.functions.key_cloned_iter() {
+        for (name, fun_def) in non_inline_functions(module_def) {
             let fun_spec_info = &function_infos.get(&name).unwrap().spec_info;
             let qsym = self.qualified_by_module_from_name(&name.0);
             for (spec_id, spec_block) in fun_def.specs.iter() {
@@ -3258,3 +3258,14 @@ pub(crate) fn extract_schema_access<'a>(exp: &'a EA::Exp, res: &mut Vec<&'a EA::
         _ => {}
     }
 }
+
+/// The functions of a module which are not `inline`. Calls to `inline` functions are expanded
+/// by the compiler, so they have no counterpart in the compiled module.
+fn non_inline_functions(
+    module_def: &EA::ModuleDefinition,
+) -> impl Iterator<Item = (PA::FunctionName, &EA::Function)> {
+    module_def
+        .functions
+        .key_cloned_iter()
+        .filter(|(_, fun_def)| !fun_def.inline)
+}
@@ -34,10 +34,6 @@ module std::compare {
     /// `compare_bcs_bytes(bcs(0x01), bcs(0x10)) == LESS_THAN` (as you'd expect), but
     /// `compare_bcs_bytes(bcs(0x100), bcs(0x001)) == LESS_THAN` (as you probably wouldn't expect).
     /// Keep this in mind when using this function to compare addresses.
-    ///
-    /// > TODO: there is currently no specification for this function, which causes no problem because it is not yet
-    /// > used in the Diem framework. However, should this functionality be needed in specification, a customized
-    /// > native abstraction is needed in the prover framework.
     public fun cmp_bcs_bytes(v1: &vector<u8>, v2: &vector<u8>): u8 {
         let i1 = vector::length(v1);
         let i2 = vector::length(v2);
@@ -46,7 +42,14 @@ module std::compare {
         // BCS uses little endian encoding for all integer types, so we choose to compare from left
         // to right. Going right to left would make the behavior of compare::cmp diverge from the
         // bytecode operators < and > on integer values (which would be confusing).
-        while (i1 > 0 && i2 > 0) {
+        while ({
+            spec {
+                invariant i1 <= len(v1) && i2 <= len(v2);
+                invariant len(v1) - i1 == len(v2) - i2;
+                invariant forall k in 0..len(v1) - i1: spec_byte_at(v1, k) == spec_byte_at(v2, k);
+            };
+            (i1 > 0 && i2 > 0)
+        }) {
             i1 = i1 - 1;
             i2 = i2 - 1;
             let elem_cmp = cmp_u8(*vector::borrow(v1, i1), *vector::borrow(v2, i2));
@@ -56,6 +59,11 @@ module std::compare {
         // all compared elements equal; use length comparion to break the tie
         len_cmp
     }
+    spec cmp_bcs_bytes {
+        pragma opaque;
+        aborts_if false;
+        ensures result == spec_cmp_bcs_bytes(v1, v2);
+    }
 
     /// Compare two `u8`'s
     fun cmp_u8(i1: u8, i2: u8): u8 {
@@ -71,4 +79,23 @@ module std::compare {
         else GREATER_THAN
     }
 
+    /// The result of `cmp_bcs_bytes(v1, v2)`
+    spec fun spec_cmp_bcs_bytes(v1: vector<u8>, v2: vector<u8>): u8 {
+        let n = if (len(v1) < len(v2)) len(v1) else len(v2);
+        if (exists k in 0..n: spec_byte_at(v1, k) != spec_byte_at(v2, k)) {
+            let k = choose min k in 0..n where spec_byte_at(v1, k) != spec_byte_at(v2, k);
+            if (spec_byte_at(v1, k) < spec_byte_at(v2, k)) LESS_THAN else GREATER_THAN
+        } else if (len(v1) == len(v2)) {
+            EQUAL
+        } else if (len(v1) < len(v2)) {
+            LESS_THAN
+        } else {
+            GREATER_THAN
+        }
+    }
+
+    /// The byte `k` places from the end of `v`
+    spec fun spec_byte_at(v: vector<u8>, k: num): u8 {
+        v[len(v) - 1 - k]
+    }
 }
@@ -0,0 +1,820 @@
+/// A map which keeps its entries ordered by key, backed by a B-tree whose nodes are stored in a
+/// vector. Lookups, insertions and removals visit a logarithmic number of nodes.
+///
+/// Keys are ordered by `compare::cmp_bcs_bytes` on their BCS encoding. For the unsigned integer
+/// types this is the numeric order; see `compare` for the order of other types.
+module std::ordered_map {
+    use std::bcs;
+    use std::compare;
+    use std::option::{Self, Option};
+    use std::vector;
+
+    /// The key is already in the map
+    const EKEY_ALREADY_EXISTS: u64 = 0x6407;
+    /// The key is not in the map
+    const EKEY_NOT_FOUND: u64 = 0x6507;
+    /// The map is not empty
+    const ENOT_EMPTY: u64 = 0x6601;
+
+    // The results of `compare::cmp_bcs_bytes`
+    const EQUAL: u8 = 0;
+    const LESS_THAN: u8 = 1;
+
+    /// Nodes other than the root hold between `MIN_DEGREE - 1` and `2 * MIN_DEGREE - 1` entries.
+    const MIN_DEGREE: u64 = 3;
+
+    /// A map from keys of type `K` to values of type `V`, ordered by key.
+    struct OrderedMap<K, V> has copy, drop, store {
+        /// The index of the root node in `nodes`
+        root: u64,
+        nodes: vector<Node<K, V>>,
+        /// The indices of unused nodes, which are empty
+        free: vector<u64>,
+        size: u64,
+    }
+    spec OrderedMap {
+        // The root and the children of every node are nodes of the map
+        invariant root < len(nodes);
+        invariant forall n in 0..len(nodes), i in 0..len(nodes[n].children):
+            nodes[n].children[i] < len(nodes);
+        // Unused nodes are empty
+        invariant forall i in 0..len(free):
+            free[i] < len(nodes) && len(nodes[free[i]].entries) == 0
+                && len(nodes[free[i]].children) == 0;
+        // A leaf has no children, any other node has one child more than entries
+        invariant forall n in 0..len(nodes): len(nodes[n].children) == 0
+            || len(nodes[n].entries) > 0 && len(nodes[n].children) == len(nodes[n].entries) + 1;
+        invariant forall n in 0..len(nodes): len(nodes[n].entries) <= 2 * MIN_DEGREE - 1;
+        // The entries of a node are in ascending order of their keys
+        invariant forall n in 0..len(nodes), i in 0..len(nodes[n].entries),
+            j in 0..len(nodes[n].entries) where i < j:
+            spec_less(nodes[n].entries[i].key, nodes[n].entries[j].key);
+        // The keys of a child are between the keys of the entries around it
+        invariant forall n in 0..len(nodes), i in 0..len(nodes[n].children),
+            j in 0..len(nodes[nodes[n].children[i]].entries):
+            (i > 0 ==> spec_less(
+                nodes[n].entries[i - 1].key,
+                nodes[nodes[n].children[i]].entries[j].key,
+            )) && (i < len(nodes[n].entries) ==> spec_less(
+                nodes[nodes[n].children[i]].entries[j].key,
+                nodes[n].entries[i].key,
+            ));
+        // No key is in more than one node
+        invariant forall n1 in 0..len(nodes), n2 in 0..len(nodes), k: K
+            where n1 != n2 && spec_node_contains(nodes[n1], k):
+            !spec_node_contains(nodes[n2], k);
+        invariant size == 0 ==> (forall n in 0..len(nodes): len(nodes[n].entries) == 0);
+    }
+
+    /// A node of the B-tree. A leaf has no children, any other node has one child more than
+    /// entries. The keys of `children[i]` are between the keys of `entries[i - 1]` and
+    /// `entries[i]`.
+    struct Node<K, V> has copy, drop, store {
+        entries: vector<Entry<K, V>>,
+        children: vector<u64>,
+    }
+
+    /// An entry of an `OrderedMap`.
+    struct Entry<K, V> has copy, drop, store {
+        key: K,
+        value: V,
+    }
+
+    /// Create an empty map.
+    public fun new<K, V>(): OrderedMap<K, V> {
+        OrderedMap {
+            root: 0,
+            nodes: vector::singleton(empty_node()),
+            free: vector::empty(),
+            size: 0,
+        }
+    }
+    spec new {
+        aborts_if false;
+        ensures spec_len(result) == 0;
+        ensures forall k: K: !spec_contains_key(result, k);
+    }
+
+    /// Return the number of entries in `map`.
+    public fun length<K, V>(map: &OrderedMap<K, V>): u64 {
+        map.size
+    }
+    spec length {
+        aborts_if false;
+        ensures result == spec_len(map);
+    }
+
+    /// Return true if `map` has no entries.
+    public fun is_empty<K, V>(map: &OrderedMap<K, V>): bool {
+        map.size == 0
+    }
+    spec is_empty {
+        aborts_if false;
+        ensures result == (spec_len(map) == 0);
+    }
+
+    /// Return true if `map` has an entry for `key`.
+    public fun contains_key<K, V>(map: &OrderedMap<K, V>, key: &K): bool {
+        let (found, _, _) = locate(map, &bcs::to_bytes(key));
+        found
+    }
+    spec contains_key {
+        aborts_if false;
+        ensures result == spec_contains_key(map, key);
+    }
+
+    /// Borrow the value associated with `key`.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow<K, V>(map: &OrderedMap<K, V>, key: &K): &V {
+        let (found, node, index) = locate(map, &bcs::to_bytes(key));
+        assert!(found, EKEY_NOT_FOUND);
+        &vector::borrow(&vector::borrow(&map.nodes, node).entries, index).value
+    }
+    spec borrow {
+        aborts_if !spec_contains_key(map, key) with EKEY_NOT_FOUND;
+        ensures result == spec_get(map, key);
+    }
+
+    /// Mutably borrow the value associated with `key`.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow_mut<K, V>(map: &mut OrderedMap<K, V>, key: &K): &mut V {
+        let (found, node, index) = locate(map, &bcs::to_bytes(key));
+        assert!(found, EKEY_NOT_FOUND);
+        &mut vector::borrow_mut(&mut vector::borrow_mut(&mut map.nodes, node).entries, index).value
+    }
+    spec borrow_mut {
+        aborts_if !spec_contains_key(map, key) with EKEY_NOT_FOUND;
+    }
+
+    /// Add an entry for `key` with `value`.
+    /// Aborts if there already is an entry for `key`.
+    public fun add<K, V>(map: &mut OrderedMap<K, V>, key: K, value: V) {
+        let key_bytes = bcs::to_bytes(&key);
+        let (found, _, _) = locate(map, &key_bytes);
+        assert!(!found, EKEY_ALREADY_EXISTS);
+
+        // Split full nodes on the way down, so that there is room for the entry in the leaf and
+        // for the median of a split child in its parent
+        let root = map.root;
+        if (is_full(map, root)) {
+            let node = Node { entries: vector::empty(), children: vector::singleton(root) };
+            let root = alloc(map, node);
+            map.root = root;
+            split_child(map, root, 0);
+        };
+        let node = map.root;
+        while ({
+            spec {
+                invariant node < len(map.nodes);
+                invariant len(map.nodes[node].entries) < 2 * MIN_DEGREE - 1;
+                invariant !spec_contains_key(map, key);
+                invariant spec_len(map) == spec_len(old(map));
+            };
+            (!is_leaf(map, node))
+        }) {
+            let (_, index) = search(vector::borrow(&map.nodes, node), &key_bytes);
+            if (is_full(map, child_at(map, node, index))) {
+                split_child(map, node, index);
+                // The median of the child moved up to `index`
+                if (compare::cmp_bcs_bytes(&key_at(map, node, index), &key_bytes) == LESS_THAN) {
+                    index = index + 1;
+                };
+            };
+            node = child_at(map, node, index);
+        };
+        let (_, index) = search(vector::borrow(&map.nodes, node), &key_bytes);
+        let entries = &mut vector::borrow_mut(&mut map.nodes, node).entries;
+        vector::insert(entries, Entry { key, value }, index);
+        map.size = map.size + 1;
+    }
+    spec add {
+        aborts_if spec_contains_key(map, key) with EKEY_ALREADY_EXISTS;
+        aborts_if spec_len(map) + 1 > MAX_U64 with EXECUTION_FAILURE;
+        ensures spec_len(map) == spec_len(old(map)) + 1;
+        ensures spec_contains_key(map, key);
+        ensures spec_get(map, key) == value;
+        ensures forall k: K where k != key:
+            spec_contains_key(map, k) == spec_contains_key(old(map), k);
+        ensures forall k: K where k != key && spec_contains_key(old(map), k):
+            spec_get(map, k) == spec_get(old(map), k);
+    }
+
+    /// Associate `key` with `value`, replacing the value of an existing entry for `key`.
+    public fun upsert<K: drop, V: drop>(map: &mut OrderedMap<K, V>, key: K, value: V) {
+        let (found, node, index) = locate(map, &bcs::to_bytes(&key));
+        if (found) {
+            let entries = &mut vector::borrow_mut(&mut map.nodes, node).entries;
+            vector::borrow_mut(entries, index).value = value;
+        } else {
+            add(map, key, value);
+        }
+    }
+    spec upsert {
+        aborts_if !spec_contains_key(map, key) && spec_len(map) + 1 > MAX_U64
+            with EXECUTION_FAILURE;
+        ensures spec_contains_key(old(map), key) ==> spec_len(map) == spec_len(old(map));
+        ensures !spec_contains_key(old(map), key) ==> spec_len(map) == spec_len(old(map)) + 1;
+        ensures spec_contains_key(map, key);
+        ensures spec_get(map, key) == value;
+        ensures forall k: K where k != key:
+            spec_contains_key(map, k) == spec_contains_key(old(map), k);
+        ensures forall k: K where k != key && spec_contains_key(old(map), k):
+            spec_get(map, k) == spec_get(old(map), k);
+    }
+
+    /// Remove the entry for `key` and return its key and value.
+    /// Aborts if there is no entry for `key`.
+    public fun remove<K, V>(map: &mut OrderedMap<K, V>, key: &K): (K, V) {
+        let key_bytes = bcs::to_bytes(key);
+        let (found, _, _) = locate(map, &key_bytes);
+        assert!(found, EKEY_NOT_FOUND);
+        map.size = map.size - 1;
+
+        // Make sure that every node below the root has more than the minimum number of entries
+        // before descending into it, so that removing an entry never leaves too few behind
+        let node = map.root;
+        loop {
+            spec {
+                invariant node < len(map.nodes);
+                invariant spec_contains_key(map, key);
+                invariant spec_len(map) == spec_len(old(map)) - 1;
+            };
+            let (found, index) = search(vector::borrow(&map.nodes, node), &key_bytes);
+            if (!found) {
+                // The entry is in a subtree, so this is not a leaf
+                node = descend(map, node, index);
+                continue
+            };
+            if (is_leaf(map, node)) {
+                let entries = &mut vector::borrow_mut(&mut map.nodes, node).entries;
+                let Entry { key, value } = vector::remove(entries, index);
+                return (key, value)
+            };
+            // Replace the entry with its predecessor or successor, or else merge the children
+            // around it and remove it from the merged child
+            let left = child_at(map, node, index);
+            if (entry_count(map, left) >= MIN_DEGREE) {
+                let entry = remove_last(map, left);
+                return replace_entry(map, node, index, entry)
+            };
+            let right = child_at(map, node, index + 1);
+            if (entry_count(map, right) >= MIN_DEGREE) {
+                let entry = remove_first(map, right);
+                return replace_entry(map, node, index, entry)
+            };
+            node = merge(map, node, index);
+        }
+    }
+    spec remove {
+        aborts_if !spec_contains_key(map, key) with EKEY_NOT_FOUND;
+        ensures result_1 == key;
+        ensures result_2 == spec_get(old(map), key);
+        ensures spec_len(map) == spec_len(old(map)) - 1;
+        ensures !spec_contains_key(map, key);
+        ensures forall k: K where k != key:
+            spec_contains_key(map, k) == spec_contains_key(old(map), k);
+        ensures forall k: K where k != key && spec_contains_key(old(map), k):
+            spec_get(map, k) == spec_get(old(map), k);
+    }
+
+    /// Destroy an empty map.
+    /// Aborts if `map` has entries.
+    public fun destroy_empty<K, V>(map: OrderedMap<K, V>) {
+        assert!(map.size == 0, ENOT_EMPTY);
+        let OrderedMap { root: _, nodes, free: _, size: _ } = map;
+        while ({
+            spec {
+                invariant forall n in 0..len(nodes): len(nodes[n].entries) == 0;
+                invariant forall n in 0..len(nodes): len(nodes[n].children) == 0;
+            };
+            (!vector::is_empty(&nodes))
+        }) {
+            destroy_empty_node(vector::pop_back(&mut nodes));
+        };
+        vector::destroy_empty(nodes);
+    }
+    spec destroy_empty {
+        aborts_if spec_len(map) != 0 with ENOT_EMPTY;
+    }
+
+    /// Return the keys of `map` in ascending order.
+    public fun keys<K: copy, V>(map: &OrderedMap<K, V>): vector<K> {
+        let keys = vector::empty();
+        append_keys(map, map.root, &mut keys);
+        keys
+    }
+    spec keys {
+        pragma opaque;
+        aborts_if false;
+        ensures len(result) == spec_len(map);
+        ensures forall k: K: spec_contains_key(map, k) <==> contains(result, k);
+        ensures forall i in 0..len(result), j in 0..len(result) where i < j:
+            spec_less(result[i], result[j]);
+    }
+
+    /// Return the values of `map` in the order of their keys.
+    public fun values<K, V: copy>(map: &OrderedMap<K, V>): vector<V> {
+        let values = vector::empty();
+        append_values(map, map.root, &mut values);
+        values
+    }
+    spec values {
+        pragma opaque;
+        aborts_if false;
+        ensures len(result) == spec_len(map);
+    }
+
+    /// Return the smallest key of `map`, or none if `map` is empty.
+    public fun min_key<K: copy, V>(map: &OrderedMap<K, V>): Option<K> {
+        if (map.size == 0) return option::none();
+        let node = map.root;
+        while ({
+            spec {
+                invariant node < len(map.nodes);
+            };
+            (!is_leaf(map, node))
+        }) {
+            node = child_at(map, node, 0);
+        };
+        option::some(vector::borrow(&vector::borrow(&map.nodes, node).entries, 0).key)
+    }
+    spec min_key {
+        aborts_if false;
+        ensures option::is_some(result) <==> spec_len(map) > 0;
+        ensures option::is_some(result) ==> spec_contains_key(map, option::borrow(result));
+        ensures option::is_some(result) ==> (forall k: K where spec_contains_key(map, k):
+            !spec_less(k, option::borrow(result)));
+    }
+
+    /// Return the largest key of `map`, or none if `map` is empty.
+    public fun max_key<K: copy, V>(map: &OrderedMap<K, V>): Option<K> {
+        if (map.size == 0) return option::none();
+        let node = map.root;
+        while ({
+            spec {
+                invariant node < len(map.nodes);
+            };
+            (!is_leaf(map, node))
+        }) {
+            node = child_at(map, node, entry_count(map, node));
+        };
+        let entries = &vector::borrow(&map.nodes, node).entries;
+        option::some(vector::borrow(entries, vector::length(entries) - 1).key)
+    }
+    spec max_key {
+        aborts_if false;
+        ensures option::is_some(result) <==> spec_len(map) > 0;
+        ensures option::is_some(result) ==> spec_contains_key(map, option::borrow(result));
+        ensures option::is_some(result) ==> (forall k: K where spec_contains_key(map, k):
+            !spec_less(option::borrow(result), k));
+    }
+
+    /// Return the smallest key of `map` which is larger than `key`, or none if there is no such
+    /// key. `key` does not need to be in `map`.
+    public fun next_key<K: copy, V>(map: &OrderedMap<K, V>, key: &K): Option<K> {
+        let key_bytes = bcs::to_bytes(key);
+        let result = option::none();
+        let node = map.root;
+        loop {
+            spec {
+                invariant node < len(map.nodes);
+                invariant option::is_some(result) ==>
+                    spec_contains_key(map, option::borrow(result));
+                invariant option::is_some(result) ==> spec_less(key, option::borrow(result));
+            };
+            let n = vector::borrow(&map.nodes, node);
+            let (found, index) = search(n, &key_bytes);
+            // `entries[index]` is the first entry with a larger key, the keys of `children[index]`
+            // are between it and `key`
+            if (found) index = index + 1;
+            if (index < vector::length(&n.entries)) {
+                result = option::some(vector::borrow(&n.entries, index).key);
+            };
+            if (vector::is_empty(&n.children)) return result;
+            node = *vector::borrow(&n.children, index);
+        }
+    }
+    spec next_key {
+        aborts_if false;
+        ensures option::is_some(result) ==> spec_contains_key(map, option::borrow(result));
+        ensures option::is_some(result) ==> spec_less(key, option::borrow(result));
+        ensures forall k: K where spec_contains_key(map, k) && spec_less(key, k):
+            option::is_some(result) && !spec_less(k, option::borrow(result));
+    }
+
+    /// Return the largest key of `map` which is smaller than `key`, or none if there is no such
+    /// key. `key` does not need to be in `map`.
+    public fun prev_key<K: copy, V>(map: &OrderedMap<K, V>, key: &K): Option<K> {
+        let key_bytes = bcs::to_bytes(key);
+        let result = option::none();
+        let node = map.root;
+        loop {
+            spec {
+                invariant node < len(map.nodes);
+                invariant option::is_some(result) ==>
+                    spec_contains_key(map, option::borrow(result));
+                invariant option::is_some(result) ==> spec_less(option::borrow(result), key);
+            };
+            let n = vector::borrow(&map.nodes, node);
+            // `entries[index - 1]` is the last entry with a smaller key, the keys of
+            // `children[index]` which are smaller than `key` are between it and `key`
+            let (_, index) = search(n, &key_bytes);
+            if (index > 0) {
+                result = option::some(vector::borrow(&n.entries, index - 1).key);
+            };
+            if (vector::is_empty(&n.children)) return result;
+            node = *vector::borrow(&n.children, index);
+        }
+    }
+    spec prev_key {
+        aborts_if false;
+        ensures option::is_some(result) ==> spec_contains_key(map, option::borrow(result));
+        ensures option::is_some(result) ==> spec_less(option::borrow(result), key);
+        ensures forall k: K where spec_contains_key(map, k) && spec_less(k, key):
+            option::is_some(result) && !spec_less(option::borrow(result), k);
+    }
+
+    // ================================================================================
+    // B-tree operations
+
+    fun empty_node<K, V>(): Node<K, V> {
+        Node { entries: vector::empty(), children: vector::empty() }
+    }
+
+    fun destroy_empty_node<K, V>(node: Node<K, V>) {
+        let Node { entries, children } = node;
+        vector::destroy_empty(entries);
+        vector::destroy_empty(children);
+    }
+
+    /// Store `node` in an unused slot of `map.nodes`, and return its index.
+    fun alloc<K, V>(map: &mut OrderedMap<K, V>, node: Node<K, V>): u64 {
+        vector::push_back(&mut map.nodes, node);
+        if (vector::is_empty(&map.free)) return vector::length(&map.nodes) - 1;
+        let index = vector::pop_back(&mut map.free);
+        destroy_empty_node(vector::swap_remove(&mut map.nodes, index));
+        index
+    }
+    spec alloc {
+        pragma opaque;
+        aborts_if false;
+        ensures result < len(map.nodes);
+        ensures map.nodes[result] == node;
+        ensures map.root == old(map.root);
+        ensures map.size == old(map.size);
+        ensures forall n in 0..len(old(map.nodes)) where n != result:
+            map.nodes[n] == old(map.nodes[n]);
+        ensures result < len(old(map.nodes)) ==> len(map.nodes) == len(old(map.nodes));
+        ensures result >= len(old(map.nodes)) ==> len(map.nodes) == len(old(map.nodes)) + 1;
+    }
+
+    /// Take the node at `index` out of `map.nodes`, leaving an unused slot.
+    fun take<K, V>(map: &mut OrderedMap<K, V>, index: u64): Node<K, V> {
+        vector::push_back(&mut map.nodes, empty_node());
+        vector::push_back(&mut map.free, index);
+        vector::swap_remove(&mut map.nodes, index)
+    }
+    spec take {
+        pragma opaque;
+        aborts_if index >= len(map.nodes);
+        ensures result == old(map.nodes[index]);
+        ensures len(map.nodes) == len(old(map.nodes));
+        ensures len(map.nodes[index].entries) == 0 && len(map.nodes[index].children) == 0;
+        ensures forall n in 0..len(map.nodes) where n != index: map.nodes[n] == old(map.nodes[n]);
+        ensures map.free == concat(old(map.free), vec(index));
+        ensures map.root == old(map.root);
+        ensures map.size == old(map.size);
+    }
+
+    fun is_leaf<K, V>(map: &OrderedMap<K, V>, node: u64): bool {
+        vector::is_empty(&vector::borrow(&map.nodes, node).children)
+    }
+
+    fun entry_count<K, V>(map: &OrderedMap<K, V>, node: u64): u64 {
+        vector::length(&vector::borrow(&map.nodes, node).entries)
+    }
+
+    fun is_full<K, V>(map: &OrderedMap<K, V>, node: u64): bool {
+        entry_count(map, node) == 2 * MIN_DEGREE - 1
+    }
+
+    fun child_at<K, V>(map: &OrderedMap<K, V>, node: u64, index: u64): u64 {
+        *vector::borrow(&vector::borrow(&map.nodes, node).children, index)
+    }
+
+    /// The BCS encoding of the key of the entry at `index` in `node`
+    fun key_at<K, V>(map: &OrderedMap<K, V>, node: u64, index: u64): vector<u8> {
+        bcs::to_bytes(&vector::borrow(&vector::borrow(&map.nodes, node).entries, index).key)
+    }
+
+    /// Binary search for the BCS encoded `key` in the entries of `node`. Returns whether the key
+    /// was found, and its index or else the index of the first entry with a larger key.
+    fun search<K, V>(node: &Node<K, V>, key: &vector<u8>): (bool, u64) {
+        let low = 0;
+        let high = vector::length(&node.entries);
+        while ({
+            spec {
+                invariant low <= high;
+                invariant high <= len(node.entries);
+                invariant forall i in 0..low: spec_cmp(node.entries[i].key, key) == LESS_THAN;
+                invariant forall i in high..len(node.entries):
+                    compare::spec_cmp_bcs_bytes(key, bcs::serialize(node.entries[i].key))
+                        == LESS_THAN;
+            };
+            (low < high)
+        }) {
+            let mid = low + (high - low) / 2;
+            let order = compare::cmp_bcs_bytes(
+                &bcs::to_bytes(&vector::borrow(&node.entries, mid).key),
+                key,
+            );
+            if (order == EQUAL) return (true, mid);
+            if (order == LESS_THAN) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        };
+        (false, low)
+    }
+    spec search {
+        pragma opaque;
+        aborts_if false;
+        ensures result_2 <= len(node.entries);
+        ensures result_1 ==> result_2 < len(node.entries);
+        ensures result_1 ==> spec_cmp(node.entries[result_2].key, key) == EQUAL;
+        ensures !result_1 ==>
+            (forall i in 0..result_2: spec_cmp(node.entries[i].key, key) == LESS_THAN);
+        ensures !result_1 ==> (forall i in result_2..len(node.entries):
+            compare::spec_cmp_bcs_bytes(key, bcs::serialize(node.entries[i].key)) == LESS_THAN);
+    }
+
+    /// Find the BCS encoded `key` in `map`. Returns whether the key was found, and the node and
+    /// index of its entry.
+    fun locate<K, V>(map: &OrderedMap<K, V>, key: &vector<u8>): (bool, u64, u64) {
+        let node = map.root;
+        loop {
+            spec {
+                invariant node < len(map.nodes);
+            };
+            let n = vector::borrow(&map.nodes, node);
+            let (found, index) = search(n, key);
+            if (found || vector::is_empty(&n.children)) return (found, node, index);
+            node = *vector::borrow(&n.children, index);
+        }
+    }
+    spec locate {
+        pragma opaque;
+        aborts_if false;
+        ensures result_2 < len(map.nodes);
+        ensures result_1 ==> result_3 < len(map.nodes[result_2].entries);
+        ensures result_1 ==> spec_cmp(map.nodes[result_2].entries[result_3].key, key) == EQUAL;
+        ensures result_1 <==> (exists n in 0..len(map.nodes), i in 0..len(map.nodes[n].entries):
+            bcs::serialize(map.nodes[n].entries[i].key) == key);
+    }
+
+    /// Split the full child at `index` of `parent` in two, moving its median entry up into
+    /// `parent`.
+    fun split_child<K, V>(map: &mut OrderedMap<K, V>, parent: u64, index: u64) {
+        let child = child_at(map, parent, index);
+        let child = vector::borrow_mut(&mut map.nodes, child);
+        let entries = split_off(&mut child.entries, MIN_DEGREE);
+        let median = vector::pop_back(&mut child.entries);
+        let children = if (vector::is_empty(&child.children)) {
+            vector::empty()
+        } else {
+            split_off(&mut child.children, MIN_DEGREE)
+        };
+        let right = alloc(map, Node { entries, children });
+        let parent = vector::borrow_mut(&mut map.nodes, parent);
+        vector::insert(&mut parent.entries, median, index);
+        vector::insert(&mut parent.children, right, index + 1);
+    }
+
+    /// Remove the elements of `v` from index `at` on, and return them.
+    fun split_off<T>(v: &mut vector<T>, at: u64): vector<T> {
+        let tail = vector::empty();
+        while ({
+            spec {
+                invariant len(v) + len(tail) == len(old(v));
+                invariant forall i in 0..len(v): v[i] == old(v)[i];
+                invariant forall i in 0..len(tail): tail[i] == old(v)[len(old(v)) - 1 - i];
+            };
+            (vector::length(v) > at)
+        }) {
+            vector::push_back(&mut tail, vector::pop_back(v));
+        };
+        vector::reverse(&mut tail);
+        tail
+    }
+    spec split_off {
+        pragma opaque;
+        aborts_if false;
+        ensures at < len(old(v)) ==> v == old(v)[0..at] && result == old(v)[at..len(old(v))];
+        ensures at >= len(old(v)) ==> v == old(v) && len(result) == 0;
+    }
+
+    /// Make sure that the child at `index` of `node` has more than the minimum number of entries,
+    /// by moving an entry over from a sibling or else merging it with a sibling. Returns the node
+    /// which now holds the keys of the child.
+    fun descend<K, V>(map: &mut OrderedMap<K, V>, node: u64, index: u64): u64 {
+        let child = child_at(map, node, index);
+        if (entry_count(map, child) >= MIN_DEGREE) return child;
+        let has_left = index > 0;
+        let has_right = index < entry_count(map, node);
+        if (has_left && entry_count(map, child_at(map, node, index - 1)) >= MIN_DEGREE) {
+            rotate_right(map, node, index - 1);
+            child
+        } else if (has_right && entry_count(map, child_at(map, node, index + 1)) >= MIN_DEGREE) {
+            rotate_left(map, node, index);
+            child
+        } else if (has_right) {
+            merge(map, node, index)
+        } else {
+            merge(map, node, index - 1)
+        }
+    }
+
+    /// Move the last entry of the child at `index` of `parent` up into `parent`, and the entry it
+    /// replaces down into the next child.
+    fun rotate_right<K, V>(map: &mut OrderedMap<K, V>, parent: u64, index: u64) {
+        let left = child_at(map, parent, index);
+        let right = child_at(map, parent, index + 1);
+        let entry = vector::pop_back(&mut vector::borrow_mut(&mut map.nodes, left).entries);
+        let entry = swap_entry(map, parent, index, entry);
+        vector::insert(&mut vector::borrow_mut(&mut map.nodes, right).entries, entry, 0);
+        if (!is_leaf(map, left)) {
+            let child = vector::pop_back(&mut vector::borrow_mut(&mut map.nodes, left).children);
+            vector::insert(&mut vector::borrow_mut(&mut map.nodes, right).children, child, 0);
+        }
+    }
+
+    /// Move the first entry of the child after `index` of `parent` up into `parent`, and the entry
+    /// it replaces down into the child at `index`.
+    fun rotate_left<K, V>(map: &mut OrderedMap<K, V>, parent: u64, index: u64) {
+        let left = child_at(map, parent, index);
+        let right = child_at(map, parent, index + 1);
+        let entry = vector::remove(&mut vector::borrow_mut(&mut map.nodes, right).entries, 0);
+        let entry = swap_entry(map, parent, index, entry);
+        vector::push_back(&mut vector::borrow_mut(&mut map.nodes, left).entries, entry);
+        if (!is_leaf(map, right)) {
+            let child = vector::remove(&mut vector::borrow_mut(&mut map.nodes, right).children, 0);
+            vector::push_back(&mut vector::borrow_mut(&mut map.nodes, left).children, child);
+        }
+    }
+
+    /// Merge the children around the entry at `index` of `parent`, and that entry, into the left
+    /// child. Returns the index of the merged node.
+    fun merge<K, V>(map: &mut OrderedMap<K, V>, parent: u64, index: u64): u64 {
+        let left = child_at(map, parent, index);
+        let node = vector::borrow_mut(&mut map.nodes, parent);
+        let entry = vector::remove(&mut node.entries, index);
+        let right = vector::remove(&mut node.children, index + 1);
+        let Node { entries, children } = take(map, right);
+        let node = vector::borrow_mut(&mut map.nodes, left);
+        vector::push_back(&mut node.entries, entry);
+        vector::append(&mut node.entries, entries);
+        vector::append(&mut node.children, children);
+        if (parent == map.root && entry_count(map, parent) == 0) {
+            // The root has no entries left, the merged node takes its place
+            let Node { entries, children } = take(map, parent);
+            vector::destroy_empty(entries);
+            vector::pop_back(&mut children);
+            vector::destroy_empty(children);
+            map.root = left;
+        };
+        left
+    }
+
+    /// Remove the entry with the largest key in the subtree of `node`, which has more than the
+    /// minimum number of entries.
+    fun remove_last<K, V>(map: &mut OrderedMap<K, V>, node: u64): Entry<K, V> {
+        while ({
+            spec {
+                invariant node < len(map.nodes);
+            };
+            (!is_leaf(map, node))
+        }) {
+            node = descend(map, node, entry_count(map, node));
+        };
+        vector::pop_back(&mut vector::borrow_mut(&mut map.nodes, node).entries)
+    }
+
+    /// Remove the entry with the smallest key in the subtree of `node`, which has more than the
+    /// minimum number of entries.
+    fun remove_first<K, V>(map: &mut OrderedMap<K, V>, node: u64): Entry<K, V> {
+        while ({
+            spec {
+                invariant node < len(map.nodes);
+            };
+            (!is_leaf(map, node))
+        }) {
+            node = descend(map, node, 0);
+        };
+        vector::remove(&mut vector::borrow_mut(&mut map.nodes, node).entries, 0)
+    }
+
+    /// Put `entry` at `index` of `node`, and return the entry it replaces.
+    fun swap_entry<K, V>(
+        map: &mut OrderedMap<K, V>,
+        node: u64,
+        index: u64,
+        entry: Entry<K, V>,
+    ): Entry<K, V> {
+        let entries = &mut vector::borrow_mut(&mut map.nodes, node).entries;
+        vector::push_back(entries, entry);
+        vector::swap_remove(entries, index)
+    }
+    spec swap_entry {
+        pragma opaque;
+        aborts_if node >= len(map.nodes);
+        aborts_if index >= len(map.nodes[node].entries);
+        ensures result == old(map.nodes[node].entries[index]);
+        ensures map.nodes[node].entries == update(old(map.nodes[node].entries), index, entry);
+        ensures map.nodes[node].children == old(map.nodes[node].children);
+        ensures len(map.nodes) == len(old(map.nodes));
+        ensures forall n in 0..len(map.nodes) where n != node: map.nodes[n] == old(map.nodes[n]);
+        ensures map.root == old(map.root);
+        ensures map.free == old(map.free);
+        ensures map.size == old(map.size);
+    }
+
+    /// Put `entry` at `index` of `node`, and return the key and value of the entry it replaces.
+    fun replace_entry<K, V>(
+        map: &mut OrderedMap<K, V>,
+        node: u64,
+        index: u64,
+        entry: Entry<K, V>,
+    ): (K, V) {
+        let Entry { key, value } = swap_entry(map, node, index, entry);
+        (key, value)
+    }
+
+    fun append_keys<K: copy, V>(map: &OrderedMap<K, V>, node: u64, keys: &mut vector<K>) {
+        let n = vector::borrow(&map.nodes, node);
+        let leaf = vector::is_empty(&n.children);
+        let i = 0;
+        let len = vector::length(&n.entries);
+        while (i < len) {
+            if (!leaf) append_keys(map, *vector::borrow(&n.children, i), keys);
+            vector::push_back(keys, vector::borrow(&n.entries, i).key);
+            i = i + 1;
+        };
+        if (!leaf) append_keys(map, *vector::borrow(&n.children, len), keys);
+    }
+    spec append_keys {
+        pragma opaque;
+        aborts_if false;
+        ensures len(keys) >= len(old(keys));
+        ensures forall i in 0..len(old(keys)): keys[i] == old(keys)[i];
+        ensures forall i in len(old(keys))..len(keys): spec_contains_key(map, keys[i]);
+    }
+
+    fun append_values<K, V: copy>(map: &OrderedMap<K, V>, node: u64, values: &mut vector<V>) {
+        let n = vector::borrow(&map.nodes, node);
+        let leaf = vector::is_empty(&n.children);
+        let i = 0;
+        let len = vector::length(&n.entries);
+        while (i < len) {
+            if (!leaf) append_values(map, *vector::borrow(&n.children, i), values);
+            vector::push_back(values, vector::borrow(&n.entries, i).value);
+            i = i + 1;
+        };
+        if (!leaf) append_values(map, *vector::borrow(&n.children, len), values);
+    }
+    spec append_values {
+        pragma opaque;
+        aborts_if false;
+        ensures len(values) >= len(old(values));
+        ensures forall i in 0..len(old(values)): values[i] == old(values)[i];
+    }
+
+    spec fun spec_len<K, V>(map: OrderedMap<K, V>): num {
+        map.size
+    }
+
+    spec fun spec_contains_key<K, V>(map: OrderedMap<K, V>, key: K): bool {
+        exists n in 0..len(map.nodes): spec_node_contains(map.nodes[n], key)
+    }
+
+    /// The value of the entry for `key`, which must be in `map`
+    spec fun spec_get<K, V>(map: OrderedMap<K, V>, key: K): V {
+        let n = choose n in 0..len(map.nodes) where spec_node_contains(map.nodes[n], key);
+        let entries = map.nodes[n].entries;
+        entries[choose i in 0..len(entries) where entries[i].key == key].value
+    }
+
+    spec fun spec_node_contains<K, V>(node: Node<K, V>, key: K): bool {
+        exists i in 0..len(node.entries): node.entries[i].key == key
+    }
+
+    /// The order of `key` relative to the BCS encoded `bytes`
+    spec fun spec_cmp<K>(key: K, bytes: vector<u8>): u8 {
+        compare::spec_cmp_bcs_bytes(bcs::serialize(key), bytes)
+    }
+
+    /// Whether `k1` comes before `k2` in the order of the map
+    spec fun spec_less<K>(k1: K, k2: K): bool {
+        spec_cmp(k1, bcs::serialize(k2)) == LESS_THAN
+    }
+}
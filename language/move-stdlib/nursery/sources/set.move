@@ -0,0 +1,131 @@
+/// A set of elements ordered like the keys of an `ordered_map`.
+module std::set {
+    use std::ordered_map::{Self, OrderedMap};
+
+    /// The element is already in the set
+    const EELEMENT_ALREADY_EXISTS: u64 = 0x80001;
+    /// The element is not in the set
+    const EELEMENT_NOT_FOUND: u64 = 0x60002;
+
+    /// A set of elements of type `T`.
+    struct Set<T> has copy, drop, store {
+        map: OrderedMap<T, bool>,
+    }
+
+    /// Create an empty set.
+    public fun empty<T>(): Set<T> {
+        Set { map: ordered_map::new() }
+    }
+    spec empty {
+        aborts_if false;
+        ensures spec_len(result) == 0;
+        ensures forall e: T: !spec_contains(result, e);
+    }
+
+    /// Create a set containing only `e`.
+    public fun singleton<T>(e: T): Set<T> {
+        let set = empty();
+        ordered_map::add(&mut set.map, e, true);
+        set
+    }
+    spec singleton {
+        aborts_if false;
+        ensures spec_len(result) == 1;
+        ensures spec_contains(result, e);
+    }
+
+    /// Return the number of elements in `set`.
+    public fun length<T>(set: &Set<T>): u64 {
+        ordered_map::length(&set.map)
+    }
+    spec length {
+        aborts_if false;
+        ensures result == spec_len(set);
+    }
+
+    /// Return true if `set` has no elements.
+    public fun is_empty<T>(set: &Set<T>): bool {
+        ordered_map::is_empty(&set.map)
+    }
+    spec is_empty {
+        aborts_if false;
+        ensures result == (spec_len(set) == 0);
+    }
+
+    /// Return true if `e` is in `set`.
+    public fun contains<T>(set: &Set<T>, e: &T): bool {
+        ordered_map::contains_key(&set.map, e)
+    }
+    spec contains {
+        aborts_if false;
+        ensures result == spec_contains(set, e);
+    }
+
+    /// Add `e` to `set`.
+    /// Aborts if `e` already is in `set`.
+    public fun add<T>(set: &mut Set<T>, e: T) {
+        assert!(!ordered_map::contains_key(&set.map, &e), EELEMENT_ALREADY_EXISTS);
+        ordered_map::add(&mut set.map, e, true);
+    }
+    spec add {
+        aborts_if spec_contains(set, e) with EELEMENT_ALREADY_EXISTS;
+        ensures spec_len(set) == spec_len(old(set)) + 1;
+        ensures spec_contains(set, e);
+        ensures forall x: T where x != e: spec_contains(set, x) == spec_contains(old(set), x);
+    }
+
+    /// Add `e` to `set` if it is not in `set` yet. Returns true if `e` was added.
+    public fun insert<T: drop>(set: &mut Set<T>, e: T): bool {
+        if (ordered_map::contains_key(&set.map, &e)) return false;
+        ordered_map::add(&mut set.map, e, true);
+        true
+    }
+    spec insert {
+        aborts_if false;
+        ensures result == !spec_contains(old(set), e);
+        ensures spec_contains(set, e);
+        ensures forall x: T where x != e: spec_contains(set, x) == spec_contains(old(set), x);
+    }
+
+    /// Remove `e` from `set` and return it.
+    /// Aborts if `e` is not in `set`.
+    public fun remove<T>(set: &mut Set<T>, e: &T): T {
+        assert!(ordered_map::contains_key(&set.map, e), EELEMENT_NOT_FOUND);
+        let (e, _) = ordered_map::remove(&mut set.map, e);
+        e
+    }
+    spec remove {
+        aborts_if !spec_contains(set, e) with EELEMENT_NOT_FOUND;
+        ensures spec_len(set) == spec_len(old(set)) - 1;
+        ensures !spec_contains(set, e);
+        ensures forall x: T where x != e: spec_contains(set, x) == spec_contains(old(set), x);
+    }
+
+    /// Return the elements of `set` in ascending order.
+    public fun elements<T: copy>(set: &Set<T>): vector<T> {
+        ordered_map::keys(&set.map)
+    }
+    spec elements {
+        aborts_if false;
+        ensures len(result) == spec_len(set);
+        ensures forall e: T: spec_contains(set, e) <==> contains(result, e);
+    }
+
+    /// Destroy an empty set.
+    /// Aborts if `set` has elements.
+    public fun destroy_empty<T>(set: Set<T>) {
+        let Set { map } = set;
+        ordered_map::destroy_empty(map);
+    }
+    spec destroy_empty {
+        aborts_if spec_len(set) != 0;
+    }
+
+    spec fun spec_len<T>(set: Set<T>): num {
+        ordered_map::spec_len(set.map)
+    }
+
+    spec fun spec_contains<T>(set: Set<T>, e: T): bool {
+        ordered_map::spec_contains_key(set.map, e)
+    }
+}
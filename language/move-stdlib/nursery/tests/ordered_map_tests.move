@@ -0,0 +1,159 @@
+#[test_only]
+module std::ordered_map_tests {
+    use std::option;
+    use std::ordered_map::{Self, OrderedMap};
+    use std::vector;
+
+    // Keys are added and removed in the order `i * step % SIZE`, which visits all of `0..SIZE`
+    // because `SIZE` is prime. This is enough entries for a tree with several levels.
+    const SIZE: u64 = 31;
+
+    fun add_all(step: u64): OrderedMap<u64, u64> {
+        let map = ordered_map::new();
+        let i = 0;
+        while (i < SIZE) {
+            let key = i * step % SIZE;
+            ordered_map::add(&mut map, key, key * 10);
+            i = i + 1;
+        };
+        map
+    }
+
+    #[test]
+    fun add_and_borrow() {
+        let map = add_all(7);
+        assert!(ordered_map::length(&map) == SIZE, 0);
+        let key = 0;
+        while (key < SIZE) {
+            assert!(*ordered_map::borrow(&map, &key) == key * 10, 1);
+            key = key + 1;
+        };
+        assert!(!ordered_map::contains_key(&map, &SIZE), 2);
+    }
+
+    #[test]
+    fun keys_are_ordered() {
+        let map = add_all(12);
+        let keys = ordered_map::keys(&map);
+        let values = ordered_map::values(&map);
+        assert!(vector::length(&keys) == SIZE, 0);
+        let i = 0;
+        while (i < SIZE) {
+            assert!(*vector::borrow(&keys, i) == i, 1);
+            assert!(*vector::borrow(&values, i) == i * 10, 2);
+            i = i + 1;
+        }
+    }
+
+    #[test]
+    fun remove_all() {
+        let map = add_all(7);
+        let i = 0;
+        while (i < SIZE) {
+            let key = i * 18 % SIZE;
+            let (removed_key, value) = ordered_map::remove(&mut map, &key);
+            assert!(removed_key == key && value == key * 10, 0);
+            assert!(!ordered_map::contains_key(&map, &key), 1);
+            i = i + 1;
+        };
+        assert!(ordered_map::is_empty(&map), 2);
+        ordered_map::destroy_empty(map);
+    }
+
+    #[test]
+    fun remove_then_add() {
+        let map = add_all(3);
+        let key = 0;
+        while (key < SIZE) {
+            ordered_map::remove(&mut map, &key);
+            key = key + 2;
+        };
+        assert!(ordered_map::length(&map) == SIZE / 2, 0);
+        let key = 1;
+        while (key < SIZE) {
+            assert!(*ordered_map::borrow(&map, &key) == key * 10, 1);
+            key = key + 2;
+        };
+        ordered_map::add(&mut map, 4, 40);
+        let expected = vector[1, 3, 4, 5, 7, 9, 11, 13, 15, 17, 19, 21, 23, 25, 27, 29];
+        assert!(ordered_map::keys(&map) == expected, 2);
+    }
+
+    #[test]
+    fun borrow_mut_and_upsert() {
+        let map = ordered_map::new<u64, u64>();
+        ordered_map::add(&mut map, 1, 10);
+        *ordered_map::borrow_mut(&mut map, &1) = 11;
+        assert!(*ordered_map::borrow(&map, &1) == 11, 0);
+        ordered_map::upsert(&mut map, 1, 12);
+        ordered_map::upsert(&mut map, 2, 20);
+        assert!(ordered_map::length(&map) == 2, 1);
+        assert!(*ordered_map::borrow(&map, &1) == 12, 2);
+        assert!(*ordered_map::borrow(&map, &2) == 20, 3);
+    }
+
+    #[test]
+    fun min_max_next_prev() {
+        let map = ordered_map::new<u64, u64>();
+        assert!(option::is_none(&ordered_map::min_key(&map)), 0);
+        assert!(option::is_none(&ordered_map::max_key(&map)), 1);
+        assert!(option::is_none(&ordered_map::next_key(&map, &0)), 2);
+
+        let key = 2;
+        while (key <= 40) {
+            ordered_map::add(&mut map, key, 0);
+            key = key + 2;
+        };
+        assert!(ordered_map::min_key(&map) == option::some(2), 3);
+        assert!(ordered_map::max_key(&map) == option::some(40), 4);
+        assert!(ordered_map::next_key(&map, &0) == option::some(2), 5);
+        assert!(ordered_map::next_key(&map, &10) == option::some(12), 6);
+        assert!(ordered_map::next_key(&map, &11) == option::some(12), 7);
+        assert!(option::is_none(&ordered_map::next_key(&map, &40)), 8);
+        assert!(ordered_map::prev_key(&map, &10) == option::some(8), 9);
+        assert!(ordered_map::prev_key(&map, &11) == option::some(10), 10);
+        assert!(ordered_map::prev_key(&map, &100) == option::some(40), 11);
+        assert!(option::is_none(&ordered_map::prev_key(&map, &2)), 12);
+    }
+
+    #[test]
+    fun vector_keys() {
+        let map = ordered_map::new<vector<u8>, u64>();
+        ordered_map::add(&mut map, b"b", 2);
+        ordered_map::add(&mut map, b"a", 1);
+        ordered_map::add(&mut map, b"ab", 3);
+        assert!(*ordered_map::borrow(&map, &b"a") == 1, 0);
+        assert!(*ordered_map::borrow(&map, &b"ab") == 3, 1);
+        assert!(*ordered_map::borrow(&map, &b"b") == 2, 2);
+        assert!(!ordered_map::contains_key(&map, &b"c"), 3);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = ordered_map::EKEY_ALREADY_EXISTS)]
+    fun add_twice() {
+        let map = add_all(7);
+        ordered_map::add(&mut map, 5, 0);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = ordered_map::EKEY_NOT_FOUND)]
+    fun borrow_missing() {
+        let map = ordered_map::new<u64, u64>();
+        ordered_map::borrow(&map, &1);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = ordered_map::EKEY_NOT_FOUND)]
+    fun remove_missing() {
+        let map = add_all(7);
+        ordered_map::remove(&mut map, &SIZE);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = ordered_map::ENOT_EMPTY)]
+    fun destroy_non_empty() {
+        let map = ordered_map::new<u64, u64>();
+        ordered_map::add(&mut map, 1, 10);
+        ordered_map::destroy_empty(map);
+    }
+}
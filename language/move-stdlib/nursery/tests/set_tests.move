@@ -0,0 +1,56 @@
+#[test_only]
+module std::set_tests {
+    use std::set;
+
+    #[test]
+    fun add_and_contains() {
+        let s = set::empty<u64>();
+        assert!(set::is_empty(&s), 0);
+        set::add(&mut s, 3);
+        set::add(&mut s, 1);
+        set::add(&mut s, 2);
+        assert!(set::length(&s) == 3, 1);
+        assert!(set::contains(&s, &1), 2);
+        assert!(!set::contains(&s, &4), 3);
+        assert!(set::elements(&s) == vector[1, 2, 3], 4);
+    }
+
+    #[test]
+    fun singleton() {
+        let s = set::singleton(5);
+        assert!(set::length(&s) == 1, 0);
+        assert!(set::contains(&s, &5), 1);
+    }
+
+    #[test]
+    fun insert() {
+        let s = set::empty<u64>();
+        assert!(set::insert(&mut s, 1), 0);
+        assert!(!set::insert(&mut s, 1), 1);
+        assert!(set::length(&s) == 1, 2);
+    }
+
+    #[test]
+    fun remove_and_destroy() {
+        let s = set::singleton(5);
+        set::add(&mut s, 6);
+        assert!(set::remove(&mut s, &5) == 5, 0);
+        assert!(!set::contains(&s, &5), 1);
+        set::remove(&mut s, &6);
+        set::destroy_empty(s);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = set::EELEMENT_ALREADY_EXISTS)]
+    fun add_twice() {
+        let s = set::singleton(5);
+        set::add(&mut s, 5);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = set::EELEMENT_NOT_FOUND)]
+    fun remove_missing() {
+        let s = set::singleton(5);
+        set::remove(&mut s, &6);
+    }
+}
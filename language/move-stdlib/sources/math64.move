@@ -0,0 +1,106 @@
+/// Standard math utilities for `u64`: exponentiation, square roots, logarithms, multiplication
+/// and division with a wider intermediate product, and checked and saturating arithmetic.
+module std::math64 {
+    use std::option::{Self, Option};
+
+    /// The result would be too large to be held in a `u64`
+    const EOVERFLOW: u64 = 0x20001;
+    /// A division by zero was encountered
+    const EDIVISION_BY_ZERO: u64 = 0x10002;
+    /// The logarithm of zero is undefined
+    const ELOG2_OF_ZERO: u64 = 0x10003;
+
+    const MAX_U64: u64 = 18446744073709551615;
+
+    /// Returns the smaller of `a` and `b`.
+    public fun min(a: u64, b: u64): u64 {
+        if (a < b) a else b
+    }
+
+    /// Returns the larger of `a` and `b`.
+    public fun max(a: u64, b: u64): u64 {
+        if (a > b) a else b
+    }
+
+    /// Returns `base` raised to the power of `exponent`. Aborts if the result
+    /// overflows.
+    native public fun pow(base: u64, exponent: u8): u64;
+    spec pow {
+        pragma opaque;
+        aborts_if spec_pow(base, exponent) > MAX_U64 with EOVERFLOW;
+        ensures result == spec_pow(base, exponent);
+    }
+
+    /// Returns the square root of `x`, rounded down.
+    native public fun sqrt(x: u64): u64;
+    spec sqrt {
+        pragma opaque;
+        aborts_if false;
+        ensures result * result <= x;
+        ensures x < (result + 1) * (result + 1);
+    }
+
+    /// Returns the base 2 logarithm of `x`, rounded down. Aborts if `x` is zero.
+    native public fun log2(x: u64): u8;
+    spec log2 {
+        pragma opaque;
+        aborts_if x == 0 with ELOG2_OF_ZERO;
+        ensures spec_pow(2, result) <= x;
+        ensures x < spec_pow(2, result + 1);
+    }
+
+    /// Returns `a * b / c`, rounded down. The product is computed with 128 bits
+    /// so that it does not overflow. Aborts if `c` is zero or if the result
+    /// overflows.
+    native public fun mul_div(a: u64, b: u64, c: u64): u64;
+    spec mul_div {
+        pragma opaque;
+        aborts_if c == 0 with EDIVISION_BY_ZERO;
+        aborts_if c != 0 && a * b / c > MAX_U64 with EOVERFLOW;
+        ensures result == a * b / c;
+    }
+
+    // Like `pow`, this is `1` for a zero `exponent`, even for a zero `base`
+    spec fun spec_pow(base: num, exponent: num): num {
+        if (exponent == 0) {
+            1
+        } else {
+            base * spec_pow(base, exponent - 1)
+        }
+    }
+
+    /// Returns `a + b`, or `none` if the sum overflows.
+    public fun checked_add(a: u64, b: u64): Option<u64> {
+        if (MAX_U64 - a < b) option::none() else option::some(a + b)
+    }
+
+    /// Returns `a - b`, or `none` if `b` is larger than `a`.
+    public fun checked_sub(a: u64, b: u64): Option<u64> {
+        if (a < b) option::none() else option::some(a - b)
+    }
+
+    /// Returns `a * b`, or `none` if the product overflows.
+    public fun checked_mul(a: u64, b: u64): Option<u64> {
+        if (a != 0 && b > MAX_U64 / a) option::none() else option::some(a * b)
+    }
+
+    /// Returns `a / b`, or `none` if `b` is zero.
+    public fun checked_div(a: u64, b: u64): Option<u64> {
+        if (b == 0) option::none() else option::some(a / b)
+    }
+
+    /// Returns `a + b`, or the largest `u64` if the sum overflows.
+    public fun saturating_add(a: u64, b: u64): u64 {
+        if (MAX_U64 - a < b) MAX_U64 else a + b
+    }
+
+    /// Returns `a - b`, or zero if `b` is larger than `a`.
+    public fun saturating_sub(a: u64, b: u64): u64 {
+        if (a < b) 0 else a - b
+    }
+
+    /// Returns `a * b`, or the largest `u64` if the product overflows.
+    public fun saturating_mul(a: u64, b: u64): u64 {
+        if (a != 0 && b > MAX_U64 / a) MAX_U64 else a * b
+    }
+}
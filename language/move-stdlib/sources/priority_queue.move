@@ -0,0 +1,227 @@
+/// A priority queue of values with `u64` priorities, implemented as a binary max-heap.
+/// Values with the same priority are popped in no particular order.
+module std::priority_queue {
+    use std::vector;
+
+    /// The queue is empty
+    const EEMPTY: u64 = 0x30001;
+    /// The queue is not empty
+    const ENOT_EMPTY: u64 = 0x30002;
+
+    /// A queue of values of type `V`.
+    struct PriorityQueue<V> has copy, drop, store {
+        /// The heap: the priority of an entry is not smaller than the priorities of the entries
+        /// at `2 * i + 1` and `2 * i + 2`
+        entries: vector<Entry<V>>,
+    }
+    spec PriorityQueue {
+        invariant spec_is_heap(entries);
+    }
+
+    /// An entry of a `PriorityQueue`.
+    struct Entry<V> has copy, drop, store {
+        priority: u64,
+        value: V,
+    }
+
+    /// Create an empty queue.
+    public fun new<V>(): PriorityQueue<V> {
+        PriorityQueue { entries: vector::empty() }
+    }
+    spec new {
+        aborts_if false;
+        ensures len(result.entries) == 0;
+    }
+
+    /// Return the number of values in `queue`.
+    public fun length<V>(queue: &PriorityQueue<V>): u64 {
+        vector::length(&queue.entries)
+    }
+    spec length {
+        aborts_if false;
+        ensures result == len(queue.entries);
+    }
+
+    /// Return true if `queue` has no values.
+    public fun is_empty<V>(queue: &PriorityQueue<V>): bool {
+        vector::is_empty(&queue.entries)
+    }
+    spec is_empty {
+        aborts_if false;
+        ensures result == (len(queue.entries) == 0);
+    }
+
+    /// Add `value` with `priority` to `queue`.
+    public fun insert<V>(queue: &mut PriorityQueue<V>, priority: u64, value: V) {
+        vector::push_back(&mut queue.entries, Entry { priority, value });
+        let last = vector::length(&queue.entries) - 1;
+        sift_up(&mut queue.entries, last);
+    }
+    spec insert {
+        aborts_if false;
+        ensures len(queue.entries) == len(old(queue).entries) + 1;
+        ensures spec_is_permutation(
+            concat(old(queue).entries, vec(Entry { priority, value })),
+            queue.entries,
+        );
+    }
+
+    /// Return the highest priority in `queue`.
+    /// Aborts if `queue` is empty.
+    public fun max_priority<V>(queue: &PriorityQueue<V>): u64 {
+        assert!(!vector::is_empty(&queue.entries), EEMPTY);
+        vector::borrow(&queue.entries, 0).priority
+    }
+    spec max_priority {
+        aborts_if len(queue.entries) == 0 with EEMPTY;
+        ensures result == queue.entries[0].priority;
+        ensures forall i in 0..len(queue.entries): queue.entries[i].priority <= result;
+    }
+
+    /// Borrow a value with the highest priority in `queue`.
+    /// Aborts if `queue` is empty.
+    public fun borrow_max<V>(queue: &PriorityQueue<V>): &V {
+        assert!(!vector::is_empty(&queue.entries), EEMPTY);
+        &vector::borrow(&queue.entries, 0).value
+    }
+    spec borrow_max {
+        aborts_if len(queue.entries) == 0 with EEMPTY;
+        ensures result == queue.entries[0].value;
+        ensures forall i in 0..len(queue.entries):
+            queue.entries[i].priority <= queue.entries[0].priority;
+    }
+
+    /// Remove a value with the highest priority from `queue`, and return its priority and value.
+    /// Aborts if `queue` is empty.
+    public fun pop_max<V>(queue: &mut PriorityQueue<V>): (u64, V) {
+        assert!(!vector::is_empty(&queue.entries), EEMPTY);
+        let Entry { priority, value } = vector::swap_remove(&mut queue.entries, 0);
+        sift_down(&mut queue.entries, 0);
+        (priority, value)
+    }
+    spec pop_max {
+        aborts_if len(queue.entries) == 0 with EEMPTY;
+        ensures len(queue.entries) == len(old(queue).entries) - 1;
+        ensures result_1 == old(queue).entries[0].priority;
+        ensures result_2 == old(queue).entries[0].value;
+        ensures forall i in 0..len(old(queue).entries): old(queue).entries[i].priority <= result_1;
+        ensures spec_is_permutation(
+            old(queue).entries,
+            concat(vec(Entry { priority: result_1, value: result_2 }), queue.entries),
+        );
+    }
+
+    /// Destroy an empty queue.
+    /// Aborts if `queue` has values.
+    public fun destroy_empty<V>(queue: PriorityQueue<V>) {
+        let PriorityQueue { entries } = queue;
+        assert!(vector::is_empty(&entries), ENOT_EMPTY);
+        vector::destroy_empty(entries);
+    }
+    spec destroy_empty {
+        aborts_if len(queue.entries) != 0 with ENOT_EMPTY;
+    }
+
+    /// Move the entry at `i` up until its parent has a priority which is not smaller.
+    fun sift_up<V>(entries: &mut vector<Entry<V>>, i: u64) {
+        let size = vector::length(entries);
+        while ({
+            spec {
+                invariant len(entries) == size;
+                invariant i < size;
+                invariant spec_is_heap_except_up(entries, i);
+                invariant spec_is_permutation(old(entries), entries);
+            };
+            i > 0
+        }) {
+            let parent = (i - 1) / 2;
+            if (vector::borrow(entries, parent).priority >= vector::borrow(entries, i).priority) {
+                break
+            };
+            vector::swap(entries, i, parent);
+            i = parent;
+        }
+    }
+    spec sift_up {
+        requires i < len(entries);
+        requires spec_is_heap_except_up(entries, i);
+        aborts_if false;
+        ensures spec_is_heap(entries);
+        ensures spec_is_permutation(old(entries), entries);
+    }
+
+    /// Move the entry at `i` down until its children have priorities which are not larger.
+    fun sift_down<V>(entries: &mut vector<Entry<V>>, i: u64) {
+        let size = vector::length(entries);
+        // An entry has children if and only if it is in the first half
+        while ({
+            spec {
+                invariant len(entries) == size;
+                invariant size == 0 || i < size;
+                invariant spec_is_heap_except_down(entries, i);
+                invariant spec_is_permutation(old(entries), entries);
+            };
+            i < size / 2
+        }) {
+            let largest = i;
+            let left = 2 * i + 1;
+            let right = left + 1;
+            if (priority_at(entries, left) > priority_at(entries, largest)) {
+                largest = left;
+            };
+            if (right < size && priority_at(entries, right) > priority_at(entries, largest)) {
+                largest = right;
+            };
+            if (largest == i) break;
+            vector::swap(entries, i, largest);
+            i = largest;
+        }
+    }
+    spec sift_down {
+        requires len(entries) == 0 || i < len(entries);
+        requires spec_is_heap_except_down(entries, i);
+        aborts_if false;
+        ensures spec_is_heap(entries);
+        ensures spec_is_permutation(old(entries), entries);
+    }
+
+    fun priority_at<V>(entries: &vector<Entry<V>>, i: u64): u64 {
+        vector::borrow(entries, i).priority
+    }
+
+    /// Whether no entry has a higher priority than its parent, so that the first entry has the
+    /// highest priority
+    spec fun spec_is_heap<V>(entries: vector<Entry<V>>): bool {
+        (forall j in 1..len(entries): entries[(j - 1) / 2].priority >= entries[j].priority)
+            && (forall j in 0..len(entries): entries[j].priority <= entries[0].priority)
+    }
+
+    /// Whether `entries` is a heap, except that the entry at `i` may have a higher priority than
+    /// its ancestors. Its parent still has a priority which is not smaller than its children, and
+    /// no entry other than the one at `i` has a higher priority than the first one.
+    spec fun spec_is_heap_except_up<V>(entries: vector<Entry<V>>, i: num): bool {
+        (forall j in 1..len(entries) where j != i:
+            entries[(j - 1) / 2].priority >= entries[j].priority)
+        && (i > 0 ==> (forall j in 1..len(entries) where (j - 1) / 2 == i:
+            entries[(i - 1) / 2].priority >= entries[j].priority))
+        && (forall j in 0..len(entries) where j != i: entries[j].priority <= entries[0].priority)
+    }
+
+    /// Whether `entries` is a heap, except that the entry at `i` may have a lower priority than
+    /// its children. Its parent still has a priority which is not smaller than its children.
+    spec fun spec_is_heap_except_down<V>(entries: vector<Entry<V>>, i: num): bool {
+        (forall j in 1..len(entries) where (j - 1) / 2 != i:
+            entries[(j - 1) / 2].priority >= entries[j].priority)
+        && (i > 0 ==> (forall j in 1..len(entries) where (j - 1) / 2 == i:
+            entries[(i - 1) / 2].priority >= entries[j].priority))
+    }
+
+    /// Whether `v2` holds the entries of `v1` in some order
+    spec fun spec_is_permutation<V>(v1: vector<Entry<V>>, v2: vector<Entry<V>>): bool {
+        len(v1) == len(v2) && (exists p: vector<u64>:
+            len(p) == len(v1)
+                && (forall i in 0..len(p): p[i] < len(v1))
+                && (forall i in 0..len(p), j in 0..len(p) where i != j: p[i] != p[j])
+                && (forall i in 0..len(p): v2[i] == v1[p[i]]))
+    }
+}
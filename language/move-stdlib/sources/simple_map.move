@@ -0,0 +1,253 @@
+/// A map backed by an unsorted vector of entries. Lookups are linear in the number of entries,
+/// so this is meant for small maps, or for keys without a meaningful order. See `ordered_map` in
+/// the nursery for a map ordered by key, with logarithmic lookups.
+module std::simple_map {
+    use std::option::{Self, Option};
+    use std::vector;
+
+    /// The key is already in the map
+    const EKEY_ALREADY_EXISTS: u64 = 0x6407;
+    /// The key is not in the map
+    const EKEY_NOT_FOUND: u64 = 0x6507;
+    /// The map is not empty
+    const ENOT_EMPTY: u64 = 0x6601;
+
+    /// A map from keys of type `K` to values of type `V`. Keys are compared with `==`.
+    struct SimpleMap<K, V> has copy, drop, store {
+        data: vector<Element<K, V>>,
+    }
+    spec SimpleMap {
+        // The keys of the entries are distinct
+        invariant forall i in 0..len(data), j in 0..len(data) where i != j:
+            data[i].key != data[j].key;
+    }
+
+    /// An entry of a `SimpleMap`.
+    struct Element<K, V> has copy, drop, store {
+        key: K,
+        value: V,
+    }
+
+    /// Create an empty map.
+    public fun create<K, V>(): SimpleMap<K, V> {
+        SimpleMap { data: vector::empty() }
+    }
+    spec create {
+        aborts_if false;
+        ensures spec_len(result) == 0;
+        ensures forall k: K: !spec_contains_key(result, k);
+    }
+
+    /// Return the number of entries in `map`.
+    public fun length<K, V>(map: &SimpleMap<K, V>): u64 {
+        vector::length(&map.data)
+    }
+    spec length {
+        aborts_if false;
+        ensures result == spec_len(map);
+    }
+
+    /// Return true if `map` has no entries.
+    public fun is_empty<K, V>(map: &SimpleMap<K, V>): bool {
+        vector::is_empty(&map.data)
+    }
+    spec is_empty {
+        aborts_if false;
+        ensures result == (spec_len(map) == 0);
+    }
+
+    /// Return true if `map` has an entry for `key`.
+    public fun contains_key<K, V>(map: &SimpleMap<K, V>, key: &K): bool {
+        option::is_some(&find(map, key))
+    }
+    spec contains_key {
+        aborts_if false;
+        ensures result == spec_contains_key(map, key);
+    }
+
+    /// Borrow the value associated with `key`.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow<K, V>(map: &SimpleMap<K, V>, key: &K): &V {
+        let index = find(map, key);
+        assert!(option::is_some(&index), EKEY_NOT_FOUND);
+        &vector::borrow(&map.data, option::destroy_some(index)).value
+    }
+    spec borrow {
+        aborts_if !spec_contains_key(map, key) with EKEY_NOT_FOUND;
+        ensures result == spec_get(map, key);
+    }
+
+    /// Mutably borrow the value associated with `key`.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow_mut<K, V>(map: &mut SimpleMap<K, V>, key: &K): &mut V {
+        let index = find(map, key);
+        assert!(option::is_some(&index), EKEY_NOT_FOUND);
+        &mut vector::borrow_mut(&mut map.data, option::destroy_some(index)).value
+    }
+    spec borrow_mut {
+        aborts_if !spec_contains_key(map, key) with EKEY_NOT_FOUND;
+    }
+
+    /// Add an entry for `key` with `value`.
+    /// Aborts if there already is an entry for `key`.
+    public fun add<K, V>(map: &mut SimpleMap<K, V>, key: K, value: V) {
+        assert!(option::is_none(&find(map, &key)), EKEY_ALREADY_EXISTS);
+        vector::push_back(&mut map.data, Element { key, value });
+    }
+    spec add {
+        aborts_if spec_contains_key(map, key) with EKEY_ALREADY_EXISTS;
+        ensures spec_len(map) == spec_len(old(map)) + 1;
+        ensures spec_contains_key(map, key);
+        ensures spec_get(map, key) == value;
+        ensures forall k: K where k != key:
+            spec_contains_key(map, k) == spec_contains_key(old(map), k);
+        ensures forall k: K where k != key && spec_contains_key(old(map), k):
+            spec_get(map, k) == spec_get(old(map), k);
+    }
+
+    /// Associate `key` with `value`, replacing the value of an existing entry for `key`.
+    public fun upsert<K: drop, V: drop>(map: &mut SimpleMap<K, V>, key: K, value: V) {
+        let index = find(map, &key);
+        if (option::is_some(&index)) {
+            vector::borrow_mut(&mut map.data, option::destroy_some(index)).value = value;
+        } else {
+            vector::push_back(&mut map.data, Element { key, value });
+        }
+    }
+    spec upsert {
+        aborts_if false;
+        ensures spec_contains_key(old(map), key) ==> spec_len(map) == spec_len(old(map));
+        ensures !spec_contains_key(old(map), key) ==> spec_len(map) == spec_len(old(map)) + 1;
+        ensures spec_contains_key(map, key);
+        ensures spec_get(map, key) == value;
+        ensures forall k: K where k != key:
+            spec_contains_key(map, k) == spec_contains_key(old(map), k);
+        ensures forall k: K where k != key && spec_contains_key(old(map), k):
+            spec_get(map, k) == spec_get(old(map), k);
+    }
+
+    /// Remove the entry for `key` and return its key and value.
+    /// Aborts if there is no entry for `key`.
+    public fun remove<K, V>(map: &mut SimpleMap<K, V>, key: &K): (K, V) {
+        let index = find(map, key);
+        assert!(option::is_some(&index), EKEY_NOT_FOUND);
+        let index = option::destroy_some(index);
+        let Element { key, value } = vector::swap_remove(&mut map.data, index);
+        (key, value)
+    }
+    spec remove {
+        aborts_if !spec_contains_key(map, key) with EKEY_NOT_FOUND;
+        ensures result_1 == key;
+        ensures result_2 == spec_get(old(map), key);
+        ensures spec_len(map) == spec_len(old(map)) - 1;
+        ensures !spec_contains_key(map, key);
+        ensures forall k: K where k != key:
+            spec_contains_key(map, k) == spec_contains_key(old(map), k);
+        ensures forall k: K where k != key && spec_contains_key(old(map), k):
+            spec_get(map, k) == spec_get(old(map), k);
+    }
+
+    /// Destroy an empty map.
+    /// Aborts if `map` has entries.
+    public fun destroy_empty<K, V>(map: SimpleMap<K, V>) {
+        let SimpleMap { data } = map;
+        assert!(vector::is_empty(&data), ENOT_EMPTY);
+        vector::destroy_empty(data);
+    }
+    spec destroy_empty {
+        aborts_if spec_len(map) != 0 with ENOT_EMPTY;
+    }
+
+    /// Return the keys of `map`, in no particular order.
+    public fun keys<K: copy, V>(map: &SimpleMap<K, V>): vector<K> {
+        let keys = vector::empty();
+        let i = 0;
+        let len = vector::length(&map.data);
+        while ({
+            spec {
+                invariant i <= len;
+                invariant len == len(map.data);
+                invariant len(keys) == i;
+                invariant forall j in 0..i: keys[j] == map.data[j].key;
+            };
+            (i < len)
+        }) {
+            vector::push_back(&mut keys, vector::borrow(&map.data, i).key);
+            i = i + 1;
+        };
+        keys
+    }
+    spec keys {
+        pragma opaque;
+        aborts_if false;
+        ensures len(result) == spec_len(map);
+        ensures forall j in 0..len(result): result[j] == map.data[j].key;
+        ensures forall k: K: spec_contains_key(map, k) <==> contains(result, k);
+    }
+
+    /// Return the values of `map`, in the order of `keys`.
+    public fun values<K, V: copy>(map: &SimpleMap<K, V>): vector<V> {
+        let values = vector::empty();
+        let i = 0;
+        let len = vector::length(&map.data);
+        while ({
+            spec {
+                invariant i <= len;
+                invariant len == len(map.data);
+                invariant len(values) == i;
+                invariant forall j in 0..i: values[j] == map.data[j].value;
+            };
+            (i < len)
+        }) {
+            vector::push_back(&mut values, vector::borrow(&map.data, i).value);
+            i = i + 1;
+        };
+        values
+    }
+    spec values {
+        pragma opaque;
+        aborts_if false;
+        ensures len(result) == spec_len(map);
+        ensures forall j in 0..len(result): result[j] == map.data[j].value;
+    }
+
+    /// Return the index of the entry for `key`, if any.
+    fun find<K, V>(map: &SimpleMap<K, V>, key: &K): Option<u64> {
+        let i = 0;
+        let len = vector::length(&map.data);
+        while ({
+            spec {
+                invariant i <= len;
+                invariant len == len(map.data);
+                invariant forall j in 0..i: map.data[j].key != key;
+            };
+            (i < len)
+        }) {
+            if (&vector::borrow(&map.data, i).key == key) {
+                return option::some(i)
+            };
+            i = i + 1;
+        };
+        option::none()
+    }
+    spec find {
+        pragma opaque;
+        aborts_if false;
+        ensures option::is_none(result) <==> !spec_contains_key(map, key);
+        ensures option::is_some(result) ==> option::borrow(result) < len(map.data);
+        ensures option::is_some(result) ==> map.data[option::borrow(result)].key == key;
+    }
+
+    spec fun spec_len<K, V>(map: SimpleMap<K, V>): num {
+        len(map.data)
+    }
+
+    spec fun spec_contains_key<K, V>(map: SimpleMap<K, V>, key: K): bool {
+        exists i in 0..len(map.data): map.data[i].key == key
+    }
+
+    /// The value of the entry for `key`, which must be in `map`
+    spec fun spec_get<K, V>(map: SimpleMap<K, V>, key: K): V {
+        map.data[choose i in 0..len(map.data) where map.data[i].key == key].value
+    }
+}
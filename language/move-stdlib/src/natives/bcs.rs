@@ -15,6 +15,7 @@ use move_vm_types::{
     pop_arg,
     values::{values_impl::Reference, Value},
 };
+use serde::{Deserialize, Serialize};
 use smallvec::smallvec;
 use std::{collections::VecDeque, sync::Arc};
 /***************************************************************************************************
@@ -28,7 +29,7 @@ use std::{collections::VecDeque, sync::Arc};
  *             will be charged.
  *
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToBytesGasParameters {
     pub per_byte_serialized: InternalGasPerByte,
     pub legacy_min_output_size: NumBytes,
@@ -92,7 +93,7 @@ pub fn make_native_to_bytes(gas_params: ToBytesGasParameters) -> NativeFunction
 /***************************************************************************************************
  * module
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GasParameters {
     pub to_bytes: ToBytesGasParameters,
 }
@@ -13,6 +13,7 @@ use move_vm_types::{
     pop_arg,
     values::{Reference, Value},
 };
+use serde::{Deserialize, Serialize};
 use smallvec::smallvec;
 use std::{collections::VecDeque, sync::Arc};
 
@@ -21,7 +22,7 @@ use std::{collections::VecDeque, sync::Arc};
  *
  *   gas cost: base_cost
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrintGasParameters {
     pub base_cost: InternalGas,
 }
@@ -83,7 +84,7 @@ pub fn make_native_print(
  *
  *   gas cost: base_cost
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrintStackTraceGasParameters {
     pub base_cost: InternalGas,
 }
@@ -120,7 +121,7 @@ pub fn make_native_print_stack_trace(gas_params: PrintStackTraceGasParameters) -
 /***************************************************************************************************
  * module
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GasParameters {
     pub print: PrintGasParameters,
     pub print_stack_trace: PrintStackTraceGasParameters,
@@ -10,6 +10,7 @@ use move_vm_types::{
     loaded_data::runtime_types::Type, natives::function::NativeResult, pop_arg, values::Value,
     views::ValueView,
 };
+use serde::{Deserialize, Serialize};
 use smallvec::smallvec;
 use std::{collections::VecDeque, sync::Arc};
 
@@ -19,7 +20,7 @@ use std::{collections::VecDeque, sync::Arc};
  *   gas cost: base_cost
  *
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WriteToEventStoreGasParameters {
     pub unit_cost: InternalGasPerAbstractMemoryUnit,
 }
@@ -61,7 +62,7 @@ pub fn make_native_write_to_event_store(
 /***************************************************************************************************
  * module
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GasParameters {
     pub write_to_event_store: WriteToEventStoreGasParameters,
 }
@@ -0,0 +1,368 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The natives of the `math64`, `math128` and `math256` modules, which are the same functions
+//! over the different integer types.
+
+use crate::natives::helpers::make_module_natives;
+use move_binary_format::errors::PartialVMResult;
+use move_core_types::{gas_algebra::InternalGas, u256::U256};
+use move_vm_runtime::native_functions::{NativeContext, NativeFunction};
+use move_vm_types::{
+    loaded_data::runtime_types::Type,
+    natives::function::NativeResult,
+    pop_arg,
+    values::{VMValueCast, Value},
+};
+use serde::{Deserialize, Serialize};
+use smallvec::smallvec;
+use std::{collections::VecDeque, sync::Arc};
+
+// See the constants of the math modules
+const EOVERFLOW: u64 = 0x20001;
+const EDIVISION_BY_ZERO: u64 = 0x10002;
+const ELOG2_OF_ZERO: u64 = 0x10003;
+
+/// The unsigned integer types with a math module
+trait Integer: Copy {
+    const BITS: u32;
+
+    fn is_zero(self) -> bool;
+
+    fn leading_zeros(self) -> u32;
+
+    fn checked_pow(self, exponent: u32) -> Option<Self>;
+
+    /// The square root, rounded down
+    fn sqrt(self) -> Self;
+
+    /// `self * mul / div` with an intermediate product twice as wide as `Self`, `None` if the
+    /// quotient overflows. `div` is not zero.
+    fn checked_mul_div(self, mul: Self, div: Self) -> Option<Self>;
+
+    fn into_value(self) -> Value;
+}
+
+impl Integer for u64 {
+    const BITS: u32 = u64::BITS;
+
+    fn is_zero(self) -> bool {
+        self == 0
+    }
+
+    fn leading_zeros(self) -> u32 {
+        u64::leading_zeros(self)
+    }
+
+    fn checked_pow(self, exponent: u32) -> Option<Self> {
+        u64::checked_pow(self, exponent)
+    }
+
+    fn sqrt(self) -> Self {
+        sqrt_u128(self as u128) as u64
+    }
+
+    fn checked_mul_div(self, mul: Self, div: Self) -> Option<Self> {
+        u64::try_from(self as u128 * mul as u128 / div as u128).ok()
+    }
+
+    fn into_value(self) -> Value {
+        Value::u64(self)
+    }
+}
+
+impl Integer for u128 {
+    const BITS: u32 = u128::BITS;
+
+    fn is_zero(self) -> bool {
+        self == 0
+    }
+
+    fn leading_zeros(self) -> u32 {
+        u128::leading_zeros(self)
+    }
+
+    fn checked_pow(self, exponent: u32) -> Option<Self> {
+        u128::checked_pow(self, exponent)
+    }
+
+    fn sqrt(self) -> Self {
+        sqrt_u128(self)
+    }
+
+    fn checked_mul_div(self, mul: Self, div: Self) -> Option<Self> {
+        let quotient = U256::from(self) * U256::from(mul) / U256::from(div);
+        (quotient <= U256::from(u128::MAX)).then_some(quotient.unchecked_as_u128())
+    }
+
+    fn into_value(self) -> Value {
+        Value::u128(self)
+    }
+}
+
+impl Integer for U256 {
+    const BITS: u32 = 256;
+
+    fn is_zero(self) -> bool {
+        self == U256::zero()
+    }
+
+    fn leading_zeros(self) -> u32 {
+        U256::leading_zeros(&self)
+    }
+
+    fn checked_pow(self, exponent: u32) -> Option<Self> {
+        U256::checked_pow(self, exponent)
+    }
+
+    fn sqrt(self) -> Self {
+        self.integer_sqrt()
+    }
+
+    fn checked_mul_div(self, mul: Self, div: Self) -> Option<Self> {
+        U256::checked_mul_div(self, mul, div)
+    }
+
+    fn into_value(self) -> Value {
+        Value::u256(self)
+    }
+}
+
+/// Newton's method, starting from a power of two above the root
+fn sqrt_u128(x: u128) -> u128 {
+    if x < 2 {
+        return x;
+    }
+    let mut root = 1u128 << ((u128::BITS - x.leading_zeros() + 1) / 2);
+    loop {
+        let next = (root + x / root) / 2;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
+
+/***************************************************************************************************
+ * native fun pow
+ *
+ *   gas cost: base_cost
+ *
+ **************************************************************************************************/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowGasParameters {
+    pub base: InternalGas,
+}
+
+fn native_pow<T: Integer>(
+    gas_params: &PowGasParameters,
+    _context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult>
+where
+    Value: VMValueCast<T>,
+{
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(args.len() == 2);
+
+    let exponent = pop_arg!(args, u8);
+    let base = pop_arg!(args, T);
+
+    match base.checked_pow(exponent as u32) {
+        Some(result) => Ok(NativeResult::ok(
+            gas_params.base,
+            smallvec![result.into_value()],
+        )),
+        None => Ok(NativeResult::err(gas_params.base, EOVERFLOW)),
+    }
+}
+
+fn make_native_pow<T: Integer>(gas_params: PowGasParameters) -> NativeFunction
+where
+    Value: VMValueCast<T>,
+{
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_pow::<T>(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+/***************************************************************************************************
+ * native fun sqrt
+ *
+ *   gas cost: base_cost
+ *
+ **************************************************************************************************/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SqrtGasParameters {
+    pub base: InternalGas,
+}
+
+fn native_sqrt<T: Integer>(
+    gas_params: &SqrtGasParameters,
+    _context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult>
+where
+    Value: VMValueCast<T>,
+{
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(args.len() == 1);
+
+    let x = pop_arg!(args, T);
+
+    Ok(NativeResult::ok(
+        gas_params.base,
+        smallvec![x.sqrt().into_value()],
+    ))
+}
+
+fn make_native_sqrt<T: Integer>(gas_params: SqrtGasParameters) -> NativeFunction
+where
+    Value: VMValueCast<T>,
+{
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_sqrt::<T>(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+/***************************************************************************************************
+ * native fun log2
+ *
+ *   gas cost: base_cost
+ *
+ **************************************************************************************************/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Log2GasParameters {
+    pub base: InternalGas,
+}
+
+fn native_log2<T: Integer>(
+    gas_params: &Log2GasParameters,
+    _context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult>
+where
+    Value: VMValueCast<T>,
+{
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(args.len() == 1);
+
+    let x = pop_arg!(args, T);
+    if x.is_zero() {
+        return Ok(NativeResult::err(gas_params.base, ELOG2_OF_ZERO));
+    }
+
+    let log2 = (T::BITS - 1 - x.leading_zeros()) as u8;
+    Ok(NativeResult::ok(
+        gas_params.base,
+        smallvec![Value::u8(log2)],
+    ))
+}
+
+fn make_native_log2<T: Integer>(gas_params: Log2GasParameters) -> NativeFunction
+where
+    Value: VMValueCast<T>,
+{
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_log2::<T>(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+/***************************************************************************************************
+ * native fun mul_div
+ *
+ *   gas cost: base_cost
+ *
+ **************************************************************************************************/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MulDivGasParameters {
+    pub base: InternalGas,
+}
+
+fn native_mul_div<T: Integer>(
+    gas_params: &MulDivGasParameters,
+    _context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult>
+where
+    Value: VMValueCast<T>,
+{
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(args.len() == 3);
+
+    let c = pop_arg!(args, T);
+    let b = pop_arg!(args, T);
+    let a = pop_arg!(args, T);
+    if c.is_zero() {
+        return Ok(NativeResult::err(gas_params.base, EDIVISION_BY_ZERO));
+    }
+
+    match a.checked_mul_div(b, c) {
+        Some(result) => Ok(NativeResult::ok(
+            gas_params.base,
+            smallvec![result.into_value()],
+        )),
+        None => Ok(NativeResult::err(gas_params.base, EOVERFLOW)),
+    }
+}
+
+fn make_native_mul_div<T: Integer>(gas_params: MulDivGasParameters) -> NativeFunction
+where
+    Value: VMValueCast<T>,
+{
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_mul_div::<T>(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+/***************************************************************************************************
+ * module
+ **************************************************************************************************/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasParameters {
+    pub pow: PowGasParameters,
+    pub sqrt: SqrtGasParameters,
+    pub log2: Log2GasParameters,
+    pub mul_div: MulDivGasParameters,
+}
+
+fn make_all<T: Integer>(gas_params: GasParameters) -> impl Iterator<Item = (String, NativeFunction)>
+where
+    Value: VMValueCast<T>,
+{
+    let natives = [
+        ("pow", make_native_pow::<T>(gas_params.pow)),
+        ("sqrt", make_native_sqrt::<T>(gas_params.sqrt)),
+        ("log2", make_native_log2::<T>(gas_params.log2)),
+        ("mul_div", make_native_mul_div::<T>(gas_params.mul_div)),
+    ];
+
+    make_module_natives(natives)
+}
+
+/// The natives of `math64`
+pub fn make_all_u64(gas_params: GasParameters) -> impl Iterator<Item = (String, NativeFunction)> {
+    make_all::<u64>(gas_params)
+}
+
+/// The natives of `math128`
+pub fn make_all_u128(gas_params: GasParameters) -> impl Iterator<Item = (String, NativeFunction)> {
+    make_all::<u128>(gas_params)
+}
+
+/// The natives of `math256`
+pub fn make_all_u256(gas_params: GasParameters) -> impl Iterator<Item = (String, NativeFunction)> {
+    make_all::<U256>(gas_params)
+}
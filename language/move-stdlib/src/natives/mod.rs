@@ -6,6 +6,7 @@ pub mod bcs;
 pub mod debug;
 pub mod event;
 pub mod hash;
+pub mod math;
 pub mod signer;
 pub mod string;
 pub mod type_name;
@@ -17,11 +18,15 @@ mod helpers;
 
 use move_core_types::account_address::AccountAddress;
 use move_vm_runtime::native_functions::{make_table_from_iter, NativeFunctionTable};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GasParameters {
     pub bcs: bcs::GasParameters,
     pub hash: hash::GasParameters,
+    pub math64: math::GasParameters,
+    pub math128: math::GasParameters,
+    pub math256: math::GasParameters,
     pub signer: signer::GasParameters,
     pub string: string::GasParameters,
     pub type_name: type_name::GasParameters,
@@ -33,6 +38,12 @@ pub struct GasParameters {
 
 impl GasParameters {
     pub fn zeros() -> Self {
+        let math = math::GasParameters {
+            pow: math::PowGasParameters { base: 0.into() },
+            sqrt: math::SqrtGasParameters { base: 0.into() },
+            log2: math::Log2GasParameters { base: 0.into() },
+            mul_div: math::MulDivGasParameters { base: 0.into() },
+        };
         Self {
             bcs: bcs::GasParameters {
                 to_bytes: bcs::ToBytesGasParameters {
@@ -54,6 +65,9 @@ impl GasParameters {
                     legacy_min_input_len: 0.into(),
                 },
             },
+            math64: math.clone(),
+            math128: math.clone(),
+            math256: math,
             type_name: type_name::GasParameters {
                 get: type_name::GetGasParameters {
                     base: 0.into(),
@@ -118,6 +132,9 @@ pub fn all_natives(
 
     add_natives!("bcs", bcs::make_all(gas_params.bcs));
     add_natives!("hash", hash::make_all(gas_params.hash));
+    add_natives!("math64", math::make_all_u64(gas_params.math64));
+    add_natives!("math128", math::make_all_u128(gas_params.math128));
+    add_natives!("math256", math::make_all_u256(gas_params.math256));
     add_natives!("signer", signer::make_all(gas_params.signer));
     add_natives!("string", string::make_all(gas_params.string));
     add_natives!("type_name", type_name::make_all(gas_params.type_name));
@@ -130,7 +147,7 @@ pub fn all_natives(
     make_table_from_iter(move_std_addr, natives)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NurseryGasParameters {
     event: event::GasParameters,
     debug: debug::GasParameters,
@@ -12,6 +12,7 @@ use move_vm_types::{
     pop_arg,
     values::{values_impl::SignerRef, Value},
 };
+use serde::{Deserialize, Serialize};
 use smallvec::smallvec;
 use std::{collections::VecDeque, sync::Arc};
 
@@ -21,7 +22,7 @@ use std::{collections::VecDeque, sync::Arc};
  *   gas cost: base_cost
  *
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BorrowAddressGasParameters {
     pub base: InternalGas,
 }
@@ -55,7 +56,7 @@ pub fn make_native_borrow_address(gas_params: BorrowAddressGasParameters) -> Nat
 /***************************************************************************************************
  * module
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GasParameters {
     pub borrow_address: BorrowAddressGasParameters,
 }
@@ -10,10 +10,11 @@ use move_vm_types::{
     values::{Struct, Value},
 };
 
+use serde::{Deserialize, Serialize};
 use smallvec::smallvec;
 use std::{collections::VecDeque, sync::Arc};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GetGasParameters {
     pub base: InternalGas,
     pub per_byte: InternalGasPerByte,
@@ -46,7 +47,7 @@ pub fn make_native_get(gas_params: GetGasParameters) -> NativeFunction {
     Arc::new(move |context, ty_args, args| native_get(&gas_params, context, ty_args, args))
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GasParameters {
     pub get: GetGasParameters,
 }
@@ -16,6 +16,7 @@ use move_vm_types::{
     values::{Value, Vector, VectorRef},
     views::ValueView,
 };
+use serde::{Deserialize, Serialize};
 use std::{collections::VecDeque, sync::Arc};
 
 /***************************************************************************************************
@@ -24,7 +25,7 @@ use std::{collections::VecDeque, sync::Arc};
  *   gas cost: base_cost
  *
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmptyGasParameters {
     pub base: InternalGas,
 }
@@ -55,7 +56,7 @@ pub fn make_native_empty(gas_params: EmptyGasParameters) -> NativeFunction {
  *   gas cost: base_cost
  *
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LengthGasParameters {
     pub base: InternalGas,
 }
@@ -87,7 +88,7 @@ pub fn make_native_length(gas_params: LengthGasParameters) -> NativeFunction {
  *   gas cost: base_cost + legacy_unit_cost * max(1, size_of(val))
  *
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PushBackGasParameters {
     pub base: InternalGas,
     pub legacy_per_abstract_memory_unit: InternalGasPerAbstractMemoryUnit,
@@ -128,7 +129,7 @@ pub fn make_native_push_back(gas_params: PushBackGasParameters) -> NativeFunctio
  *   gas cost: base_cost
  *
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BorrowGasParameters {
     pub base: InternalGas,
 }
@@ -165,7 +166,7 @@ pub fn make_native_borrow(gas_params: BorrowGasParameters) -> NativeFunction {
  *   gas cost: base_cost
  *
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PopBackGasParameters {
     pub base: InternalGas,
 }
@@ -200,7 +201,7 @@ pub fn make_native_pop_back(gas_params: PopBackGasParameters) -> NativeFunction
  *   gas cost: base_cost
  *
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DestroyEmptyGasParameters {
     pub base: InternalGas,
 }
@@ -232,7 +233,7 @@ pub fn make_native_destroy_empty(gas_params: DestroyEmptyGasParameters) -> Nativ
 /***************************************************************************************************
  * native fun swap
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwapGasParameters {
     pub base: InternalGas,
 }
@@ -289,7 +290,7 @@ fn native_error_to_abort(err: PartialVMError) -> PartialVMError {
 /***************************************************************************************************
  * module
  **************************************************************************************************/
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GasParameters {
     pub empty: EmptyGasParameters,
     pub length: LengthGasParameters,
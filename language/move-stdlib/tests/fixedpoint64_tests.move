@@ -0,0 +1,104 @@
+#[test_only]
+module std::fp64_tests {
+    use std::fixed_point64;
+
+    const MAX_U128: u128 = 340282366920938463463374607431768211455;
+
+    #[test]
+    #[expected_failure(abort_code = fixed_point64::EDENOMINATOR)]
+    fun create_div_zero() {
+        fixed_point64::create_from_rational(2, 0);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = fixed_point64::ERATIO_OUT_OF_RANGE)]
+    fun create_overflow() {
+        // The maximum value is 2^64 - 1. Check that anything larger aborts
+        // with an overflow.
+        fixed_point64::create_from_rational(18446744073709551616, 1); // 2^64
+    }
+
+    #[test]
+    #[expected_failure(abort_code = fixed_point64::ERATIO_OUT_OF_RANGE)]
+    fun create_underflow() {
+        // The minimum non-zero value is 2^-64. Check that anything smaller
+        // aborts.
+        fixed_point64::create_from_rational(1, 36893488147419103232); // 2^-65
+    }
+
+    #[test]
+    fun create_zero() {
+        let x = fixed_point64::create_from_rational(0, 1);
+        assert!(fixed_point64::is_zero(x), 0);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = fixed_point64::EDIVISION_BY_ZERO)]
+    fun divide_by_zero() {
+        let f = fixed_point64::create_from_raw_value(0);
+        fixed_point64::divide_u128(1, f);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = fixed_point64::EDIVISION)]
+    fun divide_overflow_large_numerator() {
+        let f = fixed_point64::create_from_rational(1, 2); // 0.5
+        // Divide the maximum u128 value by 0.5. This should overflow.
+        fixed_point64::divide_u128(MAX_U128, f);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = fixed_point64::EMULTIPLICATION)]
+    fun multiply_overflow() {
+        let f = fixed_point64::create_from_rational(3, 2); // 1.5
+        // Multiply the maximum u128 value by 1.5. This should overflow.
+        fixed_point64::multiply_u128(MAX_U128, f);
+    }
+
+    #[test]
+    fun exact_multiply_and_divide() {
+        let f = fixed_point64::create_from_rational(3, 4); // 0.75
+        assert!(fixed_point64::multiply_u128(12, f) == 9, 0);
+        assert!(fixed_point64::divide_u128(9, f) == 12, 1);
+    }
+
+    #[test]
+    fun create_from_rational_max_numerator_denominator() {
+        let f = fixed_point64::create_from_rational(MAX_U128, MAX_U128);
+        assert!(fixed_point64::get_raw_value(f) == 18446744073709551616, 0); // 0x1.0000000000000000
+    }
+
+    #[test]
+    fun min_max() {
+        let one = fixed_point64::create_from_u128(1);
+        let two = fixed_point64::create_from_u128(2);
+        assert!(fixed_point64::min(one, two) == one, 0);
+        assert!(fixed_point64::min(two, one) == one, 1);
+        assert!(fixed_point64::max(one, two) == two, 2);
+        assert!(fixed_point64::max(two, one) == two, 3);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = fixed_point64::ERATIO_OUT_OF_RANGE)]
+    fun create_from_u128_overflow() {
+        fixed_point64::create_from_u128(18446744073709551616); // 2^64
+    }
+
+    #[test]
+    fun floor_ceil_round() {
+        let point_five = fixed_point64::create_from_rational(1, 2);
+        assert!(fixed_point64::floor(point_five) == 0, 0);
+        assert!(fixed_point64::ceil(point_five) == 1, 1);
+        assert!(fixed_point64::round(point_five) == 1, 2);
+
+        let one_point_two_five = fixed_point64::create_from_rational(5, 4);
+        assert!(fixed_point64::floor(one_point_two_five) == 1, 3);
+        assert!(fixed_point64::ceil(one_point_two_five) == 2, 4);
+        assert!(fixed_point64::round(one_point_two_five) == 1, 5);
+
+        let max = fixed_point64::create_from_raw_value(MAX_U128);
+        assert!(fixed_point64::floor(max) == 18446744073709551615, 6);
+        assert!(fixed_point64::ceil(max) == 18446744073709551616, 7);
+        assert!(fixed_point64::round(max) == 18446744073709551616, 8);
+    }
+}
@@ -0,0 +1,94 @@
+#[test_only]
+module std::math128_tests {
+    use std::math128;
+    use std::option;
+
+    const MAX_U128: u128 = 340282366920938463463374607431768211455;
+
+    #[test]
+    fun min_max() {
+        assert!(math128::min(3, 7) == 3, 0);
+        assert!(math128::max(3, 7) == 7, 1);
+        assert!(math128::min(MAX_U128, MAX_U128) == MAX_U128, 2);
+    }
+
+    #[test]
+    fun pow() {
+        assert!(math128::pow(0, 0) == 1, 0);
+        assert!(math128::pow(7, 0) == 1, 1);
+        assert!(math128::pow(3, 4) == 81, 2);
+        assert!(math128::pow(2, 127) == 170141183460469231731687303715884105728, 3);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = math128::EOVERFLOW)]
+    fun pow_overflow() {
+        math128::pow(2, 128);
+    }
+
+    #[test]
+    fun sqrt() {
+        assert!(math128::sqrt(0) == 0, 0);
+        assert!(math128::sqrt(1) == 1, 1);
+        assert!(math128::sqrt(15) == 3, 2);
+        assert!(math128::sqrt(16) == 4, 3);
+        assert!(math128::sqrt(MAX_U128) == 18446744073709551615, 4);
+    }
+
+    #[test]
+    fun log2() {
+        assert!(math128::log2(1) == 0, 0);
+        assert!(math128::log2(2) == 1, 1);
+        assert!(math128::log2(1023) == 9, 2);
+        assert!(math128::log2(MAX_U128) == 127, 3);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = math128::ELOG2_OF_ZERO)]
+    fun log2_of_zero() {
+        math128::log2(0);
+    }
+
+    #[test]
+    fun mul_div() {
+        assert!(math128::mul_div(7, 3, 2) == 10, 0);
+        // The product overflows, but the quotient does not
+        assert!(math128::mul_div(MAX_U128, MAX_U128, MAX_U128) == MAX_U128, 1);
+        assert!(math128::mul_div(MAX_U128, 2, 4) == MAX_U128 / 2, 2);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = math128::EDIVISION_BY_ZERO)]
+    fun mul_div_by_zero() {
+        math128::mul_div(1, 1, 0);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = math128::EOVERFLOW)]
+    fun mul_div_overflow() {
+        math128::mul_div(MAX_U128, 2, 1);
+    }
+
+    #[test]
+    fun checked() {
+        assert!(math128::checked_add(1, 2) == option::some(3), 0);
+        assert!(option::is_none(&math128::checked_add(MAX_U128, 1)), 1);
+        assert!(math128::checked_sub(3, 2) == option::some(1), 2);
+        assert!(option::is_none(&math128::checked_sub(2, 3)), 3);
+        assert!(math128::checked_mul(3, 4) == option::some(12), 4);
+        assert!(math128::checked_mul(0, MAX_U128) == option::some(0), 5);
+        assert!(option::is_none(&math128::checked_mul(MAX_U128, 2)), 6);
+        assert!(math128::checked_div(7, 2) == option::some(3), 7);
+        assert!(option::is_none(&math128::checked_div(7, 0)), 8);
+    }
+
+    #[test]
+    fun saturating() {
+        assert!(math128::saturating_add(1, 2) == 3, 0);
+        assert!(math128::saturating_add(MAX_U128, 1) == MAX_U128, 1);
+        assert!(math128::saturating_sub(3, 2) == 1, 2);
+        assert!(math128::saturating_sub(2, 3) == 0, 3);
+        assert!(math128::saturating_mul(3, 4) == 12, 4);
+        assert!(math128::saturating_mul(MAX_U128, 2) == MAX_U128, 5);
+    }
+}
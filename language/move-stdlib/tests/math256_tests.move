@@ -0,0 +1,94 @@
+#[test_only]
+module std::math256_tests {
+    use std::math256;
+    use std::option;
+
+    const MAX_U256: u256 = 115792089237316195423570985008687907853269984665640564039457584007913129639935;
+
+    #[test]
+    fun min_max() {
+        assert!(math256::min(3, 7) == 3, 0);
+        assert!(math256::max(3, 7) == 7, 1);
+        assert!(math256::min(MAX_U256, MAX_U256) == MAX_U256, 2);
+    }
+
+    #[test]
+    fun pow() {
+        assert!(math256::pow(0, 0) == 1, 0);
+        assert!(math256::pow(7, 0) == 1, 1);
+        assert!(math256::pow(3, 4) == 81, 2);
+        assert!(math256::pow(2, 255) == 57896044618658097711785492504343953926634992332820282019728792003956564819968, 3);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = math256::EOVERFLOW)]
+    fun pow_overflow() {
+        math256::pow(4, 128);
+    }
+
+    #[test]
+    fun sqrt() {
+        assert!(math256::sqrt(0) == 0, 0);
+        assert!(math256::sqrt(1) == 1, 1);
+        assert!(math256::sqrt(15) == 3, 2);
+        assert!(math256::sqrt(16) == 4, 3);
+        assert!(math256::sqrt(MAX_U256) == 340282366920938463463374607431768211455, 4);
+    }
+
+    #[test]
+    fun log2() {
+        assert!(math256::log2(1) == 0, 0);
+        assert!(math256::log2(2) == 1, 1);
+        assert!(math256::log2(1023) == 9, 2);
+        assert!(math256::log2(MAX_U256) == 255, 3);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = math256::ELOG2_OF_ZERO)]
+    fun log2_of_zero() {
+        math256::log2(0);
+    }
+
+    #[test]
+    fun mul_div() {
+        assert!(math256::mul_div(7, 3, 2) == 10, 0);
+        // The product overflows, but the quotient does not
+        assert!(math256::mul_div(MAX_U256, MAX_U256, MAX_U256) == MAX_U256, 1);
+        assert!(math256::mul_div(MAX_U256, 2, 4) == MAX_U256 / 2, 2);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = math256::EDIVISION_BY_ZERO)]
+    fun mul_div_by_zero() {
+        math256::mul_div(1, 1, 0);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = math256::EOVERFLOW)]
+    fun mul_div_overflow() {
+        math256::mul_div(MAX_U256, 2, 1);
+    }
+
+    #[test]
+    fun checked() {
+        assert!(math256::checked_add(1, 2) == option::some(3), 0);
+        assert!(option::is_none(&math256::checked_add(MAX_U256, 1)), 1);
+        assert!(math256::checked_sub(3, 2) == option::some(1), 2);
+        assert!(option::is_none(&math256::checked_sub(2, 3)), 3);
+        assert!(math256::checked_mul(3, 4) == option::some(12), 4);
+        assert!(math256::checked_mul(0, MAX_U256) == option::some(0), 5);
+        assert!(option::is_none(&math256::checked_mul(MAX_U256, 2)), 6);
+        assert!(math256::checked_div(7, 2) == option::some(3), 7);
+        assert!(option::is_none(&math256::checked_div(7, 0)), 8);
+    }
+
+    #[test]
+    fun saturating() {
+        assert!(math256::saturating_add(1, 2) == 3, 0);
+        assert!(math256::saturating_add(MAX_U256, 1) == MAX_U256, 1);
+        assert!(math256::saturating_sub(3, 2) == 1, 2);
+        assert!(math256::saturating_sub(2, 3) == 0, 3);
+        assert!(math256::saturating_mul(3, 4) == 12, 4);
+        assert!(math256::saturating_mul(MAX_U256, 2) == MAX_U256, 5);
+    }
+}
@@ -0,0 +1,94 @@
+#[test_only]
+module std::math64_tests {
+    use std::math64;
+    use std::option;
+
+    const MAX_U64: u64 = 18446744073709551615;
+
+    #[test]
+    fun min_max() {
+        assert!(math64::min(3, 7) == 3, 0);
+        assert!(math64::max(3, 7) == 7, 1);
+        assert!(math64::min(MAX_U64, MAX_U64) == MAX_U64, 2);
+    }
+
+    #[test]
+    fun pow() {
+        assert!(math64::pow(0, 0) == 1, 0);
+        assert!(math64::pow(7, 0) == 1, 1);
+        assert!(math64::pow(3, 4) == 81, 2);
+        assert!(math64::pow(2, 63) == 9223372036854775808, 3);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = math64::EOVERFLOW)]
+    fun pow_overflow() {
+        math64::pow(2, 64);
+    }
+
+    #[test]
+    fun sqrt() {
+        assert!(math64::sqrt(0) == 0, 0);
+        assert!(math64::sqrt(1) == 1, 1);
+        assert!(math64::sqrt(15) == 3, 2);
+        assert!(math64::sqrt(16) == 4, 3);
+        assert!(math64::sqrt(MAX_U64) == 4294967295, 4);
+    }
+
+    #[test]
+    fun log2() {
+        assert!(math64::log2(1) == 0, 0);
+        assert!(math64::log2(2) == 1, 1);
+        assert!(math64::log2(1023) == 9, 2);
+        assert!(math64::log2(MAX_U64) == 63, 3);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = math64::ELOG2_OF_ZERO)]
+    fun log2_of_zero() {
+        math64::log2(0);
+    }
+
+    #[test]
+    fun mul_div() {
+        assert!(math64::mul_div(7, 3, 2) == 10, 0);
+        // The product overflows, but the quotient does not
+        assert!(math64::mul_div(MAX_U64, MAX_U64, MAX_U64) == MAX_U64, 1);
+        assert!(math64::mul_div(MAX_U64, 2, 4) == MAX_U64 / 2, 2);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = math64::EDIVISION_BY_ZERO)]
+    fun mul_div_by_zero() {
+        math64::mul_div(1, 1, 0);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = math64::EOVERFLOW)]
+    fun mul_div_overflow() {
+        math64::mul_div(MAX_U64, 2, 1);
+    }
+
+    #[test]
+    fun checked() {
+        assert!(math64::checked_add(1, 2) == option::some(3), 0);
+        assert!(option::is_none(&math64::checked_add(MAX_U64, 1)), 1);
+        assert!(math64::checked_sub(3, 2) == option::some(1), 2);
+        assert!(option::is_none(&math64::checked_sub(2, 3)), 3);
+        assert!(math64::checked_mul(3, 4) == option::some(12), 4);
+        assert!(math64::checked_mul(0, MAX_U64) == option::some(0), 5);
+        assert!(option::is_none(&math64::checked_mul(MAX_U64, 2)), 6);
+        assert!(math64::checked_div(7, 2) == option::some(3), 7);
+        assert!(option::is_none(&math64::checked_div(7, 0)), 8);
+    }
+
+    #[test]
+    fun saturating() {
+        assert!(math64::saturating_add(1, 2) == 3, 0);
+        assert!(math64::saturating_add(MAX_U64, 1) == MAX_U64, 1);
+        assert!(math64::saturating_sub(3, 2) == 1, 2);
+        assert!(math64::saturating_sub(2, 3) == 0, 3);
+        assert!(math64::saturating_mul(3, 4) == 12, 4);
+        assert!(math64::saturating_mul(MAX_U64, 2) == MAX_U64, 5);
+    }
+}
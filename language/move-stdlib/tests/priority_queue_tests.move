@@ -0,0 +1,65 @@
+#[test_only]
+module std::priority_queue_tests {
+    use std::priority_queue;
+
+    #[test]
+    fun pop_in_priority_order() {
+        let queue = priority_queue::new();
+        // Priorities 0..20 in the order `i * 7 % 20`
+        let i = 0;
+        while (i < 20) {
+            let priority = i * 7 % 20;
+            priority_queue::insert(&mut queue, priority, priority * 10);
+            i = i + 1;
+        };
+        assert!(priority_queue::length(&queue) == 20, 0);
+        assert!(priority_queue::max_priority(&queue) == 19, 1);
+        assert!(*priority_queue::borrow_max(&queue) == 190, 2);
+
+        let expected = 20;
+        while (expected > 0) {
+            expected = expected - 1;
+            let (priority, value) = priority_queue::pop_max(&mut queue);
+            assert!(priority == expected && value == expected * 10, 3);
+        };
+        assert!(priority_queue::is_empty(&queue), 4);
+        priority_queue::destroy_empty(queue);
+    }
+
+    #[test]
+    fun equal_priorities() {
+        let queue = priority_queue::new();
+        priority_queue::insert(&mut queue, 1, 1);
+        priority_queue::insert(&mut queue, 2, 2);
+        priority_queue::insert(&mut queue, 1, 3);
+        let (priority, value) = priority_queue::pop_max(&mut queue);
+        assert!(priority == 2 && value == 2, 0);
+        let (priority, value_1) = priority_queue::pop_max(&mut queue);
+        assert!(priority == 1, 1);
+        let (priority, value_2) = priority_queue::pop_max(&mut queue);
+        assert!(priority == 1, 2);
+        assert!(value_1 + value_2 == 4, 3);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = priority_queue::EEMPTY)]
+    fun pop_empty() {
+        let queue = priority_queue::new<u64>();
+        priority_queue::pop_max(&mut queue);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = priority_queue::EEMPTY)]
+    fun max_priority_empty() {
+        let queue = priority_queue::new<u64>();
+        priority_queue::max_priority(&queue);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = priority_queue::ENOT_EMPTY)]
+    fun destroy_non_empty() {
+        let queue = priority_queue::new();
+        priority_queue::insert(&mut queue, 1, 1);
+        priority_queue::destroy_empty(queue);
+    }
+}
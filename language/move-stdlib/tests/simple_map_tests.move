@@ -0,0 +1,98 @@
+#[test_only]
+module std::simple_map_tests {
+    use std::simple_map;
+    use std::vector;
+
+    #[test]
+    fun add_and_borrow() {
+        let map = simple_map::create<u64, u64>();
+        assert!(simple_map::is_empty(&map), 0);
+        simple_map::add(&mut map, 1, 10);
+        simple_map::add(&mut map, 2, 20);
+        assert!(simple_map::length(&map) == 2, 1);
+        assert!(simple_map::contains_key(&map, &1), 2);
+        assert!(!simple_map::contains_key(&map, &3), 3);
+        assert!(*simple_map::borrow(&map, &1) == 10, 4);
+        assert!(*simple_map::borrow(&map, &2) == 20, 5);
+    }
+
+    #[test]
+    fun borrow_mut() {
+        let map = simple_map::create<u64, u64>();
+        simple_map::add(&mut map, 1, 10);
+        *simple_map::borrow_mut(&mut map, &1) = 11;
+        assert!(*simple_map::borrow(&map, &1) == 11, 0);
+    }
+
+    #[test]
+    fun upsert() {
+        let map = simple_map::create<u64, u64>();
+        simple_map::upsert(&mut map, 1, 10);
+        simple_map::upsert(&mut map, 1, 11);
+        simple_map::upsert(&mut map, 2, 20);
+        assert!(simple_map::length(&map) == 2, 0);
+        assert!(*simple_map::borrow(&map, &1) == 11, 1);
+        assert!(*simple_map::borrow(&map, &2) == 20, 2);
+    }
+
+    #[test]
+    fun remove_and_destroy() {
+        let map = simple_map::create<u64, u64>();
+        simple_map::add(&mut map, 1, 10);
+        simple_map::add(&mut map, 2, 20);
+        simple_map::add(&mut map, 3, 30);
+        let (key, value) = simple_map::remove(&mut map, &1);
+        assert!(key == 1 && value == 10, 0);
+        assert!(!simple_map::contains_key(&map, &1), 1);
+        assert!(*simple_map::borrow(&map, &3) == 30, 2);
+        simple_map::remove(&mut map, &2);
+        simple_map::remove(&mut map, &3);
+        simple_map::destroy_empty(map);
+    }
+
+    #[test]
+    fun keys_and_values() {
+        let map = simple_map::create<u64, u64>();
+        simple_map::add(&mut map, 1, 10);
+        simple_map::add(&mut map, 2, 20);
+        let keys = simple_map::keys(&map);
+        let values = simple_map::values(&map);
+        assert!(vector::length(&keys) == 2 && vector::length(&values) == 2, 0);
+        let i = 0;
+        while (i < 2) {
+            let key = vector::borrow(&keys, i);
+            assert!(*simple_map::borrow(&map, key) == *vector::borrow(&values, i), 1);
+            i = i + 1;
+        }
+    }
+
+    #[test]
+    #[expected_failure(abort_code = simple_map::EKEY_ALREADY_EXISTS)]
+    fun add_twice() {
+        let map = simple_map::create<u64, u64>();
+        simple_map::add(&mut map, 1, 10);
+        simple_map::add(&mut map, 1, 11);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = simple_map::EKEY_NOT_FOUND)]
+    fun borrow_missing() {
+        let map = simple_map::create<u64, u64>();
+        simple_map::borrow(&map, &1);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = simple_map::EKEY_NOT_FOUND)]
+    fun remove_missing() {
+        let map = simple_map::create<u64, u64>();
+        simple_map::remove(&mut map, &1);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = simple_map::ENOT_EMPTY)]
+    fun destroy_non_empty() {
+        let map = simple_map::create<u64, u64>();
+        simple_map::add(&mut map, 1, 10);
+        simple_map::destroy_empty(map);
+    }
+}
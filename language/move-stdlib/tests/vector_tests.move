@@ -551,4 +551,55 @@ module std::vector_tests {
         let v = vector[7];
         V::insert(&mut v, 6, 2);
     }
+
+    #[test]
+    fun test_for_each() {
+        let s = 0;
+        V::for_each(vector[1, 2, 3], |e| s = s * 10 + e);
+        assert!(s == 123, 0);
+    }
+
+    #[test]
+    fun test_for_each_ref() {
+        let v = vector[1, 2, 3];
+        let s = 0;
+        V::for_each_ref(&v, |e| s = s + *e);
+        assert!(s == 6, 0);
+        assert!(v == vector[1, 2, 3], 1);
+    }
+
+    #[test]
+    fun test_for_each_mut() {
+        let v = vector[1, 2, 3];
+        V::for_each_mut(&mut v, |e| *e = *e + 1);
+        assert!(v == vector[2, 3, 4], 0);
+    }
+
+    #[test]
+    fun test_fold() {
+        let r = V::fold(vector[1, 2, 3], 0, |acc, e| acc * 10 + e);
+        assert!(r == 123, 0);
+    }
+
+    #[test]
+    fun test_map() {
+        let v = V::map(vector[1, 2, 3], |e| e > 1);
+        assert!(v == vector[false, true, true], 0);
+    }
+
+    #[test]
+    fun test_filter() {
+        let v = V::filter(vector[1, 2, 3, 4], |e| *e % 2 == 0);
+        assert!(v == vector[2, 4], 0);
+    }
+
+    #[test]
+    fun test_any_all() {
+        let v = vector[1, 2, 3];
+        assert!(V::any(&v, |e| *e == 2), 0);
+        assert!(!V::any(&v, |e| *e > 3), 1);
+        assert!(V::all(&v, |e| *e > 0), 2);
+        assert!(!V::all(&v, |e| *e > 1), 3);
+        assert!(V::all(&vector<u64>[], |e| *e > 1), 4);
+    }
 }
@@ -13,4 +13,6 @@ mod leak_tests;
 mod loader_tests;
 mod mutated_accounts_tests;
 mod nested_loop_tests;
+mod parallel_execution_tests;
+mod read_set_tests;
 mod return_value_tests;
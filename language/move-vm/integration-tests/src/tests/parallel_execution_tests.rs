@@ -0,0 +1,144 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::Op,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag},
+    value::{serialize_values, MoveValue},
+};
+use move_vm_parallel_executor::{execute_block_sequential, EntryFunctionCall, ParallelExecutor};
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::InMemoryStorage;
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+fn setup() -> (InMemoryStorage, ModuleId) {
+    let code = r#"
+        module {{ADDR}}::Counter {
+            struct Counter has key { value: u64 }
+
+            public entry fun init(account: &signer) {
+                move_to(account, Counter { value: 0 })
+            }
+
+            public entry fun increment(addr: address) acquires Counter {
+                let counter = borrow_global_mut<Counter>(addr);
+                counter.value = counter.value + 1;
+            }
+
+            public entry fun add(from: address, to: address) acquires Counter {
+                let value = borrow_global<Counter>(from).value;
+                let counter = borrow_global_mut<Counter>(to);
+                counter.value = counter.value + value;
+            }
+
+            public entry fun remove(addr: address) acquires Counter {
+                let Counter { value: _ } = move_from<Counter>(addr);
+            }
+        }
+    "#;
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    let mut units = compile_units(&code).unwrap();
+    let m = as_module(units.pop().unwrap());
+    let mut blob = vec![];
+    m.serialize(&mut blob).unwrap();
+
+    let mut storage = InMemoryStorage::new();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("Counter").unwrap());
+    storage.publish_or_overwrite_module(module_id.clone(), blob);
+    (storage, module_id)
+}
+
+fn call(module_id: &ModuleId, function: &str, args: Vec<MoveValue>) -> EntryFunctionCall {
+    EntryFunctionCall {
+        module: module_id.clone(),
+        function: Identifier::new(function).unwrap(),
+        ty_args: vec![],
+        args: serialize_values(&args),
+    }
+}
+
+#[test]
+fn parallel_execution_matches_sequential_execution() {
+    let (storage, module_id) = setup();
+    let accounts = (0..4)
+        .map(|i| AccountAddress::new([i; AccountAddress::LENGTH]))
+        .collect::<Vec<_>>();
+
+    let mut calls = vec![];
+    for account in &accounts {
+        calls.push(call(&module_id, "init", vec![MoveValue::Signer(*account)]));
+    }
+    for i in 0..100 {
+        let (from, to) = (accounts[i % 4], accounts[(i * 7 + 1) % 4]);
+        calls.push(match i % 10 {
+            // Removing and publishing again makes calls fail, then succeed
+            3 => call(&module_id, "remove", vec![MoveValue::Address(from)]),
+            4 => call(&module_id, "increment", vec![MoveValue::Address(from)]),
+            5 => call(&module_id, "init", vec![MoveValue::Signer(from)]),
+            6 | 8 => call(
+                &module_id,
+                "add",
+                vec![MoveValue::Address(from), MoveValue::Address(to)],
+            ),
+            _ => call(&module_id, "increment", vec![MoveValue::Address(to)]),
+        });
+    }
+
+    let vm = MoveVM::new(vec![]).unwrap();
+    let expected = execute_block_sequential(&vm, &storage, &calls).unwrap();
+    assert!(expected.outputs.iter().any(|output| output.status.is_err()));
+    for concurrency in [1, 2, 4, 8] {
+        let output = ParallelExecutor::new(&vm, concurrency)
+            .execute_block(&storage, &calls)
+            .unwrap();
+        assert_eq!(output, expected);
+    }
+}
+
+#[test]
+fn conflicting_calls_see_earlier_writes() {
+    let (storage, module_id) = setup();
+    let mut calls = vec![call(&module_id, "init", vec![MoveValue::Signer(TEST_ADDR)])];
+    for _ in 0..50 {
+        calls.push(call(
+            &module_id,
+            "increment",
+            vec![MoveValue::Address(TEST_ADDR)],
+        ));
+    }
+
+    let vm = MoveVM::new(vec![]).unwrap();
+    let output = ParallelExecutor::new(&vm, 4)
+        .execute_block(&storage, &calls)
+        .unwrap();
+    assert!(output.outputs.iter().all(|output| output.status.is_ok()));
+
+    let tag = StructTag {
+        address: TEST_ADDR,
+        module: Identifier::new("Counter").unwrap(),
+        name: Identifier::new("Counter").unwrap(),
+        type_params: vec![],
+    };
+    let resources = output
+        .change_set
+        .resources()
+        .filter(|(addr, resource_tag, _)| *addr == TEST_ADDR && **resource_tag == tag)
+        .map(|(_, _, op)| op)
+        .collect::<Vec<_>>();
+    assert_eq!(resources, vec![Op::New(&50u64.to_le_bytes()[..])]);
+}
+
+#[test]
+fn empty_block() {
+    let (storage, _) = setup();
+    let vm = MoveVM::new(vec![]).unwrap();
+    let output = ParallelExecutor::new(&vm, 4)
+        .execute_block(&storage, &[])
+        .unwrap();
+    assert!(output.outputs.is_empty());
+    assert!(output.change_set.accounts().is_empty());
+}
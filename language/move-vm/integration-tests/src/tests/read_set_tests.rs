@@ -0,0 +1,143 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::ReadSet,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag},
+    value::{serialize_values, MoveValue},
+    vm_status::StatusCode,
+};
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas::UnmeteredGasMeter;
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+const OTHER_ADDR: AccountAddress = AccountAddress::new([43; AccountAddress::LENGTH]);
+
+fn setup() -> (InMemoryStorage, Vec<(ModuleId, Vec<u8>)>) {
+    let code = r#"
+        module {{ADDR}}::Value {
+            public fun double(x: u64): u64 { x * 2 }
+        }
+
+        module {{ADDR}}::Holder {
+            use {{ADDR}}::Value;
+
+            struct Holder has key { value: u64 }
+
+            public entry fun read(from: address, other: address) acquires Holder {
+                assert!(!exists<Holder>(other), 1);
+                assert!(Value::double(borrow_global<Holder>(from).value) == 14, 2);
+            }
+        }
+    "#;
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    let units = compile_units(&code).unwrap();
+
+    let mut storage = InMemoryStorage::new();
+    let mut modules = vec![];
+    for unit in units {
+        let m = as_module(unit);
+        let mut blob = vec![];
+        m.serialize(&mut blob).unwrap();
+        storage.publish_or_overwrite_module(m.self_id(), blob.clone());
+        modules.push((m.self_id(), blob));
+    }
+    storage.publish_or_overwrite_resource(TEST_ADDR, holder_tag(), 7u64.to_le_bytes().to_vec());
+    (storage, modules)
+}
+
+fn holder_tag() -> StructTag {
+    StructTag {
+        address: TEST_ADDR,
+        module: Identifier::new("Holder").unwrap(),
+        name: Identifier::new("Holder").unwrap(),
+        type_params: vec![],
+    }
+}
+
+fn run(vm: &MoveVM, storage: &InMemoryStorage) -> ReadSet {
+    let mut session = vm.new_session(storage);
+    session.capture_read_set();
+    let args = serialize_values(&[
+        MoveValue::Address(TEST_ADDR),
+        MoveValue::Address(OTHER_ADDR),
+    ]);
+    session
+        .execute_entry_function(
+            &ModuleId::new(TEST_ADDR, Identifier::new("Holder").unwrap()),
+            &Identifier::new("read").unwrap(),
+            vec![],
+            args,
+            &mut UnmeteredGasMeter,
+        )
+        .unwrap();
+    let (change_set, _, read_set) = session.finish_with_read_set().unwrap();
+    assert!(change_set.accounts().is_empty());
+    read_set
+}
+
+fn check_read_set(read_set: &ReadSet, modules: &[(ModuleId, Vec<u8>)]) {
+    let tag = holder_tag();
+    let resources = read_set.resources().collect::<Vec<_>>();
+    assert_eq!(
+        resources,
+        vec![
+            (TEST_ADDR, &tag, Some(&7u64.to_le_bytes()[..])),
+            (OTHER_ADDR, &tag, None),
+        ]
+    );
+    for (module_id, blob) in modules {
+        let read = read_set
+            .modules()
+            .find(|(addr, name, _)| {
+                *addr == *module_id.address() && name.as_ident_str() == module_id.name()
+            })
+            .map(|(_, _, read)| read);
+        assert_eq!(read, Some(Some(blob.as_slice())));
+    }
+}
+
+#[test]
+fn read_set_of_session() {
+    let (storage, modules) = setup();
+    let vm = MoveVM::new(vec![]).unwrap();
+    check_read_set(&run(&vm, &storage), &modules);
+}
+
+#[test]
+fn read_set_includes_cached_modules() {
+    let (storage, modules) = setup();
+    let vm = MoveVM::new(vec![]).unwrap();
+    run(&vm, &storage);
+    // The modules and their dependencies are in the code cache now
+    check_read_set(&run(&vm, &storage), &modules);
+}
+
+#[test]
+fn read_set_records_cached_modules_at_read_time() {
+    let (mut storage, modules) = setup();
+    let vm = MoveVM::new(vec![]).unwrap();
+    run(&vm, &storage);
+    // The storage changes after the modules are cached, but the session still runs the cached
+    // code, which is what its read set must record.
+    for (module_id, _) in &modules {
+        storage.publish_or_overwrite_module(module_id.clone(), vec![0xde, 0xad]);
+    }
+    check_read_set(&run(&vm, &storage), &modules);
+}
+
+#[test]
+fn read_set_not_captured() {
+    let (storage, _) = setup();
+    let vm = MoveVM::new(vec![]).unwrap();
+    let session = vm.new_session(&storage);
+    let err = session.finish_with_read_set().unwrap_err();
+    assert_eq!(
+        err.major_status(),
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+}
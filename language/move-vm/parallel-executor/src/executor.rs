@@ -0,0 +1,195 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    mv_memory::{self, MVMemory, ReadOrigin, StateKey},
+    scheduler::{Scheduler, Task, Version},
+    view::VersionedView,
+    BlockOutput, EntryFunctionCall, TransactionOutput,
+};
+use anyhow::Result;
+use move_core_types::{effects::ChangeSet, resolver::MoveResolver};
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::DeltaStorage;
+use move_vm_types::gas::UnmeteredGasMeter;
+use parking_lot::Mutex;
+use std::{collections::BTreeMap, thread};
+
+/// Executes blocks of calls on a number of threads
+pub struct ParallelExecutor<'a> {
+    vm: &'a MoveVM,
+    concurrency: usize,
+}
+
+/// The state shared by the threads executing a block
+struct BlockContext<'a, S> {
+    storage: &'a S,
+    calls: &'a [EntryFunctionCall],
+    scheduler: Scheduler,
+    memory: MVMemory,
+    /// The origins of the values read by the last execution of each call
+    last_reads: Vec<Mutex<BTreeMap<StateKey, ReadOrigin>>>,
+    outputs: Vec<Mutex<Option<TransactionOutput>>>,
+}
+
+impl<'a> ParallelExecutor<'a> {
+    pub fn new(vm: &'a MoveVM, concurrency: usize) -> Self {
+        assert!(concurrency > 0, "at least one thread is needed");
+        Self { vm, concurrency }
+    }
+
+    /// Executes `calls` in parallel against `storage`, with the same result as
+    /// `execute_block_sequential`
+    pub fn execute_block<S: MoveResolver + Sync>(
+        &self,
+        storage: &S,
+        calls: &[EntryFunctionCall],
+    ) -> Result<BlockOutput> {
+        let context = BlockContext {
+            storage,
+            calls,
+            scheduler: Scheduler::new(calls.len()),
+            memory: MVMemory::new(calls.len()),
+            last_reads: calls.iter().map(|_| Mutex::new(BTreeMap::new())).collect(),
+            outputs: calls.iter().map(|_| Mutex::new(None)).collect(),
+        };
+        if !calls.is_empty() {
+            thread::scope(|scope| {
+                for _ in 0..self.concurrency.min(calls.len()) {
+                    scope.spawn(|| self.work(&context));
+                }
+            });
+        }
+
+        let outputs = context
+            .outputs
+            .into_iter()
+            .map(|output| {
+                output
+                    .into_inner()
+                    .expect("every call is executed before the scheduler is done")
+            })
+            .collect();
+        block_output(outputs)
+    }
+
+    fn work<S: MoveResolver>(&self, context: &BlockContext<S>) {
+        let mut task = None;
+        loop {
+            task = match task {
+                Some(Task::Execution(version)) => self.execute(version, context),
+                Some(Task::Validation(version)) => self.validate(version, context),
+                None if context.scheduler.done() => break,
+                None => {
+                    let task = context.scheduler.next_task();
+                    if task.is_none() {
+                        thread::yield_now();
+                    }
+                    task
+                }
+            };
+        }
+    }
+
+    fn execute<S: MoveResolver>(
+        &self,
+        (txn_idx, incarnation): Version,
+        context: &BlockContext<S>,
+    ) -> Option<Task> {
+        let view = VersionedView::new(context.storage, &context.memory, txn_idx);
+        let output = execute_call(self.vm, &view, &context.calls[txn_idx]);
+        let (reads, dependency) = view.into_reads();
+        if let Some(blocking_txn_idx) = dependency {
+            if context.scheduler.add_dependency(txn_idx, blocking_txn_idx) {
+                return None;
+            }
+            // The earlier call was executed in the meantime
+            return Some(Task::Execution((txn_idx, incarnation)));
+        }
+
+        let wrote_new_location =
+            context
+                .memory
+                .record(txn_idx, incarnation, mv_memory::writes(&output.change_set));
+        *context.last_reads[txn_idx].lock() = reads;
+        *context.outputs[txn_idx].lock() = Some(output);
+        context
+            .scheduler
+            .finish_execution(txn_idx, incarnation, wrote_new_location)
+    }
+
+    fn validate<S>(
+        &self,
+        (txn_idx, incarnation): Version,
+        context: &BlockContext<S>,
+    ) -> Option<Task> {
+        let is_valid = context
+            .memory
+            .validate_reads(txn_idx, &context.last_reads[txn_idx].lock());
+        let aborted = !is_valid && context.scheduler.try_validation_abort(txn_idx, incarnation);
+        if aborted {
+            context.memory.convert_writes_to_estimates(txn_idx);
+        }
+        context.scheduler.finish_validation(txn_idx, aborted)
+    }
+}
+
+/// Executes `calls` one after the other against `storage`
+pub fn execute_block_sequential<S: MoveResolver>(
+    vm: &MoveVM,
+    storage: &S,
+    calls: &[EntryFunctionCall],
+) -> Result<BlockOutput> {
+    let mut change_set = ChangeSet::new();
+    let mut outputs = vec![];
+    for call in calls {
+        let output = execute_call(vm, &DeltaStorage::new(storage, &change_set), call);
+        change_set.squash(output.change_set.clone())?;
+        outputs.push(output);
+    }
+    Ok(BlockOutput {
+        change_set,
+        outputs,
+    })
+}
+
+fn execute_call<S: MoveResolver>(
+    vm: &MoveVM,
+    storage: &S,
+    call: &EntryFunctionCall,
+) -> TransactionOutput {
+    let mut session = vm.new_session(storage);
+    let args = call.args.iter().map(|arg| arg.as_slice()).collect::<Vec<_>>();
+    let result = session
+        .execute_entry_function(
+            &call.module,
+            &call.function,
+            call.ty_args.clone(),
+            args,
+            &mut UnmeteredGasMeter,
+        )
+        .and_then(|_| session.finish());
+    match result {
+        Ok((change_set, events)) => TransactionOutput {
+            status: Ok(()),
+            change_set,
+            events,
+        },
+        Err(err) => TransactionOutput {
+            status: Err(err),
+            change_set: ChangeSet::new(),
+            events: vec![],
+        },
+    }
+}
+
+fn block_output(outputs: Vec<TransactionOutput>) -> Result<BlockOutput> {
+    let mut change_set = ChangeSet::new();
+    for output in &outputs {
+        change_set.squash(output.change_set.clone())?;
+    }
+    Ok(BlockOutput {
+        change_set,
+        outputs,
+    })
+}
@@ -0,0 +1,65 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Optimistic parallel execution of a block of entry function calls, in the style of Block-STM.
+//!
+//! The calls of a block are executed concurrently, each in its own `Session`, against a
+//! multi-versioned memory holding the writes of every call. A call reads the latest write of the
+//! calls before it in the block, or the storage if there is none. Once executed, a call is
+//! validated by checking that what it read is still what it would read now; when it is not, the
+//! call conflicted with an earlier one and is executed again. A call which reads a value being
+//! rewritten by an earlier call waits for that call to be executed again.
+//!
+//! The result is the same as executing the calls one after the other, as done by
+//! `execute_block_sequential`: the calls which fail have no effects, and the change set of the
+//! block is the change sets of the calls squashed in order.
+//!
+//! Calls are executed without gas metering and without native extensions. Calls must not publish
+//! modules, as the code cache of the VM is shared by all the sessions.
+
+mod executor;
+mod mv_memory;
+mod scheduler;
+mod view;
+
+pub use executor::{execute_block_sequential, ParallelExecutor};
+
+use move_binary_format::errors::VMResult;
+use move_core_types::{
+    effects::{ChangeSet, Event},
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+};
+
+/// The position of a call in its block
+pub type TxnIndex = usize;
+/// The number of times a call was executed before
+pub type Incarnation = usize;
+
+/// A call to an entry function, with its arguments serialized
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryFunctionCall {
+    pub module: ModuleId,
+    pub function: Identifier,
+    pub ty_args: Vec<TypeTag>,
+    pub args: Vec<Vec<u8>>,
+}
+
+/// The outcome of a call of a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionOutput {
+    /// The error the call failed with, if it failed. A failed call has no effects.
+    pub status: VMResult<()>,
+    pub change_set: ChangeSet,
+    pub events: Vec<Event>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockOutput {
+    /// The effects of the block: the change sets of its calls, squashed in order
+    pub change_set: ChangeSet,
+    /// The outcomes of the calls, in the order of the block
+    pub outputs: Vec<TransactionOutput>,
+}
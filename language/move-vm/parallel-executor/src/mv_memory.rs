@@ -0,0 +1,154 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Incarnation, TxnIndex};
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::{ChangeSet, Op},
+    language_storage::{ModuleId, StructTag},
+};
+use parking_lot::{Mutex, RwLock};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A location of the storage read or written by calls
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum StateKey {
+    Module(ModuleId),
+    Resource(AccountAddress, StructTag),
+}
+
+/// Where a call read a value from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReadOrigin {
+    Storage,
+    Version(TxnIndex, Incarnation),
+}
+
+pub(crate) enum MVRead {
+    /// No call before the reader wrote the location: the value is in the storage
+    Storage,
+    /// The value written by an incarnation of an earlier call, `None` if it deleted the location
+    Version(TxnIndex, Incarnation, Option<Vec<u8>>),
+    /// The earlier call which last wrote the location is being executed again, and will likely
+    /// write it again
+    Dependency(TxnIndex),
+}
+
+struct Entry {
+    incarnation: Incarnation,
+    value: Option<Vec<u8>>,
+    /// Whether the call which wrote the value was aborted: the value is an estimate of what it
+    /// writes once executed again
+    is_estimate: bool,
+}
+
+/// The values written by the calls of a block, by location and by call
+pub(crate) struct MVMemory {
+    data: RwLock<BTreeMap<StateKey, BTreeMap<TxnIndex, Entry>>>,
+    /// The locations written by the last execution of each call
+    last_written: Vec<Mutex<BTreeSet<StateKey>>>,
+}
+
+impl MVMemory {
+    pub fn new(num_txns: usize) -> Self {
+        Self {
+            data: RwLock::new(BTreeMap::new()),
+            last_written: (0..num_txns).map(|_| Mutex::new(BTreeSet::new())).collect(),
+        }
+    }
+
+    /// The value of `key` for the call `txn_idx`, as written by the last call before it
+    pub fn read(&self, key: &StateKey, txn_idx: TxnIndex) -> MVRead {
+        let data = self.data.read();
+        match data
+            .get(key)
+            .and_then(|versions| versions.range(..txn_idx).next_back())
+        {
+            None => MVRead::Storage,
+            Some((idx, entry)) if entry.is_estimate => MVRead::Dependency(*idx),
+            Some((idx, entry)) => MVRead::Version(*idx, entry.incarnation, entry.value.clone()),
+        }
+    }
+
+    /// Records the writes of an incarnation of the call `txn_idx`, replacing those of its previous
+    /// incarnation. Returns whether it wrote a location the previous incarnation did not write.
+    pub fn record(
+        &self,
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+        writes: BTreeMap<StateKey, Option<Vec<u8>>>,
+    ) -> bool {
+        let mut last_written = self.last_written[txn_idx].lock();
+        let mut data = self.data.write();
+        for key in last_written.iter() {
+            if !writes.contains_key(key) {
+                if let Some(versions) = data.get_mut(key) {
+                    versions.remove(&txn_idx);
+                }
+            }
+        }
+        let wrote_new_location = writes.keys().any(|key| !last_written.contains(key));
+        *last_written = writes.keys().cloned().collect();
+        for (key, value) in writes {
+            data.entry(key).or_default().insert(
+                txn_idx,
+                Entry {
+                    incarnation,
+                    value,
+                    is_estimate: false,
+                },
+            );
+        }
+        wrote_new_location
+    }
+
+    /// Marks the writes of the call `txn_idx`, which was aborted, as estimates
+    pub fn convert_writes_to_estimates(&self, txn_idx: TxnIndex) {
+        let last_written = self.last_written[txn_idx].lock();
+        let mut data = self.data.write();
+        for key in last_written.iter() {
+            if let Some(entry) = data
+                .get_mut(key)
+                .and_then(|versions| versions.get_mut(&txn_idx))
+            {
+                entry.is_estimate = true;
+            }
+        }
+    }
+
+    /// Whether reading `reads` again for the call `txn_idx` would read from the same origins
+    pub fn validate_reads(
+        &self,
+        txn_idx: TxnIndex,
+        reads: &BTreeMap<StateKey, ReadOrigin>,
+    ) -> bool {
+        reads
+            .iter()
+            .all(|(key, origin)| match (self.read(key, txn_idx), origin) {
+                (MVRead::Storage, ReadOrigin::Storage) => true,
+                (
+                    MVRead::Version(idx, incarnation, _),
+                    ReadOrigin::Version(read_idx, read_incarnation),
+                ) => idx == *read_idx && incarnation == *read_incarnation,
+                _ => false,
+            })
+    }
+}
+
+/// The locations written by `change_set`, with the values written, `None` for deletions
+pub(crate) fn writes(change_set: &ChangeSet) -> BTreeMap<StateKey, Option<Vec<u8>>> {
+    let modules = change_set.modules().map(|(addr, name, op)| {
+        (
+            StateKey::Module(ModuleId::new(addr, name.clone())),
+            op_value(op),
+        )
+    });
+    let resources = change_set
+        .resources()
+        .map(|(addr, tag, op)| (StateKey::Resource(addr, tag.clone()), op_value(op)));
+    modules.chain(resources).collect()
+}
+
+fn op_value(op: Op<&[u8]>) -> Option<Vec<u8>> {
+    op.ok().map(|blob| blob.to_vec())
+}
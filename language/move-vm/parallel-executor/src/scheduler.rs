@@ -0,0 +1,222 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The collaborative scheduler of Block-STM. Executions and validations of calls are handed out
+//! in the order of the block, from two indices which move back when a call must be executed or
+//! validated again. The scheduler is done once both indices are past the end of the block with no
+//! task running.
+
+use crate::{Incarnation, TxnIndex};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+pub(crate) type Version = (TxnIndex, Incarnation);
+
+pub(crate) enum Task {
+    Execution(Version),
+    Validation(Version),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    ReadyToExecute(Incarnation),
+    Executing(Incarnation),
+    Executed(Incarnation),
+    Aborting(Incarnation),
+}
+
+pub(crate) struct Scheduler {
+    num_txns: usize,
+    execution_idx: AtomicUsize,
+    validation_idx: AtomicUsize,
+    /// Incremented whenever one of the indices moves back, so that a thread checking whether the
+    /// scheduler is done can tell that the indices changed while it was reading them
+    decrease_cnt: AtomicUsize,
+    num_active_tasks: AtomicUsize,
+    done_marker: AtomicBool,
+    txn_status: Vec<Mutex<Status>>,
+    /// The calls waiting for each call to be executed again
+    txn_dependency: Vec<Mutex<Vec<TxnIndex>>>,
+}
+
+impl Scheduler {
+    pub fn new(num_txns: usize) -> Self {
+        Self {
+            num_txns,
+            execution_idx: AtomicUsize::new(0),
+            validation_idx: AtomicUsize::new(0),
+            decrease_cnt: AtomicUsize::new(0),
+            num_active_tasks: AtomicUsize::new(0),
+            done_marker: AtomicBool::new(false),
+            txn_status: (0..num_txns)
+                .map(|_| Mutex::new(Status::ReadyToExecute(0)))
+                .collect(),
+            txn_dependency: (0..num_txns).map(|_| Mutex::new(vec![])).collect(),
+        }
+    }
+
+    pub fn done(&self) -> bool {
+        self.done_marker.load(Ordering::SeqCst)
+    }
+
+    /// The next task to run, validations first as they are cheaper and find conflicts early
+    pub fn next_task(&self) -> Option<Task> {
+        if self.validation_idx.load(Ordering::SeqCst) < self.execution_idx.load(Ordering::SeqCst) {
+            self.next_version_to_validate().map(Task::Validation)
+        } else {
+            self.next_version_to_execute().map(Task::Execution)
+        }
+    }
+
+    /// Suspends the execution of `txn_idx` until `blocking_txn_idx` is executed again. Returns
+    /// false if it already was, in which case `txn_idx` can be executed again right away.
+    pub fn add_dependency(&self, txn_idx: TxnIndex, blocking_txn_idx: TxnIndex) -> bool {
+        let mut dependencies = self.txn_dependency[blocking_txn_idx].lock();
+        if matches!(
+            *self.txn_status[blocking_txn_idx].lock(),
+            Status::Executed(_)
+        ) {
+            return false;
+        }
+        let mut status = self.txn_status[txn_idx].lock();
+        if let Status::Executing(incarnation) = *status {
+            *status = Status::Aborting(incarnation);
+        }
+        dependencies.push(txn_idx);
+        self.num_active_tasks.fetch_sub(1, Ordering::SeqCst);
+        true
+    }
+
+    /// Records the end of the execution of an incarnation of `txn_idx`, returning the validation
+    /// of that incarnation if it should run right away
+    pub fn finish_execution(
+        &self,
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+        wrote_new_location: bool,
+    ) -> Option<Task> {
+        *self.txn_status[txn_idx].lock() = Status::Executed(incarnation);
+        let dependencies = std::mem::take(&mut *self.txn_dependency[txn_idx].lock());
+        for dependency in &dependencies {
+            self.set_ready_status(*dependency);
+        }
+        if let Some(min_dependency) = dependencies.iter().min() {
+            self.decrease_execution_idx(*min_dependency);
+        }
+
+        if self.validation_idx.load(Ordering::SeqCst) > txn_idx {
+            if wrote_new_location {
+                // The calls after this one may read the new locations: validate them all again
+                self.decrease_validation_idx(txn_idx);
+            } else {
+                return Some(Task::Validation((txn_idx, incarnation)));
+            }
+        }
+        self.num_active_tasks.fetch_sub(1, Ordering::SeqCst);
+        None
+    }
+
+    /// Aborts `incarnation` of `txn_idx` after it failed validation. Returns false if it was
+    /// already aborted by another validation.
+    pub fn try_validation_abort(&self, txn_idx: TxnIndex, incarnation: Incarnation) -> bool {
+        let mut status = self.txn_status[txn_idx].lock();
+        if *status == Status::Executed(incarnation) {
+            *status = Status::Aborting(incarnation);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Records the end of a validation of `txn_idx`, returning the execution of its next
+    /// incarnation if it was aborted
+    pub fn finish_validation(&self, txn_idx: TxnIndex, aborted: bool) -> Option<Task> {
+        if aborted {
+            self.set_ready_status(txn_idx);
+            // The calls after this one may have read its writes
+            self.decrease_validation_idx(txn_idx + 1);
+            if self.execution_idx.load(Ordering::SeqCst) > txn_idx {
+                if let Some(version) = self.try_incarnate(txn_idx) {
+                    return Some(Task::Execution(version));
+                }
+            }
+        }
+        self.num_active_tasks.fetch_sub(1, Ordering::SeqCst);
+        None
+    }
+
+    fn next_version_to_execute(&self) -> Option<Version> {
+        if self.execution_idx.load(Ordering::SeqCst) >= self.num_txns {
+            self.check_done();
+            return None;
+        }
+        self.num_active_tasks.fetch_add(1, Ordering::SeqCst);
+        let idx = self.execution_idx.fetch_add(1, Ordering::SeqCst);
+        let version = self.try_incarnate(idx);
+        if version.is_none() {
+            self.num_active_tasks.fetch_sub(1, Ordering::SeqCst);
+        }
+        version
+    }
+
+    fn next_version_to_validate(&self) -> Option<Version> {
+        if self.validation_idx.load(Ordering::SeqCst) >= self.num_txns {
+            self.check_done();
+            return None;
+        }
+        self.num_active_tasks.fetch_add(1, Ordering::SeqCst);
+        let idx = self.validation_idx.fetch_add(1, Ordering::SeqCst);
+        if idx < self.num_txns {
+            if let Status::Executed(incarnation) = *self.txn_status[idx].lock() {
+                return Some((idx, incarnation));
+            }
+        }
+        self.num_active_tasks.fetch_sub(1, Ordering::SeqCst);
+        None
+    }
+
+    /// Starts the execution of `txn_idx` if it is ready for it
+    fn try_incarnate(&self, txn_idx: TxnIndex) -> Option<Version> {
+        if txn_idx >= self.num_txns {
+            return None;
+        }
+        let mut status = self.txn_status[txn_idx].lock();
+        match *status {
+            Status::ReadyToExecute(incarnation) => {
+                *status = Status::Executing(incarnation);
+                Some((txn_idx, incarnation))
+            }
+            _ => None,
+        }
+    }
+
+    fn set_ready_status(&self, txn_idx: TxnIndex) {
+        let mut status = self.txn_status[txn_idx].lock();
+        if let Status::Aborting(incarnation) = *status {
+            *status = Status::ReadyToExecute(incarnation + 1);
+        }
+    }
+
+    fn decrease_execution_idx(&self, target_idx: TxnIndex) {
+        self.execution_idx.fetch_min(target_idx, Ordering::SeqCst);
+        self.decrease_cnt.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn decrease_validation_idx(&self, target_idx: TxnIndex) {
+        self.validation_idx.fetch_min(target_idx, Ordering::SeqCst);
+        self.decrease_cnt.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn check_done(&self) {
+        let observed_cnt = self.decrease_cnt.load(Ordering::SeqCst);
+        let execution_idx = self.execution_idx.load(Ordering::SeqCst);
+        let validation_idx = self.validation_idx.load(Ordering::SeqCst);
+        let num_active_tasks = self.num_active_tasks.load(Ordering::SeqCst);
+        if execution_idx.min(validation_idx) >= self.num_txns
+            && num_active_tasks == 0
+            && observed_cnt == self.decrease_cnt.load(Ordering::SeqCst)
+        {
+            self.done_marker.store(true, Ordering::SeqCst);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    mv_memory::{MVMemory, MVRead, ReadOrigin, StateKey},
+    TxnIndex,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+    resolver::{ModuleResolver, MoveResolver, ResourceResolver},
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+};
+
+#[derive(Debug)]
+pub(crate) enum ReadError {
+    /// The read depends on the execution of an earlier call
+    Dependency(TxnIndex),
+    Storage(String),
+}
+
+/// The state seen by a call of a block: the writes of the calls before it over the storage. The
+/// view records where each value was read from, to validate the execution of the call later.
+pub(crate) struct VersionedView<'a, S> {
+    storage: &'a S,
+    memory: &'a MVMemory,
+    txn_idx: TxnIndex,
+    reads: RefCell<BTreeMap<StateKey, ReadOrigin>>,
+    dependency: Cell<Option<TxnIndex>>,
+}
+
+impl<'a, S: MoveResolver> VersionedView<'a, S> {
+    pub fn new(storage: &'a S, memory: &'a MVMemory, txn_idx: TxnIndex) -> Self {
+        Self {
+            storage,
+            memory,
+            txn_idx,
+            reads: RefCell::new(BTreeMap::new()),
+            dependency: Cell::new(None),
+        }
+    }
+
+    /// The origins of the values read, and the first call whose execution a read depended on
+    pub fn into_reads(self) -> (BTreeMap<StateKey, ReadOrigin>, Option<TxnIndex>) {
+        (self.reads.into_inner(), self.dependency.get())
+    }
+
+    fn read(&self, key: StateKey) -> Result<Option<Vec<u8>>, ReadError> {
+        let (origin, value) = match self.memory.read(&key, self.txn_idx) {
+            MVRead::Dependency(idx) => {
+                if self.dependency.get().is_none() {
+                    self.dependency.set(Some(idx));
+                }
+                return Err(ReadError::Dependency(idx));
+            }
+            MVRead::Version(idx, incarnation, value) => {
+                (ReadOrigin::Version(idx, incarnation), value)
+            }
+            MVRead::Storage => {
+                let value = match &key {
+                    StateKey::Module(module_id) => self
+                        .storage
+                        .get_module(module_id)
+                        .map_err(|err| ReadError::Storage(format!("{:?}", err)))?,
+                    StateKey::Resource(address, tag) => self
+                        .storage
+                        .get_resource(address, tag)
+                        .map_err(|err| ReadError::Storage(format!("{:?}", err)))?,
+                };
+                (ReadOrigin::Storage, value)
+            }
+        };
+        // A location read twice is validated against the first read, so that reading two
+        // different values fails validation
+        self.reads.borrow_mut().entry(key).or_insert(origin);
+        Ok(value)
+    }
+}
+
+impl<'a, S: MoveResolver> ModuleResolver for VersionedView<'a, S> {
+    type Error = ReadError;
+
+    fn get_module(&self, module_id: &ModuleId) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.read(StateKey::Module(module_id.clone()))
+    }
+}
+
+impl<'a, S: MoveResolver> ResourceResolver for VersionedView<'a, S> {
+    type Error = ReadError;
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.read(StateKey::Resource(*address, tag.clone()))
+    }
+}
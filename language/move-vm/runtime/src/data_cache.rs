@@ -7,7 +7,7 @@ use crate::loader::Loader;
 use move_binary_format::errors::*;
 use move_core_types::{
     account_address::AccountAddress,
-    effects::{AccountChangeSet, ChangeSet, Event, Op},
+    effects::{AccountChangeSet, ChangeSet, Event, Op, ReadSet},
     gas_algebra::NumBytes,
     identifier::Identifier,
     language_storage::{ModuleId, TypeTag},
@@ -20,7 +20,7 @@ use move_vm_types::{
     loaded_data::runtime_types::Type,
     values::{GlobalValue, Value},
 };
-use std::collections::btree_map::BTreeMap;
+use std::{cell::RefCell, collections::btree_map::BTreeMap};
 
 pub struct AccountDataCache {
     data_map: BTreeMap<Type, (MoveTypeLayout, GlobalValue)>,
@@ -54,6 +54,10 @@ pub(crate) struct TransactionDataCache<'r, 'l, S> {
     loader: &'l Loader,
     account_map: BTreeMap<AccountAddress, AccountDataCache>,
     event_data: Vec<(Vec<u8>, u64, Type, MoveTypeLayout, Value)>,
+    /// The reads from the remote cache, when they are captured
+    read_set: Option<RefCell<ReadSet>>,
+    /// The first error met capturing the reads, reported by `read_set`
+    read_set_error: RefCell<Option<PartialVMError>>,
 }
 
 impl<'r, 'l, S: MoveResolver> TransactionDataCache<'r, 'l, S> {
@@ -65,6 +69,44 @@ impl<'r, 'l, S: MoveResolver> TransactionDataCache<'r, 'l, S> {
             loader,
             account_map: BTreeMap::new(),
             event_data: vec![],
+            read_set: None,
+            read_set_error: RefCell::new(None),
+        }
+    }
+
+    /// Start capturing the modules and resources read from the remote cache
+    pub(crate) fn capture_read_set(&mut self) {
+        self.read_set = Some(RefCell::new(ReadSet::new()));
+    }
+
+    /// The modules and resources read from the remote cache since `capture_read_set`, including
+    /// the modules found in the code cache of the VM and their dependencies.
+    pub(crate) fn read_set(&self) -> PartialVMResult<ReadSet> {
+        if let Some(err) = self.read_set_error.borrow_mut().take() {
+            return Err(err);
+        }
+        match &self.read_set {
+            Some(read_set) => Ok(read_set.borrow().clone()),
+            None => Err(
+                PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                    .with_message("The read set of the session was not captured".to_string()),
+            ),
+        }
+    }
+
+    fn is_published_module(&self, module_id: &ModuleId) -> bool {
+        self.account_map
+            .get(module_id.address())
+            .map_or(false, |account_cache| {
+                account_cache.module_map.contains_key(module_id.name())
+            })
+    }
+
+    fn record_module_read(&self, module_id: &ModuleId, blob: Option<&Vec<u8>>) {
+        if let Some(read_set) = &self.read_set {
+            read_set
+                .borrow_mut()
+                .add_module_read(module_id.clone(), blob.cloned());
         }
     }
 
@@ -188,7 +230,13 @@ impl<'r, 'l, S: MoveResolver> DataStore for TransactionDataCache<'r, 'l, S> {
             // TODO(Gas): Shall we charge for this?
             let ty_layout = self.loader.type_to_type_layout(ty)?;
 
-            let gv = match self.remote.get_resource(&addr, &ty_tag) {
+            let resource = self.remote.get_resource(&addr, &ty_tag);
+            if let (Some(read_set), Ok(blob)) = (&self.read_set, &resource) {
+                read_set
+                    .borrow_mut()
+                    .add_resource_read(addr, (*ty_tag).clone(), blob.clone());
+            }
+            let gv = match resource {
                 Ok(Some(blob)) => {
                     load_res = Some(Some(NumBytes::new(blob.len() as u64)));
                     let val = match Value::simple_deserialize(&blob, &ty_layout) {
@@ -237,7 +285,11 @@ impl<'r, 'l, S: MoveResolver> DataStore for TransactionDataCache<'r, 'l, S> {
                 return Ok(blob.clone());
             }
         }
-        match self.remote.get_module(module_id) {
+        let module = self.remote.get_module(module_id);
+        if let Ok(blob) = &module {
+            self.record_module_read(module_id, blob.as_ref());
+        }
+        match module {
             Ok(Some(bytes)) => Ok(bytes),
             Ok(None) => Err(PartialVMError::new(StatusCode::LINKER_ERROR)
                 .with_message(format!("Cannot find {:?} in data cache", module_id))
@@ -277,13 +329,39 @@ impl<'r, 'l, S: MoveResolver> DataStore for TransactionDataCache<'r, 'l, S> {
                 return Ok(true);
             }
         }
-        Ok(self
-            .remote
-            .get_module(module_id)
-            .map_err(|_| {
-                PartialVMError::new(StatusCode::STORAGE_ERROR).finish(Location::Undefined)
-            })?
-            .is_some())
+        let blob = self.remote.get_module(module_id).map_err(|_| {
+            PartialVMError::new(StatusCode::STORAGE_ERROR).finish(Location::Undefined)
+        })?;
+        self.record_module_read(module_id, blob.as_ref());
+        Ok(blob.is_some())
+    }
+
+    fn record_module_cache_hit(&self, module_id: &ModuleId) {
+        let read_set = match &self.read_set {
+            Some(read_set) => read_set,
+            None => return,
+        };
+        if read_set.borrow().contains_module(module_id) {
+            return;
+        }
+        // The code cache holds the modules as they were read from the remote cache, so their
+        // bytes are the ones this session observed, whatever the remote cache holds now.
+        for module in self.loader.cached_module_closure(module_id) {
+            let id = module.self_id();
+            if read_set.borrow().contains_module(&id) || self.is_published_module(&id) {
+                continue;
+            }
+            let mut blob = vec![];
+            if let Err(err) = module.serialize(&mut blob) {
+                self.read_set_error.borrow_mut().get_or_insert_with(|| {
+                    PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR).with_message(
+                        format!("Unable to serialize cached module {}: {:?}", id, err),
+                    )
+                });
+                continue;
+            }
+            read_set.borrow_mut().add_module_read(id, Some(blob));
+        }
     }
 
     fn emit_event(
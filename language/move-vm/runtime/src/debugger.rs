@@ -0,0 +1,286 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An interface for debuggers to observe the execution of Move code.
+//!
+//! A `Debugger` installed on a thread with `set_debugger` is called before every instruction the
+//! interpreter executes on that thread, with a view of the state of the execution, e.g. the call
+//! stack and the operand stack. As the debugger is called synchronously, it can suspend the
+//! execution by not returning, e.g. while the user inspects the locals of the current frames, or
+//! record the state to trace the execution.
+
+use crate::{
+    interpreter::Interpreter,
+    loader::{Function, Loader},
+};
+use move_binary_format::{
+    errors::PartialVMResult,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_algebra::InternalGas,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    value::MoveValue,
+};
+use move_vm_types::{
+    loaded_data::runtime_types::Type,
+    values::{self, Locals, Reference, StructRef, Value},
+};
+use std::cell::RefCell;
+
+thread_local! {
+    static DEBUGGER: RefCell<Option<Box<dyn Debugger>>> = RefCell::new(None);
+}
+
+/// Observes the execution of Move code on the thread it is installed on
+pub trait Debugger {
+    /// Called before the instruction of `state` is executed
+    fn on_instruction(&mut self, state: &DebugState);
+}
+
+/// Installs `debugger` on the current thread, or uninstalls the current debugger if `None`.
+/// Returns the previously installed debugger, if any.
+pub fn set_debugger(debugger: Option<Box<dyn Debugger>>) -> Option<Box<dyn Debugger>> {
+    DEBUGGER.with(|d| std::mem::replace(&mut *d.borrow_mut(), debugger))
+}
+
+/// The state of the execution before an instruction, as seen by a debugger
+pub struct DebugState<'a> {
+    frames: Vec<DebugFrame<'a>>,
+    instruction: &'a Bytecode,
+    operand_stack: &'a [Value],
+    remaining_gas: InternalGas,
+}
+
+/// An access to global storage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalAccess {
+    pub kind: GlobalAccessKind,
+    pub address: AccountAddress,
+    pub struct_tag: StructTag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlobalAccessKind {
+    MoveTo,
+    MoveFrom,
+    BorrowGlobal { mutable: bool },
+    Exists,
+}
+
+/// A frame of the call stack, as seen by a debugger
+pub struct DebugFrame<'a> {
+    function: &'a Function,
+    locals: &'a Locals,
+    ty_args: &'a [Type],
+    pc: CodeOffset,
+    loader: &'a Loader,
+}
+
+/// The value of a local. The value a reference points to is read through the reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugValue {
+    /// Whether the local is a mutable (`Some(true)`) or an immutable (`Some(false)`) reference
+    pub reference: Option<bool>,
+    pub type_: TypeTag,
+    pub value: MoveValue,
+}
+
+impl<'a> DebugState<'a> {
+    /// The frames of the active functions, outermost first. The last frame is about to execute
+    /// the instruction at its `pc`.
+    pub fn frames(&self) -> &[DebugFrame<'a>] {
+        &self.frames
+    }
+
+    pub fn instruction(&self) -> &Bytecode {
+        self.instruction
+    }
+
+    /// The gas left before the instruction is charged
+    pub fn remaining_gas(&self) -> InternalGas {
+        self.remaining_gas
+    }
+
+    /// The values on the operand stack, bottom first, as printed for debugging
+    pub fn operand_stack(&self) -> Vec<String> {
+        self.operand_stack
+            .iter()
+            .map(|value| {
+                let mut buf = String::new();
+                match values::debug::print_value(&mut buf, value) {
+                    Ok(()) => buf,
+                    Err(_) => "<unprintable>".to_string(),
+                }
+            })
+            .collect()
+    }
+
+    /// The access to global storage performed by the instruction, if any
+    pub fn global_access(&self) -> Option<GlobalAccess> {
+        use Bytecode as B;
+        use GlobalAccessKind as K;
+
+        let frame = self.frames.last()?;
+        let resolver = frame.function.get_resolver(frame.loader);
+        let kind = match self.instruction {
+            B::MoveTo(_) | B::MoveToGeneric(_) => K::MoveTo,
+            B::MoveFrom(_) | B::MoveFromGeneric(_) => K::MoveFrom,
+            B::ImmBorrowGlobal(_) | B::ImmBorrowGlobalGeneric(_) => {
+                K::BorrowGlobal { mutable: false }
+            }
+            B::MutBorrowGlobal(_) | B::MutBorrowGlobalGeneric(_) => {
+                K::BorrowGlobal { mutable: true }
+            }
+            B::Exists(_) | B::ExistsGeneric(_) => K::Exists,
+            _ => return None,
+        };
+        let ty = match self.instruction {
+            B::MoveTo(idx)
+            | B::MoveFrom(idx)
+            | B::ImmBorrowGlobal(idx)
+            | B::MutBorrowGlobal(idx)
+            | B::Exists(idx) => resolver.get_struct_type(*idx),
+            B::MoveToGeneric(idx)
+            | B::MoveFromGeneric(idx)
+            | B::ImmBorrowGlobalGeneric(idx)
+            | B::MutBorrowGlobalGeneric(idx)
+            | B::ExistsGeneric(idx) => resolver
+                .instantiate_generic_type(*idx, frame.ty_args)
+                .ok()?,
+            _ => return None,
+        };
+        let struct_tag = match frame.loader.type_to_type_tag(&ty).ok()? {
+            TypeTag::Struct(struct_tag) => *struct_tag,
+            _ => return None,
+        };
+        // the address is on top of the operand stack, or the signer is under the resource moved
+        let address = match kind {
+            K::MoveTo => {
+                let signer = self.operand_stack.iter().rev().nth(1)?;
+                signer
+                    .copy_value()
+                    .and_then(|signer| signer.value_as::<StructRef>())
+                    .and_then(|signer| signer.borrow_field(0))
+                    .and_then(|addr| addr.value_as::<Reference>())
+                    .and_then(|addr| addr.read_ref())
+                    .and_then(|addr| addr.value_as::<AccountAddress>())
+                    .ok()?
+            }
+            _ => self
+                .operand_stack
+                .last()?
+                .copy_value()
+                .and_then(|addr| addr.value_as::<AccountAddress>())
+                .ok()?,
+        };
+        Some(GlobalAccess {
+            kind,
+            address,
+            struct_tag,
+        })
+    }
+}
+
+impl<'a> DebugFrame<'a> {
+    pub(crate) fn new(
+        function: &'a Function,
+        locals: &'a Locals,
+        ty_args: &'a [Type],
+        pc: CodeOffset,
+        loader: &'a Loader,
+    ) -> Self {
+        Self {
+            function,
+            locals,
+            ty_args,
+            pc,
+            loader,
+        }
+    }
+
+    pub(crate) fn function(&self) -> &Function {
+        self.function
+    }
+
+    pub(crate) fn locals(&self) -> &Locals {
+        self.locals
+    }
+
+    pub(crate) fn loader(&self) -> &Loader {
+        self.loader
+    }
+
+    /// The module of the function, or `None` for a script
+    pub fn module_id(&self) -> Option<&ModuleId> {
+        self.function.module_id()
+    }
+
+    pub fn function_name(&self) -> &str {
+        self.function.name()
+    }
+
+    pub fn function_index(&self) -> FunctionDefinitionIndex {
+        self.function.index()
+    }
+
+    /// The offset of the instruction executed by the frame, i.e. of the call for the frames of
+    /// callers
+    pub fn pc(&self) -> CodeOffset {
+        self.pc
+    }
+
+    /// The number of locals, including parameters
+    pub fn local_count(&self) -> usize {
+        self.function.local_count()
+    }
+
+    /// The value of the local at `idx`, or `None` if the local holds no value (it has not been
+    /// assigned yet or it has been moved)
+    pub fn local(&self, idx: usize) -> PartialVMResult<Option<DebugValue>> {
+        if self.locals.is_invalid(idx)? {
+            return Ok(None);
+        }
+        let ty = self
+            .function
+            .get_resolver(self.loader)
+            .subst(&self.function.local_types()[idx], self.ty_args)?;
+        let value = self.locals.copy_loc(idx)?;
+        let (reference, ty, value) = match ty {
+            Type::Reference(ty) => (Some(false), *ty, value.value_as::<Reference>()?.read_ref()?),
+            Type::MutableReference(ty) => {
+                (Some(true), *ty, value.value_as::<Reference>()?.read_ref()?)
+            }
+            ty => (None, ty, value),
+        };
+        let layout = self.loader.type_to_type_layout(&ty)?;
+        Ok(Some(DebugValue {
+            reference,
+            type_: self.loader.type_to_type_tag(&ty)?,
+            value: value.as_move_value(&layout),
+        }))
+    }
+}
+
+/// Calls the debugger installed on the current thread, if any, before `frame` executes
+/// `instruction`
+pub(crate) fn on_instruction(
+    frame: DebugFrame,
+    instruction: &Bytecode,
+    remaining_gas: InternalGas,
+    interp: &Interpreter,
+) {
+    DEBUGGER.with(|d| {
+        if let Some(debugger) = &mut *d.borrow_mut() {
+            let mut frames = interp.debug_frames(frame.loader);
+            frames.push(frame);
+            debugger.on_instruction(&DebugState {
+                frames,
+                instruction,
+                operand_stack: interp.debug_operand_stack(),
+                remaining_gas,
+            })
+        }
+    })
+}
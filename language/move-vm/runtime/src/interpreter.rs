@@ -755,6 +755,33 @@ impl Interpreter {
         Ok(())
     }
 
+    /// The values on the operand stack, bottom first
+    #[cfg(any(debug_assertions, feature = "debugging"))]
+    pub(crate) fn debug_operand_stack(&self) -> &[Value] {
+        &self.operand_stack.value
+    }
+
+    /// The frames of the callers of the function being executed, outermost first
+    #[cfg(any(debug_assertions, feature = "debugging"))]
+    pub(crate) fn debug_frames<'a>(
+        &'a self,
+        loader: &'a Loader,
+    ) -> Vec<crate::debugger::DebugFrame<'a>> {
+        self.call_stack
+            .0
+            .iter()
+            .map(|frame| {
+                crate::debugger::DebugFrame::new(
+                    &frame.function,
+                    &frame.locals,
+                    &frame.ty_args,
+                    frame.pc,
+                    loader,
+                )
+            })
+            .collect()
+    }
+
     #[allow(dead_code)]
     pub(crate) fn debug_print_stack_trace<B: Write>(
         &self,
@@ -1757,9 +1784,11 @@ impl Frame {
                 trace!(
                     &self.function,
                     &self.locals,
+                    &self.ty_args,
                     self.pc,
                     instruction,
                     resolver,
+                    gas_meter,
                     interpreter
                 );
 
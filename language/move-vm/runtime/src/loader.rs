@@ -639,6 +639,20 @@ impl Loader {
         result
     }
 
+    /// The module `id` in the code cache, with its transitive dependencies
+    pub(crate) fn cached_module_closure(&self, id: &ModuleId) -> Vec<Arc<CompiledModule>> {
+        if self.module_cache.read().module_at(id).is_none() {
+            return vec![];
+        }
+        let mut ids = BTreeSet::new();
+        self.transitive_dep_closure(id, &mut ids);
+        let module_cache = self.module_cache.read();
+        ids.iter()
+            .filter_map(|id| module_cache.module_at(id))
+            .map(|module| module.module.clone())
+            .collect()
+    }
+
     fn transitive_dep_closure(&self, id: &ModuleId, visited: &mut BTreeSet<ModuleId>) {
         if !visited.insert(id.clone()) {
             return;
@@ -1091,9 +1105,12 @@ impl Loader {
         bundle_unverified: &BTreeSet<ModuleId>,
         data_store: &impl DataStore,
     ) -> VMResult<Arc<Module>> {
-        // if the module is already in the code cache, load the cached version
-        if let Some(cached) = self.module_cache.read().module_at(id) {
+        // if the module is already in the code cache, load the cached version. The lock on the
+        // cache is released first, as the data store reads the cache to record the hit.
+        let cached = self.module_cache.read().module_at(id);
+        if let Some(cached) = cached {
             self.module_cache_hits.write().insert(id.clone());
+            data_store.record_module_cache_hit(id);
             return Ok(cached);
         }
 
@@ -13,7 +13,7 @@ use move_binary_format::{
 };
 use move_core_types::{
     account_address::AccountAddress,
-    effects::{ChangeSet, Event},
+    effects::{ChangeSet, Event, ReadSet},
     identifier::IdentStr,
     language_storage::{ModuleId, TypeTag},
     resolver::MoveResolver,
@@ -259,6 +259,29 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
         Ok((change_set, events, native_extensions))
     }
 
+    /// Start capturing the modules and resources the session reads from the storage, to be
+    /// returned by `finish_with_read_set`.
+    pub fn capture_read_set(&mut self) {
+        self.data_cache.capture_read_set()
+    }
+
+    /// Same like `finish`, but also returns the modules and resources read from the storage since
+    /// `capture_read_set` was called, with the bytes read, `None` for those which did not exist.
+    ///
+    /// Modules found in the code cache of the VM are included with their dependencies, with the
+    /// bytes of the cached code the session ran, as the session did not read them itself.
+    pub fn finish_with_read_set(self) -> VMResult<(ChangeSet, Vec<Event>, ReadSet)> {
+        let read_set = self
+            .data_cache
+            .read_set()
+            .map_err(|e| e.finish(Location::Undefined))?;
+        let (change_set, events) = self
+            .data_cache
+            .into_effects()
+            .map_err(|e| e.finish(Location::Undefined))?;
+        Ok((change_set, events, read_set))
+    }
+
     /// Load a script and all of its types into cache
     pub fn load_script(
         &self,
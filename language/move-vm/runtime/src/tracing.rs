@@ -3,12 +3,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 #[cfg(any(debug_assertions, feature = "debugging"))]
-use crate::debug::DebugContext;
+use crate::{
+    debug::DebugContext,
+    debugger::{self, DebugFrame},
+};
 
 #[cfg(any(debug_assertions, feature = "debugging"))]
 use ::{
     move_binary_format::file_format::Bytecode,
-    move_vm_types::values::Locals,
+    move_core_types::gas_algebra::InternalGas,
     once_cell::sync::Lazy,
     std::{
         env,
@@ -21,10 +24,7 @@ use ::{
 };
 
 #[cfg(any(debug_assertions, feature = "debugging"))]
-use crate::{
-    interpreter::Interpreter,
-    loader::{Function, Loader},
-};
+use crate::interpreter::Interpreter;
 
 #[cfg(any(debug_assertions, feature = "debugging"))]
 const MOVE_VM_TRACING_ENV_VAR_NAME: &str = "MOVE_VM_TRACE";
@@ -62,13 +62,13 @@ static DEBUG_CONTEXT: Lazy<Mutex<DebugContext>> = Lazy::new(|| Mutex::new(DebugC
 // Only include in debug builds
 #[cfg(any(debug_assertions, feature = "debugging"))]
 pub(crate) fn trace(
-    function_desc: &Function,
-    locals: &Locals,
-    pc: u16,
+    frame: DebugFrame,
     instr: &Bytecode,
-    loader: &Loader,
+    remaining_gas: InternalGas,
     interp: &Interpreter,
 ) {
+    let function_desc = frame.function();
+    let pc = frame.pc();
     if *TRACING_ENABLED {
         let f = &mut *LOGGING_FILE.lock().unwrap();
         writeln!(
@@ -83,24 +83,33 @@ pub(crate) fn trace(
         .unwrap();
     }
     if *DEBUGGING_ENABLED {
-        DEBUG_CONTEXT
-            .lock()
-            .unwrap()
-            .debug_loop(function_desc, locals, pc, instr, loader, interp);
+        DEBUG_CONTEXT.lock().unwrap().debug_loop(
+            function_desc,
+            frame.locals(),
+            pc,
+            instr,
+            frame.loader(),
+            interp,
+        );
     }
+    debugger::on_instruction(frame, instr, remaining_gas, interp);
 }
 
 #[macro_export]
 macro_rules! trace {
-    ($function_desc:expr, $locals:expr, $pc:expr, $instr:tt, $resolver:expr, $interp:expr) => {
+    ($function_desc:expr, $locals:expr, $ty_args:expr, $pc:expr, $instr:tt, $resolver:expr, $gas_meter:expr, $interp:expr) => {
         // Only include this code in debug releases
         #[cfg(any(debug_assertions, feature = "debugging"))]
         $crate::tracing::trace(
-            &$function_desc,
-            $locals,
-            $pc,
+            $crate::debugger::DebugFrame::new(
+                &$function_desc,
+                $locals,
+                $ty_args,
+                $pc,
+                $resolver.loader(),
+            ),
             &$instr,
-            $resolver.loader(),
+            $gas_meter.balance_internal(),
             $interp,
         )
     };
@@ -0,0 +1,757 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A gas meter attributing the gas charged by another gas meter to the call stack and to the kind
+//! of instruction being charged.
+//!
+//! The resulting `GasProfile` can be written as folded stacks, the input format of flamegraph
+//! tools such as `inferno-flamegraph` or `flamegraph.pl`, or summarized per kind of instruction.
+
+use move_binary_format::errors::PartialVMResult;
+use move_core_types::{
+    gas_algebra::{InternalGas, NumArgs, NumBytes},
+    language_storage::ModuleId,
+};
+use move_vm_types::{
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView},
+};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// The name of the file with the folded stacks of functions written by `GasProfile::save`
+pub const FUNCTION_STACKS_FILE_NAME: &str = "functions.folded";
+/// The name of the file with the folded stacks of functions and instructions written by
+/// `GasProfile::save`
+pub const INSTRUCTION_STACKS_FILE_NAME: &str = "instructions.folded";
+
+/// The gas charged during an execution, by call stack and kind of instruction
+#[derive(Debug, Clone, Default)]
+pub struct GasProfile {
+    charges: BTreeMap<(Vec<String>, &'static str), Charges>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Charges {
+    count: u64,
+    gas: u64,
+}
+
+/// Wraps a gas meter to profile the gas it charges
+pub struct GasProfiler<G> {
+    base: G,
+    /// The active functions, outermost first, and whether each of them is a native function
+    frames: Vec<(String, bool)>,
+    profile: GasProfile,
+}
+
+impl<G: GasMeter> GasProfiler<G> {
+    /// Profiles the execution of the function named `entry` (e.g. `0x1::m::f`), charged by `base`
+    pub fn new(base: G, entry: String) -> Self {
+        Self {
+            base,
+            frames: vec![(entry, false)],
+            profile: GasProfile::default(),
+        }
+    }
+
+    /// Returns the wrapped gas meter, and the gas it charged since the profiler was created
+    pub fn finish(self) -> (G, GasProfile) {
+        (self.base, self.profile)
+    }
+
+    fn record<T>(
+        &mut self,
+        kind: &'static str,
+        charge: impl FnOnce(&mut G) -> PartialVMResult<T>,
+    ) -> PartialVMResult<T> {
+        let before = u64::from(self.base.balance_internal());
+        let res = charge(&mut self.base);
+        let gas = before.saturating_sub(self.base.balance_internal().into());
+        let stack = self.frames.iter().map(|(name, _)| name.clone()).collect();
+        let charges = self.profile.charges.entry((stack, kind)).or_default();
+        charges.count += 1;
+        charges.gas += gas;
+        res
+    }
+
+    fn enter(&mut self, module_id: &ModuleId, func_name: &str) {
+        let name = format!(
+            "0x{}::{}::{}",
+            module_id.address().short_str_lossless(),
+            module_id.name(),
+            func_name
+        );
+        self.frames.push((name, false))
+    }
+
+    // The entry function is never left, so that charges after it returns are still attributed
+    fn leave(&mut self) {
+        if self.frames.len() > 1 {
+            self.frames.pop();
+        }
+    }
+}
+
+impl<G: GasMeter> GasMeter for GasProfiler<G> {
+    fn balance_internal(&self) -> InternalGas {
+        self.base.balance_internal()
+    }
+
+    fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()> {
+        self.record(simple_instruction_name(instr), |base| {
+            base.charge_simple_instr(instr)
+        })
+    }
+
+    fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()> {
+        self.record("Pop", |base| base.charge_pop(popped_val))
+    }
+
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let res = self.record("Call", |base| {
+            base.charge_call(module_id, func_name, args, num_locals)
+        });
+        self.enter(module_id, func_name);
+        res
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let res = self.record("CallGeneric", |base| {
+            base.charge_call_generic(module_id, func_name, ty_args, args, num_locals)
+        });
+        self.enter(module_id, func_name);
+        res
+    }
+
+    fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()> {
+        self.record("LdConst", |base| base.charge_ld_const(size))
+    }
+
+    fn charge_ld_const_after_deserialization(
+        &mut self,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.record("LdConst", |base| {
+            base.charge_ld_const_after_deserialization(val)
+        })
+    }
+
+    fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.record("CopyLoc", |base| base.charge_copy_loc(val))
+    }
+
+    fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.record("MoveLoc", |base| base.charge_move_loc(val))
+    }
+
+    fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.record("StLoc", |base| base.charge_store_loc(val))
+    }
+
+    fn charge_pack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let kind = if is_generic { "PackGeneric" } else { "Pack" };
+        self.record(kind, |base| base.charge_pack(is_generic, args))
+    }
+
+    fn charge_unpack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let kind = if is_generic {
+            "UnpackGeneric"
+        } else {
+            "Unpack"
+        };
+        self.record(kind, |base| base.charge_unpack(is_generic, args))
+    }
+
+    fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.record("ReadRef", |base| base.charge_read_ref(val))
+    }
+
+    fn charge_write_ref(
+        &mut self,
+        new_val: impl ValueView,
+        old_val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.record("WriteRef", |base| base.charge_write_ref(new_val, old_val))
+    }
+
+    fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.record("Eq", |base| base.charge_eq(lhs, rhs))
+    }
+
+    fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.record("Neq", |base| base.charge_neq(lhs, rhs))
+    }
+
+    fn charge_borrow_global(
+        &mut self,
+        is_mut: bool,
+        is_generic: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        let kind = match (is_mut, is_generic) {
+            (false, false) => "ImmBorrowGlobal",
+            (false, true) => "ImmBorrowGlobalGeneric",
+            (true, false) => "MutBorrowGlobal",
+            (true, true) => "MutBorrowGlobalGeneric",
+        };
+        self.record(kind, |base| {
+            base.charge_borrow_global(is_mut, is_generic, ty, is_success)
+        })
+    }
+
+    fn charge_exists(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        exists: bool,
+    ) -> PartialVMResult<()> {
+        let kind = if is_generic {
+            "ExistsGeneric"
+        } else {
+            "Exists"
+        };
+        self.record(kind, |base| base.charge_exists(is_generic, ty, exists))
+    }
+
+    fn charge_move_from(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let kind = if is_generic {
+            "MoveFromGeneric"
+        } else {
+            "MoveFrom"
+        };
+        self.record(kind, |base| base.charge_move_from(is_generic, ty, val))
+    }
+
+    fn charge_move_to(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: impl ValueView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        let kind = if is_generic {
+            "MoveToGeneric"
+        } else {
+            "MoveTo"
+        };
+        self.record(kind, |base| {
+            base.charge_move_to(is_generic, ty, val, is_success)
+        })
+    }
+
+    fn charge_vec_pack<'a>(
+        &mut self,
+        ty: impl TypeView + 'a,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.record("VecPack", |base| base.charge_vec_pack(ty, args))
+    }
+
+    fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.record("VecLen", |base| base.charge_vec_len(ty))
+    }
+
+    fn charge_vec_borrow(
+        &mut self,
+        is_mut: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        let kind = if is_mut {
+            "VecMutBorrow"
+        } else {
+            "VecImmBorrow"
+        };
+        self.record(kind, |base| base.charge_vec_borrow(is_mut, ty, is_success))
+    }
+
+    fn charge_vec_push_back(
+        &mut self,
+        ty: impl TypeView,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.record("VecPushBack", |base| base.charge_vec_push_back(ty, val))
+    }
+
+    fn charge_vec_pop_back(
+        &mut self,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.record("VecPopBack", |base| base.charge_vec_pop_back(ty, val))
+    }
+
+    fn charge_vec_unpack(
+        &mut self,
+        ty: impl TypeView,
+        expect_num_elements: NumArgs,
+        elems: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.record("VecUnpack", |base| {
+            base.charge_vec_unpack(ty, expect_num_elements, elems)
+        })
+    }
+
+    fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.record("VecSwap", |base| base.charge_vec_swap(ty))
+    }
+
+    fn charge_load_resource(
+        &mut self,
+        loaded: Option<(NumBytes, impl ValueView)>,
+    ) -> PartialVMResult<()> {
+        self.record("LoadResource", |base| base.charge_load_resource(loaded))
+    }
+
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView>>,
+    ) -> PartialVMResult<()> {
+        let res = self.record("Native", |base| {
+            base.charge_native_function(amount, ret_vals)
+        });
+        if matches!(self.frames.last(), Some((_, true))) {
+            self.leave();
+        }
+        res
+    }
+
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        // Only native functions are charged before their execution, so the current frame is the
+        // one of a native function
+        if let Some((_, is_native)) = self.frames.last_mut() {
+            *is_native = true;
+        }
+        self.record("Native", |base| {
+            base.charge_native_function_before_execution(ty_args, args)
+        })
+    }
+
+    fn charge_drop_frame(
+        &mut self,
+        locals: impl Iterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let res = self.record("DropFrame", |base| base.charge_drop_frame(locals));
+        self.leave();
+        res
+    }
+}
+
+fn simple_instruction_name(instr: SimpleInstruction) -> &'static str {
+    use SimpleInstruction::*;
+
+    match instr {
+        Nop => "Nop",
+        Ret => "Ret",
+        BrTrue => "BrTrue",
+        BrFalse => "BrFalse",
+        Branch => "Branch",
+        LdU8 => "LdU8",
+        LdU16 => "LdU16",
+        LdU32 => "LdU32",
+        LdU64 => "LdU64",
+        LdU128 => "LdU128",
+        LdU256 => "LdU256",
+        LdTrue => "LdTrue",
+        LdFalse => "LdFalse",
+        FreezeRef => "FreezeRef",
+        MutBorrowLoc => "MutBorrowLoc",
+        ImmBorrowLoc => "ImmBorrowLoc",
+        ImmBorrowField => "ImmBorrowField",
+        MutBorrowField => "MutBorrowField",
+        ImmBorrowFieldGeneric => "ImmBorrowFieldGeneric",
+        MutBorrowFieldGeneric => "MutBorrowFieldGeneric",
+        CastU8 => "CastU8",
+        CastU16 => "CastU16",
+        CastU32 => "CastU32",
+        CastU64 => "CastU64",
+        CastU128 => "CastU128",
+        CastU256 => "CastU256",
+        Add => "Add",
+        Sub => "Sub",
+        Mul => "Mul",
+        Mod => "Mod",
+        Div => "Div",
+        BitOr => "BitOr",
+        BitAnd => "BitAnd",
+        Xor => "Xor",
+        Shl => "Shl",
+        Shr => "Shr",
+        Or => "Or",
+        And => "And",
+        Not => "Not",
+        Lt => "Lt",
+        Gt => "Gt",
+        Le => "Le",
+        Ge => "Ge",
+        Abort => "Abort",
+    }
+}
+
+impl GasProfile {
+    /// The total gas charged
+    pub fn total_gas(&self) -> u64 {
+        self.charges.values().map(|charges| charges.gas).sum()
+    }
+
+    /// Adds the charges of `other` to this profile, e.g. to profile several executions together
+    pub fn merge(&mut self, other: GasProfile) {
+        for (key, other) in other.charges {
+            let charges = self.charges.entry(key).or_default();
+            charges.count += other.count;
+            charges.gas += other.gas;
+        }
+    }
+
+    /// Writes the gas charged by each call stack as folded stacks, i.e. one line per call stack
+    /// with the names of the functions separated by `;`, followed by the gas charged in the
+    /// innermost function
+    pub fn write_function_stacks(&self, w: &mut impl Write) -> io::Result<()> {
+        let mut stacks: BTreeMap<&[String], u64> = BTreeMap::new();
+        for ((stack, _), charges) in &self.charges {
+            *stacks.entry(stack.as_slice()).or_default() += charges.gas;
+        }
+        for (stack, gas) in stacks {
+            if gas > 0 {
+                writeln!(w, "{} {}", stack.join(";"), gas)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the gas charged by each call stack as folded stacks, with the kind of instruction
+    /// charged as the innermost frame
+    pub fn write_instruction_stacks(&self, w: &mut impl Write) -> io::Result<()> {
+        for ((stack, kind), charges) in &self.charges {
+            if charges.gas > 0 {
+                writeln!(w, "{};{} {}", stack.join(";"), kind, charges.gas)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a table with the number of charges and the gas charged by kind of instruction, from
+    /// the most expensive one
+    pub fn write_instruction_breakdown(&self, w: &mut impl Write) -> io::Result<()> {
+        let mut kinds: BTreeMap<&str, Charges> = BTreeMap::new();
+        for ((_, kind), charges) in &self.charges {
+            let total = kinds.entry(*kind).or_default();
+            total.count += charges.count;
+            total.gas += charges.gas;
+        }
+        let mut kinds = kinds.into_iter().collect::<Vec<_>>();
+        kinds.sort_by(|(_, c1), (_, c2)| c2.gas.cmp(&c1.gas));
+
+        let total_gas = self.total_gas();
+        writeln!(
+            w,
+            "{:<24} {:>12} {:>16} {:>8}",
+            "Instruction", "Count", "Gas", "Share"
+        )?;
+        for (kind, charges) in kinds {
+            let share = if total_gas == 0 {
+                0.0
+            } else {
+                charges.gas as f64 * 100.0 / total_gas as f64
+            };
+            writeln!(
+                w,
+                "{:<24} {:>12} {:>16} {:>7.2}%",
+                kind, charges.count, charges.gas, share
+            )?;
+        }
+        writeln!(w, "{:<24} {:>12} {:>16}", "Total", "", total_gas)
+    }
+
+    /// Writes the folded stacks of functions and of instructions to `FUNCTION_STACKS_FILE_NAME`
+    /// and `INSTRUCTION_STACKS_FILE_NAME` in `dir`, which is created if needed
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let mut w = BufWriter::new(File::create(dir.join(FUNCTION_STACKS_FILE_NAME))?);
+        self.write_function_stacks(&mut w)?;
+        w.flush()?;
+        let mut w = BufWriter::new(File::create(dir.join(INSTRUCTION_STACKS_FILE_NAME))?);
+        self.write_instruction_stacks(&mut w)?;
+        w.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas_schedule::{
+        new_from_instructions, zero_cost_instruction_table, CostTable, Gas, GasCost, GasStatus,
+    };
+    use move_core_types::{
+        account_address::AccountAddress, identifier::Identifier, language_storage::TypeTag,
+    };
+    use move_vm_types::values::Value;
+    use std::iter;
+
+    struct U64Type;
+
+    impl TypeView for U64Type {
+        fn to_type_tag(&self) -> TypeTag {
+            TypeTag::U64
+        }
+    }
+
+    /// A cost table where every instruction costs one unit of internal gas, per unit of size
+    fn unit_cost_table() -> CostTable {
+        let instrs = zero_cost_instruction_table()
+            .into_iter()
+            .map(|(instr, _)| (instr, GasCost::new(1, 0)))
+            .collect();
+        new_from_instructions(instrs)
+    }
+
+    fn module(name: &str) -> ModuleId {
+        ModuleId::new(AccountAddress::ONE, Identifier::new(name).unwrap())
+    }
+
+    fn call<G: GasMeter>(meter: &mut G, module_id: &ModuleId, func_name: &str, args: &[Value]) {
+        meter
+            .charge_call(
+                module_id,
+                func_name,
+                args.iter(),
+                NumArgs::new(args.len() as u64),
+            )
+            .unwrap()
+    }
+
+    fn ret<G: GasMeter>(meter: &mut G) {
+        meter.charge_simple_instr(SimpleInstruction::Ret).unwrap();
+        meter.charge_drop_frame(iter::empty::<Value>()).unwrap();
+    }
+
+    fn function_stacks(profile: &GasProfile) -> String {
+        let mut out = vec![];
+        profile.write_function_stacks(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn instruction_stacks(profile: &GasProfile) -> String {
+        let mut out = vec![];
+        profile.write_instruction_stacks(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn nested_calls() {
+        let cost_table = unit_cost_table();
+        let base = GasStatus::new(&cost_table, Gas::new(1000));
+        let mut profiler = GasProfiler::new(base, "0x1::m::f".to_string());
+        let m = module("m");
+
+        profiler
+            .charge_simple_instr(SimpleInstruction::LdU64)
+            .unwrap();
+        call(&mut profiler, &m, "g", &[Value::u64(1)]);
+        profiler
+            .charge_simple_instr(SimpleInstruction::Add)
+            .unwrap();
+        call(&mut profiler, &m, "h", &[]);
+        profiler
+            .charge_simple_instr(SimpleInstruction::LdTrue)
+            .unwrap();
+        ret(&mut profiler);
+        ret(&mut profiler);
+        ret(&mut profiler);
+        // charges after the entry function returned are still attributed to it
+        profiler
+            .charge_simple_instr(SimpleInstruction::Nop)
+            .unwrap();
+
+        let (base, profile) = profiler.finish();
+        assert_eq!(
+            profile.total_gas(),
+            1_000_000 - u64::from(base.balance_internal())
+        );
+        assert_eq!(
+            function_stacks(&profile),
+            "0x1::m::f 5\n\
+             0x1::m::f;0x1::m::g 3\n\
+             0x1::m::f;0x1::m::g;0x1::m::h 2\n"
+        );
+        // the frames being dropped are free, so they are not written
+        assert_eq!(
+            instruction_stacks(&profile),
+            "0x1::m::f;Call 2\n\
+             0x1::m::f;LdU64 1\n\
+             0x1::m::f;Nop 1\n\
+             0x1::m::f;Ret 1\n\
+             0x1::m::f;0x1::m::g;Add 1\n\
+             0x1::m::f;0x1::m::g;Call 1\n\
+             0x1::m::f;0x1::m::g;Ret 1\n\
+             0x1::m::f;0x1::m::g;0x1::m::h;LdTrue 1\n\
+             0x1::m::f;0x1::m::g;0x1::m::h;Ret 1\n"
+        );
+    }
+
+    #[test]
+    fn native_calls() {
+        let cost_table = unit_cost_table();
+        let base = GasStatus::new(&cost_table, Gas::new(1000));
+        let mut profiler = GasProfiler::new(base, "0x1::m::f".to_string());
+
+        for _ in 0..2 {
+            call(&mut profiler, &module("hash"), "sha3", &[Value::u64(1)]);
+            profiler
+                .charge_native_function_before_execution(
+                    iter::once(U64Type),
+                    iter::once(Value::u64(1)),
+                )
+                .unwrap();
+            profiler
+                .charge_native_function(InternalGas::new(5), Some(iter::once(Value::u64(2))))
+                .unwrap();
+        }
+        // the native function returned, so this is charged in the caller
+        ret(&mut profiler);
+
+        let (_, profile) = profiler.finish();
+        assert_eq!(
+            function_stacks(&profile),
+            "0x1::m::f 5\n\
+             0x1::m::f;0x1::hash::sha3 10\n"
+        );
+
+        let mut out = vec![];
+        profile.write_instruction_breakdown(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let rows = out
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["Instruction", "Count", "Gas", "Share"],
+                vec!["Native", "4", "10", "66.67%"],
+                vec!["Call", "2", "4", "26.67%"],
+                vec!["Ret", "1", "1", "6.67%"],
+                vec!["DropFrame", "1", "0", "0.00%"],
+                vec!["Total", "15"],
+            ]
+        );
+    }
+
+    #[test]
+    fn aborts() {
+        let cost_table = unit_cost_table();
+        let base = GasStatus::new(&cost_table, Gas::new(1000));
+        let mut profiler = GasProfiler::new(base, "0x1::m::f".to_string());
+
+        // an abort leaves the frames on the stack
+        call(&mut profiler, &module("m"), "g", &[]);
+        profiler
+            .charge_simple_instr(SimpleInstruction::LdU64)
+            .unwrap();
+        profiler
+            .charge_simple_instr(SimpleInstruction::Abort)
+            .unwrap();
+
+        let (_, profile) = profiler.finish();
+        assert_eq!(
+            instruction_stacks(&profile),
+            "0x1::m::f;Call 1\n\
+             0x1::m::f;0x1::m::g;Abort 1\n\
+             0x1::m::f;0x1::m::g;LdU64 1\n"
+        );
+    }
+
+    #[test]
+    fn out_of_gas() {
+        let cost_table = unit_cost_table();
+        let base = GasStatus::new(&cost_table, Gas::new(1));
+        let mut profiler = GasProfiler::new(base, "0x1::m::f".to_string());
+
+        profiler
+            .charge_simple_instr(SimpleInstruction::LdU64)
+            .unwrap();
+        call(&mut profiler, &module("hash"), "sha3", &[]);
+        profiler
+            .charge_native_function_before_execution(
+                iter::empty::<U64Type>(),
+                iter::empty::<Value>(),
+            )
+            .unwrap();
+        // the gas left is charged to the native function that ran out of gas
+        assert!(profiler
+            .charge_native_function(InternalGas::new(5000), None::<iter::Empty<Value>>)
+            .is_err());
+
+        let (_, profile) = profiler.finish();
+        assert_eq!(profile.total_gas(), 1000);
+        assert_eq!(
+            function_stacks(&profile),
+            "0x1::m::f 2\n\
+             0x1::m::f;0x1::hash::sha3 998\n"
+        );
+    }
+
+    #[test]
+    fn merge_and_save() {
+        let cost_table = unit_cost_table();
+        let mut merged = GasProfile::default();
+        for entry in ["0x1::m::f", "0x1::m::g", "0x1::m::f"] {
+            let base = GasStatus::new(&cost_table, Gas::new(1000));
+            let mut profiler = GasProfiler::new(base, entry.to_string());
+            profiler
+                .charge_simple_instr(SimpleInstruction::Ret)
+                .unwrap();
+            merged.merge(profiler.finish().1);
+        }
+        assert_eq!(function_stacks(&merged), "0x1::m::f 2\n0x1::m::g 1\n");
+
+        let dir = std::env::temp_dir().join(format!("gas_profile_{}", std::process::id()));
+        merged.save(&dir).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join(FUNCTION_STACKS_FILE_NAME)).unwrap(),
+            function_stacks(&merged)
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join(INSTRUCTION_STACKS_FILE_NAME)).unwrap(),
+            "0x1::m::f;Ret 2\n0x1::m::g;Ret 1\n"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
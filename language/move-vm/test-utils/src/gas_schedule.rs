@@ -7,6 +7,7 @@
 //! It is important to note that the cost schedule defined in this file does not track hashing
 //! operations or other native operations; the cost of each native operation will be returned by the
 //! native function itself.
+use anyhow::{anyhow, bail, Result};
 use move_binary_format::{
     errors::{PartialVMError, PartialVMResult},
     file_format::{
@@ -32,6 +33,7 @@ use move_vm_types::{
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::{BTreeMap, BTreeSet},
     ops::{Add, Mul},
     u64,
 };
@@ -75,6 +77,53 @@ impl CostTable {
         debug_assert!(instr_index > 0 && instr_index <= (self.instruction_table.len() as u8));
         &self.instruction_table[(instr_index - 1) as usize]
     }
+
+    /// The costs of the instructions, keyed by the names of the instructions without their
+    /// operands, e.g. `MoveTo` or `LdU64`
+    pub fn named_instruction_costs(&self) -> BTreeMap<String, GasCost> {
+        zero_cost_instruction_table()
+            .iter()
+            .filter_map(|(instr, _)| {
+                let cost = self
+                    .instruction_table
+                    .get((instruction_key(instr) - 1) as usize)?;
+                Some((instruction_name(instr), cost.clone()))
+            })
+            .collect()
+    }
+
+    /// Creates a table from the costs of the instructions keyed by their names, as returned by
+    /// `named_instruction_costs`. Every instruction must be given a cost.
+    pub fn from_named_instruction_costs(costs: &BTreeMap<String, GasCost>) -> Result<Self> {
+        let instrs = zero_cost_instruction_table();
+        let names = instrs
+            .iter()
+            .map(|(instr, _)| instruction_name(instr))
+            .collect::<BTreeSet<_>>();
+        if let Some(name) = costs.keys().find(|name| !names.contains(*name)) {
+            bail!("Unknown instruction {}", name)
+        }
+        let instrs = instrs
+            .into_iter()
+            .map(|(instr, _)| {
+                let name = instruction_name(&instr);
+                match costs.get(&name) {
+                    Some(cost) => Ok((instr, cost.clone())),
+                    None => Err(anyhow!("Missing cost for instruction {}", name)),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(new_from_instructions(instrs))
+    }
+}
+
+/// The name of an instruction without its operands
+fn instruction_name(instr: &Bytecode) -> String {
+    let name = format!("{:?}", instr);
+    match name.find('(') {
+        Some(idx) => name[..idx].to_string(),
+        None => name,
+    }
 }
 
 /// The  `GasCost` tracks:
@@ -6,5 +6,6 @@
 
 mod storage;
 
+pub mod gas_profiler;
 pub mod gas_schedule;
 pub use storage::{BlankStorage, DeltaStorage, InMemoryStorage};
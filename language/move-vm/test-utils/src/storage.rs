@@ -19,6 +19,7 @@ use std::{
 use {
     anyhow::Error,
     move_table_extension::{TableChangeSet, TableHandle, TableResolver},
+    std::ops::Bound,
 };
 
 /// A dummy storage containing no modules or resources.
@@ -60,6 +61,19 @@ impl TableResolver for BlankStorage {
     ) -> Result<Option<Vec<u8>>, Error> {
         Ok(None)
     }
+
+    fn resolve_table_length(&self, _handle: &TableHandle) -> Result<u64, Error> {
+        Ok(0)
+    }
+
+    fn resolve_table_keys(
+        &self,
+        _handle: &TableHandle,
+        _after: Option<&[u8]>,
+        _limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        Ok(vec![])
+    }
 }
 
 /// A storage adapter created by stacking a change set on top of an existing storage backend.
@@ -112,6 +126,19 @@ impl<'a, 'b, S: TableResolver> TableResolver for DeltaStorage<'a, 'b, S> {
         // TODO: No support for table deltas
         self.base.resolve_table_entry(handle, key)
     }
+
+    fn resolve_table_length(&self, handle: &TableHandle) -> std::result::Result<u64, Error> {
+        self.base.resolve_table_length(handle)
+    }
+
+    fn resolve_table_keys(
+        &self,
+        handle: &TableHandle,
+        after: Option<&[u8]>,
+        limit: usize,
+    ) -> std::result::Result<Vec<Vec<u8>>, Error> {
+        self.base.resolve_table_keys(handle, after, limit)
+    }
 }
 
 impl<'a, 'b, S: MoveResolver> DeltaStorage<'a, 'b, S> {
@@ -315,4 +342,26 @@ impl TableResolver for InMemoryStorage {
     ) -> std::result::Result<Option<Vec<u8>>, Error> {
         Ok(self.tables.get(handle).and_then(|t| t.get(key).cloned()))
     }
+
+    fn resolve_table_length(&self, handle: &TableHandle) -> std::result::Result<u64, Error> {
+        Ok(self.tables.get(handle).map_or(0, |t| t.len() as u64))
+    }
+
+    fn resolve_table_keys(
+        &self,
+        handle: &TableHandle,
+        after: Option<&[u8]>,
+        limit: usize,
+    ) -> std::result::Result<Vec<Vec<u8>>, Error> {
+        let lower = match after {
+            Some(key) => Bound::Excluded(key),
+            None => Bound::Unbounded,
+        };
+        Ok(self.tables.get(handle).map_or_else(Vec::new, |t| {
+            t.range::<[u8], _>((lower, Bound::Unbounded))
+                .take(limit)
+                .map(|(key, _)| key.clone())
+                .collect()
+        }))
+    }
 }
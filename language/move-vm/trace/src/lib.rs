@@ -0,0 +1,181 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Structured traces of the execution of Move code.
+//!
+//! A trace is a file of JSON lines: a `TraceHeader` with the version of the format, followed by
+//! a `TraceEvent` for every instruction executed, in order. It is recorded by installing a
+//! `TraceRecorder` as the debugger of the thread running the VM.
+
+mod recorder;
+
+pub use recorder::TraceRecorder;
+
+use anyhow::{bail, Context, Result};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+};
+use move_vm_runtime::debugger::GlobalAccessKind;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+/// The version of the trace format, to be bumped on changes that break existing readers
+pub const TRACE_FORMAT_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceHeader {
+    pub version: u64,
+}
+
+/// The state of the execution before an instruction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEvent {
+    /// The module of the function executing the instruction, or `None` for a script
+    pub module: Option<ModuleId>,
+    pub function: String,
+    pub function_index: u16,
+    pub pc: u16,
+    pub instruction: String,
+    /// The number of active functions, i.e. 1 in the entry function
+    pub depth: usize,
+    /// The values on the operand stack, bottom first
+    pub operand_stack: Vec<String>,
+    /// The values of the locals of the function, `None` for the locals holding no value
+    pub locals: Vec<Option<String>>,
+    /// The gas left before the instruction is charged
+    pub gas_remaining: u64,
+    /// The access to global storage performed by the instruction, if any
+    pub effect: Option<StorageEffect>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageEffect {
+    pub kind: StorageEffectKind,
+    pub address: AccountAddress,
+    pub struct_tag: StructTag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageEffectKind {
+    MoveTo,
+    MoveFrom,
+    BorrowGlobal,
+    BorrowGlobalMut,
+    Exists,
+}
+
+impl From<GlobalAccessKind> for StorageEffectKind {
+    fn from(kind: GlobalAccessKind) -> Self {
+        match kind {
+            GlobalAccessKind::MoveTo => Self::MoveTo,
+            GlobalAccessKind::MoveFrom => Self::MoveFrom,
+            GlobalAccessKind::BorrowGlobal { mutable: false } => Self::BorrowGlobal,
+            GlobalAccessKind::BorrowGlobal { mutable: true } => Self::BorrowGlobalMut,
+            GlobalAccessKind::Exists => Self::Exists,
+        }
+    }
+}
+
+impl TraceEvent {
+    /// The function executing the instruction, e.g. `0x1::vector::append`
+    pub fn qualified_function(&self) -> String {
+        match &self.module {
+            Some(module_id) => format!(
+                "0x{}::{}::{}",
+                module_id.address().short_str_lossless(),
+                module_id.name(),
+                self.function
+            ),
+            None => self.function.clone(),
+        }
+    }
+}
+
+/// Reads the events of the trace at `path`
+pub fn read_trace_file(path: &Path) -> Result<Vec<TraceEvent>> {
+    let file = File::open(path).with_context(|| format!("Unable to open {}", path.display()))?;
+    read_trace(BufReader::new(file))
+}
+
+/// Reads the events of a trace, checking that its format is supported
+pub fn read_trace(input: impl BufRead) -> Result<Vec<TraceEvent>> {
+    let mut lines = input.lines();
+    let header: TraceHeader = match lines.next() {
+        Some(line) => serde_json::from_str(&line?).context("Invalid trace header")?,
+        None => bail!("Empty trace"),
+    };
+    if header.version != TRACE_FORMAT_VERSION {
+        bail!(
+            "Unsupported trace format version {} (expected {})",
+            header.version,
+            TRACE_FORMAT_VERSION
+        );
+    }
+    lines
+        .enumerate()
+        .map(|(idx, line)| {
+            serde_json::from_str(&line?)
+                .with_context(|| format!("Invalid trace event at line {}", idx + 2))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::identifier::Identifier;
+
+    fn event() -> TraceEvent {
+        let module_id = ModuleId::new(AccountAddress::ONE, Identifier::new("m").unwrap());
+        TraceEvent {
+            module: Some(module_id.clone()),
+            function: "f".to_string(),
+            function_index: 0,
+            pc: 3,
+            instruction: "MoveTo(StructDefinitionIndex(0))".to_string(),
+            depth: 1,
+            operand_stack: vec!["(&) { 0x1 }".to_string(), "{ 7 }".to_string()],
+            locals: vec![None, Some("7".to_string())],
+            gas_remaining: 100,
+            effect: Some(StorageEffect {
+                kind: StorageEffectKind::MoveTo,
+                address: AccountAddress::ONE,
+                struct_tag: StructTag {
+                    address: AccountAddress::ONE,
+                    module: module_id.name().to_owned(),
+                    name: Identifier::new("R").unwrap(),
+                    type_params: vec![],
+                },
+            }),
+        }
+    }
+
+    #[test]
+    fn trace_roundtrip() {
+        let mut trace = serde_json::to_string(&TraceHeader {
+            version: TRACE_FORMAT_VERSION,
+        })
+        .unwrap();
+        trace.push('\n');
+        trace.push_str(&serde_json::to_string(&event()).unwrap());
+        assert_eq!(read_trace(trace.as_bytes()).unwrap(), vec![event()]);
+        assert_eq!(event().qualified_function(), "0x1::m::f");
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let trace = serde_json::to_string(&TraceHeader {
+            version: TRACE_FORMAT_VERSION + 1,
+        })
+        .unwrap();
+        assert!(read_trace(trace.as_bytes()).is_err());
+    }
+}
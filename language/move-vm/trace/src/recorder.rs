@@ -0,0 +1,132 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{StorageEffect, TraceEvent, TraceHeader, TRACE_FORMAT_VERSION};
+use move_vm_runtime::debugger::{self, DebugState, Debugger};
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    rc::Rc,
+};
+
+/// A debugger writing a trace of the execution.
+///
+/// ```ignore
+/// let recorder = TraceRecorder::create(Path::new("test.trace.json"))?;
+/// let (result, finished) = recorder.record(|| {
+///     // execute with the VM...
+/// });
+/// finished?;
+/// ```
+///
+/// An error writing the trace does not interrupt the execution: the recorder stops recording,
+/// and the error is returned when the trace is finished.
+pub struct TraceRecorder<W: Write> {
+    writer: W,
+    /// The first error writing the trace, if any
+    error: Option<io::Error>,
+}
+
+/// A recorder installed as the debugger of a thread, shared with `TraceRecorder::record`
+struct InstalledRecorder<W: Write>(Rc<RefCell<TraceRecorder<W>>>);
+
+impl TraceRecorder<BufWriter<File>> {
+    /// Creates a recorder writing to the file at `path`, which is truncated
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> TraceRecorder<W> {
+    /// Creates a recorder writing to `writer`, starting with the header of the trace
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        let header = TraceHeader {
+            version: TRACE_FORMAT_VERSION,
+        };
+        serde_json::to_writer(&mut writer, &header)?;
+        writeln!(writer)?;
+        Ok(Self {
+            writer,
+            error: None,
+        })
+    }
+
+    /// Flushes the trace, returning the first error writing it, if any
+    pub fn finish(&mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => self.writer.flush(),
+        }
+    }
+
+    fn write_event(&mut self, event: &TraceEvent) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, event)?;
+        writeln!(self.writer)
+    }
+}
+
+impl<W: Write + 'static> TraceRecorder<W> {
+    /// Records the execution of `f`, installing the recorder as the debugger of the current thread
+    /// meanwhile. Returns the result of `f`, and the result of finishing the trace.
+    pub fn record<T>(self, f: impl FnOnce() -> T) -> (T, io::Result<()>) {
+        let recorder = Rc::new(RefCell::new(self));
+        let previous = debugger::set_debugger(Some(Box::new(InstalledRecorder(recorder.clone()))));
+        let result = f();
+        debugger::set_debugger(previous);
+        let finished = recorder.borrow_mut().finish();
+        (result, finished)
+    }
+}
+
+impl<W: Write> Debugger for TraceRecorder<W> {
+    fn on_instruction(&mut self, state: &DebugState) {
+        if self.error.is_some() {
+            return;
+        }
+        let frame = state.frames().last().unwrap();
+        let locals = (0..frame.local_count())
+            .map(|idx| match frame.local(idx) {
+                Ok(Some(local)) => Some(match local.reference {
+                    Some(true) => format!("&mut {}", local.value),
+                    Some(false) => format!("&{}", local.value),
+                    None => local.value.to_string(),
+                }),
+                Ok(None) => None,
+                Err(_) => Some("<unprintable>".to_string()),
+            })
+            .collect();
+        let event = TraceEvent {
+            module: frame.module_id().cloned(),
+            function: frame.function_name().to_string(),
+            function_index: frame.function_index().0,
+            pc: frame.pc(),
+            instruction: format!("{:?}", state.instruction()),
+            depth: state.frames().len(),
+            operand_stack: state.operand_stack(),
+            locals,
+            gas_remaining: state.remaining_gas().into(),
+            effect: state.global_access().map(|access| StorageEffect {
+                kind: access.kind.into(),
+                address: access.address,
+                struct_tag: access.struct_tag,
+            }),
+        };
+        if let Err(err) = self.write_event(&event) {
+            self.error = Some(err);
+        }
+    }
+}
+
+impl<W: Write> Debugger for InstalledRecorder<W> {
+    fn on_instruction(&mut self, state: &DebugState) {
+        self.0.borrow_mut().on_instruction(state)
+    }
+}
+
+impl<W: Write> Drop for TraceRecorder<W> {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
@@ -45,6 +45,10 @@ pub trait DataStore {
     /// Check if this module exists.
     fn exists_module(&self, module_id: &ModuleId) -> VMResult<bool>;
 
+    /// Record that a module was found in the code cache of the VM, and so was not loaded from
+    /// remote storage.
+    fn record_module_cache_hit(&self, _module_id: &ModuleId) {}
+
     // ---
     // EventStore operations
     // ---
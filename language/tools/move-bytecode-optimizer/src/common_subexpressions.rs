@@ -0,0 +1,99 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{basic_blocks, modified_local, FunctionContext};
+use move_binary_format::file_format::Bytecode;
+
+/// Within a basic block, an operation on two operands whose result is stored in a local, e.g.
+/// `CopyLoc(a); LdU64(1); Add; StLoc(t)`, does not need to be computed again: later occurrences
+/// of `CopyLoc(a); LdU64(1); Add` become `CopyLoc(t)`, as long as neither `a` nor `t` is modified
+/// in between.
+///
+/// Only operations without side effects other than aborting are considered, on operands which
+/// are copies of locals or constants. Reusing the result of an operation which did not abort is
+/// safe, as computing it again would not have aborted either.
+#[allow(clippy::ptr_arg)]
+pub fn optimize(_context: &FunctionContext, code: &mut Vec<Bytecode>) -> bool {
+    let mut changed = false;
+    for block in basic_blocks(code) {
+        for i in block.start..block.end.saturating_sub(3) {
+            let expr = &code[i..i + 3];
+            if !is_operand(&expr[0]) || !is_operand(&expr[1]) || !is_pure_operation(&expr[2]) {
+                continue;
+            }
+            let result = match code[i + 3] {
+                Bytecode::StLoc(result) => result,
+                _ => continue,
+            };
+            let expr = expr.to_vec();
+            let operand_locals = expr[..2]
+                .iter()
+                .filter_map(|instr| match instr {
+                    Bytecode::CopyLoc(local) => Some(*local),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            // The result overwrites an operand: later occurrences compute another value
+            if operand_locals.contains(&result) {
+                continue;
+            }
+
+            let mut j = i + 4;
+            while j < block.end {
+                if j + 3 <= block.end && code[j..j + 3] == expr[..] {
+                    code[j] = Bytecode::CopyLoc(result);
+                    code[j + 1] = Bytecode::Nop;
+                    code[j + 2] = Bytecode::Nop;
+                    changed = true;
+                    j += 3;
+                    continue;
+                }
+                if let Some(local) = modified_local(&code[j]) {
+                    if local == result || operand_locals.contains(&local) {
+                        break;
+                    }
+                }
+                j += 1;
+            }
+        }
+    }
+    changed
+}
+
+fn is_operand(instr: &Bytecode) -> bool {
+    matches!(
+        instr,
+        Bytecode::CopyLoc(_)
+            | Bytecode::LdU8(_)
+            | Bytecode::LdU16(_)
+            | Bytecode::LdU32(_)
+            | Bytecode::LdU64(_)
+            | Bytecode::LdU128(_)
+            | Bytecode::LdU256(_)
+            | Bytecode::LdConst(_)
+            | Bytecode::LdTrue
+            | Bytecode::LdFalse
+    )
+}
+
+fn is_pure_operation(instr: &Bytecode) -> bool {
+    matches!(
+        instr,
+        Bytecode::Add
+            | Bytecode::Sub
+            | Bytecode::Mul
+            | Bytecode::Div
+            | Bytecode::Mod
+            | Bytecode::BitOr
+            | Bytecode::BitAnd
+            | Bytecode::Xor
+            | Bytecode::Shl
+            | Bytecode::Shr
+            | Bytecode::Or
+            | Bytecode::And
+            | Bytecode::Lt
+            | Bytecode::Gt
+            | Bytecode::Le
+            | Bytecode::Ge
+    )
+}
@@ -0,0 +1,42 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_binary_format::{
+    file_format::{Bytecode, ConstantPoolIndex, TableIndex},
+    CompiledModule,
+};
+use std::collections::HashMap;
+
+/// Removes the duplicates from the constant pool of `module`, loading the first occurrence of a
+/// constant wherever one of its duplicates was loaded. Returns whether the pool changed.
+pub fn deduplicate(module: &mut CompiledModule) -> bool {
+    let mut pool = vec![];
+    let mut first_occurrences = HashMap::new();
+    // new_indices[i] is the index in the deduplicated pool of the constant at index i
+    let mut new_indices = vec![];
+    for constant in module.constant_pool.drain(..) {
+        let idx = *first_occurrences
+            .entry(constant.clone())
+            .or_insert_with(|| {
+                pool.push(constant);
+                (pool.len() - 1) as TableIndex
+            });
+        new_indices.push(idx);
+    }
+    let changed = pool.len() != new_indices.len();
+    module.constant_pool = pool;
+    if !changed {
+        return false;
+    }
+
+    for def in &mut module.function_defs {
+        if let Some(code_unit) = &mut def.code {
+            for instr in &mut code_unit.code {
+                if let Bytecode::LdConst(idx) = instr {
+                    *idx = ConstantPoolIndex(new_indices[idx.0 as usize]);
+                }
+            }
+        }
+    }
+    true
+}
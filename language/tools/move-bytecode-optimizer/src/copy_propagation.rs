@@ -0,0 +1,39 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{basic_blocks, modified_local, FunctionContext};
+use move_binary_format::file_format::Bytecode;
+
+/// After a copy `CopyLoc(x); StLoc(y)`, reads `CopyLoc(y)` later in the basic block become
+/// `CopyLoc(x)`, as long as neither local is modified in between. The store to `y` is then
+/// often dead, and removed by dead store elimination.
+///
+/// Copies of references are left alone, as reading a reference from another local changes what
+/// it is borrowed from.
+#[allow(clippy::ptr_arg)]
+pub fn optimize(context: &FunctionContext, code: &mut Vec<Bytecode>) -> bool {
+    let mut changed = false;
+    for block in basic_blocks(code) {
+        for i in block.start..block.end.saturating_sub(1) {
+            let (source, copy) = match (&code[i], &code[i + 1]) {
+                (Bytecode::CopyLoc(source), Bytecode::StLoc(copy))
+                    if source != copy && !context.is_reference(*source) =>
+                {
+                    (*source, *copy)
+                }
+                _ => continue,
+            };
+            for instr in &mut code[i + 2..block.end] {
+                if *instr == Bytecode::CopyLoc(copy) {
+                    *instr = Bytecode::CopyLoc(source);
+                    changed = true;
+                } else if let Some(local) = modified_local(instr) {
+                    if local == source || local == copy {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    changed
+}
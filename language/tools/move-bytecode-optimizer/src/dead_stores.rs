@@ -0,0 +1,92 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{basic_blocks, FunctionContext};
+use move_binary_format::file_format::{Bytecode, CodeOffset, LocalIndex};
+use std::{collections::BTreeSet, ops::Range};
+
+/// Replaces the stores to locals which are not read afterwards, on any path, with `Pop`s. Stores
+/// of values without the `drop` ability are kept, as such values cannot be popped.
+#[allow(clippy::ptr_arg)]
+pub fn optimize(context: &FunctionContext, code: &mut Vec<Bytecode>) -> bool {
+    let blocks = basic_blocks(code);
+    let live_out = live_locals_after_blocks(code, &blocks);
+
+    let mut changed = false;
+    for (block, live_out) in blocks.into_iter().zip(live_out) {
+        let mut live = live_out;
+        for i in block.rev() {
+            if let Bytecode::StLoc(local) = code[i] {
+                if !live.contains(&local) && context.has_drop(local) {
+                    code[i] = Bytecode::Pop;
+                    changed = true;
+                }
+            }
+            update_live_locals(&code[i], &mut live);
+        }
+    }
+    changed
+}
+
+/// The locals which may be read after each block, computed by iterating a backward dataflow
+/// analysis until it reaches a fixpoint
+fn live_locals_after_blocks(
+    code: &[Bytecode],
+    blocks: &[Range<usize>],
+) -> Vec<BTreeSet<LocalIndex>> {
+    let block_index = |offset: usize| {
+        blocks
+            .iter()
+            .position(|block| block.start == offset)
+            .expect("branches go to the start of a block")
+    };
+    let successors = blocks
+        .iter()
+        .map(|block| {
+            Bytecode::get_successors((block.end - 1) as CodeOffset, code)
+                .into_iter()
+                .map(|offset| block_index(offset as usize))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut live_in = vec![BTreeSet::new(); blocks.len()];
+    let mut live_out = vec![BTreeSet::new(); blocks.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for idx in (0..blocks.len()).rev() {
+            let out = successors[idx]
+                .iter()
+                .flat_map(|succ| live_in[*succ].iter().copied())
+                .collect::<BTreeSet<_>>();
+            let mut live = out.clone();
+            for instr in code[blocks[idx].clone()].iter().rev() {
+                update_live_locals(instr, &mut live);
+            }
+            live_out[idx] = out;
+            if live != live_in[idx] {
+                live_in[idx] = live;
+                changed = true;
+            }
+        }
+    }
+    live_out
+}
+
+/// Turns the locals live after `instr` into the locals live before it. Borrows count as reads, as
+/// the local may be read through the reference.
+fn update_live_locals(instr: &Bytecode, live: &mut BTreeSet<LocalIndex>) {
+    match instr {
+        Bytecode::StLoc(local) => {
+            live.remove(local);
+        }
+        Bytecode::CopyLoc(local)
+        | Bytecode::MoveLoc(local)
+        | Bytecode::ImmBorrowLoc(local)
+        | Bytecode::MutBorrowLoc(local) => {
+            live.insert(*local);
+        }
+        _ => (),
+    }
+}
@@ -0,0 +1,233 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An optimizer for the code of compiled modules. It works on `CodeUnit`s, after the compiler has
+//! run its own optimizations, and applies:
+//! - peephole rules, e.g. removing a `CopyLoc` whose value is immediately popped,
+//! - copy propagation, reading a local instead of the copy of it stored in another local,
+//! - dead store elimination, popping values stored in locals which are never read again,
+//! - common subexpression elimination for pure arithmetic, reading the result of an operation
+//!   from the local it was stored in instead of computing it again,
+//! - deduplication of the constant pool.
+//!
+//! The optimized module is checked with the bytecode verifier. The code of any function rejected
+//! by the verifier is restored to its original version, so that optimizing never makes a module
+//! unpublishable. Code offsets change with the optimizations: source maps of the original module
+//! no longer apply to the optimized one.
+
+mod common_subexpressions;
+mod constants;
+mod copy_propagation;
+mod dead_stores;
+mod peephole;
+
+use anyhow::{bail, Result};
+use move_binary_format::{
+    access::ModuleAccess,
+    binary_views::BinaryIndexedView,
+    control_flow_graph::{ControlFlowGraph, VMControlFlowGraph},
+    file_format::{
+        AbilitySet, Bytecode, CodeOffset, FunctionDefinitionIndex, LocalIndex, SignatureToken,
+        TableIndex,
+    },
+    CompiledModule, IndexKind,
+};
+use move_core_types::identifier::Identifier;
+use std::{collections::BTreeSet, ops::Range};
+
+/// An optimization of the code of a function, returning whether the code changed. Optimizations
+/// replace the instructions they remove with `Nop`s, which are removed after each optimization.
+pub type Optimization = fn(&FunctionContext, &mut Vec<Bytecode>) -> bool;
+
+const OPTIMIZATIONS: &[Optimization] = &[
+    peephole::optimize,
+    copy_propagation::optimize,
+    dead_stores::optimize,
+    common_subexpressions::optimize,
+];
+
+/// What the code of a function is optimized against
+pub struct FunctionContext<'a> {
+    module: &'a CompiledModule,
+    type_parameters: &'a [AbilitySet],
+    /// The types of the parameters followed by the types of the other locals
+    local_types: Vec<SignatureToken>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OptimizationStats {
+    pub instructions_before: usize,
+    pub instructions_after: usize,
+    pub constants_before: usize,
+    pub constants_after: usize,
+    /// The functions whose optimized code was rejected by the verifier, and kept unoptimized
+    pub reverted_functions: Vec<Identifier>,
+}
+
+/// Optimizes the code of `module`, which must pass the bytecode verifier. The optimized module
+/// passes the verifier as well.
+pub fn optimize_module(module: &CompiledModule) -> Result<(CompiledModule, OptimizationStats)> {
+    if let Err(err) = move_bytecode_verifier::verify_module(module) {
+        bail!(
+            "Module {} does not pass the bytecode verifier: {:?}",
+            module.self_id(),
+            err
+        )
+    }
+
+    let mut optimized = module.clone();
+    constants::deduplicate(&mut optimized);
+    for idx in 0..optimized.function_defs.len() {
+        let mut code = match &optimized.function_defs[idx].code {
+            Some(code_unit) => code_unit.code.clone(),
+            None => continue,
+        };
+        let context = FunctionContext::new(&optimized, FunctionDefinitionIndex(idx as TableIndex));
+        optimize_code(&context, &mut code);
+        if let Some(code_unit) = &mut optimized.function_defs[idx].code {
+            code_unit.code = code;
+        }
+    }
+
+    // Restore the functions rejected by the verifier one at a time, as the verifier stops at the
+    // first function it rejects
+    let mut reverted = BTreeSet::new();
+    while let Err(err) = move_bytecode_verifier::verify_module(&optimized) {
+        let idx = err.indices().iter().find_map(|(kind, idx)| {
+            (*kind == IndexKind::FunctionDefinition).then_some(*idx as usize)
+        });
+        match idx {
+            Some(idx) if reverted.insert(idx) => {
+                optimized.function_defs[idx].code = module.function_defs[idx].code.clone();
+            }
+            _ => bail!(
+                "Optimized module {} does not pass the bytecode verifier: {:?}",
+                module.self_id(),
+                err
+            ),
+        }
+    }
+
+    let stats = OptimizationStats {
+        instructions_before: num_instructions(module),
+        instructions_after: num_instructions(&optimized),
+        constants_before: module.constant_pool.len(),
+        constants_after: optimized.constant_pool.len(),
+        reverted_functions: reverted
+            .into_iter()
+            .map(|idx| {
+                let handle = module.function_handle_at(module.function_defs[idx].function);
+                module.identifier_at(handle.name).to_owned()
+            })
+            .collect(),
+    };
+    Ok((optimized, stats))
+}
+
+/// Runs the optimizations on `code` until none of them changes it
+pub fn optimize_code(context: &FunctionContext, code: &mut Vec<Bytecode>) {
+    let mut count = 0;
+    for optimization in OPTIMIZATIONS.iter().cycle() {
+        // if we have fully cycled through the list of optimizations without a change,
+        // it is safe to stop
+        if count >= OPTIMIZATIONS.len() {
+            debug_assert_eq!(count, OPTIMIZATIONS.len());
+            break;
+        }
+
+        // reset the count if something has changed
+        if optimization(context, code) {
+            remove_nops(code);
+            count = 0
+        } else {
+            count += 1
+        }
+    }
+}
+
+fn num_instructions(module: &CompiledModule) -> usize {
+    module
+        .function_defs
+        .iter()
+        .filter_map(|def| def.code.as_ref())
+        .map(|code_unit| code_unit.code.len())
+        .sum()
+}
+
+impl<'a> FunctionContext<'a> {
+    pub fn new(module: &'a CompiledModule, idx: FunctionDefinitionIndex) -> Self {
+        let def = module.function_def_at(idx);
+        let handle = module.function_handle_at(def.function);
+        let mut local_types = module.signature_at(handle.parameters).0.clone();
+        if let Some(code_unit) = &def.code {
+            local_types.extend(module.signature_at(code_unit.locals).0.iter().cloned());
+        }
+        Self {
+            module,
+            type_parameters: &handle.type_parameters,
+            local_types,
+        }
+    }
+
+    /// Whether the values of `local` can be dropped
+    fn has_drop(&self, local: LocalIndex) -> bool {
+        let ty = &self.local_types[local as usize];
+        BinaryIndexedView::Module(self.module)
+            .abilities(ty, self.type_parameters)
+            .map_or(false, |abilities| abilities.has_drop())
+    }
+
+    /// Whether the values of `local` are references
+    fn is_reference(&self, local: LocalIndex) -> bool {
+        matches!(
+            self.local_types[local as usize],
+            SignatureToken::Reference(_) | SignatureToken::MutableReference(_)
+        )
+    }
+}
+
+/// The ranges of the instructions of the basic blocks of `code`
+fn basic_blocks(code: &[Bytecode]) -> Vec<Range<usize>> {
+    if code.is_empty() {
+        return vec![];
+    }
+    let cfg = VMControlFlowGraph::new(code);
+    cfg.blocks()
+        .into_iter()
+        .map(|block| cfg.block_start(block) as usize..cfg.block_end(block) as usize + 1)
+        .collect()
+}
+
+/// The local whose value is replaced, moved or mutably borrowed by `instr`
+fn modified_local(instr: &Bytecode) -> Option<LocalIndex> {
+    match instr {
+        Bytecode::StLoc(local) | Bytecode::MoveLoc(local) | Bytecode::MutBorrowLoc(local) => {
+            Some(*local)
+        }
+        _ => None,
+    }
+}
+
+/// Removes the `Nop`s of `code`, redirecting branches to a `Nop` to the instruction after it
+fn remove_nops(code: &mut Vec<Bytecode>) {
+    // new_offsets[i] is the offset of the first instruction kept at or after i
+    let mut new_offsets = Vec::with_capacity(code.len() + 1);
+    let mut num_kept = 0;
+    for instr in code.iter() {
+        new_offsets.push(num_kept as CodeOffset);
+        if !matches!(instr, Bytecode::Nop) {
+            num_kept += 1;
+        }
+    }
+    new_offsets.push(num_kept as CodeOffset);
+
+    code.retain(|instr| !matches!(instr, Bytecode::Nop));
+    for instr in code.iter_mut() {
+        match instr {
+            Bytecode::Branch(offset) | Bytecode::BrTrue(offset) | Bytecode::BrFalse(offset) => {
+                *offset = new_offsets[*offset as usize]
+            }
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::Context;
+use clap::Parser;
+use move_binary_format::CompiledModule;
+use move_bytecode_optimizer::optimize_module;
+use std::{fs, path::PathBuf};
+
+#[derive(Debug, Parser)]
+#[clap(author, version, about)]
+struct Args {
+    /// The bytecode file of the module to optimize
+    #[clap(parse(from_os_str))]
+    pub input: PathBuf,
+
+    /// Where to write the optimized module. The source map of the module does not apply to the
+    /// optimized one.
+    #[clap(short = 'o', long = "output", parse(from_os_str))]
+    pub output: PathBuf,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let bytes = fs::read(&args.input)
+        .with_context(|| format!("Unable to read {}", args.input.display()))?;
+    let module = CompiledModule::deserialize(&bytes)
+        .with_context(|| format!("Unable to deserialize {}", args.input.display()))?;
+    let (optimized, stats) = optimize_module(&module)?;
+
+    let mut bytes = vec![];
+    optimized.serialize(&mut bytes)?;
+    fs::write(&args.output, bytes)
+        .with_context(|| format!("Unable to write {}", args.output.display()))?;
+
+    println!(
+        "Instructions: {} -> {}",
+        stats.instructions_before, stats.instructions_after
+    );
+    println!(
+        "Constants: {} -> {}",
+        stats.constants_before, stats.constants_after
+    );
+    for function in &stats.reverted_functions {
+        println!(
+            "Kept function {} unoptimized: its optimized code does not pass the verifier",
+            function
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{basic_blocks, FunctionContext};
+use move_binary_format::file_format::Bytecode;
+
+/// Rewrites pairs of consecutive instructions of a basic block:
+/// - a value loaded and popped right away is never loaded: `CopyLoc(_); Pop` becomes nothing,
+///   as do constants and borrows of locals followed by `Pop`,
+/// - a value stored and moved back right away stays on the stack: `StLoc(x); MoveLoc(x)` becomes
+///   nothing,
+/// - branches on a constant become unconditional: `LdTrue; BrTrue(l)` becomes `Branch(l)` and
+///   `LdTrue; BrFalse(l)` becomes nothing,
+/// - a negated condition flips the branch: `Not; BrTrue(l)` becomes `BrFalse(l)`,
+/// - double negations cancel out: `Not; Not` becomes nothing,
+/// - a mutable borrow of a local frozen right away is an immutable borrow:
+///   `MutBorrowLoc(x); FreezeRef` becomes `ImmBorrowLoc(x)`.
+///
+/// A branch to the next instruction is removed as well.
+#[allow(clippy::ptr_arg)]
+pub fn optimize(_context: &FunctionContext, code: &mut Vec<Bytecode>) -> bool {
+    use Bytecode as B;
+
+    let mut changed = false;
+    for block in basic_blocks(code) {
+        let mut i = block.start;
+        while i < block.end {
+            if let B::Branch(target) = code[i] {
+                if target as usize == i + 1 {
+                    code[i] = B::Nop;
+                    changed = true;
+                }
+            }
+            if i + 1 == block.end {
+                break;
+            }
+            let replacement = match (&code[i], &code[i + 1]) {
+                (
+                    B::CopyLoc(_)
+                    | B::ImmBorrowLoc(_)
+                    | B::MutBorrowLoc(_)
+                    | B::LdU8(_)
+                    | B::LdU16(_)
+                    | B::LdU32(_)
+                    | B::LdU64(_)
+                    | B::LdU128(_)
+                    | B::LdU256(_)
+                    | B::LdConst(_)
+                    | B::LdTrue
+                    | B::LdFalse,
+                    B::Pop,
+                )
+                | (B::LdTrue, B::BrFalse(_))
+                | (B::LdFalse, B::BrTrue(_))
+                | (B::Not, B::Not) => Some((B::Nop, B::Nop)),
+                (B::StLoc(stored), B::MoveLoc(moved)) if stored == moved => Some((B::Nop, B::Nop)),
+                (B::LdTrue, B::BrTrue(target)) | (B::LdFalse, B::BrFalse(target)) => {
+                    Some((B::Nop, B::Branch(*target)))
+                }
+                (B::Not, B::BrTrue(target)) => Some((B::Nop, B::BrFalse(*target))),
+                (B::Not, B::BrFalse(target)) => Some((B::Nop, B::BrTrue(*target))),
+                (B::MutBorrowLoc(local), B::FreezeRef) => Some((B::ImmBorrowLoc(*local), B::Nop)),
+                _ => None,
+            };
+            match replacement {
+                Some((first, second)) => {
+                    code[i] = first;
+                    code[i + 1] = second;
+                    changed = true;
+                    i += 2;
+                }
+                None => i += 1,
+            }
+        }
+    }
+    changed
+}
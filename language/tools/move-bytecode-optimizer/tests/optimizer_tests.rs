@@ -0,0 +1,145 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_binary_format::{
+    file_format::{
+        empty_module, Bytecode, CodeUnit, Constant, ConstantPoolIndex, FunctionDefinition,
+        FunctionHandle, FunctionHandleIndex, IdentifierIndex, ModuleHandleIndex, Signature,
+        SignatureIndex, SignatureToken, Visibility,
+    },
+    CompiledModule,
+};
+use move_bytecode_optimizer::optimize_module;
+use move_core_types::identifier::Identifier;
+
+/// A module with a function `f(a: u64, b: u64): u64` with two more `u64` locals, whose body is
+/// `code`
+fn module_with_code(code: Vec<Bytecode>) -> CompiledModule {
+    let mut m = empty_module();
+    m.signatures.push(Signature(vec![SignatureToken::U64; 2]));
+    m.signatures.push(Signature(vec![SignatureToken::U64]));
+    m.function_handles.push(FunctionHandle {
+        module: ModuleHandleIndex(0),
+        name: IdentifierIndex(m.identifiers.len() as u16),
+        parameters: SignatureIndex(1),
+        return_: SignatureIndex(2),
+        type_parameters: vec![],
+    });
+    m.identifiers.push(Identifier::new("f").unwrap());
+    m.function_defs.push(FunctionDefinition {
+        function: FunctionHandleIndex(0),
+        visibility: Visibility::Public,
+        is_entry: false,
+        acquires_global_resources: vec![],
+        code: Some(CodeUnit {
+            locals: SignatureIndex(1),
+            code,
+        }),
+    });
+    m
+}
+
+fn optimized_code(module: &CompiledModule) -> Vec<Bytecode> {
+    let (optimized, stats) = optimize_module(module).unwrap();
+    assert!(stats.reverted_functions.is_empty());
+    optimized.function_defs[0]
+        .code
+        .as_ref()
+        .unwrap()
+        .code
+        .clone()
+}
+
+#[test]
+fn reuse_common_subexpressions_and_propagate_copies() {
+    use Bytecode::*;
+    let module = module_with_code(vec![
+        // let t = a + b;
+        CopyLoc(0),
+        CopyLoc(1),
+        Add,
+        StLoc(2),
+        // let c = a;
+        CopyLoc(0),
+        StLoc(3),
+        // (a + b) + c + t
+        CopyLoc(0),
+        CopyLoc(1),
+        Add,
+        CopyLoc(3),
+        Add,
+        CopyLoc(2),
+        Add,
+        Ret,
+    ]);
+    assert_eq!(
+        optimized_code(&module),
+        vec![
+            CopyLoc(0),
+            CopyLoc(1),
+            Add,
+            StLoc(2),
+            CopyLoc(2),
+            CopyLoc(0),
+            Add,
+            CopyLoc(2),
+            Add,
+            Ret,
+        ]
+    );
+}
+
+#[test]
+fn remove_constant_branches_and_unused_values() {
+    use Bytecode::*;
+    let module = module_with_code(vec![
+        LdTrue,
+        BrFalse(5),
+        CopyLoc(1),
+        Pop,
+        Branch(5),
+        CopyLoc(0),
+        Ret,
+    ]);
+    assert_eq!(optimized_code(&module), vec![CopyLoc(0), Ret]);
+}
+
+#[test]
+fn deduplicate_constants() {
+    use Bytecode::*;
+    let mut module = module_with_code(vec![
+        LdConst(ConstantPoolIndex(0)),
+        LdConst(ConstantPoolIndex(1)),
+        Add,
+        LdConst(ConstantPoolIndex(2)),
+        Add,
+        Ret,
+    ]);
+    for value in [7u64, 7, 8] {
+        module.constant_pool.push(Constant {
+            type_: SignatureToken::U64,
+            data: value.to_le_bytes().to_vec(),
+        });
+    }
+
+    let (optimized, stats) = optimize_module(&module).unwrap();
+    assert_eq!((stats.constants_before, stats.constants_after), (3, 2));
+    assert_eq!(optimized.constant_pool[..], module.constant_pool[1..]);
+    assert_eq!(
+        optimized.function_defs[0].code.as_ref().unwrap().code,
+        vec![
+            LdConst(ConstantPoolIndex(0)),
+            LdConst(ConstantPoolIndex(0)),
+            Add,
+            LdConst(ConstantPoolIndex(1)),
+            Add,
+            Ret,
+        ]
+    );
+}
+
+#[test]
+fn reject_unverifiable_modules() {
+    let module = module_with_code(vec![Bytecode::Ret]);
+    assert!(optimize_module(&module).is_err());
+}
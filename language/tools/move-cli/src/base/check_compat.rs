@@ -0,0 +1,162 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::module_versions::{compile_package, load_modules, VersionedModule};
+use anyhow::bail;
+use clap::*;
+use move_binary_format::{
+    access::ModuleAccess,
+    compatibility::{module_changes, CompatibilityGuarantee, ModuleChange, ModuleChangeKind},
+    file_format::{FunctionDefinitionIndex, StructDefinitionIndex},
+    normalized,
+};
+use move_package::BuildConfig;
+use std::path::PathBuf;
+
+/// Check that the modules of a package can be published as an upgrade of an older version of
+/// them, listing every breaking change.
+#[derive(Parser)]
+#[clap(name = "check-compat")]
+pub struct CheckCompat {
+    /// The old version of the modules: a package, a bytecode file, or a directory of bytecode files
+    #[clap(long = "old", parse(from_os_str))]
+    pub old: PathBuf,
+    /// The package with the new version of the modules. Defaults to the current package.
+    #[clap(long = "new", parse(from_os_str))]
+    pub new: Option<PathBuf>,
+    /// The changes to reject: `strict` rejects the changes refused by the VM when publishing,
+    /// `additive-only` rejects any change to the declarations of the old modules, and
+    /// `layout-preserving` only rejects changes to the layout of structs
+    #[clap(long = "policy", arg_enum, default_value = "strict")]
+    pub policy: CompatibilityPolicy,
+}
+
+#[derive(Debug, Clone, Copy, ArgEnum)]
+pub enum CompatibilityPolicy {
+    Strict,
+    AdditiveOnly,
+    LayoutPreserving,
+}
+
+impl CompatibilityPolicy {
+    fn rejects(self, change: &ModuleChange) -> bool {
+        match self {
+            Self::Strict => !change.is_compatible(),
+            Self::AdditiveOnly => true,
+            Self::LayoutPreserving => change
+                .breaks
+                .contains(&CompatibilityGuarantee::StructLayout),
+        }
+    }
+}
+
+impl CheckCompat {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        let new_path = self.new.or(path).unwrap_or_else(|| PathBuf::from("."));
+        let old_modules = load_modules(&self.old, &config)?;
+        let new_modules = compile_package(&new_path, config)?;
+
+        let mut num_rejected = 0;
+        for (module_id, old) in &old_modules {
+            let new = match new_modules.get(module_id) {
+                Some(new) => new,
+                None => {
+                    println!("{}", module_id.short_str_lossless());
+                    println!("  error: the module was removed");
+                    num_rejected += 1;
+                    continue;
+                }
+            };
+            let rejected = module_changes(
+                &normalized::Module::new(&old.module),
+                &normalized::Module::new(&new.module),
+            )
+            .into_iter()
+            .filter(|change| self.policy.rejects(change))
+            .collect::<Vec<_>>();
+            if rejected.is_empty() {
+                continue;
+            }
+            println!("{}", module_id.short_str_lossless());
+            for change in &rejected {
+                println!("  error: {}", change.kind);
+                for guarantee in &change.breaks {
+                    println!("    {}", describe_guarantee(*guarantee));
+                }
+                // Removed members are located in the old module, other changes in the new one
+                let location = match &change.kind {
+                    ModuleChangeKind::StructRemoved(_) | ModuleChangeKind::FunctionRemoved(_) => {
+                        old.location(change)
+                    }
+                    _ => new.location(change),
+                };
+                if let Some(location) = location {
+                    println!("    --> {}", location);
+                }
+            }
+            num_rejected += rejected.len();
+        }
+
+        if num_rejected > 0 {
+            bail!(
+                "Found {} change(s) rejected by the {:?} compatibility policy",
+                num_rejected,
+                self.policy
+            )
+        }
+        println!(
+            "The new modules are compatible with the old ones under the {:?} policy",
+            self.policy
+        );
+        Ok(())
+    }
+}
+
+fn describe_guarantee(guarantee: CompatibilityGuarantee) -> &'static str {
+    match guarantee {
+        CompatibilityGuarantee::StructAndPubFunctionLinking => {
+            "dependent modules may no longer link"
+        }
+        CompatibilityGuarantee::StructLayout => "published structs may no longer be readable",
+        CompatibilityGuarantee::FriendLinking => "friend modules may no longer link",
+    }
+}
+
+impl VersionedModule {
+    /// The location of the declaration affected by `change` in the sources of this module, as
+    /// `file:line:column`
+    fn location(&self, change: &ModuleChange) -> Option<String> {
+        let source = self.source.as_ref()?;
+        let module = &self.module;
+        let loc = match (&change.kind, change.member_name()) {
+            (
+                ModuleChangeKind::StructRemoved(_)
+                | ModuleChangeKind::StructAbilitiesChanged { .. }
+                | ModuleChangeKind::StructTypeParametersChanged(_)
+                | ModuleChangeKind::StructFieldsChanged(_),
+                Some(name),
+            ) => {
+                let idx = module.struct_defs().iter().position(|def| {
+                    module.identifier_at(module.struct_handle_at(def.struct_handle).name) == name
+                })?;
+                source
+                    .source_map
+                    .get_struct_source_map(StructDefinitionIndex(idx as u16))
+                    .ok()?
+                    .definition_location
+            }
+            (_, Some(name)) => {
+                let idx = module.function_defs().iter().position(|def| {
+                    module.identifier_at(module.function_handle_at(def.function).name) == name
+                })?;
+                source
+                    .source_map
+                    .get_function_source_map(FunctionDefinitionIndex(idx as u16))
+                    .ok()?
+                    .definition_location
+            }
+            (_, None) => source.source_map.definition_location,
+        };
+        Some(source.format_loc(loc))
+    }
+}
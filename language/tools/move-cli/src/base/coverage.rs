@@ -2,15 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::reroot_path;
+use crate::COVERAGE_REPORT_FILE;
 use clap::*;
 use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
 use move_coverage::{
-    coverage_map::CoverageMap, format_csv_summary, format_human_summary,
-    source_coverage::SourceCoverageBuilder, summary::summarize_inst_cov,
+    coverage_map::CoverageMap,
+    format_csv_summary, format_human_summary,
+    source_coverage::SourceCoverageBuilder,
+    source_report::{CoverageFormat, SourceReport},
+    summary::summarize_inst_cov,
 };
 use move_disassembler::disassembler::Disassembler;
-use move_package::BuildConfig;
-use std::path::PathBuf;
+use move_package::{compilation::package_layout::CompiledPackageLayout, BuildConfig};
+use std::{fs::File, path::PathBuf};
 
 #[derive(Parser)]
 pub enum CoverageSummaryOptions {
@@ -36,6 +40,24 @@ pub enum CoverageSummaryOptions {
         #[clap(long = "module")]
         module_name: String,
     },
+    /// Export the line and branch coverage of the modules in this package against source code,
+    /// for standard coverage tooling
+    #[clap(name = "export")]
+    Export {
+        /// The format of the coverage report
+        #[clap(long = "format", arg_enum, default_value = "lcov")]
+        format: CoverageFormat,
+        /// The file the coverage report is written to. Defaults to `build/coverage.info` or
+        /// `build/coverage.xml`
+        #[clap(long = "output", short = 'o', parse(from_os_str))]
+        output: Option<PathBuf>,
+        /// Fail if less than this percentage of the lines is covered
+        #[clap(long = "min-line-coverage")]
+        min_line_coverage: Option<f64>,
+        /// Fail if less than this percentage of the outcomes of conditional branches is covered
+        #[clap(long = "min-branch-coverage")]
+        min_branch_coverage: Option<f64>,
+    },
 }
 
 /// Inspect test coverage for this package. A previous test run with the `--coverage` flag must
@@ -49,6 +71,8 @@ pub struct Coverage {
 
 impl Coverage {
     pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        // The output of the report may be relative to the current directory
+        let current_dir = std::env::current_dir()?;
         let path = reroot_path(path)?;
         let coverage_map = CoverageMap::from_binary_file(path.join(".coverage_map.mvcov"))?;
         let package = config.compile_package(&path, &mut Vec::new())?;
@@ -104,7 +128,71 @@ impl Coverage {
                 disassembler.add_coverage_map(coverage_map.to_unified_exec_map());
                 println!("{}", disassembler.disassemble()?);
             }
+            CoverageSummaryOptions::Export {
+                format,
+                output,
+                min_line_coverage,
+                min_branch_coverage,
+            } => {
+                let coverage_map = coverage_map.to_unified_exec_map();
+                let mut report = SourceReport::new();
+                for unit in package.root_modules() {
+                    if let CompiledUnit::Module(NamedCompiledModule {
+                        module, source_map, ..
+                    }) = &unit.unit
+                    {
+                        let source_path = unit.source_path.canonicalize()?;
+                        report.add_module(module, source_map, &source_path, &coverage_map)?;
+                    }
+                }
+
+                let output = match output {
+                    Some(output) => current_dir.join(output),
+                    None => path
+                        .join(CompiledPackageLayout::Root.path())
+                        .join(COVERAGE_REPORT_FILE)
+                        .with_extension(format.extension()),
+                };
+                report.write(
+                    format,
+                    &mut File::create(&output)?,
+                    package.compiled_package_info.package_name.as_str(),
+                    &std::env::current_dir()?,
+                )?;
+
+                let (lines_covered, lines_total) = report.line_coverage();
+                let (branches_covered, branches_total) = report.branch_coverage();
+                let line_coverage = percentage(lines_covered, lines_total);
+                let branch_coverage = percentage(branches_covered, branches_total);
+                println!("Line coverage: {:.2}%", line_coverage);
+                println!("Branch coverage: {:.2}%", branch_coverage);
+                println!("Coverage report written to {}", output.display());
+
+                if let Some(min) = min_line_coverage.filter(|min| line_coverage < *min) {
+                    anyhow::bail!(
+                        "Line coverage of {:.2}% is below the minimum of {:.2}%",
+                        line_coverage,
+                        min
+                    )
+                }
+                if let Some(min) = min_branch_coverage.filter(|min| branch_coverage < *min) {
+                    anyhow::bail!(
+                        "Branch coverage of {:.2}% is below the minimum of {:.2}%",
+                        branch_coverage,
+                        min
+                    )
+                }
+            }
         }
         Ok(())
     }
 }
+
+/// The percentage of `covered` out of `total`, with nothing to cover counting as fully covered
+fn percentage(covered: u64, total: u64) -> f64 {
+    if total == 0 {
+        100f64
+    } else {
+        (covered as f64 / total as f64) * 100f64
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::module_versions::{load_modules, ModuleSource};
+use clap::*;
+use move_disassembler::module_diff::{diff_modules, CodeEdit, Declaration, DeclarationChange};
+use move_package::BuildConfig;
+use std::{collections::BTreeSet, path::PathBuf};
+
+/// Compare two versions of a set of modules declaration by declaration: the structs, functions,
+/// constants, friends and metadata added, removed or changed, with the changes to the code of
+/// functions shown under the source lines they come from.
+#[derive(Parser)]
+#[clap(name = "diff")]
+pub struct Diff {
+    /// The old version of the modules: a package, a bytecode file, or a directory of bytecode files
+    #[clap(long = "old", parse(from_os_str))]
+    pub old: PathBuf,
+    /// The new version of the modules, in the same forms. Defaults to the current package.
+    #[clap(long = "new", parse(from_os_str))]
+    pub new: Option<PathBuf>,
+}
+
+impl Diff {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        let new_path = self.new.or(path).unwrap_or_else(|| PathBuf::from("."));
+        let old_modules = load_modules(&self.old, &config)?;
+        let new_modules = load_modules(&new_path, &config)?;
+
+        let module_ids = old_modules
+            .keys()
+            .chain(new_modules.keys())
+            .collect::<BTreeSet<_>>();
+        let mut is_changed = false;
+        for module_id in module_ids {
+            let (old, new) = match (old_modules.get(module_id), new_modules.get(module_id)) {
+                (Some(old), Some(new)) => (old, new),
+                (Some(_), None) => {
+                    println!("- module {}", module_id.short_str_lossless());
+                    is_changed = true;
+                    continue;
+                }
+                (None, Some(_)) => {
+                    println!("+ module {}", module_id.short_str_lossless());
+                    is_changed = true;
+                    continue;
+                }
+                (None, None) => unreachable!(),
+            };
+            let changes = diff_modules(
+                (&old.module, &old.source_map()?),
+                (&new.module, &new.source_map()?),
+            )?;
+            if changes.is_empty() {
+                continue;
+            }
+            is_changed = true;
+            println!("~ module {}", module_id.short_str_lossless());
+            for change in changes {
+                match change {
+                    DeclarationChange::Added(decl) => {
+                        println!("  + {} {}", decl.kind, decl.name);
+                        print_text("+", &decl);
+                    }
+                    DeclarationChange::Removed(decl) => {
+                        println!("  - {} {}", decl.kind, decl.name);
+                        print_text("-", &decl);
+                    }
+                    DeclarationChange::Changed {
+                        old: old_decl,
+                        new: new_decl,
+                        code,
+                    } => {
+                        println!("  ~ {} {}", new_decl.kind, new_decl.name);
+                        if old_decl.normalized_text != new_decl.normalized_text {
+                            print_text("-", &old_decl);
+                            print_text("+", &new_decl);
+                        }
+                        print_code_edits(&code, old.source.as_ref(), new.source.as_ref());
+                    }
+                }
+            }
+        }
+        if !is_changed {
+            println!("No changes");
+        }
+        Ok(())
+    }
+}
+
+fn print_text(sign: &str, decl: &Declaration) {
+    for line in decl.text.lines() {
+        println!("    {} {}", sign, line.trim_start());
+    }
+}
+
+/// Prints the instructions removed and added, under the source lines they were compiled from
+fn print_code_edits(
+    edits: &[CodeEdit],
+    old_source: Option<&ModuleSource>,
+    new_source: Option<&ModuleSource>,
+) {
+    let mut last_line = None;
+    for edit in edits {
+        let (sign, instr, loc, source) = match edit {
+            CodeEdit::Same(..) => continue,
+            CodeEdit::Removed(instr, loc) => ("-", instr, loc, old_source),
+            CodeEdit::Added(instr, loc) => ("+", instr, loc, new_source),
+        };
+        if let Some(source) = source {
+            let (line, _) = source.line_and_column(*loc);
+            if last_line != Some((&source.path, line)) {
+                println!(
+                    "    {}:{}: {}",
+                    source.path.display(),
+                    line,
+                    source.line(line).trim()
+                );
+                last_line = Some((&source.path, line));
+            }
+        }
+        println!("      {} {}", sign, instr);
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::reroot_path;
+use anyhow::bail;
+use clap::*;
+use move_command_line_common::files::{find_move_filenames, FileHash};
+use move_compiler::{diagnostics, formatter};
+use move_package::{source_package::layout::SourcePackageLayout, BuildConfig};
+use move_symbol_pool::Symbol;
+use std::{collections::HashMap, fs, path::PathBuf};
+
+/// Format the Move source files of the package at `path`.
+#[derive(Parser)]
+#[clap(name = "fmt")]
+pub struct Fmt {
+    /// Do not modify any file, but fail if one of them is not formatted. The unformatted files are
+    /// listed on stdout.
+    #[clap(long)]
+    pub check: bool,
+}
+
+impl Fmt {
+    pub fn execute(self, path: Option<PathBuf>, _config: BuildConfig) -> anyhow::Result<()> {
+        let rerooted_path = reroot_path(path)?;
+        let source_dirs: Vec<_> = [
+            SourcePackageLayout::Sources,
+            SourcePackageLayout::Specifications,
+            SourcePackageLayout::Tests,
+            SourcePackageLayout::Scripts,
+            SourcePackageLayout::Examples,
+        ]
+        .iter()
+        .map(|dir| rerooted_path.join(dir.path()))
+        .filter(|dir| dir.exists())
+        .collect();
+
+        let mut unformatted = vec![];
+        for file in find_move_filenames(&source_dirs, false)? {
+            let source = fs::read_to_string(&file)?;
+            let file_hash = FileHash::new(&source);
+            let formatted = match formatter::format_string(file_hash, &source) {
+                Ok(formatted) => formatted,
+                Err(diags) => {
+                    let files = HashMap::from([(file_hash, (Symbol::from(file.as_str()), source))]);
+                    let buffer = diagnostics::report_diagnostics_to_buffer(&files, diags);
+                    eprint!("{}", String::from_utf8_lossy(&buffer));
+                    bail!("Unable to format '{}'", file)
+                }
+            };
+            if formatted == source {
+                continue;
+            }
+            if self.check {
+                println!("{}", file);
+                unformatted.push(file)
+            } else {
+                fs::write(&file, formatted)?
+            }
+        }
+        if !unformatted.is_empty() {
+            bail!("{} file(s) are not formatted", unformatted.len())
+        }
+        Ok(())
+    }
+}
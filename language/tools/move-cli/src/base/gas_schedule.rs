@@ -0,0 +1,56 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::*;
+use move_unit_test::gas_schedule;
+use move_vm_test_utils::gas_schedule::CostTable;
+use std::path::PathBuf;
+
+/// Export and compare gas schedules, which can be given to `move test --gas-schedule`.
+#[derive(Parser)]
+#[clap(name = "gas-schedule")]
+pub struct GasSchedule {
+    #[clap(subcommand)]
+    pub cmd: GasScheduleCommand,
+}
+
+#[derive(Parser)]
+pub enum GasScheduleCommand {
+    /// Write the gas schedule used by default, to start tuning costs from. The schedule is written
+    /// in JSON if the extension of the file is `json` and in TOML otherwise.
+    #[clap(name = "export")]
+    Export {
+        #[clap(parse(from_os_str))]
+        output: PathBuf,
+    },
+    /// Print the costs which differ between two gas schedules.
+    #[clap(name = "diff")]
+    Diff {
+        #[clap(parse(from_os_str))]
+        old: PathBuf,
+        #[clap(parse(from_os_str))]
+        new: PathBuf,
+    },
+}
+
+impl GasSchedule {
+    pub fn execute(self, cost_table: &CostTable) -> anyhow::Result<()> {
+        match self.cmd {
+            GasScheduleCommand::Export { output } => {
+                gas_schedule::GasSchedule::new(cost_table).save(&output)
+            }
+            GasScheduleCommand::Diff { old, new } => {
+                let old = gas_schedule::GasSchedule::load(&old)?;
+                let new = gas_schedule::GasSchedule::load(&new)?;
+                let changes = old.diff(&new)?;
+                if changes.is_empty() {
+                    println!("The gas schedules are identical");
+                }
+                for change in changes {
+                    println!("{}", change);
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::reroot_path;
+use clap::*;
+use move_compiler::diagnostics::{self, codes::Category};
+use move_package::{compilation::build_plan::BuildPlan, BuildConfig};
+use std::path::PathBuf;
+
+/// Run the built-in lints on the package at `path`, and fail if any of them reports a warning.
+/// If no path is provided defaults to current directory.
+#[derive(Parser)]
+#[clap(name = "lint")]
+pub struct Lint;
+
+impl Lint {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        let rerooted_path = reroot_path(path)?;
+        let mut config = config;
+        // Lints are only run outside of test mode
+        config.test_mode = false;
+        let bytecode_version = config.bytecode_version;
+        let resolution_graph =
+            config.resolution_graph_for_package(&rerooted_path, &mut std::io::stdout())?;
+
+        let mut lint_warnings = 0;
+        BuildPlan::create(resolution_graph)?.compile_with_driver(
+            &mut std::io::stdout(),
+            bytecode_version,
+            |compiler| {
+                let (files, units_res) = compiler.build()?;
+                let (units, warnings) =
+                    diagnostics::unwrap_or_report_diagnostics(&files, units_res);
+                lint_warnings += warnings.count_category(Category::Lint);
+                diagnostics::report_warnings(&files, warnings);
+                Ok((files, units))
+            },
+        )?;
+
+        if lint_warnings > 0 {
+            anyhow::bail!("{} lint warning(s)", lint_warnings)
+        }
+        Ok(())
+    }
+}
@@ -2,13 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod build;
+pub mod check_compat;
 pub mod coverage;
+pub mod diff;
 pub mod disassemble;
 pub mod docgen;
 pub mod errmap;
+pub mod fmt;
+pub mod gas_schedule;
 pub mod info;
+pub mod lint;
+mod module_versions;
 pub mod new;
 pub mod prove;
+pub mod replay;
 pub mod test;
 
 use move_package::source_package::layout::SourcePackageLayout;
@@ -0,0 +1,143 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Loading of the versions of modules compared by `move check-compat` and `move diff`, either
+//! compiled from a package or read from bytecode files.
+
+use anyhow::Context;
+use move_binary_format::{binary_views::BinaryIndexedView, CompiledModule};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::files::MOVE_COMPILED_EXTENSION;
+use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_core_types::language_storage::ModuleId;
+use move_ir_types::location::{Loc, Spanned};
+use move_package::{source_package::layout::SourcePackageLayout, BuildConfig};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A compiled module, with its source if it was compiled from a package
+pub(crate) struct VersionedModule {
+    pub module: CompiledModule,
+    pub source: Option<ModuleSource>,
+}
+
+pub(crate) struct ModuleSource {
+    pub path: PathBuf,
+    pub contents: String,
+    pub source_map: SourceMap,
+}
+
+impl VersionedModule {
+    /// The source map of the module, or one without locations for a module read from bytecode
+    pub fn source_map(&self) -> anyhow::Result<SourceMap> {
+        match &self.source {
+            Some(source) => Ok(source.source_map.clone()),
+            None => SourceMap::dummy_from_view(
+                &BinaryIndexedView::Module(&self.module),
+                Spanned::unsafe_no_loc(()).loc,
+            ),
+        }
+    }
+}
+
+impl ModuleSource {
+    /// The (1-based) line and column of `loc`
+    pub fn line_and_column(&self, loc: Loc) -> (usize, usize) {
+        let offset = (loc.start() as usize).min(self.contents.len());
+        let before = &self.contents[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = offset - before.rfind('\n').map_or(0, |idx| idx + 1) + 1;
+        (line, column)
+    }
+
+    /// The text of the (1-based) `line`
+    pub fn line(&self, line: usize) -> &str {
+        self.contents.lines().nth(line - 1).unwrap_or_default()
+    }
+
+    /// Formats `loc` as `file:line:column`
+    pub fn format_loc(&self, loc: Loc) -> String {
+        let (line, column) = self.line_and_column(loc);
+        format!("{}:{}:{}", self.path.display(), line, column)
+    }
+}
+
+/// Loads the modules at `path`: the modules of a package, a bytecode file, or the bytecode files
+/// of a directory
+pub(crate) fn load_modules(
+    path: &Path,
+    config: &BuildConfig,
+) -> anyhow::Result<BTreeMap<ModuleId, VersionedModule>> {
+    if path.join(SourcePackageLayout::Manifest.path()).is_file() {
+        return compile_package(path, config.clone());
+    }
+    let files = if path.is_dir() {
+        let mut files = vec![];
+        for entry in fs::read_dir(path)? {
+            let file = entry?.path();
+            if file
+                .extension()
+                .map_or(false, |ext| ext == MOVE_COMPILED_EXTENSION)
+            {
+                files.push(file);
+            }
+        }
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let mut modules = BTreeMap::new();
+    for file in files {
+        let bytes =
+            fs::read(&file).with_context(|| format!("Unable to read {}", file.display()))?;
+        let module = CompiledModule::deserialize(&bytes)
+            .with_context(|| format!("Unable to deserialize {}", file.display()))?;
+        modules.insert(
+            module.self_id(),
+            VersionedModule {
+                module,
+                source: None,
+            },
+        );
+    }
+    Ok(modules)
+}
+
+/// Compiles the package at `path`, returning its own modules without its dependencies
+pub(crate) fn compile_package(
+    path: &Path,
+    config: BuildConfig,
+) -> anyhow::Result<BTreeMap<ModuleId, VersionedModule>> {
+    let root = SourcePackageLayout::try_find_root(&path.canonicalize()?)?;
+    let package = config.compile_package(&root, &mut Vec::new())?;
+    // Sources are shown relative to the current directory when they are under it
+    let current_dir = std::env::current_dir()?.canonicalize()?;
+    let mut modules = BTreeMap::new();
+    for unit in package.root_modules() {
+        if let CompiledUnit::Module(NamedCompiledModule {
+            module, source_map, ..
+        }) = &unit.unit
+        {
+            let source = ModuleSource {
+                path: unit
+                    .source_path
+                    .strip_prefix(&current_dir)
+                    .unwrap_or(&unit.source_path)
+                    .to_path_buf(),
+                contents: fs::read_to_string(&unit.source_path)?,
+                source_map: source_map.clone(),
+            };
+            modules.insert(
+                module.self_id(),
+                VersionedModule {
+                    module: module.clone(),
+                    source: Some(source),
+                },
+            );
+        }
+    }
+    Ok(modules)
+}
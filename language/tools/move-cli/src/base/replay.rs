@@ -0,0 +1,224 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::reroot_path;
+use anyhow::bail;
+use clap::*;
+use move_binary_format::file_format::FunctionDefinitionIndex;
+use move_bytecode_source_map::source_map::SourceMap;
+use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_core_types::language_storage::ModuleId;
+use move_package::BuildConfig;
+use move_vm_trace::{read_trace_file, StorageEffect, StorageEffectKind, TraceEvent};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+const HELP: &str = "\
+Commands:
+  n, next [N]    step forward by N instructions (default 1)
+  p, prev [N]    step backward by N instructions (default 1)
+  g, goto <N>    go to the N-th instruction of the trace
+  l, locals      show the locals of the current function
+  s, stack       show the operand stack
+  e, effects     list the accesses to global storage in the trace
+  q, quit        stop replaying";
+
+/// Replay a trace recorded by `move test --trace` against the sources of this package, stepping
+/// forward and backward through the execution. Commands are read from stdin.
+#[derive(Parser)]
+#[clap(name = "replay")]
+pub struct Replay {
+    /// The trace to replay
+    #[clap(parse(from_os_str))]
+    pub trace: PathBuf,
+}
+
+impl Replay {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        // Read the trace before rerooting, as its path may be relative to the current directory
+        let events = read_trace_file(&self.trace)?;
+        if events.is_empty() {
+            bail!("The trace {} is empty", self.trace.display())
+        }
+        let path = reroot_path(path)?;
+        let mut config = config;
+        // Traces are recorded by unit tests, which are only compiled in test mode
+        config.test_mode = true;
+        let package = config.compile_package(&path, &mut Vec::new())?;
+
+        let mut sources = BTreeMap::new();
+        for unit in package.all_modules() {
+            if let CompiledUnit::Module(NamedCompiledModule {
+                module, source_map, ..
+            }) = &unit.unit
+            {
+                let source = ModuleSource {
+                    path: &unit.source_path,
+                    contents: fs::read_to_string(&unit.source_path)?,
+                    source_map,
+                };
+                sources.insert(module.self_id(), source);
+            }
+        }
+
+        let mut replayer = Replayer {
+            events,
+            sources,
+            current: 0,
+        };
+        replayer.print_event();
+        prompt()?;
+        for line in io::stdin().lock().lines() {
+            let line = line?;
+            let mut words = line.split_whitespace();
+            let command = words.next();
+            let arg = words.next().map(|arg| arg.parse::<usize>());
+            match (command, arg) {
+                (None, _) => (),
+                (Some("n" | "next"), None) => replayer.move_to(replayer.current + 1),
+                (Some("n" | "next"), Some(Ok(n))) => replayer.move_to(replayer.current + n),
+                (Some("p" | "prev"), None) => replayer.move_to(replayer.current.saturating_sub(1)),
+                (Some("p" | "prev"), Some(Ok(n))) => {
+                    replayer.move_to(replayer.current.saturating_sub(n))
+                }
+                (Some("g" | "goto"), Some(Ok(n))) => replayer.move_to(n.saturating_sub(1)),
+                (Some("l" | "locals"), None) => replayer.print_locals(),
+                (Some("s" | "stack"), None) => replayer.print_operand_stack(),
+                (Some("e" | "effects"), None) => replayer.print_effects(),
+                (Some("q" | "quit"), None) => return Ok(()),
+                _ => println!("{}", HELP),
+            }
+            prompt()?;
+        }
+        Ok(())
+    }
+}
+
+fn prompt() -> io::Result<()> {
+    print!("> ");
+    io::stdout().flush()
+}
+
+struct ModuleSource<'a> {
+    path: &'a Path,
+    contents: String,
+    source_map: &'a SourceMap,
+}
+
+struct Replayer<'a> {
+    events: Vec<TraceEvent>,
+    sources: BTreeMap<ModuleId, ModuleSource<'a>>,
+    /// The index of the event being replayed
+    current: usize,
+}
+
+impl<'a> Replayer<'a> {
+    /// Moves to the event at `idx`, or to the last event if the trace is shorter
+    fn move_to(&mut self, idx: usize) {
+        self.current = idx.min(self.events.len() - 1);
+        self.print_event();
+    }
+
+    fn print_event(&self) {
+        let event = &self.events[self.current];
+        println!(
+            "[{}/{}] {}, pc {}: {}",
+            self.current + 1,
+            self.events.len(),
+            event.qualified_function(),
+            event.pc,
+            event.instruction
+        );
+        if let Some((source, line)) = self.source_line(event) {
+            let text = source.contents.lines().nth(line - 1).unwrap_or_default();
+            println!("  {}:{}: {}", source.path.display(), line, text.trim());
+        }
+        println!("  gas remaining: {}", event.gas_remaining);
+        if let Some(effect) = &event.effect {
+            println!("  effect: {}", format_effect(effect));
+        }
+    }
+
+    fn print_locals(&self) {
+        let event = &self.events[self.current];
+        let source = event
+            .module
+            .as_ref()
+            .and_then(|module_id| self.sources.get(module_id));
+        if event.locals.is_empty() {
+            println!("  (none)");
+        }
+        for (idx, value) in event.locals.iter().enumerate() {
+            let name = source
+                .and_then(|source| {
+                    source
+                        .source_map
+                        .get_parameter_or_local_name(
+                            FunctionDefinitionIndex(event.function_index),
+                            idx as u64,
+                        )
+                        .ok()
+                })
+                .map_or_else(|| format!("[{}]", idx), |(name, _)| name);
+            match value {
+                Some(value) => println!("  {}: {}", name, value),
+                None => println!("  {}: <no value>", name),
+            }
+        }
+    }
+
+    fn print_operand_stack(&self) {
+        let operand_stack = &self.events[self.current].operand_stack;
+        if operand_stack.is_empty() {
+            println!("  (empty)");
+        }
+        for (idx, value) in operand_stack.iter().enumerate().rev() {
+            println!("  [{}] {}", idx, value);
+        }
+    }
+
+    fn print_effects(&self) {
+        for (idx, event) in self.events.iter().enumerate() {
+            if let Some(effect) = &event.effect {
+                println!(
+                    "  [{}] {}: {}",
+                    idx + 1,
+                    event.qualified_function(),
+                    format_effect(effect)
+                );
+            }
+        }
+    }
+
+    /// The source of the module of `event`, and the (1-based) line of its instruction
+    fn source_line(&self, event: &TraceEvent) -> Option<(&ModuleSource<'a>, usize)> {
+        let source = self.sources.get(event.module.as_ref()?)?;
+        let loc = source
+            .source_map
+            .get_code_location(FunctionDefinitionIndex(event.function_index), event.pc)
+            .ok()?;
+        let offset = (loc.start() as usize).min(source.contents.len());
+        let line = source.contents[..offset].matches('\n').count() + 1;
+        Some((source, line))
+    }
+}
+
+fn format_effect(effect: &StorageEffect) -> String {
+    let kind = match effect.kind {
+        StorageEffectKind::MoveTo => "move_to",
+        StorageEffectKind::MoveFrom => "move_from",
+        StorageEffectKind::BorrowGlobal => "borrow_global",
+        StorageEffectKind::BorrowGlobalMut => "borrow_global_mut",
+        StorageEffectKind::Exists => "exists",
+    };
+    format!(
+        "{}<{}>(0x{})",
+        kind,
+        effect.struct_tag,
+        effect.address.short_str_lossless()
+    )
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::reroot_path;
-use crate::NativeFunctionRecord;
+use crate::{NativeFunctionRecord, GAS_PROFILE_DIR, TEST_REPORT_FILE};
 use anyhow::Result;
 use clap::*;
 use move_command_line_common::files::{FileHash, MOVE_COVERAGE_MAP_EXTENSION};
@@ -12,9 +12,12 @@ use move_compiler::{
     unit_test::{plan_builder::construct_test_plan, TestPlan},
     PASS_CFGIR,
 };
-use move_coverage::coverage_map::{output_map_to_file, CoverageMap};
-use move_package::{compilation::build_plan::BuildPlan, BuildConfig};
-use move_unit_test::UnitTestingConfig;
+use move_coverage::coverage_map::CoverageMap;
+use move_package::{
+    compilation::{build_plan::BuildPlan, package_layout::CompiledPackageLayout},
+    BuildConfig,
+};
+use move_unit_test::{gas_schedule::GasSchedule, machine_report::ReportFormat, UnitTestingConfig};
 use move_vm_test_utils::gas_schedule::CostTable;
 use std::{
     collections::HashMap,
@@ -76,6 +79,55 @@ pub struct Test {
     /// Collect coverage information for later use with the various `move coverage` subcommands
     #[clap(long = "coverage")]
     pub compute_coverage: bool,
+    /// Run the tests one at a time under a debugger, serving the Debug Adapter Protocol over
+    /// stdin and stdout, e.g. to debug them from an IDE. Other output goes to stderr.
+    #[clap(long = "debug-adapter")]
+    pub debug_adapter: bool,
+    /// Record a structured trace of the execution of each test in this directory, to replay it
+    /// with `move replay`
+    #[clap(long = "trace", parse(from_os_str))]
+    pub trace: Option<PathBuf>,
+    /// Profile the gas used by the tests, reporting it by kind of instruction and writing it as
+    /// folded stacks, e.g. to render flamegraphs, in `build/gas_profile`
+    #[clap(long = "profile-gas")]
+    pub profile_gas: bool,
+    /// Charge gas according to this gas schedule, in TOML or JSON, instead of the default one. See
+    /// `move gas-schedule export` for its format.
+    #[clap(long = "gas-schedule", parse(from_os_str))]
+    pub gas_schedule: Option<PathBuf>,
+    /// After the tests pass, run them against mutants of the modules of this package, e.g. with
+    /// an operator swapped or an `assert!` removed, and report the mutants which survive and the
+    /// mutation score of each function
+    #[clap(long = "mutate")]
+    pub mutate: bool,
+    /// Number of sets of generated arguments each #[random_test] is run with
+    #[clap(long = "random-test-iterations", default_value = "100")]
+    pub random_test_iterations: u64,
+    /// Seed for the arguments generated for #[random_test]s, e.g. to reproduce a failure. A
+    /// random seed is picked otherwise
+    #[clap(long = "seed")]
+    pub seed: Option<u64>,
+    /// Also write a machine-readable report of the test results in this format, e.g. for CI
+    /// dashboards, to `--report-file`. JSON events are written as the tests complete, JUnit XML
+    /// once they all have
+    #[clap(long = "report-format", arg_enum)]
+    pub report_format: Option<ReportFormat>,
+    /// The file the machine-readable report is written to. Defaults to `build/test_report.xml`
+    /// or `build/test_report.json`
+    #[clap(long = "report-file", parse(from_os_str), requires = "report_format")]
+    pub report_file: Option<PathBuf>,
+    /// The error map, e.g. generated by `move errmap`, explaining the abort codes in the
+    /// machine-readable report. Defaults to the error map of the standard library
+    #[clap(long = "error-map", parse(from_os_str))]
+    pub error_map: Option<PathBuf>,
+    /// Check the storage state at the end of each passing test against its snapshot, in the
+    /// `snapshots` directory next to the source file of the test
+    #[clap(long = "snapshots")]
+    pub check_snapshots: bool,
+    /// Save the storage state at the end of each passing test as its new snapshot, instead of
+    /// checking it
+    #[clap(long = "update-snapshots")]
+    pub update_snapshots: bool,
 
     /// Use the EVM-based execution backend.
     /// Does not work with --stackless.
@@ -98,6 +150,22 @@ impl Test {
         natives: Vec<NativeFunctionRecord>,
         cost_table: Option<CostTable>,
     ) -> anyhow::Result<()> {
+        // Read the gas schedule before rerooting, as its path may be relative to the current
+        // directory
+        let gas_schedule = self
+            .gas_schedule
+            .as_ref()
+            .map(|path| GasSchedule::load(path))
+            .transpose()?;
+        // The mutants would be traced as well, skewing the coverage of the tests
+        if self.mutate && self.compute_coverage {
+            anyhow::bail!("--mutate cannot be used with --coverage")
+        }
+        // Like the gas schedule, the report and the error map may be relative to the current
+        // directory
+        let current_dir = std::env::current_dir()?;
+        let report_file = self.report_file.as_ref().map(|file| current_dir.join(file));
+        let error_map = self.error_map.as_ref().map(|file| current_dir.join(file));
         let rerooted_path = reroot_path(path)?;
         let Self {
             gas_limit,
@@ -110,11 +178,31 @@ impl Test {
             check_stackless_vm,
             verbose_mode,
             compute_coverage,
+            debug_adapter,
+            trace,
+            profile_gas,
+            gas_schedule: _,
+            mutate,
+            random_test_iterations,
+            seed,
+            report_format,
+            report_file: _,
+            error_map: _,
+            check_snapshots,
+            update_snapshots,
             #[cfg(feature = "evm-backend")]
             evm,
             #[cfg(feature = "solana-backend")]
             solana,
         } = self;
+        let report_file = report_format.map(|format| {
+            report_file.unwrap_or_else(|| {
+                rerooted_path
+                    .join(CompiledPackageLayout::Root.path())
+                    .join(TEST_REPORT_FILE)
+                    .with_extension(format.extension())
+            })
+        });
         let unit_test_config = UnitTestingConfig {
             gas_limit,
             filter,
@@ -125,6 +213,22 @@ impl Test {
             check_stackless_vm,
             verbose: verbose_mode,
             ignore_compile_warnings,
+            debug_adapter,
+            trace,
+            profile_gas: profile_gas.then(|| {
+                rerooted_path
+                    .join(CompiledPackageLayout::Root.path())
+                    .join(GAS_PROFILE_DIR)
+            }),
+            mutate,
+            random_test_iterations,
+            seed,
+            report_format,
+            report_file,
+            error_map,
+            check_snapshots,
+            update_snapshots,
+            gas_schedule,
             #[cfg(feature = "evm-backend")]
             evm,
             #[cfg(feature = "solana-backend")]
@@ -132,6 +236,12 @@ impl Test {
 
             ..UnitTestingConfig::default_with_bound(None)
         };
+        // stdout is reserved for the messages of the debug adapter
+        let mut writer: Box<dyn Write + Send> = if debug_adapter {
+            Box::new(std::io::stderr())
+        } else {
+            Box::new(std::io::stdout())
+        };
         let result = run_move_unit_tests(
             &rerooted_path,
             config,
@@ -139,7 +249,7 @@ impl Test {
             natives,
             cost_table,
             compute_coverage,
-            &mut std::io::stdout(),
+            &mut writer,
         )?;
 
         // Return a non-zero exit code if any test failed
@@ -260,8 +370,7 @@ pub fn run_move_unit_tests<W: Write + Send>(
     // Run the tests. If any of the tests fail, then we don't produce a coverage report, so cleanup
     // the trace files.
     if !unit_test_config
-        .run_and_report_unit_tests(test_plan, Some(natives), cost_table, writer)
-        .unwrap()
+        .run_and_report_unit_tests(test_plan, Some(natives), cost_table, writer)?
         .1
     {
         cleanup_trace();
@@ -271,7 +380,7 @@ pub fn run_move_unit_tests<W: Write + Send>(
     // Compute the coverage map. This will be used by other commands after this.
     if compute_coverage && !no_tests {
         let coverage_map = CoverageMap::from_trace_file(trace_path);
-        output_map_to_file(coverage_map_path, &coverage_map).unwrap();
+        coverage_map.to_binary_file(coverage_map_path).unwrap();
     }
     Ok(UnitTestResult::Success)
 }
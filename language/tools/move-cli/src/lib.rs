@@ -3,8 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use base::{
-    build::Build, coverage::Coverage, disassemble::Disassemble, docgen::Docgen, errmap::Errmap,
-    info::Info, new::New, prove::Prove, test::Test,
+    build::Build, check_compat::CheckCompat, coverage::Coverage, diff::Diff,
+    disassemble::Disassemble, docgen::Docgen, errmap::Errmap, fmt::Fmt, gas_schedule::GasSchedule,
+    info::Info, lint::Lint, new::New, prove::Prove, replay::Replay, test::Test,
 };
 use move_package::BuildConfig;
 
@@ -18,6 +19,17 @@ pub const DEFAULT_STORAGE_DIR: &str = "storage";
 /// Default directory for build output
 pub const DEFAULT_BUILD_DIR: &str = ".";
 
+/// Directory, in the build output of a package, where `--profile-gas` writes the gas profile
+pub const GAS_PROFILE_DIR: &str = "gas_profile";
+
+/// Name of the file, in the build output of a package, where `move test --report-format` writes
+/// the report of the test results by default, with the extension of the format
+pub const TEST_REPORT_FILE: &str = "test_report";
+
+/// Name of the file, in the build output of a package, where `move coverage export` writes the
+/// coverage report by default, with the extension of the format
+pub const COVERAGE_REPORT_FILE: &str = "coverage";
+
 /// Extension for resource and event files, which are in BCS format
 const BCS_EXTENSION: &str = "bcs";
 
@@ -63,13 +75,19 @@ pub struct MoveCLI {
 #[derive(Parser)]
 pub enum Command {
     Build(Build),
+    CheckCompat(CheckCompat),
     Coverage(Coverage),
+    Diff(Diff),
     Disassemble(Disassemble),
     Docgen(Docgen),
     Errmap(Errmap),
+    Fmt(Fmt),
+    GasSchedule(GasSchedule),
     Info(Info),
+    Lint(Lint),
     New(New),
     Prove(Prove),
+    Replay(Replay),
     Test(Test),
     /// Execute a sandbox command.
     #[clap(name = "sandbox")]
@@ -105,13 +123,19 @@ pub fn run_cli(
     //         2. The CostTable only affects sandbox runs, but not unit tests, which use a unit cost table.
     match cmd {
         Command::Build(c) => c.execute(move_args.package_path, move_args.build_config),
+        Command::CheckCompat(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Coverage(c) => c.execute(move_args.package_path, move_args.build_config),
+        Command::Diff(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Disassemble(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Docgen(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Errmap(c) => c.execute(move_args.package_path, move_args.build_config),
+        Command::Fmt(c) => c.execute(move_args.package_path, move_args.build_config),
+        Command::GasSchedule(c) => c.execute(cost_table),
         Command::Info(c) => c.execute(move_args.package_path, move_args.build_config),
+        Command::Lint(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::New(c) => c.execute_with_defaults(move_args.package_path),
         Command::Prove(c) => c.execute(move_args.package_path, move_args.build_config),
+        Command::Replay(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Test(c) => c.execute(
             move_args.package_path,
             move_args.build_config,
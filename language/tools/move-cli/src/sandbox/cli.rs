@@ -7,7 +7,7 @@ use crate::{
         self,
         utils::{on_disk_state_view::OnDiskStateView, PackageContext},
     },
-    Move, NativeFunctionRecord, DEFAULT_BUILD_DIR,
+    Move, NativeFunctionRecord, DEFAULT_BUILD_DIR, GAS_PROFILE_DIR,
 };
 use anyhow::Result;
 use clap::Parser;
@@ -105,6 +105,11 @@ pub enum SandboxCommand {
         /// By default, no `gas-budget` is specified and gas metering is disabled.
         #[clap(long = "gas-budget", short = 'g')]
         gas_budget: Option<u64>,
+        /// Profile the gas used by the execution, reporting it by kind of instruction and writing
+        /// it as folded stacks, e.g. to render flamegraphs, in `build/gas_profile`. Requires
+        /// `gas-budget` to be set.
+        #[clap(long = "profile-gas")]
+        profile_gas: bool,
         /// If set, the effects of executing `script_file` (i.e., published, updated, and
         /// deleted resources) will NOT be committed to disk.
         #[clap(long = "dry-run", short = 'n')]
@@ -236,11 +241,23 @@ impl SandboxCommand {
                 args,
                 type_args,
                 gas_budget,
+                profile_gas,
                 dry_run,
             } => {
                 let context =
                     PackageContext::new(&move_args.package_path, &move_args.build_config)?;
                 let state = context.prepare_state(bytecode_version, storage_dir)?;
+                let profile_dir = profile_gas.then(|| {
+                    Path::new(
+                        move_args
+                            .build_config
+                            .install_dir
+                            .as_ref()
+                            .unwrap_or(&PathBuf::from(DEFAULT_BUILD_DIR)),
+                    )
+                    .join(CompiledPackageLayout::Root.path())
+                    .join(GAS_PROFILE_DIR)
+                });
                 sandbox::commands::run(
                     natives,
                     cost_table,
@@ -253,6 +270,7 @@ impl SandboxCommand {
                     args,
                     type_args.to_vec(),
                     *gas_budget,
+                    profile_dir.as_deref(),
                     bytecode_version,
                     *dry_run,
                     move_args.verbose,
@@ -10,19 +10,24 @@ use crate::{
     NativeFunctionRecord,
 };
 use anyhow::{anyhow, bail, Result};
-use move_binary_format::file_format::CompiledModule;
+use move_binary_format::{errors::VMResult, file_format::CompiledModule};
 use move_command_line_common::env::get_bytecode_version_from_env;
 use move_core_types::{
     account_address::AccountAddress,
     errmap::ErrorMapping,
     identifier::IdentStr,
-    language_storage::TypeTag,
+    language_storage::{ModuleId, TypeTag},
+    resolver::MoveResolver,
     transaction_argument::{convert_txn_args, TransactionArgument},
     value::MoveValue,
 };
 use move_package::compilation::compiled_package::CompiledPackage;
-use move_vm_runtime::move_vm::MoveVM;
-use move_vm_test_utils::gas_schedule::CostTable;
+use move_vm_runtime::{
+    move_vm::MoveVM,
+    session::{SerializedReturnValues, Session},
+};
+use move_vm_test_utils::{gas_profiler::GasProfiler, gas_schedule::CostTable};
+use move_vm_types::gas::GasMeter;
 use std::{fs, path::Path};
 
 #[allow(clippy::too_many_arguments)]
@@ -38,6 +43,7 @@ pub fn run(
     txn_args: &[TransactionArgument],
     vm_type_args: Vec<TypeTag>,
     gas_budget: Option<u64>,
+    profile_dir: Option<&Path>,
     bytecode_version: Option<u32>,
     dry_run: bool,
     verbose: bool,
@@ -45,6 +51,9 @@ pub fn run(
     if !script_path.exists() {
         bail!("Script file {:?} does not exist", script_path)
     };
+    if profile_dir.is_some() && gas_budget.is_none() {
+        bail!("Profiling the gas used requires a gas budget, as gas is not metered without one")
+    }
     let bytecode_version = get_bytecode_version_from_env(bytecode_version);
 
     let bytecode = if is_bytecode_file(script_path) {
@@ -92,21 +101,49 @@ move run` must be applied to a module inside `storage/`",
         })
         .chain(vm_args)
         .collect();
-    let res = match script_name_opt {
+    let entry_function = match script_name_opt {
         Some(script_name) => {
             // script fun. parse module, extract script ID to pass to VM
             let module = CompiledModule::deserialize(&bytecode)
                 .map_err(|e| anyhow!("Error deserializing module: {:?}", e))?;
-            session.execute_entry_function(
-                &module.self_id(),
-                IdentStr::new(script_name)?,
+            Some((module.self_id(), IdentStr::new(script_name)?))
+        }
+        None => None,
+    };
+    let res = match profile_dir {
+        Some(profile_dir) => {
+            let entry = match &entry_function {
+                Some((module_id, function_name)) => format!(
+                    "0x{}::{}::{}",
+                    module_id.address().short_str_lossless(),
+                    module_id.name(),
+                    function_name
+                ),
+                None => "script".to_string(),
+            };
+            let mut profiler = GasProfiler::new(gas_status, entry);
+            let res = execute(
+                &mut session,
+                &bytecode,
+                &entry_function,
                 vm_type_args.clone(),
                 vm_args,
-                &mut gas_status,
-            )
+                &mut profiler,
+            );
+            let (_, profile) = profiler.finish();
+            println!("Gas profile:");
+            profile.write_instruction_breakdown(&mut std::io::stdout())?;
+            profile.save(profile_dir)?;
+            println!(
+                "Folded stacks of the gas used written to {}",
+                profile_dir.display()
+            );
+            res
         }
-        None => session.execute_script(
-            bytecode.to_vec(),
+        None => execute(
+            &mut session,
+            &bytecode,
+            &entry_function,
             vm_type_args.clone(),
             vm_args,
             &mut gas_status,
@@ -132,3 +169,20 @@ move run` must be applied to a module inside `storage/`",
         maybe_commit_effects(!dry_run, changeset, events, state)
     }
 }
+
+/// Executes the entry function of a module if `entry_function` is set, or the script in `bytecode`
+fn execute<S: MoveResolver>(
+    session: &mut Session<S>,
+    bytecode: &[u8],
+    entry_function: &Option<(ModuleId, &IdentStr)>,
+    ty_args: Vec<TypeTag>,
+    args: Vec<Vec<u8>>,
+    gas_meter: &mut impl GasMeter,
+) -> VMResult<SerializedReturnValues> {
+    match entry_function {
+        Some((module_id, function_name)) => {
+            session.execute_entry_function(module_id, function_name, ty_args, args, gas_meter)
+        }
+        None => session.execute_script(bytecode.to_vec(), ty_args, args, gas_meter),
+    }
+}
@@ -0,0 +1,9 @@
+module 0x2::m {
+    struct S has store { x: u64, y: bool }
+
+    public entry fun e() {}
+
+    public fun f(): u64 { 2 }
+
+    public fun k() {}
+}
@@ -0,0 +1,9 @@
+module 0x2::m {
+    struct S has store { x: u64 }
+
+    public fun e() {}
+
+    public fun f(): u64 { 1 }
+
+    public fun g() {}
+}
@@ -0,0 +1,3 @@
+module 0x42::m {
+    public fun foo(x:u64):u64 { x+1 }
+}
@@ -0,0 +1,13 @@
+module 0x2::m {
+    fun sum(v: &vector<u64>): u64 {
+        let s = 0;
+        for (x in v) s = s + *x;
+        s
+    }
+
+    fun count(n: u64): u64 {
+        let c = 0;
+        for (_ in 0..n) c = c + 1;
+        c
+    }
+}
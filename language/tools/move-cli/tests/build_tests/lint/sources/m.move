@@ -0,0 +1,10 @@
+module 0x42::m {
+    public fun foo(b: bool): bool {
+        b == true
+    }
+
+    #[lint_allow(bool_comparison)]
+    public fun bar(b: bool): bool {
+        b == false
+    }
+}
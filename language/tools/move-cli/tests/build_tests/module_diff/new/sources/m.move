@@ -0,0 +1,9 @@
+module 0x2::m {
+    public fun double(a: u64): u64 {
+        a + 1 + a
+    }
+
+    public fun triple(a: u64): u64 {
+        a + a + a
+    }
+}
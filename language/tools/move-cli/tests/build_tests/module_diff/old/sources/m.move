@@ -0,0 +1,7 @@
+module 0x2::m {
+    public fun double(a: u64): u64 {
+        a + a
+    }
+
+    fun unused() {}
+}
@@ -0,0 +1,9 @@
+module 0x2::m {
+    fun nop() {}
+
+    #[test]
+    fun calls() {
+        nop();
+        nop()
+    }
+}
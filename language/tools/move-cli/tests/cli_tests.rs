@@ -3,7 +3,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use move_cli::sandbox::commands::test;
-use std::{env, path::PathBuf, process::Command};
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
 
 pub const CLI_METATEST_PATH: [&str; 3] = ["tests", "metatests", "args.txt"];
 
@@ -52,3 +57,126 @@ fn cross_process_locking_git_deps() {
         .expect("Package2 failed");
     handle.join().unwrap();
 }
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let path = entry.unwrap().path();
+        let target = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &target);
+        } else {
+            fs::copy(&path, &target).unwrap();
+        }
+    }
+}
+
+#[test]
+fn record_and_replay_trace() {
+    let cli_exe = env!("CARGO_BIN_EXE_move");
+    let package = tempfile::tempdir().unwrap();
+    copy_dir(Path::new("./tests/replay_tests/Replayed"), package.path());
+
+    let status = Command::new(cli_exe)
+        .current_dir(package.path())
+        .args(["test", "--trace", "traces"])
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success());
+
+    let mut replay = Command::new(cli_exe)
+        .current_dir(package.path())
+        .args(["replay", "traces/2.m.test_double.json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    replay
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"n 2\nl\np\ne\ng 1\nq\n")
+        .unwrap();
+    let output = replay.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    // the output of each command follows a prompt
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let outputs = stdout.split("> ").collect::<Vec<_>>();
+    assert_eq!(outputs.len(), 7, "{}", stdout);
+    assert!(outputs[0].starts_with("[1/"));
+    assert!(outputs[0].contains("0x2::m::test_double, pc 0: LdU64(2)"));
+    assert!(outputs[0].contains("m.move:10: let v = double(2);"));
+    // n 2
+    assert!(outputs[1].starts_with("[3/"));
+    assert!(outputs[1].contains("0x2::m::double, pc 0: CopyLoc"));
+    assert!(outputs[1].contains("m.move:5: x + x"));
+    // l
+    assert_eq!(outputs[2], "  x: U64(2)\n");
+    // p
+    assert!(outputs[3].starts_with("[2/"));
+    assert!(outputs[3].contains("0x2::m::test_double, pc 1: Call"));
+    // e
+    assert!(outputs[4].contains(": 0x2::m::test_double: move_to<0x2::m::R>(0x2)"));
+    // g 1
+    assert_eq!(outputs[5], outputs[0]);
+    // q
+    assert_eq!(outputs[6], "");
+}
+
+#[test]
+fn trace_errors_fail_move_test() {
+    let cli_exe = env!("CARGO_BIN_EXE_move");
+    let package = tempfile::tempdir().unwrap();
+    copy_dir(Path::new("./tests/replay_tests/Replayed"), package.path());
+    // the trace of the test cannot be written where there is a directory
+    std::fs::create_dir_all(package.path().join("traces/2.m.test_double.json")).unwrap();
+
+    let output = Command::new(cli_exe)
+        .current_dir(package.path())
+        .args(["test", "--trace", "traces"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Unable to record trace") && stderr.contains("2.m.test_double.json"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn json_report_of_move_test() {
+    let cli_exe = env!("CARGO_BIN_EXE_move");
+    let package = tempfile::tempdir().unwrap();
+    copy_dir(Path::new("./tests/replay_tests/Replayed"), package.path());
+
+    let status = Command::new(cli_exe)
+        .current_dir(package.path())
+        .args([
+            "test",
+            "--report-format",
+            "json",
+            "--report-file",
+            "report.json",
+        ])
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success());
+
+    let report = fs::read_to_string(package.path().join("report.json")).unwrap();
+    let events = report
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect::<Vec<serde_json::Value>>();
+    assert_eq!(events.len(), 3, "{}", report);
+    assert_eq!(events[0]["event"], "started");
+    assert_eq!(events[0]["test_count"], 1);
+    assert_eq!(events[1]["event"], "ok");
+    assert_eq!(events[1]["name"], "0x2::m::test_double");
+    assert_eq!(events[2]["event"], "ok");
+    assert_eq!(events[2]["passed"], 1);
+}
@@ -0,0 +1,13 @@
+module 0x2::m {
+    struct R has key { v: u64 }
+
+    fun double(x: u64): u64 {
+        x + x
+    }
+
+    #[test(s = @0x2)]
+    fun test_double(s: signer) {
+        let v = double(2);
+        move_to(&s, R { v });
+    }
+}
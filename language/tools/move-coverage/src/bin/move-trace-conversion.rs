@@ -44,7 +44,8 @@ fn main() {
             CoverageMap::from_trace_file(input_path)
         };
 
-        output_map_to_file(output_path, &coverage_map)
+        coverage_map
+            .to_binary_file(output_path)
             .expect("Unable to serialize coverage map to output file")
     } else {
         let trace_map = if let Some(old_trace_path) = &args.update {
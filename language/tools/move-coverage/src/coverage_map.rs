@@ -4,7 +4,7 @@
 
 #![forbid(unsafe_code)]
 
-use anyhow::{format_err, Result};
+use anyhow::{bail, format_err, Result};
 use move_binary_format::file_format::{CodeOffset, CompiledModule};
 use move_core_types::{
     account_address::AccountAddress,
@@ -20,6 +20,17 @@ use std::{
 
 pub type FunctionCoverage = BTreeMap<u64, u64>;
 
+/// The number of times control was transferred from an offset of a function to another offset
+/// than the next one, e.g. when a branch is taken, keyed by the source and destination offsets
+pub type FunctionJumps = BTreeMap<(u64, u64), u64>;
+
+/// The prefix of serialized coverage maps, followed by the version of their format
+const COVERAGE_MAP_MAGIC: &[u8] = b"MVCOV";
+
+/// The version of the format of serialized coverage maps, to be bumped on each change to it. Maps
+/// of version 1 had no `function_jumps`.
+pub const COVERAGE_MAP_VERSION: u32 = 2;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CoverageMap {
     pub exec_maps: BTreeMap<String, ExecCoverageMap>,
@@ -30,6 +41,7 @@ pub struct ModuleCoverageMap {
     pub module_addr: AccountAddress,
     pub module_name: Identifier,
     pub function_maps: BTreeMap<Identifier, FunctionCoverage>,
+    pub function_jumps: BTreeMap<Identifier, FunctionJumps>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -57,20 +69,38 @@ pub struct TraceMap {
     pub exec_maps: BTreeMap<String, Vec<TraceEntry>>,
 }
 
+/// A frame of a traced execution: the function and its last instruction traced
+struct TracedFrame {
+    context: String,
+    pc: u64,
+    instr: String,
+}
+
 impl CoverageMap {
     /// Takes in a file containing a raw VM trace, and returns an updated coverage map.
     pub fn update_coverage_from_trace_file<P: AsRef<Path> + std::fmt::Debug>(
-        mut self,
+        self,
         filename: P,
     ) -> Self {
         let file = File::open(&filename)
             .unwrap_or_else(|_| panic!("Unable to open coverage trace file '{:?}'", filename));
-        for line in BufReader::new(file).lines() {
+        self.update_coverage_from_trace(BufReader::new(file))
+    }
+
+    /// Takes in a raw VM trace, and returns an updated coverage map.
+    fn update_coverage_from_trace<R: BufRead>(mut self, trace: R) -> Self {
+        // The call stack of each execution, to record the jumps between instructions of a frame
+        let mut call_stacks: BTreeMap<String, Vec<TracedFrame>> = BTreeMap::new();
+        for line in trace.lines() {
             let line = line.unwrap();
-            let mut splits = line.split(',');
+            let mut splits = line.splitn(4, ',');
             let exec_id = splits.next().unwrap();
             let context = splits.next().unwrap();
             let pc = splits.next().unwrap().parse::<u64>().unwrap();
+            let instr = splits.next().unwrap_or_default();
+
+            let frames = call_stacks.entry(exec_id.to_owned()).or_default();
+            let last_pc = TracedFrame::step(frames, context, pc, instr);
 
             let mut context_segs: Vec<_> = context.split("::").collect();
             let is_script = context_segs.len() == 2;
@@ -79,6 +109,18 @@ impl CoverageMap {
                 let module_name = Identifier::new(context_segs.pop().unwrap()).unwrap();
                 let module_addr =
                     AccountAddress::from_hex_literal(context_segs.pop().unwrap()).unwrap();
+                if let Some(last_pc) = last_pc {
+                    if last_pc + 1 != pc {
+                        self.insert_jump(
+                            exec_id,
+                            module_addr,
+                            module_name.clone(),
+                            func_name.clone(),
+                            last_pc,
+                            pc,
+                        );
+                    }
+                }
                 self.insert(exec_id, module_addr, module_name, func_name, pc);
             } else {
                 // Don't count scripts (for now)
@@ -105,7 +147,35 @@ impl CoverageMap {
             .read_to_end(&mut bytes)
             .ok()
             .ok_or_else(|| format_err!("Unable to read coverage map"))?;
-        bcs::from_bytes(&bytes).map_err(|_| format_err!("Error deserializing coverage map"))
+        let bytes = match bytes.strip_prefix(COVERAGE_MAP_MAGIC) {
+            Some(bytes) => bytes,
+            None => bail!(
+                "Coverage map file '{:?}' was written by an older version of Move, \
+                collect the coverage again",
+                filename
+            ),
+        };
+        let (version, bytes) = bytes.split_at(bytes.len().min(4));
+        let version = u32::from_le_bytes(version.try_into().unwrap_or_default());
+        if version != COVERAGE_MAP_VERSION {
+            bail!(
+                "Coverage map file '{:?}' has version {}, expected version {}, \
+                collect the coverage again",
+                filename,
+                version,
+                COVERAGE_MAP_VERSION
+            );
+        }
+        bcs::from_bytes(bytes).map_err(|_| format_err!("Error deserializing coverage map"))
+    }
+
+    /// Writes the coverage map to a file, in the format read by `from_binary_file`.
+    pub fn to_binary_file<P: AsRef<Path>>(&self, filename: P) -> Result<()> {
+        let mut file = File::create(filename)?;
+        file.write_all(COVERAGE_MAP_MAGIC)?;
+        file.write_all(&COVERAGE_MAP_VERSION.to_le_bytes())?;
+        file.write_all(&bcs::to_bytes(self)?)?;
+        Ok(())
     }
 
     // add entries in a cascading manner
@@ -124,6 +194,22 @@ impl CoverageMap {
         exec_entry.insert(module_addr, module_name, func_name, pc);
     }
 
+    pub fn insert_jump(
+        &mut self,
+        exec_id: &str,
+        module_addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+        from_pc: u64,
+        to_pc: u64,
+    ) {
+        let exec_entry = self
+            .exec_maps
+            .entry(exec_id.to_owned())
+            .or_insert_with(|| ExecCoverageMap::new(exec_id.to_owned()));
+        exec_entry.insert_jump(module_addr, module_name, func_name, from_pc, to_pc);
+    }
+
     pub fn to_unified_exec_map(&self) -> ExecCoverageMap {
         let mut unified_map = ExecCoverageMap::new(String::new());
         for (_, exec_map) in self.exec_maps.iter() {
@@ -139,6 +225,18 @@ impl CoverageMap {
                         );
                     }
                 }
+                for (func_name, func_jumps) in module_map.function_jumps.iter() {
+                    for ((from_pc, to_pc), count) in func_jumps.iter() {
+                        unified_map.insert_jump_multi(
+                            *module_addr,
+                            module_name.clone(),
+                            func_name.clone(),
+                            *from_pc,
+                            *to_pc,
+                            *count,
+                        );
+                    }
+                }
             }
         }
         unified_map
@@ -151,6 +249,7 @@ impl ModuleCoverageMap {
             module_addr,
             module_name,
             function_maps: BTreeMap::new(),
+            function_jumps: BTreeMap::new(),
         }
     }
 
@@ -167,18 +266,46 @@ impl ModuleCoverageMap {
         self.insert_multi(func_name, pc, 1);
     }
 
+    pub fn insert_jump_multi(
+        &mut self,
+        func_name: Identifier,
+        from_pc: u64,
+        to_pc: u64,
+        count: u64,
+    ) {
+        let func_entry = self
+            .function_jumps
+            .entry(func_name)
+            .or_insert_with(FunctionJumps::new);
+        let jump_entry = func_entry.entry((from_pc, to_pc)).or_insert(0);
+        *jump_entry += count;
+    }
+
+    pub fn insert_jump(&mut self, func_name: Identifier, from_pc: u64, to_pc: u64) {
+        self.insert_jump_multi(func_name, from_pc, to_pc, 1);
+    }
+
+    /// Adds the counts of `another` to the ones of this map
     pub fn merge(&mut self, another: ModuleCoverageMap) {
-        for (key, val) in another.function_maps {
-            self.function_maps
-                .entry(key)
-                .or_insert_with(FunctionCoverage::new)
-                .extend(val);
+        for (func_name, coverage) in another.function_maps {
+            for (pc, count) in coverage {
+                self.insert_multi(func_name.clone(), pc, count);
+            }
+        }
+        for (func_name, jumps) in another.function_jumps {
+            for ((from_pc, to_pc), count) in jumps {
+                self.insert_jump_multi(func_name.clone(), from_pc, to_pc, count);
+            }
         }
     }
 
     pub fn get_function_coverage(&self, func_name: &IdentStr) -> Option<&FunctionCoverage> {
         self.function_maps.get(func_name)
     }
+
+    pub fn get_function_jumps(&self, func_name: &IdentStr) -> Option<&FunctionJumps> {
+        self.function_jumps.get(func_name)
+    }
 }
 
 impl ExecCoverageMap {
@@ -214,6 +341,33 @@ impl ExecCoverageMap {
         self.insert_multi(module_addr, module_name, func_name, pc, 1);
     }
 
+    pub fn insert_jump_multi(
+        &mut self,
+        module_addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+        from_pc: u64,
+        to_pc: u64,
+        count: u64,
+    ) {
+        let module_entry = self
+            .module_maps
+            .entry((module_addr, module_name.clone()))
+            .or_insert_with(|| ModuleCoverageMap::new(module_addr, module_name));
+        module_entry.insert_jump_multi(func_name, from_pc, to_pc, count);
+    }
+
+    pub fn insert_jump(
+        &mut self,
+        module_addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+        from_pc: u64,
+        to_pc: u64,
+    ) {
+        self.insert_jump_multi(module_addr, module_name, func_name, from_pc, to_pc, 1);
+    }
+
     pub fn into_coverage_map_with_modules(
         self,
         modules: BTreeMap<AccountAddress, BTreeMap<Identifier, (String, CompiledModule)>>,
@@ -270,6 +424,47 @@ impl ExecCoverageMapWithModules {
     }
 }
 
+impl TracedFrame {
+    /// Updates the call stack `frames` of an execution with the instruction traced next, returning
+    /// the offset of the instruction traced before it in the same frame, if any.
+    fn step(frames: &mut Vec<TracedFrame>, context: &str, pc: u64, instr: &str) -> Option<u64> {
+        let last_pc = match frames.pop() {
+            // A call to a native function is not traced, so the caller continues after the call
+            Some(caller)
+                if caller.is_call() && (caller.context != context || caller.pc + 1 != pc) =>
+            {
+                frames.push(caller);
+                None
+            }
+            Some(last) if last.instr == "Ret" => match frames.pop() {
+                Some(caller) if caller.context == context => Some(caller.pc),
+                // The execution ended and another one started
+                _ => {
+                    frames.clear();
+                    None
+                }
+            },
+            Some(last) if last.instr != "Abort" && last.context == context => Some(last.pc),
+            // The execution aborted, or ended on an error, and another one started
+            Some(_) => {
+                frames.clear();
+                None
+            }
+            None => None,
+        };
+        frames.push(TracedFrame {
+            context: context.to_owned(),
+            pc,
+            instr: instr.to_owned(),
+        });
+        last_pc
+    }
+
+    fn is_call(&self) -> bool {
+        self.instr.starts_with("Call(") || self.instr.starts_with("CallGeneric(")
+    }
+}
+
 impl TraceMap {
     /// Takes in a file containing a raw VM trace, and returns an updated coverage map.
     pub fn update_from_trace_file<P: AsRef<Path>>(mut self, filename: P) -> Self {
@@ -347,3 +542,184 @@ pub fn output_map_to_file<M: Serialize, P: AsRef<Path>>(file_name: P, data: &M)
     file.write_all(&bytes)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXEC_ID: &str = "1-ThreadId(1)";
+
+    /// The jumps recorded in `func_name` of `0x1::M` from `trace`, a line per instruction traced
+    fn jumps(trace: &[&str], func_name: &str) -> FunctionJumps {
+        let trace: String = trace
+            .iter()
+            .map(|line| format!("{},{}\n", EXEC_ID, line))
+            .collect();
+        let coverage_map = CoverageMap {
+            exec_maps: BTreeMap::new(),
+        }
+        .update_coverage_from_trace(trace.as_bytes());
+        coverage_map.exec_maps[EXEC_ID].module_maps
+            [&(AccountAddress::ONE, Identifier::new("M").unwrap())]
+            .get_function_jumps(&Identifier::new(func_name).unwrap())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn jumps_of_recursive_calls() {
+        // `f(1)`, with `f(n)` returning if `n == 0` and calling `f(n - 1)` otherwise
+        let trace = [
+            "0x1::M::f,0,CopyLoc(0)",
+            "0x1::M::f,1,LdU64(0)",
+            "0x1::M::f,2,Eq",
+            "0x1::M::f,3,BrFalse(5)",
+            "0x1::M::f,5,CopyLoc(0)",
+            "0x1::M::f,6,LdU64(1)",
+            "0x1::M::f,7,Sub",
+            "0x1::M::f,8,Call(0)",
+            "0x1::M::f,0,CopyLoc(0)",
+            "0x1::M::f,1,LdU64(0)",
+            "0x1::M::f,2,Eq",
+            "0x1::M::f,3,BrFalse(5)",
+            "0x1::M::f,4,Ret",
+            "0x1::M::f,9,Ret",
+        ];
+        assert_eq!(jumps(&trace, "f"), BTreeMap::from([((3, 5), 1)]));
+    }
+
+    #[test]
+    fn jumps_of_calls_between_functions() {
+        let trace = [
+            "0x1::M::g,0,LdTrue",
+            "0x1::M::g,1,BrTrue(3)",
+            "0x1::M::g,3,Call(1)",
+            "0x1::M::h,0,Branch(2)",
+            "0x1::M::h,2,Ret",
+            "0x1::M::g,4,Ret",
+        ];
+        assert_eq!(jumps(&trace, "g"), BTreeMap::from([((1, 3), 1)]));
+        assert_eq!(jumps(&trace, "h"), BTreeMap::from([((0, 2), 1)]));
+    }
+
+    #[test]
+    fn jumps_around_native_calls() {
+        // Native functions are not traced, so execution continues in the caller
+        let trace = [
+            "0x1::M::g,0,Call(2)",
+            "0x1::M::g,1,Branch(3)",
+            "0x1::M::g,3,Ret",
+        ];
+        assert_eq!(jumps(&trace, "g"), BTreeMap::from([((1, 3), 1)]));
+    }
+
+    #[test]
+    fn jumps_of_loops() {
+        let trace = [
+            "0x1::M::f,0,CopyLoc(0)",
+            "0x1::M::f,1,BrFalse(3)",
+            "0x1::M::f,2,Branch(0)",
+            "0x1::M::f,0,CopyLoc(0)",
+            "0x1::M::f,1,BrFalse(3)",
+            "0x1::M::f,3,Ret",
+        ];
+        assert_eq!(
+            jumps(&trace, "f"),
+            BTreeMap::from([((1, 3), 1), ((2, 0), 1)])
+        );
+    }
+
+    #[test]
+    fn jumps_of_executions_one_after_another() {
+        // An execution aborting, then one ending with a return, then another one
+        let trace = [
+            "0x1::M::f,0,LdU64(1)",
+            "0x1::M::f,1,Abort",
+            "0x1::M::f,0,LdU64(1)",
+            "0x1::M::f,1,Branch(3)",
+            "0x1::M::f,3,Ret",
+            "0x1::M::f,0,LdU64(1)",
+        ];
+        assert_eq!(jumps(&trace, "f"), BTreeMap::from([((1, 3), 1)]));
+    }
+
+    #[test]
+    fn merge_adds_counts() {
+        let module_name = Identifier::new("M").unwrap();
+        let f = Identifier::new("f").unwrap();
+        let g = Identifier::new("g").unwrap();
+        let module_map = |jumps: &[(u64, u64, u64)]| {
+            let mut module_map = ModuleCoverageMap::new(AccountAddress::ONE, module_name.clone());
+            for (from_pc, to_pc, count) in jumps {
+                module_map.insert_multi(f.clone(), *from_pc, *count);
+                module_map.insert_jump_multi(f.clone(), *from_pc, *to_pc, *count);
+            }
+            let mut map = ExecCoverageMapWithModules::empty();
+            map.module_maps.insert(
+                ("m.mv".to_string(), AccountAddress::ONE, module_name.clone()),
+                module_map,
+            );
+            map
+        };
+
+        let mut merged = module_map(&[(1, 3, 2), (2, 0, 1)]);
+        let mut other = module_map(&[(1, 3, 3), (1, 2, 1)]);
+        other
+            .module_maps
+            .values_mut()
+            .next()
+            .unwrap()
+            .insert(g.clone(), 0);
+        merged.merge(other);
+
+        let module_map = merged.module_maps.values().next().unwrap();
+        assert_eq!(
+            module_map.get_function_jumps(&f),
+            Some(&BTreeMap::from([((1, 2), 1), ((1, 3), 5), ((2, 0), 1)]))
+        );
+        assert_eq!(
+            module_map.get_function_coverage(&f),
+            Some(&BTreeMap::from([(1, 6), (2, 1)]))
+        );
+        assert_eq!(
+            module_map.get_function_coverage(&g),
+            Some(&BTreeMap::from([(0, 1)]))
+        );
+    }
+
+    #[test]
+    fn binary_file_roundtrip() {
+        let path = std::env::temp_dir().join(format!(
+            "coverage_map_roundtrip_{}.mvcov",
+            std::process::id()
+        ));
+        let mut coverage_map = CoverageMap {
+            exec_maps: BTreeMap::new(),
+        };
+        coverage_map.insert(
+            EXEC_ID,
+            AccountAddress::ONE,
+            Identifier::new("M").unwrap(),
+            Identifier::new("f").unwrap(),
+            0,
+        );
+        coverage_map.to_binary_file(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.starts_with(COVERAGE_MAP_MAGIC));
+        let read = CoverageMap::from_binary_file(&path).unwrap();
+        assert_eq!(
+            read.exec_maps[EXEC_ID].module_maps.len(),
+            coverage_map.exec_maps[EXEC_ID].module_maps.len()
+        );
+
+        // Maps without a version, or of another version, are rejected
+        std::fs::write(&path, bcs::to_bytes(&coverage_map).unwrap()).unwrap();
+        assert!(CoverageMap::from_binary_file(&path).is_err());
+        let mut other_version = COVERAGE_MAP_MAGIC.to_vec();
+        other_version.extend((COVERAGE_MAP_VERSION + 1).to_le_bytes());
+        other_version.extend(bcs::to_bytes(&coverage_map).unwrap());
+        std::fs::write(&path, other_version).unwrap();
+        assert!(CoverageMap::from_binary_file(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -8,6 +8,7 @@ use std::io::Write;
 
 pub mod coverage_map;
 pub mod source_coverage;
+pub mod source_report;
 pub mod summary;
 
 pub fn format_human_summary<M, F, W: Write>(
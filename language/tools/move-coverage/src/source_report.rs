@@ -0,0 +1,662 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Line and branch coverage of modules mapped back to their source files, and its export in the
+//! LCOV and Cobertura formats understood by standard coverage tooling.
+//!
+//! A line is covered if one of the instructions it starts is executed. Each conditional branch
+//! (`BrTrue` or `BrFalse`) has two outcomes, jumping to its target or falling through to the next
+//! instruction, and is fully covered once both are taken.
+
+#![forbid(unsafe_code)]
+
+use crate::coverage_map::{ExecCoverageMap, FunctionCoverage, FunctionJumps};
+use anyhow::{bail, Result};
+use clap::ArgEnum;
+use codespan::{FileId, Files};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_ir_types::location::Loc;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum CoverageFormat {
+    Lcov,
+    Cobertura,
+}
+
+#[derive(Debug, Default)]
+pub struct SourceReport {
+    pub modules: Vec<ModuleReport>,
+}
+
+#[derive(Debug)]
+pub struct ModuleReport {
+    /// The name of the module, e.g. `0x1::vector`
+    pub module_name: String,
+    pub source_path: PathBuf,
+    pub functions: Vec<FunctionReport>,
+}
+
+#[derive(Debug)]
+pub struct FunctionReport {
+    pub fn_name: String,
+    /// The (1-based) line the function is declared on
+    pub line: u32,
+    /// The number of times the function was called
+    pub hits: u64,
+    /// The number of times each (1-based) line of the function was executed
+    pub lines: BTreeMap<u32, u64>,
+    pub branches: Vec<BranchReport>,
+}
+
+#[derive(Debug)]
+pub struct BranchReport {
+    /// The (1-based) line of the branch
+    pub line: u32,
+    pub code_offset: CodeOffset,
+    /// The number of times the branch was executed, or `None` if it never was
+    pub hits: Option<u64>,
+    /// The number of times the branch jumped to its target, and fell through
+    pub taken: [u64; 2],
+}
+
+impl CoverageFormat {
+    /// The extension of files in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Lcov => "info",
+            Self::Cobertura => "xml",
+        }
+    }
+}
+
+impl SourceReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the coverage in `coverage_map` of `module`, compiled from `source_path`
+    pub fn add_module(
+        &mut self,
+        module: &CompiledModule,
+        source_map: &SourceMap,
+        source_path: &Path,
+        coverage_map: &ExecCoverageMap,
+    ) -> Result<()> {
+        let file_contents = fs::read_to_string(source_path)?;
+        if !source_map.check(&file_contents) {
+            bail!(
+                "File contents of {} out of sync with source map",
+                source_path.display()
+            );
+        }
+        let mut files = Files::new();
+        let file_id = files.add(source_path.as_os_str().to_os_string(), file_contents);
+        let line_of = |loc: Loc| line_number(&files, file_id, loc);
+
+        let module_id = module.self_id();
+        let module_map = coverage_map
+            .module_maps
+            .get(&(*module_id.address(), module_id.name().to_owned()));
+        let empty_coverage = FunctionCoverage::new();
+        let empty_jumps = FunctionJumps::new();
+
+        let mut functions = vec![];
+        for (function_def_idx, function_def) in module.function_defs().iter().enumerate() {
+            let code_unit = match &function_def.code {
+                // Native functions have no source to cover
+                None => continue,
+                Some(code_unit) => code_unit,
+            };
+            let fn_handle = module.function_handle_at(function_def.function);
+            let fn_name = module.identifier_at(fn_handle.name);
+            let function_def_idx = FunctionDefinitionIndex(function_def_idx as u16);
+            let function_map = source_map.get_function_source_map(function_def_idx)?;
+            let coverage = module_map
+                .and_then(|map| map.get_function_coverage(fn_name))
+                .unwrap_or(&empty_coverage);
+            let jumps = module_map
+                .and_then(|map| map.get_function_jumps(fn_name))
+                .unwrap_or(&empty_jumps);
+            let count = |offset: CodeOffset| coverage.get(&(offset as u64)).copied();
+
+            let mut lines = BTreeMap::new();
+            let mut branches = vec![];
+            for (offset, instruction) in code_unit.code.iter().enumerate() {
+                let offset = offset as CodeOffset;
+                let line = line_of(source_map.get_code_location(function_def_idx, offset)?);
+                let line_hits = lines.entry(line).or_insert(0);
+                *line_hits = (*line_hits).max(count(offset).unwrap_or(0));
+
+                let target = match instruction {
+                    Bytecode::BrTrue(target) | Bytecode::BrFalse(target) => *target,
+                    _ => continue,
+                };
+                // A branch to the next instruction has a single outcome
+                if target == offset + 1 {
+                    continue;
+                }
+                let hits = count(offset);
+                let jumped = jumps
+                    .get(&(offset as u64, target as u64))
+                    .copied()
+                    .unwrap_or(0);
+                branches.push(BranchReport {
+                    line,
+                    code_offset: offset,
+                    hits,
+                    taken: [jumped, hits.unwrap_or(0).saturating_sub(jumped)],
+                });
+            }
+
+            // The entry of the function is also reached by loops jumping back to it
+            let loop_entries: u64 = jumps
+                .iter()
+                .filter(|((from, to), _)| {
+                    *to == 0
+                        && matches!(
+                            code_unit.code.get(*from as usize),
+                            Some(Bytecode::Branch(_) | Bytecode::BrTrue(_) | Bytecode::BrFalse(_))
+                        )
+                })
+                .map(|(_, count)| *count)
+                .sum();
+            functions.push(FunctionReport {
+                fn_name: fn_name.to_string(),
+                line: line_of(function_map.definition_location),
+                hits: count(0).unwrap_or(0).saturating_sub(loop_entries),
+                lines,
+                branches,
+            });
+        }
+
+        self.modules.push(ModuleReport {
+            module_name: format!(
+                "0x{}::{}",
+                module_id.address().short_str_lossless(),
+                module_id.name()
+            ),
+            source_path: source_path.to_path_buf(),
+            functions,
+        });
+        Ok(())
+    }
+
+    /// The number of covered lines and the total number of lines
+    pub fn line_coverage(&self) -> (u64, u64) {
+        self.modules
+            .iter()
+            .fold((0, 0), |(covered, total), module| {
+                let lines = module.lines();
+                (
+                    covered + lines.values().filter(|hits| **hits > 0).count() as u64,
+                    total + lines.len() as u64,
+                )
+            })
+    }
+
+    /// The number of branch outcomes taken and the total number of branch outcomes
+    pub fn branch_coverage(&self) -> (u64, u64) {
+        self.modules
+            .iter()
+            .flat_map(|module| module.functions.iter())
+            .flat_map(|function| function.branches.iter())
+            .fold((0, 0), |(covered, total), branch| {
+                (covered + branch.outcomes_taken(), total + 2)
+            })
+    }
+
+    /// Writes the report in `format`, naming the source files relative to `source_root` if the
+    /// format allows it
+    pub fn write<W: Write>(
+        &self,
+        format: CoverageFormat,
+        w: &mut W,
+        package_name: &str,
+        source_root: &Path,
+    ) -> io::Result<()> {
+        match format {
+            CoverageFormat::Lcov => self.write_lcov(w),
+            CoverageFormat::Cobertura => self.write_cobertura(w, package_name, source_root),
+        }
+    }
+
+    /// Writes the report in the LCOV tracefile format, with one record per source file
+    pub fn write_lcov<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut files: BTreeMap<&Path, Vec<&ModuleReport>> = BTreeMap::new();
+        for module in &self.modules {
+            files
+                .entry(module.source_path.as_path())
+                .or_default()
+                .push(module);
+        }
+
+        for (source_path, modules) in files {
+            writeln!(w, "TN:")?;
+            writeln!(w, "SF:{}", source_path.display())?;
+            let functions: Vec<_> = modules
+                .iter()
+                .flat_map(|module| {
+                    module
+                        .functions
+                        .iter()
+                        .map(move |function| (module.module_name.as_str(), function))
+                })
+                .collect();
+            for (module_name, function) in &functions {
+                writeln!(
+                    w,
+                    "FN:{},{}::{}",
+                    function.line, module_name, function.fn_name
+                )?;
+            }
+            for (module_name, function) in &functions {
+                writeln!(
+                    w,
+                    "FNDA:{},{}::{}",
+                    function.hits, module_name, function.fn_name
+                )?;
+            }
+            writeln!(w, "FNF:{}", functions.len())?;
+            writeln!(
+                w,
+                "FNH:{}",
+                functions.iter().filter(|(_, f)| f.hits > 0).count()
+            )?;
+
+            let mut branches_found = 0;
+            let mut branches_hit = 0;
+            let branches = functions
+                .iter()
+                .flat_map(|(_, function)| function.branches.iter());
+            // Each branch is a block of its own, with its jump and fall through as its outcomes
+            for (block, branch) in branches.enumerate() {
+                for (outcome, taken) in branch.taken.iter().enumerate() {
+                    match branch.hits {
+                        None => writeln!(w, "BRDA:{},{},{},-", branch.line, block, outcome)?,
+                        Some(_) => {
+                            writeln!(w, "BRDA:{},{},{},{}", branch.line, block, outcome, taken)?
+                        }
+                    }
+                }
+                branches_found += 2;
+                branches_hit += branch.outcomes_taken();
+            }
+            writeln!(w, "BRF:{}", branches_found)?;
+            writeln!(w, "BRH:{}", branches_hit)?;
+
+            let mut lines = BTreeMap::new();
+            for module in &modules {
+                merge_lines(&mut lines, &module.lines());
+            }
+            for (line, hits) in &lines {
+                writeln!(w, "DA:{},{}", line, hits)?;
+            }
+            writeln!(w, "LF:{}", lines.len())?;
+            writeln!(w, "LH:{}", lines.values().filter(|hits| **hits > 0).count())?;
+            writeln!(w, "end_of_record")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the report in the Cobertura XML format, with one class per module. The source
+    /// files are named relative to `source_root`
+    pub fn write_cobertura<W: Write>(
+        &self,
+        w: &mut W,
+        package_name: &str,
+        source_root: &Path,
+    ) -> io::Result<()> {
+        let (lines_covered, lines_valid) = self.line_coverage();
+        let (branches_covered, branches_valid) = self.branch_coverage();
+        writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            w,
+            r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+        )?;
+        writeln!(
+            w,
+            r#"<coverage line-rate="{:.4}" branch-rate="{:.4}" lines-covered="{}" lines-valid="{}" branches-covered="{}" branches-valid="{}" complexity="0" version="{}" timestamp="0">"#,
+            rate(lines_covered, lines_valid),
+            rate(branches_covered, branches_valid),
+            lines_covered,
+            lines_valid,
+            branches_covered,
+            branches_valid,
+            env!("CARGO_PKG_VERSION"),
+        )?;
+        writeln!(w, "  <sources>")?;
+        writeln!(
+            w,
+            "    <source>{}</source>",
+            escape_xml(&source_root.display().to_string())
+        )?;
+        writeln!(w, "  </sources>")?;
+        writeln!(w, "  <packages>")?;
+        writeln!(
+            w,
+            r#"    <package name="{}" line-rate="{:.4}" branch-rate="{:.4}" complexity="0">"#,
+            escape_xml(package_name),
+            rate(lines_covered, lines_valid),
+            rate(branches_covered, branches_valid),
+        )?;
+        writeln!(w, "      <classes>")?;
+        for module in &self.modules {
+            let file_name = module
+                .source_path
+                .strip_prefix(source_root)
+                .unwrap_or(&module.source_path);
+            let lines = module.lines();
+            let branches = module.branches_by_line();
+            writeln!(
+                w,
+                r#"        <class name="{}" filename="{}" line-rate="{:.4}" branch-rate="{:.4}" complexity="0">"#,
+                escape_xml(&module.module_name),
+                escape_xml(&file_name.display().to_string()),
+                line_rate(&lines),
+                branch_rate(branches.values().flatten().copied()),
+            )?;
+            writeln!(w, "          <methods>")?;
+            for function in &module.functions {
+                writeln!(
+                    w,
+                    r#"            <method name="{}" signature="" line-rate="{:.4}" branch-rate="{:.4}" complexity="0">"#,
+                    escape_xml(&function.fn_name),
+                    line_rate(&function.lines),
+                    branch_rate(function.branches.iter()),
+                )?;
+                writeln!(w, "              <lines>")?;
+                write_cobertura_lines(
+                    w,
+                    "                ",
+                    &function.lines,
+                    &function.branches_by_line(),
+                )?;
+                writeln!(w, "              </lines>")?;
+                writeln!(w, "            </method>")?;
+            }
+            writeln!(w, "          </methods>")?;
+            writeln!(w, "          <lines>")?;
+            write_cobertura_lines(w, "            ", &lines, &branches)?;
+            writeln!(w, "          </lines>")?;
+            writeln!(w, "        </class>")?;
+        }
+        writeln!(w, "      </classes>")?;
+        writeln!(w, "    </package>")?;
+        writeln!(w, "  </packages>")?;
+        writeln!(w, "</coverage>")
+    }
+}
+
+impl ModuleReport {
+    /// The number of times each line of the module was executed
+    fn lines(&self) -> BTreeMap<u32, u64> {
+        let mut lines = BTreeMap::new();
+        for function in &self.functions {
+            merge_lines(&mut lines, &function.lines);
+        }
+        lines
+    }
+
+    fn branches_by_line(&self) -> BTreeMap<u32, Vec<&BranchReport>> {
+        let mut branches: BTreeMap<u32, Vec<&BranchReport>> = BTreeMap::new();
+        for (line, line_branches) in self
+            .functions
+            .iter()
+            .flat_map(|function| function.branches_by_line())
+        {
+            branches.entry(line).or_default().extend(line_branches);
+        }
+        branches
+    }
+}
+
+impl FunctionReport {
+    fn branches_by_line(&self) -> BTreeMap<u32, Vec<&BranchReport>> {
+        let mut branches: BTreeMap<u32, Vec<&BranchReport>> = BTreeMap::new();
+        for branch in &self.branches {
+            branches.entry(branch.line).or_default().push(branch);
+        }
+        branches
+    }
+}
+
+impl BranchReport {
+    /// The number of outcomes of the branch which were taken at least once
+    pub fn outcomes_taken(&self) -> u64 {
+        self.taken.iter().filter(|taken| **taken > 0).count() as u64
+    }
+}
+
+fn line_number(files: &Files<String>, file_id: FileId, loc: Loc) -> u32 {
+    files
+        .location(file_id, loc.start())
+        .map_or(0, |location| location.line.0)
+        + 1
+}
+
+fn merge_lines(lines: &mut BTreeMap<u32, u64>, other: &BTreeMap<u32, u64>) {
+    for (line, hits) in other {
+        let line_hits = lines.entry(*line).or_insert(0);
+        *line_hits = (*line_hits).max(*hits);
+    }
+}
+
+fn write_cobertura_lines<W: Write>(
+    w: &mut W,
+    indent: &str,
+    lines: &BTreeMap<u32, u64>,
+    branches: &BTreeMap<u32, Vec<&BranchReport>>,
+) -> io::Result<()> {
+    for (line, hits) in lines {
+        match branches.get(line) {
+            None => writeln!(w, r#"{}<line number="{}" hits="{}"/>"#, indent, line, hits)?,
+            Some(line_branches) => {
+                let taken: u64 = line_branches.iter().map(|b| b.outcomes_taken()).sum();
+                let total = 2 * line_branches.len() as u64;
+                writeln!(
+                    w,
+                    r#"{}<line number="{}" hits="{}" branch="true" condition-coverage="{:.0}% ({}/{})"/>"#,
+                    indent,
+                    line,
+                    hits,
+                    rate(taken, total) * 100f64,
+                    taken,
+                    total
+                )?
+            }
+        }
+    }
+    Ok(())
+}
+
+fn line_rate(lines: &BTreeMap<u32, u64>) -> f64 {
+    rate(
+        lines.values().filter(|hits| **hits > 0).count() as u64,
+        lines.len() as u64,
+    )
+}
+
+fn branch_rate<'a>(branches: impl Iterator<Item = &'a BranchReport>) -> f64 {
+    let (taken, total) = branches.fold((0, 0), |(taken, total), branch| {
+        (taken + branch.outcomes_taken(), total + 2)
+    });
+    rate(taken, total)
+}
+
+/// The ratio of `covered` to `total`, with nothing to cover counting as fully covered
+fn rate(covered: u64, total: u64) -> f64 {
+    if total == 0 {
+        1f64
+    } else {
+        covered as f64 / total as f64
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A module with a fully covered function, one with an untaken branch outcome and one never
+    /// called
+    fn report() -> SourceReport {
+        SourceReport {
+            modules: vec![ModuleReport {
+                module_name: "0x2::m".to_string(),
+                source_path: PathBuf::from("/pkg/sources/m.move"),
+                functions: vec![
+                    FunctionReport {
+                        fn_name: "covered".to_string(),
+                        line: 2,
+                        hits: 2,
+                        lines: BTreeMap::from([(3, 2), (4, 2)]),
+                        branches: vec![BranchReport {
+                            line: 3,
+                            code_offset: 2,
+                            hits: Some(2),
+                            taken: [1, 1],
+                        }],
+                    },
+                    FunctionReport {
+                        fn_name: "partly_covered".to_string(),
+                        line: 7,
+                        hits: 1,
+                        lines: BTreeMap::from([(8, 1), (9, 0)]),
+                        branches: vec![BranchReport {
+                            line: 8,
+                            code_offset: 3,
+                            hits: Some(1),
+                            taken: [0, 1],
+                        }],
+                    },
+                    FunctionReport {
+                        fn_name: "uncovered".to_string(),
+                        line: 12,
+                        hits: 0,
+                        lines: BTreeMap::from([(13, 0)]),
+                        branches: vec![BranchReport {
+                            line: 13,
+                            code_offset: 1,
+                            hits: None,
+                            taken: [0, 0],
+                        }],
+                    },
+                ],
+            }],
+        }
+    }
+
+    fn write(format: CoverageFormat) -> String {
+        let mut output = vec![];
+        report()
+            .write(format, &mut output, "Pkg", Path::new("/pkg"))
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn coverage_totals() {
+        let report = report();
+        assert_eq!(report.line_coverage(), (3, 5));
+        assert_eq!(report.branch_coverage(), (3, 6));
+    }
+
+    #[test]
+    fn lcov() {
+        let expected = "\
+TN:
+SF:/pkg/sources/m.move
+FN:2,0x2::m::covered
+FN:7,0x2::m::partly_covered
+FN:12,0x2::m::uncovered
+FNDA:2,0x2::m::covered
+FNDA:1,0x2::m::partly_covered
+FNDA:0,0x2::m::uncovered
+FNF:3
+FNH:2
+BRDA:3,0,0,1
+BRDA:3,0,1,1
+BRDA:8,1,0,0
+BRDA:8,1,1,1
+BRDA:13,2,0,-
+BRDA:13,2,1,-
+BRF:6
+BRH:3
+DA:3,2
+DA:4,2
+DA:8,1
+DA:9,0
+DA:13,0
+LF:5
+LH:3
+end_of_record
+";
+        assert_eq!(write(CoverageFormat::Lcov), expected);
+    }
+
+    #[test]
+    fn cobertura() {
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">
+<coverage line-rate="0.6000" branch-rate="0.5000" lines-covered="3" lines-valid="5" branches-covered="3" branches-valid="6" complexity="0" version="VERSION" timestamp="0">
+  <sources>
+    <source>/pkg</source>
+  </sources>
+  <packages>
+    <package name="Pkg" line-rate="0.6000" branch-rate="0.5000" complexity="0">
+      <classes>
+        <class name="0x2::m" filename="sources/m.move" line-rate="0.6000" branch-rate="0.5000" complexity="0">
+          <methods>
+            <method name="covered" signature="" line-rate="1.0000" branch-rate="1.0000" complexity="0">
+              <lines>
+                <line number="3" hits="2" branch="true" condition-coverage="100% (2/2)"/>
+                <line number="4" hits="2"/>
+              </lines>
+            </method>
+            <method name="partly_covered" signature="" line-rate="0.5000" branch-rate="0.5000" complexity="0">
+              <lines>
+                <line number="8" hits="1" branch="true" condition-coverage="50% (1/2)"/>
+                <line number="9" hits="0"/>
+              </lines>
+            </method>
+            <method name="uncovered" signature="" line-rate="0.0000" branch-rate="0.0000" complexity="0">
+              <lines>
+                <line number="13" hits="0" branch="true" condition-coverage="0% (0/2)"/>
+              </lines>
+            </method>
+          </methods>
+          <lines>
+            <line number="3" hits="2" branch="true" condition-coverage="100% (2/2)"/>
+            <line number="4" hits="2"/>
+            <line number="8" hits="1" branch="true" condition-coverage="50% (1/2)"/>
+            <line number="9" hits="0"/>
+            <line number="13" hits="0" branch="true" condition-coverage="0% (0/2)"/>
+          </lines>
+        </class>
+      </classes>
+    </package>
+  </packages>
+</coverage>
+"#
+        .replace("VERSION", env!("CARGO_PKG_VERSION"));
+        assert_eq!(write(CoverageFormat::Cobertura), expected);
+    }
+}
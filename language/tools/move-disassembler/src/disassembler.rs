@@ -11,7 +11,7 @@ use move_binary_format::{
     binary_views::BinaryIndexedView,
     control_flow_graph::{ControlFlowGraph, VMControlFlowGraph},
     file_format::{
-        Ability, AbilitySet, Bytecode, CodeUnit, FieldHandleIndex, FunctionDefinition,
+        Ability, AbilitySet, Bytecode, CodeOffset, CodeUnit, FieldHandleIndex, FunctionDefinition,
         FunctionDefinitionIndex, FunctionHandle, ModuleHandle, Signature, SignatureIndex,
         SignatureToken, StructDefinition, StructDefinitionIndex, StructFieldInformation,
         StructTypeParameter, TableIndex, TypeSignature, Visibility,
@@ -1245,6 +1245,75 @@ impl<'a> Disassembler<'a> {
         ))
     }
 
+    /// Disassembles the declaration of the function at `function_definition_index`, without its
+    /// body.
+    pub fn disassemble_function_signature(
+        &self,
+        function_definition_index: FunctionDefinitionIndex,
+    ) -> Result<String> {
+        let function_definition = self.get_function_def(function_definition_index)?;
+        let function_handle = self
+            .source_mapper
+            .bytecode
+            .function_handle_at(function_definition.function);
+        self.disassemble_function_def(
+            self.source_mapper
+                .source_map
+                .get_function_source_map(function_definition_index)?,
+            Some((function_definition, function_handle)),
+            self.source_mapper
+                .bytecode
+                .identifier_at(function_handle.name),
+            &function_handle.type_parameters,
+            function_handle.parameters,
+            None,
+        )
+    }
+
+    /// Disassembles the instructions of the function at `function_definition_index`, each with
+    /// its location in the source. Native functions have no instructions.
+    pub fn disassemble_instructions(
+        &self,
+        function_definition_index: FunctionDefinitionIndex,
+    ) -> Result<Vec<(String, Loc)>> {
+        let function_definition = self.get_function_def(function_definition_index)?;
+        let code = match &function_definition.code {
+            Some(code) => code,
+            None => return Ok(vec![]),
+        };
+        let function_handle = self
+            .source_mapper
+            .bytecode
+            .function_handle_at(function_definition.function);
+        let function_source_map = self
+            .source_mapper
+            .source_map
+            .get_function_source_map(function_definition_index)?;
+        let parameters = self
+            .source_mapper
+            .bytecode
+            .signature_at(function_handle.parameters);
+        let locals_sigs = self.source_mapper.bytecode.signature_at(code.locals);
+        let decl_location = &function_source_map.definition_location;
+        code.code
+            .iter()
+            .enumerate()
+            .map(|(code_offset, instruction)| {
+                let instr = self.disassemble_instruction(
+                    parameters,
+                    instruction,
+                    locals_sigs,
+                    function_source_map,
+                    decl_location,
+                )?;
+                let loc = function_source_map
+                    .get_code_location(code_offset as CodeOffset)
+                    .unwrap_or(*decl_location);
+                Ok((instr, loc))
+            })
+            .collect()
+    }
+
     pub fn disassemble(&self) -> Result<String> {
         let name_opt = self.source_mapper.source_map.module_name_opt.as_ref();
         let name = name_opt.map(|(addr, n)| format!("{}.{}", addr.short_str_lossless(), n));
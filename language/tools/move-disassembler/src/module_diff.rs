@@ -0,0 +1,472 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compares two versions of a module declaration by declaration: its structs, functions,
+//! constants, friends and metadata. The code of changed functions is compared instruction by
+//! instruction, each instruction carrying its location in the source so that changes can be shown
+//! next to the source lines they come from.
+//!
+//! Declarations are compared by what is in bytecode, so a module built from source compares equal
+//! to its bytecode, which has no names for parameters and locals. Indices into the tables of the
+//! module and branch targets are left out of the comparison, as adding a declaration or an
+//! instruction shifts them.
+
+use crate::disassembler::{Disassembler, DisassemblerOptions};
+use anyhow::Result;
+use move_binary_format::{
+    access::ModuleAccess,
+    binary_views::BinaryIndexedView,
+    file_format::{
+        Bytecode, CodeOffset, FunctionDefinitionIndex, StructDefinitionIndex, TableIndex,
+    },
+    CompiledModule,
+};
+use move_bytecode_source_map::{mapping::SourceMapping, source_map::SourceMap};
+use move_ir_types::location::Loc;
+use std::{collections::BTreeMap, fmt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DeclarationKind {
+    Struct,
+    Function,
+    Constant,
+    Friend,
+    Metadata,
+}
+
+/// A declaration of a module, in its disassembled form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declaration {
+    pub kind: DeclarationKind,
+    pub name: String,
+    pub text: String,
+    /// `text` with the names which are not in bytecode, e.g. of parameters, replaced by the ones
+    /// made up when disassembling bytecode without a source map
+    pub normalized_text: String,
+    /// For functions, their disassembled instructions
+    pub code: Vec<Instruction>,
+}
+
+/// A disassembled instruction of a function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub text: String,
+    /// `text` with names normalized like in `Declaration::normalized_text`, without the index of
+    /// the declaration the instruction refers to and with branch targets relative to the
+    /// instruction
+    pub normalized_text: String,
+    /// The location of the instruction in the source
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeclarationChange {
+    Added(Declaration),
+    Removed(Declaration),
+    Changed {
+        old: Declaration,
+        new: Declaration,
+        /// The edits turning the code of the old declaration into the code of the new one
+        code: Vec<CodeEdit>,
+    },
+}
+
+/// An instruction kept, removed or added between two versions of the code of a function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeEdit {
+    Same(String, Loc),
+    Removed(String, Loc),
+    Added(String, Loc),
+}
+
+/// Compares two versions of a module, with their source maps, returning the changes to its
+/// declarations ordered by kind and name.
+pub fn diff_modules(
+    old: (&CompiledModule, &SourceMap),
+    new: (&CompiledModule, &SourceMap),
+) -> Result<Vec<DeclarationChange>> {
+    let mut old_decls = declarations(old.0, old.1)?;
+    let new_decls = declarations(new.0, new.1)?;
+
+    let mut changes = vec![];
+    for (key, new) in new_decls {
+        match old_decls.remove(&key) {
+            None => changes.push(DeclarationChange::Added(new)),
+            Some(old) if !old.same_as(&new) => {
+                let code = diff_code(&old.code, &new.code);
+                changes.push(DeclarationChange::Changed { old, new, code })
+            }
+            Some(_) => (),
+        }
+    }
+    changes.extend(old_decls.into_values().map(DeclarationChange::Removed));
+    changes.sort_by(|c1, c2| {
+        let (d1, d2) = (c1.declaration(), c2.declaration());
+        (d1.kind, &d1.name).cmp(&(d2.kind, &d2.name))
+    });
+    Ok(changes)
+}
+
+/// The declarations of `module`, keyed by kind and name
+fn declarations(
+    module: &CompiledModule,
+    source_map: &SourceMap,
+) -> Result<BTreeMap<(DeclarationKind, String), Declaration>> {
+    let disassembler = new_disassembler(module, source_map.clone());
+    // Without a source map, the disassembler makes up the names which are not in bytecode
+    let normalizer = new_disassembler(
+        module,
+        SourceMap::dummy_from_view(
+            &BinaryIndexedView::Module(module),
+            source_map.definition_location,
+        )?,
+    );
+    let mut declarations = vec![];
+
+    for (idx, def) in module.struct_defs().iter().enumerate() {
+        let idx = StructDefinitionIndex(idx as TableIndex);
+        let name = module.identifier_at(module.struct_handle_at(def.struct_handle).name);
+        declarations.push(Declaration {
+            kind: DeclarationKind::Struct,
+            name: name.to_string(),
+            text: disassembler.disassemble_struct_def(idx)?,
+            normalized_text: normalizer.disassemble_struct_def(idx)?,
+            code: vec![],
+        });
+    }
+    for (idx, def) in module.function_defs().iter().enumerate() {
+        let idx = FunctionDefinitionIndex(idx as TableIndex);
+        let name = module.identifier_at(module.function_handle_at(def.function).name);
+        let bytecode = def.code.as_ref().map_or(&[][..], |code| &code.code);
+        let code = disassembler
+            .disassemble_instructions(idx)?
+            .into_iter()
+            .zip(normalizer.disassemble_instructions(idx)?)
+            .zip(bytecode)
+            .enumerate()
+            .map(
+                |(offset, (((text, loc), (normalized_text, _)), instr))| Instruction {
+                    text,
+                    normalized_text: normalize_instruction(
+                        instr,
+                        offset as CodeOffset,
+                        &normalized_text,
+                    ),
+                    loc,
+                },
+            )
+            .collect();
+        declarations.push(Declaration {
+            kind: DeclarationKind::Function,
+            name: name.to_string(),
+            text: disassembler.disassemble_function_signature(idx)?,
+            normalized_text: normalizer.disassemble_function_signature(idx)?,
+            code,
+        });
+    }
+    // Constants have no name, they are identified by their type and value
+    for constant in module.constant_pool() {
+        let text = match constant.deserialize_constant() {
+            Some(value) => format!("{:?}: {}", constant.type_, value),
+            None => format!("{:?}: {:?}", constant.type_, constant.data),
+        };
+        declarations.push(Declaration {
+            kind: DeclarationKind::Constant,
+            name: text.clone(),
+            normalized_text: text.clone(),
+            text,
+            code: vec![],
+        });
+    }
+    for friend in module.immediate_friends() {
+        let text = format!("friend {}", friend);
+        declarations.push(Declaration {
+            kind: DeclarationKind::Friend,
+            name: friend.to_string(),
+            normalized_text: text.clone(),
+            text,
+            code: vec![],
+        });
+    }
+    for metadata in &module.metadata {
+        let key = String::from_utf8_lossy(&metadata.key).to_string();
+        let value: String = metadata
+            .value
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let text = format!("{} = 0x{}", key, value);
+        declarations.push(Declaration {
+            kind: DeclarationKind::Metadata,
+            name: key,
+            normalized_text: text.clone(),
+            text,
+            code: vec![],
+        });
+    }
+
+    Ok(declarations
+        .into_iter()
+        .map(|decl| ((decl.kind, decl.name.clone()), decl))
+        .collect())
+}
+
+fn new_disassembler(module: &CompiledModule, source_map: SourceMap) -> Disassembler {
+    let mut options = DisassemblerOptions::new();
+    options.print_basic_blocks = false;
+    Disassembler::new(
+        SourceMapping::new(source_map, BinaryIndexedView::Module(module)),
+        options,
+    )
+}
+
+/// The normalized text of the instruction at `offset`, given its text disassembled without a
+/// source map
+fn normalize_instruction(instr: &Bytecode, offset: CodeOffset, text: &str) -> String {
+    let relative = |target: &CodeOffset| *target as i64 - offset as i64;
+    match instr {
+        Bytecode::Branch(target) => format!("Branch({:+})", relative(target)),
+        Bytecode::BrTrue(target) => format!("BrTrue({:+})", relative(target)),
+        Bytecode::BrFalse(target) => format!("BrFalse({:+})", relative(target)),
+        _ => {
+            // Instructions referring to a declaration show its index as `Name[index](...)`
+            let index = text.find('[').and_then(|start| {
+                let len = text[start..].find(']')?;
+                let is_index = text[..start].chars().all(|c| c.is_ascii_alphanumeric())
+                    && text[start + 1..start + len]
+                        .chars()
+                        .all(|c| c.is_ascii_digit());
+                is_index.then_some((start, start + len + 1))
+            });
+            match index {
+                Some((start, end)) => format!("{}{}", &text[..start], &text[end..]),
+                None => text.to_string(),
+            }
+        }
+    }
+}
+
+/// Aligns the instructions of two versions of a function along their longest common subsequence
+fn diff_code(old: &[Instruction], new: &[Instruction]) -> Vec<CodeEdit> {
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i].normalized_text == new[j].normalized_text {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut edits = vec![];
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i].normalized_text == new[j].normalized_text {
+            edits.push(CodeEdit::Same(new[j].text.clone(), new[j].loc));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            edits.push(CodeEdit::Added(new[j].text.clone(), new[j].loc));
+            j += 1;
+        } else {
+            edits.push(CodeEdit::Removed(old[i].text.clone(), old[i].loc));
+            i += 1;
+        }
+    }
+    edits
+}
+
+impl Declaration {
+    /// Whether the declarations are the same, regardless of where they are in the source and of
+    /// the names which are not in bytecode
+    fn same_as(&self, other: &Declaration) -> bool {
+        self.normalized_text == other.normalized_text
+            && self
+                .code
+                .iter()
+                .map(|instr| &instr.normalized_text)
+                .eq(other.code.iter().map(|instr| &instr.normalized_text))
+    }
+}
+
+impl DeclarationChange {
+    /// The new declaration, or the old one if it was removed
+    pub fn declaration(&self) -> &Declaration {
+        match self {
+            Self::Added(decl) | Self::Removed(decl) | Self::Changed { new: decl, .. } => decl,
+        }
+    }
+}
+
+impl fmt::Display for DeclarationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            Self::Struct => "struct",
+            Self::Function => "function",
+            Self::Constant => "constant",
+            Self::Friend => "friend",
+            Self::Metadata => "metadata",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_binary_format::file_format::{
+        basic_test_module, empty_module, Signature, SignatureIndex, SignatureToken,
+    };
+    use move_ir_types::location::Spanned;
+
+    fn no_loc() -> Loc {
+        Spanned::unsafe_no_loc(()).loc
+    }
+
+    fn instr(text: &str, normalized_text: &str) -> Instruction {
+        Instruction {
+            text: text.to_string(),
+            normalized_text: normalized_text.to_string(),
+            loc: no_loc(),
+        }
+    }
+
+    /// `basic_test_module` where `foo` takes a `u64` named `param` in the source map, and has
+    /// `code`
+    fn module(param: &str, code: Vec<Bytecode>) -> (CompiledModule, SourceMap) {
+        let mut module = basic_test_module();
+        // The source map is made while `foo` has no parameters, which are then added to it
+        let mut source_map =
+            SourceMap::dummy_from_view(&BinaryIndexedView::Module(&module), no_loc()).unwrap();
+        source_map
+            .add_parameter_mapping(FunctionDefinitionIndex(0), (param.to_string(), no_loc()))
+            .unwrap();
+        module.signatures.push(Signature(vec![SignatureToken::U64]));
+        module.function_handles[0].parameters =
+            SignatureIndex((module.signatures.len() - 1) as TableIndex);
+        module.function_defs[0].code.as_mut().unwrap().code = code;
+        (module, source_map)
+    }
+
+    /// The instructions added and removed by the change to a function
+    fn code_edits(change: &DeclarationChange) -> Vec<String> {
+        match change {
+            DeclarationChange::Changed { code, .. } => code
+                .iter()
+                .filter_map(|edit| match edit {
+                    CodeEdit::Same(..) => None,
+                    CodeEdit::Removed(instr, _) => Some(format!("- {}", instr)),
+                    CodeEdit::Added(instr, _) => Some(format!("+ {}", instr)),
+                })
+                .collect(),
+            _ => panic!("expected a changed declaration, got {:?}", change),
+        }
+    }
+
+    #[test]
+    fn diff_code_aligns_instructions() {
+        let old = [instr("a", "a"), instr("b", "b"), instr("c", "c")];
+        let new = [instr("a", "a"), instr("x", "x"), instr("c", "c")];
+        assert_eq!(
+            diff_code(&old, &new),
+            vec![
+                CodeEdit::Same("a".to_string(), no_loc()),
+                CodeEdit::Added("x".to_string(), no_loc()),
+                CodeEdit::Removed("b".to_string(), no_loc()),
+                CodeEdit::Same("c".to_string(), no_loc()),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_code_compares_normalized_instructions() {
+        let old = [instr("CopyLoc[0](x: u64)", "CopyLoc(Arg0: u64)")];
+        let new = [instr("CopyLoc[0](Arg0: u64)", "CopyLoc(Arg0: u64)")];
+        assert_eq!(
+            diff_code(&old, &new),
+            vec![CodeEdit::Same(
+                "CopyLoc[0](Arg0: u64)".to_string(),
+                no_loc()
+            )]
+        );
+    }
+
+    #[test]
+    fn normalize_instructions() {
+        assert_eq!(
+            normalize_instruction(&Bytecode::Pack(StructDefinitionIndex(1)), 0, "Pack[1](Bar)"),
+            "Pack(Bar)"
+        );
+        // Only the index of the declaration is left out, not the value of a constant
+        assert_eq!(
+            normalize_instruction(&Bytecode::Pop, 0, "LdConst[0](U8: [5])"),
+            "LdConst(U8: [5])"
+        );
+        assert_eq!(
+            normalize_instruction(&Bytecode::BrTrue(2), 5, "BrTrue(2)"),
+            "BrTrue(-3)"
+        );
+    }
+
+    #[test]
+    fn diff_modules_ignores_names_not_in_bytecode() {
+        let code = vec![Bytecode::CopyLoc(0), Bytecode::Pop, Bytecode::Ret];
+        let (old, old_map) = module("x", code.clone());
+        let (new, new_map) = module("Arg0", code);
+        assert!(diff_modules((&old, &old_map), (&new, &new_map))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn diff_modules_compares_relative_branch_targets() {
+        let (old, old_map) = module(
+            "x",
+            vec![
+                Bytecode::LdTrue,
+                Bytecode::BrFalse(3),
+                Bytecode::Nop,
+                Bytecode::Ret,
+            ],
+        );
+        let (new, new_map) = module(
+            "x",
+            vec![
+                Bytecode::LdFalse,
+                Bytecode::Pop,
+                Bytecode::LdTrue,
+                Bytecode::BrFalse(5),
+                Bytecode::Nop,
+                Bytecode::Ret,
+            ],
+        );
+        let changes = diff_modules((&old, &old_map), (&new, &new_map)).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(code_edits(&changes[0]), vec!["+ LdFalse", "+ Pop"]);
+    }
+
+    #[test]
+    fn diff_modules_removed_declarations() {
+        let (old, old_map) = module("x", vec![Bytecode::Ret]);
+        let new = empty_module();
+        let new_map =
+            SourceMap::dummy_from_view(&BinaryIndexedView::Module(&new), no_loc()).unwrap();
+        let removed = diff_modules((&old, &old_map), (&new, &new_map))
+            .unwrap()
+            .into_iter()
+            .map(|change| match change {
+                DeclarationChange::Removed(decl) => (decl.kind, decl.name),
+                _ => panic!("expected a removed declaration, got {:?}", change),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            removed,
+            vec![
+                (DeclarationKind::Struct, "Bar".to_string()),
+                (DeclarationKind::Function, "foo".to_string()),
+            ]
+        );
+    }
+}
@@ -3,15 +3,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    compilation::compiled_package::CompiledPackage, resolution::resolution_graph::ResolvedGraph,
+    compilation::compiled_package::{make_source_and_deps_for_compiler, CompiledPackage},
+    resolution::resolution_graph::{ResolvedGraph, ResolvedTable},
     source_package::parsed_manifest::PackageName,
 };
 use anyhow::Result;
 use move_compiler::{
     compiled_unit::AnnotatedCompiledUnit,
     diagnostics::{report_diagnostics_to_color_buffer, report_warnings, FilesSourceText},
+    shared::PackagePaths,
     Compiler,
 };
+use move_symbol_pool::Symbol;
 use petgraph::algo::toposort;
 use std::{collections::BTreeSet, io::Write, path::Path};
 
@@ -157,9 +160,53 @@ impl BuildPlan {
             Some(under_path) => under_path.clone(),
             None => self.resolution_graph.root_package_path.clone(),
         };
+        let transitive_dependencies = self.transitive_dependencies();
+
+        let compiled = CompiledPackage::build_all(
+            writer,
+            &project_root,
+            root_package.clone(),
+            transitive_dependencies,
+            bytecode_version,
+            &self.resolution_graph,
+            &mut compiler_driver,
+        )?;
+
+        Self::clean(
+            &project_root.join(CompiledPackageLayout::Root.path()),
+            self.sorted_deps.iter().copied().collect(),
+        )?;
+        Ok(compiled)
+    }
+
+    /// The files of the root package and of its dependencies, with their address mappings, as
+    /// they are passed to the compiler. Each dependency is paired with whether its sources are
+    /// available, otherwise its paths are those of its bytecode files.
+    pub fn package_paths(&self) -> Result<(PackagePaths, Vec<(PackagePaths, bool)>)> {
+        let root_package = &self.resolution_graph.package_table[&self.root];
+        let deps = self
+            .transitive_dependencies()
+            .into_iter()
+            .map(|(name, _, paths, resolution_table, source_available)| {
+                (name, paths, resolution_table, source_available)
+            })
+            .collect();
+        make_source_and_deps_for_compiler(&self.resolution_graph, root_package, deps)
+    }
+
+    fn transitive_dependencies(
+        &self,
+    ) -> Vec<(
+        /* name */ Symbol,
+        /* is immediate */ bool,
+        /* source paths */ Vec<Symbol>,
+        /* address mapping */ &ResolvedTable,
+        /* whether source is available */ bool,
+    )> {
+        let root_package = &self.resolution_graph.package_table[&self.root];
         let immediate_dependencies_names =
             root_package.immediate_dependencies(&self.resolution_graph);
-        let transitive_dependencies = root_package
+        root_package
             .transitive_dependencies(&self.resolution_graph)
             .into_iter()
             .map(|package_name| {
@@ -185,23 +232,7 @@ impl BuildPlan {
                     source_available,
                 )
             })
-            .collect();
-
-        let compiled = CompiledPackage::build_all(
-            writer,
-            &project_root,
-            root_package.clone(),
-            transitive_dependencies,
-            bytecode_version,
-            &self.resolution_graph,
-            &mut compiler_driver,
-        )?;
-
-        Self::clean(
-            &project_root.join(CompiledPackageLayout::Root.path()),
-            self.sorted_deps.iter().copied().collect(),
-        )?;
-        Ok(compiled)
+            .collect()
     }
 
     #[cfg(feature = "evm-backend")]
@@ -575,10 +575,11 @@ impl CompiledPackage {
             &resolved_package,
             transitive_dependencies,
         )?;
+        // Lints are not run in test mode, where warnings can be treated as errors
         let flags = if resolution_graph.build_options.test_mode {
             Flags::testing()
         } else {
-            Flags::empty()
+            Flags::empty().set_lint(true)
         };
         // Partition deps_package according whether src is available
         let (src_deps, bytecode_deps): (Vec<_>, Vec<_>) = deps_package_paths
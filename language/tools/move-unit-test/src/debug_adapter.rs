@@ -0,0 +1,615 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A server of the Debug Adapter Protocol (DAP), to debug unit tests from an IDE.
+//!
+//! The client sends requests over the input of the server, while the server sends responses and
+//! events over its output. Both are JSON messages preceded by a `Content-Length` header. Requests
+//! are served on a separate thread, while the tests run one after the other on the thread that
+//! created the adapter, with a debugger installed on the Move VM. When the debugger stops, e.g. at
+//! a breakpoint, it snapshots the call stack and waits for the client to resume the execution.
+//!
+//! Breakpoints and stepping are per source line, as mapped by the source maps of the modules,
+//! while the values of locals are rendered with the resource viewer.
+
+use anyhow::{anyhow, bail, Result};
+use move_binary_format::file_format::FunctionDefinitionIndex;
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::files::FileHash;
+use move_compiler::{
+    hlir::translate::{display_var, DisplayVar},
+    unit_test::TestPlan,
+};
+use move_core_types::language_storage::ModuleId;
+use move_resource_viewer::MoveValueAnnotator;
+use move_symbol_pool::Symbol;
+use move_vm_runtime::debugger::{DebugFrame, DebugState, Debugger};
+use move_vm_test_utils::InMemoryStorage;
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io::{BufRead, Read, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+};
+
+/// The tests run on a single thread
+const THREAD_ID: u64 = 1;
+
+/// A position in a source file, with 1-based line and column
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SourceLocation {
+    path: Arc<PathBuf>,
+    line: u64,
+    column: u64,
+}
+
+struct SourceFile {
+    path: Arc<PathBuf>,
+    /// The offsets at which the lines of the file start
+    line_starts: Vec<usize>,
+}
+
+impl SourceLocation {
+    fn same_line(&self, other: &SourceLocation) -> bool {
+        self.path == other.path && self.line == other.line
+    }
+}
+
+/// Maps code offsets of functions to source locations
+struct Sources {
+    source_maps: BTreeMap<ModuleId, SourceMap>,
+    files: HashMap<FileHash, SourceFile>,
+    /// The lines of each file that have code, where breakpoints can be set
+    code_lines: BTreeMap<Arc<PathBuf>, BTreeSet<u64>>,
+}
+
+/// A frame of the call stack at which execution stopped
+struct StackFrame {
+    name: String,
+    location: Option<SourceLocation>,
+    /// The names of the locals holding a value, and their rendered values
+    locals: Vec<(String, String)>,
+}
+
+/// How execution proceeds until it stops next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Stop on the first line executed
+    Entry,
+    /// Stop at breakpoints only
+    Continue,
+    /// Stop on the next line executed
+    Pause,
+    /// Stop on the next line executed, if it is in a frame at the given depth or in a caller
+    StepOver(usize),
+    /// Stop on the next line executed, in any frame
+    StepIn,
+    /// Stop on the next line executed in a caller of the frame at the given depth
+    StepOut(usize),
+}
+
+struct State {
+    configured: bool,
+    disconnected: bool,
+    breakpoints: BTreeMap<PathBuf, BTreeSet<u64>>,
+    mode: Mode,
+    /// The call stack, innermost frame first, while execution is stopped
+    stopped: Option<Vec<StackFrame>>,
+}
+
+/// The state shared by the thread serving requests and the thread running the tests
+pub struct DebugAdapter {
+    sources: Sources,
+    /// The modules of the tests, used to render values
+    storage: InMemoryStorage,
+    output: Mutex<(Box<dyn Write + Send>, u64)>,
+    state: Mutex<State>,
+    cvar: Condvar,
+}
+
+impl Sources {
+    fn new(test_plan: &TestPlan) -> Self {
+        let files = test_plan
+            .files
+            .iter()
+            .map(|(fhash, (fname, contents))| {
+                let path = Path::new(fname.as_str());
+                let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+                let line_starts = std::iter::once(0)
+                    .chain(contents.match_indices('\n').map(|(idx, _)| idx + 1))
+                    .collect();
+                let file = SourceFile {
+                    path: Arc::new(path),
+                    line_starts,
+                };
+                (*fhash, file)
+            })
+            .collect::<HashMap<_, _>>();
+        let mut sources = Sources {
+            source_maps: BTreeMap::new(),
+            files,
+            code_lines: BTreeMap::new(),
+        };
+        for (module_id, info) in &test_plan.module_info {
+            for idx in 0..info.module.function_defs.len() {
+                let fdef_idx = FunctionDefinitionIndex(idx as u16);
+                let function_map = match info.source_map.get_function_source_map(fdef_idx) {
+                    Ok(function_map) => function_map,
+                    Err(_) => continue,
+                };
+                for loc in function_map.code_map.values() {
+                    if let Some(location) = sources.location(loc.file_hash(), loc.start()) {
+                        sources
+                            .code_lines
+                            .entry(location.path)
+                            .or_default()
+                            .insert(location.line);
+                    }
+                }
+            }
+            sources
+                .source_maps
+                .insert(module_id.clone(), info.source_map.clone());
+        }
+        sources
+    }
+
+    fn location(&self, fhash: FileHash, offset: u32) -> Option<SourceLocation> {
+        let file = self.files.get(&fhash)?;
+        let offset = offset as usize;
+        let line = file.line_starts.partition_point(|start| *start <= offset);
+        Some(SourceLocation {
+            path: file.path.clone(),
+            line: line as u64,
+            column: (offset - file.line_starts[line - 1]) as u64 + 1,
+        })
+    }
+
+    /// The location of the instruction executed by `frame`
+    fn frame_location(&self, frame: &DebugFrame) -> Option<SourceLocation> {
+        let loc = self
+            .source_maps
+            .get(frame.module_id()?)?
+            .get_code_location(frame.function_index(), frame.pc())
+            .ok()?;
+        self.location(loc.file_hash(), loc.start())
+    }
+}
+
+impl DebugAdapter {
+    pub fn new(
+        test_plan: &TestPlan,
+        storage: InMemoryStorage,
+        output: impl Write + Send + 'static,
+    ) -> Self {
+        DebugAdapter {
+            sources: Sources::new(test_plan),
+            storage,
+            output: Mutex::new((Box::new(output), 1)),
+            state: Mutex::new(State {
+                configured: false,
+                disconnected: false,
+                breakpoints: BTreeMap::new(),
+                mode: Mode::Continue,
+                stopped: None,
+            }),
+            cvar: Condvar::new(),
+        }
+    }
+
+    /// Serves the requests read from `input` on a new thread, until the client disconnects
+    pub fn serve(self: Arc<Self>, mut input: impl BufRead + Send + 'static) -> JoinHandle<()> {
+        thread::spawn(move || loop {
+            let request = match read_message(&mut input) {
+                Ok(Some(request)) => request,
+                Ok(None) => {
+                    self.disconnect();
+                    return;
+                }
+                Err(err) => {
+                    eprintln!("could not read request: {}", err);
+                    self.disconnect();
+                    return;
+                }
+            };
+            let command = request["command"].as_str().unwrap_or_default().to_string();
+            match self.on_request(&command, &request["arguments"]) {
+                Ok(body) => self.send(json!({
+                    "type": "response",
+                    "request_seq": request["seq"],
+                    "success": true,
+                    "command": command,
+                    "body": body,
+                })),
+                Err(err) => self.send(json!({
+                    "type": "response",
+                    "request_seq": request["seq"],
+                    "success": false,
+                    "command": command,
+                    "message": err.to_string(),
+                })),
+            }
+            match command.as_str() {
+                "initialize" => self.send_event("initialized", json!({})),
+                "disconnect" | "terminate" => return,
+                _ => (),
+            }
+        })
+    }
+
+    /// Waits until the client is done configuring the session, e.g. setting breakpoints. Returns
+    /// `false` if the client disconnected instead.
+    pub fn wait_until_configured(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        while !state.configured && !state.disconnected {
+            state = self.cvar.wait(state).unwrap();
+        }
+        !state.disconnected
+    }
+
+    /// Tells the client that the tests are done
+    pub fn terminate(&self) {
+        self.send_event("terminated", json!({}))
+    }
+
+    /// A debugger for a test, to install on the thread running it
+    pub fn debugger(self: &Arc<Self>) -> Box<dyn Debugger> {
+        Box::new(TestDebugger {
+            adapter: self.clone(),
+            lines: vec![],
+        })
+    }
+
+    /// A writer sending what is written to the client, to be shown as the output of the tests
+    pub fn output_writer(self: &Arc<Self>) -> impl Write + Send {
+        DebugOutput(self.clone())
+    }
+
+    fn on_request(&self, command: &str, args: &Value) -> Result<Value> {
+        let mut state = self.state.lock().unwrap();
+        Ok(match command {
+            "initialize" => json!({
+                "supportsConfigurationDoneRequest": true,
+            }),
+            "launch" | "attach" => {
+                if args["stopOnEntry"].as_bool().unwrap_or(false) {
+                    state.mode = Mode::Entry;
+                }
+                json!({})
+            }
+            "setBreakpoints" => {
+                let path = args["source"]["path"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("missing source path"))?;
+                let path = Path::new(path);
+                let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+                let lines = args["breakpoints"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|bp| bp["line"].as_u64())
+                    .collect::<BTreeSet<_>>();
+                let code_lines = self.sources.code_lines.get(&path);
+                let breakpoints = lines
+                    .iter()
+                    .map(|line| {
+                        let verified = code_lines.map_or(false, |lines| lines.contains(line));
+                        json!({ "verified": verified, "line": line })
+                    })
+                    .collect::<Vec<_>>();
+                state.breakpoints.insert(path, lines);
+                json!({ "breakpoints": breakpoints })
+            }
+            "setExceptionBreakpoints" => json!({}),
+            "configurationDone" => {
+                state.configured = true;
+                self.cvar.notify_all();
+                json!({})
+            }
+            "threads" => json!({
+                "threads": [{ "id": THREAD_ID, "name": "Move unit tests" }],
+            }),
+            "stackTrace" => {
+                let frames = state.stopped.iter().flatten().enumerate();
+                let frames = frames
+                    .map(|(id, frame)| {
+                        let mut json = json!({
+                            "id": id,
+                            "name": frame.name,
+                            "line": 0,
+                            "column": 0,
+                        });
+                        if let Some(location) = &frame.location {
+                            json["source"] = json!({
+                                "name": location.path.file_name().map(|n| n.to_string_lossy()),
+                                "path": location.path.to_string_lossy(),
+                            });
+                            json["line"] = json!(location.line);
+                            json["column"] = json!(location.column);
+                        }
+                        json
+                    })
+                    .collect::<Vec<_>>();
+                json!({ "stackFrames": frames, "totalFrames": frames.len() })
+            }
+            "scopes" => {
+                // a frame has a single scope, with the same (1-based) reference as the frame
+                let frame_id = args["frameId"].as_u64().unwrap_or_default();
+                json!({
+                    "scopes": [{
+                        "name": "Locals",
+                        "variablesReference": frame_id + 1,
+                        "expensive": false,
+                    }],
+                })
+            }
+            "variables" => {
+                let reference = args["variablesReference"].as_u64().unwrap_or_default();
+                let locals = state
+                    .stopped
+                    .iter()
+                    .flatten()
+                    .nth((reference as usize).wrapping_sub(1))
+                    .map(|frame| frame.locals.as_slice())
+                    .unwrap_or_default();
+                let variables = locals
+                    .iter()
+                    .map(|(name, value)| {
+                        json!({ "name": name, "value": value, "variablesReference": 0 })
+                    })
+                    .collect::<Vec<_>>();
+                json!({ "variables": variables })
+            }
+            "continue" => {
+                self.resume(&mut state, Mode::Continue);
+                json!({ "allThreadsContinued": true })
+            }
+            "next" => {
+                let depth = state.stopped.as_ref().map_or(0, |frames| frames.len());
+                self.resume(&mut state, Mode::StepOver(depth));
+                json!({})
+            }
+            "stepIn" => {
+                self.resume(&mut state, Mode::StepIn);
+                json!({})
+            }
+            "stepOut" => {
+                let depth = state.stopped.as_ref().map_or(0, |frames| frames.len());
+                self.resume(&mut state, Mode::StepOut(depth));
+                json!({})
+            }
+            "pause" => {
+                state.mode = Mode::Pause;
+                json!({})
+            }
+            "disconnect" | "terminate" => {
+                state.disconnected = true;
+                self.cvar.notify_all();
+                json!({})
+            }
+            _ => bail!("unsupported request '{}'", command),
+        })
+    }
+
+    fn resume(&self, state: &mut MutexGuard<State>, mode: Mode) {
+        state.mode = mode;
+        state.stopped = None;
+        self.cvar.notify_all();
+    }
+
+    fn disconnect(&self) {
+        self.state.lock().unwrap().disconnected = true;
+        self.cvar.notify_all();
+    }
+
+    fn send_event(&self, event: &str, body: Value) {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+
+    fn send(&self, mut message: Value) {
+        let (output, seq) = &mut *self.output.lock().unwrap();
+        message["seq"] = json!(*seq);
+        *seq += 1;
+        if let Err(err) = write_message(output, &message) {
+            eprintln!("could not send message: {}", err);
+        }
+    }
+
+    /// Snapshots `stack`, innermost frame first
+    fn stack_frames(&self, stack: &[DebugFrame]) -> Vec<StackFrame> {
+        let annotator = MoveValueAnnotator::new(&self.storage);
+        stack
+            .iter()
+            .rev()
+            .map(|frame| {
+                let name = match frame.module_id() {
+                    Some(module_id) => format!(
+                        "0x{}::{}::{}",
+                        module_id.address().short_str_lossless(),
+                        module_id.name(),
+                        frame.function_name()
+                    ),
+                    None => frame.function_name().to_string(),
+                };
+                let function_map = frame
+                    .module_id()
+                    .and_then(|module_id| self.sources.source_maps.get(module_id))
+                    .and_then(|source_map| {
+                        source_map
+                            .get_function_source_map(frame.function_index())
+                            .ok()
+                    });
+                let mut locals = vec![];
+                for idx in 0..frame.local_count() {
+                    let name = match function_map
+                        .and_then(|map| map.get_parameter_or_local_name(idx as u64))
+                        .and_then(|(name, _)| local_name(&name))
+                    {
+                        Some(name) => name,
+                        None => continue,
+                    };
+                    let value = match frame.local(idx) {
+                        Ok(Some(value)) => value,
+                        Ok(None) | Err(_) => continue,
+                    };
+                    let rendered = value
+                        .value
+                        .simple_serialize()
+                        .and_then(|blob| annotator.view_value(&value.type_, &blob).ok())
+                        .map_or_else(|| format!("{:?}", value.value), |v| v.to_string());
+                    let prefix = match value.reference {
+                        None => "",
+                        Some(false) => "&",
+                        Some(true) => "&mut ",
+                    };
+                    locals.push((name, format!("{}{}", prefix, rendered)))
+                }
+                StackFrame {
+                    name,
+                    location: self.sources.frame_location(frame),
+                    locals,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The name shown for a local, or `None` for a local introduced by the compiler, i.e. a temporary
+/// or the state of a `for` loop
+fn local_name(name: &str) -> Option<String> {
+    match display_var(Symbol::from(name)) {
+        DisplayVar::Tmp => None,
+        DisplayVar::Orig(_) if name.starts_with("_for#") => None,
+        DisplayVar::Orig(name) => Some(name),
+    }
+}
+
+/// The debugger of a single test, stopping execution according to the state of the adapter
+struct TestDebugger {
+    adapter: Arc<DebugAdapter>,
+    /// The last line executed by each frame of the call stack, outermost first
+    lines: Vec<SourceLocation>,
+}
+
+impl Debugger for TestDebugger {
+    fn on_instruction(&mut self, debug_state: &DebugState) {
+        let stack = debug_state.frames();
+        let location = match self.adapter.sources.frame_location(stack.last().unwrap()) {
+            Some(location) => location,
+            None => return,
+        };
+        // only the first instruction of a line can stop the execution, and returning from a call
+        // does not count as executing a new line
+        let depth = stack.len();
+        if self.lines.len() >= depth && self.lines[depth - 1].same_line(&location) {
+            self.lines.truncate(depth);
+            return;
+        }
+        self.lines.truncate(depth - 1);
+        self.lines.push(location.clone());
+
+        let mut state = self.adapter.state.lock().unwrap();
+        if state.disconnected {
+            return;
+        }
+        let at_breakpoint = state
+            .breakpoints
+            .get(location.path.as_path())
+            .map_or(false, |lines| lines.contains(&location.line));
+        let reason = match state.mode {
+            Mode::Entry => "entry",
+            Mode::Pause => "pause",
+            Mode::StepIn => "step",
+            Mode::StepOver(from) if depth <= from => "step",
+            Mode::StepOut(from) if depth < from => "step",
+            _ if at_breakpoint => "breakpoint",
+            Mode::Continue | Mode::StepOver(_) | Mode::StepOut(_) => return,
+        };
+
+        state.stopped = Some(self.adapter.stack_frames(stack));
+        self.adapter.send_event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        );
+        while state.stopped.is_some() && !state.disconnected {
+            state = self.adapter.cvar.wait(state).unwrap();
+        }
+    }
+}
+
+struct DebugOutput(Arc<DebugAdapter>);
+
+impl Write for DebugOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.send_event(
+            "output",
+            json!({ "category": "stdout", "output": String::from_utf8_lossy(buf) }),
+        );
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads a message, or returns `None` at the end of the input
+fn read_message(input: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix("Content-Length:") {
+            content_length = Some(length.trim().parse::<usize>()?);
+        }
+    }
+    let content_length = content_length.ok_or_else(|| anyhow!("missing Content-Length header"))?;
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> Result<()> {
+    let content = message.to_string();
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_roundtrip() {
+        let message = json!({ "seq": 1, "type": "request", "command": "threads" });
+        let mut buf = vec![];
+        write_message(&mut buf, &message).unwrap();
+        write_message(&mut buf, &message).unwrap();
+
+        let mut input = buf.as_slice();
+        assert_eq!(read_message(&mut input).unwrap(), Some(message.clone()));
+        assert_eq!(read_message(&mut input).unwrap(), Some(message));
+        assert_eq!(read_message(&mut input).unwrap(), None);
+    }
+
+    #[test]
+    fn compiler_locals_are_hidden() {
+        assert_eq!(local_name("x#1#0"), Some("x".to_string()));
+        assert_eq!(local_name("%#3"), None);
+        assert_eq!(local_name("_for#index#2#0"), None);
+    }
+}
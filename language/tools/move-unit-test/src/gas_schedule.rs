@@ -0,0 +1,430 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A declarative gas schedule, covering the costs of instructions, of natives and of storage
+//! accesses, which is read from a TOML or JSON file so that costs can be tuned without recompiling.
+//!
+//! ```toml
+//! version = 1
+//!
+//! [instructions.MoveTo]
+//! instruction_gas = 13
+//! memory_gas = 1
+//!
+//! [natives.stdlib.vector.push_back]
+//! base = 0
+//! legacy_per_abstract_memory_unit = 0
+//!
+//! [storage.table]
+//! load_base = 0
+//! load_per_byte = 0
+//! load_failure = 0
+//! ```
+//!
+//! The costs are bare numbers in the schedule, while a `GasQuantity` serializes as a struct
+//! holding its quantity, so quantities are converted when reading and writing schedules.
+
+use anyhow::{bail, Context, Result};
+use move_core_types::account_address::AccountAddress;
+use move_stdlib::natives::{all_natives, nursery_natives, GasParameters, NurseryGasParameters};
+use move_table_extension::{
+    table_natives, AddBoxGasParameters, BorrowBoxGasParameters, CommonGasParameters,
+    ContainsBoxGasParameters, DestroyEmptyBoxGasParameters, DropUncheckedBoxGasParameters,
+    LengthBoxGasParameters, NewTableHandleGasParameters, NextKeyBoxGasParameters,
+    RemoveGasParameters,
+};
+use move_vm_runtime::native_functions::NativeFunctionTable;
+use move_vm_test_utils::gas_schedule::{CostTable, GasCost, INITIAL_COST_SCHEDULE};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    path::Path,
+};
+
+/// The modules of the table extension, whose natives are costed by the `natives.table` section
+const TABLE_MODULES: &[&str] = &["table", "iterable_table"];
+
+/// The version of the gas schedule format, to be bumped on changes that break existing schedules
+pub const GAS_SCHEDULE_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasSchedule {
+    pub version: u64,
+    /// The costs of the instructions, keyed by their names, e.g. `MoveTo`
+    pub instructions: BTreeMap<String, GasCost>,
+    pub natives: NativeGasSchedule,
+    pub storage: StorageGasSchedule,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NativeGasSchedule {
+    pub stdlib: GasParameters,
+    pub nursery: NurseryGasParameters,
+    pub table: TableGasSchedule,
+}
+
+/// The costs of the table natives, except for loading entries which is part of the storage costs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableGasSchedule {
+    pub new_table_handle: NewTableHandleGasParameters,
+    pub add_box: AddBoxGasParameters,
+    pub borrow_box: BorrowBoxGasParameters,
+    pub contains_box: ContainsBoxGasParameters,
+    pub remove_box: RemoveGasParameters,
+    pub destroy_empty_box: DestroyEmptyBoxGasParameters,
+    pub drop_unchecked_box: DropUncheckedBoxGasParameters,
+    pub length_box: LengthBoxGasParameters,
+    pub next_key_box: NextKeyBoxGasParameters,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageGasSchedule {
+    /// The costs of loading table entries
+    pub table: CommonGasParameters,
+}
+
+/// The change of a cost between two schedules, `None` for a cost missing in a schedule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasScheduleChange {
+    /// The path of the cost in the schedule, e.g. `natives.stdlib.vector.push_back.base`
+    pub key: String,
+    pub old: Option<u64>,
+    pub new: Option<u64>,
+}
+
+impl GasSchedule {
+    /// The schedule with the given costs of instructions, where natives and storage are free
+    pub fn new(cost_table: &CostTable) -> Self {
+        let table = move_table_extension::GasParameters::zeros();
+        Self {
+            version: GAS_SCHEDULE_VERSION,
+            instructions: cost_table.named_instruction_costs(),
+            natives: NativeGasSchedule {
+                stdlib: GasParameters::zeros(),
+                nursery: NurseryGasParameters::zeros(),
+                table: TableGasSchedule {
+                    new_table_handle: table.new_table_handle,
+                    add_box: table.add_box,
+                    borrow_box: table.borrow_box,
+                    contains_box: table.contains_box,
+                    remove_box: table.remove_box,
+                    destroy_empty_box: table.destroy_empty_box,
+                    drop_unchecked_box: table.drop_unchecked_box,
+                    length_box: table.length_box,
+                    next_key_box: table.next_key_box,
+                },
+            },
+            storage: StorageGasSchedule {
+                table: table.common,
+            },
+        }
+    }
+
+    /// The schedule of the costs hardcoded in the Move VM test utilities
+    pub fn initial() -> Self {
+        Self::new(&INITIAL_COST_SCHEDULE)
+    }
+
+    /// Reads the schedule at `path`, in JSON if its extension is `json` and in TOML otherwise
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read the gas schedule {}", path.display()))?;
+        let schedule = Self::parse(&contents, is_json(path))
+            .with_context(|| format!("Invalid gas schedule {}", path.display()))?;
+        if schedule.version != GAS_SCHEDULE_VERSION {
+            bail!(
+                "Unsupported gas schedule version {} in {} (expected {})",
+                schedule.version,
+                path.display(),
+                GAS_SCHEDULE_VERSION
+            );
+        }
+        Ok(schedule)
+    }
+
+    /// Writes the schedule to `path`, in JSON if its extension is `json` and in TOML otherwise
+    pub fn save(&self, path: &Path) -> Result<()> {
+        Ok(fs::write(path, self.print(is_json(path))?)?)
+    }
+
+    /// Parses a schedule in JSON if `json` and in TOML otherwise
+    fn parse(contents: &str, json: bool) -> Result<Self> {
+        let mut value: Value = if json {
+            serde_json::from_str(contents)?
+        } else {
+            toml::from_str(contents)?
+        };
+        // All costs of natives and storage are gas quantities
+        for section in ["natives", "storage"] {
+            if let Some(costs) = value.get_mut(section) {
+                wrap_quantities(costs);
+            }
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Prints the schedule in JSON if `json` and in TOML otherwise
+    fn print(&self, json: bool) -> Result<String> {
+        let value = self.to_value()?;
+        Ok(if json {
+            serde_json::to_string_pretty(&value)?
+        } else {
+            // Unlike `Value`, `toml::Value` puts the plain values of a table before its subtables,
+            // as TOML requires
+            toml::to_string(&toml::Value::try_from(value)?)?
+        })
+    }
+
+    /// The schedule as a JSON value, with the gas quantities as bare numbers
+    fn to_value(&self) -> Result<Value> {
+        let mut value = serde_json::to_value(self)?;
+        unwrap_quantities(&mut value);
+        Ok(value)
+    }
+
+    pub fn cost_table(&self) -> Result<CostTable> {
+        CostTable::from_named_instruction_costs(&self.instructions)
+    }
+
+    /// The stdlib and nursery natives, published at `move_std_addr`
+    pub fn natives(&self, move_std_addr: AccountAddress) -> NativeFunctionTable {
+        all_natives(move_std_addr, self.natives.stdlib.clone())
+            .into_iter()
+            .chain(nursery_natives(move_std_addr, self.natives.nursery.clone()))
+            .collect()
+    }
+
+    /// The parameters of the table natives, to be given to `move_table_extension::table_natives`
+    pub fn table_gas_parameters(&self) -> move_table_extension::GasParameters {
+        let table = self.natives.table.clone();
+        move_table_extension::GasParameters {
+            common: self.storage.table.clone(),
+            new_table_handle: table.new_table_handle,
+            add_box: table.add_box,
+            borrow_box: table.borrow_box,
+            contains_box: table.contains_box,
+            remove_box: table.remove_box,
+            destroy_empty_box: table.destroy_empty_box,
+            drop_unchecked_box: table.drop_unchecked_box,
+            length_box: table.length_box,
+            next_key_box: table.next_key_box,
+        }
+    }
+
+    /// Replaces the natives in `natives` which the schedule has costs for by ones costed by the
+    /// schedule, keeping the other natives as they are. These are the stdlib and nursery natives
+    /// at `move_std_addr`, if any, and the natives of the table extension at any address.
+    pub fn schedule_natives(
+        &self,
+        natives: NativeFunctionTable,
+        move_std_addr: Option<AccountAddress>,
+    ) -> NativeFunctionTable {
+        let table_addrs = natives
+            .iter()
+            .filter(|(_, module, _, _)| TABLE_MODULES.contains(&module.as_str()))
+            .map(|(addr, _, _, _)| *addr)
+            .collect::<BTreeSet<_>>();
+        let mut scheduled = move_std_addr
+            .map(|addr| self.natives(addr))
+            .unwrap_or_default()
+            .into_iter()
+            .chain(
+                table_addrs
+                    .into_iter()
+                    .flat_map(|addr| table_natives(addr, self.table_gas_parameters())),
+            )
+            .map(|(addr, module, name, native)| ((addr, module, name), native))
+            .collect::<BTreeMap<_, _>>();
+        natives
+            .into_iter()
+            .map(|(addr, module, name, native)| {
+                let native = scheduled
+                    .remove(&(addr, module.clone(), name.clone()))
+                    .unwrap_or(native);
+                (addr, module, name, native)
+            })
+            .collect()
+    }
+
+    /// The costs which differ between this schedule and `other`, ordered by key
+    pub fn diff(&self, other: &Self) -> Result<Vec<GasScheduleChange>> {
+        let old = self.costs()?;
+        let mut new = other.costs()?;
+        let mut changes = vec![];
+        for (key, old_cost) in old {
+            let new_cost = new.remove(&key);
+            if new_cost != Some(old_cost) {
+                changes.push(GasScheduleChange {
+                    key,
+                    old: Some(old_cost),
+                    new: new_cost,
+                });
+            }
+        }
+        changes.extend(new.into_iter().map(|(key, new_cost)| GasScheduleChange {
+            key,
+            old: None,
+            new: Some(new_cost),
+        }));
+        changes.sort_by(|c1, c2| c1.key.cmp(&c2.key));
+        Ok(changes)
+    }
+
+    /// All costs of the schedule, keyed by their path
+    fn costs(&self) -> Result<BTreeMap<String, u64>> {
+        let mut costs = BTreeMap::new();
+        if let Value::Object(sections) = self.to_value()? {
+            for (name, section) in sections {
+                if name != "version" {
+                    flatten(name, &section, &mut costs);
+                }
+            }
+        }
+        Ok(costs)
+    }
+}
+
+/// Replaces the serialized gas quantities in `value`, i.e. `{"val": n, "phantom": null}`, by `n`
+fn unwrap_quantities(value: &mut Value) {
+    if let Value::Object(fields) = value {
+        if fields.len() == 2 && fields.get("phantom") == Some(&Value::Null) {
+            if let Some(val) = fields.remove("val") {
+                *value = val;
+                return;
+            }
+        }
+        fields.values_mut().for_each(unwrap_quantities);
+    }
+}
+
+/// Replaces the numbers in `value` by serialized gas quantities, undoing `unwrap_quantities`
+fn wrap_quantities(value: &mut Value) {
+    match value {
+        Value::Object(fields) => fields.values_mut().for_each(wrap_quantities),
+        Value::Number(_) => *value = json!({ "val": value.take(), "phantom": null }),
+        _ => (),
+    }
+}
+
+fn flatten(key: String, value: &Value, costs: &mut BTreeMap<String, u64>) {
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                flatten(format!("{}.{}", key, name), value, costs)
+            }
+        }
+        Value::Number(n) => {
+            if let Some(n) = n.as_u64() {
+                costs.insert(key, n);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext == "json")
+}
+
+impl fmt::Display for GasScheduleChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.old, self.new) {
+            (Some(old), Some(new)) => write!(f, "~ {}: {} -> {}", self.key, old, new),
+            (Some(old), None) => write!(f, "- {}: {}", self.key, old),
+            (None, Some(new)) => write!(f, "+ {}: {}", self.key, new),
+            (None, None) => write!(f, "  {}", self.key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn toml_roundtrip() {
+        let mut schedule = GasSchedule::initial();
+        schedule.storage.table.load_base = 7.into();
+        let printed = schedule.print(false).unwrap();
+        assert!(printed.contains("load_base = 7\n"));
+        let parsed = GasSchedule::parse(&printed, false).unwrap();
+        assert_eq!(parsed.cost_table().unwrap(), *INITIAL_COST_SCHEDULE);
+        assert!(schedule.diff(&parsed).unwrap().is_empty());
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let mut schedule = GasSchedule::initial();
+        schedule.storage.table.load_base = 7.into();
+        let printed = schedule.print(true).unwrap();
+        assert!(printed.contains("\"load_base\": 7"));
+        let parsed = GasSchedule::parse(&printed, true).unwrap();
+        assert!(schedule.diff(&parsed).unwrap().is_empty());
+    }
+
+    #[test]
+    fn schedule_table_natives() {
+        let natives = table_natives(
+            AccountAddress::TWO,
+            move_table_extension::GasParameters::zeros(),
+        );
+        let scheduled = GasSchedule::initial().schedule_natives(natives.clone(), None);
+        assert_eq!(scheduled.len(), natives.len());
+        for ((addr, module, name, native), (s_addr, s_module, s_name, s_native)) in
+            natives.iter().zip(&scheduled)
+        {
+            assert_eq!((addr, module, name), (s_addr, s_module, s_name));
+            assert!(!Arc::ptr_eq(native, s_native));
+        }
+    }
+
+    #[test]
+    fn schedule_stdlib_natives_at_std_address() {
+        let natives = all_natives(AccountAddress::TWO, GasParameters::zeros());
+        let schedule = GasSchedule::initial();
+        // The stdlib is not at the default address, so its natives are kept
+        let kept = schedule.schedule_natives(natives.clone(), Some(AccountAddress::ONE));
+        assert!(natives
+            .iter()
+            .zip(&kept)
+            .all(|((_, _, _, native), (_, _, _, kept))| Arc::ptr_eq(native, kept)));
+        let scheduled = schedule.schedule_natives(natives.clone(), Some(AccountAddress::TWO));
+        assert!(natives
+            .iter()
+            .zip(&scheduled)
+            .all(|((_, _, _, native), (_, _, _, scheduled))| !Arc::ptr_eq(native, scheduled)));
+    }
+
+    #[test]
+    fn diff_changed_costs() {
+        let old = GasSchedule::initial();
+        let mut new = old.clone();
+        new.instructions.get_mut("MoveTo").unwrap().instruction_gas += 1;
+        new.storage.table.load_base = 7.into();
+        assert_eq!(
+            old.diff(&new).unwrap(),
+            vec![
+                GasScheduleChange {
+                    key: "instructions.MoveTo.instruction_gas".to_string(),
+                    old: Some(13),
+                    new: Some(14),
+                },
+                GasScheduleChange {
+                    key: "storage.table.load_base".to_string(),
+                    old: Some(0),
+                    new: Some(7),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_instruction() {
+        let mut schedule = GasSchedule::initial();
+        schedule.instructions.remove("MoveTo");
+        assert!(schedule.cost_table().is_err());
+    }
+}
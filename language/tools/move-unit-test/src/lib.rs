@@ -3,11 +3,22 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod cargo_runner;
+#[cfg(feature = "debugging")]
+pub mod debug_adapter;
 pub mod extensions;
+pub mod gas_schedule;
+pub mod machine_report;
+pub mod mutation;
+pub mod random_test;
+pub mod storage_snapshot;
 pub mod test_reporter;
 pub mod test_runner;
 
-use crate::test_runner::TestRunner;
+use crate::{
+    gas_schedule::GasSchedule,
+    machine_report::ReportFormat,
+    test_runner::{TestRunner, DEFAULT_RANDOM_TEST_ITERATIONS},
+};
 use clap::*;
 use move_command_line_common::files::verify_and_create_named_address_mapping;
 use move_compiler::{
@@ -17,13 +28,19 @@ use move_compiler::{
     unit_test::{self, TestPlan},
     Compiler, Flags, PASS_CFGIR,
 };
-use move_core_types::language_storage::ModuleId;
+use move_core_types::{
+    account_address::AccountAddress, errmap::ErrorMapping, language_storage::ModuleId,
+};
 use move_vm_runtime::native_functions::NativeFunctionTable;
 use move_vm_test_utils::gas_schedule::CostTable;
+#[cfg(feature = "debugging")]
+use std::io::BufReader;
 use std::{
-    collections::BTreeMap,
-    io::{Result, Write},
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::{ErrorKind, Result, Write},
     marker::Send,
+    path::{Path, PathBuf},
     sync::Mutex,
 };
 
@@ -115,6 +132,68 @@ pub struct UnitTestingConfig {
     #[clap(short = 'v', long = "verbose")]
     pub report_writeset: bool,
 
+    /// Run the tests one at a time under a debugger, serving the Debug Adapter Protocol over
+    /// stdin and stdout, e.g. to debug them from an IDE
+    #[cfg(feature = "debugging")]
+    #[clap(long = "debug-adapter")]
+    pub debug_adapter: bool,
+
+    /// Record a structured trace of the execution of each test in this directory, e.g. to
+    /// replay it with `move replay`
+    #[cfg(feature = "debugging")]
+    #[clap(long = "trace", parse(from_os_str))]
+    pub trace: Option<PathBuf>,
+
+    /// Profile the gas used by the tests, reporting it by kind of instruction and writing it as
+    /// folded stacks, e.g. to render flamegraphs, in this directory
+    #[clap(long = "profile-gas", parse(from_os_str))]
+    pub profile_gas: Option<PathBuf>,
+
+    /// After the tests pass, run them against mutants of the modules under test, e.g. with an
+    /// operator swapped, and report the mutants which survive and the mutation score of each
+    /// function
+    #[clap(long = "mutate")]
+    pub mutate: bool,
+
+    /// Number of sets of generated arguments each #[random_test] is run with
+    #[clap(long = "random-test-iterations", default_value = "100")]
+    pub random_test_iterations: u64,
+
+    /// Seed for the arguments generated for #[random_test]s, e.g. to reproduce a failure. A
+    /// random seed is picked otherwise
+    #[clap(long = "seed")]
+    pub seed: Option<u64>,
+
+    /// Also write a machine-readable report of the test results in this format to
+    /// `--report-file`, e.g. for CI dashboards. JSON events are written as the tests complete,
+    /// JUnit XML once they all have
+    #[clap(long = "report-format", arg_enum, requires = "report_file")]
+    pub report_format: Option<ReportFormat>,
+
+    /// The file the machine-readable report of the test results is written to
+    #[clap(long = "report-file", parse(from_os_str))]
+    pub report_file: Option<PathBuf>,
+
+    /// The error map, e.g. generated by `move errmap`, explaining the abort codes in the
+    /// machine-readable report. Defaults to the error map of the standard library
+    #[clap(long = "error-map", parse(from_os_str))]
+    pub error_map: Option<PathBuf>,
+
+    /// Check the storage state at the end of each passing test against its snapshot, in the
+    /// `snapshots` directory next to the source file of the test
+    #[clap(long = "snapshots")]
+    pub check_snapshots: bool,
+
+    /// Save the storage state at the end of each passing test as its new snapshot, instead of
+    /// checking it
+    #[clap(long = "update-snapshots")]
+    pub update_snapshots: bool,
+
+    /// The gas schedule costing the tests, instead of the given cost table and the costs of the
+    /// given natives. Its stdlib natives are the ones at the `std` address.
+    #[clap(skip)]
+    pub gas_schedule: Option<GasSchedule>,
+
     /// Use the EVM-based execution backend.
     /// Does not work with --stackless.
     #[cfg(feature = "evm-backend")]
@@ -154,6 +233,20 @@ impl UnitTestingConfig {
             list: false,
             named_address_values: vec![],
             report_writeset: false,
+            #[cfg(feature = "debugging")]
+            debug_adapter: false,
+            #[cfg(feature = "debugging")]
+            trace: None,
+            profile_gas: None,
+            mutate: false,
+            random_test_iterations: DEFAULT_RANDOM_TEST_ITERATIONS,
+            seed: None,
+            report_format: None,
+            report_file: None,
+            error_map: None,
+            check_snapshots: false,
+            update_snapshots: false,
+            gas_schedule: None,
 
             #[cfg(feature = "evm-backend")]
             evm: false,
@@ -249,6 +342,25 @@ impl UnitTestingConfig {
         }
 
         writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
+        let (native_function_table, cost_table) = match &self.gas_schedule {
+            Some(schedule) => (
+                native_function_table
+                    .map(|natives| schedule.schedule_natives(natives, self.std_address())),
+                Some(
+                    schedule
+                        .cost_table()
+                        .map_err(|err| std::io::Error::new(ErrorKind::InvalidData, err))?,
+                ),
+            ),
+            None => (native_function_table, cost_table),
+        };
+        let mutation_inputs = self.mutate.then(|| {
+            (
+                test_plan.clone(),
+                native_function_table.clone(),
+                cost_table.clone(),
+            )
+        });
         let num_threads = if cfg!(feature = "solana-backend") {
             1 // enforce single threaded execution for Solana, as llvm-sys is not re-entrant.
         } else {
@@ -276,8 +388,36 @@ impl UnitTestingConfig {
         if let Some(filter_str) = &self.filter {
             test_runner.filter(filter_str)
         }
+        #[cfg(feature = "debugging")]
+        if let Some(trace_dir) = &self.trace {
+            std::fs::create_dir_all(trace_dir)?;
+            test_runner.record_traces(trace_dir.clone());
+        }
+        if self.profile_gas.is_some() {
+            test_runner.profile_gas();
+        }
+        let seed = self.seed.unwrap_or_else(rand::random);
+        test_runner.random_tests(self.random_test_iterations, seed);
+        if self.check_snapshots || self.update_snapshots {
+            test_runner.check_storage_snapshots(self.update_snapshots);
+        }
+        if let (Some(ReportFormat::Json), Some(report_file)) =
+            (self.report_format, &self.report_file)
+        {
+            test_runner
+                .stream_json_events(Box::new(File::create(report_file)?), self.load_error_map()?)?;
+        }
 
-        let test_results = test_runner.run(&shared_writer).unwrap();
+        #[cfg(feature = "debugging")]
+        let test_results = if self.debug_adapter {
+            test_runner.run_with_debug_adapter(BufReader::new(std::io::stdin()), std::io::stdout())
+        } else {
+            test_runner.run(&shared_writer)
+        };
+        #[cfg(not(feature = "debugging"))]
+        let test_results = test_runner.run(&shared_writer);
+        let test_results =
+            test_results.map_err(|err| std::io::Error::new(ErrorKind::Other, err))?;
         if self.report_statistics {
             test_results.report_statistics(&shared_writer)?;
         }
@@ -286,9 +426,118 @@ impl UnitTestingConfig {
             test_results.report_goldens(&shared_writer)?;
         }
 
+        if let Some(profile_dir) = &self.profile_gas {
+            test_results.report_gas_profile(&shared_writer, profile_dir)?;
+        }
+
+        if let (Some(ReportFormat::Junit), Some(report_file)) =
+            (self.report_format, &self.report_file)
+        {
+            test_results.write_junit_report(&self.load_error_map()?, report_file)?;
+        }
+
         let ok = test_results.summarize(&shared_writer)?;
 
+        // Mutants are only meaningful if the tests pass on the original modules
+        if let Some((test_plan, native_function_table, cost_table)) = mutation_inputs.filter(|_| ok)
+        {
+            self.run_and_report_mutation_tests(
+                test_plan,
+                native_function_table,
+                cost_table,
+                seed,
+                &shared_writer,
+            )?;
+        }
+
         let writer = shared_writer.into_inner().unwrap();
         Ok((writer, ok))
     }
+
+    /// The address of the standard library, if `std` is a named address
+    fn std_address(&self) -> Option<AccountAddress> {
+        self.named_address_values
+            .iter()
+            .find(|(name, _)| name == "std")
+            .map(|(_, addr)| addr.into_inner())
+    }
+
+    fn load_error_map(&self) -> Result<ErrorMapping> {
+        let bytes = match &self.error_map {
+            Some(path) => std::fs::read(path)?,
+            None => move_stdlib::error_descriptions().to_vec(),
+        };
+        bcs::from_bytes(&bytes).map_err(|err| std::io::Error::new(ErrorKind::InvalidData, err))
+    }
+
+    /// Runs the tests against each mutant of the modules under test, i.e. the modules of the
+    /// tested packages which are not dependencies, and reports the mutants which survive
+    fn run_and_report_mutation_tests<W: Write + Send>(
+        &self,
+        test_plan: TestPlan,
+        native_function_table: Option<NativeFunctionTable>,
+        cost_table: Option<CostTable>,
+        seed: u64,
+        writer: &Mutex<W>,
+    ) -> Result<()> {
+        writeln!(writer.lock().unwrap(), "\nRunning mutation tests")?;
+        let named_addresses =
+            verify_and_create_named_address_mapping(self.named_address_values.clone()).unwrap();
+        let run_tests = |mutated_plan: TestPlan| -> anyhow::Result<bool> {
+            let mut test_runner = TestRunner::new(
+                self.gas_limit.unwrap_or(DEFAULT_EXECUTION_BOUND),
+                self.num_threads,
+                false,
+                false,
+                false,
+                false,
+                mutated_plan,
+                native_function_table.clone(),
+                cost_table.clone(),
+                named_addresses.clone(),
+                false,
+                #[cfg(feature = "evm-backend")]
+                false,
+                #[cfg(feature = "solana-backend")]
+                false,
+            )?;
+            if let Some(filter_str) = &self.filter {
+                test_runner.filter(filter_str)
+            }
+            test_runner.random_tests(self.random_test_iterations, seed);
+            Ok(test_runner.run(&Mutex::new(std::io::sink()))?.passed())
+        };
+        let report =
+            mutation::run_mutants(&test_plan, &self.mutation_targets(&test_plan), run_tests)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+        report
+            .report(&test_plan, writer)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
+    }
+
+    /// The modules of the packages with tests, except those compiled from the dependency files
+    fn mutation_targets(&self, test_plan: &TestPlan) -> BTreeSet<ModuleId> {
+        let packages: BTreeSet<_> = test_plan
+            .module_tests
+            .keys()
+            .filter_map(|module_id| test_plan.module_info.get(module_id))
+            .map(|info| info.package_name)
+            .collect();
+        test_plan
+            .module_info
+            .iter()
+            .filter(|(_, info)| packages.contains(&info.package_name))
+            .filter(|(_, info)| {
+                let file_hash = info.source_map.definition_location.file_hash();
+                test_plan
+                    .files
+                    .get(&file_hash)
+                    .map_or(true, |(file_name, _)| {
+                        let path = Path::new(file_name.as_str());
+                        !self.dep_files.iter().any(|dep| path.starts_with(dep))
+                    })
+            })
+            .map(|(module_id, _)| module_id.clone())
+            .collect()
+    }
 }
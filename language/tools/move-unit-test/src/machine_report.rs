@@ -0,0 +1,390 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Machine-readable reports of the results of a test run, e.g. for CI dashboards: JUnit XML,
+//! written once the whole run has finished, and a stream of JSON events with one event per line,
+//! written as the tests complete.
+
+use clap::ArgEnum;
+use move_core_types::errmap::ErrorDescription;
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    io::{Error, Result, Write},
+    sync::Mutex,
+    time::Duration,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum ReportFormat {
+    Junit,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    TimedOut,
+}
+
+/// The outcome of a single test
+#[derive(Debug, Clone)]
+pub struct TestRecord {
+    /// The module of the test, e.g. `0x1::vector_tests`
+    pub module: String,
+    pub name: String,
+    pub status: TestStatus,
+    pub duration: Duration,
+    pub gas_used: u64,
+    pub failure: Option<FailureRecord>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FailureRecord {
+    /// A one line summary of the failure
+    pub message: String,
+    /// The failure as reported to humans, with its location and stack trace if any
+    pub details: String,
+    /// The abort code and the module it originates from, if the test aborted
+    pub abort: Option<(u64, String)>,
+    /// The explanation of the abort code, if it is in the error map
+    pub abort_explanation: Option<ErrorDescription>,
+}
+
+/// Writes the JSON events of a test run as it progresses: the suite starting, each test as it
+/// completes, and the suite finishing. Each event is flushed once written, for the events to be
+/// read while the tests run.
+///
+/// An error writing an event does not interrupt the run: no more events are written, and the
+/// error is returned when the stream is finished.
+pub struct JsonEventStream<W: Write> {
+    state: Mutex<JsonEventState<W>>,
+}
+
+struct JsonEventState<W: Write> {
+    writer: W,
+    passed: usize,
+    failed: usize,
+    exec_time: Duration,
+    /// The first error writing an event, if any
+    error: Option<Error>,
+}
+
+impl ReportFormat {
+    /// The extension of files in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Junit => "xml",
+            Self::Json => "json",
+        }
+    }
+}
+
+impl<W: Write> JsonEventStream<W> {
+    /// Starts the stream of a run of `test_count` tests
+    pub fn start(writer: W, test_count: usize) -> Result<Self> {
+        let mut state = JsonEventState {
+            writer,
+            passed: 0,
+            failed: 0,
+            exec_time: Duration::ZERO,
+            error: None,
+        };
+        state.write_event(
+            json!({ "type": "suite", "event": "started", "test_count": test_count }),
+        )?;
+        Ok(Self {
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Writes the event of the test of `record` completing
+    pub fn test_completed(&self, record: &TestRecord) {
+        let mut state = self.state.lock().unwrap();
+        if record.status == TestStatus::Passed {
+            state.passed += 1;
+        } else {
+            state.failed += 1;
+        }
+        state.exec_time += record.duration;
+        if state.error.is_none() {
+            if let Err(err) = state.write_event(test_event(record)) {
+                state.error = Some(err);
+            }
+        }
+    }
+
+    /// Writes the event of the suite finishing, or returns the first error writing an event
+    pub fn finish(self) -> Result<()> {
+        let mut state = self.state.into_inner().unwrap();
+        if let Some(err) = state.error.take() {
+            return Err(err);
+        }
+        let suite_event = if state.failed == 0 { "ok" } else { "failed" };
+        let event = json!({
+            "type": "suite",
+            "event": suite_event,
+            "passed": state.passed,
+            "failed": state.failed,
+            "exec_time": state.exec_time.as_secs_f64(),
+        });
+        state.write_event(event)
+    }
+}
+
+impl<W: Write> JsonEventState<W> {
+    fn write_event(&mut self, event: Value) -> Result<()> {
+        writeln!(self.writer, "{}", event)?;
+        self.writer.flush()
+    }
+}
+
+impl TestStatus {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Passed => "ok",
+            Self::Failed => "failed",
+            Self::TimedOut => "timeout",
+        }
+    }
+}
+
+impl FailureRecord {
+    /// The summary of the failure, with the abort code and its explanation if the test aborted
+    fn full_message(&self) -> String {
+        match (&self.abort, &self.abort_explanation) {
+            (None, _) => self.message.clone(),
+            (Some((code, _)), None) => format!("{} (abort code {})", self.message, code),
+            (Some((code, _)), Some(explanation)) => format!(
+                "{} (abort code {}: {}: {})",
+                self.message, code, explanation.code_name, explanation.code_description
+            ),
+        }
+    }
+}
+
+/// Writes a JUnit XML report of `records`, grouping the tests by module
+pub fn write_junit<W: Write>(records: &[TestRecord], w: &mut W) -> Result<()> {
+    let mut modules: BTreeMap<&str, Vec<&TestRecord>> = BTreeMap::new();
+    for record in records {
+        modules
+            .entry(record.module.as_str())
+            .or_default()
+            .push(record);
+    }
+
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        w,
+        r#"<testsuites name="move-unit-tests" tests="{}" failures="{}" time="{:.3}">"#,
+        records.len(),
+        count_failures(records),
+        total_seconds(records),
+    )?;
+    for (module, tests) in modules {
+        writeln!(
+            w,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" time="{:.3}">"#,
+            escape_xml(module),
+            tests.len(),
+            count_failures(tests.iter().copied()),
+            total_seconds(tests.iter().copied()),
+        )?;
+        for test in tests {
+            writeln!(
+                w,
+                r#"    <testcase classname="{}" name="{}" time="{:.3}">"#,
+                escape_xml(module),
+                escape_xml(&test.name),
+                test.duration.as_secs_f64(),
+            )?;
+            writeln!(w, "      <properties>")?;
+            writeln!(
+                w,
+                r#"        <property name="gas_used" value="{}"/>"#,
+                test.gas_used
+            )?;
+            if let Some((code, location)) = test.failure.as_ref().and_then(|f| f.abort.as_ref()) {
+                writeln!(
+                    w,
+                    r#"        <property name="abort_code" value="{}"/>"#,
+                    code
+                )?;
+                writeln!(
+                    w,
+                    r#"        <property name="abort_location" value="{}"/>"#,
+                    escape_xml(location)
+                )?;
+            }
+            writeln!(w, "      </properties>")?;
+            if let Some(failure) = &test.failure {
+                writeln!(
+                    w,
+                    r#"      <failure type="{}" message="{}">{}</failure>"#,
+                    test.status.name(),
+                    escape_xml(&failure.full_message()),
+                    escape_xml(&failure.details),
+                )?;
+            }
+            writeln!(w, "    </testcase>")?;
+        }
+        writeln!(w, "  </testsuite>")?;
+    }
+    writeln!(w, "</testsuites>")
+}
+
+fn count_failures<'a>(records: impl IntoIterator<Item = &'a TestRecord>) -> usize {
+    records
+        .into_iter()
+        .filter(|record| record.status != TestStatus::Passed)
+        .count()
+}
+
+fn total_seconds<'a>(records: impl IntoIterator<Item = &'a TestRecord>) -> f64 {
+    records
+        .into_iter()
+        .map(|record| record.duration)
+        .sum::<Duration>()
+        .as_secs_f64()
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than whitespace are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn test_event(record: &TestRecord) -> Value {
+    let mut event = json!({
+        "type": "test",
+        "event": record.status.name(),
+        "name": format!("{}::{}", record.module, record.name),
+        "module": record.module,
+        "exec_time": record.duration.as_secs_f64(),
+        "gas_used": record.gas_used,
+    });
+    if let Some(failure) = &record.failure {
+        event["message"] = json!(failure.message);
+        event["stdout"] = json!(failure.details);
+        if let Some((code, location)) = &failure.abort {
+            event["abort_code"] = json!(code);
+            event["abort_location"] = json!(location);
+        }
+        if let Some(explanation) = &failure.abort_explanation {
+            event["abort_explanation"] = json!({
+                "code_name": explanation.code_name,
+                "code_description": explanation.code_description,
+            });
+        }
+    }
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<TestRecord> {
+        vec![
+            TestRecord {
+                module: "0x1::m".to_string(),
+                name: "passes".to_string(),
+                status: TestStatus::Passed,
+                duration: Duration::from_millis(2),
+                gas_used: 7,
+                failure: None,
+            },
+            TestRecord {
+                module: "0x1::m".to_string(),
+                name: "aborts".to_string(),
+                status: TestStatus::Failed,
+                duration: Duration::from_millis(1),
+                gas_used: 3,
+                failure: Some(FailureRecord {
+                    message: "Test was not expected to error".to_string(),
+                    details: "aborted with code 1 in <0x1::m>".to_string(),
+                    abort: Some((1, "0x1::m".to_string())),
+                    abort_explanation: Some(ErrorDescription {
+                        code_name: "EINVALID".to_string(),
+                        code_description: "The \"value\" is invalid".to_string(),
+                    }),
+                }),
+            },
+        ]
+    }
+
+    #[test]
+    fn junit_escapes_and_counts_failures() {
+        let mut out = vec![];
+        write_junit(&records(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(r#"<testsuite name="0x1::m" tests="2" failures="1" time="0.003">"#));
+        assert!(out.contains(r#"<property name="abort_code" value="1"/>"#));
+        assert!(out.contains(
+            r#"message="Test was not expected to error (abort code 1: EINVALID: The &quot;value&quot; is invalid)">aborted with code 1 in &lt;0x1::m&gt;</failure>"#
+        ));
+    }
+
+    #[test]
+    fn json_emits_one_event_per_line() {
+        let mut out = vec![];
+        let stream = JsonEventStream::start(&mut out, 2).unwrap();
+        for record in records() {
+            stream.test_completed(&record);
+        }
+        stream.finish().unwrap();
+        let events: Vec<Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0]["test_count"], 2);
+        assert_eq!(events[1]["event"], "ok");
+        assert_eq!(events[2]["name"], "0x1::m::aborts");
+        assert_eq!(events[2]["abort_explanation"]["code_name"], "EINVALID");
+        assert_eq!(events[3]["event"], "failed");
+        assert_eq!(events[3]["failed"], 1);
+    }
+
+    /// A writer failing once it has written `lines` lines
+    struct FullWriter {
+        lines: usize,
+    }
+
+    impl Write for FullWriter {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            if self.lines == 0 {
+                return Err(std::io::ErrorKind::WriteZero.into());
+            }
+            self.lines -= buf.iter().filter(|b| **b == b'\n').count();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_stream_returns_the_first_error_when_finished() {
+        let stream = JsonEventStream::start(FullWriter { lines: 1 }, 2).unwrap();
+        for record in records() {
+            stream.test_completed(&record);
+        }
+        let err = stream.finish().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+    }
+}
@@ -0,0 +1,395 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mutation testing of the modules under test. Each mutant changes one instruction of a function
+//! of these modules, e.g. replacing an `Add` by a `Sub`. The tests are run against each mutant: a
+//! mutant is killed if a test fails, and survives otherwise. Surviving mutants point at behavior
+//! the tests do not check, which line coverage cannot tell.
+//!
+//! Mutations are applied to the compiled code, which keeps the source maps of the modules valid
+//! to report where each mutant is. Mutants rejected by the bytecode verifier are discarded.
+
+use crate::format_module_id;
+use anyhow::Result;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex, TableIndex},
+    CompiledModule,
+};
+use move_command_line_common::files::FileHash;
+use move_compiler::unit_test::TestPlan;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId, u256::U256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    io::Write,
+    sync::Mutex,
+};
+
+/// The kinds of mutations of an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MutationKind {
+    /// An arithmetic, bitwise, logical or comparison operator replaced by a similar one
+    OperatorSwap,
+    /// A comparison replaced by its negation, or a negation removed
+    ConditionNegation,
+    /// An integer constant incremented, or a boolean constant flipped
+    ConstantTweak,
+    /// A conditional branch to an abort, e.g. of an `assert!`, removed
+    RemovedAssert,
+    /// The targets of a conditional branch swapped
+    SwappedBranch,
+}
+
+/// A mutation of one instruction of a function
+#[derive(Debug, Clone)]
+pub struct Mutant {
+    pub module_id: ModuleId,
+    pub function: FunctionDefinitionIndex,
+    pub function_name: Identifier,
+    pub offset: CodeOffset,
+    pub kind: MutationKind,
+    /// The instructions replacing the instruction at `offset`, whose branch targets are offsets
+    /// of the original code
+    pub replacement: Vec<Bytecode>,
+}
+
+/// Whether each mutant was killed by the tests
+#[derive(Debug, Default)]
+pub struct MutationReport {
+    results: Vec<(Mutant, bool)>,
+}
+
+impl fmt::Display for MutationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MutationKind::OperatorSwap => write!(f, "operator swap"),
+            MutationKind::ConditionNegation => write!(f, "condition negation"),
+            MutationKind::ConstantTweak => write!(f, "constant tweak"),
+            MutationKind::RemovedAssert => write!(f, "removed assert"),
+            MutationKind::SwappedBranch => write!(f, "swapped branch"),
+        }
+    }
+}
+
+/// Returns the mutants of the functions of `module` not in `skipped_functions`, e.g. the tests
+/// themselves
+pub fn mutants(module: &CompiledModule, skipped_functions: &BTreeSet<&str>) -> Vec<Mutant> {
+    let mut mutants = vec![];
+    for (idx, def) in module.function_defs().iter().enumerate() {
+        let code = match &def.code {
+            Some(code_unit) => &code_unit.code,
+            None => continue,
+        };
+        let function_name = module
+            .identifier_at(module.function_handle_at(def.function).name)
+            .to_owned();
+        if skipped_functions.contains(function_name.as_str()) {
+            continue;
+        }
+        for (offset, instr) in code.iter().enumerate() {
+            for (kind, replacement) in instruction_mutations(code, offset, instr) {
+                mutants.push(Mutant {
+                    module_id: module.self_id(),
+                    function: FunctionDefinitionIndex(idx as TableIndex),
+                    function_name: function_name.clone(),
+                    offset: offset as CodeOffset,
+                    kind,
+                    replacement,
+                })
+            }
+        }
+    }
+    mutants
+}
+
+/// The mutations of the instruction `instr` at `offset` in `code`
+fn instruction_mutations(
+    code: &[Bytecode],
+    offset: usize,
+    instr: &Bytecode,
+) -> Vec<(MutationKind, Vec<Bytecode>)> {
+    use Bytecode as B;
+    use MutationKind as K;
+
+    let mut mutations = vec![];
+    let swapped = match instr {
+        B::Add => Some(B::Sub),
+        B::Sub => Some(B::Add),
+        B::Mul => Some(B::Div),
+        B::Div => Some(B::Mul),
+        B::Mod => Some(B::Div),
+        B::BitOr => Some(B::BitAnd),
+        B::BitAnd => Some(B::BitOr),
+        B::Xor => Some(B::BitOr),
+        B::Shl => Some(B::Shr),
+        B::Shr => Some(B::Shl),
+        B::Lt => Some(B::Le),
+        B::Le => Some(B::Lt),
+        B::Gt => Some(B::Ge),
+        B::Ge => Some(B::Gt),
+        B::Or => Some(B::And),
+        B::And => Some(B::Or),
+        _ => None,
+    };
+    if let Some(swapped) = swapped {
+        mutations.push((K::OperatorSwap, vec![swapped]))
+    }
+
+    let negated = match instr {
+        B::Lt => Some(B::Ge),
+        B::Ge => Some(B::Lt),
+        B::Gt => Some(B::Le),
+        B::Le => Some(B::Gt),
+        B::Eq => Some(B::Neq),
+        B::Neq => Some(B::Eq),
+        B::Not => Some(B::Nop),
+        _ => None,
+    };
+    if let Some(negated) = negated {
+        mutations.push((K::ConditionNegation, vec![negated]))
+    }
+
+    let tweaked = match instr {
+        B::LdU8(n) => Some(B::LdU8(n.checked_add(1).unwrap_or(n - 1))),
+        B::LdU16(n) => Some(B::LdU16(n.checked_add(1).unwrap_or(n - 1))),
+        B::LdU32(n) => Some(B::LdU32(n.checked_add(1).unwrap_or(n - 1))),
+        B::LdU64(n) => Some(B::LdU64(n.checked_add(1).unwrap_or(n - 1))),
+        B::LdU128(n) => Some(B::LdU128(n.checked_add(1).unwrap_or(n - 1))),
+        B::LdU256(n) => Some(B::LdU256(
+            n.checked_add(U256::one())
+                .unwrap_or_else(|| n.wrapping_sub(U256::one())),
+        )),
+        B::LdTrue => Some(B::LdFalse),
+        B::LdFalse => Some(B::LdTrue),
+        _ => None,
+    };
+    if let Some(tweaked) = tweaked {
+        mutations.push((K::ConstantTweak, vec![tweaked]))
+    }
+
+    if let B::BrTrue(target) | B::BrFalse(target) = instr {
+        // The condition is popped and the branch to the abort never taken
+        let target = *target;
+        let fallthrough = offset as CodeOffset + 1;
+        if aborts(code, target) && !aborts(code, fallthrough) {
+            mutations.push((K::RemovedAssert, vec![B::Pop]))
+        } else if aborts(code, fallthrough) && !aborts(code, target) {
+            mutations.push((K::RemovedAssert, vec![B::Pop, B::Branch(target)]))
+        }
+
+        let swapped = match instr {
+            B::BrTrue(_) => B::BrFalse(target),
+            _ => B::BrTrue(target),
+        };
+        mutations.push((K::SwappedBranch, vec![swapped]))
+    }
+    mutations
+}
+
+/// Whether the code at `offset` aborts without branching
+fn aborts(code: &[Bytecode], offset: CodeOffset) -> bool {
+    for instr in &code[offset as usize..] {
+        match instr {
+            Bytecode::Abort => return true,
+            Bytecode::Branch(_) | Bytecode::BrTrue(_) | Bytecode::BrFalse(_) | Bytecode::Ret => {
+                return false
+            }
+            _ => (),
+        }
+    }
+    false
+}
+
+impl Mutant {
+    /// Returns `module` with the mutation applied
+    pub fn apply(&self, module: &CompiledModule) -> CompiledModule {
+        let mut mutated = module.clone();
+        let code_unit = mutated.function_defs[self.function.0 as usize]
+            .code
+            .as_mut()
+            .expect("mutants are only created for functions with code");
+
+        // The instructions after the mutated one move by the number of instructions added
+        let offset = self.offset;
+        let shift = self.replacement.len() as CodeOffset - 1;
+        let mut code = Vec::with_capacity(code_unit.code.len() + shift as usize);
+        code.extend_from_slice(&code_unit.code[..offset as usize]);
+        code.extend(self.replacement.iter().cloned());
+        code.extend_from_slice(&code_unit.code[offset as usize + 1..]);
+        for instr in code.iter_mut() {
+            match instr {
+                Bytecode::Branch(target) | Bytecode::BrTrue(target) | Bytecode::BrFalse(target)
+                    if *target > offset =>
+                {
+                    *target += shift
+                }
+                _ => (),
+            }
+        }
+        code_unit.code = code;
+        mutated
+    }
+
+    /// Describes the mutation, e.g. "`Add` replaced by `Sub`"
+    pub fn description(&self, module: &CompiledModule) -> String {
+        let code = &module.function_defs[self.function.0 as usize]
+            .code
+            .as_ref()
+            .expect("mutants are only created for functions with code")
+            .code;
+        let original = &code[self.offset as usize];
+        match self.kind {
+            MutationKind::RemovedAssert => format!("branch `{:?}` to an abort removed", original),
+            _ => format!("`{:?}` replaced by `{:?}`", original, self.replacement[0]),
+        }
+    }
+}
+
+/// Runs `run_tests`, which returns whether all tests passed, against each mutant of the modules
+/// `targets` of `test_plan`. The functions tested by `test_plan` are not mutated.
+pub fn run_mutants(
+    test_plan: &TestPlan,
+    targets: &BTreeSet<ModuleId>,
+    mut run_tests: impl FnMut(TestPlan) -> Result<bool>,
+) -> Result<MutationReport> {
+    let mut report = MutationReport::default();
+    for module_id in targets {
+        let module = &test_plan.module_info[module_id].module;
+        let tests = test_plan
+            .module_tests
+            .get(module_id)
+            .map(|module_tests| module_tests.tests.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        for mutant in mutants(module, &tests) {
+            let mutated = mutant.apply(module);
+            if move_bytecode_verifier::verify_module(&mutated).is_err() {
+                continue;
+            }
+            let mut mutated_plan = test_plan.clone();
+            mutated_plan.module_info.get_mut(module_id).unwrap().module = mutated;
+            let killed = !run_tests(mutated_plan)?;
+            report.results.push((mutant, killed));
+        }
+    }
+    Ok(report)
+}
+
+impl MutationReport {
+    /// Reports the mutation score of each function, i.e. the ratio of its mutants killed, and
+    /// where the surviving mutants are
+    pub fn report<W: Write>(&self, test_plan: &TestPlan, writer: &Mutex<W>) -> Result<()> {
+        let num_killed = self.results.iter().filter(|(_, killed)| *killed).count();
+        writeln!(
+            writer.lock().unwrap(),
+            "\nMutation testing: {} of {} mutants killed",
+            num_killed,
+            self.results.len()
+        )?;
+        if self.results.is_empty() {
+            return Ok(());
+        }
+
+        let mut scores: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+        for (mutant, killed) in &self.results {
+            let score = scores.entry(qualified_function_name(mutant)).or_default();
+            score.0 += *killed as usize;
+            score.1 += 1;
+        }
+        let width = scores.keys().map(String::len).max().unwrap_or_default();
+        writeln!(
+            writer.lock().unwrap(),
+            "\n┌─{:─^width$}─┬─{:─^10}─┬─{:─^10}─┐",
+            "",
+            "",
+            "",
+            width = width,
+        )?;
+        writeln!(
+            writer.lock().unwrap(),
+            "│ {name:^width$} │ {killed:^10} │ {score:^10} │",
+            width = width,
+            name = "Function",
+            killed = "Killed",
+            score = "Score",
+        )?;
+        for (name, (killed, total)) in &scores {
+            let killed_of_total = format!("{} / {}", killed, total);
+            let score = format!("{:.2}%", *killed as f64 * 100.0 / *total as f64);
+            writeln!(
+                writer.lock().unwrap(),
+                "├─{:─^width$}─┼─{:─^10}─┼─{:─^10}─┤",
+                "",
+                "",
+                "",
+                width = width,
+            )?;
+            writeln!(
+                writer.lock().unwrap(),
+                "│ {name:<width$} │ {killed:^10} │ {score:^10} │",
+                width = width,
+                name = name,
+                killed = killed_of_total,
+                score = score,
+            )?;
+        }
+        writeln!(
+            writer.lock().unwrap(),
+            "└─{:─^width$}─┴─{:─^10}─┴─{:─^10}─┘",
+            "",
+            "",
+            "",
+            width = width,
+        )?;
+
+        let survivors: Vec<_> = self
+            .results
+            .iter()
+            .filter(|(_, killed)| !*killed)
+            .map(|(mutant, _)| mutant)
+            .collect();
+        if !survivors.is_empty() {
+            writeln!(writer.lock().unwrap(), "\nSurviving mutants:")?;
+            for mutant in survivors {
+                let info = &test_plan.module_info[&mutant.module_id];
+                let location = info
+                    .source_map
+                    .get_code_location(mutant.function, mutant.offset)
+                    .ok()
+                    .map(|loc| source_line(test_plan, loc.file_hash(), loc.start()))
+                    .unwrap_or_else(|| "an unknown location".to_string());
+                writeln!(
+                    writer.lock().unwrap(),
+                    "{}: {} ({}) at {}",
+                    qualified_function_name(mutant),
+                    mutant.description(&info.module),
+                    mutant.kind,
+                    location,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn qualified_function_name(mutant: &Mutant) -> String {
+    format!(
+        "{}::{}",
+        format_module_id(&mutant.module_id),
+        mutant.function_name
+    )
+}
+
+/// Formats the position `start` in a file as `<file>:<line>`
+fn source_line(test_plan: &TestPlan, file_hash: FileHash, start: u32) -> String {
+    match test_plan.files.get(&file_hash) {
+        Some((file_name, contents)) => {
+            let line = contents
+                .get(..start as usize)
+                .map_or(0, |prefix| prefix.matches('\n').count())
+                + 1;
+            format!("{}:{}", file_name, line)
+        }
+        None => "an unknown location".to_string(),
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation and shrinking of the arguments of `#[random_test]` functions.
+//!
+//! A random test is run with freshly generated arguments for a configurable number of iterations.
+//! Generated values are biased towards the edges of their domain (zero, one, the maximum value,
+//! empty vectors, ...) since that is where bugs tend to hide. Once an iteration fails, its
+//! arguments are greedily shrunk to a smaller set of arguments that still makes the test fail.
+
+use move_compiler::unit_test::RandomTestType;
+use move_core_types::{
+    account_address::AccountAddress,
+    u256::U256,
+    value::{MoveStruct, MoveValue},
+};
+use rand::{rngs::StdRng, Rng};
+
+/// The maximum length of generated vectors and strings
+const MAX_VECTOR_LENGTH: usize = 16;
+
+/// The maximum number of times a test is re-run while shrinking its failing arguments
+const MAX_SHRINK_RUNS: usize = 1000;
+
+/// Characters used in generated strings, which are always valid UTF-8
+const STRING_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 _-";
+
+/// Generates a value of type `ty`
+pub fn generate(rng: &mut StdRng, ty: &RandomTestType) -> MoveValue {
+    // One value in four is picked among the edge cases of the type
+    let edge = rng.gen_ratio(1, 4);
+    match ty {
+        RandomTestType::Bool => MoveValue::Bool(rng.gen()),
+        RandomTestType::U8 => MoveValue::U8(if edge {
+            pick_edge(rng, u8::MAX)
+        } else {
+            rng.gen()
+        }),
+        RandomTestType::U16 => MoveValue::U16(if edge {
+            pick_edge(rng, u16::MAX)
+        } else {
+            rng.gen()
+        }),
+        RandomTestType::U32 => MoveValue::U32(if edge {
+            pick_edge(rng, u32::MAX)
+        } else {
+            rng.gen()
+        }),
+        RandomTestType::U64 => MoveValue::U64(if edge {
+            pick_edge(rng, u64::MAX)
+        } else {
+            rng.gen()
+        }),
+        RandomTestType::U128 => MoveValue::U128(if edge {
+            pick_edge(rng, u128::MAX)
+        } else {
+            rng.gen()
+        }),
+        RandomTestType::U256 => MoveValue::U256(if edge {
+            pick_edge(rng, U256::max_value())
+        } else {
+            U256::from_le_bytes(&rng.gen())
+        }),
+        RandomTestType::Address => MoveValue::Address(generate_address(rng, edge)),
+        RandomTestType::Signer => MoveValue::Signer(generate_address(rng, edge)),
+        RandomTestType::String => {
+            let len = generate_length(rng, edge);
+            let bytes = (0..len)
+                .map(|_| STRING_CHARS[rng.gen_range(0..STRING_CHARS.len())])
+                .collect();
+            string_value(bytes)
+        }
+        RandomTestType::Vector(elem_ty) => {
+            let len = generate_length(rng, edge);
+            MoveValue::Vector((0..len).map(|_| generate(rng, elem_ty)).collect())
+        }
+    }
+}
+
+fn pick_edge<T: From<u8>>(rng: &mut StdRng, max: T) -> T {
+    match rng.gen_range(0..3) {
+        0 => T::from(0),
+        1 => T::from(1),
+        _ => max,
+    }
+}
+
+fn generate_address(rng: &mut StdRng, edge: bool) -> AccountAddress {
+    if edge {
+        pick_edge_address(rng)
+    } else {
+        AccountAddress::new(rng.gen())
+    }
+}
+
+fn pick_edge_address(rng: &mut StdRng) -> AccountAddress {
+    match rng.gen_range(0..3) {
+        0 => AccountAddress::ZERO,
+        1 => AccountAddress::ONE,
+        _ => AccountAddress::new([u8::MAX; AccountAddress::LENGTH]),
+    }
+}
+
+fn generate_length(rng: &mut StdRng, edge: bool) -> usize {
+    if edge {
+        0
+    } else {
+        rng.gen_range(0..=MAX_VECTOR_LENGTH)
+    }
+}
+
+fn string_value(bytes: Vec<u8>) -> MoveValue {
+    MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::vector_u8(bytes)]))
+}
+
+fn string_bytes(value: &MoveValue) -> Vec<u8> {
+    match value {
+        MoveValue::Struct(MoveStruct::Runtime(fields)) => match fields.as_slice() {
+            [MoveValue::Vector(bytes)] => bytes
+                .iter()
+                .map(|byte| match byte {
+                    MoveValue::U8(b) => *b,
+                    _ => unreachable!("ICE: string bytes must be u8s"),
+                })
+                .collect(),
+            _ => unreachable!("ICE: a string has a single vector field"),
+        },
+        _ => unreachable!("ICE: a string must be a struct"),
+    }
+}
+
+/// Returns values of type `ty` that are smaller than `value`, the most aggressive first
+fn shrink_candidates(ty: &RandomTestType, value: &MoveValue) -> Vec<MoveValue> {
+    match (ty, value) {
+        (RandomTestType::Bool, MoveValue::Bool(true)) => vec![MoveValue::Bool(false)],
+        (RandomTestType::Bool, _) => vec![],
+        (RandomTestType::U8, MoveValue::U8(u)) => {
+            shrink_integer(*u as u128, |u| MoveValue::U8(u as u8))
+        }
+        (RandomTestType::U16, MoveValue::U16(u)) => {
+            shrink_integer(*u as u128, |u| MoveValue::U16(u as u16))
+        }
+        (RandomTestType::U32, MoveValue::U32(u)) => {
+            shrink_integer(*u as u128, |u| MoveValue::U32(u as u32))
+        }
+        (RandomTestType::U64, MoveValue::U64(u)) => {
+            shrink_integer(*u as u128, |u| MoveValue::U64(u as u64))
+        }
+        (RandomTestType::U128, MoveValue::U128(u)) => shrink_integer(*u, MoveValue::U128),
+        (RandomTestType::U256, MoveValue::U256(u)) => {
+            let zero = U256::zero();
+            if *u == zero {
+                return vec![];
+            }
+            let mut candidates = vec![MoveValue::U256(zero)];
+            let half = *u / U256::from(2u8);
+            if half != zero {
+                candidates.push(MoveValue::U256(half));
+            }
+            if u.checked_sub(U256::one()) != Some(half) {
+                candidates.push(MoveValue::U256(*u - U256::one()));
+            }
+            candidates
+        }
+        (RandomTestType::Address, MoveValue::Address(a)) if *a != AccountAddress::ZERO => {
+            vec![MoveValue::Address(AccountAddress::ZERO)]
+        }
+        (RandomTestType::Signer, MoveValue::Signer(a)) if *a != AccountAddress::ZERO => {
+            vec![MoveValue::Signer(AccountAddress::ZERO)]
+        }
+        (RandomTestType::Address | RandomTestType::Signer, _) => vec![],
+        (RandomTestType::String, _) => {
+            let bytes = string_bytes(value);
+            let mut candidates: Vec<_> = shrink_sequence(&bytes)
+                .into_iter()
+                .map(string_value)
+                .collect();
+            // Then simplify the characters themselves
+            for (i, byte) in bytes.iter().enumerate() {
+                if *byte != b'a' {
+                    let mut simpler = bytes.clone();
+                    simpler[i] = b'a';
+                    candidates.push(string_value(simpler));
+                }
+            }
+            candidates
+        }
+        (RandomTestType::Vector(elem_ty), MoveValue::Vector(elems)) => {
+            let mut candidates: Vec<_> = shrink_sequence(elems)
+                .into_iter()
+                .map(MoveValue::Vector)
+                .collect();
+            for (i, elem) in elems.iter().enumerate() {
+                for shrunk in shrink_candidates(elem_ty, elem) {
+                    let mut simpler = elems.clone();
+                    simpler[i] = shrunk;
+                    candidates.push(MoveValue::Vector(simpler));
+                }
+            }
+            candidates
+        }
+        _ => unreachable!("ICE: random test value does not match its type"),
+    }
+}
+
+fn shrink_integer(u: u128, make: impl Fn(u128) -> MoveValue) -> Vec<MoveValue> {
+    if u == 0 {
+        return vec![];
+    }
+    let mut candidates = vec![make(0)];
+    if u / 2 != 0 {
+        candidates.push(make(u / 2));
+    }
+    if u - 1 != u / 2 {
+        candidates.push(make(u - 1));
+    }
+    candidates
+}
+
+/// Shorter versions of `elems`: empty, each half, then with a single element removed
+fn shrink_sequence<T: Clone>(elems: &[T]) -> Vec<Vec<T>> {
+    if elems.is_empty() {
+        return vec![];
+    }
+    let mut candidates = vec![vec![]];
+    let mid = elems.len() / 2;
+    if mid > 0 {
+        candidates.push(elems[..mid].to_vec());
+        candidates.push(elems[mid..].to_vec());
+    }
+    if elems.len() > 1 {
+        for i in 0..elems.len() {
+            let mut shorter = elems.to_vec();
+            shorter.remove(i);
+            candidates.push(shorter);
+        }
+    }
+    candidates
+}
+
+/// Greedily replaces the arguments in `failing` with smaller ones for which `fails` still holds,
+/// until no smaller arguments fail or the shrinking budget is exhausted
+pub fn shrink(
+    parameters: &[(String, RandomTestType)],
+    mut failing: Vec<MoveValue>,
+    mut fails: impl FnMut(&[MoveValue]) -> bool,
+) -> Vec<MoveValue> {
+    let mut runs = 0;
+    'shrink: loop {
+        for (i, (_, ty)) in parameters.iter().enumerate() {
+            for candidate in shrink_candidates(ty, &failing[i]) {
+                if runs == MAX_SHRINK_RUNS {
+                    break 'shrink;
+                }
+                runs += 1;
+                let mut arguments = failing.clone();
+                arguments[i] = candidate;
+                if fails(&arguments) {
+                    failing = arguments;
+                    continue 'shrink;
+                }
+            }
+        }
+        break;
+    }
+    failing
+}
+
+/// Renders the arguments of a random test as `name = value` pairs
+pub fn format_arguments(
+    parameters: &[(String, RandomTestType)],
+    arguments: &[MoveValue],
+) -> String {
+    parameters
+        .iter()
+        .zip(arguments)
+        .map(|((name, ty), value)| format!("{} = {}", name, format_value(ty, value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_value(ty: &RandomTestType, value: &MoveValue) -> String {
+    match (ty, value) {
+        (RandomTestType::String, _) => {
+            format!("{:?}", String::from_utf8_lossy(&string_bytes(value)))
+        }
+        (RandomTestType::Vector(elem_ty), MoveValue::Vector(elems)) => format!(
+            "vector[{}]",
+            elems
+                .iter()
+                .map(|elem| format_value(elem_ty, elem))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => value.to_string(),
+    }
+}
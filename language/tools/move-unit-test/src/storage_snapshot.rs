@@ -0,0 +1,118 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Snapshot assertions on the storage state at the end of passing tests.
+//!
+//! The storage state a test leaves behind, as rendered by the resource viewer, is compared to the
+//! snapshot committed for the test in `snapshots/<module>.<test>.snap`, next to the source file
+//! of the test's module. In update mode the snapshots are (re)written instead, in the same way as
+//! the `.exp` baselines of the testsuites.
+
+use crate::test_reporter::FailureReason;
+use move_command_line_common::testing::{format_diff, format_diff_no_color};
+use move_compiler::unit_test::TestPlan;
+use move_core_types::language_storage::ModuleId;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The directory holding the snapshots, next to the source files of the tests
+pub const SNAPSHOT_DIR: &str = "snapshots";
+/// Extension for snapshot files
+pub const SNAPSHOT_EXT: &str = "snap";
+
+pub struct StorageSnapshots {
+    /// Whether to write the snapshots instead of checking them
+    update: bool,
+    /// The snapshot directory of each module with tests
+    dirs: BTreeMap<ModuleId, PathBuf>,
+}
+
+impl StorageSnapshots {
+    pub fn new(test_plan: &TestPlan, update: bool) -> Self {
+        let dirs = test_plan
+            .module_tests
+            .keys()
+            .filter_map(|module_id| {
+                let info = test_plan.module_info.get(module_id)?;
+                let file_hash = info.source_map.definition_location.file_hash();
+                let (file_name, _) = test_plan.files.get(&file_hash)?;
+                let source_dir = Path::new(file_name.as_str()).parent()?;
+                Some((module_id.clone(), source_dir.join(SNAPSHOT_DIR)))
+            })
+            .collect();
+        Self { update, dirs }
+    }
+
+    /// Checks `storage_state`, the storage state at the end of the test `test_name` of
+    /// `module_id`, against its snapshot, or saves it as the snapshot in update mode
+    pub fn check(
+        &self,
+        module_id: &ModuleId,
+        test_name: &str,
+        storage_state: &str,
+    ) -> Result<(), FailureReason> {
+        let dir = match self.dirs.get(module_id) {
+            Some(dir) => dir,
+            None => {
+                return Err(FailureReason::snapshot_mismatch(
+                    "Unable to locate the snapshot of the storage state".to_string(),
+                    format!("The source file of module {} is unknown", module_id),
+                ))
+            }
+        };
+        let path = dir.join(format!(
+            "{}.{}.{}",
+            module_id.name(),
+            test_name,
+            SNAPSHOT_EXT
+        ));
+
+        if self.update {
+            return fs::create_dir_all(dir)
+                .and_then(|_| fs::write(&path, storage_state))
+                .map_err(|err| {
+                    FailureReason::snapshot_mismatch(
+                        "Unable to save the snapshot of the storage state".to_string(),
+                        format!("Failed to write {}: {}", path.display(), err),
+                    )
+                });
+        }
+
+        let expected = match fs::read_to_string(&path) {
+            Ok(expected) => expected,
+            Err(_) => {
+                return Err(FailureReason::snapshot_mismatch(
+                    "No snapshot of the storage state found".to_string(),
+                    format!(
+                        "Expected a snapshot at {}.\n\
+                        Run with `--update-snapshots` to save the current storage state as the \
+                        snapshot",
+                        path.display()
+                    ),
+                ))
+            }
+        };
+        if expected == storage_state {
+            return Ok(());
+        }
+
+        let diff = if colored::control::SHOULD_COLORIZE.should_colorize() {
+            format_diff(expected, storage_state)
+        } else {
+            format_diff_no_color(expected, storage_state)
+        };
+        Err(FailureReason::snapshot_mismatch(
+            "Storage state differs from its snapshot".to_string(),
+            format!(
+                "Differences with {}:\n{}\n\
+                Run with `--update-snapshots` to save the current storage state as the new \
+                snapshot",
+                path.display(),
+                diff.trim_end()
+            ),
+        ))
+    }
+}
@@ -2,7 +2,10 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::format_module_id;
+use crate::{
+    format_module_id,
+    machine_report::{write_junit, FailureRecord, JsonEventStream, TestRecord, TestStatus},
+};
 use codespan_reporting::files::{Files, SimpleFiles};
 use colored::{control, Colorize};
 use move_binary_format::{
@@ -14,18 +17,30 @@ use move_compiler::{
     diagnostics::{self, Diagnostic, Diagnostics},
     unit_test::{ModuleTestPlan, TestName, TestPlan},
 };
-use move_core_types::{effects::ChangeSet, language_storage::ModuleId, vm_status::StatusType};
+use move_core_types::{
+    effects::ChangeSet,
+    errmap::ErrorMapping,
+    language_storage::ModuleId,
+    vm_status::{StatusCode, StatusType},
+};
 use move_ir_types::location::Loc;
 use move_symbol_pool::Symbol;
+use move_vm_test_utils::gas_profiler::GasProfile;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
+    fs::File,
     io::{Result, Write},
+    path::Path,
     sync::Mutex,
     time::Duration,
 };
 
 pub use move_compiler::unit_test::ExpectedMoveError as MoveError;
 
+static ANSI_ESCAPES: Lazy<Regex> = Lazy::new(|| Regex::new("\x1b\\[[0-9;]*m").unwrap());
+
 #[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
 pub enum FailureReason {
     // Expected to error, but it didn't
@@ -47,6 +62,8 @@ pub enum FailureReason {
     },
     // Property checking failed
     Property(String),
+    // The storage state at the end of the test does not match its snapshot
+    SnapshotMismatch(String, String),
 
     // Failed to compile Move code into EVM bytecode.
     #[cfg(feature = "evm-backend")]
@@ -67,6 +84,7 @@ pub struct TestFailure {
     pub vm_error: Option<VMError>,
     pub failure_reason: FailureReason,
     pub storage_state: Option<String>,
+    pub random_inputs: Option<String>,
 }
 
 #[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
@@ -83,10 +101,17 @@ pub struct TestStatistics {
     output: BTreeMap<ModuleId, BTreeMap<TestName, String>>,
 }
 
+/// The JSON events of a test run, explaining the abort codes of the failed tests with `error_map`
+pub struct TestEventStream {
+    events: JsonEventStream<Box<dyn Write + Send>>,
+    error_map: ErrorMapping,
+}
+
 #[derive(Debug, Clone)]
 pub struct TestResults {
     final_statistics: TestStatistics,
     test_plan: TestPlan,
+    gas_profile: Option<GasProfile>,
 }
 
 impl TestRunInfo {
@@ -97,6 +122,18 @@ impl TestRunInfo {
             instructions_executed,
         }
     }
+
+    /// The machine-readable record of this test, of `module_id`, passing
+    fn record(&self, module_id: &ModuleId) -> TestRecord {
+        TestRecord {
+            module: format_module_id(module_id),
+            name: self.function_ident.clone(),
+            status: TestStatus::Passed,
+            duration: self.elapsed_time,
+            gas_used: self.instructions_executed,
+            failure: None,
+        }
+    }
 }
 
 impl FailureReason {
@@ -146,6 +183,10 @@ impl FailureReason {
         FailureReason::Property(details)
     }
 
+    pub fn snapshot_mismatch(message: String, details: String) -> Self {
+        FailureReason::SnapshotMismatch(message, details)
+    }
+
     #[cfg(feature = "evm-backend")]
     pub fn move_to_evm_error(diagnostics: String) -> Self {
         FailureReason::MoveToEVMError(diagnostics)
@@ -160,6 +201,49 @@ impl FailureReason {
     pub fn solana_vm_error(diagnostics: String) -> Self {
         FailureReason::SolanaVMError(diagnostics)
     }
+
+    /// A one line summary of the failure
+    pub fn summary(&self) -> &str {
+        match self {
+            FailureReason::NoError(message)
+            | FailureReason::WrongError(message, _, _)
+            | FailureReason::WrongAbortDEPRECATED(message, _, _)
+            | FailureReason::UnexpectedError(message, _)
+            | FailureReason::Timeout(message)
+            | FailureReason::SnapshotMismatch(message, _) => message.as_str(),
+            FailureReason::Mismatch { .. } => {
+                "Executions via Move VM and stackless VM yield different results"
+            }
+            FailureReason::Property(message) => message.lines().next().unwrap_or_default(),
+
+            #[cfg(feature = "evm-backend")]
+            FailureReason::MoveToEVMError(_) => "Failed to compile Move code into EVM bytecode",
+
+            #[cfg(feature = "solana-backend")]
+            FailureReason::MoveToSolanaError(_) => {
+                "Failed to compile Move code into Solana VM bytecode"
+            }
+
+            #[cfg(feature = "solana-backend")]
+            FailureReason::SolanaVMError(_) => "Failed to run a program on Solana VM",
+        }
+    }
+
+    /// The abort code of the test and the module it originates from, if the test aborted
+    fn abort(&self) -> Option<(u64, &ModuleId)> {
+        let actual = match self {
+            FailureReason::WrongError(_, _, actual)
+            | FailureReason::WrongAbortDEPRECATED(_, _, actual)
+            | FailureReason::UnexpectedError(_, actual) => actual,
+            _ => return None,
+        };
+        match actual {
+            MoveError(StatusCode::ABORTED, Some(code), Location::Module(module_id)) => {
+                Some((*code, module_id))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl TestFailure {
@@ -174,6 +258,43 @@ impl TestFailure {
             vm_error,
             failure_reason,
             storage_state,
+            random_inputs: None,
+        }
+    }
+
+    /// Records the (shrunk) generated inputs a `#[random_test]` failed with
+    pub fn with_random_inputs(mut self, random_inputs: String) -> Self {
+        self.random_inputs = Some(random_inputs);
+        self
+    }
+
+    /// The machine-readable record of this failure of a test of `module_id`, explaining its abort
+    /// code with `error_map`
+    fn record(
+        &self,
+        module_id: &ModuleId,
+        test_plan: &TestPlan,
+        error_map: &ErrorMapping,
+    ) -> TestRecord {
+        let reason = &self.failure_reason;
+        let abort = reason.abort();
+        let details = self.render_error(test_plan);
+        TestRecord {
+            module: format_module_id(module_id),
+            name: self.test_run_info.function_ident.clone(),
+            status: match reason {
+                FailureReason::Timeout(_) => TestStatus::TimedOut,
+                _ => TestStatus::Failed,
+            },
+            duration: self.test_run_info.elapsed_time,
+            gas_used: self.test_run_info.instructions_executed,
+            failure: Some(FailureRecord {
+                message: reason.summary().to_string(),
+                details: ANSI_ESCAPES.replace_all(&details, "").into_owned(),
+                abort: abort.map(|(code, module_id)| (code, format_module_id(module_id))),
+                abort_explanation: abort
+                    .and_then(|(code, module_id)| error_map.get_explanation(module_id, code)),
+            }),
         }
     }
 
@@ -243,6 +364,9 @@ impl TestFailure {
                 )
             }
             FailureReason::Property(message) => message.clone(),
+            FailureReason::SnapshotMismatch(message, details) => {
+                format!("{}\n{}", message, details)
+            }
 
             #[cfg(feature = "evm-backend")]
             FailureReason::MoveToEVMError(diagnostics) => {
@@ -266,6 +390,11 @@ impl TestFailure {
             }
         };
 
+        let error_string = match &self.random_inputs {
+            None => error_string,
+            Some(random_inputs) => format!("{}\n{}", error_string, random_inputs),
+        };
+
         match &self.storage_state {
             None => error_string,
             Some(storage_state) => {
@@ -468,14 +597,99 @@ impl TestStatistics {
     }
 }
 
+impl TestEventStream {
+    /// Starts writing the events of a run of the tests of `test_plan` to `writer`
+    pub fn start(
+        writer: Box<dyn Write + Send>,
+        test_plan: &TestPlan,
+        error_map: ErrorMapping,
+    ) -> Result<Self> {
+        let test_count = test_plan
+            .module_tests
+            .values()
+            .map(|module_tests| module_tests.tests.len())
+            .sum();
+        Ok(Self {
+            events: JsonEventStream::start(writer, test_count)?,
+            error_map,
+        })
+    }
+
+    /// Writes the event of the test of `test_run_info`, of `module_id`, passing
+    pub fn test_passed(&self, module_id: &ModuleId, test_run_info: &TestRunInfo) {
+        self.events.test_completed(&test_run_info.record(module_id))
+    }
+
+    /// Writes the event of a test of `module_id` failing with `test_failure`
+    pub fn test_failed(
+        &self,
+        module_id: &ModuleId,
+        test_failure: &TestFailure,
+        test_plan: &TestPlan,
+    ) {
+        self.events
+            .test_completed(&test_failure.record(module_id, test_plan, &self.error_map))
+    }
+
+    /// Writes the event of the run finishing, or returns the first error writing an event
+    pub fn finish(self) -> Result<()> {
+        self.events.finish()
+    }
+}
+
 impl TestResults {
-    pub fn new(final_statistics: TestStatistics, test_plan: TestPlan) -> Self {
+    pub fn new(
+        final_statistics: TestStatistics,
+        test_plan: TestPlan,
+        gas_profile: Option<GasProfile>,
+    ) -> Self {
         Self {
             final_statistics,
             test_plan,
+            gas_profile,
         }
     }
 
+    /// Reports the gas used by the tests by kind of instruction, and saves it as folded stacks in
+    /// `profile_dir`, if the gas was profiled
+    pub fn report_gas_profile<W: Write>(
+        &self,
+        writer: &Mutex<W>,
+        profile_dir: &Path,
+    ) -> Result<()> {
+        let gas_profile = match &self.gas_profile {
+            Some(gas_profile) => gas_profile,
+            None => return Ok(()),
+        };
+        let mut writer = writer.lock().unwrap();
+        writeln!(writer, "\nGas Profile:\n")?;
+        gas_profile.write_instruction_breakdown(&mut *writer)?;
+        gas_profile.save(profile_dir)?;
+        writeln!(
+            writer,
+            "\nFolded stacks of the gas used written to {}",
+            profile_dir.display()
+        )
+    }
+
+    /// Writes a JUnit XML report of the results to `path`, explaining the abort codes of the
+    /// failed tests with `error_map`
+    pub fn write_junit_report(&self, error_map: &ErrorMapping, path: &Path) -> Result<()> {
+        let mut records = vec![];
+        for (module_id, test_run_infos) in &self.final_statistics.passed {
+            records.extend(test_run_infos.iter().map(|info| info.record(module_id)));
+        }
+        for (module_id, test_failures) in &self.final_statistics.failed {
+            records.extend(
+                test_failures
+                    .iter()
+                    .map(|failure| failure.record(module_id, &self.test_plan, error_map)),
+            );
+        }
+        records.sort_by(|r1, r2| (&r1.module, &r1.name).cmp(&(&r2.module, &r2.name)));
+        write_junit(&records, &mut File::create(path)?)
+    }
+
     pub fn report_goldens<W: Write>(&self, writer: &Mutex<W>) -> Result<()> {
         for (module_name, test_outputs) in self.final_statistics.output.iter() {
             for (test_name, write_set) in test_outputs.iter() {
@@ -581,6 +795,11 @@ impl TestResults {
         writeln!(writer.lock().unwrap())
     }
 
+    /// Returns `true` if all tests passed, without reporting anything
+    pub fn passed(&self) -> bool {
+        self.final_statistics.failed.is_empty()
+    }
+
     /// Returns `true` if all tests passed, `false` if there was a test failure/timeout
     pub fn summarize<W: Write>(self, writer: &Mutex<W>) -> Result<bool> {
         let num_failed_tests = self
@@ -2,10 +2,14 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "debugging")]
+use crate::debug_adapter::DebugAdapter;
 use crate::{
-    extensions, format_module_id,
+    extensions, format_module_id, random_test,
+    storage_snapshot::StorageSnapshots,
     test_reporter::{
-        FailureReason, MoveError, TestFailure, TestResults, TestRunInfo, TestStatistics,
+        FailureReason, MoveError, TestEventStream, TestFailure, TestResults, TestRunInfo,
+        TestStatistics,
     },
 };
 use anyhow::Result;
@@ -24,8 +28,9 @@ use move_compiler::{
 use move_core_types::{
     account_address::AccountAddress,
     effects::{ChangeSet, Op},
+    errmap::ErrorMapping,
     identifier::IdentStr,
-    value::serialize_values,
+    value::{serialize_values, MoveValue},
     vm_status::StatusCode,
 };
 use move_model::{
@@ -40,12 +45,25 @@ use move_stackless_bytecode_interpreter::{
 };
 use move_vm_runtime::{move_vm::MoveVM, native_functions::NativeFunctionTable};
 use move_vm_test_utils::{
+    gas_profiler::{GasProfile, GasProfiler},
     gas_schedule::{zero_cost_schedule, CostTable, Gas, GasCost, GasStatus},
     InMemoryStorage,
 };
+use rand::{rngs::StdRng, SeedableRng};
 use rayon::prelude::*;
 use std::{collections::BTreeMap, io::Write, marker::Send, sync::Mutex, time::Instant};
 
+#[cfg(feature = "debugging")]
+use {
+    move_vm_runtime::debugger,
+    move_vm_trace::TraceRecorder,
+    std::{
+        io::BufRead,
+        path::{Path, PathBuf},
+        sync::Arc,
+    },
+};
+
 use move_vm_runtime::native_extensions::NativeContextExtensions;
 #[cfg(any(feature = "evm-backend", feature = "solana-backend"))]
 use std::time::Duration;
@@ -75,6 +93,18 @@ pub struct SharedTestingConfig {
     check_stackless_vm: bool,
     verbose: bool,
     record_writeset: bool,
+    #[cfg(feature = "debugging")]
+    debug_adapter: Option<Arc<DebugAdapter>>,
+    #[cfg(feature = "debugging")]
+    trace_dir: Option<PathBuf>,
+    /// The first error recording the trace of a test, if any
+    #[cfg(feature = "debugging")]
+    trace_error: Mutex<Option<anyhow::Error>>,
+    gas_profile: Option<Mutex<GasProfile>>,
+    random_test_iterations: u64,
+    random_test_seed: u64,
+    storage_snapshots: Option<StorageSnapshots>,
+    json_events: Option<TestEventStream>,
 
     #[cfg(feature = "evm-backend")]
     evm: bool,
@@ -89,6 +119,9 @@ pub struct TestRunner {
     tests: TestPlan,
 }
 
+/// The number of sets of generated arguments each `#[random_test]` is run with by default
+pub const DEFAULT_RANDOM_TEST_ITERATIONS: u64 = 100;
+
 /// A gas schedule where every instruction has a cost of "1". This is used to bound execution of a
 /// test to a certain number of ticks.
 fn unit_cost_table() -> CostTable {
@@ -195,6 +228,17 @@ impl TestRunner {
                 verbose,
                 named_address_values,
                 record_writeset,
+                #[cfg(feature = "debugging")]
+                debug_adapter: None,
+                #[cfg(feature = "debugging")]
+                trace_dir: None,
+                #[cfg(feature = "debugging")]
+                trace_error: Mutex::new(None),
+                gas_profile: None,
+                random_test_iterations: DEFAULT_RANDOM_TEST_ITERATIONS,
+                random_test_seed: 0,
+                storage_snapshots: None,
+                json_events: None,
                 #[cfg(feature = "evm-backend")]
                 evm,
                 #[cfg(feature = "solana-backend")]
@@ -205,7 +249,7 @@ impl TestRunner {
         })
     }
 
-    pub fn run<W: Write + Send>(self, writer: &Mutex<W>) -> Result<TestResults> {
+    pub fn run<W: Write + Send>(mut self, writer: &Mutex<W>) -> Result<TestResults> {
         rayon::ThreadPoolBuilder::new()
             .num_threads(self.num_threads)
             .build()
@@ -215,13 +259,98 @@ impl TestRunner {
                     .tests
                     .module_tests
                     .par_iter()
-                    .map(|(_, test_plan)| self.testing_config.exec_module_tests(test_plan, writer))
+                    .map(|(_, test_plan)| {
+                        self.testing_config
+                            .exec_module_tests(test_plan, &self.tests, writer)
+                    })
                     .reduce(TestStatistics::new, |acc, stats| acc.combine(stats));
 
-                Ok(TestResults::new(final_statistics, self.tests))
+                if let Some(json_events) = self.testing_config.json_events.take() {
+                    json_events.finish()?;
+                }
+
+                #[cfg(feature = "debugging")]
+                if let Some(err) = self.testing_config.trace_error.get_mut().unwrap().take() {
+                    return Err(err);
+                }
+                let gas_profile = self.testing_config.take_gas_profile();
+                Ok(TestResults::new(final_statistics, self.tests, gas_profile))
             })
     }
 
+    /// Runs the tests one at a time under a debugger, controlled by a client of the Debug Adapter
+    /// Protocol over `input` and `output`. The output of the tests is sent to the client.
+    #[cfg(feature = "debugging")]
+    pub fn run_with_debug_adapter(
+        mut self,
+        input: impl BufRead + Send + 'static,
+        output: impl Write + Send + 'static,
+    ) -> Result<TestResults> {
+        let adapter = Arc::new(DebugAdapter::new(
+            &self.tests,
+            self.testing_config.starting_storage_state.clone(),
+            output,
+        ));
+        let server = adapter.clone().serve(input);
+        let mut final_statistics = TestStatistics::new();
+        if adapter.wait_until_configured() {
+            self.testing_config.debug_adapter = Some(adapter.clone());
+            let writer = Mutex::new(adapter.output_writer());
+            for test_plan in self.tests.module_tests.values() {
+                let stats = self
+                    .testing_config
+                    .exec_module_tests(test_plan, &self.tests, &writer);
+                final_statistics = final_statistics.combine(stats);
+            }
+            adapter.terminate();
+        }
+        if let Some(json_events) = self.testing_config.json_events.take() {
+            json_events.finish()?;
+        }
+        server
+            .join()
+            .map_err(|_| anyhow::anyhow!("debug adapter failed"))?;
+        let gas_profile = self.testing_config.take_gas_profile();
+        Ok(TestResults::new(final_statistics, self.tests, gas_profile))
+    }
+
+    /// Records a trace of the execution of each test in `trace_dir`, which must exist
+    #[cfg(feature = "debugging")]
+    pub fn record_traces(&mut self, trace_dir: PathBuf) {
+        self.testing_config.trace_dir = Some(trace_dir);
+    }
+
+    /// Writes a JSON event to `writer` as each test completes, explaining the abort codes of the
+    /// failed tests with `error_map`
+    pub fn stream_json_events(
+        &mut self,
+        writer: Box<dyn Write + Send>,
+        error_map: ErrorMapping,
+    ) -> std::io::Result<()> {
+        self.testing_config.json_events =
+            Some(TestEventStream::start(writer, &self.tests, error_map)?);
+        Ok(())
+    }
+
+    /// Profiles the gas used by the tests, attributing it to the functions and instructions
+    /// charged
+    pub fn profile_gas(&mut self) {
+        self.testing_config.gas_profile = Some(Mutex::new(GasProfile::default()));
+    }
+
+    /// Runs each `#[random_test]` with up to `iterations` sets of arguments generated from `seed`,
+    /// stopping at the first set the test fails with
+    pub fn random_tests(&mut self, iterations: u64, seed: u64) {
+        self.testing_config.random_test_iterations = iterations;
+        self.testing_config.random_test_seed = seed;
+    }
+
+    /// Checks the storage state at the end of each passing test against its snapshot, or saves
+    /// it as the snapshot if `update` is set
+    pub fn check_storage_snapshots(&mut self, update: bool) {
+        self.testing_config.storage_snapshots = Some(StorageSnapshots::new(&self.tests, update));
+    }
+
     pub fn filter(&mut self, test_name_slice: &str) {
         for (module_id, module_test) in self.tests.module_tests.iter_mut() {
             if module_id.name().as_str().contains(test_name_slice) {
@@ -244,45 +373,73 @@ impl TestRunner {
 // TODO: do not expose this to backend implementations
 struct TestOutput<'a, 'b, W> {
     test_plan: &'a ModuleTestPlan,
+    /// The plan of all the tests, to render the failures of the JSON events with
+    tests: &'a TestPlan,
     writer: &'b Mutex<W>,
+    json_events: Option<&'a TestEventStream>,
 }
 
 impl<'a, 'b, W: Write> TestOutput<'a, 'b, W> {
-    fn pass(&self, fn_name: &str) {
+    /// Reports the test of `test_run_info` passing, and records it in `stats`
+    fn pass(&self, stats: &mut TestStatistics, test_run_info: TestRunInfo) {
         writeln!(
             self.writer.lock().unwrap(),
             "[ {}    ] {}::{}",
             "PASS".bold().bright_green(),
             format_module_id(&self.test_plan.module_id),
-            fn_name
+            test_run_info.function_ident
         )
-        .unwrap()
-    }
-
-    fn fail(&self, fn_name: &str) {
-        writeln!(
-            self.writer.lock().unwrap(),
-            "[ {}    ] {}::{}",
-            "FAIL".bold().bright_red(),
-            format_module_id(&self.test_plan.module_id),
-            fn_name,
-        )
-        .unwrap()
+        .unwrap();
+        if let Some(json_events) = self.json_events {
+            json_events.test_passed(&self.test_plan.module_id, &test_run_info);
+        }
+        stats.test_success(test_run_info, self.test_plan);
     }
 
-    fn timeout(&self, fn_name: &str) {
+    /// Reports a test failing, or timing out, with `test_failure`, and records it in `stats`
+    fn fail(&self, stats: &mut TestStatistics, test_failure: TestFailure) {
+        let status = match test_failure.failure_reason {
+            FailureReason::Timeout(_) => format!("[ {} ]", "TIMEOUT".bold().bright_yellow()),
+            _ => format!("[ {}    ]", "FAIL".bold().bright_red()),
+        };
         writeln!(
             self.writer.lock().unwrap(),
-            "[ {} ] {}::{}",
-            "TIMEOUT".bold().bright_yellow(),
+            "{} {}::{}",
+            status,
             format_module_id(&self.test_plan.module_id),
-            fn_name,
+            test_failure.test_run_info.function_ident,
         )
         .unwrap();
+        if let Some(json_events) = self.json_events {
+            json_events.test_failed(&self.test_plan.module_id, &test_failure, self.tests);
+        }
+        stats.test_failure(test_failure, self.test_plan);
     }
 }
 
 impl SharedTestingConfig {
+    fn take_gas_profile(&mut self) -> Option<GasProfile> {
+        self.gas_profile
+            .take()
+            .map(|gas_profile| gas_profile.into_inner().unwrap())
+    }
+
+    /// Records the trace of `execute` at `path`. Errors creating or writing the trace are kept to
+    /// be returned once all the tests have run, without failing the test.
+    #[cfg(feature = "debugging")]
+    fn record_trace<T>(&self, path: &Path, execute: impl FnOnce() -> T) -> T {
+        let (result, finished) = match TraceRecorder::create(path) {
+            Ok(recorder) => recorder.record(execute),
+            Err(err) => (execute(), Err(err)),
+        };
+        if let Err(err) = finished {
+            self.trace_error.lock().unwrap().get_or_insert_with(|| {
+                anyhow::anyhow!("Unable to record trace {}: {}", path.display(), err)
+            });
+        }
+        result
+    }
+
     fn execute_via_move_vm(
         &self,
         test_plan: &ModuleTestPlan,
@@ -300,15 +457,59 @@ impl SharedTestingConfig {
             move_vm.new_session_with_extensions(&self.starting_storage_state, extensions);
         let mut gas_meter = GasStatus::new(&self.cost_table, Gas::new(self.execution_bound));
         // TODO: collect VM logs if the verbose flag (i.e, `self.verbose`) is set
-
         let now = Instant::now();
-        let serialized_return_values_result = session.execute_function_bypass_visibility(
-            &test_plan.module_id,
-            IdentStr::new(function_name).unwrap(),
-            vec![], // no ty args, at least for now
-            serialize_values(test_info.arguments.iter()),
-            &mut gas_meter,
-        );
+        let function_ident = IdentStr::new(function_name).unwrap();
+        let args = serialize_values(test_info.arguments.iter());
+        let execute = || match &self.gas_profile {
+            Some(gas_profile) => {
+                let entry = format!(
+                    "{}::{}",
+                    format_module_id(&test_plan.module_id),
+                    function_name
+                );
+                let mut profiler = GasProfiler::new(gas_meter, entry);
+                let result = session.execute_function_bypass_visibility(
+                    &test_plan.module_id,
+                    function_ident,
+                    vec![], // no ty args, at least for now
+                    args,
+                    &mut profiler,
+                );
+                let (gas_meter, profile) = profiler.finish();
+                gas_profile.lock().unwrap().merge(profile);
+                (result, gas_meter)
+            }
+            None => {
+                let result = session.execute_function_bypass_visibility(
+                    &test_plan.module_id,
+                    function_ident,
+                    vec![], // no ty args, at least for now
+                    args,
+                    &mut gas_meter,
+                );
+                (result, gas_meter)
+            }
+        };
+        #[cfg(feature = "debugging")]
+        let (serialized_return_values_result, gas_meter) =
+            if let Some(adapter) = &self.debug_adapter {
+                debugger::set_debugger(Some(adapter.debugger()));
+                let result = execute();
+                debugger::set_debugger(None);
+                result
+            } else if let Some(trace_dir) = &self.trace_dir {
+                let path = trace_dir.join(format!(
+                    "{}.{}.{}.json",
+                    test_plan.module_id.address().short_str_lossless(),
+                    test_plan.module_id.name(),
+                    function_name
+                ));
+                self.record_trace(&path, execute)
+            } else {
+                execute()
+            };
+        #[cfg(not(feature = "debugging"))]
+        let (serialized_return_values_result, gas_meter) = execute();
         let mut return_result = serialized_return_values_result.map(|res| {
             res.return_values
                 .into_iter()
@@ -336,6 +537,54 @@ impl SharedTestingConfig {
         }
     }
 
+    /// Searches for arguments which make the `#[random_test]` `test_info` fail. Returns the
+    /// arguments to run the test with, i.e. the shrunk failing arguments or the last arguments
+    /// generated if the test never failed, and a description of the failing arguments if any.
+    fn random_test_arguments(
+        &self,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+    ) -> (Vec<MoveValue>, Option<String>) {
+        let move_vm = MoveVM::new(self.native_function_table.clone()).unwrap();
+        let parameters = &test_info.random_parameters;
+        let fails = |arguments: &[MoveValue]| {
+            let mut session = move_vm.new_session_with_extensions(
+                &self.starting_storage_state,
+                extensions::new_extensions(),
+            );
+            let mut gas_meter = GasStatus::new(&self.cost_table, Gas::new(self.execution_bound));
+            let result = session.execute_function_bypass_visibility(
+                &test_plan.module_id,
+                IdentStr::new(function_name).unwrap(),
+                vec![], // no ty args, at least for now
+                serialize_values(arguments.iter()),
+                &mut gas_meter,
+            );
+            !test_passes(test_info.expected_failure.as_ref(), &result)
+        };
+
+        let mut rng = StdRng::seed_from_u64(self.random_test_seed);
+        let mut arguments = vec![];
+        for _ in 0..self.random_test_iterations.max(1) {
+            arguments = parameters
+                .iter()
+                .map(|(_, ty)| random_test::generate(&mut rng, ty))
+                .collect();
+            if fails(&arguments) {
+                let shrunk = random_test::shrink(parameters, arguments, &fails);
+                let description = format!(
+                    "Failed with the inputs {}, after shrinking. Rerun with `--seed {}` to \
+                     reproduce.",
+                    random_test::format_arguments(parameters, &shrunk),
+                    self.random_test_seed
+                );
+                return (shrunk, Some(description));
+            }
+        }
+        (arguments, None)
+    }
+
     fn execute_via_stackless_vm(
         &self,
         env: &GlobalEnv,
@@ -385,6 +634,28 @@ impl SharedTestingConfig {
         )
     }
 
+    /// Checks the storage state at the end of the passing test `function_name` against its
+    /// snapshot
+    fn check_storage_snapshot(
+        &self,
+        snapshots: &StorageSnapshots,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        cs_result: VMResult<ChangeSet>,
+        ext_result: VMResult<NativeContextExtensions>,
+    ) -> std::result::Result<(), FailureReason> {
+        let render = || -> Result<String> {
+            print_resources_and_extensions(&cs_result?, ext_result?, &self.starting_storage_state)
+        };
+        let storage_state = render().map_err(|err| {
+            FailureReason::snapshot_mismatch(
+                "Unable to render the storage state".to_string(),
+                err.to_string(),
+            )
+        })?;
+        snapshots.check(&test_plan.module_id, function_name, &storage_state)
+    }
+
     fn exec_module_tests_move_vm_and_stackless_vm(
         &self,
         test_plan: &ModuleTestPlan,
@@ -427,6 +698,26 @@ impl SharedTestingConfig {
         let mut stats = TestStatistics::new();
 
         for (function_name, test_info) in &test_plan.tests {
+            // The arguments of a random test are only known once they have been searched for
+            let mut random_inputs = None;
+            let random_test_info;
+            let test_info = if test_info.is_random_test() {
+                let (arguments, inputs) =
+                    self.random_test_arguments(test_plan, function_name, test_info);
+                random_inputs = inputs;
+                random_test_info = TestCase {
+                    arguments,
+                    ..test_info.clone()
+                };
+                &random_test_info
+            } else {
+                test_info
+            };
+            let annotate = |failure: TestFailure| match &random_inputs {
+                Some(inputs) => failure.with_random_inputs(inputs.clone()),
+                None => failure,
+            };
+
             let (cs_result, ext_result, exec_result, test_run_info) =
                 self.execute_via_move_vm(test_plan, function_name, test_info);
 
@@ -452,9 +743,9 @@ impl SharedTestingConfig {
                 if stackless_vm_result != move_vm_result
                     || stackless_vm_change_set != move_vm_change_set
                 {
-                    output.fail(function_name);
-                    stats.test_failure(
-                        TestFailure::new(
+                    output.fail(
+                        &mut stats,
+                        annotate(TestFailure::new(
                             FailureReason::mismatch(
                                 move_vm_result,
                                 move_vm_change_set,
@@ -464,26 +755,51 @@ impl SharedTestingConfig {
                             test_run_info,
                             None,
                             None,
-                        ),
-                        test_plan,
+                        )),
                     );
                     continue;
                 }
                 if let Some(prop_failure) = prop_check_result {
-                    output.fail(function_name);
-                    stats.test_failure(
-                        TestFailure::new(
+                    output.fail(
+                        &mut stats,
+                        annotate(TestFailure::new(
                             FailureReason::property(prop_failure),
                             test_run_info,
                             None,
                             None,
-                        ),
-                        test_plan,
+                        )),
                     );
                     continue;
                 }
             }
 
+            // Random tests are not snapshotted: their storage state varies with their arguments
+            if let Some(snapshots) = &self.storage_snapshots {
+                if exec_result.is_ok()
+                    && test_info.expected_failure.is_none()
+                    && !test_info.is_random_test()
+                {
+                    match self.check_storage_snapshot(
+                        snapshots,
+                        test_plan,
+                        function_name,
+                        cs_result,
+                        ext_result,
+                    ) {
+                        Ok(()) => {
+                            output.pass(&mut stats, test_run_info);
+                        }
+                        Err(reason) => {
+                            output.fail(
+                                &mut stats,
+                                annotate(TestFailure::new(reason, test_run_info, None, None)),
+                            );
+                        }
+                    }
+                    continue;
+                }
+            }
+
             let save_session_state = || {
                 if self.save_storage_state_on_failure {
                     cs_result.ok().and_then(|changeset| {
@@ -507,40 +823,36 @@ impl SharedTestingConfig {
                     assert!(err.major_status() != StatusCode::EXECUTED);
                     match test_info.expected_failure.as_ref() {
                         Some(ExpectedFailure::Expected) => {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
+                            output.pass(&mut stats, test_run_info);
                         }
                         Some(ExpectedFailure::ExpectedWithError(expected_err))
                             if expected_err == &actual_err =>
                         {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
+                            output.pass(&mut stats, test_run_info);
                         }
                         Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
                             if actual_err.0 == StatusCode::ABORTED
                                 && actual_err.1.is_some()
                                 && actual_err.1.unwrap() == *code =>
                         {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
+                            output.pass(&mut stats, test_run_info);
                         }
                         // incorrect cases
                         Some(ExpectedFailure::ExpectedWithError(expected_err)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
+                            output.fail(
+                                &mut stats,
+                                annotate(TestFailure::new(
                                     FailureReason::wrong_error(expected_err.clone(), actual_err),
                                     test_run_info,
                                     Some(err),
                                     save_session_state(),
-                                ),
-                                test_plan,
-                            )
+                                )),
+                            );
                         }
                         Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
+                            output.fail(
+                                &mut stats,
+                                annotate(TestFailure::new(
                                     FailureReason::wrong_abort_deprecated(
                                         *expected_code,
                                         actual_err,
@@ -548,54 +860,49 @@ impl SharedTestingConfig {
                                     test_run_info,
                                     Some(err),
                                     save_session_state(),
-                                ),
-                                test_plan,
-                            )
+                                )),
+                            );
                         }
                         None if err.major_status() == StatusCode::OUT_OF_GAS => {
                             // Ran out of ticks, report a test timeout and log a test failure
-                            output.timeout(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
+                            output.fail(
+                                &mut stats,
+                                annotate(TestFailure::new(
                                     FailureReason::timeout(),
                                     test_run_info,
                                     Some(err),
                                     save_session_state(),
-                                ),
-                                test_plan,
-                            )
+                                )),
+                            );
                         }
                         None => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
+                            output.fail(
+                                &mut stats,
+                                annotate(TestFailure::new(
                                     FailureReason::unexpected_error(actual_err),
                                     test_run_info,
                                     Some(err),
                                     save_session_state(),
-                                ),
-                                test_plan,
-                            )
+                                )),
+                            );
                         }
                     }
                 }
                 Ok(_) => {
                     // Expected the test to fail, but it executed
                     if test_info.expected_failure.is_some() {
-                        output.fail(function_name);
-                        stats.test_failure(
-                            TestFailure::new(
+                        output.fail(
+                            &mut stats,
+                            annotate(TestFailure::new(
                                 FailureReason::no_error(),
                                 test_run_info,
                                 None,
                                 save_session_state(),
-                            ),
-                            test_plan,
-                        )
+                            )),
+                        );
                     } else {
                         // Expected the test to execute fully and it did
-                        output.pass(function_name);
-                        stats.test_success(test_run_info, test_plan);
+                        output.pass(&mut stats, test_run_info);
                     }
                 }
             }
@@ -681,15 +988,14 @@ impl SharedTestingConfig {
                 Err(diagnostics) => {
                     // Failed to generate yul code due to some user errors.
                     // Mark test as failed.
-                    output.fail(function_name);
-                    stats.test_failure(
+                    output.fail(
+                        &mut stats,
                         TestFailure::new(
                             FailureReason::move_to_evm_error(diagnostics),
                             TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0),
                             None,
                             None,
                         ),
-                        test_plan,
                     );
                     return stats;
                 }
@@ -720,8 +1026,8 @@ impl SharedTestingConfig {
                     ),
                     ExitReason::Revert(_),
                 ) if abort_code() == u64::MAX => {
-                    output.fail(function_name);
-                    stats.test_failure(
+                    output.fail(
+                        &mut stats,
                         TestFailure::new(
                             FailureReason::unexpected_error(MoveError(
                                 StatusCode::UNKNOWN_STATUS,
@@ -732,14 +1038,13 @@ impl SharedTestingConfig {
                             None,
                             None,
                         ),
-                        test_plan,
                     );
                 }
 
                 // Test expected to succeed, but aborted.
                 (None, ExitReason::Revert(_)) => {
-                    output.fail(function_name);
-                    stats.test_failure(
+                    output.fail(
+                        &mut stats,
                         TestFailure::new(
                             FailureReason::unexpected_error(MoveError(
                                 StatusCode::ABORTED,
@@ -750,8 +1055,7 @@ impl SharedTestingConfig {
                             None,
                             None,
                         ),
-                        test_plan,
-                    )
+                    );
                 }
 
                 // Expect the test to abort with a specific code.
@@ -764,11 +1068,10 @@ impl SharedTestingConfig {
                 ) => {
                     let abort_code = abort_code();
                     if abort_code == *exp_abort_code {
-                        output.pass(function_name);
-                        stats.test_success(test_run_info(), test_plan);
+                        output.pass(&mut stats, test_run_info());
                     } else {
-                        output.fail(function_name);
-                        stats.test_failure(
+                        output.fail(
+                            &mut stats,
                             TestFailure::new(
                                 FailureReason::wrong_abort_deprecated(
                                     *exp_abort_code,
@@ -782,7 +1085,6 @@ impl SharedTestingConfig {
                                 None,
                                 None,
                             ),
-                            test_plan,
                         );
                     }
                 }
@@ -796,18 +1098,16 @@ impl SharedTestingConfig {
                     ),
                     ExitReason::Succeed(_),
                 ) => {
-                    output.fail(function_name);
-                    stats.test_failure(
+                    output.fail(
+                        &mut stats,
                         TestFailure::new(FailureReason::no_error(), test_run_info(), None, None),
-                        test_plan,
-                    )
+                    );
                 }
 
                 // Test succeeded or failed as expected.
                 (None, ExitReason::Succeed(_))
                 | (Some(ExpectedFailure::Expected), ExitReason::Revert(_)) => {
-                    output.pass(function_name);
-                    stats.test_success(test_run_info(), test_plan);
+                    output.pass(&mut stats, test_run_info());
                 }
 
                 (exp, reason) => {
@@ -867,15 +1167,14 @@ impl SharedTestingConfig {
                 Err(diagnostics) => {
                     // Failed to generate Solana bytecode due to some user errors.
                     // Mark test as failed.
-                    output.fail(function_name);
-                    stats.test_failure(
+                    output.fail(
+                        &mut stats,
                         TestFailure::new(
                             FailureReason::move_to_solana_error(diagnostics),
                             TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0),
                             None,
                             None,
                         ),
-                        test_plan,
                     );
                     return stats;
                 }
@@ -900,8 +1199,8 @@ impl SharedTestingConfig {
                     ),
                     move_to_solana::runner::ExitReason::Abort,
                 ) if result.return_value == u64::MAX => {
-                    output.fail(function_name);
-                    stats.test_failure(
+                    output.fail(
+                        &mut stats,
                         TestFailure::new(
                             FailureReason::unexpected_error(MoveError(
                                 StatusCode::UNKNOWN_STATUS,
@@ -912,14 +1211,13 @@ impl SharedTestingConfig {
                             None,
                             None,
                         ),
-                        test_plan,
                     );
                 }
 
                 // Test expected to succeed, but aborted.
                 (None, move_to_solana::runner::ExitReason::Abort) => {
-                    output.fail(function_name);
-                    stats.test_failure(
+                    output.fail(
+                        &mut stats,
                         TestFailure::new(
                             FailureReason::unexpected_error(MoveError(
                                 StatusCode::ABORTED,
@@ -930,8 +1228,7 @@ impl SharedTestingConfig {
                             None,
                             None,
                         ),
-                        test_plan,
-                    )
+                    );
                 }
 
                 // Expect the test to abort with a specific code.
@@ -943,11 +1240,10 @@ impl SharedTestingConfig {
                     move_to_solana::runner::ExitReason::Abort,
                 ) => {
                     if result.return_value == *exp_abort_code {
-                        output.pass(function_name);
-                        stats.test_success(test_run_info(), test_plan);
+                        output.pass(&mut stats, test_run_info());
                     } else {
-                        output.fail(function_name);
-                        stats.test_failure(
+                        output.fail(
+                            &mut stats,
                             TestFailure::new(
                                 FailureReason::wrong_abort_deprecated(
                                     *exp_abort_code,
@@ -961,7 +1257,6 @@ impl SharedTestingConfig {
                                 None,
                                 None,
                             ),
-                            test_plan,
                         );
                     }
                 }
@@ -975,31 +1270,28 @@ impl SharedTestingConfig {
                     ),
                     move_to_solana::runner::ExitReason::Success,
                 ) => {
-                    output.fail(function_name);
-                    stats.test_failure(
+                    output.fail(
+                        &mut stats,
                         TestFailure::new(FailureReason::no_error(), test_run_info(), None, None),
-                        test_plan,
-                    )
+                    );
                 }
 
                 // Test succeeded or failed as expected.
                 (None, move_to_solana::runner::ExitReason::Success)
                 | (Some(ExpectedFailure::Expected), move_to_solana::runner::ExitReason::Abort) => {
-                    output.pass(function_name);
-                    stats.test_success(test_run_info(), test_plan);
+                    output.pass(&mut stats, test_run_info());
                 }
 
                 (_exp, _reason) => {
-                    output.fail(function_name);
-                    stats.test_failure(
+                    output.fail(
+                        &mut stats,
                         TestFailure::new(
                             FailureReason::solana_vm_error(result.log),
                             test_run_info(),
                             None,
                             None,
                         ),
-                        test_plan,
-                    )
+                    );
                 }
             }
         }
@@ -1012,9 +1304,15 @@ impl SharedTestingConfig {
     fn exec_module_tests(
         &self,
         test_plan: &ModuleTestPlan,
+        tests: &TestPlan,
         writer: &Mutex<impl Write>,
     ) -> TestStatistics {
-        let output = TestOutput { test_plan, writer };
+        let output = TestOutput {
+            test_plan,
+            tests,
+            writer,
+            json_events: self.json_events.as_ref(),
+        };
 
         #[cfg(feature = "evm-backend")]
         if self.evm {
@@ -1029,3 +1327,18 @@ impl SharedTestingConfig {
         self.exec_module_tests_move_vm_and_stackless_vm(test_plan, &output)
     }
 }
+
+/// Whether a test which finished with `result` passes, given what it is expected to fail with
+fn test_passes<T>(expected_failure: Option<&ExpectedFailure>, result: &VMResult<T>) -> bool {
+    match (result, expected_failure) {
+        (Ok(_), None) => true,
+        (Ok(_), Some(_)) | (Err(_), None) => false,
+        (Err(_), Some(ExpectedFailure::Expected)) => true,
+        (Err(err), Some(ExpectedFailure::ExpectedWithError(expected_err))) => {
+            expected_err == &MoveError(err.major_status(), err.sub_status(), err.location().clone())
+        }
+        (Err(err), Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))) => {
+            err.major_status() == StatusCode::ABORTED && err.sub_status() == Some(*code)
+        }
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A scripted session of a client of the Debug Adapter Protocol, debugging a unit test.
+
+use move_unit_test::{test_runner::TestRunner, UnitTestingConfig};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::{BufRead, BufReader, Read, Write},
+    path::PathBuf,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+// The lines of `tests/sources/debugged.move` the session stops at
+const DOUBLE_FIRST_LINE: u64 = 3;
+const DOUBLE_ASSERT_LINE: u64 = 4;
+const FIRST_CALL_LINE: u64 = 10;
+const SECOND_CALL_LINE: u64 = 11;
+
+/// The reading end of an in-memory pipe, at its end once the writing end is dropped
+struct PipeReader {
+    receiver: Receiver<Vec<u8>>,
+    buf: VecDeque<u8>,
+}
+
+struct PipeWriter(Sender<Vec<u8>>);
+
+fn pipe() -> (PipeReader, PipeWriter) {
+    let (sender, receiver) = channel();
+    let reader = PipeReader {
+        receiver,
+        buf: VecDeque::new(),
+    };
+    (reader, PipeWriter(sender))
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.buf.is_empty() {
+            match self.receiver.recv() {
+                Ok(bytes) => self.buf.extend(bytes),
+                Err(_) => return Ok(0),
+            }
+        }
+        self.buf.read(buf)
+    }
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .send(buf.to_vec())
+            .map_err(|_| std::io::ErrorKind::BrokenPipe)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+struct Client {
+    input: PipeWriter,
+    output: BufReader<PipeReader>,
+    seq: u64,
+    /// The events received while waiting for a response
+    events: VecDeque<Value>,
+}
+
+impl Client {
+    fn send(&mut self, command: &str, arguments: Value) -> u64 {
+        self.seq += 1;
+        let content = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        })
+        .to_string();
+        write!(
+            self.input,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        )
+        .unwrap();
+        self.seq
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut content_length = None;
+        loop {
+            let mut header = String::new();
+            assert_ne!(self.output.read_line(&mut header).unwrap(), 0, "no message");
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(length) = header.strip_prefix("Content-Length:") {
+                content_length = Some(length.trim().parse::<usize>().unwrap());
+            }
+        }
+        let mut content = vec![0; content_length.unwrap()];
+        self.output.read_exact(&mut content).unwrap();
+        serde_json::from_slice(&content).unwrap()
+    }
+
+    /// Sends a request and returns the body of its response, which must be successful
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        let seq = self.send(command, arguments);
+        loop {
+            let message = self.receive();
+            if message["type"] == "event" {
+                self.events.push_back(message);
+            } else if message["request_seq"] == seq {
+                assert_eq!(message["success"], true, "{}", message);
+                return message["body"].clone();
+            }
+        }
+    }
+
+    /// Waits for the event `name` and returns its body, skipping any other event
+    fn wait_event(&mut self, name: &str) -> Value {
+        loop {
+            let message = match self.events.pop_front() {
+                Some(event) => event,
+                None => self.receive(),
+            };
+            if message["type"] == "event" && message["event"] == name {
+                return message["body"].clone();
+            }
+        }
+    }
+
+    /// Waits until execution stops, checking the reason
+    fn wait_stopped(&mut self, reason: &str) {
+        assert_eq!(self.wait_event("stopped")["reason"], reason);
+    }
+
+    /// The names and lines of the frames of the stack, innermost first
+    fn stack_trace(&mut self) -> Vec<(String, u64)> {
+        let body = self.request("stackTrace", json!({ "threadId": 1 }));
+        body["stackFrames"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|frame| {
+                let name = frame["name"].as_str().unwrap().to_string();
+                (name, frame["line"].as_u64().unwrap())
+            })
+            .collect()
+    }
+
+    /// The names and values of the locals of the innermost frame
+    fn locals(&mut self) -> Vec<(String, String)> {
+        let scopes = self.request("scopes", json!({ "frameId": 0 }));
+        let reference = scopes["scopes"][0]["variablesReference"].clone();
+        let body = self.request("variables", json!({ "variablesReference": reference }));
+        body["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|var| {
+                let name = var["name"].as_str().unwrap().to_string();
+                (name, var["value"].as_str().unwrap().to_string())
+            })
+            .collect()
+    }
+}
+
+fn source_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/sources/debugged.move")
+        .canonicalize()
+        .unwrap()
+}
+
+/// Runs the tests of `tests/sources/debugged.move` under the debug adapter, on a new thread that
+/// returns whether they passed
+fn start_session() -> (Client, JoinHandle<bool>) {
+    let (adapter_input, client_input) = pipe();
+    let (client_output, adapter_output) = pipe();
+    let handle = thread::spawn(move || {
+        let config = UnitTestingConfig {
+            source_files: vec![source_path().to_string_lossy().to_string()],
+            ..UnitTestingConfig::default_with_bound(None)
+        };
+        let test_plan = config.build_test_plan().unwrap();
+        let runner = TestRunner::new(
+            config.gas_limit.unwrap(),
+            1,
+            false,
+            false,
+            false,
+            false,
+            test_plan,
+            None,
+            None,
+            BTreeMap::new(),
+            false,
+            #[cfg(feature = "evm-backend")]
+            false,
+            #[cfg(feature = "solana-backend")]
+            false,
+        )
+        .unwrap();
+        runner
+            .run_with_debug_adapter(BufReader::new(adapter_input), adapter_output)
+            .unwrap()
+            .summarize(&Mutex::new(Vec::new()))
+            .unwrap()
+    });
+    let client = Client {
+        input: client_input,
+        output: BufReader::new(client_output),
+        seq: 0,
+        events: VecDeque::new(),
+    };
+    (client, handle)
+}
+
+#[test]
+fn breakpoint_step_over_and_step_out() {
+    let (mut client, handle) = start_session();
+    client.request("initialize", json!({ "adapterID": "move" }));
+    client.wait_event("initialized");
+    client.request("launch", json!({}));
+    let breakpoints = client.request(
+        "setBreakpoints",
+        json!({
+            "source": { "path": source_path() },
+            "breakpoints": [{ "line": DOUBLE_FIRST_LINE }],
+        }),
+    );
+    assert_eq!(breakpoints["breakpoints"][0]["verified"], true);
+    client.request("configurationDone", json!({}));
+
+    // the first call of `double` stops at the breakpoint
+    client.wait_stopped("breakpoint");
+    assert_eq!(
+        client.stack_trace(),
+        vec![
+            ("0x1::debugged::double".to_string(), DOUBLE_FIRST_LINE),
+            ("0x1::debugged::test_double".to_string(), FIRST_CALL_LINE),
+        ]
+    );
+    assert_eq!(client.locals(), vec![("x".to_string(), "1".to_string())]);
+
+    // stepping over goes to the next line of the same function
+    client.request("next", json!({ "threadId": 1 }));
+    client.wait_stopped("step");
+    assert_eq!(
+        client.stack_trace(),
+        vec![
+            ("0x1::debugged::double".to_string(), DOUBLE_ASSERT_LINE),
+            ("0x1::debugged::test_double".to_string(), FIRST_CALL_LINE),
+        ]
+    );
+    assert_eq!(
+        client.locals(),
+        vec![
+            ("x".to_string(), "1".to_string()),
+            ("y".to_string(), "2".to_string()),
+        ]
+    );
+
+    // stepping out goes to the next line of the caller
+    client.request("stepOut", json!({ "threadId": 1 }));
+    client.wait_stopped("step");
+    assert_eq!(
+        client.stack_trace(),
+        vec![("0x1::debugged::test_double".to_string(), SECOND_CALL_LINE)]
+    );
+    assert_eq!(client.locals(), vec![("a".to_string(), "2".to_string())]);
+
+    // continuing stops at the breakpoint again, in the second call of `double`
+    client.request("continue", json!({ "threadId": 1 }));
+    client.wait_stopped("breakpoint");
+    assert_eq!(
+        client.stack_trace(),
+        vec![
+            ("0x1::debugged::double".to_string(), DOUBLE_FIRST_LINE),
+            ("0x1::debugged::test_double".to_string(), SECOND_CALL_LINE),
+        ]
+    );
+    assert_eq!(client.locals(), vec![("x".to_string(), "2".to_string())]);
+
+    client.request("continue", json!({ "threadId": 1 }));
+    client.wait_event("terminated");
+    client.request("disconnect", json!({}));
+
+    assert!(handle.join().unwrap());
+}
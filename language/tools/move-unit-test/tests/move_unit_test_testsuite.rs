@@ -16,6 +16,8 @@ use std::{
 // tests flaky.
 const TEST_MODIFIER_STRS: &[&str] = &[
     "storage",
+    "mutate",
+    "snapshots",
     #[cfg(feature = "evm-backend")]
     "evm",
     #[cfg(feature = "solana-backend")]
@@ -27,6 +29,9 @@ pub fn modify(mut base_config: UnitTestingConfig, modifier_str: &str) -> Option<
     match modifier_str {
         #[cfg(not(feature = "solana-backend"))]
         "storage" => base_config.report_storage_on_error = true,
+        "mutate" => base_config.mutate = true,
+        #[cfg(not(feature = "solana-backend"))]
+        "snapshots" => base_config.check_snapshots = true,
         #[cfg(feature = "evm-backend")]
         "evm" => base_config.evm = true,
         #[cfg(feature = "solana-backend")]
@@ -97,6 +102,7 @@ fn run_test_impl(path: &Path) -> anyhow::Result<()> {
             .collect(),
         report_writeset: true,
         report_stacktrace_on_abort: true,
+        seed: Some(0),
 
         ..UnitTestingConfig::default_with_bound(None)
     };
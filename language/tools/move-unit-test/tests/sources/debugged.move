@@ -0,0 +1,14 @@
+module 0x1::debugged {
+    fun double(x: u64): u64 {
+        let y = x + x;
+        assert!(y > x, 1);
+        y
+    }
+
+    #[test]
+    fun test_double() {
+        let a = double(1);
+        let b = double(a);
+        assert!(b == 4, 0);
+    }
+}
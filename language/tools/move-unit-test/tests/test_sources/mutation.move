@@ -0,0 +1,19 @@
+module 0x1::M {
+    public fun add(x: u64, y: u64): u64 {
+        x + y
+    }
+
+    public fun is_small(x: u64): bool {
+        x < 10
+    }
+
+    #[test]
+    fun test_add() {
+        assert!(add(1, 2) == 3, 0);
+    }
+
+    #[test]
+    fun test_is_small() {
+        assert!(is_small(1), 0);
+    }
+}
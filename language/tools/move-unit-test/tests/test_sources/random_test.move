@@ -0,0 +1,25 @@
+module 0x1::R {
+    use std::string::{Self, String};
+    use std::vector;
+
+    #[random_test]
+    fun addition_commutes(a: u32, b: u32) {
+        let (a, b) = ((a as u64), (b as u64));
+        assert!(a + b == b + a, 0)
+    }
+
+    #[random_test]
+    fun fails_above_limit(x: u64) {
+        assert!(x < 100, 1)
+    }
+
+    #[random_test]
+    fun string_length(s: String) {
+        assert!(string::length(&s) < 2, 2)
+    }
+
+    #[random_test]
+    fun vector_length(v: vector<u8>) {
+        assert!(vector::length(&v) < 3, 3)
+    }
+}
@@ -0,0 +1,30 @@
+module 0x1::storage_snapshot {
+    struct Counter has key { value: u64 }
+
+    #[test(a=@0x1)]
+    fun matches_snapshot(a: signer) {
+        move_to(&a, Counter { value: 1 });
+    }
+
+    #[test(a=@0x1)]
+    fun differs_from_snapshot(a: signer) {
+        move_to(&a, Counter { value: 2 });
+    }
+
+    #[test]
+    fun no_changes_match_empty_snapshot() {}
+
+    #[test(a=@0x1)]
+    fun without_snapshot(a: signer) {
+        move_to(&a, Counter { value: 3 });
+    }
+
+    // make sure that the storage state of tests expected to fail is not checked
+
+    #[test(a=@0x1)]
+    #[expected_failure(abort_code = 0, location = 0x1::storage_snapshot)]
+    fun expected_failure_is_not_snapshotted(a: signer) {
+        move_to(&a, Counter { value: 4 });
+        abort 0
+    }
+}